@@ -16,20 +16,76 @@
 //!
 //! This module contains application-specific metrics functions that can be
 //! customized by users. Core metrics functionality is provided by `crate::core::metrics`.
+//!
+//! Record domain events through the typed helpers below rather than calling
+//! `metrics::counter!` with ad-hoc names, so every metric keeps the `app_`
+//! prefix and the same labels wherever it is recorded.
 
 // Re-export core metrics functionality
 pub use crate::core::metrics::*;
 
-// Example of defining application-specific metrics:
-//
-// pub fn record_user_login() {
-//     metrics::counter!("user_logins_total", 1);
-// }
-//
-// pub fn record_api_request_duration(endpoint: &str, duration_ms: f64) {
-//     metrics::histogram!("api_request_duration_ms", duration_ms, "endpoint" => endpoint.to_string());
-// }
-//
-// pub fn record_cache_hit_rate(rate: f64) {
-//     metrics::gauge!("cache_hit_rate", rate);
-// }
+use std::time::Duration;
+
+/// Pets created through the API
+pub const PETS_CREATED_TOTAL: &str = "app_pets_created_total";
+
+/// Pets deleted through the API
+pub const PETS_DELETED_TOTAL: &str = "app_pets_deleted_total";
+
+/// API handler latency in seconds, labelled by `route`
+pub const API_LATENCY_SECONDS: &str = "app_api_latency_seconds";
+
+/// Record a pet being created
+pub fn record_pet_created() {
+    metrics::counter!(PETS_CREATED_TOTAL).increment(1);
+}
+
+/// Record a pet being deleted
+pub fn record_pet_deleted() {
+    metrics::counter!(PETS_DELETED_TOTAL).increment(1);
+}
+
+/// Record how long a request to `route` took
+///
+/// Pass the route template (`/pets/{id}`), not the concrete path, so each
+/// route is one time series.
+pub fn record_api_latency(route: &str, duration: Duration) {
+    metrics::histogram!(API_LATENCY_SECONDS, "route" => route.to_string())
+        .record(duration.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    #[test]
+    fn test_business_metrics_are_exported() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || {
+            record_pet_created();
+            record_pet_created();
+            record_pet_deleted();
+            record_api_latency("/pets/{id}", Duration::from_millis(25));
+        });
+
+        let rendered = handle.render();
+        assert!(
+            rendered.contains("app_pets_created_total 2"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("app_pets_deleted_total 1"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("app_api_latency_seconds_count{route=\"/pets/{id}\"} 1"),
+            "{}",
+            rendered
+        );
+    }
+}
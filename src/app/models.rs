@@ -1,6 +1,8 @@
 /// Example models
+pub mod example_pet_entity;
 pub mod example_user_entity;
 
 // Make example types available but with prefixes
+pub use example_pet_entity::Pet as ExamplePet;
 pub use example_user_entity::User as ExampleUser;
 pub use example_user_entity::UserRole as ExampleUserRole;
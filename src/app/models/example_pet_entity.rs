@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::core::models::Entity;
+use crate::core::services::error::ServiceError;
+use crate::core::utils::text::nfc;
+
+/// Represents a pet in the system - example implementation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pet {
+    /// Unique identifier for the pet
+    pub id: Uuid,
+
+    /// Pet's name, stored in NFC
+    pub name: String,
+
+    /// Creation timestamp
+    pub created_at: chrono::DateTime<chrono::Utc>,
+
+    /// Version for optimistic concurrency control, incremented on every update
+    #[serde(default)]
+    pub version: u64,
+}
+
+impl Entity for Pet {
+    type Id = Uuid;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn collection_name() -> String {
+        "pets".to_string()
+    }
+
+    fn version(&self) -> Option<u64> {
+        Some(self.version)
+    }
+
+    fn set_version(&mut self, version: u64) {
+        self.version = version;
+    }
+
+    fn validate(&self) -> Result<(), ServiceError> {
+        if self.name.trim().is_empty() {
+            return Err(ServiceError::validation("name: Name must not be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Pet {
+    /// Create a new pet with a specific ID, normalizing the name to NFC
+    pub fn with_id(id: Uuid, name: String) -> Self {
+        Self {
+            id,
+            name: nfc(&name),
+            created_at: chrono::Utc::now(),
+            version: 0,
+        }
+    }
+}
@@ -4,6 +4,7 @@ use validator::{Validate, ValidationError};
 
 use crate::core::models::Entity;
 use crate::core::services::error::ServiceError;
+use crate::core::utils::text::{deserialize_nfc, grapheme_length, nfc, no_control_chars};
 
 /// Represents a user in the system - example implementation
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -23,12 +24,9 @@ pub struct User {
     #[validate(email(message = "Email must be a valid email address"))]
     pub email: String,
 
-    /// User's display name
-    #[validate(length(
-        min = 3,
-        max = 100,
-        message = "Display name must be between 3 and 100 characters"
-    ))]
+    /// User's display name, stored in NFC
+    #[serde(deserialize_with = "deserialize_nfc")]
+    #[validate(custom(function = "validate_display_name"))]
     pub display_name: String,
 
     /// Whether the user account is active
@@ -42,6 +40,26 @@ pub struct User {
 
     /// Last updated timestamp
     pub updated_at: chrono::DateTime<chrono::Utc>,
+
+    /// Version for optimistic concurrency control, incremented on every save
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// Display names are 3-100 grapheme clusters without control characters
+fn validate_display_name(display_name: &str) -> Result<(), ValidationError> {
+    let with_message = |message: &'static str| {
+        move |mut error: ValidationError| {
+            error.message = Some(message.into());
+            error
+        }
+    };
+    no_control_chars(display_name).map_err(with_message(
+        "Display name must not contain control characters",
+    ))?;
+    grapheme_length(display_name, 3, 100).map_err(with_message(
+        "Display name must be between 3 and 100 characters",
+    ))
 }
 
 /// User roles in the system
@@ -74,6 +92,14 @@ impl Entity for User {
         "users".to_string()
     }
 
+    fn version(&self) -> Option<u64> {
+        Some(self.version)
+    }
+
+    fn set_version(&mut self, version: u64) {
+        self.version = version;
+    }
+
     fn validate(&self) -> Result<(), ServiceError> {
         let validator = Validate::validate(self);
         if let Err(errors) = &validator {
@@ -102,31 +128,33 @@ impl Entity for User {
 }
 
 impl User {
-    /// Create a new user with default values
+    /// Create a new user with default values, normalizing the display name to NFC
     pub fn new(username: String, email: String, display_name: String) -> Self {
         Self {
             id: Uuid::new_v4(),
             username,
             email,
-            display_name,
+            display_name: nfc(&display_name),
             active: true,
             role: UserRole::default(),
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            version: 0,
         }
     }
 
-    /// Create a new user with specific ID (for migrations or testing)
+    /// Create a new user with specific ID, normalizing the display name to NFC
     pub fn with_id(id: Uuid, username: String, email: String, display_name: String) -> Self {
         Self {
             id,
             username,
             email,
-            display_name,
+            display_name: nfc(&display_name),
             active: true,
             role: UserRole::default(),
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            version: 0,
         }
     }
 
@@ -194,6 +222,7 @@ mod tests {
             active: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            version: 0,
         };
 
         assert!(entity::Entity::validate(&user).is_err());
@@ -210,6 +239,7 @@ mod tests {
             active: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            version: 0,
         };
 
         assert!(entity::Entity::validate(&user).is_err());
@@ -226,8 +256,46 @@ mod tests {
             active: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            version: 0,
         };
 
         assert!(entity::Entity::validate(&user).is_err());
     }
+
+    fn user_with_display_name(display_name: &str) -> User {
+        let user = User::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "Test User".to_string(),
+        );
+        let mut json = serde_json::to_value(&user).unwrap();
+        json["display_name"] = serde_json::json!(display_name);
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_user_display_name_length_counts_graphemes() {
+        // 100 graphemes of two chars each pass; one more is rejected
+        let accented = "e\u{301}".repeat(100);
+        assert!(entity::Entity::validate(&user_with_display_name(&accented)).is_ok());
+
+        let overlong = "e\u{301}".repeat(101);
+        let error = entity::Entity::validate(&user_with_display_name(&overlong)).unwrap_err();
+        assert!(error.to_string().contains("between 3 and 100"));
+    }
+
+    #[test]
+    fn test_user_display_name_rejects_control_characters() {
+        let user = user_with_display_name("Test\u{7}User");
+
+        let error = entity::Entity::validate(&user).unwrap_err();
+        assert!(error.to_string().contains("control characters"));
+    }
+
+    #[test]
+    fn test_user_display_name_is_normalized_on_input() {
+        let user = user_with_display_name("Zoe\u{301} Smith");
+
+        assert_eq!(user.display_name, "Zo\u{e9} Smith");
+    }
 }
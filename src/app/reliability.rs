@@ -19,6 +19,20 @@
 
 use std::time::Duration;
 
+use crate::core::reliability::retry::OperationRetryPolicy;
+use crate::core::services::error::ServiceError;
+
+/// Retry policy for database calls
+///
+/// Retries timeouts and unavailable backends up to three times, starting at
+/// 50ms; anything else (conflicts, validation, missing rows) fails at once.
+/// Use with [`retry`](crate::core::reliability::retry::retry).
+pub fn create_db_retry_policy() -> OperationRetryPolicy<ServiceError> {
+    OperationRetryPolicy::new(3)
+        .with_backoff(Duration::from_millis(50), Duration::from_secs(1))
+        .with_retry_if(|e| matches!(e, ServiceError::Timeout(_) | ServiceError::Unavailable(_)))
+}
+
 // Example: Define custom retry policies for specific application use cases
 // pub mod custom_retries;
 
@@ -27,3 +41,33 @@ use std::time::Duration;
 
 // Example: Define custom circuit breakers for external services
 // pub mod service_circuit_breakers;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::reliability::retry::retry;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_db_retry_policy_retries_transient_errors() {
+        let calls = AtomicU32::new(0);
+        let result = retry(&create_db_retry_policy(), || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(ServiceError::timeout("query timed out")),
+                1 => Err(ServiceError::unavailable("connection reset")),
+                _ => Ok("pet"),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "pet");
+
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&create_db_retry_policy(), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(ServiceError::conflict("duplicate key"))
+        })
+        .await;
+        assert!(matches!(result, Err(ServiceError::Conflict(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -26,6 +26,7 @@ impl UserRepository {
         let config = RepositoryConfig {
             provider: "memory".to_string(),
             collection_name: Some(User::collection_name()),
+            optimistic_locking: true,
             ..Default::default()
         };
 
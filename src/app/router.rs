@@ -19,7 +19,7 @@ pub fn create_router(_config: Config) -> Router {
     let _app_state = Arc::new(AppState::default());
 
     // Create a router using the builder pattern
-    let router = RouterBuilder::new()
+    let (router, _handle) = RouterBuilder::new()
         .with_config(app_config)
         .with_metrics_enabled(true)
         .with_cors(true)
@@ -39,6 +39,8 @@ async fn health_check() -> &'static str {
 }
 
 async fn metrics() -> String {
-    let handle = crate::core::metrics::init_metrics();
-    crate::core::metrics::metrics_endpoint_handler(&handle).await
+    match crate::core::metrics::init_metrics() {
+        Ok(handle) => crate::core::metrics::metrics_endpoint_handler(&handle).await,
+        Err(e) => format!("# {}", e),
+    }
 }
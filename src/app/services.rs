@@ -3,10 +3,16 @@
 //! This module contains service implementations
 //! that can be customized by users.
 
+/// Example pet service module
+pub mod example_pet_service;
+
 /// Example user service module
 pub mod example_user_service;
 
 // Make example types available but with prefixes
+pub use example_pet_service::CreatePetInput as ExampleCreatePetInput;
+pub use example_pet_service::PetService as ExamplePetService;
+pub use example_pet_service::UpdatePetInput as ExampleUpdatePetInput;
 pub use example_user_service::CreateUserInput as ExampleCreateUserInput;
 pub use example_user_service::UpdateUserInput as ExampleUpdateUserInput;
 pub use example_user_service::UserOutput as ExampleUserOutput;
@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::app::metrics::record_pet_created;
+use crate::app::models::example_pet_entity::Pet;
+use crate::core::models::Entity;
+use crate::core::models::id_generator::{IdGenerator, UuidV4Generator};
+use crate::core::services::Service;
+use crate::core::services::database_interface::DatabaseOperations;
+use crate::core::services::error::ServiceError;
+use crate::core::services::idempotency::{IdempotencyStore, Idempotent};
+use crate::core::utils::text::nfc;
+
+/// Input for creating a pet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePetInput {
+    /// Name of the pet
+    pub name: String,
+}
+
+/// Input for updating a pet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePetInput {
+    /// New name of the pet
+    pub name: Option<String>,
+}
+
+/// Example service creating pets exactly once per idempotency key
+///
+/// The pet and the idempotency record are written in one transaction, so a
+/// retry either replays the created pet or finds neither.
+#[derive(Clone)]
+pub struct PetService {
+    /// Database holding pets and idempotency records
+    db: Arc<dyn DatabaseOperations>,
+
+    /// Records idempotency keys of create requests
+    idempotency: IdempotencyStore,
+
+    /// Source of ids for new pets
+    id_generator: Arc<dyn IdGenerator>,
+
+    /// Serializes updates, so a version check and its write can't interleave
+    update_lock: Arc<Mutex<()>>,
+}
+
+impl std::fmt::Debug for PetService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PetService")
+            .field("idempotency", &self.idempotency)
+            .field("id_generator", &self.id_generator)
+            .finish()
+    }
+}
+
+impl Service for PetService {}
+
+impl PetService {
+    /// Create a pet service storing pets and idempotency keys in `db`
+    pub fn new(db: Arc<dyn DatabaseOperations>) -> Self {
+        Self {
+            idempotency: IdempotencyStore::new(db.clone()),
+            db,
+            id_generator: Arc::new(UuidV4Generator),
+            update_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Record idempotency keys in `store` instead of the default collection
+    pub fn with_idempotency_store(mut self, store: IdempotencyStore) -> Self {
+        self.idempotency = store;
+        self
+    }
+
+    /// Generate ids for new pets with `id_generator` instead of random UUIDs
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
+    /// Find a pet by ID
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Pet>, ServiceError> {
+        match self
+            .db
+            .get(&Pet::collection_name(), &id.to_string())
+            .await?
+        {
+            Some(json) => serde_json::from_str(&json).map(Some).map_err(|e| {
+                ServiceError::conversion_error(format!("Failed to deserialize pet: {}", e))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Create a pet at most once per idempotency key
+    ///
+    /// A retry with the same `key` and input returns the pet created by the
+    /// first request without inserting another one.
+    pub async fn create_pet(
+        &self,
+        key: &str,
+        input: CreatePetInput,
+    ) -> Result<Idempotent<Pet>, ServiceError> {
+        let pet = Pet::with_id(self.id_generator.generate(), input.name.clone());
+        pet.validate()?;
+        let json = serde_json::to_string(&pet).map_err(|e| {
+            ServiceError::conversion_error(format!("Failed to serialize pet: {}", e))
+        })?;
+
+        let created = self
+            .idempotency
+            .execute(key, &input, move |tx| {
+                Box::pin(async move {
+                    tx.insert(&Pet::collection_name(), &pet.id.to_string(), &json);
+                    Ok(pet)
+                })
+            })
+            .await?;
+        if !created.replayed {
+            record_pet_created();
+        }
+        Ok(created)
+    }
+
+    /// Update a pet, provided it is still at `expected_version`
+    ///
+    /// `expected_version` is the version the caller last read (e.g. from an
+    /// `If-Match` header). A stale version fails with a conflict, which maps
+    /// to `409 Conflict`; a successful update increments the version.
+    pub async fn update(
+        &self,
+        id: Uuid,
+        input: UpdatePetInput,
+        expected_version: u64,
+    ) -> Result<Pet, ServiceError> {
+        let _guard = self.update_lock.lock().await;
+
+        let mut pet = self
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found(format!("Pet with ID {} not found", id)))?;
+        if pet.version != expected_version {
+            return Err(ServiceError::conflict(format!(
+                "Pet {} has version {}, not {}",
+                id, pet.version, expected_version
+            )));
+        }
+
+        if let Some(name) = input.name {
+            pet.name = nfc(&name);
+        }
+        pet.validate()?;
+        pet.version += 1;
+
+        let json = serde_json::to_string(&pet).map_err(|e| {
+            ServiceError::conversion_error(format!("Failed to serialize pet: {}", e))
+        })?;
+        self.db
+            .set(&Pet::collection_name(), &id.to_string(), &json)
+            .await?;
+        Ok(pet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::services::database_interface::DatabaseConfig;
+    use crate::core::services::memory_database::InMemoryDatabase;
+
+    fn database() -> Arc<dyn DatabaseOperations> {
+        Arc::new(InMemoryDatabase::new(Arc::new(DatabaseConfig::default())))
+    }
+
+    fn rename(name: &str) -> UpdatePetInput {
+        UpdatePetInput {
+            name: Some(name.to_string()),
+        }
+    }
+
+    fn rex() -> CreatePetInput {
+        CreatePetInput {
+            name: "Rex".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_returns_created_pet_without_duplicate_row() {
+        let db = database();
+        let service = PetService::new(db.clone());
+
+        let first = service.create_pet("key-1", rex()).await.unwrap();
+        let retry = service.create_pet("key-1", rex()).await.unwrap();
+
+        assert!(!first.replayed);
+        assert!(retry.replayed);
+        assert_eq!(first.value, retry.value);
+        assert_eq!(db.query("pets", "").await.unwrap().len(), 1);
+        assert_eq!(
+            service.find_by_id(first.value.id).await.unwrap(),
+            Some(first.value)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_pet_reserves_nothing() {
+        let db = database();
+        let service = PetService::new(db.clone());
+        let unnamed = CreatePetInput {
+            name: " ".to_string(),
+        };
+
+        let result = service.create_pet("key-1", unnamed).await;
+
+        assert!(matches!(result, Err(ServiceError::Validation(_))));
+        assert!(db.query("pets", "").await.unwrap().is_empty());
+        assert!(!service.create_pet("key-1", rex()).await.unwrap().replayed);
+    }
+
+    #[tokio::test]
+    async fn test_update_with_current_version_bumps_version() {
+        let service = PetService::new(database());
+        let created = service.create_pet("key-1", rex()).await.unwrap().value;
+
+        let updated = service
+            .update(created.id, rename("Max"), created.version)
+            .await
+            .unwrap();
+
+        assert_eq!(updated.name, "Max");
+        assert_eq!(updated.version, created.version + 1);
+        assert_eq!(service.find_by_id(created.id).await.unwrap(), Some(updated));
+    }
+
+    #[tokio::test]
+    async fn test_update_with_stale_version_conflicts() {
+        let service = PetService::new(database());
+        let created = service.create_pet("key-1", rex()).await.unwrap().value;
+        service
+            .update(created.id, rename("Max"), created.version)
+            .await
+            .unwrap();
+
+        let stale = service
+            .update(created.id, rename("Buddy"), created.version)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(stale, ServiceError::Conflict(_)));
+        assert_eq!(
+            crate::core::error::AppError::from(stale).status_code(),
+            axum::http::StatusCode::CONFLICT
+        );
+        let current = service.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(current.name, "Max");
+    }
+}
@@ -8,11 +8,14 @@ use uuid::Uuid;
 
 use crate::app::models::example_user_entity::{User, UserRole};
 use crate::app::repositories::example_user_repository::UserRepository;
+use crate::core::config::app_config::AppConfig;
 use crate::core::models::entity::Repository;
+use crate::core::models::id_generator::{IdGenerator, UuidV4Generator};
 use crate::core::services::Lifecycle;
 use crate::core::services::Service;
 use crate::core::services::error::ServiceError;
 use crate::core::services::repository_service::RepositoryService;
+use crate::core::utils::text::nfc;
 
 /// Input for creating a user
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +100,9 @@ impl From<User> for UserOutput {
 pub struct UserService {
     /// User repository
     repository: Arc<UserRepository>,
+
+    /// Source of ids for new users
+    id_generator: Arc<dyn IdGenerator>,
 }
 
 impl Service for UserService {}
@@ -131,9 +137,26 @@ impl UserService {
         let repository = UserRepository::new(repository_service).await?;
         Ok(Self {
             repository: Arc::new(repository),
+            id_generator: Arc::new(UuidV4Generator),
         })
     }
 
+    /// Create a user service generating ids with the configured `ids.strategy`
+    pub async fn from_config(
+        repository_service: &RepositoryService,
+        config: &AppConfig,
+    ) -> Result<Self, ServiceError> {
+        Ok(Self::new(repository_service)
+            .await?
+            .with_id_generator(config.id_generator()))
+    }
+
+    /// Generate ids for new users with `id_generator` instead of random UUIDs
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
     /// Find a user by ID
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<UserOutput>, ServiceError> {
         let user = self.repository.find_by_id(&id).await?;
@@ -158,7 +181,12 @@ impl UserService {
         }
 
         // Create user entity
-        let mut user = User::new(input.username, input.email, input.display_name);
+        let mut user = User::with_id(
+            self.id_generator.generate(),
+            input.username,
+            input.email,
+            input.display_name,
+        );
 
         // Set optional fields
         if let Some(role) = input.role {
@@ -195,7 +223,7 @@ impl UserService {
         }
 
         if let Some(display_name) = input.display_name {
-            updated_user.display_name = display_name;
+            updated_user.display_name = nfc(&display_name);
         }
 
         if let Some(role) = input.role {
@@ -325,6 +353,34 @@ mod tests {
         assert!(!updated_user.active);
     }
 
+    #[test]
+    async fn test_display_names_are_stored_in_nfc() {
+        let service = create_test_service().await;
+
+        let created = service
+            .create_user(CreateUserInput {
+                username: "zoe".to_string(),
+                email: "zoe@example.com".to_string(),
+                display_name: "Zoe\u{301} Smith".to_string(),
+                role: None,
+                active: None,
+            })
+            .await
+            .unwrap();
+        let stored = service.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(stored.display_name, "Zo\u{e9} Smith");
+
+        let update = UpdateUserInput {
+            email: None,
+            display_name: Some("Zoe\u{301} Jones".to_string()),
+            role: None,
+            active: None,
+        };
+        service.update_user(created.id, update).await.unwrap();
+        let stored = service.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(stored.display_name, "Zo\u{e9} Jones");
+    }
+
     #[test]
     async fn test_find_by_role() {
         let service = create_test_service().await;
@@ -385,4 +441,55 @@ mod tests {
         let found_after = service.find_by_id(created_user.id).await.unwrap();
         assert!(found_after.is_none());
     }
+
+    #[test]
+    async fn test_create_user_uses_id_generator() {
+        use crate::core::models::id_generator::{Ulid, UlidGenerator};
+
+        let service = create_test_service()
+            .await
+            .with_id_generator(Arc::new(UlidGenerator::new()));
+
+        let mut ids = Vec::new();
+        for name in ["first", "second", "third"] {
+            let input = CreateUserInput {
+                username: name.to_string(),
+                email: format!("{}@example.com", name),
+                display_name: name.to_string(),
+                role: None,
+                active: None,
+            };
+            ids.push(Ulid::from(service.create_user(input).await.unwrap().id));
+        }
+
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        let text = ids[0].to_string();
+        assert_eq!(text.parse::<Ulid>(), Ok(ids[0]));
+    }
+
+    #[test]
+    async fn test_from_config_uses_configured_id_strategy() {
+        use crate::core::models::id_generator::{IdStrategy, Ulid};
+        use crate::core::utils::sources::now_millis;
+
+        let repo_service = RepositoryService::new();
+        repo_service.init().await.unwrap();
+        let mut config = AppConfig::default();
+        config.ids.strategy = IdStrategy::Ulid;
+        let service = UserService::from_config(&repo_service, &config)
+            .await
+            .unwrap();
+
+        let input = CreateUserInput {
+            username: "configured".to_string(),
+            email: "configured@example.com".to_string(),
+            display_name: "Configured".to_string(),
+            role: None,
+            active: None,
+        };
+        let id = Ulid::from(service.create_user(input).await.unwrap().id);
+
+        // A ULID carries its creation time; a random UUID does not
+        assert!((now_millis() as u64).abs_diff(id.timestamp_ms()) < 60_000);
+    }
 }
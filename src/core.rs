@@ -30,7 +30,8 @@ pub use metrics::{init_metrics, metrics_endpoint_handler, try_record_metrics};
 pub use reliability::apply_reliability;
 pub use router::CoreRouter;
 pub use utils::api_resource::{
-    ApiHandlerOptions, ApiResource, ApiResourceRegistry, create_api_handler,
+    ApiHandlerOptions, ApiResource, ApiResourceRegistry, ApiVersion, CacheScope, CacheSubject,
+    create_api_handler,
 };
 
 // Export specific items from modules to avoid name conflicts
@@ -10,8 +10,12 @@ pub mod claims;
 #[cfg(feature = "auth")]
 pub mod client;
 #[cfg(feature = "auth")]
+pub mod credential_schemes;
+#[cfg(feature = "auth")]
 pub mod error;
 #[cfg(feature = "auth")]
+pub mod identity;
+#[cfg(feature = "auth")]
 pub mod interfaces;
 #[cfg(feature = "auth")]
 pub mod middleware;
@@ -20,6 +24,8 @@ pub mod mock;
 #[cfg(feature = "auth")]
 pub mod models;
 #[cfg(feature = "auth")]
+pub mod policy;
+#[cfg(feature = "auth")]
 pub mod providers;
 
 #[cfg(feature = "auth")]
@@ -32,13 +38,16 @@ use std::collections::HashMap;
 pub use self::{
     claims::StandardClaims,
     client::EntraTokenClient,
+    credential_schemes::{CredentialSchemes, enforce_single_credential},
     error::AuthError,
+    identity::{ClaimsMapper, DefaultClaimsMapper, Identity},
     interfaces::{TokenClient as InterfaceTokenClient, TokenValidationResult},
     middleware::{
         AuthMiddleware, EntraAuthConfig, EntraAuthLayer, Permission, PermissionRequirement, Role,
         RoleRequirement, auth_middleware, require_auth, require_roles, role_from_string,
     },
     mock::MockTokenClient,
+    policy::{AuthorizationPolicy, authorize},
 };
 
 /// Core authentication configuration
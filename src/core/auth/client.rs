@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
@@ -27,6 +27,19 @@ struct TokenCacheEntry {
     expires_at: SystemTime,
 }
 
+/// Process-wide client for token requests, which must not follow redirects
+fn token_endpoint_client() -> Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
 /// Entra token client for acquiring tokens for downstream services
 #[derive(Debug)]
 pub struct EntraTokenClient {
@@ -60,7 +73,7 @@ impl EntraTokenClient {
         let token_url = TokenUrl::new(token_url_str).unwrap();
 
         Self {
-            client: Client::new(),
+            client: token_endpoint_client(),
             client_id,
             client_secret,
             auth_url,
@@ -118,7 +131,7 @@ impl EntraTokenClient {
         let token_url = TokenUrl::new(token_url_str).unwrap();
 
         Self {
-            client: Client::new(),
+            client: token_endpoint_client(),
             client_id: client_id_obj,
             client_secret: client_secret_obj,
             auth_url,
@@ -147,7 +160,7 @@ impl EntraTokenClient {
         let token_url = TokenUrl::new(token_url_str).unwrap();
 
         Self {
-            client: Client::new(),
+            client: token_endpoint_client(),
             client_id: client_id_obj,
             client_secret: client_secret_obj,
             auth_url,
@@ -156,6 +169,15 @@ impl EntraTokenClient {
         }
     }
 
+    /// Send token requests through `client`
+    ///
+    /// The client should not follow redirects, so token requests cannot be
+    /// redirected to another host.
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
     /// Acquire a token for the specified resource/scope
     pub async fn get_token(&self, scope: &str) -> Result<String, String> {
         // Check cache first
@@ -173,12 +195,6 @@ impl EntraTokenClient {
 
         info!("Acquiring new token for scope: {}", scope);
 
-        // Configure HTTP client for oauth2
-        let http_client = reqwest::ClientBuilder::new()
-            .redirect(reqwest::redirect::Policy::none())
-            .build()
-            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-
         // Create a new OAuth2 client for this request
         let oauth_client = BasicClient::new(self.client_id.clone())
             .set_client_secret(self.client_secret.clone())
@@ -189,7 +205,7 @@ impl EntraTokenClient {
         let token_result = oauth_client
             .exchange_client_credentials()
             .add_scope(Scope::new(scope.to_string()))
-            .request_async(&http_client)
+            .request_async(&self.client)
             .await
             .map_err(|e| format!("Failed to get token: {}", e))?;
 
@@ -270,7 +286,7 @@ impl TokenClient for EntraTokenClient {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::config::app_config::{AppConfig, ProviderConfig};
+    use crate::core::config::app_config::{AppConfig, ProviderConfig, default_allowed_algorithms};
     use std::collections::HashMap;
     use std::env;
     use std::time::{Duration, SystemTime};
@@ -314,6 +330,7 @@ mod tests {
             audience: "api://default".to_string(),
             role_mappings: HashMap::new(),
             provider_specific: HashMap::new(),
+            allowed_algorithms: default_allowed_algorithms(),
         };
 
         // Add tenant_id to provider specific config
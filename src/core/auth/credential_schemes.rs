@@ -0,0 +1,255 @@
+//! One credential scheme per request
+//!
+//! A request carrying both a bearer token and an API key is ambiguous: the
+//! two may identify different callers. [`enforce_single_credential`] runs
+//! ahead of authentication and, per `auth.multiple_credentials`, either
+//! rejects such requests with 400 or keeps only the preferred credential so
+//! later layers see a single identity.
+//!
+//! ```yaml
+//! auth:
+//!   multiple_credentials: prefer_bearer   # reject (default) | prefer_bearer | prefer_api_key
+//! ```
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::debug;
+
+use crate::core::auth::middleware::AuthError;
+use crate::core::auth::providers::api_key::DEFAULT_API_KEY_HEADER;
+use crate::core::config::app_config::{AuthConfig, MultipleCredentials};
+use crate::core::error::AppError;
+
+/// Credential headers recognized on a request and what to do when both appear
+#[derive(Debug, Clone)]
+pub struct CredentialSchemes {
+    api_key_header: HeaderName,
+    policy: MultipleCredentials,
+}
+
+impl CredentialSchemes {
+    /// Check `Authorization: Bearer` against the API key header `api_key_header`
+    pub fn new(api_key_header: &str, policy: MultipleCredentials) -> Result<Self, AppError> {
+        let api_key_header =
+            HeaderName::try_from(api_key_header.to_ascii_lowercase()).map_err(|_| {
+                AppError::ConfigurationError(format!(
+                    "API key header '{}' is not a valid header name",
+                    api_key_header
+                ))
+            })?;
+        Ok(Self {
+            api_key_header,
+            policy,
+        })
+    }
+
+    /// Policy from `auth.multiple_credentials`, header from the `api_key` provider
+    pub fn from_config(config: &AuthConfig) -> Result<Self, AppError> {
+        Self::new(api_key_header(config), config.multiple_credentials)
+    }
+
+    fn has_bearer(headers: &HeaderMap) -> bool {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.get(..7))
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("bearer "))
+    }
+}
+
+/// Header the `api_key` provider reads keys from
+fn api_key_header(config: &AuthConfig) -> &str {
+    config
+        .providers
+        .get("api_key")
+        .and_then(|provider| provider.provider_specific.get("header"))
+        .and_then(|header| header.as_str())
+        .unwrap_or(DEFAULT_API_KEY_HEADER)
+}
+
+/// Middleware rejecting or resolving requests that present more than one credential
+///
+/// Use with `axum::middleware::from_fn_with_state(schemes, enforce_single_credential)`,
+/// outside the authentication layer.
+pub async fn enforce_single_credential(
+    State(schemes): State<CredentialSchemes>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    if !(CredentialSchemes::has_bearer(headers) && headers.contains_key(&schemes.api_key_header)) {
+        return next.run(request).await;
+    }
+
+    match schemes.policy {
+        MultipleCredentials::Reject => {
+            debug!("Rejecting request carrying both a bearer token and an API key");
+            return AuthError::AmbiguousCredentials(format!(
+                "Send either a bearer token or the {} header, not both",
+                schemes.api_key_header
+            ))
+            .into_response();
+        }
+        MultipleCredentials::PreferBearer => {
+            request.headers_mut().remove(&schemes.api_key_header);
+        }
+        MultipleCredentials::PreferApiKey => {
+            request.headers_mut().remove(header::AUTHORIZATION);
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    /// Echoes which credentials reached the handler
+    async fn credentials(headers: HeaderMap) -> String {
+        format!(
+            "bearer={} api_key={}",
+            headers.contains_key(header::AUTHORIZATION),
+            headers.contains_key(DEFAULT_API_KEY_HEADER)
+        )
+    }
+
+    async fn send(policy: MultipleCredentials, headers: &[(&str, &str)]) -> (StatusCode, String) {
+        let app = Router::new().route("/pets", get(credentials)).layer(
+            axum::middleware::from_fn_with_state(
+                CredentialSchemes::new(DEFAULT_API_KEY_HEADER, policy).unwrap(),
+                enforce_single_credential,
+            ),
+        );
+        let mut request = Request::builder().uri("/pets");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    const BOTH: &[(&str, &str)] = &[("authorization", "Bearer abc.def.ghi"), ("x-api-key", "k1")];
+
+    #[tokio::test]
+    async fn test_both_credentials_rejected_by_default() {
+        let (status, body) = send(MultipleCredentials::default(), BOTH).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("not both"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_prefer_bearer_drops_api_key() {
+        let (status, body) = send(MultipleCredentials::PreferBearer, BOTH).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "bearer=true api_key=false");
+    }
+
+    #[tokio::test]
+    async fn test_prefer_api_key_drops_bearer() {
+        let (status, body) = send(MultipleCredentials::PreferApiKey, BOTH).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "bearer=false api_key=true");
+    }
+
+    #[tokio::test]
+    async fn test_prefer_api_key_authenticates_with_the_key() {
+        use crate::core::auth::identity::Identity;
+        use crate::core::auth::middleware::EntraAuthLayer;
+        use crate::core::config::app_config::{
+            AppConfig, ProviderConfig, default_allowed_algorithms,
+        };
+        use serde_json::json;
+        use std::collections::HashMap;
+
+        let mut config = AppConfig::default();
+        config.auth.enabled = true;
+        config.auth.default_provider = "api_key".to_string();
+        config.auth.multiple_credentials = MultipleCredentials::PreferApiKey;
+        config.auth.providers.insert(
+            "api_key".to_string(),
+            ProviderConfig {
+                enabled: true,
+                client_id: String::new(),
+                jwks_uri: String::new(),
+                issuer_url: String::new(),
+                audience: String::new(),
+                role_mappings: HashMap::new(),
+                provider_specific: HashMap::from([(
+                    "keys".to_string(),
+                    json!([{ "key": "k1", "subject": "reporting-service" }]),
+                )]),
+                allowed_algorithms: default_allowed_algorithms(),
+            },
+        );
+        let app = Router::new()
+            .route(
+                "/pets",
+                get(
+                    |axum::Extension(identity): axum::Extension<Identity>| async move {
+                        identity.subject
+                    },
+                ),
+            )
+            .layer(EntraAuthLayer::from_app_config_require_any_role(
+                &config,
+                Vec::new(),
+            ))
+            .layer(axum::middleware::from_fn_with_state(
+                CredentialSchemes::from_config(&config.auth).unwrap(),
+                enforce_single_credential,
+            ));
+        let mut request = Request::builder().uri("/pets");
+        for (name, value) in BOTH {
+            request = request.header(*name, *value);
+        }
+
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.as_ref(), b"reporting-service");
+    }
+
+    #[test]
+    fn test_invalid_header_is_a_configuration_error() {
+        let schemes = CredentialSchemes::new("X API Key", MultipleCredentials::Reject);
+        assert!(matches!(schemes, Err(AppError::ConfigurationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_single_credential_passes() {
+        let (status, body) = send(MultipleCredentials::Reject, &[("x-api-key", "k1")]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "bearer=false api_key=true");
+
+        // Basic auth next to an API key is not a bearer token
+        let (status, _) = send(
+            MultipleCredentials::Reject,
+            &[("authorization", "Basic dXNlcjpwdw=="), ("x-api-key", "k1")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
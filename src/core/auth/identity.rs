@@ -0,0 +1,193 @@
+//! Mapping validated token claims to the caller's [`Identity`]
+//!
+//! The authentication layer hands the validated token payload to a
+//! [`ClaimsMapper`], which decides how claims become the subject, tenant,
+//! roles and scopes the rest of the app sees. [`DefaultClaimsMapper`] reads
+//! the standard `sub`, `iss`, `tid`, `roles` and `scp`/`scope` claims.
+//! Applications with non-standard tokens plug in their own:
+//!
+//! ```ignore
+//! #[derive(Debug)]
+//! struct GroupsMapper;
+//!
+//! impl ClaimsMapper for GroupsMapper {
+//!     fn map_claims(&self, claims: &Value) -> Result<Identity, AuthError> {
+//!         let mut identity = DefaultClaimsMapper.map_claims(claims)?;
+//!         identity.roles = string_list(claims, "groups");
+//!         Ok(identity)
+//!     }
+//! }
+//!
+//! let config = EntraAuthConfig::new(&app_config, &provider).with_claims_mapper(GroupsMapper);
+//! ```
+//!
+//! Role requirements and authorization policies are checked against the
+//! mapped roles, and the [`Identity`] is added to the request extensions.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::core::auth::error::AuthError;
+
+/// The authenticated caller, as seen by handlers and authorization checks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Identity {
+    /// Subject (user/client ID)
+    pub subject: String,
+    /// Issuer of the token
+    pub issuer: String,
+    /// Tenant the caller belongs to
+    pub tenant: Option<String>,
+    /// Roles used for authorization
+    pub roles: Vec<String>,
+    /// Delegated scopes
+    pub scopes: Vec<String>,
+}
+
+impl Identity {
+    /// Whether the caller has `role`
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    /// Whether the caller was granted `scope`
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+/// Turns a validated token payload into an [`Identity`]
+pub trait ClaimsMapper: Send + Sync + fmt::Debug {
+    /// Map the token's claims; an error rejects the token
+    fn map_claims(&self, claims: &Value) -> Result<Identity, AuthError>;
+}
+
+/// Reads the standard claims: `sub`, `iss`, `tid`, `roles` and `scp` (or `scope`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultClaimsMapper;
+
+impl ClaimsMapper for DefaultClaimsMapper {
+    fn map_claims(&self, claims: &Value) -> Result<Identity, AuthError> {
+        let subject = claims
+            .get("sub")
+            .and_then(Value::as_str)
+            .ok_or_else(|| AuthError::ValidationFailed("Token is missing 'sub'".to_string()))?;
+
+        // Entra puts delegated scopes in `scp`, other issuers in `scope`
+        let scope_claim = if claims.get("scp").is_some() {
+            "scp"
+        } else {
+            "scope"
+        };
+
+        Ok(Identity {
+            subject: subject.to_string(),
+            issuer: claims
+                .get("iss")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            tenant: claims.get("tid").and_then(Value::as_str).map(String::from),
+            roles: string_list(claims, "roles"),
+            scopes: string_list(claims, scope_claim),
+        })
+    }
+}
+
+/// Read `key` as a list of strings
+///
+/// Accepts a JSON array of strings or a space-separated string; anything
+/// else (including a missing claim) is an empty list.
+pub fn string_list(claims: &Value, key: &str) -> Vec<String> {
+    match claims.get(key) {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        Some(Value::String(values)) => values.split_whitespace().map(String::from).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Takes roles from an IdP-specific `groups` claim and the tenant from `org`
+    #[derive(Debug)]
+    struct GroupsMapper;
+
+    impl ClaimsMapper for GroupsMapper {
+        fn map_claims(&self, claims: &Value) -> Result<Identity, AuthError> {
+            let mut identity = DefaultClaimsMapper.map_claims(claims)?;
+            identity.roles = string_list(claims, "groups");
+            identity.tenant = claims.get("org").and_then(Value::as_str).map(String::from);
+            Ok(identity)
+        }
+    }
+
+    fn token_claims() -> Value {
+        json!({
+            "sub": "user-1",
+            "iss": "https://sts.windows.net/tenant-a/",
+            "tid": "tenant-a",
+            "roles": ["reader"],
+            "scp": "pets.read pets.write",
+            "groups": ["pet-admins", "pet-editors"],
+            "org": "acme",
+        })
+    }
+
+    #[test]
+    fn test_default_mapper_reads_standard_claims() {
+        let identity = DefaultClaimsMapper.map_claims(&token_claims()).unwrap();
+
+        assert_eq!(
+            identity,
+            Identity {
+                subject: "user-1".to_string(),
+                issuer: "https://sts.windows.net/tenant-a/".to_string(),
+                tenant: Some("tenant-a".to_string()),
+                roles: vec!["reader".to_string()],
+                scopes: vec!["pets.read".to_string(), "pets.write".to_string()],
+            }
+        );
+        // Non-standard claims are ignored
+        assert!(!identity.has_role("pet-admins"));
+    }
+
+    #[test]
+    fn test_default_mapper_falls_back_to_scope_claim() {
+        let identity = DefaultClaimsMapper
+            .map_claims(&json!({ "sub": "client-1", "scope": "pets.read" }))
+            .unwrap();
+
+        assert_eq!(identity.scopes, vec!["pets.read".to_string()]);
+        assert!(identity.roles.is_empty());
+        assert_eq!(identity.tenant, None);
+    }
+
+    #[test]
+    fn test_default_mapper_requires_subject() {
+        let result = DefaultClaimsMapper.map_claims(&json!({ "roles": ["reader"] }));
+        assert!(matches!(result, Err(AuthError::ValidationFailed(_))));
+    }
+
+    #[test]
+    fn test_custom_mapper_reads_groups_claim() {
+        let identity = GroupsMapper.map_claims(&token_claims()).unwrap();
+
+        assert_eq!(
+            identity.roles,
+            vec!["pet-admins".to_string(), "pet-editors".to_string()]
+        );
+        assert!(identity.has_role("pet-admins"));
+        assert!(!identity.has_role("reader"));
+        assert_eq!(identity.tenant.as_deref(), Some("acme"));
+        assert!(identity.has_scope("pets.write"));
+    }
+}
@@ -11,20 +11,23 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use futures::future::BoxFuture;
-use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tower::{Layer, Service};
 use tracing::{debug, error, info};
 
+use crate::core::auth::identity::{ClaimsMapper, DefaultClaimsMapper, Identity};
 use crate::core::auth::providers;
+use crate::core::auth::providers::api_key::{API_KEY_ISSUER, ApiKeyProvider};
 use crate::core::auth::providers::common::ProviderConfig;
-use crate::core::auth::providers::common::ProviderRegistry;
+use crate::core::auth::providers::common::{JwksRefreshOutcome, ProviderRegistry};
 use crate::core::auth::{AuthError as CoreAuthError, StandardClaims, providers::OAuthProvider};
 use crate::core::config::app_config;
 use crate::core::config::app_config::AppConfig;
 use crate::core::config::constants;
 use crate::core::router::AppState;
+use crate::core::utils::http_client::shared_client;
 
 /// JWKS (JSON Web Key Set) response
 #[derive(Debug, Clone, Deserialize)]
@@ -174,6 +177,11 @@ pub struct EntraAuthConfig {
     validate_iss: bool,
     validate_aud: bool,
     jwks_client: Option<Client>,
+    claims_mapper: Arc<dyn ClaimsMapper>,
+    /// Authenticates requests that present an API key instead of a bearer token
+    api_keys: Option<ApiKeyProvider>,
+    /// Token header algorithms accepted before any key is looked up
+    allowed_algorithms: Vec<Algorithm>,
 }
 
 /// OpenID Connect configuration response
@@ -190,7 +198,7 @@ impl Default for EntraAuthConfig {
         Self {
             required_roles: RoleRequirement::None,
             required_permissions: PermissionRequirement::None,
-            client: Client::new(),
+            client: shared_client(),
             jwks_uri: String::new(),
             jwks_cache: Arc::new(Mutex::new(None)),
             debug_validation: false,
@@ -205,6 +213,9 @@ impl Default for EntraAuthConfig {
             validate_iss: true,
             validate_aud: true,
             jwks_client: None,
+            claims_mapper: Arc::new(DefaultClaimsMapper),
+            api_keys: None,
+            allowed_algorithms: providers::common::default_algorithms(),
         }
     }
 }
@@ -290,7 +301,7 @@ impl EntraAuthConfig {
         Self {
             required_roles: RoleRequirement::None,
             required_permissions: PermissionRequirement::None,
-            client: Client::new(),
+            client: shared_client(),
             jwks_uri,
             jwks_cache: Arc::new(Mutex::new(None)),
             debug_validation,
@@ -311,6 +322,9 @@ impl EntraAuthConfig {
             validate_iss: true,
             validate_aud: true,
             jwks_client: None,
+            claims_mapper: Arc::new(DefaultClaimsMapper),
+            api_keys: api_key_provider(config),
+            allowed_algorithms: allowed_algorithms(provider_config),
         }
     }
 
@@ -328,6 +342,54 @@ impl EntraAuthConfig {
         self.required_permissions = permission_requirement;
         self
     }
+
+    /// Use `mapper` to turn validated claims into the caller's [`Identity`]
+    pub fn with_claims_mapper(mut self, mapper: impl ClaimsMapper + 'static) -> Self {
+        self.claims_mapper = Arc::new(mapper);
+        self
+    }
+
+    /// Accept API keys known to `provider` from requests without a bearer token
+    pub fn with_api_key_provider(mut self, provider: ApiKeyProvider) -> Self {
+        self.api_keys = Some(provider);
+        self
+    }
+
+    /// Fetch JWKS and OpenID metadata through `client`
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+/// The enabled `auth.providers.api_key` provider, if any
+fn api_key_provider(config: &AppConfig) -> Option<ApiKeyProvider> {
+    let provider_config = config
+        .auth
+        .providers
+        .get("api_key")
+        .filter(|provider| provider.enabled)?;
+    match ApiKeyProvider::from_app_config(provider_config) {
+        Ok(provider) => Some(provider),
+        Err(e) => {
+            error!(
+                "API key provider is misconfigured, API keys are rejected: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Algorithms `provider_config` accepts; a misconfigured list accepts none
+fn allowed_algorithms(provider_config: &app_config::ProviderConfig) -> Vec<Algorithm> {
+    match providers::common::parse_algorithms(&provider_config.allowed_algorithms) {
+        Ok(algorithms) => algorithms,
+        Err(e) => {
+            error!("Allowed JWT algorithms are misconfigured, tokens are rejected: {}", e);
+            Vec::new()
+        }
+    }
 }
 
 /// Error response for authentication failures
@@ -343,6 +405,8 @@ pub enum AuthError {
     InternalError(String),
     /// Authorization failed
     AccessDenied(String),
+    /// More than one kind of credential was presented
+    AmbiguousCredentials(String),
 }
 
 impl IntoResponse for AuthError {
@@ -365,6 +429,7 @@ impl IntoResponse for AuthError {
                 format!("Server error during authentication: {}", reason),
             ),
             AuthError::AccessDenied(reason) => (StatusCode::FORBIDDEN, reason),
+            AuthError::AmbiguousCredentials(reason) => (StatusCode::BAD_REQUEST, reason),
         };
 
         let body = axum::Json(serde_json::json!({
@@ -423,6 +488,42 @@ fn extract_token(headers: &HeaderMap) -> Result<String, AuthError> {
     Ok(token)
 }
 
+/// Authenticate `req` by API key, applying the same role and permission requirements as tokens
+async fn authenticate_api_key(
+    mut req: Request,
+    provider: &ApiKeyProvider,
+    presented: &str,
+    config: &EntraAuthConfig,
+) -> Result<Request, AuthError> {
+    let key = provider
+        .authenticate(presented)
+        .await
+        .map_err(|e| match e {
+            CoreAuthError::ValidationFailed(reason) => AuthError::ValidationFailed(reason),
+            other => AuthError::InternalError(other.to_string()),
+        })?;
+    let payload = serde_json::json!({
+        "sub": key.subject,
+        "aud": "",
+        "iss": API_KEY_ISSUER,
+        "exp": key.expires_at.map_or(constants::timestamps::YEAR_2100, |expires_at| {
+            expires_at.timestamp().max(0) as usize
+        }),
+        "nbf": 0,
+        "iat": 0,
+        "roles": key.roles,
+        "scp": (!key.scopes.is_empty()).then(|| key.scopes.join(" ")),
+    });
+    let (claims, identity) = map_claims(payload, config)?;
+
+    validate_claims(&claims, config)?;
+    validate_permissions(&claims, config)?;
+
+    req.extensions_mut().insert(claims);
+    req.extensions_mut().insert(identity);
+    Ok(req)
+}
+
 /// Fetch and cache JWKS from the Microsoft endpoint
 async fn fetch_jwks(config: &EntraAuthConfig) -> Result<JwksResponse, AuthError> {
     // Check if we have a cached JWKS that's still valid
@@ -435,28 +536,34 @@ async fn fetch_jwks(config: &EntraAuthConfig) -> Result<JwksResponse, AuthError>
         }
     }
 
+    download_jwks(config)
+        .await
+        .map_err(AuthError::InternalError)
+}
+
+/// Fetch the JWKS and replace the cached one, keeping the cache on failure
+async fn download_jwks(config: &EntraAuthConfig) -> Result<JwksResponse, String> {
     // Replace {tenant} placeholder with actual tenant ID
     let jwks_uri = config.jwks_uri.replace("{tenant}", &config.tenant_id);
 
-    // If not, fetch a new JWKS
     let response = config
         .client
         .get(&jwks_uri)
         .send()
         .await
-        .map_err(|e| AuthError::InternalError(format!("Failed to fetch JWKS: {}", e)))?;
+        .map_err(|e| format!("Failed to fetch JWKS: {}", e))?;
 
     if !response.status().is_success() {
-        return Err(AuthError::InternalError(format!(
+        return Err(format!(
             "Failed to fetch JWKS, status: {}",
             response.status()
-        )));
+        ));
     }
 
     let jwks = response
         .json::<JwksResponse>()
         .await
-        .map_err(|e| AuthError::InternalError(format!("Failed to parse JWKS: {}", e)))?;
+        .map_err(|e| format!("Failed to parse JWKS: {}", e))?;
 
     // Cache the JWKS for 1 hour
     let expires_at = SystemTime::now() + Duration::from_secs(3600);
@@ -471,6 +578,37 @@ async fn fetch_jwks(config: &EntraAuthConfig) -> Result<JwksResponse, AuthError>
     Ok(jwks)
 }
 
+/// Refreshes the JWKS cache an [`EntraAuthLayer`] validates tokens against
+///
+/// The layer's middleware services share one cache, so a refresher obtained
+/// from [`EntraAuthLayer::jwks_refresher`] covers all of them.
+#[derive(Clone)]
+pub struct JwksRefresher {
+    config: EntraAuthConfig,
+}
+
+impl JwksRefresher {
+    /// Fetch the JWKS now, replacing the cached keys if that succeeds
+    pub async fn refresh(&self) -> JwksRefreshOutcome {
+        match download_jwks(&self.config).await {
+            Ok(_) => {
+                info!("Refreshed JWKS for the auth middleware");
+                JwksRefreshOutcome {
+                    refreshed: true,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                error!("Failed to refresh JWKS for the auth middleware: {}", e);
+                JwksRefreshOutcome {
+                    refreshed: false,
+                    error: Some(e),
+                }
+            }
+        }
+    }
+}
+
 /// Find a JWK by its key ID
 fn find_jwk<'a>(jwks: &'a JwksResponse, kid: &str) -> Result<&'a Jwk, AuthError> {
     jwks.keys
@@ -547,6 +685,24 @@ fn extract_claims_without_validation(token: &str) -> Option<serde_json::Value> {
     None
 }
 
+/// Run the configured [`ClaimsMapper`] over a validated token payload
+///
+/// The returned claims carry the mapped roles, so role requirements and
+/// authorization policies see the same roles as the [`Identity`].
+fn map_claims(
+    payload: serde_json::Value,
+    config: &EntraAuthConfig,
+) -> Result<(EntraClaims, Identity), AuthError> {
+    let identity = config
+        .claims_mapper
+        .map_claims(&payload)
+        .map_err(|e| AuthError::ValidationFailed(e.to_string()))?;
+    let mut claims: EntraClaims = serde_json::from_value(payload)
+        .map_err(|e| AuthError::ValidationFailed(format!("Invalid token claims: {}", e)))?;
+    claims.roles = identity.roles.clone();
+    Ok((claims, identity))
+}
+
 /// Validate claims in the token
 fn validate_claims(claims: &EntraClaims, config: &EntraAuthConfig) -> Result<(), AuthError> {
     // Authorization based on roles
@@ -681,7 +837,7 @@ impl EntraAuthLayer {
             config: EntraAuthConfig {
                 required_roles: RoleRequirement::None,
                 required_permissions: PermissionRequirement::None,
-                client: Client::new(),
+                client: shared_client(),
                 jwks_uri: String::new(),
                 jwks_cache: Arc::new(Mutex::new(None)),
                 debug_validation: false,
@@ -696,6 +852,9 @@ impl EntraAuthLayer {
                 validate_iss: true,
                 validate_aud: true,
                 jwks_client: None,
+                claims_mapper: Arc::new(DefaultClaimsMapper),
+                api_keys: None,
+                allowed_algorithms: providers::common::default_algorithms(),
             },
         }
     }
@@ -752,7 +911,7 @@ impl EntraAuthLayer {
         let auth_config = EntraAuthConfig {
             required_roles: RoleRequirement::Any(roles),
             required_permissions: PermissionRequirement::None,
-            client: Client::new(),
+            client: shared_client(),
             jwks_uri: provider_config.jwks_uri.clone(),
             jwks_cache: Arc::new(Mutex::new(None)),
             debug_validation: config.auth.debug,
@@ -778,6 +937,9 @@ impl EntraAuthLayer {
             validate_iss: true,
             validate_aud: true,
             jwks_client: None,
+            claims_mapper: Arc::new(DefaultClaimsMapper),
+            api_keys: api_key_provider(config),
+            allowed_algorithms: allowed_algorithms(provider_config),
         };
 
         Self::new(auth_config)
@@ -843,6 +1005,18 @@ impl EntraAuthLayer {
     pub fn with_role_requirement(self, role_requirement: RoleRequirement) -> Self {
         Self::new(self.config.with_role_requirement(role_requirement))
     }
+
+    /// Fetch JWKS and OpenID metadata through `client`, e.g. `AppState::http_client`
+    pub fn with_http_client(self, client: Client) -> Self {
+        Self::new(self.config.with_http_client(client))
+    }
+
+    /// Handle refreshing the JWKS cache this layer validates tokens against
+    pub fn jwks_refresher(&self) -> JwksRefresher {
+        JwksRefresher {
+            config: self.config.clone(),
+        }
+    }
 }
 
 impl<S> Layer<S> for EntraAuthLayer {
@@ -905,13 +1079,32 @@ async fn validate_token_wrapper(
     mut req: Request,
     config: &EntraAuthConfig,
 ) -> Result<Request, AuthError> {
+    // API keys are only considered when no bearer token is presented
+    let api_key = config
+        .api_keys
+        .as_ref()
+        .filter(|_| {
+            !req.headers()
+                .contains_key(axum::http::header::AUTHORIZATION)
+        })
+        .and_then(|provider| {
+            let key = provider.key_from_headers(req.headers())?;
+            Some((provider, key.to_string()))
+        });
+    if let Some((provider, key)) = api_key {
+        return authenticate_api_key(req, provider, &key, config).await;
+    }
+
     // Extract the token
     let headers = req.headers();
     let token = extract_token(headers)?;
 
-    let header = decode_header(&token).map_err(|e| {
-        AuthError::ValidationFailed(format!("Failed to decode token header: {}", e))
-    })?;
+    // Refuse disallowed algorithms before the token can pick how it is verified
+    let header = providers::common::check_algorithm(&token, &config.allowed_algorithms)
+        .map_err(|e| match e {
+            CoreAuthError::ValidationFailed(reason) => AuthError::ValidationFailed(reason),
+            other => AuthError::InternalError(other.to_string()),
+        })?;
 
     if config.debug_validation {
         // In debug mode, let's completely bypass JWT validation
@@ -929,8 +1122,12 @@ async fn validate_token_wrapper(
             app_id_uri: Some("debug_app_id_uri".to_string()),
             scp: Some("api-access".to_string()),
         };
+        let payload = serde_json::to_value(&claims)
+            .map_err(|e| AuthError::InternalError(format!("Failed to encode claims: {}", e)))?;
+        let (claims, identity) = map_claims(payload, config)?;
 
         req.extensions_mut().insert(claims);
+        req.extensions_mut().insert(identity);
         return Ok(req);
     }
 
@@ -956,7 +1153,7 @@ async fn validate_token_wrapper(
     let decoding_key = create_decoding_key(jwk)?;
 
     // Set up validation
-    let mut validation = Validation::new(Algorithm::RS256);
+    let mut validation = Validation::new(header.alg);
     validation.validate_exp = true;
 
     // Extract the token payload to get the actual audience
@@ -1023,11 +1220,11 @@ async fn validate_token_wrapper(
         debug!("  Tenant ID: {}", config.tenant_id);
         debug!("  Accepted issuers: {:?}", issuers);
         debug!("  Expected audience: {}", audience);
-        debug!("  Algorithm: RS256");
+        debug!("  Algorithm: {:?}", header.alg);
     }
 
     // Validate token with better error handling
-    let token_data = match decode::<EntraClaims>(&token, &decoding_key, &validation) {
+    let token_data = match decode::<serde_json::Value>(&token, &decoding_key, &validation) {
         Ok(data) => data,
         Err(e) => {
             let detail = match e.kind() {
@@ -1091,14 +1288,17 @@ async fn validate_token_wrapper(
         }
     };
 
+    let (claims, identity) = map_claims(token_data.claims, config)?;
+
     // Role-based authorization check
-    validate_claims(&token_data.claims, config)?;
+    validate_claims(&claims, config)?;
 
     // Permission-based authorization check
-    validate_permissions(&token_data.claims, config)?;
+    validate_permissions(&claims, config)?;
 
     // Store claims in request extensions for handlers to access
-    req.extensions_mut().insert(token_data.claims);
+    req.extensions_mut().insert(claims);
+    req.extensions_mut().insert(identity);
 
     Ok(req)
 }
@@ -1215,7 +1415,7 @@ pub fn role_from_string(role: &str) -> Role {
 mod tests {
     use super::*;
     use crate::core::auth::mock::MockTokenClient;
-    use crate::core::config::app_config::{AppConfig, ProviderConfig};
+    use crate::core::config::app_config::{AppConfig, ProviderConfig, default_allowed_algorithms};
     use axum::body::Body;
     use axum::http::{Request, StatusCode};
     use serde_json::json;
@@ -1248,6 +1448,7 @@ mod tests {
                 map
             },
             provider_specific: HashMap::new(),
+            allowed_algorithms: default_allowed_algorithms(),
         };
 
         // Add provider to app config
@@ -1326,6 +1527,7 @@ mod tests {
                 map
             },
             provider_specific: HashMap::new(),
+            allowed_algorithms: default_allowed_algorithms(),
         };
 
         // Add provider to app config
@@ -1521,6 +1723,62 @@ mod tests {
         ));
     }
 
+    #[derive(Debug)]
+    struct GroupsMapper;
+
+    impl ClaimsMapper for GroupsMapper {
+        fn map_claims(&self, claims: &serde_json::Value) -> Result<Identity, CoreAuthError> {
+            let mut identity = DefaultClaimsMapper.map_claims(claims)?;
+            identity.roles = crate::core::auth::identity::string_list(claims, "groups");
+            Ok(identity)
+        }
+    }
+
+    fn groups_token_payload() -> serde_json::Value {
+        serde_json::json!({
+            "sub": "test-subject",
+            "aud": "test-audience",
+            "iss": "test-issuer",
+            "exp": constants::timestamps::YEAR_2100,
+            "nbf": 0,
+            "iat": constants::timestamps::JAN_1_2021,
+            "roles": ["reader"],
+            "groups": ["pet-admins"],
+            "scp": "pets.read",
+        })
+    }
+
+    #[test]
+    fn test_default_claims_mapper_keeps_token_roles() {
+        let config = EntraAuthConfig::default();
+        let (claims, identity) = map_claims(groups_token_payload(), &config).unwrap();
+
+        assert_eq!(claims.roles, vec!["reader".to_string()]);
+        assert_eq!(identity.roles, claims.roles);
+        assert_eq!(identity.scopes, vec!["pets.read".to_string()]);
+        assert_eq!(claims.get_scopes(), vec!["pets.read", "reader"]);
+    }
+
+    #[test]
+    fn test_custom_claims_mapper_roles_are_authorized() {
+        let config = EntraAuthConfig::default()
+            .with_role_requirement(RoleRequirement::Any(vec!["pet-admins".to_string()]))
+            .with_claims_mapper(GroupsMapper);
+        let (claims, identity) = map_claims(groups_token_payload(), &config).unwrap();
+
+        assert!(identity.has_role("pet-admins"));
+        assert!(validate_claims(&claims, &config).is_ok());
+
+        // The same token without the mapper lacks the role
+        let config = EntraAuthConfig::default()
+            .with_role_requirement(RoleRequirement::Any(vec!["pet-admins".to_string()]));
+        let (claims, _) = map_claims(groups_token_payload(), &config).unwrap();
+        assert!(matches!(
+            validate_claims(&claims, &config),
+            Err(AuthError::AccessDenied(_))
+        ));
+    }
+
     #[test]
     fn test_auth_error_into_response() {
         // Test error response conversion for each error type
@@ -1686,6 +1944,9 @@ mod tests {
             validate_iss: true,
             validate_aud: true,
             jwks_client: None,
+            claims_mapper: Arc::new(DefaultClaimsMapper),
+            api_keys: None,
+            allowed_algorithms: providers::common::default_algorithms(),
         };
 
         // Test the matches function directly with roles and config
@@ -1709,6 +1970,120 @@ mod tests {
         assert!(!RoleRequirement::FullAccess.matches(&read_test_roles, &config));
     }
 
+    /// App config whose default provider is `api_key`, mapping the `ops` role to admin
+    fn api_key_app_config() -> AppConfig {
+        let mut app_config = AppConfig::default();
+        app_config.auth.enabled = true;
+        app_config.auth.default_provider = "api_key".to_string();
+        app_config.auth.providers.insert(
+            "api_key".to_string(),
+            ProviderConfig {
+                enabled: true,
+                client_id: String::new(),
+                jwks_uri: String::new(),
+                issuer_url: String::new(),
+                audience: String::new(),
+                role_mappings: HashMap::from([("admin".to_string(), vec!["ops".to_string()])]),
+                provider_specific: HashMap::from([
+                    ("header".to_string(), json!("X-Partner-Key")),
+                    (
+                        "keys".to_string(),
+                        json!([
+                            {"key": "ops-secret", "subject": "ops-bot", "roles": ["ops"]},
+                            {"key": "read-secret", "subject": "reporting", "roles": ["read"]},
+                        ]),
+                    ),
+                ]),
+                allowed_algorithms: default_allowed_algorithms(),
+            },
+        );
+        app_config
+    }
+
+    /// Router behind the admin layer, answering with the caller's subject
+    fn admin_router(app_config: &AppConfig) -> axum::Router {
+        axum::Router::new()
+            .route(
+                "/admin",
+                axum::routing::get(
+                    |axum::Extension(identity): axum::Extension<Identity>| async move {
+                        identity.subject
+                    },
+                ),
+            )
+            .layer(EntraAuthLayer::from_app_config_require_admin(app_config))
+    }
+
+    async fn send_with(router: axum::Router, headers: &[(&str, &str)]) -> (StatusCode, String) {
+        use tower::ServiceExt;
+
+        let mut request = Request::builder().uri("/admin");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_api_key_reaches_provider_through_router() {
+        let router = admin_router(&api_key_app_config());
+
+        let (status, body) = send_with(router.clone(), &[("x-partner-key", "ops-secret")]).await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "ops-bot"));
+
+        let (status, _) = send_with(router.clone(), &[("x-partner-key", "wrong")]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // Keys are held to the same role requirement as tokens
+        let (status, _) = send_with(router.clone(), &[("x-partner-key", "read-secret")]).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = send_with(router, &[]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_token_algorithm_is_rejected() {
+        use jsonwebtoken::{EncodingKey, Header, encode};
+
+        let mut app_config = AppConfig::default();
+        app_config.auth.default_provider = "entra".to_string();
+        app_config.auth.providers.insert(
+            "entra".to_string(),
+            ProviderConfig {
+                enabled: true,
+                client_id: "client".to_string(),
+                jwks_uri: "http://127.0.0.1:9/keys".to_string(),
+                issuer_url: String::new(),
+                audience: String::new(),
+                role_mappings: HashMap::from([("admin".to_string(), vec!["admin".to_string()])]),
+                provider_specific: HashMap::new(),
+                allowed_algorithms: vec!["RS256".to_string()],
+            },
+        );
+        let router = admin_router(&app_config);
+
+        // A symmetric token must not be verified at all, whatever key it names
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("key-1".to_string());
+        let claims = json!({"sub": "attacker", "roles": ["admin"], "exp": 4_102_444_800u64});
+        let token = encode(&header, &claims, &EncodingKey::from_secret(b"secret")).unwrap();
+        let bearer = format!("Bearer {}", token);
+
+        let (status, body) = send_with(router, &[("authorization", bearer.as_str())]).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains("HS256 is not allowed"), "{}", body);
+    }
+
     #[tokio::test]
     async fn test_generic_middleware() {
         // Skip this test for now as the middleware structure has changed
@@ -0,0 +1,457 @@
+//! Declarative route authorization
+//!
+//! Instead of layering `require_roles` onto individual routes, list the
+//! requirements under `auth.rules`:
+//!
+//! ```yaml
+//! auth:
+//!   default_policy: deny
+//!   rules:
+//!     - { method: POST, path: /api/pets, roles: [editor] }
+//!     - { path: /api/pets/{id}, roles: [reader, editor] }
+//!     - { path: /health }  # no roles: public
+//! ```
+//!
+//! [`AuthorizationPolicy`] picks the first rule matching the request's
+//! method and path. The caller needs any one of the rule's roles, taken from
+//! the [`EntraClaims`] attached by the authentication layer: without claims
+//! the request gets 401, with the wrong roles 403. Requests matching no rule
+//! follow `auth.default_policy`, except the health and readiness probes in
+//! [`PUBLIC_PATHS`], which stay public unless a rule says otherwise.
+//!
+//! The router builder applies the policy to the whole router, including
+//! routes added with `RouterBuilder::with_routes`. There is no
+//! authentication layer in front of every route, so the policy
+//! authenticates a request itself when a rule requires roles (see
+//! [`AuthorizationPolicy::with_authentication`]). A policy can also be
+//! layered onto a router by hand:
+//!
+//! ```ignore
+//! let policy = AuthorizationPolicy::from_config(&config.auth);
+//! let api = api_routes
+//!     .layer(axum::middleware::from_fn_with_state(policy, authorize))
+//!     .layer(require_auth());
+//! ```
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{OriginalUri, Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tower::{Layer, ServiceExt};
+use tracing::{debug, warn};
+
+use super::middleware::{AuthError, EntraAuthLayer, EntraClaims};
+use crate::core::config::app_config::{AuthConfig, AuthorizationRule, DefaultPolicy};
+
+/// Health and readiness probes, allowed when no rule matches even under default-deny
+pub const PUBLIC_PATHS: &[&str] = &["/health", "/health/readiness"];
+
+/// One segment of a rule's path pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    /// `{param}`: exactly one segment
+    Param,
+    /// `{*rest}`: the remaining segments, possibly none
+    Rest,
+}
+
+/// A compiled [`AuthorizationRule`]
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    method: Option<Method>,
+    segments: Vec<Segment>,
+    roles: Vec<String>,
+}
+
+impl CompiledRule {
+    fn compile(rule: &AuthorizationRule) -> Option<Self> {
+        let method = match &rule.method {
+            Some(method) => match method.to_ascii_uppercase().parse::<Method>() {
+                Ok(method) => Some(method),
+                Err(_) => {
+                    warn!(
+                        "Ignoring authorization rule with invalid method '{}'",
+                        method
+                    );
+                    return None;
+                }
+            },
+            None => None,
+        };
+        let segments = split_path(&rule.path)
+            .map(|segment| {
+                if segment.starts_with("{*") && segment.ends_with('}') {
+                    Segment::Rest
+                } else if segment.starts_with('{') && segment.ends_with('}') {
+                    Segment::Param
+                } else {
+                    Segment::Literal(segment.to_string())
+                }
+            })
+            .collect();
+        Some(Self {
+            method,
+            segments,
+            roles: rule.roles.clone(),
+        })
+    }
+
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        if self.method.as_ref().is_some_and(|m| m != method) {
+            return false;
+        }
+        let mut path = split_path(path);
+        for segment in &self.segments {
+            match segment {
+                Segment::Rest => return true,
+                Segment::Param => {
+                    if path.next().is_none() {
+                        return false;
+                    }
+                }
+                Segment::Literal(literal) => {
+                    if path.next() != Some(literal.as_str()) {
+                        return false;
+                    }
+                }
+            }
+        }
+        path.next().is_none()
+    }
+}
+
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// Outcome of checking a request against the policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// The request may proceed
+    Allow,
+    /// A rule applies but the request carries no identity
+    Unauthenticated,
+    /// The identity lacks the required roles, or no rule matched under default-deny
+    Deny(String),
+}
+
+/// Route authorization rules built from [`AuthConfig`]
+#[derive(Debug, Clone)]
+pub struct AuthorizationPolicy {
+    rules: Arc<Vec<CompiledRule>>,
+    public: Arc<Vec<CompiledRule>>,
+    default_policy: DefaultPolicy,
+    authentication: Option<EntraAuthLayer>,
+}
+
+impl Default for AuthorizationPolicy {
+    fn default() -> Self {
+        Self::new(&[], DefaultPolicy::default())
+    }
+}
+
+impl AuthorizationPolicy {
+    /// Policy from `auth.rules` and `auth.default_policy`
+    pub fn from_config(config: &AuthConfig) -> Self {
+        Self::new(&config.rules, config.default_policy)
+    }
+
+    /// Policy checking `rules` in order, falling back to `default_policy`
+    pub fn new(rules: &[AuthorizationRule], default_policy: DefaultPolicy) -> Self {
+        let public: Vec<AuthorizationRule> = PUBLIC_PATHS
+            .iter()
+            .map(|path| AuthorizationRule {
+                method: None,
+                path: path.to_string(),
+                roles: Vec::new(),
+            })
+            .collect();
+        Self {
+            rules: Arc::new(rules.iter().filter_map(CompiledRule::compile).collect()),
+            public: Arc::new(public.iter().filter_map(CompiledRule::compile).collect()),
+            default_policy,
+            authentication: None,
+        }
+    }
+
+    /// Authenticate requests with `layer` when a matching rule requires roles
+    ///
+    /// Without it, such requests get 401 unless an authentication layer in
+    /// front of the policy already attached claims.
+    pub fn with_authentication(mut self, layer: EntraAuthLayer) -> Self {
+        self.authentication = Some(layer);
+        self
+    }
+
+    /// Whether the policy lets every request through unchecked
+    pub fn is_permissive(&self) -> bool {
+        self.rules.is_empty() && self.default_policy == DefaultPolicy::Allow
+    }
+
+    /// Check a request to `method` `path` made with `claims`
+    pub fn decide(&self, method: &Method, path: &str, claims: Option<&EntraClaims>) -> Decision {
+        let rule = self
+            .rules
+            .iter()
+            .chain(self.public.iter())
+            .find(|rule| rule.matches(method, path));
+        let Some(rule) = rule else {
+            return match self.default_policy {
+                DefaultPolicy::Allow => Decision::Allow,
+                DefaultPolicy::Deny => {
+                    Decision::Deny(format!("No authorization rule permits {} {}", method, path))
+                }
+            };
+        };
+
+        if rule.roles.is_empty() {
+            return Decision::Allow;
+        }
+        let Some(claims) = claims else {
+            return Decision::Unauthenticated;
+        };
+        if claims.roles.iter().any(|role| rule.roles.contains(role)) {
+            Decision::Allow
+        } else {
+            Decision::Deny(format!(
+                "Access denied: requires one of the roles: {}",
+                rule.roles.join(", ")
+            ))
+        }
+    }
+}
+
+/// Middleware enforcing an [`AuthorizationPolicy`]
+///
+/// Use with `axum::middleware::from_fn_with_state(policy, authorize)`, inside
+/// the authentication layer so the claims are available, or with a policy
+/// that [authenticates](AuthorizationPolicy::with_authentication) by itself.
+pub async fn authorize(
+    State(policy): State<AuthorizationPolicy>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let decision = decide_request(&policy, &request);
+    match (decision, &policy.authentication) {
+        (Decision::Unauthenticated, Some(authentication)) => {
+            // Validate the token, then check the roles from the claims it attached
+            let policy = policy.clone();
+            let check = tower::service_fn(move |request: Request| {
+                let policy = policy.clone();
+                let next = next.clone();
+                async move {
+                    let decision = decide_request(&policy, &request);
+                    Ok::<_, Infallible>(enforce(decision, request, next).await)
+                }
+            });
+            match authentication.layer(check).oneshot(request).await {
+                Ok(response) => response,
+                Err(never) => match never {},
+            }
+        }
+        (decision, _) => enforce(decision, request, next).await,
+    }
+}
+
+fn decide_request(policy: &AuthorizationPolicy, request: &Request) -> Decision {
+    // Nested routers see a stripped URI; rules are written against the full path
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| request.uri().path(), |uri| uri.0.path());
+    policy.decide(
+        request.method(),
+        path,
+        request.extensions().get::<EntraClaims>(),
+    )
+}
+
+async fn enforce(decision: Decision, request: Request, next: Next) -> Response {
+    match decision {
+        Decision::Allow => next.run(request).await,
+        Decision::Unauthenticated => AuthError::MissingToken.into_response(),
+        Decision::Deny(reason) => {
+            debug!(
+                "{} {} denied by authorization policy",
+                request.method(),
+                request.uri().path()
+            );
+            AuthError::AccessDenied(reason).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::Body,
+        http::StatusCode,
+        routing::{get, post},
+    };
+    use tower::ServiceExt;
+
+    fn rule(method: Option<&str>, path: &str, roles: &[&str]) -> AuthorizationRule {
+        AuthorizationRule {
+            method: method.map(str::to_string),
+            path: path.to_string(),
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+        }
+    }
+
+    fn claims(roles: &[&str]) -> EntraClaims {
+        EntraClaims {
+            sub: "tester".to_string(),
+            aud: "api".to_string(),
+            iss: "issuer".to_string(),
+            exp: 0,
+            nbf: 0,
+            iat: 0,
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            appid: None,
+            app_id_uri: None,
+            scp: None,
+        }
+    }
+
+    /// Pets API behind the policy; `roles` simulates the authentication layer
+    fn app(policy: AuthorizationPolicy, roles: Option<&'static [&'static str]>) -> Router {
+        Router::new()
+            .route(
+                "/api/pets",
+                get(|| async { "list" }).post(|| async { "created" }),
+            )
+            .route("/api/admin/purge", post(|| async { "purged" }))
+            .layer(axum::middleware::from_fn_with_state(policy, authorize))
+            .layer(axum::middleware::from_fn(
+                move |mut request: Request, next: Next| async move {
+                    if let Some(roles) = roles {
+                        request.extensions_mut().insert(claims(roles));
+                    }
+                    next.run(request).await
+                },
+            ))
+    }
+
+    async fn status(app: Router, method: Method, uri: &str) -> StatusCode {
+        let request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_rule_enforces_role() {
+        let policy = AuthorizationPolicy::new(
+            &[rule(Some("POST"), "/api/pets", &["editor"])],
+            DefaultPolicy::Allow,
+        );
+
+        let reader = app(policy.clone(), Some(&["reader"]));
+        assert_eq!(
+            status(reader.clone(), Method::POST, "/api/pets").await,
+            StatusCode::FORBIDDEN
+        );
+        // The rule only covers POST
+        assert_eq!(
+            status(reader, Method::GET, "/api/pets").await,
+            StatusCode::OK
+        );
+
+        let editor = app(policy.clone(), Some(&["editor"]));
+        assert_eq!(
+            status(editor, Method::POST, "/api/pets").await,
+            StatusCode::OK
+        );
+
+        let anonymous = app(policy, None);
+        assert_eq!(
+            status(anonymous, Method::POST, "/api/pets").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_deny_blocks_unconfigured_route() {
+        let policy = AuthorizationPolicy::new(
+            &[rule(None, "/api/pets", &["reader", "admin"])],
+            DefaultPolicy::Deny,
+        );
+        let admin = app(policy, Some(&["admin"]));
+
+        assert_eq!(
+            status(admin.clone(), Method::GET, "/api/pets").await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(admin, Method::POST, "/api/admin/purge").await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn test_path_patterns() {
+        let policy = AuthorizationPolicy::new(
+            &[
+                rule(None, "/pets/{id}", &["reader"]),
+                rule(Some("delete"), "/admin/{*rest}", &["admin"]),
+                rule(None, "/health", &[]),
+            ],
+            DefaultPolicy::Deny,
+        );
+        let reader = claims(&["reader"]);
+
+        let decide = |method: Method, path: &str| policy.decide(&method, path, Some(&reader));
+        assert_eq!(decide(Method::GET, "/pets/42"), Decision::Allow);
+        assert_eq!(decide(Method::GET, "/pets/42/"), Decision::Allow);
+        assert!(matches!(decide(Method::GET, "/pets"), Decision::Deny(_)));
+        assert!(matches!(
+            decide(Method::GET, "/pets/42/toys"),
+            Decision::Deny(_)
+        ));
+        assert!(matches!(
+            decide(Method::DELETE, "/admin/users/7"),
+            Decision::Deny(_)
+        ));
+        // `{*rest}` only applies to the rule's method
+        assert!(matches!(
+            decide(Method::GET, "/admin/users"),
+            Decision::Deny(_)
+        ));
+        assert_eq!(
+            policy.decide(&Method::GET, "/health", None),
+            Decision::Allow
+        );
+    }
+
+    #[test]
+    fn test_health_probes_are_public_by_default() {
+        let policy = AuthorizationPolicy::new(&[], DefaultPolicy::Deny);
+        for path in PUBLIC_PATHS {
+            assert_eq!(policy.decide(&Method::GET, path, None), Decision::Allow);
+        }
+        assert!(matches!(
+            policy.decide(&Method::GET, "/health/details", None),
+            Decision::Deny(_)
+        ));
+
+        // Configured rules take precedence
+        let policy = AuthorizationPolicy::new(
+            &[rule(None, "/health/readiness", &["ops"])],
+            DefaultPolicy::Deny,
+        );
+        assert_eq!(
+            policy.decide(&Method::GET, "/health/readiness", None),
+            Decision::Unauthenticated
+        );
+    }
+}
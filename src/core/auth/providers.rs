@@ -1,5 +1,9 @@
+pub mod api_key;
 pub mod common;
 pub mod entra;
+pub mod jwt;
 
+pub use api_key::{ApiKey, ApiKeyProvider, ApiKeyStore, StaticApiKeyStore};
 pub use common::*;
 pub use entra::EntraProvider;
+pub use jwt::{JwtProvider, JwtProviderConfig};
@@ -0,0 +1,350 @@
+//! Static API key provider
+//!
+//! Some clients authenticate with a long-lived key sent in a header
+//! (`X-API-Key` by default) instead of a bearer token. Each key maps to a
+//! subject, roles and scopes, and may expire. Keys come from an
+//! [`ApiKeyStore`]: [`StaticApiKeyStore`] reads them from the provider's
+//! `provider_specific.keys` list, and a database-backed store can implement
+//! the same trait.
+//!
+//! ```yaml
+//! auth:
+//!   providers:
+//!     api_key:
+//!       enabled: true
+//!       client_id: ""
+//!       jwks_uri: ""
+//!       provider_specific:
+//!         header: "X-API-Key"
+//!         keys:
+//!           - key: "${REPORTING_API_KEY}"
+//!             subject: "reporting-service"
+//!             roles: ["read"]
+//!             scopes: ["pets:read"]
+//!             expires_at: "2027-01-01T00:00:00Z"
+//! ```
+//!
+//! Presented keys are compared with every configured key in constant time, so
+//! response timing doesn't reveal how much of a key was right.
+//!
+//! When the provider is enabled, the authentication middleware reads the key
+//! header on requests without an `Authorization` header and authenticates
+//! them here.
+
+use super::common::{CircuitState, HealthStatus, OAuthProvider, StandardClaims};
+use crate::config::app_config::AuthConfig;
+use crate::core::auth::error::AuthError;
+use crate::core::config::app_config;
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Header carrying the key unless `provider_specific.header` says otherwise
+pub const DEFAULT_API_KEY_HEADER: &str = "x-api-key";
+
+/// Issuer reported in the claims of key-authenticated requests
+pub const API_KEY_ISSUER: &str = "api_key";
+
+/// A key and the identity it authenticates
+#[derive(Clone, Deserialize)]
+pub struct ApiKey {
+    /// The secret key value
+    pub key: String,
+    /// Subject the key authenticates as
+    pub subject: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// When the key stops being accepted; never if unset
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    /// Whether the key has expired at `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The key itself never appears in debug output
+        f.debug_struct("ApiKey")
+            .field("subject", &self.subject)
+            .field("roles", &self.roles)
+            .field("scopes", &self.scopes)
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// Source of API keys
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync + fmt::Debug {
+    /// The key matching `presented`, if any; implementations must compare in constant time
+    async fn find(&self, presented: &str) -> Result<Option<ApiKey>, AuthError>;
+}
+
+/// Keys held in memory, typically loaded from configuration
+#[derive(Debug, Clone, Default)]
+pub struct StaticApiKeyStore {
+    keys: Vec<ApiKey>,
+}
+
+impl StaticApiKeyStore {
+    pub fn new(keys: Vec<ApiKey>) -> Self {
+        Self { keys }
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for StaticApiKeyStore {
+    async fn find(&self, presented: &str) -> Result<Option<ApiKey>, AuthError> {
+        // Check every key so the time taken doesn't depend on which one matched
+        let mut found = None;
+        for key in &self.keys {
+            if constant_time_eq(key.key.as_bytes(), presented.as_bytes()) {
+                found = Some(key);
+            }
+        }
+        Ok(found.cloned())
+    }
+}
+
+/// Compare two byte strings without exiting early on the first difference
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Provider authenticating requests by static API key
+#[derive(Debug, Clone)]
+pub struct ApiKeyProvider {
+    config: AuthConfig,
+    header: String,
+    store: Arc<dyn ApiKeyStore>,
+    created_at: SystemTime,
+}
+
+impl ApiKeyProvider {
+    /// Provider reading keys from `header` and looking them up in `store`
+    pub fn new(header: impl Into<String>, store: Arc<dyn ApiKeyStore>) -> Self {
+        let mut config = AuthConfig::default();
+        config.enabled = true;
+        config.default_provider = "api_key".to_string();
+
+        Self {
+            config,
+            header: header.into().to_ascii_lowercase(),
+            store,
+            created_at: SystemTime::now(),
+        }
+    }
+
+    /// Build the provider from `provider_specific.header` and `provider_specific.keys`
+    pub fn from_app_config(config: &app_config::ProviderConfig) -> Result<Self, AuthError> {
+        let header = config
+            .provider_specific
+            .get("header")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_API_KEY_HEADER);
+        let keys: Vec<ApiKey> = match config.provider_specific.get("keys") {
+            Some(keys) => serde_json::from_value(keys.clone()).map_err(|e| {
+                AuthError::ConfigurationError(format!("Invalid API key list: {}", e))
+            })?,
+            None => Vec::new(),
+        };
+        if keys.iter().any(|key| key.key.is_empty()) {
+            return Err(AuthError::ConfigurationError(
+                "API keys must not be empty".to_string(),
+            ));
+        }
+
+        Ok(Self::new(header, Arc::new(StaticApiKeyStore::new(keys))))
+    }
+
+    /// Header the key is read from, lowercased
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    /// The key presented in `headers`, if any
+    pub fn key_from_headers<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        headers
+            .get(self.header.as_str())
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+    }
+
+    /// The identity for `presented`, rejecting unknown and expired keys
+    pub async fn authenticate(&self, presented: &str) -> Result<ApiKey, AuthError> {
+        let key = self
+            .store
+            .find(presented)
+            .await?
+            .ok_or_else(|| AuthError::ValidationFailed("Unknown API key".to_string()))?;
+        if key.is_expired_at(Utc::now()) {
+            return Err(AuthError::ValidationFailed(format!(
+                "API key for {} has expired",
+                key.subject
+            )));
+        }
+        Ok(key)
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for ApiKeyProvider {
+    async fn validate_token(&self, token: &str) -> Result<StandardClaims, AuthError> {
+        let key = self.authenticate(token).await?;
+        let scope = (!key.scopes.is_empty()).then(|| key.scopes.join(" "));
+        Ok(StandardClaims {
+            sub: key.subject,
+            aud: String::new(),
+            exp: key
+                .expires_at
+                .map_or(0, |expires_at| expires_at.timestamp()),
+            iat: 0,
+            nbf: None,
+            iss: API_KEY_ISSUER.to_string(),
+            scope,
+        })
+    }
+
+    async fn refresh_jwks(&self) -> Result<(), AuthError> {
+        // No signing keys involved
+        Ok(())
+    }
+
+    fn config(&self) -> &AuthConfig {
+        &self.config
+    }
+
+    async fn get_roles(&self, token: &str) -> Result<Vec<String>, AuthError> {
+        Ok(self.authenticate(token).await?.roles)
+    }
+
+    fn name(&self) -> &str {
+        "api_key"
+    }
+
+    async fn health_check(&self) -> HealthStatus {
+        HealthStatus {
+            ready: true,
+            jwks_valid: true,
+            last_refresh: self.created_at,
+            error: None,
+            circuit_state: CircuitState::Closed,
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn OAuthProvider> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn provider() -> ApiKeyProvider {
+        let config = app_config::ProviderConfig {
+            enabled: true,
+            client_id: String::new(),
+            jwks_uri: String::new(),
+            issuer_url: String::new(),
+            audience: String::new(),
+            role_mappings: HashMap::new(),
+            provider_specific: HashMap::from([
+                ("header".to_string(), json!("X-Partner-Key")),
+                (
+                    "keys".to_string(),
+                    json!([
+                        {
+                            "key": "reporting-secret",
+                            "subject": "reporting-service",
+                            "roles": ["read"],
+                            "scopes": ["pets:read", "owners:read"],
+                            "expires_at": (Utc::now() + Duration::days(30)).to_rfc3339(),
+                        },
+                        {
+                            "key": "retired-secret",
+                            "subject": "legacy-importer",
+                            "roles": ["write"],
+                            "expires_at": (Utc::now() - Duration::days(1)).to_rfc3339(),
+                        },
+                    ]),
+                ),
+            ]),
+            allowed_algorithms: app_config::default_allowed_algorithms(),
+        };
+        ApiKeyProvider::from_app_config(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_valid_key_maps_to_identity() {
+        let provider = provider();
+
+        let claims = provider.validate_token("reporting-secret").await.unwrap();
+        assert_eq!(claims.sub, "reporting-service");
+        assert_eq!(claims.iss, API_KEY_ISSUER);
+        assert_eq!(claims.scope.as_deref(), Some("pets:read owners:read"));
+        assert!(claims.exp > Utc::now().timestamp());
+
+        let roles = provider.get_roles("reporting-secret").await.unwrap();
+        assert_eq!(roles, vec!["read".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_and_expired_keys_are_rejected() {
+        let provider = provider();
+
+        let unknown = provider.validate_token("reporting-secreT").await;
+        assert!(matches!(unknown, Err(AuthError::ValidationFailed(_))));
+
+        let expired = provider.validate_token("retired-secret").await;
+        assert!(
+            matches!(expired, Err(AuthError::ValidationFailed(ref reason)) if reason.contains("expired"))
+        );
+    }
+
+    #[test]
+    fn test_key_is_read_from_configured_header() {
+        let provider = provider();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-partner-key", "reporting-secret".parse().unwrap());
+
+        assert_eq!(provider.header(), "x-partner-key");
+        assert_eq!(
+            provider.key_from_headers(&headers),
+            Some("reporting-secret")
+        );
+        assert_eq!(provider.key_from_headers(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret-longer"));
+    }
+
+    #[test]
+    fn test_debug_output_omits_key() {
+        let provider = provider();
+        let debug = format!("{:?}", provider);
+        assert!(!debug.contains("reporting-secret"));
+    }
+}
@@ -1,16 +1,30 @@
 use crate::config::app_config::AuthConfig;
 use crate::core::auth::AuthError;
+use crate::core::auth::providers::api_key::ApiKeyProvider;
 use crate::core::auth::providers::entra::EntraProvider;
+use crate::core::auth::providers::jwt::JwtProvider;
 use crate::core::config::AppConfig;
+use crate::core::config::app_config;
+use crate::core::utils::http_client::shared_client;
 use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use chrono::{DateTime, Utc};
-use governor::{Quota, RateLimiter, clock::DefaultClock, state::InMemoryState, state::NotKeyed};
+use governor::{
+    Quota, RateLimiter,
+    clock::{Clock, DefaultClock},
+    state::InMemoryState,
+    state::NotKeyed,
+};
+use jsonwebtoken::{Algorithm, Header, decode_header};
 use metrics::{counter, gauge, histogram};
 use nonzero_ext::nonzero;
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::num::NonZeroU32;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::watch;
@@ -23,6 +37,8 @@ pub struct StandardClaims {
     pub aud: String,
     pub exp: i64,
     pub iat: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
     pub iss: String,
     pub scope: Option<String>,
 }
@@ -61,10 +77,30 @@ pub struct HealthStatus {
     pub circuit_state: CircuitState,
 }
 
+/// Result of refreshing one provider's JWKS on request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JwksRefreshOutcome {
+    pub refreshed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Minimum time between manual JWKS refreshes
+const MANUAL_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+type ManualRefreshLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
+fn manual_refresh_limiter(interval: Duration) -> Arc<ManualRefreshLimiter> {
+    let quota = Quota::with_period(interval)
+        .unwrap_or_else(|| Quota::with_period(MANUAL_REFRESH_INTERVAL).unwrap());
+    Arc::new(RateLimiter::direct(quota))
+}
+
 /// Provider registry implementation
 pub struct ProviderRegistry {
     providers: HashMap<String, Arc<dyn OAuthProvider>>,
     pub default_provider: String,
+    manual_refresh: Arc<ManualRefreshLimiter>,
 }
 
 impl ProviderRegistry {
@@ -74,7 +110,55 @@ impl ProviderRegistry {
         Self {
             providers,
             default_provider: config.default_provider,
+            manual_refresh: manual_refresh_limiter(MANUAL_REFRESH_INTERVAL),
+        }
+    }
+
+    /// Add or replace the provider registered as `name`
+    pub fn register(&mut self, name: impl Into<String>, provider: Arc<dyn OAuthProvider>) {
+        self.providers.insert(name.into(), provider);
+    }
+
+    /// Allow [`refresh_all`](Self::refresh_all) at most once per `interval`
+    pub fn with_manual_refresh_interval(mut self, interval: Duration) -> Self {
+        self.manual_refresh = manual_refresh_limiter(interval);
+        self
+    }
+
+    /// Refresh every provider's JWKS now, e.g. after rotating keys at the IdP
+    ///
+    /// Fails with [`AuthError::RateLimited`] when called again within the
+    /// manual refresh interval (30 seconds by default).
+    pub async fn refresh_all(&self) -> Result<BTreeMap<String, JwksRefreshOutcome>, AuthError> {
+        if let Err(not_until) = self.manual_refresh.check() {
+            let wait = not_until.wait_time_from(DefaultClock::default().now());
+            return Err(AuthError::RateLimited(format!(
+                "JWKS was refreshed recently, retry in {}s",
+                wait.as_secs().max(1)
+            )));
+        }
+
+        let mut outcomes = BTreeMap::new();
+        for (name, provider) in &self.providers {
+            let outcome = match provider.refresh_jwks().await {
+                Ok(()) => {
+                    info!("Refreshed JWKS for {}", name);
+                    JwksRefreshOutcome {
+                        refreshed: true,
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to refresh JWKS for {}: {}", name, e);
+                    JwksRefreshOutcome {
+                        refreshed: false,
+                        error: Some(e.to_string()),
+                    }
+                }
+            };
+            outcomes.insert(name.clone(), outcome);
         }
+        Ok(outcomes)
     }
 
     pub fn get_provider(&self, name: &str) -> Option<&dyn OAuthProvider> {
@@ -91,6 +175,11 @@ impl ProviderRegistry {
     }
 
     pub fn initialize(config: AuthConfig) -> Result<Self, AuthError> {
+        Self::initialize_with_client(config, shared_client())
+    }
+
+    /// Initialize the providers, fetching JWKS through `client`
+    pub fn initialize_with_client(config: AuthConfig, client: Client) -> Result<Self, AuthError> {
         let mut providers: HashMap<String, Arc<dyn OAuthProvider>> = HashMap::new();
 
         for (name, provider_config) in &config.providers {
@@ -99,9 +188,18 @@ impl ProviderRegistry {
                 match name.as_str() {
                     "entra" => {
                         let common_config = ProviderConfig::from_app_config(provider_config);
-                        let entra_provider = EntraProvider::new(common_config)?;
+                        let entra_provider =
+                            EntraProvider::new(common_config)?.with_http_client(client.clone());
                         providers.insert(name.clone(), Arc::new(entra_provider));
                     }
+                    "jwt" => {
+                        let jwt_provider = JwtProvider::from_app_config(provider_config)?;
+                        providers.insert(name.clone(), Arc::new(jwt_provider));
+                    }
+                    "api_key" => {
+                        let api_key_provider = ApiKeyProvider::from_app_config(provider_config)?;
+                        providers.insert(name.clone(), Arc::new(api_key_provider));
+                    }
                     // Add other providers here
                     _ => {
                         return Err(AuthError::ConfigurationError(format!(
@@ -116,11 +214,21 @@ impl ProviderRegistry {
         Ok(Self {
             providers,
             default_provider: config.default_provider,
+            manual_refresh: manual_refresh_limiter(MANUAL_REFRESH_INTERVAL),
         })
     }
 
     pub fn from_app_config(config: &AppConfig) -> Result<Self, AuthError> {
-        let registry = Self::initialize(config.auth.clone())?;
+        Self::from_app_config_with_client(config, shared_client())
+    }
+
+    /// [`from_app_config`](Self::from_app_config), fetching JWKS through `client`,
+    /// e.g. `AppState::http_client`
+    pub fn from_app_config_with_client(
+        config: &AppConfig,
+        client: Client,
+    ) -> Result<Self, AuthError> {
+        let registry = Self::initialize_with_client(config.auth.clone(), client)?;
 
         if config.auth.debug {
             debug!(
@@ -318,6 +426,8 @@ pub struct ProviderConfig {
     pub role_mappings: HashMap<String, Vec<String>>,
     #[serde(default)]
     pub provider_specific: HashMap<String, Value>,
+    #[serde(default = "app_config::default_allowed_algorithms")]
+    pub allowed_algorithms: Vec<String>,
     // Additional fields specific to common::ProviderConfig
     #[serde(default = "default_refresh_rate")]
     pub refresh_rate_limit: RateLimitConfig,
@@ -356,12 +466,66 @@ impl ProviderConfig {
             issuer,
             role_mappings: config.role_mappings.clone(),
             provider_specific: config.provider_specific.clone(),
+            allowed_algorithms: config.allowed_algorithms.clone(),
             refresh_rate_limit: default_refresh_rate(),
             tenant_id,
         }
     }
 }
 
+/// Parse configured algorithm names, refusing `none`
+pub fn parse_algorithms(names: &[String]) -> Result<Vec<Algorithm>, AuthError> {
+    names
+        .iter()
+        .map(|name| {
+            if name.eq_ignore_ascii_case("none") {
+                return Err(AuthError::ConfigurationError(
+                    "JWT algorithm 'none' cannot be allowed".to_string(),
+                ));
+            }
+            Algorithm::from_str(name).map_err(|_| {
+                AuthError::ConfigurationError(format!("Unsupported JWT algorithm: {}", name))
+            })
+        })
+        .collect()
+}
+
+/// The asymmetric algorithms accepted by default
+pub fn default_algorithms() -> Vec<Algorithm> {
+    parse_algorithms(&app_config::default_allowed_algorithms()).unwrap_or_default()
+}
+
+/// Decode the token header, rejecting `alg: none` and algorithms not in `allowed`
+///
+/// Run this before choosing a key, so a token can't pick how it is verified.
+pub fn check_algorithm(token: &str, allowed: &[Algorithm]) -> Result<Header, AuthError> {
+    // jsonwebtoken can't represent `none`; read it from the raw header for a clear error
+    let raw_alg = token
+        .split('.')
+        .next()
+        .and_then(|header| URL_SAFE_NO_PAD.decode(header).ok())
+        .and_then(|header| serde_json::from_slice::<Value>(&header).ok())
+        .and_then(|header| header.get("alg").and_then(Value::as_str).map(String::from));
+    if raw_alg.is_some_and(|alg| alg.eq_ignore_ascii_case("none")) {
+        counter!("auth_rejected_algorithm_total", "alg" => "none").increment(1);
+        return Err(AuthError::ValidationFailed(
+            "Unsigned tokens (alg 'none') are not accepted".to_string(),
+        ));
+    }
+
+    let header = decode_header(token)
+        .map_err(|e| AuthError::ValidationFailed(format!("Failed to decode JWT header: {}", e)))?;
+    if !allowed.contains(&header.alg) {
+        counter!("auth_rejected_algorithm_total", "alg" => format!("{:?}", header.alg))
+            .increment(1);
+        return Err(AuthError::ValidationFailed(format!(
+            "Token algorithm {:?} is not allowed",
+            header.alg
+        )));
+    }
+    Ok(header)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitConfig {
     pub max_requests: u32,
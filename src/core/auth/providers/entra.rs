@@ -6,9 +6,10 @@ use super::common::{
 };
 use crate::config::app_config::AuthConfig;
 use crate::core::auth::error::AuthError;
+use crate::core::utils::http_client::shared_client;
 use async_trait::async_trait;
 use chrono::Utc;
-use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode_header};
+use jsonwebtoken::{DecodingKey, Validation};
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::Value;
@@ -164,6 +165,7 @@ impl EntraProvider {
             audience: config.audience.clone(),
             role_mappings: config.role_mappings.clone(),
             provider_specific: config.provider_specific.clone(),
+            allowed_algorithms: config.allowed_algorithms.clone(),
         };
 
         // Set up auth config
@@ -200,13 +202,19 @@ impl EntraProvider {
         Ok(Self {
             config: auth_config,
             jwks_cache: Arc::new(RwLock::new(None)),
-            http_client: Client::new(),
+            http_client: shared_client(),
             entra_specific,
             refresh_limiter,
             circuit_breaker,
         })
     }
 
+    /// Fetch JWKS through `client`, e.g. `AppState::http_client`
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.http_client = client;
+        self
+    }
+
     pub fn from_config(config: &common::ProviderConfig) -> Result<Self, AuthError> {
         let mut entra_specific = HashMap::new();
 
@@ -237,6 +245,7 @@ impl EntraProvider {
             audience: config.audience.clone(),
             role_mappings: config.role_mappings.clone(),
             provider_specific: config.provider_specific.clone(),
+            allowed_algorithms: config.allowed_algorithms.clone(),
         };
 
         // Set up auth config
@@ -249,7 +258,7 @@ impl EntraProvider {
         Ok(Self {
             config: auth_config,
             jwks_cache: Arc::new(RwLock::new(None)),
-            http_client: Client::new(),
+            http_client: shared_client(),
             entra_specific,
             refresh_limiter: RefreshLimiter::new(
                 config.refresh_rate_limit.max_requests,
@@ -262,6 +271,13 @@ impl EntraProvider {
     }
 
     async fn validate_token_internal(&self, token: &str) -> Result<StandardClaims, AuthError> {
+        // Reject disallowed algorithms before touching the JWKS
+        let allowed = match self.config.providers.get("entra") {
+            Some(provider) => common::parse_algorithms(&provider.allowed_algorithms)?,
+            None => common::default_algorithms(),
+        };
+        let header = common::check_algorithm(token, &allowed)?;
+
         // Implementation for token validation
         // Check if we need to refresh JWKS
         let refresh_needed = {
@@ -275,11 +291,7 @@ impl EntraProvider {
             self.refresh_jwks().await?;
         }
 
-        // Get header from token to determine which key to use
-        let header = decode_header(token).map_err(|e| {
-            AuthError::ValidationFailed(format!("Failed to decode JWT header: {}", e))
-        })?;
-
+        // Use the header's key ID to determine which key to use
         let kid = header.kid.ok_or_else(|| {
             AuthError::ValidationFailed("Token header missing 'kid' field".to_string())
         })?;
@@ -349,7 +361,7 @@ impl EntraProvider {
             .unwrap_or_else(|| format!("https://sts.windows.net/{}/", tenant_id));
 
         // Setup validation
-        let mut validation = Validation::new(Algorithm::RS256);
+        let mut validation = Validation::new(header.alg);
         validation.set_audience(&[&audience]);
         validation.set_issuer(&[&issuer]);
 
@@ -376,6 +388,7 @@ impl EntraProvider {
             aud: audience,
             exp: Utc::now().timestamp() + 3600,
             iat: Utc::now().timestamp(),
+            nbf: None,
             iss: issuer,
             scope: Some("read write".to_string()),
         })
@@ -4,9 +4,13 @@
 //! - Cache registry for tracking cached resources
 //! - Statistics collection and reporting
 //! - Cache eviction policies
+//! - HTTP response caching for `GET` endpoints
+//! - Warming hot entries at startup
 
 pub mod cache_manager;
+pub mod http_cache;
 pub mod registry_stats;
+pub mod warming;
 
 // Re-export main types and functions from cache_manager
 pub use cache_manager::{
@@ -15,5 +19,9 @@ pub use cache_manager::{
     start_metrics_updater,
 };
 
+pub use http_cache::{CachedResponse, HttpResponseCache, http_cache};
+
 // Re-export from registry_stats
 pub use registry_stats::get_all_cache_stats_with_metrics;
+
+pub use warming::{CacheWarmer, WarmReport};
@@ -3,19 +3,20 @@ use moka::future::Cache;
 use serde::{Deserialize, Serialize};
 use std::any::{Any, type_name};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::{
-    Arc, RwLock,
-    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, RwLock,
+    atomic::{AtomicBool, AtomicU64, Ordering},
 };
 use std::thread_local;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::time::interval;
 use tracing::{debug, info, warn};
 
 // Import ApiResource trait
 use crate::core::error::AppError;
+use crate::core::services::cache_provider::CacheError;
 use crate::core::utils::api_resource::ApiResource;
 
 /// Generic cache for any resource type that implements ApiResource
@@ -26,6 +27,14 @@ pub struct ResourceCache<T: ApiResource> {
     pub ttl_seconds: u64,
     pub active_entries: Arc<AtomicU64>,
     pub resource_type: String,
+    /// How long past its TTL an entry may still be served while it refreshes
+    pub stale_ttl: Duration,
+    /// TTL before an entry counts as stale
+    fresh_for: Duration,
+    /// When each entry was last fetched, for telling fresh from stale
+    fetched_at: Arc<Cache<String, Instant>>,
+    /// Keys with a background refresh in flight
+    refreshing: Arc<Mutex<HashSet<String>>>,
 }
 
 /// Cache registry to store caches for different resource types
@@ -37,6 +46,8 @@ pub struct CacheRegistry {
     pub ttl_seconds: u64,
     pub max_capacity: u64,
     pub creation_time: SystemTime,
+    /// Default stale-while-revalidate window; zero disables stale serving
+    pub stale_ttl: Duration,
 }
 
 /// Cache statistics for a resource type
@@ -65,6 +76,7 @@ pub fn init_cache_registry(enabled: bool, max_capacity: u64, ttl_seconds: u64) -
         ttl_seconds,
         max_capacity,
         creation_time: SystemTime::now(),
+        stale_ttl: Duration::ZERO,
     }
 }
 
@@ -72,7 +84,7 @@ pub fn init_cache_registry(enabled: bool, max_capacity: u64, ttl_seconds: u64) -
 pub fn register_resource_cache<T: ApiResource + 'static>(
     registry: &CacheRegistry,
     resource_type: &str,
-) -> Result<(), String> {
+) -> Result<(), CacheError> {
     if !registry.enabled {
         debug!(
             "Cache is disabled, not registering cache for {}",
@@ -81,7 +93,11 @@ pub fn register_resource_cache<T: ApiResource + 'static>(
         return Ok(());
     }
 
-    let ttl = Duration::from_secs(registry.ttl_seconds);
+    // Resources may outlive or expire sooner than the registry default
+    let ttl = T::cache_ttl().unwrap_or(Duration::from_secs(registry.ttl_seconds));
+    let stale_ttl = T::stale_ttl().unwrap_or(registry.stale_ttl);
+    // Stale entries stay in the cache until they can no longer be served
+    let lifetime = ttl + stale_ttl;
     let resource_type_clone = resource_type.to_string();
 
     // Create a ResourceCache that we'll box and store
@@ -92,8 +108,8 @@ pub fn register_resource_cache<T: ApiResource + 'static>(
     // Create the cache with eviction listener
     let cache_builder = Cache::builder()
         .max_capacity(registry.max_capacity)
-        .time_to_live(ttl)
-        .time_to_idle(ttl.mul_f32(1.5))
+        .time_to_live(lifetime)
+        .time_to_idle(lifetime.mul_f32(1.5))
         .initial_capacity(100)
         .eviction_listener(move |_key, _value, cause| {
             // Track cache evictions in metrics and update counter
@@ -166,15 +182,29 @@ pub fn register_resource_cache<T: ApiResource + 'static>(
     let resource_cache: ResourceCache<T> = ResourceCache {
         cache: Arc::new(cache_builder),
         creation_time: SystemTime::now(),
-        ttl_seconds: registry.ttl_seconds,
+        // Round up so a sub-second TTL isn't reported as 0 (no expiry)
+        ttl_seconds: ttl.as_millis().div_ceil(1000) as u64,
         active_entries,
         resource_type: resource_type.to_string(),
+        stale_ttl,
+        fresh_for: ttl,
+        fetched_at: Arc::new(
+            Cache::builder()
+                .max_capacity(registry.max_capacity)
+                .time_to_live(lifetime)
+                .build(),
+        ),
+        refreshing: Default::default(),
     };
 
     // Attempt to insert the cache into the registry
     let mut caches = match registry.caches.write() {
         Ok(caches) => caches,
-        Err(_) => return Err("Failed to acquire write lock on cache registry".to_string()),
+        Err(_) => {
+            return Err(CacheError::Operation(
+                "Failed to acquire write lock on cache registry".to_string(),
+            ));
+        }
     };
 
     caches.insert(
@@ -214,6 +244,10 @@ pub fn get_resource_cache<T: ApiResource + 'static>(
                     ttl_seconds: boxed_cache.ttl_seconds,
                     active_entries: boxed_cache.active_entries.clone(),
                     resource_type: boxed_cache.resource_type.clone(),
+                    stale_ttl: boxed_cache.stale_ttl,
+                    fresh_for: boxed_cache.fresh_for,
+                    fetched_at: boxed_cache.fetched_at.clone(),
+                    refreshing: boxed_cache.refreshing.clone(),
                 })
             } else {
                 debug!(
@@ -262,6 +296,11 @@ pub fn last_fetch_from_cache() -> bool {
 }
 
 /// Generic function to get or fetch a resource from cache
+///
+/// With a stale TTL (see [`ApiResource::stale_ttl`]), an entry past its TTL
+/// but within the stale window is returned immediately while `fetch_fn`
+/// refreshes it in the background; beyond the window the caller waits for a
+/// fresh fetch as on any miss.
 pub async fn get_or_fetch<T, F, Fut>(
     registry: &CacheRegistry,
     resource_type: &str,
@@ -270,8 +309,8 @@ pub async fn get_or_fetch<T, F, Fut>(
 ) -> Result<T, String>
 where
     T: ApiResource + 'static,
-    F: FnOnce() -> Fut,
-    Fut: std::future::Future<Output = Result<T, String>>,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Result<T, String>> + Send + 'static,
 {
     // Reset the thread-local at the start of each fetch operation
     LAST_FETCH_FROM_CACHE.with(|cell| {
@@ -303,6 +342,17 @@ where
 
     // Try to get from cache first
     if let Some(resource) = cache.get(id).await {
+        if !resource_cache.is_fresh(id).await {
+            counter!("cache_stale_hits_total", "resource_type" => resource_type.to_string())
+                .increment(1);
+            debug!("⏳ Serving stale {} ID: {}", resource_type, id);
+            LAST_FETCH_FROM_CACHE.with(|cell| {
+                *cell.borrow_mut() = true;
+            });
+            resource_cache.refresh_in_background(id, fetch_fn);
+            return Ok(resource);
+        }
+
         counter!("cache_hits_total", "resource_type" => resource_type.to_string()).increment(1);
         debug!("🔍 Cache hit for {} ID: {}", resource_type, id);
 
@@ -335,12 +385,32 @@ where
         resource_type, id
     );
 
-    // Fetch the resource
-    match fetch_fn().await {
+    // Fetch the resource. Concurrent misses for the same key are coalesced by
+    // the cache: only one fetch runs and the other callers await its result.
+    let fetched = AtomicBool::new(false);
+    let result = cache
+        .try_get_with(id.to_string(), async {
+            fetched.store(true, Ordering::SeqCst);
+            let resource = fetch_fn().await?;
+            resource_cache.mark_fetched(id).await;
+            Ok(resource)
+        })
+        .await
+        .map_err(|e: Arc<String>| e.as_ref().clone());
+
+    match result {
+        Ok(resource) if !fetched.load(Ordering::SeqCst) => {
+            // Another caller's in-flight fetch produced this value
+            counter!("cache_coalesced_total", "resource_type" => resource_type.to_string())
+                .increment(1);
+            debug!(
+                "🔗 Coalesced with in-flight fetch for {} ID: {}",
+                resource_type, id
+            );
+            Ok(resource)
+        }
         Ok(resource) => {
-            // Store in cache
-            debug!("➕ About to add {} ID: {} to cache", resource_type, id);
-            cache.insert(id.to_string(), resource.clone()).await;
+            debug!("➕ Added {} ID: {} to cache", resource_type, id);
 
             // Increment our counters
             counter!("cache_entries_created", "resource_type" => resource_type.to_string())
@@ -354,11 +424,6 @@ where
             gauge!("cache_active_entries", "resource_type" => resource_type.to_string())
                 .set(new_count as f64);
 
-            debug!(
-                "📊 Cache entry count right after insertion: {} (active: {})",
-                current_size, new_count
-            );
-
             debug!(
                 "➕ Added {} ID: {} to cache (current size: {}, active: {})",
                 resource_type, id, current_size, new_count
@@ -450,9 +515,19 @@ impl CacheRegistry {
             ttl_seconds: 300,
             max_capacity: 1000,
             creation_time: SystemTime::now(),
+            stale_ttl: Duration::ZERO,
         }
     }
 
+    /// Serve entries up to `stale_ttl` past their TTL while they refresh
+    ///
+    /// Applies to resources registered afterwards that don't set
+    /// [`ApiResource::stale_ttl`] themselves.
+    pub fn with_stale_ttl(mut self, stale_ttl: Duration) -> Self {
+        self.stale_ttl = stale_ttl;
+        self
+    }
+
     /// Count the total number of cache entries across all resource caches
     pub fn count_entries(&self) -> usize {
         if !self.enabled {
@@ -486,8 +561,8 @@ impl CacheRegistry {
     pub async fn get_or_fetch<T, F, Fut>(&self, cache_key: String, fetch_fn: F) -> Result<T, String>
     where
         T: ApiResource + 'static,
-        F: FnOnce() -> Fut,
-        Fut: std::future::Future<Output = Result<T, String>>,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<T, String>> + Send + 'static,
     {
         if !self.enabled {
             return fetch_fn().await;
@@ -502,20 +577,21 @@ impl CacheRegistry {
         &self,
         cache_key: String,
         resource: T,
-    ) -> Result<(), String> {
+    ) -> Result<(), CacheError> {
         if !self.enabled {
             return Ok(());
         }
 
         let resource_type = T::resource_type();
         if let Some(cache) = get_resource_cache::<T>(self, resource_type) {
+            cache.mark_fetched(&cache_key).await;
             cache.cache.insert(cache_key, resource).await;
             Ok(())
         } else {
-            Err(format!(
+            Err(CacheError::NotFound(format!(
                 "No cache found for resource type: {}",
                 resource_type
-            ))
+            )))
         }
     }
 
@@ -663,7 +739,72 @@ impl<T: ApiResource> ResourceCache<T> {
             ttl_seconds,
             active_entries: Arc::new(AtomicU64::new(0)),
             resource_type,
+            stale_ttl: Duration::ZERO,
+            fresh_for: Duration::from_secs(ttl_seconds),
+            fetched_at: Arc::new(Cache::new(10_000)),
+            refreshing: Default::default(),
+        }
+    }
+
+    /// Whether `key` is within its TTL; always true without a stale window
+    ///
+    /// Entries inserted without going through the cache's fetch paths have
+    /// no fetch time and count as fresh.
+    async fn is_fresh(&self, key: &str) -> bool {
+        if self.stale_ttl.is_zero() {
+            return true;
         }
+        self.fetched_at
+            .get(key)
+            .await
+            .is_none_or(|fetched_at| fetched_at.elapsed() < self.fresh_for)
+    }
+
+    /// Record that `key` was just fetched from the source
+    async fn mark_fetched(&self, key: &str) {
+        if !self.stale_ttl.is_zero() {
+            self.fetched_at
+                .insert(key.to_string(), Instant::now())
+                .await;
+        }
+    }
+
+    /// Refresh `key` with `fetch_fn` unless a refresh is already running
+    ///
+    /// A failed refresh keeps the stale entry, which is retried on the
+    /// next read until it expires.
+    fn refresh_in_background<F, Fut>(&self, key: &str, fetch_fn: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<T, String>> + Send + 'static,
+    {
+        if !self.refreshing.lock().unwrap().insert(key.to_string()) {
+            return;
+        }
+
+        let cache = self.cache.clone();
+        let fetched_at = self.fetched_at.clone();
+        let refreshing = self.refreshing.clone();
+        let resource_type = self.resource_type.clone();
+        let key = key.to_string();
+        tokio::spawn(async move {
+            match fetch_fn().await {
+                Ok(resource) => {
+                    fetched_at.insert(key.clone(), Instant::now()).await;
+                    cache.insert(key.clone(), resource).await;
+                    counter!("cache_background_refreshes_total", "resource_type" => resource_type.clone())
+                        .increment(1);
+                    debug!("🔄 Refreshed stale {} ID: {}", resource_type, key);
+                }
+                Err(e) => {
+                    warn!(
+                        "Background refresh of {} ID {} failed, keeping stale entry: {}",
+                        resource_type, key, e
+                    );
+                }
+            }
+            refreshing.lock().unwrap().remove(&key);
+        });
     }
 
     /// Get cache statistics
@@ -720,6 +861,7 @@ impl<T: ApiResource> ResourceCache<T> {
         }
 
         // Insert the value with automatic conversion of key to String
+        self.mark_fetched(key).await;
         self.cache.insert(key.to_string(), value).await;
 
         debug!("📥 Added entry to cache: {}/{}", self.resource_type, key);
@@ -940,6 +1082,75 @@ mod tests {
         assert_eq!(cache.ttl_seconds, 3600);
     }
 
+    // Volatile resource with a TTL shorter than the registry's
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct VolatileResource {
+        id: String,
+    }
+
+    impl ApiResource for VolatileResource {
+        type Id = String;
+
+        fn resource_type() -> &'static str {
+            "volatile_resource"
+        }
+
+        fn api_name() -> &'static str {
+            "TestService"
+        }
+
+        fn cache_ttl() -> Option<Duration> {
+            Some(Duration::from_millis(200))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resources_expire_on_their_own_ttl() {
+        let registry = init_cache_registry(true, 100, 3600);
+        register_resource_cache::<TestResource>(&registry, "test_resource").unwrap();
+        register_resource_cache::<VolatileResource>(&registry, "volatile_resource").unwrap();
+
+        let stable = get_resource_cache::<TestResource>(&registry, "test_resource").unwrap();
+        let volatile =
+            get_resource_cache::<VolatileResource>(&registry, "volatile_resource").unwrap();
+        assert_eq!(stable.ttl_seconds, 3600);
+        assert_eq!(volatile.ttl_seconds, 1);
+
+        let resource = TestResource {
+            id: "1".to_string(),
+            name: "Stable".to_string(),
+            value: 1,
+        };
+        stable.cache.insert("1".to_string(), resource).await;
+        volatile
+            .cache
+            .insert(
+                "1".to_string(),
+                VolatileResource {
+                    id: "1".to_string(),
+                },
+            )
+            .await;
+
+        sleep(Duration::from_millis(400)).await;
+
+        assert!(volatile.cache.get("1").await.is_none());
+        assert!(stable.cache.get("1").await.is_some());
+
+        // The next fetch of the expired resource goes back to the source
+        let fetched = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = fetched.clone();
+        get_or_fetch(&registry, "volatile_resource", "1", move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(VolatileResource {
+                id: "1".to_string(),
+            })
+        })
+        .await
+        .unwrap();
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+    }
+
     async fn helper_set_and_get_in_cache(registry: &CacheRegistry, key: &str, value: TestResource) {
         // Get resource cache
         let cache_opt = get_resource_cache::<TestResource>(registry, "test_resource");
@@ -973,6 +1184,214 @@ mod tests {
         helper_set_and_get_in_cache(&registry, "test-1", resource).await;
     }
 
+    #[tokio::test]
+    async fn test_get_or_fetch_coalesces_concurrent_misses() {
+        let registry = init_cache_registry(true, 100, 3600);
+        let _ = register_resource_cache::<TestResource>(&registry, "test_resource");
+
+        let fetch_calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let requests = (0..16).map(|_| {
+            let fetch_calls = fetch_calls.clone();
+            get_or_fetch(&registry, "test_resource", "cold-key", move || async move {
+                fetch_calls.fetch_add(1, Ordering::SeqCst);
+                sleep(Duration::from_millis(50)).await;
+                Ok(TestResource {
+                    id: "cold-key".to_string(),
+                    name: "Cold".to_string(),
+                    value: 7,
+                })
+            })
+        });
+
+        let results = futures::future::join_all(requests).await;
+
+        assert_eq!(fetch_calls.load(Ordering::SeqCst), 1);
+        assert!(
+            results
+                .iter()
+                .all(|result| result.as_ref().is_ok_and(|resource| resource.value == 7))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_or_fetch_shares_fetch_error() {
+        let registry = init_cache_registry(true, 100, 3600);
+        let _ = register_resource_cache::<TestResource>(&registry, "test_resource");
+
+        let fetch_calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let requests = (0..4).map(|_| {
+            let fetch_calls = fetch_calls.clone();
+            get_or_fetch::<TestResource, _, _>(
+                &registry,
+                "test_resource",
+                "broken",
+                move || async move {
+                    fetch_calls.fetch_add(1, Ordering::SeqCst);
+                    sleep(Duration::from_millis(50)).await;
+                    Err("backend down".to_string())
+                },
+            )
+        });
+
+        let results = futures::future::join_all(requests).await;
+
+        assert_eq!(fetch_calls.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(|result| result.is_err()));
+
+        // Errors are not cached, so the next call fetches again
+        let result = get_or_fetch(&registry, "test_resource", "broken", || async {
+            Ok(TestResource {
+                id: "broken".to_string(),
+                name: "Recovered".to_string(),
+                value: 1,
+            })
+        })
+        .await;
+        assert!(result.is_ok());
+    }
+
+    // Resource served up to 300ms stale after a 150ms TTL
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct StaleResource {
+        version: u32,
+    }
+
+    impl ApiResource for StaleResource {
+        type Id = String;
+
+        fn resource_type() -> &'static str {
+            "stale_resource"
+        }
+
+        fn api_name() -> &'static str {
+            "TestService"
+        }
+
+        fn cache_ttl() -> Option<Duration> {
+            Some(Duration::from_millis(150))
+        }
+
+        fn stale_ttl() -> Option<Duration> {
+            Some(Duration::from_millis(300))
+        }
+    }
+
+    /// Fetch `version` after 100ms, counting calls in `fetches`
+    fn slow_fetch(
+        fetches: &Arc<std::sync::atomic::AtomicUsize>,
+        version: u32,
+    ) -> impl FnOnce() -> futures::future::BoxFuture<'static, Result<StaleResource, String>>
+    + Send
+    + 'static {
+        let fetches = fetches.clone();
+        move || {
+            Box::pin(async move {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                sleep(Duration::from_millis(100)).await;
+                Ok(StaleResource { version })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stale_entry_served_while_refreshing() {
+        let registry = init_cache_registry(true, 100, 3600);
+        register_resource_cache::<StaleResource>(&registry, "stale_resource").unwrap();
+        let fetches = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        get_or_fetch(&registry, "stale_resource", "1", slow_fetch(&fetches, 1))
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(200)).await;
+
+        // Past the TTL but within the stale window: no waiting on the source
+        let started = std::time::Instant::now();
+        let stale = get_or_fetch(&registry, "stale_resource", "1", slow_fetch(&fetches, 2))
+            .await
+            .unwrap();
+        let again = get_or_fetch(&registry, "stale_resource", "1", slow_fetch(&fetches, 3))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_millis(50));
+        assert_eq!(stale, StaleResource { version: 1 });
+        assert_eq!(again, StaleResource { version: 1 });
+
+        // One background refresh replaced the entry
+        sleep(Duration::from_millis(150)).await;
+        let refreshed = get_or_fetch(&registry, "stale_resource", "1", slow_fetch(&fetches, 4))
+            .await
+            .unwrap();
+        assert_eq!(refreshed, StaleResource { version: 2 });
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_entry_beyond_stale_ttl_is_fetched() {
+        let registry = init_cache_registry(true, 100, 3600);
+        register_resource_cache::<StaleResource>(&registry, "stale_resource").unwrap();
+        let fetches = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        get_or_fetch(&registry, "stale_resource", "1", slow_fetch(&fetches, 1))
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(500)).await;
+
+        let started = std::time::Instant::now();
+        let fresh = get_or_fetch(&registry, "stale_resource", "1", slow_fetch(&fetches, 2))
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(fresh, StaleResource { version: 2 });
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    // Resource relying on the registry's stale window
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct RegistryStaleResource {
+        version: u32,
+    }
+
+    impl ApiResource for RegistryStaleResource {
+        type Id = String;
+
+        fn resource_type() -> &'static str {
+            "registry_stale_resource"
+        }
+
+        fn api_name() -> &'static str {
+            "TestService"
+        }
+
+        fn cache_ttl() -> Option<Duration> {
+            Some(Duration::from_millis(150))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_stale_ttl_keeps_sub_second_window() {
+        let registry =
+            init_cache_registry(true, 100, 3600).with_stale_ttl(Duration::from_millis(300));
+        register_resource_cache::<RegistryStaleResource>(&registry, "registry_stale_resource")
+            .unwrap();
+        assert_eq!(registry.stale_ttl, Duration::from_millis(300));
+
+        get_or_fetch(&registry, "registry_stale_resource", "1", || async {
+            Ok(RegistryStaleResource { version: 1 })
+        })
+        .await
+        .unwrap();
+        sleep(Duration::from_millis(200)).await;
+
+        // Past the TTL but within the 300ms window, the stale entry is served
+        let stale = get_or_fetch(&registry, "registry_stale_resource", "1", || async {
+            Ok(RegistryStaleResource { version: 2 })
+        })
+        .await
+        .unwrap();
+        assert_eq!(stale, RegistryStaleResource { version: 1 });
+    }
+
     #[tokio::test]
     async fn test_get_or_fetch() {
         let registry = init_cache_registry(true, 100, 3600);
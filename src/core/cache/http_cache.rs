@@ -0,0 +1,825 @@
+//! HTTP response caching
+//!
+//! [`http_cache`] stores whole `GET` responses in a cache backend and replays
+//! them without calling the handler, following the `Cache-Control` headers of
+//! both sides:
+//!
+//! - the response's `s-maxage` or `max-age` decides how long it is served;
+//!   responses without one, or marked `no-store`, `no-cache` or `private`,
+//!   are not stored
+//! - a request sending `no-store` or `no-cache` skips the cache entirely
+//! - responses to requests carrying credentials (`Authorization`, `Cookie`
+//!   or `X-API-Key` unless [configured](HttpResponseCache::with_credential_headers))
+//!   are only stored, and stored entries only served to such requests, when
+//!   the response is marked `public` or has an `s-maxage`, so one user's
+//!   response is never replayed to another
+//! - entries are keyed by method, path, query and the values of the
+//!   configured vary headers (plus any the response names in `Vary`)
+//!
+//! With [`HttpResponseCache::with_stale_while_revalidate`], an expired entry
+//! is still served for that long (or for the response's own
+//! `stale-while-revalidate`) while one background request refreshes it.
+//!
+//! ```ignore
+//! let cache = HttpResponseCache::from_config(backend.for_type().create_typed_cache().into(), &config)
+//!     .with_vary_headers(["accept-language"])
+//!     .with_stale_while_revalidate(Duration::from_secs(30));
+//! let api = api_routes.layer(axum::middleware::from_fn_with_state(cache, http_cache));
+//! ```
+//!
+//! Responses are served with an `X-Cache` header of `HIT`, `STALE` or `MISS`.
+//!
+//! Entries record the wall-clock time they were stored. When instances share
+//! a backend, a reader whose clock runs ahead of the writer's would see
+//! entries age faster and expire early;
+//! [`HttpResponseCache::with_clock_skew_tolerance`] (`cache.clock_skew_tolerance_ms`
+//! with [`HttpResponseCache::from_config`]) discounts that much of an entry's
+//! age, and entries stored "in the future" by a writer whose clock runs ahead
+//! count as brand new.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::{OriginalUri, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri, header},
+    middleware::Next,
+    response::Response,
+};
+use bincode::{Decode, Encode};
+use chrono::{DateTime, Utc};
+use metrics::counter;
+use tracing::{debug, warn};
+
+use crate::core::config::app_config::AppConfig;
+use crate::core::error::AppError;
+use crate::core::error::middleware::request_id_of;
+use crate::core::services::cache_provider::TypedCache;
+use crate::core::utils::sources::{self, TimeSource};
+
+/// Response header reporting how the cache handled the request
+pub const X_CACHE: HeaderName = HeaderName::from_static("x-cache");
+
+/// Largest response body stored unless configured otherwise
+pub const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Request headers that carry credentials unless configured otherwise
+pub const DEFAULT_CREDENTIAL_HEADERS: [HeaderName; 3] = [
+    header::AUTHORIZATION,
+    header::COOKIE,
+    HeaderName::from_static("x-api-key"),
+];
+
+/// Headers that describe a single exchange and are not replayed
+const UNSTORED_HEADERS: [HeaderName; 4] = [
+    header::CONNECTION,
+    header::TRANSFER_ENCODING,
+    header::DATE,
+    X_CACHE,
+];
+
+/// A stored response
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, Vec<u8>)>,
+    pub body: Vec<u8>,
+    /// Unix timestamp in milliseconds
+    pub stored_at: i64,
+    /// Seconds the response is fresh for
+    pub max_age: u64,
+    /// Seconds past `max_age` the response may be served while it is refreshed
+    pub stale_while_revalidate: u64,
+}
+
+impl CachedResponse {
+    /// Milliseconds since the response was stored; zero if stored after `now`
+    fn age_ms(&self, now: i64) -> u64 {
+        u64::try_from(now.saturating_sub(self.stored_at)).unwrap_or(0)
+    }
+
+    /// Directives of the stored `Cache-Control` header
+    fn cache_control(&self) -> CacheControl {
+        let mut headers = HeaderMap::new();
+        let values = self
+            .headers
+            .iter()
+            .filter(|(name, _)| name == header::CACHE_CONTROL.as_str())
+            .filter_map(|(_, value)| HeaderValue::from_bytes(value).ok());
+        for value in values {
+            headers.append(header::CACHE_CONTROL, value);
+        }
+        CacheControl::parse(&headers)
+    }
+
+    fn into_response(self, age: u64, status: &'static str) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let headers = response.headers_mut();
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) =
+                (HeaderName::try_from(name), HeaderValue::from_bytes(&value))
+            {
+                headers.append(name, value);
+            }
+        }
+        headers.insert(header::AGE, HeaderValue::from(age));
+        headers.insert(X_CACHE, HeaderValue::from_static(status));
+        response
+    }
+}
+
+/// `Cache-Control` directives relevant to a shared cache
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    public: bool,
+    max_age: Option<u64>,
+    s_maxage: Option<u64>,
+    stale_while_revalidate: Option<u64>,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut directives = Self::default();
+        for value in headers.get_all(header::CACHE_CONTROL) {
+            for directive in value.to_str().unwrap_or("").split(',') {
+                let (name, argument) = match directive.split_once('=') {
+                    Some((name, argument)) => (name, Some(argument.trim().trim_matches('"'))),
+                    None => (directive, None),
+                };
+                let seconds = argument.and_then(|argument| argument.parse::<u64>().ok());
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "no-store" => directives.no_store = true,
+                    "no-cache" => directives.no_cache = true,
+                    "private" => directives.private = true,
+                    "public" => directives.public = true,
+                    "max-age" => directives.max_age = seconds,
+                    "s-maxage" => directives.s_maxage = seconds,
+                    "stale-while-revalidate" => directives.stale_while_revalidate = seconds,
+                    _ => {}
+                }
+            }
+        }
+        directives
+    }
+
+    /// Whether a response may be shared between requests carrying different credentials
+    fn shareable_with_credentials(&self) -> bool {
+        self.public || self.s_maxage.is_some()
+    }
+}
+
+/// Response cache for `GET` endpoints, shared by every clone
+#[derive(Clone)]
+pub struct HttpResponseCache {
+    store: Arc<dyn TypedCache<CachedResponse>>,
+    vary_headers: Arc<Vec<HeaderName>>,
+    credential_headers: Arc<Vec<HeaderName>>,
+    stale_while_revalidate: Option<Duration>,
+    max_body_bytes: usize,
+    clock_skew_tolerance: Duration,
+    time: Option<Arc<dyn TimeSource>>,
+    /// Keys with a background refresh in flight
+    revalidating: Arc<Mutex<HashSet<String>>>,
+}
+
+impl HttpResponseCache {
+    /// Cache storing responses in `store`
+    pub fn new(store: Arc<dyn TypedCache<CachedResponse>>) -> Self {
+        Self {
+            store,
+            vary_headers: Arc::new(Vec::new()),
+            credential_headers: Arc::new(DEFAULT_CREDENTIAL_HEADERS.to_vec()),
+            stale_while_revalidate: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            clock_skew_tolerance: Duration::ZERO,
+            time: None,
+            revalidating: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Cache storing responses in `store`, tolerating the clock skew set in
+    /// `cache.clock_skew_tolerance_ms`
+    pub fn from_config(store: Arc<dyn TypedCache<CachedResponse>>, config: &AppConfig) -> Self {
+        Self::new(store).with_clock_skew_tolerance(config.cache_clock_skew_tolerance())
+    }
+
+    /// Request headers whose values are part of every key
+    pub fn with_vary_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.vary_headers = Arc::new(
+            headers
+                .into_iter()
+                .filter_map(|name| HeaderName::try_from(name.as_ref()).ok())
+                .collect(),
+        );
+        self
+    }
+
+    /// Request headers that mark a request as carrying credentials
+    ///
+    /// Replaces [`DEFAULT_CREDENTIAL_HEADERS`]; list every header an
+    /// authentication scheme reads, such as a custom API key header.
+    pub fn with_credential_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.credential_headers = Arc::new(
+            headers
+                .into_iter()
+                .filter_map(|name| HeaderName::try_from(name.as_ref()).ok())
+                .collect(),
+        );
+        self
+    }
+
+    /// Whether a request with `headers` carries credentials
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        self.credential_headers
+            .iter()
+            .any(|name| headers.contains_key(name))
+    }
+
+    /// Serve expired entries for up to `window` while they are refreshed
+    pub fn with_stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = Some(window);
+        self
+    }
+
+    /// Don't store responses with bodies larger than `bytes`
+    pub fn with_max_body_bytes(mut self, bytes: usize) -> Self {
+        self.max_body_bytes = bytes;
+        self
+    }
+
+    /// Don't expire entries early while this instance's clock runs up to
+    /// `tolerance` ahead of the clock of the instance that wrote them
+    ///
+    /// Entries stay fresh for up to `tolerance` past their `max-age` by this
+    /// instance's clock, and are kept in the backend that much longer.
+    pub fn with_clock_skew_tolerance(mut self, tolerance: Duration) -> Self {
+        self.clock_skew_tolerance = tolerance;
+        self
+    }
+
+    /// Read the time from `time` instead of the process-wide source
+    pub fn with_time_source(mut self, time: Arc<dyn TimeSource>) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Current time as a Unix timestamp in milliseconds
+    fn now_ms(&self) -> i64 {
+        let time = self.time.clone().unwrap_or_else(sources::time);
+        DateTime::<Utc>::from(time.now()).timestamp_millis()
+    }
+
+    /// Age in seconds used for freshness, less the tolerated clock skew
+    fn effective_age(&self, entry: &CachedResponse, now: i64) -> u64 {
+        let skew = u64::try_from(self.clock_skew_tolerance.as_millis()).unwrap_or(u64::MAX);
+        entry.age_ms(now).saturating_sub(skew) / 1000
+    }
+
+    /// Key for a request: method, path and query, then the vary header values
+    fn key(&self, method: &Method, uri: &Uri, headers: &HeaderMap, vary: &[HeaderName]) -> String {
+        let mut key = format!("{} {}", method, uri);
+        for name in self.vary_headers.iter().chain(vary) {
+            let values: Vec<&str> = headers
+                .get_all(name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .collect();
+            key.push_str(&format!("|{}={}", name, values.join(",")));
+        }
+        key
+    }
+
+    /// Look up `key`, treating backend errors as a miss
+    ///
+    /// With `authorized`, only entries that may be shared between
+    /// credentials are returned.
+    async fn lookup(&self, key: &str, authorized: bool) -> Option<CachedResponse> {
+        match self.store.get(key).await {
+            Ok(entry) => entry
+                .filter(|entry| !authorized || entry.cache_control().shareable_with_credentials()),
+            Err(e) => {
+                warn!("HTTP cache lookup for {} failed: {}", key, e);
+                None
+            }
+        }
+    }
+
+    /// Store `response` under `key` if its headers allow it, returning it unchanged
+    ///
+    /// `authorized` marks a response to a request carrying credentials. If
+    /// the body can't be read, the response is replaced with a 500 carrying
+    /// `request_id`.
+    async fn store(
+        &self,
+        key: &str,
+        response: Response,
+        authorized: bool,
+        request_id: Option<String>,
+    ) -> Response {
+        let control = CacheControl::parse(response.headers());
+        let Some(max_age) = control.s_maxage.or(control.max_age) else {
+            return response;
+        };
+        if response.status() != StatusCode::OK
+            || control.no_store
+            || control.no_cache
+            || control.private
+            || (authorized && !control.shareable_with_credentials())
+            || response.headers().contains_key(header::SET_COOKIE)
+        {
+            return response;
+        }
+        let fits = response
+            .body()
+            .size_hint()
+            .upper()
+            .is_some_and(|len| len <= self.max_body_bytes as u64);
+        if !fits {
+            return response;
+        }
+
+        let (parts, body) = response.into_parts();
+        let bytes = match to_bytes(body, self.max_body_bytes).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Failed to buffer response for HTTP cache: {}", e);
+                return AppError::internal_server_error("Failed to read the response body")
+                    .into_response_with_request_id(request_id);
+            }
+        };
+
+        let stale_while_revalidate = match self.stale_while_revalidate {
+            Some(window) => control.stale_while_revalidate.unwrap_or(window.as_secs()),
+            None => 0,
+        };
+        let entry = CachedResponse {
+            status: parts.status.as_u16(),
+            headers: parts
+                .headers
+                .iter()
+                .filter(|(name, _)| !UNSTORED_HEADERS.contains(name))
+                .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+                .collect(),
+            body: bytes.to_vec(),
+            stored_at: self.now_ms(),
+            max_age,
+            stale_while_revalidate,
+        };
+        let ttl = Duration::from_secs(max_age + stale_while_revalidate) + self.clock_skew_tolerance;
+        if let Err(e) = self.store.set(key, entry, Some(ttl)).await {
+            warn!("Failed to store {} in HTTP cache: {}", key, e);
+        }
+
+        Response::from_parts(parts, Body::from(bytes))
+    }
+
+    /// Refresh `key` in the background unless a refresh is already running
+    fn revalidate(&self, key: String, request: Request, next: Next) {
+        if !self.revalidating.lock().unwrap().insert(key.clone()) {
+            return;
+        }
+        // Released however the refresh ends, including a panicking handler
+        let guard = RevalidatingGuard {
+            keys: self.revalidating.clone(),
+            key,
+        };
+        let cache = self.clone();
+        tokio::spawn(async move {
+            let key = &guard.key;
+            debug!("Revalidating {} in HTTP cache", key);
+            let authorized = cache.is_authorized(request.headers());
+            let request_id = request_id_of(&request);
+            let response = next.run(request).await;
+            cache.store(key, response, authorized, request_id).await;
+        });
+    }
+}
+
+/// Removes `key` from the in-flight refreshes when dropped
+struct RevalidatingGuard {
+    keys: Arc<Mutex<HashSet<String>>>,
+    key: String,
+}
+
+impl Drop for RevalidatingGuard {
+    fn drop(&mut self) {
+        self.keys.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Header names listed in `Vary` values; `None` for `Vary: *`, which can't be cached
+fn parse_vary<'a>(values: impl Iterator<Item = &'a [u8]>) -> Option<Vec<HeaderName>> {
+    let mut names = Vec::new();
+    for value in values {
+        for name in String::from_utf8_lossy(value).split(',').map(str::trim) {
+            if name == "*" {
+                return None;
+            }
+            if let Ok(name) = HeaderName::try_from(name) {
+                names.push(name);
+            }
+        }
+    }
+    Some(names)
+}
+
+/// Copy of a bodiless request, for replaying it in the background
+fn clone_request(request: &Request) -> Request {
+    let mut clone = Request::new(Body::empty());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    *clone.extensions_mut() = request.extensions().clone();
+    clone
+}
+
+fn record(result: &'static str) {
+    counter!("http_cache_requests_total", "result" => result).increment(1);
+}
+
+/// Middleware serving `GET` responses from an [`HttpResponseCache`]
+///
+/// Use with `axum::middleware::from_fn_with_state(cache, http_cache)`.
+pub async fn http_cache(
+    State(cache): State<HttpResponseCache>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let control = CacheControl::parse(request.headers());
+    if request.method() != Method::GET || control.no_store || control.no_cache {
+        record("bypass");
+        return next.run(request).await;
+    }
+
+    // Nested routers see a stripped URI; key on the full one
+    let uri = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| request.uri().clone(), |uri| uri.0.clone());
+    let (method, headers) = (request.method().clone(), request.headers().clone());
+    let authorized = cache.is_authorized(&headers);
+    let request_id = request_id_of(&request);
+    let base_key = cache.key(&method, &uri, &headers, &[]);
+    if let Some(entry) = cache.lookup(&base_key, authorized).await {
+        // With `Vary`, the base entry only records which headers the real key includes
+        let vary = parse_vary(
+            entry
+                .headers
+                .iter()
+                .filter(|(name, _)| name == header::VARY.as_str())
+                .map(|(_, value)| value.as_slice()),
+        )
+        .unwrap_or_default();
+        let (key, entry) = if vary.is_empty() {
+            (base_key.clone(), Some(entry))
+        } else {
+            let key = cache.key(&method, &uri, &headers, &vary);
+            let entry = cache.lookup(&key, authorized).await;
+            (key, entry)
+        };
+
+        if let Some(entry) = entry {
+            let now = cache.now_ms();
+            let age = entry.age_ms(now) / 1000;
+            let effective_age = cache.effective_age(&entry, now);
+            if effective_age < entry.max_age {
+                record("hit");
+                return entry.into_response(age, "HIT");
+            }
+            if effective_age < entry.max_age + entry.stale_while_revalidate {
+                record("stale");
+                cache.revalidate(key, clone_request(&request), next);
+                return entry.into_response(age, "STALE");
+            }
+        }
+    }
+
+    record("miss");
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert(X_CACHE, HeaderValue::from_static("MISS"));
+
+    let Some(vary) = parse_vary(
+        response
+            .headers()
+            .get_all(header::VARY)
+            .iter()
+            .map(HeaderValue::as_bytes),
+    ) else {
+        return response;
+    };
+    if vary.is_empty() {
+        return cache
+            .store(&base_key, response, authorized, request_id)
+            .await;
+    }
+
+    // Store under the varying key, with a marker under the base key naming the headers
+    let response = cache
+        .store(
+            &cache.key(&method, &uri, &headers, &vary),
+            response,
+            authorized,
+            request_id.clone(),
+        )
+        .await;
+    let mut marker = Response::new(Body::empty());
+    *marker.headers_mut() = response.headers().clone();
+    cache
+        .store(&base_key, marker, authorized, request_id)
+        .await;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::services::cache_provider::{CacheConfig, CacheFactory};
+    use crate::core::services::memory_cache::InMemoryCache;
+    use crate::core::utils::sources::ManualClock;
+    use axum::{Router, routing::get};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    fn cache() -> HttpResponseCache {
+        let backend = InMemoryCache::new(CacheConfig::default());
+        HttpResponseCache::new(Arc::from(
+            backend.for_type::<CachedResponse>().create_typed_cache(),
+        ))
+    }
+
+    /// Router counting handler calls; the handler answers with `cache_control`
+    fn app(cache: HttpResponseCache, cache_control: &'static str) -> (Router, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new()
+            .route(
+                "/pets",
+                get(move || {
+                    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move {
+                        (
+                            [(header::CACHE_CONTROL, cache_control)],
+                            format!("call {}", n),
+                        )
+                    }
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(cache, http_cache));
+        (router, calls)
+    }
+
+    async fn get_pets(app: &Router, headers: &[(&str, &str)]) -> (String, String) {
+        let mut request = axum::http::Request::builder().uri("/pets");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let x_cache = response.headers()[X_CACHE].to_str().unwrap().to_string();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (x_cache, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_cacheable_get_is_served_from_cache() {
+        let (app, calls) = app(cache(), "public, max-age=60");
+
+        assert_eq!(get_pets(&app, &[]).await, ("MISS".into(), "call 1".into()));
+        assert_eq!(get_pets(&app, &[]).await, ("HIT".into(), "call 1".into()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_no_store_bypasses_cache() {
+        let (app, calls) = app(cache(), "max-age=60");
+        get_pets(&app, &[]).await;
+
+        let no_store = [("cache-control", "no-store")];
+        assert_eq!(get_pets(&app, &no_store).await.1, "call 2");
+        assert_eq!(get_pets(&app, &no_store).await.1, "call 3");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_uncacheable_responses_are_not_stored() {
+        for cache_control in ["no-store, max-age=60", "private, max-age=60", "no-cache"] {
+            let (app, calls) = app(cache(), cache_control);
+            get_pets(&app, &[]).await;
+            get_pets(&app, &[]).await;
+            assert_eq!(calls.load(Ordering::SeqCst), 2, "{}", cache_control);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authorized_responses_are_not_shared_unless_public() {
+        let alice = [("authorization", "Bearer alice")];
+        let bob = [("authorization", "Bearer bob")];
+
+        let (per_user, calls) = app(cache(), "max-age=60");
+        assert_eq!(
+            get_pets(&per_user, &alice).await,
+            ("MISS".into(), "call 1".into())
+        );
+        assert_eq!(
+            get_pets(&per_user, &bob).await,
+            ("MISS".into(), "call 2".into())
+        );
+        assert_eq!(get_pets(&per_user, &alice).await.1, "call 3");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // An anonymous response isn't replayed to a request with credentials either
+        get_pets(&per_user, &[]).await;
+        assert_eq!(get_pets(&per_user, &bob).await.0, "MISS");
+
+        for cache_control in ["public, max-age=60", "s-maxage=60"] {
+            let (shared, calls) = app(cache(), cache_control);
+            get_pets(&shared, &alice).await;
+            assert_eq!(get_pets(&shared, &bob).await.0, "HIT", "{}", cache_control);
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_key_and_cookie_requests_count_as_credentialed() {
+        for name in ["x-api-key", "cookie"] {
+            let (app, calls) = app(cache(), "max-age=60");
+            get_pets(&app, &[(name, "alice")]).await;
+            assert_eq!(get_pets(&app, &[(name, "bob")]).await.1, "call 2", "{}", name);
+            get_pets(&app, &[]).await;
+            assert_eq!(get_pets(&app, &[(name, "bob")]).await.0, "MISS", "{}", name);
+            assert_eq!(calls.load(Ordering::SeqCst), 4, "{}", name);
+        }
+
+        // A custom API key header only counts once configured
+        let cache = cache().with_credential_headers(["authorization", "x-tenant-key"]);
+        let (app, calls) = app(cache, "max-age=60");
+        get_pets(&app, &[("x-tenant-key", "alice")]).await;
+        assert_eq!(get_pets(&app, &[("x-tenant-key", "bob")]).await.1, "call 2");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_vary_headers_are_part_of_key() {
+        let (app, calls) = app(cache().with_vary_headers(["accept-language"]), "max-age=60");
+
+        get_pets(&app, &[("accept-language", "en")]).await;
+        let (x_cache, _) = get_pets(&app, &[("accept-language", "de")]).await;
+        assert_eq!(x_cache, "MISS");
+        let (x_cache, body) = get_pets(&app, &[("accept-language", "en")]).await;
+        assert_eq!((x_cache.as_str(), body.as_str()), ("HIT", "call 1"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stale_entry_is_served_while_revalidating() {
+        let cache = cache().with_stale_while_revalidate(Duration::from_secs(30));
+        let (app, calls) = app(cache, "max-age=0");
+
+        get_pets(&app, &[]).await;
+        assert_eq!(get_pets(&app, &[]).await, ("STALE".into(), "call 1".into()));
+
+        // The background refresh replaces the entry
+        for _ in 0..50 {
+            if calls.load(Ordering::SeqCst) == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(get_pets(&app, &[]).await, ("STALE".into(), "call 2".into()));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_refresh_allows_another() {
+        let cache = cache().with_stale_while_revalidate(Duration::from_secs(30));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new()
+            .route(
+                "/pets",
+                get(move || {
+                    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move {
+                        assert_ne!(n, 2, "refresh failed");
+                        ([(header::CACHE_CONTROL, "max-age=0")], format!("call {}", n))
+                    }
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(cache.clone(), http_cache));
+
+        get_pets(&app, &[]).await;
+        // The first refresh panics; its key must not stay marked as in flight
+        assert_eq!(get_pets(&app, &[]).await.0, "STALE");
+        for _ in 0..50 {
+            if calls.load(Ordering::SeqCst) == 2 && cache.revalidating.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(cache.revalidating.lock().unwrap().is_empty());
+
+        assert_eq!(get_pets(&app, &[]).await.0, "STALE");
+        for _ in 0..50 {
+            if calls.load(Ordering::SeqCst) == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_clock_skew_within_tolerance_keeps_entry_fresh() {
+        let backend = InMemoryCache::new(CacheConfig::default());
+        let store: Arc<dyn TypedCache<CachedResponse>> =
+            Arc::from(backend.for_type::<CachedResponse>().create_typed_cache());
+        let start = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let writer_clock = Arc::new(ManualClock::new(start));
+        // The reader's clock runs 2s ahead of the writer's
+        let reader_clock = Arc::new(ManualClock::new(start + Duration::from_secs(2)));
+
+        let instance = |clock: &Arc<ManualClock>, tolerance: Duration| {
+            HttpResponseCache::new(store.clone())
+                .with_time_source(clock.clone())
+                .with_clock_skew_tolerance(tolerance)
+        };
+        let (writer, _) = app(instance(&writer_clock, Duration::ZERO), "max-age=10");
+        let (tolerant, _) = app(
+            instance(&reader_clock, Duration::from_secs(3)),
+            "max-age=10",
+        );
+        let (strict, _) = app(instance(&reader_clock, Duration::ZERO), "max-age=10");
+
+        assert_eq!(get_pets(&writer, &[]).await.0, "MISS");
+
+        // 9s in by the writer's clock, 11s by the reader's
+        writer_clock.advance(Duration::from_secs(9));
+        reader_clock.advance(Duration::from_secs(9));
+        assert_eq!(get_pets(&writer, &[]).await.0, "HIT");
+        assert_eq!(get_pets(&tolerant, &[]).await.0, "HIT");
+        assert_eq!(get_pets(&strict, &[]).await.0, "MISS");
+    }
+
+    #[test]
+    fn test_from_config_applies_clock_skew_tolerance() {
+        let mut config = AppConfig::default();
+        config.cache.clock_skew_tolerance_ms = 2500;
+        let backend = InMemoryCache::new(CacheConfig::default());
+        let cache = HttpResponseCache::from_config(
+            Arc::from(backend.for_type::<CachedResponse>().create_typed_cache()),
+            &config,
+        );
+        assert_eq!(cache.clock_skew_tolerance, Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn test_entries_from_a_clock_ahead_are_not_aged_negatively() {
+        let entry = CachedResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: Vec::new(),
+            stored_at: 10_000,
+            max_age: 5,
+            stale_while_revalidate: 0,
+        };
+        let cache = cache().with_clock_skew_tolerance(Duration::from_millis(1500));
+
+        assert_eq!(entry.age_ms(8_000), 0);
+        assert_eq!(cache.effective_age(&entry, 16_000), 4);
+        assert_eq!(cache.effective_age(&entry, 17_000), 5);
+    }
+
+    #[test]
+    fn test_cache_control_parsing() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("public, Max-Age=\"120\", stale-while-revalidate=30"),
+        );
+        let control = CacheControl::parse(&headers);
+        assert_eq!(control.max_age, Some(120));
+        assert_eq!(control.stale_while_revalidate, Some(30));
+        assert!(control.public && control.shareable_with_credentials());
+        assert!(!control.no_store && !control.private);
+    }
+}
@@ -0,0 +1,360 @@
+//! Cache warming at startup
+//!
+//! [`CacheWarmer`] fetches a known set of hot resources through
+//! [`get_or_fetch`] before traffic arrives, so the first requests after a
+//! deploy don't all miss. Fetches run with bounded concurrency and within a
+//! time budget; failures are logged and never fail startup.
+//!
+//! ```yaml
+//! cache:
+//!   warming:
+//!     enabled: true
+//!     concurrency: 4
+//!     budget_ms: 10000
+//!     keys:
+//!       pet: ["1", "2", "42"]
+//! ```
+//!
+//! ```ignore
+//! let report = CacheWarmer::from_config(registry.clone(), &config.cache)
+//!     .warm_configured::<Pet, _, _>(move |id| {
+//!         let pets = pets.clone();
+//!         async move { pets.get(&id).await.map_err(|e| e.to_string()) }
+//!     })
+//!     .run()
+//!     .await;
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::{Instant, timeout_at};
+use tracing::{info, warn};
+
+use crate::core::cache::cache_manager::{CacheRegistry, get_or_fetch};
+use crate::core::config::app_config::CacheConfig;
+use crate::core::utils::api_resource::ApiResource;
+
+type WarmFn = Box<dyn FnOnce() -> BoxFuture<'static, Result<(), String>> + Send>;
+
+struct WarmJob {
+    key: String,
+    warm: WarmFn,
+}
+
+/// Outcome of a warming run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmReport {
+    /// Entries fetched and cached
+    pub warmed: usize,
+    /// Entries whose fetch failed
+    pub failed: usize,
+    /// Entries abandoned when the budget ran out
+    pub unfinished: usize,
+}
+
+/// Fetches configured resources into the cache ahead of traffic
+pub struct CacheWarmer {
+    registry: CacheRegistry,
+    enabled: bool,
+    concurrency: usize,
+    budget: Duration,
+    keys: HashMap<String, Vec<String>>,
+    jobs: Vec<WarmJob>,
+}
+
+impl std::fmt::Debug for CacheWarmer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CacheWarmer")
+            .field("enabled", &self.enabled)
+            .field("concurrency", &self.concurrency)
+            .field("budget", &self.budget)
+            .field("jobs", &self.jobs.len())
+            .finish()
+    }
+}
+
+impl CacheWarmer {
+    /// Warmer fetching 4 entries at a time within 10 seconds
+    pub fn new(registry: CacheRegistry) -> Self {
+        Self {
+            registry,
+            enabled: true,
+            concurrency: 4,
+            budget: Duration::from_secs(10),
+            keys: Default::default(),
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Warmer using `cache.warming`
+    pub fn from_config(registry: CacheRegistry, config: &CacheConfig) -> Self {
+        let warming = &config.warming;
+        Self {
+            enabled: warming.enabled,
+            keys: warming.keys.clone(),
+            ..Self::new(registry)
+                .with_concurrency(warming.concurrency)
+                .with_budget(Duration::from_millis(warming.budget_ms))
+        }
+    }
+
+    /// Fetch at most `concurrency` entries at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Stop warming once `budget` has passed
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Warm `ids` of `T`, fetching each with `fetch`
+    pub fn warm<T, F, Fut>(mut self, ids: impl IntoIterator<Item = String>, fetch: F) -> Self
+    where
+        T: ApiResource,
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, String>> + Send + 'static,
+    {
+        let fetch = Arc::new(fetch);
+        for id in ids {
+            let registry = self.registry.clone();
+            let fetch = fetch.clone();
+            self.jobs.push(WarmJob {
+                key: format!("{}:{}", T::resource_type(), id),
+                warm: Box::new(move || {
+                    Box::pin(async move {
+                        let key = id.clone();
+                        get_or_fetch::<T, _, _>(&registry, T::resource_type(), &key, move || {
+                            fetch(id)
+                        })
+                        .await
+                        .map(|_| ())
+                    })
+                }),
+            });
+        }
+        self
+    }
+
+    /// Warm the ids listed for `T` under `cache.warming.keys`
+    pub fn warm_configured<T, F, Fut>(self, fetch: F) -> Self
+    where
+        T: ApiResource,
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, String>> + Send + 'static,
+    {
+        let ids = self
+            .keys
+            .get(T::resource_type())
+            .cloned()
+            .unwrap_or_default();
+        self.warm::<T, F, Fut>(ids, fetch)
+    }
+
+    /// Run every fetch, returning once all finish or the budget runs out
+    pub async fn run(self) -> WarmReport {
+        let mut report = WarmReport::default();
+        if !self.enabled || !self.registry.enabled || self.jobs.is_empty() {
+            return report;
+        }
+
+        let started = Instant::now();
+        let deadline = started + self.budget;
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = JoinSet::new();
+        for job in self.jobs {
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (job.key, (job.warm)().await)
+            });
+        }
+
+        loop {
+            match timeout_at(deadline, tasks.join_next()).await {
+                Ok(Some(Ok((_, Ok(()))))) => report.warmed += 1,
+                Ok(Some(Ok((key, Err(e))))) => {
+                    warn!("Failed to warm cache entry {}: {}", key, e);
+                    report.failed += 1;
+                }
+                Ok(Some(Err(e))) => {
+                    warn!("Cache warming task failed: {}", e);
+                    report.failed += 1;
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    report.unfinished = tasks.len();
+                    warn!(
+                        "Cache warming budget of {:?} exhausted, skipping {} entries",
+                        self.budget, report.unfinished
+                    );
+                    tasks.abort_all();
+                    break;
+                }
+            }
+        }
+
+        info!(
+            "🔥 Warmed {} cache entries in {:?} ({} failed, {} unfinished)",
+            report.warmed,
+            started.elapsed(),
+            report.failed,
+            report.unfinished
+        );
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cache::cache_manager::{
+        get_resource_cache, init_cache_registry, register_resource_cache,
+    };
+    use crate::core::config::app_config::CacheWarmingConfig;
+    use serde::{Deserialize, Serialize};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct HotPet {
+        id: String,
+    }
+
+    impl ApiResource for HotPet {
+        type Id = String;
+
+        fn resource_type() -> &'static str {
+            "hot_pet"
+        }
+
+        fn api_name() -> &'static str {
+            "PetService"
+        }
+    }
+
+    fn registry() -> CacheRegistry {
+        let registry = init_cache_registry(true, 100, 3600);
+        register_resource_cache::<HotPet>(&registry, HotPet::resource_type()).unwrap();
+        registry
+    }
+
+    async fn cached(registry: &CacheRegistry, id: &str) -> Option<HotPet> {
+        get_resource_cache::<HotPet>(registry, HotPet::resource_type())
+            .unwrap()
+            .cache
+            .get(id)
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_configured_keys_are_cached() {
+        let registry = registry();
+        let config = CacheConfig {
+            enabled: true,
+            warming: CacheWarmingConfig {
+                enabled: true,
+                keys: [(
+                    "hot_pet".to_string(),
+                    vec!["1".to_string(), "2".to_string(), "missing".to_string()],
+                )]
+                .into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let report = CacheWarmer::from_config(registry.clone(), &config)
+            .warm_configured::<HotPet, _, _>(|id| async move {
+                if id == "missing" {
+                    Err("not found".to_string())
+                } else {
+                    Ok(HotPet { id })
+                }
+            })
+            .run()
+            .await;
+
+        assert_eq!(
+            report,
+            WarmReport {
+                warmed: 2,
+                failed: 1,
+                unfinished: 0
+            }
+        );
+        assert_eq!(
+            cached(&registry, "1").await,
+            Some(HotPet { id: "1".into() })
+        );
+        assert!(cached(&registry, "2").await.is_some());
+        assert!(cached(&registry, "missing").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_is_limited() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (counter, max) = (in_flight.clone(), peak.clone());
+
+        let report = CacheWarmer::new(registry())
+            .with_concurrency(2)
+            .warm::<HotPet, _, _>((0..8).map(|i| i.to_string()), move |id| {
+                let (counter, max) = (counter.clone(), max.clone());
+                async move {
+                    let now = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    counter.fetch_sub(1, Ordering::SeqCst);
+                    Ok(HotPet { id })
+                }
+            })
+            .run()
+            .await;
+
+        assert_eq!(report.warmed, 8);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_budget_stops_slow_warming() {
+        let started = std::time::Instant::now();
+
+        let report = CacheWarmer::new(registry())
+            .with_budget(Duration::from_millis(50))
+            .warm::<HotPet, _, _>(["slow".to_string()], |id| async move {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(HotPet { id })
+            })
+            .run()
+            .await;
+
+        assert_eq!(report.unfinished, 1);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_warming_does_nothing() {
+        let registry = registry();
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = fetches.clone();
+
+        let report = CacheWarmer::from_config(registry.clone(), &CacheConfig::default())
+            .warm::<HotPet, _, _>(["1".to_string()], move |id| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async move { Ok(HotPet { id }) }
+            })
+            .run()
+            .await;
+
+        assert_eq!(report, WarmReport::default());
+        assert_eq!(fetches.load(Ordering::SeqCst), 0);
+    }
+}
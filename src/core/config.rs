@@ -6,7 +6,10 @@
 //! - Type-safe configuration access
 
 pub mod app_config;
+pub mod audit;
 pub mod constants;
+pub mod redaction;
+pub mod secrets;
 #[cfg(test)]
 mod tests;
 
@@ -15,6 +18,8 @@ pub use app_config::load_config;
 use app_config::{
     ApiConfig, AuthConfig, CacheConfig, LoggingConfig, ReliabilityConfig, ServerConfig,
 };
+pub use audit::{ConfigAuditLog, ConfigChange, ConfigChangeEvent};
+pub use secrets::{EnvSecretProvider, SecretProvider};
 
 use lazy_static::lazy_static;
 use std::default::Default;
@@ -36,6 +41,11 @@ impl Default for AppConfig {
                 timeout_seconds: 30,
                 max_retries: 3,
                 protocol: "http".to_string(),
+                tuning: app_config::ServerTuning::default(),
+                error_format: app_config::ErrorFormat::default(),
+                trusted_proxies: Vec::new(),
+                cors: app_config::CorsConfig::default(),
+                path_normalization: app_config::PathNormalizationConfig::default(),
             },
             api: ApiConfig::default(),
             logging: LoggingConfig::default(),
@@ -45,6 +55,9 @@ impl Default for AppConfig {
             openapi: app_config::OpenApiConfig::default(),
             endpoint_security: app_config::EndpointSecurityConfig::default(),
             features: app_config::FeaturesConfig::default(),
+            migrations: app_config::MigrationsConfig::default(),
+            health: app_config::HealthChecksConfig::default(),
+            ids: app_config::IdsConfig::default(),
         }
     }
 }
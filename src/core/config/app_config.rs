@@ -1,4 +1,8 @@
 use super::constants;
+use super::redaction;
+use super::secrets::{EnvSecretProvider, SecretProvider, resolve_secrets};
+use crate::core::error::AppError;
+use crate::core::models::id_generator::{IdGenerator, IdStrategy};
 use config::{Config, ConfigError, Environment, File};
 use dotenvy::dotenv;
 use serde::{Deserialize, Serialize};
@@ -6,6 +10,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::path::Path;
 use std::time::Duration;
 use tracing::info;
@@ -19,6 +24,234 @@ pub struct ServerConfig {
     pub max_retries: u32,
     #[serde(default = "default_protocol")]
     pub protocol: String,
+    /// Socket and HTTP protocol tuning for the listener
+    #[serde(default)]
+    pub tuning: ServerTuning,
+    /// Body format used for error responses
+    #[serde(default)]
+    pub error_format: ErrorFormat,
+    /// Proxy networks (CIDR) whose `X-Forwarded-For`/`X-Forwarded-Proto` are trusted
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+    /// Cross-origin resource sharing
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Canonical request paths (trailing slash, casing)
+    #[serde(default)]
+    pub path_normalization: PathNormalizationConfig,
+}
+
+impl ServerConfig {
+    /// Address the server listens on
+    ///
+    /// `host` may be an IPv4 address, an IPv6 address with or without
+    /// brackets, or a hostname, which is resolved to its first address.
+    pub fn socket_addr(&self) -> Result<SocketAddr, AppError> {
+        let host = self.host.trim();
+        let invalid = |reason: &str| {
+            AppError::ConfigurationError(format!(
+                "Invalid server address (server.host '{}', server.port {}): {}",
+                self.host, self.port, reason
+            ))
+        };
+
+        let unbracketed = host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host);
+        if let Ok(ip) = unbracketed.parse::<IpAddr>() {
+            return Ok(SocketAddr::new(ip, self.port));
+        }
+        if host.starts_with('[') {
+            return Err(invalid("brackets must enclose an IPv6 address"));
+        }
+        if !is_hostname(host) {
+            return Err(invalid("not an IP address or hostname"));
+        }
+
+        (host, self.port)
+            .to_socket_addrs()
+            .map_err(|e| invalid(&format!("could not resolve '{}': {}", host, e)))?
+            .next()
+            .ok_or_else(|| invalid(&format!("'{}' resolved to no addresses", host)))
+    }
+}
+
+/// Whether `host` is a syntactically valid DNS name
+fn is_hostname(host: &str) -> bool {
+    !host.is_empty()
+        && host.len() <= 253
+        && host.trim_end_matches('.').split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// CORS policy applied when the router builder enables CORS
+///
+/// `"*"` in a list allows any value. Credentials can't be combined with a
+/// wildcard origin or exposed headers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsConfig {
+    /// Origins allowed to make cross-origin requests, as `scheme://host[:port]`;
+    /// none by default
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Methods allowed in cross-origin requests
+    #[serde(default = "default_cors_wildcard")]
+    pub allowed_methods: Vec<String>,
+    /// Request headers allowed in cross-origin requests
+    #[serde(default = "default_cors_wildcard")]
+    pub allowed_headers: Vec<String>,
+    /// Response headers scripts may read (`Access-Control-Expose-Headers`)
+    #[serde(default)]
+    pub exposed_headers: Vec<String>,
+    /// Send `Access-Control-Allow-Credentials: true`
+    #[serde(default)]
+    pub allow_credentials: bool,
+    /// How long browsers may cache a preflight (`Access-Control-Max-Age`)
+    #[serde(default)]
+    pub max_age_seconds: Option<u64>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: default_cors_wildcard(),
+            allowed_headers: default_cors_wildcard(),
+            exposed_headers: Vec::new(),
+            allow_credentials: false,
+            max_age_seconds: None,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Whether `values` allows anything
+    pub fn is_wildcard(values: &[String]) -> bool {
+        values.iter().any(|value| value == "*")
+    }
+}
+
+fn default_cors_wildcard() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+/// How integers are written in JSON responses
+///
+/// JavaScript clients parse numbers as `f64`, losing precision beyond 2^53 - 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegerEncoding {
+    /// Plain JSON numbers
+    #[default]
+    Number,
+    /// Every integer as a string
+    String,
+    /// Only integers a JavaScript number can't hold exactly, as strings
+    UnsafeAsString,
+}
+
+/// Body format for error responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorFormat {
+    /// The `ErrorResponse` shape (`code`, `message`, `error_type`, `details`)
+    #[default]
+    Legacy,
+    /// RFC 7807 `application/problem+json` for every error
+    ProblemJson,
+    /// `application/problem+json` only when the client's `Accept` header asks for it
+    Negotiate,
+}
+
+/// What happens to a request whose path is not canonical
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PathNormalizationMode {
+    /// Route the path as sent
+    #[default]
+    Off,
+    /// Redirect to the canonical path (301 for GET/HEAD, 308 otherwise)
+    Redirect,
+    /// Route the canonical path without telling the client
+    Rewrite,
+}
+
+/// Canonical request paths
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathNormalizationConfig {
+    #[serde(default)]
+    pub mode: PathNormalizationMode,
+    /// Strip trailing slashes (`/api/pets/` becomes `/api/pets`)
+    #[serde(default = "default_true")]
+    pub trim_trailing_slash: bool,
+    /// Lowercase the whole path, path parameters included
+    #[serde(default)]
+    pub lowercase: bool,
+}
+
+impl Default for PathNormalizationConfig {
+    fn default() -> Self {
+        Self {
+            mode: PathNormalizationMode::Off,
+            trim_trailing_slash: true,
+            lowercase: false,
+        }
+    }
+}
+
+/// Socket and HTTP protocol tuning applied when binding the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTuning {
+    /// Disable Nagle's algorithm on accepted connections
+    #[serde(default = "default_true")]
+    pub tcp_nodelay: bool,
+
+    /// TCP keep-alive idle time in seconds; `None` disables keep-alive probes
+    #[serde(default = "default_tcp_keepalive")]
+    pub tcp_keepalive_seconds: Option<u64>,
+
+    /// Set `SO_REUSEADDR` so restarts can rebind while old sockets linger in TIME_WAIT
+    #[serde(default = "default_true")]
+    pub reuse_address: bool,
+
+    /// Maximum pending connections queued by the kernel
+    #[serde(default = "default_listen_backlog")]
+    pub backlog: u32,
+
+    /// Accept HTTP/1.1 connections
+    #[serde(default = "default_true")]
+    pub http1: bool,
+
+    /// Accept HTTP/2 (prior knowledge) connections
+    #[serde(default = "default_true")]
+    pub http2: bool,
+}
+
+impl Default for ServerTuning {
+    fn default() -> Self {
+        Self {
+            tcp_nodelay: true,
+            tcp_keepalive_seconds: default_tcp_keepalive(),
+            reuse_address: true,
+            backlog: default_listen_backlog(),
+            http1: true,
+            http2: true,
+        }
+    }
+}
+
+fn default_tcp_keepalive() -> Option<u64> {
+    Some(60)
+}
+
+fn default_listen_backlog() -> u32 {
+    1024
 }
 
 /// Cache configuration
@@ -29,6 +262,48 @@ pub struct CacheConfig {
     pub max_capacity: u64,
     #[serde(default = "default_reconnect_interval")]
     pub reconnect_interval_seconds: u64,
+    /// How far this instance's clock may run ahead of the instances that
+    /// wrote entries to a shared backend before those entries expire early
+    #[serde(default)]
+    pub clock_skew_tolerance_ms: u64,
+    /// Resources fetched into the cache at startup
+    #[serde(default)]
+    pub warming: CacheWarmingConfig,
+}
+
+/// Cache warming at startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheWarmingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Entries fetched at once
+    #[serde(default = "default_warming_concurrency")]
+    pub concurrency: usize,
+    /// Time after which remaining entries are skipped
+    #[serde(default = "default_warming_budget_ms")]
+    pub budget_ms: u64,
+    /// Ids to warm, keyed by resource type
+    #[serde(default)]
+    pub keys: HashMap<String, Vec<String>>,
+}
+
+impl Default for CacheWarmingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            concurrency: default_warming_concurrency(),
+            budget_ms: default_warming_budget_ms(),
+            keys: HashMap::new(),
+        }
+    }
+}
+
+fn default_warming_concurrency() -> usize {
+    4
+}
+
+fn default_warming_budget_ms() -> u64 {
+    10_000
 }
 
 /// API configuration
@@ -37,11 +312,18 @@ pub struct ApiConfig {
     /// Base URL for the API
     pub base_url: String,
     /// API key for authentication
+    #[serde(serialize_with = "redaction::sensitive")]
     pub api_key: Option<String>,
     /// API version
     pub version: String,
     /// API timeout in seconds
     pub timeout_seconds: u64,
+    /// Connection pool and per-host limits for outbound requests
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+    /// Page sizes of paginated lists
+    #[serde(default)]
+    pub pagination: PaginationConfig,
 }
 
 impl Default for ApiConfig {
@@ -51,10 +333,118 @@ impl Default for ApiConfig {
             api_key: None,
             version: String::from("v1"),
             timeout_seconds: 30,
+            http_client: HttpClientConfig::default(),
+            pagination: PaginationConfig::default(),
+        }
+    }
+}
+
+/// Connection pool sizing and per-host concurrency for `HttpClient`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    /// Idle connections kept open per host; `None` keeps as many as were used
+    #[serde(default)]
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// Seconds an idle pooled connection is kept; `None` keeps it indefinitely
+    #[serde(default = "default_pool_idle_timeout")]
+    pub pool_idle_timeout_seconds: Option<u64>,
+
+    /// Seconds to wait for a connection to be established; `None` waits up to the request timeout
+    #[serde(default)]
+    pub connect_timeout_seconds: Option<u64>,
+
+    /// Headers sent with every outbound request, e.g. `User-Agent`; may carry credentials
+    #[serde(default, serialize_with = "redaction::sensitive")]
+    pub default_headers: HashMap<String, String>,
+
+    /// Requests in flight to any one host before further requests queue; `None` is unlimited
+    #[serde(default)]
+    pub max_in_flight_per_host: Option<usize>,
+
+    /// Per-host overrides keyed by `host:port`
+    #[serde(default)]
+    pub hosts: HashMap<String, HostLimitsConfig>,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_seconds: default_pool_idle_timeout(),
+            connect_timeout_seconds: None,
+            default_headers: HashMap::new(),
+            max_in_flight_per_host: None,
+            hosts: HashMap::new(),
+        }
+    }
+}
+
+/// Limits for a single downstream host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostLimitsConfig {
+    /// Requests in flight to this host before further requests queue
+    pub max_in_flight: usize,
+}
+
+fn default_pool_idle_timeout() -> Option<u64> {
+    Some(90)
+}
+
+/// What to do with a requested page size above the maximum
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizePage {
+    /// Answer 400 Bad Request
+    #[default]
+    Reject,
+    /// Serve the maximum page size instead
+    Clamp,
+}
+
+/// Default and maximum page size of a paginated list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageLimits {
+    /// Page size when the request does not give one
+    #[serde(default = "default_per_page")]
+    pub default_per_page: u32,
+    /// Largest page size a client may request
+    #[serde(default = "default_max_per_page")]
+    pub max_per_page: u32,
+    /// Whether larger requests are rejected or clamped
+    #[serde(default)]
+    pub oversize: OversizePage,
+}
+
+impl Default for PageLimits {
+    fn default() -> Self {
+        Self {
+            default_per_page: default_per_page(),
+            max_per_page: default_max_per_page(),
+            oversize: OversizePage::default(),
         }
     }
 }
 
+fn default_per_page() -> u32 {
+    crate::core::utils::pagination::DEFAULT_PER_PAGE
+}
+
+fn default_max_per_page() -> u32 {
+    crate::core::utils::pagination::MAX_PER_PAGE
+}
+
+/// Page size limits for all lists, with per-resource overrides
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaginationConfig {
+    /// Limits for resources without an override
+    #[serde(flatten)]
+    pub limits: PageLimits,
+    /// Overrides keyed by `ApiResource::resource_type`
+    #[serde(default)]
+    pub resources: HashMap<String, PageLimits>,
+}
+
 /// Authentication configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
@@ -64,6 +454,15 @@ pub struct AuthConfig {
     pub providers: HashMap<String, ProviderConfig>,
     #[serde(default)]
     pub debug: bool,
+    /// Role requirements by method and path, checked in order; the first match wins
+    #[serde(default)]
+    pub rules: Vec<AuthorizationRule>,
+    /// Outcome for requests matching no rule
+    #[serde(default)]
+    pub default_policy: DefaultPolicy,
+    /// What to do with requests carrying both a bearer token and an API key
+    #[serde(default)]
+    pub multiple_credentials: MultipleCredentials,
 }
 
 impl Default for AuthConfig {
@@ -73,10 +472,50 @@ impl Default for AuthConfig {
             default_provider: String::new(),
             providers: HashMap::new(),
             debug: false,
+            rules: Vec::new(),
+            default_policy: DefaultPolicy::default(),
+            multiple_credentials: MultipleCredentials::default(),
         }
     }
 }
 
+/// Roles required to call the routes matching a path pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationRule {
+    /// HTTP method, e.g. `POST`; any method when omitted
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Path pattern: literal segments, `{param}` for one segment, `{*rest}` for the remainder
+    pub path: String,
+    /// Any one of these roles grants access; empty makes the route public
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+/// Outcome for requests that match no authorization rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DefaultPolicy {
+    /// Leave unmatched routes to their own route-level checks
+    #[default]
+    Allow,
+    /// Reject unmatched routes with 403
+    Deny,
+}
+
+/// Handling of requests that carry both a bearer token and an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MultipleCredentials {
+    /// Reject with 400
+    #[default]
+    Reject,
+    /// Authenticate with the bearer token and ignore the API key
+    PreferBearer,
+    /// Authenticate with the API key and ignore the bearer token
+    PreferApiKey,
+}
+
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
@@ -103,6 +542,98 @@ fn default_log_format() -> String {
     "json".to_string()
 }
 
+/// Health check configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthChecksConfig {
+    /// Downstream HTTP dependencies checked for readiness, keyed by name
+    #[serde(default)]
+    pub downstream: HashMap<String, DownstreamHealthConfig>,
+
+    /// How long a readiness result is reused before checking again; 0 disables caching
+    #[serde(default = "default_readiness_cache_ms")]
+    pub readiness_cache_ms: u64,
+
+    /// Criticality overrides keyed by component name, e.g. `diskSpace: non_critical`
+    #[serde(default)]
+    pub criticality: HashMap<String, Criticality>,
+}
+
+impl Default for HealthChecksConfig {
+    fn default() -> Self {
+        Self {
+            downstream: HashMap::new(),
+            readiness_cache_ms: default_readiness_cache_ms(),
+            criticality: HashMap::new(),
+        }
+    }
+}
+
+/// How much a health component counts towards the overall status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Criticality {
+    /// Down makes the service down
+    Critical,
+    /// Down makes the service degraded
+    NonCritical,
+    /// Reported but never affects the overall status
+    Ignored,
+}
+
+impl Criticality {
+    /// `Critical` or `NonCritical`
+    pub fn from_critical(critical: bool) -> Self {
+        if critical {
+            Criticality::Critical
+        } else {
+            Criticality::NonCritical
+        }
+    }
+}
+
+fn default_readiness_cache_ms() -> u64 {
+    2000
+}
+
+/// Health check of a downstream HTTP dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownstreamHealthConfig {
+    /// URL answering GET with a 2xx status when the dependency is healthy
+    pub url: String,
+
+    /// How long to wait for the health URL
+    #[serde(default = "default_downstream_health_timeout")]
+    pub timeout_ms: u64,
+
+    /// Whether a failure makes this service not ready (503); otherwise it is reported as degraded
+    #[serde(default = "default_true")]
+    pub critical: bool,
+}
+
+fn default_downstream_health_timeout() -> u64 {
+    2000
+}
+
+/// Database migration configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MigrationsConfig {
+    /// Whether to apply pending migrations at startup
+    #[serde(default)]
+    pub run_on_startup: bool,
+
+    /// Connection URL of the database to migrate
+    #[serde(default, serialize_with = "redaction::sensitive")]
+    pub database_url: Option<String>,
+}
+
+/// Entity id configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct IdsConfig {
+    /// Format of ids generated for new entities (`uuid` or `ulid`)
+    #[serde(default)]
+    pub strategy: IdStrategy,
+}
+
 /// Reliability configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ReliabilityConfig {
@@ -114,6 +645,11 @@ pub struct ReliabilityConfig {
     #[serde(default)]
     pub circuit_breaker: CircuitBreakerConfig,
 
+    /// Circuit breakers for external dependencies, keyed by dependency name;
+    /// dependencies without an entry use `circuit_breaker`
+    #[serde(default)]
+    pub circuit_breakers: HashMap<String, CircuitBreakerConfig>,
+
     /// Rate limiting configuration
     #[serde(default)]
     pub rate_limit: RateLimitConfig,
@@ -125,6 +661,14 @@ pub struct ReliabilityConfig {
     /// Concurrency limits
     #[serde(default)]
     pub concurrency: ConcurrencyConfig,
+
+    /// Bulkheads for external dependencies, keyed by dependency name
+    #[serde(default)]
+    pub bulkheads: HashMap<String, BulkheadConfig>,
+
+    /// Fault injection for resilience testing; ignored in production
+    #[serde(default)]
+    pub chaos: ChaosConfig,
 }
 
 /// Retry configuration
@@ -201,6 +745,13 @@ pub struct RateLimitConfig {
     /// Whether to apply per-client rate limiting
     #[serde(default = "default_false")]
     pub per_client: bool,
+
+    /// Separate quota for mutating methods (POST, PUT, PATCH, DELETE)
+    ///
+    /// When set, `requests_per_window` only applies to safe methods (GET,
+    /// HEAD, OPTIONS). When unset, all methods share `requests_per_window`.
+    #[serde(default)]
+    pub write_requests_per_window: Option<u32>,
 }
 
 /// Timeout configuration
@@ -213,6 +764,18 @@ pub struct TimeoutConfig {
     /// Timeout in seconds
     #[serde(default = "default_timeout")]
     pub timeout_seconds: u64,
+
+    /// Seconds the client has to send the request body; answered with 408
+    #[serde(default)]
+    pub body_read_timeout_seconds: Option<u64>,
+
+    /// Seconds the handler has to produce a response; answered with 504
+    #[serde(default)]
+    pub handler_timeout_seconds: Option<u64>,
+
+    /// Seconds allowed for writing the response body before it is cut short
+    #[serde(default)]
+    pub response_write_timeout_seconds: Option<u64>,
 }
 
 /// Concurrency configuration
@@ -227,6 +790,50 @@ pub struct ConcurrencyConfig {
     pub max_concurrent_requests: u32,
 }
 
+/// Bulkhead isolating calls to a single external dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkheadConfig {
+    /// Maximum number of concurrent calls to the dependency
+    #[serde(default = "default_bulkhead_concurrency")]
+    pub max_concurrent_calls: u32,
+
+    /// Maximum number of calls waiting for a slot; further calls are rejected
+    #[serde(default = "default_bulkhead_queue")]
+    pub max_queued_calls: u32,
+
+    /// How long a queued call waits for a slot before it is shed
+    #[serde(default = "default_bulkhead_wait")]
+    pub max_wait_ms: u64,
+}
+
+/// Fault injection for exercising retries and circuit breakers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    /// Whether faults are injected; never applied in production
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+
+    /// Percentage of requests (0-100) answered with `error_status`
+    #[serde(default)]
+    pub error_percentage: f64,
+
+    /// Status returned for injected errors
+    #[serde(default = "default_chaos_error_status")]
+    pub error_status: u16,
+
+    /// Percentage of requests (0-100) delayed by `latency_ms`
+    #[serde(default)]
+    pub latency_percentage: f64,
+
+    /// Latency added to delayed requests, in milliseconds
+    #[serde(default)]
+    pub latency_ms: u64,
+
+    /// Only inject faults into requests under this path
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+}
+
 impl Default for RetryConfig {
     fn default() -> Self {
         Self {
@@ -260,6 +867,7 @@ impl Default for RateLimitConfig {
             requests_per_window: default_rate_limit(),
             window_seconds: default_rate_window(),
             per_client: default_false(),
+            write_requests_per_window: None,
         }
     }
 }
@@ -269,6 +877,9 @@ impl Default for TimeoutConfig {
         Self {
             enabled: default_true(),
             timeout_seconds: default_timeout(),
+            body_read_timeout_seconds: None,
+            handler_timeout_seconds: None,
+            response_write_timeout_seconds: None,
         }
     }
 }
@@ -282,6 +893,29 @@ impl Default for ConcurrencyConfig {
     }
 }
 
+impl Default for BulkheadConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_calls: default_bulkhead_concurrency(),
+            max_queued_calls: default_bulkhead_queue(),
+            max_wait_ms: default_bulkhead_wait(),
+        }
+    }
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_false(),
+            error_percentage: 0.0,
+            error_status: default_chaos_error_status(),
+            latency_percentage: 0.0,
+            latency_ms: 0,
+            path_prefix: None,
+        }
+    }
+}
+
 fn default_true() -> bool {
     true
 }
@@ -326,6 +960,22 @@ fn default_max_concurrency() -> u32 {
     100
 }
 
+fn default_bulkhead_concurrency() -> u32 {
+    10
+}
+
+fn default_bulkhead_queue() -> u32 {
+    10
+}
+
+fn default_bulkhead_wait() -> u64 {
+    1000
+}
+
+fn default_chaos_error_status() -> u16 {
+    503
+}
+
 fn default_retry_status_codes() -> Vec<u16> {
     vec![408, 429, 500, 502, 503, 504]
 }
@@ -383,23 +1033,37 @@ pub struct OpenApiConfig {
     /// Name of the OpenAPI spec file (just the filename, not the full path)
     #[serde(default = "default_openapi_spec_file")]
     pub spec_file: String,
+
+    /// Largest spec file that will be loaded; bigger ones are replaced by an empty spec
+    #[serde(default = "default_openapi_max_spec_bytes")]
+    pub max_spec_bytes: u64,
+
+    /// Serve Swagger UI and the spec; unset means everywhere but production
+    #[serde(default)]
+    pub enabled: Option<bool>,
 }
 
 impl Default for OpenApiConfig {
     fn default() -> Self {
         Self {
             spec_file: default_openapi_spec_file(),
+            max_spec_bytes: default_openapi_max_spec_bytes(),
+            enabled: None,
         }
     }
 }
 
+fn default_openapi_max_spec_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
 /// Default name for the OpenAPI spec file
 fn default_openapi_spec_file() -> String {
     "navius-swagger.yaml".to_string()
 }
 
 /// Environment type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 pub enum EnvironmentType {
     #[default]
     Development,
@@ -453,6 +1117,14 @@ pub struct EndpointSecurityConfig {
     /// Whether to show sensitive info in health checks - usually false in production
     #[serde(default = "default_false")]
     pub expose_sensitive_info: bool,
+
+    /// Whether `/actuator/config` dumps the effective (redacted) configuration
+    #[serde(default = "default_false")]
+    pub expose_config: bool,
+
+    /// Whether `/actuator/loggers` can view and change log levels at runtime
+    #[serde(default = "default_false")]
+    pub expose_loggers: bool,
 }
 
 impl Default for EndpointSecurityConfig {
@@ -463,6 +1135,8 @@ impl Default for EndpointSecurityConfig {
             public_detailed_health: false,
             public_metrics: false,
             expose_sensitive_info: false,
+            expose_config: false,
+            expose_loggers: false,
         }
     }
 }
@@ -485,6 +1159,8 @@ pub fn get_endpoint_security_for_env(
             public_detailed_health: true,
             public_metrics: true,
             expose_sensitive_info: true,
+            expose_config: true,
+            expose_loggers: true,
         },
         EnvironmentType::Testing => EndpointSecurityConfig {
             public_health: true,
@@ -492,6 +1168,8 @@ pub fn get_endpoint_security_for_env(
             public_detailed_health: true,
             public_metrics: true,
             expose_sensitive_info: false,
+            expose_config: true,
+            expose_loggers: true,
         },
         EnvironmentType::Staging => EndpointSecurityConfig {
             public_health: true,
@@ -499,6 +1177,8 @@ pub fn get_endpoint_security_for_env(
             public_detailed_health: false,
             public_metrics: false,
             expose_sensitive_info: false,
+            expose_config: false,
+            expose_loggers: false,
         },
         EnvironmentType::Production => EndpointSecurityConfig {
             public_health: true,
@@ -506,6 +1186,8 @@ pub fn get_endpoint_security_for_env(
             public_detailed_health: false,
             public_metrics: false,
             expose_sensitive_info: false,
+            expose_config: false,
+            expose_loggers: false,
         },
     }
 }
@@ -556,6 +1238,18 @@ pub struct AppConfig {
     /// Feature flags and configuration
     #[serde(default)]
     pub features: FeaturesConfig,
+
+    /// Database migration configuration
+    #[serde(default)]
+    pub migrations: MigrationsConfig,
+
+    /// Health check configuration
+    #[serde(default)]
+    pub health: HealthChecksConfig,
+
+    /// Entity id configuration
+    #[serde(default)]
+    pub ids: IdsConfig,
 }
 
 /// Feature flags and configurations
@@ -568,6 +1262,10 @@ pub struct FeaturesConfig {
     /// Feature-specific configuration
     #[serde(default)]
     pub config: HashMap<String, Value>,
+
+    /// Per-request overrides through the `X-Feature-Override` header
+    #[serde(default)]
+    pub overrides: FeatureOverridesConfig,
 }
 
 impl Default for FeaturesConfig {
@@ -575,10 +1273,23 @@ impl Default for FeaturesConfig {
         Self {
             enabled: Vec::new(),
             config: HashMap::new(),
+            overrides: FeatureOverridesConfig::default(),
         }
     }
 }
 
+/// Per-request feature overrides for testing; never applied in production
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureOverridesConfig {
+    /// Whether the override header is honoured
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Client networks (CIDR) allowed to send overrides
+    #[serde(default)]
+    pub trusted_sources: Vec<String>,
+}
+
 /// Application metadata configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApplicationConfig {
@@ -601,6 +1312,16 @@ impl AppConfig {
         Duration::from_secs(self.cache.ttl_seconds)
     }
 
+    /// Generator for new entity ids, following `ids.strategy`
+    pub fn id_generator(&self) -> std::sync::Arc<dyn IdGenerator> {
+        self.ids.strategy.generator()
+    }
+
+    /// Clock skew tolerated when checking expiry of shared cache entries
+    pub fn cache_clock_skew_tolerance(&self) -> Duration {
+        Duration::from_millis(self.cache.clock_skew_tolerance_ms)
+    }
+
     /// Get the OpenAPI spec file path
     pub fn openapi_spec_path(&self) -> String {
         // Hardcoded directory + filename from config
@@ -614,6 +1335,13 @@ impl AppConfig {
         format!("/actuator/docs/{}", self.openapi.spec_file)
     }
 
+    /// Whether the docs routes are served, per `openapi.enabled` or the environment
+    pub fn docs_enabled(&self) -> bool {
+        self.openapi
+            .enabled
+            .unwrap_or(self.environment != EnvironmentType::Production)
+    }
+
     pub fn api_url(&self) -> &str {
         &self.api.base_url
     }
@@ -622,10 +1350,466 @@ impl AppConfig {
     pub fn enabled_features(&self) -> HashSet<String> {
         self.features.enabled.iter().cloned().collect()
     }
+
+    /// Semantically validate the configuration, reporting every problem at once
+    pub fn validate(&self) -> Result<(), Vec<ConfigValidationError>> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, field: &str, reason: &str| {
+            if !ok {
+                errors.push(ConfigValidationError::new(field, reason));
+            }
+        };
+
+        // Server
+        check(
+            !self.server.host.trim().is_empty(),
+            "server.host",
+            "must not be empty",
+        );
+        check(
+            self.server.port != 0,
+            "server.port",
+            "must be between 1 and 65535",
+        );
+        check(
+            self.server.timeout_seconds > 0,
+            "server.timeout_seconds",
+            "must be greater than 0",
+        );
+        check(
+            matches!(self.server.protocol.as_str(), "http" | "https"),
+            "server.protocol",
+            "must be 'http' or 'https'",
+        );
+        check(
+            self.server.tuning.http1 || self.server.tuning.http2,
+            "server.tuning",
+            "must enable at least one of http1 and http2",
+        );
+
+        for cidr in &self.server.trusted_proxies {
+            check(
+                cidr.parse::<crate::core::utils::trusted_proxy::Cidr>()
+                    .is_ok(),
+                "server.trusted_proxies",
+                &format!("'{}' is not a valid CIDR range", cidr),
+            );
+        }
+
+        for cidr in &self.features.overrides.trusted_sources {
+            check(
+                cidr.parse::<crate::core::utils::trusted_proxy::Cidr>()
+                    .is_ok(),
+                "features.overrides.trusted_sources",
+                &format!("'{}' is not a valid CIDR range", cidr),
+            );
+        }
+
+        let cors = &self.server.cors;
+        check(
+            !(cors.allow_credentials && CorsConfig::is_wildcard(&cors.allowed_origins)),
+            "server.cors.allowed_origins",
+            "must list specific origins when allow_credentials is true",
+        );
+        check(
+            !(cors.allow_credentials && CorsConfig::is_wildcard(&cors.exposed_headers)),
+            "server.cors.exposed_headers",
+            "must list specific headers when allow_credentials is true",
+        );
+        for origin in &cors.allowed_origins {
+            check(
+                origin == "*" || crate::core::utils::cors::parse_origin(origin.trim()).is_ok(),
+                "server.cors.allowed_origins",
+                &format!(
+                    "'{}' is not a valid origin, expected scheme://host[:port]",
+                    origin
+                ),
+            );
+        }
+        for method in &cors.allowed_methods {
+            check(
+                method == "*" || method.parse::<axum::http::Method>().is_ok(),
+                "server.cors.allowed_methods",
+                &format!("'{}' is not a valid HTTP method", method),
+            );
+        }
+
+        check(
+            self.openapi.max_spec_bytes > 0,
+            "openapi.max_spec_bytes",
+            "must be greater than 0",
+        );
+
+        // Upstream API
+        check(
+            self.api.timeout_seconds > 0,
+            "api.timeout_seconds",
+            "must be greater than 0",
+        );
+
+        let http_client = &self.api.http_client;
+        check(
+            http_client.max_in_flight_per_host != Some(0),
+            "api.http_client.max_in_flight_per_host",
+            "must be greater than 0 when set",
+        );
+        check(
+            http_client.connect_timeout_seconds != Some(0),
+            "api.http_client.connect_timeout_seconds",
+            "must be greater than 0 when set",
+        );
+        for (name, value) in &http_client.default_headers {
+            check(
+                axum::http::HeaderName::try_from(name.as_str()).is_ok()
+                    && axum::http::HeaderValue::from_str(value).is_ok(),
+                &format!("api.http_client.default_headers.{}", name),
+                "must be a valid header name and value",
+            );
+        }
+        for (host, limits) in &http_client.hosts {
+            check(
+                limits.max_in_flight > 0,
+                &format!("api.http_client.hosts.{}.max_in_flight", host),
+                "must be greater than 0",
+            );
+        }
+
+        let pagination = &self.api.pagination;
+        let page_limits = std::iter::once(("api.pagination".to_string(), &pagination.limits))
+            .chain(pagination.resources.iter().map(|(resource, limits)| {
+                (format!("api.pagination.resources.{}", resource), limits)
+            }));
+        for (field, limits) in page_limits {
+            check(
+                limits.default_per_page > 0,
+                &format!("{}.default_per_page", field),
+                "must be greater than 0",
+            );
+            check(
+                limits.max_per_page >= limits.default_per_page,
+                &format!("{}.max_per_page", field),
+                "must be at least default_per_page",
+            );
+        }
+
+        // Logging
+        check(
+            matches!(
+                self.logging.level.to_lowercase().as_str(),
+                "trace" | "debug" | "info" | "warn" | "error"
+            ),
+            "logging.level",
+            "must be one of trace, debug, info, warn, error",
+        );
+
+        // Cache
+        if self.cache.enabled {
+            check(
+                self.cache.ttl_seconds > 0,
+                "cache.ttl_seconds",
+                "must be greater than 0 when the cache is enabled",
+            );
+            check(
+                self.cache.max_capacity > 0,
+                "cache.max_capacity",
+                "must be greater than 0 when the cache is enabled",
+            );
+        }
+        if self.cache.warming.enabled {
+            check(
+                self.cache.warming.concurrency > 0,
+                "cache.warming.concurrency",
+                "must be greater than 0 when warming is enabled",
+            );
+            check(
+                self.cache.warming.budget_ms > 0,
+                "cache.warming.budget_ms",
+                "must be greater than 0 when warming is enabled",
+            );
+        }
+
+        // Authentication
+        if self.auth.enabled {
+            check(
+                !self.auth.providers.is_empty(),
+                "auth.providers",
+                "must configure at least one provider when auth is enabled",
+            );
+
+            match self.auth.providers.get(&self.auth.default_provider) {
+                None => check(
+                    false,
+                    "auth.default_provider",
+                    &format!(
+                        "'{}' is not a configured provider",
+                        self.auth.default_provider
+                    ),
+                ),
+                Some(provider) => {
+                    for role in ["admin", "read_only", "full_access"] {
+                        let configured = provider
+                            .role_mappings
+                            .get(role)
+                            .is_some_and(|roles| !roles.is_empty());
+                        check(
+                            configured,
+                            &format!(
+                                "auth.providers.{}.role_mappings.{}",
+                                self.auth.default_provider, role
+                            ),
+                            "must map at least one role",
+                        );
+                    }
+                }
+            }
+        }
+        for (name, provider) in &self.auth.providers {
+            let field = format!("auth.providers.{}.allowed_algorithms", name);
+            check(
+                !provider.allowed_algorithms.is_empty(),
+                &field,
+                "must allow at least one algorithm",
+            );
+            for alg in &provider.allowed_algorithms {
+                if alg.eq_ignore_ascii_case("none") {
+                    check(false, &field, "'none' is never accepted");
+                } else {
+                    check(
+                        DEFAULT_JWT_ALGORITHMS
+                            .iter()
+                            .chain(HMAC_JWT_ALGORITHMS)
+                            .any(|known| known == alg),
+                        &field,
+                        &format!("'{}' is not a supported JWT algorithm", alg),
+                    );
+                }
+            }
+        }
+        if let Some(header) = self
+            .auth
+            .providers
+            .get("api_key")
+            .and_then(|provider| provider.provider_specific.get("header"))
+        {
+            check(
+                header
+                    .as_str()
+                    .is_some_and(|name| axum::http::HeaderName::try_from(name).is_ok()),
+                "auth.providers.api_key.provider_specific.header",
+                "must be a valid header name",
+            );
+        }
+        for (index, rule) in self.auth.rules.iter().enumerate() {
+            if let Some(method) = &rule.method {
+                check(
+                    method.parse::<axum::http::Method>().is_ok(),
+                    &format!("auth.rules[{}].method", index),
+                    &format!("'{}' is not an HTTP method", method),
+                );
+            }
+            check(
+                rule.path.starts_with('/'),
+                &format!("auth.rules[{}].path", index),
+                "must start with '/'",
+            );
+        }
+
+        // Reliability
+        let reliability = &self.reliability;
+        if reliability.retry.enabled {
+            check(
+                reliability.retry.max_attempts > 0,
+                "reliability.retry.max_attempts",
+                "must be at least 1",
+            );
+            check(
+                reliability.retry.base_delay_ms > 0,
+                "reliability.retry.base_delay_ms",
+                "must be greater than 0",
+            );
+            check(
+                reliability.retry.max_delay_ms >= reliability.retry.base_delay_ms,
+                "reliability.retry.max_delay_ms",
+                "must not be less than base_delay_ms",
+            );
+        }
+        let named_breakers = reliability
+            .circuit_breakers
+            .iter()
+            .map(|(name, breaker)| (format!("reliability.circuit_breakers.{}", name), breaker));
+        let breakers = std::iter::once((
+            "reliability.circuit_breaker".to_string(),
+            &reliability.circuit_breaker,
+        ))
+        .chain(named_breakers);
+        for (prefix, breaker) in breakers.filter(|(_, breaker)| breaker.enabled) {
+            check(
+                (1..=100).contains(&breaker.failure_percentage),
+                &format!("{}.failure_percentage", prefix),
+                "must be between 1 and 100",
+            );
+            check(
+                breaker.window_seconds > 0,
+                &format!("{}.window_seconds", prefix),
+                "must be greater than 0",
+            );
+            check(
+                breaker.success_threshold > 0,
+                &format!("{}.success_threshold", prefix),
+                "must be at least 1",
+            );
+        }
+        if reliability.rate_limit.enabled {
+            check(
+                reliability.rate_limit.requests_per_window > 0,
+                "reliability.rate_limit.requests_per_window",
+                "must be greater than 0",
+            );
+            check(
+                reliability.rate_limit.window_seconds > 0,
+                "reliability.rate_limit.window_seconds",
+                "must be greater than 0",
+            );
+            check(
+                reliability.rate_limit.write_requests_per_window != Some(0),
+                "reliability.rate_limit.write_requests_per_window",
+                "must be greater than 0",
+            );
+        }
+        if reliability.chaos.enabled {
+            let chaos = &reliability.chaos;
+            check(
+                (0.0..=100.0).contains(&chaos.error_percentage),
+                "reliability.chaos.error_percentage",
+                "must be between 0 and 100",
+            );
+            check(
+                (0.0..=100.0).contains(&chaos.latency_percentage),
+                "reliability.chaos.latency_percentage",
+                "must be between 0 and 100",
+            );
+            check(
+                (400..=599).contains(&chaos.error_status),
+                "reliability.chaos.error_status",
+                "must be a 4xx or 5xx status",
+            );
+            if let Some(prefix) = &chaos.path_prefix {
+                check(
+                    prefix.starts_with('/'),
+                    "reliability.chaos.path_prefix",
+                    "must start with '/'",
+                );
+            }
+        }
+        if reliability.timeout.enabled {
+            check(
+                reliability.timeout.timeout_seconds > 0,
+                "reliability.timeout.timeout_seconds",
+                "must be greater than 0",
+            );
+            let phases = [
+                (
+                    reliability.timeout.body_read_timeout_seconds,
+                    "reliability.timeout.body_read_timeout_seconds",
+                ),
+                (
+                    reliability.timeout.handler_timeout_seconds,
+                    "reliability.timeout.handler_timeout_seconds",
+                ),
+                (
+                    reliability.timeout.response_write_timeout_seconds,
+                    "reliability.timeout.response_write_timeout_seconds",
+                ),
+            ];
+            for (seconds, field) in phases {
+                check(seconds != Some(0), field, "must be greater than 0 when set");
+            }
+        }
+        if reliability.concurrency.enabled {
+            check(
+                reliability.concurrency.max_concurrent_requests > 0,
+                "reliability.concurrency.max_concurrent_requests",
+                "must be greater than 0",
+            );
+        }
+        for (name, bulkhead) in &reliability.bulkheads {
+            check(
+                bulkhead.max_concurrent_calls > 0,
+                &format!("reliability.bulkheads.{}.max_concurrent_calls", name),
+                "must be greater than 0",
+            );
+        }
+
+        // Health checks
+        for (name, dependency) in &self.health.downstream {
+            check(
+                dependency.url.starts_with("http://") || dependency.url.starts_with("https://"),
+                &format!("health.downstream.{}.url", name),
+                "must be an http(s) URL",
+            );
+            check(
+                dependency.timeout_ms > 0,
+                &format!("health.downstream.{}.timeout_ms", name),
+                "must be greater than 0",
+            );
+        }
+
+        // Migrations
+        if self.migrations.run_on_startup {
+            check(
+                self.migrations
+                    .database_url
+                    .as_ref()
+                    .is_some_and(|url| !url.trim().is_empty()),
+                "migrations.database_url",
+                "is required when migrations.run_on_startup is enabled",
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
 
+/// A semantic problem with a configuration value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigValidationError {
+    /// Dotted path of the offending field, e.g. `server.port`
+    pub field: String,
+    /// Why the value is invalid
+    pub reason: String,
+}
+
+impl ConfigValidationError {
+    pub fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.reason)
+    }
+}
+
+impl std::error::Error for ConfigValidationError {}
+
 /// Load configuration from files and environment variables
+///
+/// `secret://name` values are resolved from the environment; use
+/// [`load_config_with_secrets`] to resolve them from another store.
 pub fn load_config() -> Result<AppConfig, ConfigError> {
+    load_config_with_secrets(&EnvSecretProvider::new())
+}
+
+/// Load configuration, resolving `secret://name` values through `secrets`
+pub fn load_config_with_secrets(secrets: &dyn SecretProvider) -> Result<AppConfig, ConfigError> {
     // Load .env file for secrets and overrides
     let _ = dotenv();
 
@@ -673,45 +1857,6 @@ pub fn load_config() -> Result<AppConfig, ConfigError> {
     // Deserialize the config into our AppConfig struct
     let mut app_config: AppConfig = config.try_deserialize()?;
 
-    // Validate critical configuration values
-    if app_config.auth.enabled {
-        // Get the default provider config
-        let default_provider = app_config.auth.default_provider.clone();
-        if let Some(provider_config) = app_config.auth.providers.get(&default_provider) {
-            // Check roles configuration - panic if no roles are configured
-            if provider_config
-                .role_mappings
-                .get("admin")
-                .unwrap_or(&Vec::new())
-                .is_empty()
-            {
-                panic!("No admin roles configured. Please specify admin_roles in configuration.");
-            }
-
-            if provider_config
-                .role_mappings
-                .get("read_only")
-                .unwrap_or(&Vec::new())
-                .is_empty()
-            {
-                panic!(
-                    "No read-only roles configured. Please specify read_only_roles in configuration."
-                );
-            }
-
-            if provider_config
-                .role_mappings
-                .get("full_access")
-                .unwrap_or(&Vec::new())
-                .is_empty()
-            {
-                panic!(
-                    "No full access roles configured. Please specify full_access_roles in configuration."
-                );
-            }
-        }
-    }
-
     // Manually set provider configuration from environment variables if they exist
     // This ensures the environment variables are properly mapped to the configuration
     let default_provider = app_config.auth.default_provider.clone();
@@ -759,6 +1904,23 @@ pub fn load_config() -> Result<AppConfig, ConfigError> {
         }
     }
 
+    // Resolve secret:// references before validating the final values
+    let app_config = resolve_secrets(app_config, secrets)?;
+
+    // Fail fast on semantically invalid configuration, reporting every problem
+    if let Err(errors) = app_config.validate() {
+        let details = errors
+            .iter()
+            .map(|e| format!("  - {}", e))
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(ConfigError::Message(format!(
+            "Invalid configuration ({} problems):\n{}",
+            errors.len(),
+            details
+        )));
+    }
+
     Ok(app_config)
 }
 
@@ -780,6 +1942,26 @@ pub struct ProviderConfig {
     pub audience: String,
     #[serde(default)]
     pub role_mappings: RoleMappings,
-    #[serde(default)]
+    #[serde(default, serialize_with = "redaction::sensitive_entries")]
     pub provider_specific: HashMap<String, Value>,
+    /// JWT `alg` values accepted in token headers; `none` is always rejected
+    #[serde(default = "default_allowed_algorithms")]
+    pub allowed_algorithms: Vec<String>,
+}
+
+/// JWT algorithms a provider accepts unless configured otherwise
+///
+/// Asymmetric only, so a public key can never be used as an HMAC secret.
+pub const DEFAULT_JWT_ALGORITHMS: &[&str] = &[
+    "RS256", "RS384", "RS512", "PS256", "PS384", "PS512", "ES256", "ES384", "EdDSA",
+];
+
+/// Symmetric JWT algorithms, accepted only when listed explicitly
+const HMAC_JWT_ALGORITHMS: &[&str] = &["HS256", "HS384", "HS512"];
+
+pub fn default_allowed_algorithms() -> Vec<String> {
+    DEFAULT_JWT_ALGORITHMS
+        .iter()
+        .map(|alg| alg.to_string())
+        .collect()
 }
@@ -0,0 +1,162 @@
+//! Audit trail of configuration changes
+//!
+//! Whenever configuration is reloaded, pass the previous and new
+//! [`AppConfig`] to [`ConfigAuditLog::record`]. Each change becomes a
+//! [`ConfigChangeEvent`] listing the affected keys (dotted paths such as
+//! `reliability.circuit_breaker.failure_percentage`) with their old and new
+//! values. Sensitive fields are compared on their real values but reported
+//! redacted, so a rotated secret shows up as changed without being exposed.
+//! Settings changed at runtime without a reload, such as log levels set
+//! through `/actuator/loggers`, are recorded with
+//! [`ConfigAuditLog::record_changes`].
+//!
+//! The last [`DEFAULT_AUDIT_CAPACITY`] events of [`ConfigAuditLog::global`]
+//! are served at `/actuator/config/changes`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use super::AppConfig;
+use super::redaction;
+
+/// Events kept by the global audit log
+pub const DEFAULT_AUDIT_CAPACITY: usize = 50;
+
+/// One configuration key whose value changed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigChange {
+    /// Dotted path of the key
+    pub key: String,
+    /// Previous value, redacted if sensitive; `null` if the key was added
+    pub old: Value,
+    /// New value, redacted if sensitive; `null` if the key was removed
+    pub new: Value,
+}
+
+/// A configuration reload that changed at least one key
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChangeEvent {
+    pub timestamp: DateTime<Utc>,
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Keys that differ between `old` and `new`, sorted by key
+pub fn diff_configs(old: &AppConfig, new: &AppConfig) -> serde_json::Result<Vec<ConfigChange>> {
+    let old_raw = flatten(serde_json::to_value(old)?);
+    let new_raw = flatten(serde_json::to_value(new)?);
+    let old_redacted = flatten(redaction::to_redacted_value(old)?);
+    let new_redacted = flatten(redaction::to_redacted_value(new)?);
+
+    let mut keys: Vec<&String> = old_raw.keys().chain(new_raw.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    Ok(keys
+        .into_iter()
+        .filter(|key| old_raw.get(*key) != new_raw.get(*key))
+        .map(|key| ConfigChange {
+            key: key.clone(),
+            old: old_redacted.get(key).cloned().unwrap_or(Value::Null),
+            new: new_redacted.get(key).cloned().unwrap_or(Value::Null),
+        })
+        .collect())
+}
+
+/// Flatten nested objects into dotted keys; arrays are compared whole
+fn flatten(value: Value) -> BTreeMap<String, Value> {
+    fn walk(prefix: String, value: Value, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    let path = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(path, value, out);
+                }
+            }
+            value => {
+                out.insert(prefix, value);
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk(String::new(), value, &mut out);
+    out
+}
+
+/// Bounded log of the most recent configuration changes
+#[derive(Debug, Clone)]
+pub struct ConfigAuditLog {
+    events: Arc<Mutex<VecDeque<ConfigChangeEvent>>>,
+    capacity: usize,
+}
+
+impl ConfigAuditLog {
+    /// Log keeping the last `capacity` events
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Process-wide log served by the actuator
+    pub fn global() -> &'static ConfigAuditLog {
+        static GLOBAL: OnceLock<ConfigAuditLog> = OnceLock::new();
+        GLOBAL.get_or_init(|| ConfigAuditLog::new(DEFAULT_AUDIT_CAPACITY))
+    }
+
+    /// Record a reload from `old` to `new`, returning the event if anything changed
+    pub fn record(&self, old: &AppConfig, new: &AppConfig) -> Option<ConfigChangeEvent> {
+        match diff_configs(old, new) {
+            Ok(changes) => self.record_changes(changes),
+            Err(e) => {
+                warn!("Failed to diff configuration for audit: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Record changes made at runtime, returning the event unless `changes` is empty
+    ///
+    /// Values must already be redacted where sensitive.
+    pub fn record_changes(&self, changes: Vec<ConfigChange>) -> Option<ConfigChangeEvent> {
+        if changes.is_empty() {
+            return None;
+        }
+
+        for change in &changes {
+            info!(
+                key = %change.key,
+                old = %change.old,
+                new = %change.new,
+                "Configuration changed"
+            );
+        }
+
+        let event = ConfigChangeEvent {
+            timestamp: Utc::now(),
+            changes,
+        };
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+        Some(event)
+    }
+
+    /// Recorded events, most recent first
+    pub fn recent(&self) -> Vec<ConfigChangeEvent> {
+        let events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        events.iter().rev().cloned().collect()
+    }
+}
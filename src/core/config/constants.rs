@@ -1,6 +1,14 @@
 //! Constants used throughout the application configuration
 //! This centralizes common values to reduce duplication
 
+/// Environment-derived constants fixed for the lifetime of the process
+pub mod runtime;
+
+/// Constants with per-environment values and a global default
+pub mod scoped;
+
+pub use scoped::get;
+
 /// Authentication-related constants
 pub mod auth {
     /// URL formats
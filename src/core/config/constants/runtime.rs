@@ -0,0 +1,259 @@
+//! Runtime constants
+//!
+//! Values that are fixed for the lifetime of a process but only known at
+//! startup (build hash, region, deployment id, ...). They are populated once
+//! from the environment and configuration via [`init`] and then read with
+//! [`get`] instead of calling `env::var` throughout the code base.
+
+use lazy_static::lazy_static;
+use std::any::{Any, type_name};
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::sync::RwLock;
+use tracing::debug;
+
+use crate::core::config::app_config::AppConfig;
+
+/// Well-known runtime constant keys
+pub mod keys {
+    /// Git commit hash of the running build
+    pub const BUILD_HASH: &str = "build.hash";
+    /// Crate version of the running build
+    pub const BUILD_VERSION: &str = "build.version";
+    /// Cloud region the instance runs in
+    pub const REGION: &str = "deployment.region";
+    /// Identifier of the current deployment
+    pub const DEPLOYMENT_ID: &str = "deployment.id";
+    /// Environment type from configuration
+    pub const ENVIRONMENT: &str = "deployment.environment";
+}
+
+/// Environment variables read into runtime constants
+pub mod env_vars {
+    pub const BUILD_HASH: &str = "NAVIUS_BUILD_HASH";
+    pub const REGION: &str = "NAVIUS_REGION";
+    pub const DEPLOYMENT_ID: &str = "NAVIUS_DEPLOYMENT_ID";
+}
+
+/// Error looking up a runtime constant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeConstantError {
+    /// No value registered under the key
+    Missing { key: String },
+    /// A value exists but has a different type
+    TypeMismatch {
+        key: String,
+        expected: &'static str,
+        actual: &'static str,
+    },
+}
+
+impl fmt::Display for RuntimeConstantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeConstantError::Missing { key } => {
+                write!(f, "Runtime constant '{}' is not set", key)
+            }
+            RuntimeConstantError::TypeMismatch {
+                key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Runtime constant '{}' is a {}, not a {}",
+                key, actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeConstantError {}
+
+struct Entry {
+    value: Box<dyn Any + Send + Sync>,
+    type_name: &'static str,
+}
+
+/// Typed key/value registry of runtime constants
+#[derive(Default)]
+pub struct RuntimeConstants {
+    entries: RwLock<HashMap<String, Entry>>,
+}
+
+impl fmt::Debug for RuntimeConstants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeConstants")
+            .field("keys", &self.keys())
+            .finish()
+    }
+}
+
+impl RuntimeConstants {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a constant, overriding any previous value
+    pub fn set<T: Any + Send + Sync>(&self, key: &str, value: T) {
+        self.entries.write().unwrap().insert(
+            key.to_string(),
+            Entry {
+                value: Box::new(value),
+                type_name: type_name::<T>(),
+            },
+        );
+    }
+
+    /// Get a constant of type `T`
+    pub fn get<T: Any + Clone>(&self, key: &str) -> Result<T, RuntimeConstantError> {
+        let entries = self.entries.read().unwrap();
+        let entry = entries
+            .get(key)
+            .ok_or_else(|| RuntimeConstantError::Missing {
+                key: key.to_string(),
+            })?;
+
+        entry
+            .value
+            .downcast_ref::<T>()
+            .cloned()
+            .ok_or_else(|| RuntimeConstantError::TypeMismatch {
+                key: key.to_string(),
+                expected: type_name::<T>(),
+                actual: entry.type_name,
+            })
+    }
+
+    /// Whether a constant is set
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.read().unwrap().contains_key(key)
+    }
+
+    /// All registered keys, sorted
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.entries.read().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Populate the well-known constants from the environment and configuration
+    pub fn populate(&self, config: &AppConfig) {
+        self.set(keys::BUILD_VERSION, env!("CARGO_PKG_VERSION").to_string());
+        self.set(keys::ENVIRONMENT, config.environment.to_string());
+
+        for (key, var) in [
+            (keys::BUILD_HASH, env_vars::BUILD_HASH),
+            (keys::REGION, env_vars::REGION),
+            (keys::DEPLOYMENT_ID, env_vars::DEPLOYMENT_ID),
+        ] {
+            if let Some(value) = env::var(var).ok().filter(|value| !value.is_empty()) {
+                self.set(key, value);
+            }
+        }
+
+        debug!("Runtime constants populated: {:?}", self.keys());
+    }
+}
+
+lazy_static! {
+    static ref RUNTIME_CONSTANTS: RuntimeConstants = RuntimeConstants::new();
+}
+
+/// Populate the process-wide registry at startup
+pub fn init(config: &AppConfig) {
+    RUNTIME_CONSTANTS.populate(config);
+}
+
+/// Set a process-wide runtime constant
+pub fn set<T: Any + Send + Sync>(key: &str, value: T) {
+    RUNTIME_CONSTANTS.set(key, value);
+}
+
+/// Get a process-wide runtime constant of type `T`
+pub fn get<T: Any + Clone>(key: &str) -> Result<T, RuntimeConstantError> {
+    RUNTIME_CONSTANTS.get(key)
+}
+
+/// Whether a process-wide runtime constant is set
+pub fn contains(key: &str) -> bool {
+    RUNTIME_CONSTANTS.contains(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_values_round_trip() {
+        let constants = RuntimeConstants::new();
+        constants.set(keys::REGION, "eu-west-1".to_string());
+        constants.set("deployment.replicas", 3u32);
+
+        assert_eq!(
+            constants.get::<String>(keys::REGION),
+            Ok("eu-west-1".to_string())
+        );
+        assert_eq!(constants.get::<u32>("deployment.replicas"), Ok(3));
+        assert_eq!(
+            constants.keys(),
+            vec!["deployment.region", "deployment.replicas"]
+        );
+    }
+
+    #[test]
+    fn test_set_overrides_previous_value() {
+        let constants = RuntimeConstants::new();
+        constants.set(keys::DEPLOYMENT_ID, "first".to_string());
+        constants.set(keys::DEPLOYMENT_ID, "second".to_string());
+
+        assert_eq!(
+            constants.get::<String>(keys::DEPLOYMENT_ID),
+            Ok("second".to_string())
+        );
+    }
+
+    #[test]
+    fn test_missing_and_mistyped_values() {
+        let constants = RuntimeConstants::new();
+        constants.set("deployment.replicas", 3u32);
+
+        assert_eq!(
+            constants.get::<String>(keys::BUILD_HASH),
+            Err(RuntimeConstantError::Missing {
+                key: keys::BUILD_HASH.to_string()
+            })
+        );
+
+        let err = constants.get::<String>("deployment.replicas").unwrap_err();
+        assert!(matches!(
+            &err,
+            RuntimeConstantError::TypeMismatch { key, actual: "u32", .. } if key == "deployment.replicas"
+        ));
+        assert!(err.to_string().contains("'deployment.replicas' is a u32"));
+    }
+
+    #[test]
+    fn test_populate_from_config() {
+        let constants = RuntimeConstants::new();
+        constants.populate(&AppConfig::default());
+
+        assert_eq!(
+            constants.get::<String>(keys::BUILD_VERSION),
+            Ok(env!("CARGO_PKG_VERSION").to_string())
+        );
+        assert_eq!(
+            constants.get::<String>(keys::ENVIRONMENT),
+            Ok("development".to_string())
+        );
+    }
+
+    #[test]
+    fn test_global_registry() {
+        set("test.global.flag", true);
+
+        assert!(contains("test.global.flag"));
+        assert_eq!(get::<bool>("test.global.flag"), Ok(true));
+    }
+}
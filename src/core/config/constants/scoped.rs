@@ -0,0 +1,166 @@
+//! Environment-scoped constants
+//!
+//! Constants whose value depends on the environment, e.g. a shorter cache
+//! TTL in development. Each key has a global default and optional
+//! per-environment values; [`get`] returns the value for the environment
+//! recorded by [`init`] and falls back to the global default when that
+//! environment has no value of its own. Before [`init`], values resolve for
+//! [`EnvironmentType::Development`].
+
+use lazy_static::lazy_static;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use super::runtime::{RuntimeConstantError, RuntimeConstants};
+use crate::core::config::app_config::{AppConfig, EnvironmentType};
+
+/// Well-known environment-scoped constant keys
+pub mod keys {
+    /// Default cache time-to-live, as a `Duration`
+    pub const CACHE_DEFAULT_TTL: &str = "cache.default_ttl";
+}
+
+/// Constants with a global default and per-environment overrides
+#[derive(Default)]
+pub struct ScopedConstants {
+    defaults: RuntimeConstants,
+    by_environment: RwLock<HashMap<EnvironmentType, RuntimeConstants>>,
+}
+
+impl fmt::Debug for ScopedConstants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopedConstants")
+            .field("defaults", &self.defaults)
+            .field("by_environment", &self.by_environment.read().unwrap())
+            .finish()
+    }
+}
+
+impl ScopedConstants {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry holding the framework's built-in values
+    pub fn with_builtins() -> Self {
+        let constants = Self::new();
+        constants.set_default(keys::CACHE_DEFAULT_TTL, Duration::from_secs(3600));
+        constants.set_for(
+            EnvironmentType::Development,
+            keys::CACHE_DEFAULT_TTL,
+            Duration::from_secs(60),
+        );
+        constants
+    }
+
+    /// Set the global default for `key`
+    pub fn set_default<T: Any + Send + Sync>(&self, key: &str, value: T) {
+        self.defaults.set(key, value);
+    }
+
+    /// Set the value of `key` in `environment`
+    pub fn set_for<T: Any + Send + Sync>(&self, environment: EnvironmentType, key: &str, value: T) {
+        self.by_environment
+            .write()
+            .unwrap()
+            .entry(environment)
+            .or_default()
+            .set(key, value);
+    }
+
+    /// Value of `key` in `environment`, or its global default
+    pub fn resolve<T: Any + Clone>(
+        &self,
+        key: &str,
+        environment: &EnvironmentType,
+    ) -> Result<T, RuntimeConstantError> {
+        let scoped = self.by_environment.read().unwrap();
+        match scoped.get(environment) {
+            Some(values) if values.contains(key) => values.get(key),
+            _ => self.defaults.get(key),
+        }
+    }
+}
+
+lazy_static! {
+    static ref SCOPED_CONSTANTS: ScopedConstants = ScopedConstants::with_builtins();
+    static ref ENVIRONMENT: RwLock<EnvironmentType> = RwLock::new(EnvironmentType::default());
+}
+
+/// Record the environment the process-wide constants resolve for
+pub fn init(config: &AppConfig) {
+    *ENVIRONMENT.write().unwrap() = config.environment.clone();
+}
+
+/// Set the process-wide global default for `key`
+pub fn set_default<T: Any + Send + Sync>(key: &str, value: T) {
+    SCOPED_CONSTANTS.set_default(key, value);
+}
+
+/// Set the process-wide value of `key` in `environment`
+pub fn set_for<T: Any + Send + Sync>(environment: EnvironmentType, key: &str, value: T) {
+    SCOPED_CONSTANTS.set_for(environment, key, value);
+}
+
+/// Get `key` for the current environment, falling back to its global default
+pub fn get<T: Any + Clone>(key: &str) -> Result<T, RuntimeConstantError> {
+    SCOPED_CONSTANTS.resolve(key, &ENVIRONMENT.read().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_key_differs_by_environment() {
+        let constants = ScopedConstants::new();
+        constants.set_default("page.size", 50u32);
+        constants.set_for(EnvironmentType::Development, "page.size", 5u32);
+        constants.set_for(EnvironmentType::Production, "page.size", 100u32);
+
+        assert_eq!(
+            constants.resolve::<u32>("page.size", &EnvironmentType::Development),
+            Ok(5)
+        );
+        assert_eq!(
+            constants.resolve::<u32>("page.size", &EnvironmentType::Production),
+            Ok(100)
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_global_default() {
+        let constants = ScopedConstants::with_builtins();
+
+        assert_eq!(
+            constants.resolve::<Duration>(keys::CACHE_DEFAULT_TTL, &EnvironmentType::Development),
+            Ok(Duration::from_secs(60))
+        );
+        assert_eq!(
+            constants.resolve::<Duration>(keys::CACHE_DEFAULT_TTL, &EnvironmentType::Production),
+            Ok(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            constants.resolve::<Duration>("cache.unknown", &EnvironmentType::Production),
+            Err(RuntimeConstantError::Missing {
+                key: "cache.unknown".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_environment_value_type_is_checked() {
+        let constants = ScopedConstants::new();
+        constants.set_default("page.size", 50u32);
+        constants.set_for(EnvironmentType::Staging, "page.size", "fifty".to_string());
+
+        assert!(matches!(
+            constants.resolve::<u32>("page.size", &EnvironmentType::Staging),
+            Err(RuntimeConstantError::TypeMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+//! Redaction of sensitive configuration values
+//!
+//! Mark a field with `#[serde(serialize_with = "redaction::sensitive")]` and
+//! it is replaced by `***` whenever the configuration is rendered through
+//! [`to_redacted_value`]. Ordinary serialization is unaffected, so the real
+//! value still round-trips everywhere else. Free-form maps use
+//! [`sensitive_entries`], which redacts entries by key name instead.
+
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::cell::Cell;
+
+/// Replacement shown for sensitive values
+pub const REDACTED: &str = "***";
+
+thread_local! {
+    static REDACTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Serializer for sensitive fields; unset (`None` or empty) values are kept
+pub fn sensitive<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    if !REDACTING.with(Cell::get) {
+        return value.serialize(serializer);
+    }
+
+    match serde_json::to_value(value) {
+        Ok(Value::Null) => serializer.serialize_none(),
+        Ok(Value::String(s)) if s.is_empty() => serializer.serialize_str(""),
+        Ok(Value::Object(map)) if map.is_empty() => value.serialize(serializer),
+        _ => serializer.serialize_str(REDACTED),
+    }
+}
+
+/// Key name fragments that mark a free-form entry as sensitive
+const SENSITIVE_KEY_FRAGMENTS: &[&str] = &["key", "secret", "password", "token", "pem"];
+
+/// Serializer for free-form maps; entries whose name looks sensitive are redacted
+pub fn sensitive_entries<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    if !REDACTING.with(Cell::get) {
+        return value.serialize(serializer);
+    }
+
+    let mut value = serde_json::to_value(value).map_err(serde::ser::Error::custom)?;
+    redact_entries(&mut value);
+    value.serialize(serializer)
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_KEY_FRAGMENTS
+        .iter()
+        .any(|fragment| key.contains(fragment))
+}
+
+fn redact_entries(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if !is_sensitive_key(key) {
+                    redact_entries(field);
+                } else if !matches!(field, Value::Null)
+                    && field.as_str().is_none_or(|s| !s.is_empty())
+                {
+                    *field = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_entries),
+        _ => {}
+    }
+}
+
+/// Serialize `value` to JSON with every sensitive field redacted
+pub fn to_redacted_value<T: Serialize>(value: &T) -> serde_json::Result<Value> {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            REDACTING.with(|redacting| redacting.set(self.0));
+        }
+    }
+
+    let _reset = Reset(REDACTING.with(|redacting| redacting.replace(true)));
+    serde_json::to_value(value)
+}
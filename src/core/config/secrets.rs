@@ -0,0 +1,119 @@
+//! Secret references in configuration
+//!
+//! Any string value in the configuration may be a `secret://name` reference,
+//! resolved through a [`SecretProvider`] when the configuration is loaded.
+//! The default [`EnvSecretProvider`] reads secrets from environment variables;
+//! Vault or AWS Secrets Manager backends implement the same trait.
+//! Resolved values are never logged, only the secret names.
+
+use config::ConfigError;
+use serde_json::Value;
+use std::env;
+use tracing::debug;
+
+use super::app_config::AppConfig;
+use crate::core::error::AppError;
+
+/// Scheme marking a configuration value as a secret reference
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// Source of secret values
+pub trait SecretProvider: Send + Sync {
+    /// Look up the secret called `name`
+    fn get_secret(&self, name: &str) -> Result<String, AppError>;
+}
+
+/// Reads `secret://db-password` from the `DB_PASSWORD` environment variable
+#[derive(Debug, Clone, Default)]
+pub struct EnvSecretProvider;
+
+impl EnvSecretProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Environment variable holding the secret called `name`
+    pub fn env_var(name: &str) -> String {
+        name.chars()
+            .map(|c| match c {
+                '-' | '.' | '/' => '_',
+                c => c.to_ascii_uppercase(),
+            })
+            .collect()
+    }
+}
+
+impl SecretProvider for EnvSecretProvider {
+    fn get_secret(&self, name: &str) -> Result<String, AppError> {
+        let var = Self::env_var(name);
+        env::var(&var).map_err(|_| {
+            AppError::ConfigurationError(format!(
+                "Secret '{}' not found (environment variable {} is not set)",
+                name, var
+            ))
+        })
+    }
+}
+
+/// Name of the secret referenced by `value`, if it is a `secret://` reference
+pub fn secret_name(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(SECRET_SCHEME)
+        .filter(|name| !name.is_empty())
+}
+
+/// Replace every `secret://` reference in `config` with its resolved value
+pub fn resolve_secrets(
+    config: AppConfig,
+    provider: &dyn SecretProvider,
+) -> Result<AppConfig, ConfigError> {
+    let mut value = serde_json::to_value(&config)
+        .map_err(|e| ConfigError::Message(format!("Failed to inspect configuration: {}", e)))?;
+
+    let mut resolved = 0;
+    resolve_value(&mut value, "", provider, &mut resolved)?;
+    if resolved == 0 {
+        return Ok(config);
+    }
+
+    debug!("Resolved {} secret references in configuration", resolved);
+    serde_json::from_value(value)
+        .map_err(|e| ConfigError::Message(format!("Failed to apply resolved secrets: {}", e)))
+}
+
+fn resolve_value(
+    value: &mut Value,
+    path: &str,
+    provider: &dyn SecretProvider,
+    resolved: &mut usize,
+) -> Result<(), ConfigError> {
+    match value {
+        Value::String(s) => {
+            if let Some(name) = secret_name(s) {
+                let secret = provider.get_secret(name).map_err(|e| {
+                    ConfigError::Message(format!("Failed to resolve secret for {}: {}", path, e))
+                })?;
+                debug!("Resolved secret '{}' for {}", name, path);
+                *s = secret;
+                *resolved += 1;
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                resolve_value(item, &format!("{}[{}]", path, i), provider, resolved)?;
+            }
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                resolve_value(field, &path, provider, resolved)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
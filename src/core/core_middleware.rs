@@ -39,10 +39,16 @@ pub mod metrics {
     use axum::middleware::Next;
     use axum::response::IntoResponse;
 
+    use crate::core::metrics::route_metrics::{RouteMetrics, observe};
+
+    /// Records request metrics for every route, and latency into [`LatencyTracker::global`]
+    ///
+    /// Use [`record_request_metrics`] to exclude routes or add labels.
+    ///
+    /// [`LatencyTracker::global`]: crate::core::metrics::LatencyTracker::global
+    /// [`record_request_metrics`]: crate::core::metrics::record_request_metrics
     pub async fn metrics_middleware(req: Request, next: Next) -> impl IntoResponse {
-        // Record metrics about the request
-        let response = next.run(req).await;
-        response
+        observe(&RouteMetrics::default(), req, next).await
     }
 }
 
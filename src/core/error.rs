@@ -1,13 +1,19 @@
 pub mod error_types;
+pub mod localization;
 pub mod logger;
 pub mod middleware;
+pub mod problem;
+pub mod rejection;
 pub mod result_ext;
 
 // Re-export common types and functions
 pub use error_types::{AppError, ErrorResponse, ErrorSeverity, Result};
+pub use localization::{LocalizableError, MessageCatalog, localize_errors};
 pub use logger::{LogInfo, LogLevel, log, log_error};
-pub use middleware::RequestTrackingLayer;
-pub use middleware::{RequestId, RequestIdExt, generate_request_id};
+pub use middleware::{RequestId, RequestIdExt, generate_request_id, generate_request_id_with};
+pub use middleware::{RequestTrackingLayer, TraceSampling};
+pub use problem::{ProblemDetails, problem_json_errors};
+pub use rejection::json_rejections;
 pub use result_ext::{ResultExt, StatusCodeExt};
 
 // Re-export macro
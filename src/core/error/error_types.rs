@@ -1,4 +1,5 @@
 use crate::app;
+use crate::core::error::localization::LocalizableError;
 use crate::core::services::error::ServiceError as CoreServiceError;
 use axum::{
     Json,
@@ -14,6 +15,9 @@ use tracing::{error, warn};
 
 pub type Result<T> = result::Result<T, AppError>;
 
+/// Non-standard status for requests the client abandoned, as used by nginx
+pub const CLIENT_CLOSED_REQUEST: u16 = 499;
+
 // The struct to be returned from the API in case of an error
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
@@ -22,6 +26,8 @@ pub struct ErrorResponse {
     pub error_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl ErrorResponse {
@@ -31,6 +37,7 @@ impl ErrorResponse {
             message: message.into(),
             error_type: error_type.into(),
             details: None,
+            request_id: None,
         }
     }
 }
@@ -114,6 +121,27 @@ pub enum AppError {
 
     #[error("Network error: {0}")]
     NetworkError(String),
+
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+
+    #[error("Unprocessable entity: {0}")]
+    UnprocessableEntity(String),
+
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
+    #[error("Precondition required: {0}")]
+    PreconditionRequired(String),
+
+    #[error("Client closed request: {0}")]
+    ClientClosedRequest(String),
 }
 
 impl AppError {
@@ -139,6 +167,13 @@ impl AppError {
             AppError::ConflictError(_) => ErrorSeverity::Medium,
             AppError::NotFoundError(_) => ErrorSeverity::Low,
             AppError::NetworkError(_) => ErrorSeverity::Medium,
+            AppError::Timeout(_) => ErrorSeverity::Medium,
+            AppError::PayloadTooLarge(_) => ErrorSeverity::Low,
+            AppError::UnsupportedMediaType(_) => ErrorSeverity::Low,
+            AppError::UnprocessableEntity(_) => ErrorSeverity::Low,
+            AppError::PreconditionFailed(_) => ErrorSeverity::Low,
+            AppError::PreconditionRequired(_) => ErrorSeverity::Low,
+            AppError::ClientClosedRequest(_) => ErrorSeverity::Low,
         }
     }
 
@@ -164,6 +199,13 @@ impl AppError {
             AppError::ConflictError(_) => "conflict_error",
             AppError::NotFoundError(_) => "not_found_error",
             AppError::NetworkError(_) => "network_error",
+            AppError::Timeout(_) => "timeout",
+            AppError::PayloadTooLarge(_) => "payload_too_large",
+            AppError::UnsupportedMediaType(_) => "unsupported_media_type",
+            AppError::UnprocessableEntity(_) => "unprocessable_entity",
+            AppError::PreconditionFailed(_) => "precondition_failed",
+            AppError::PreconditionRequired(_) => "precondition_required",
+            AppError::ClientClosedRequest(_) => "client_closed_request",
         }
         .to_string()
     }
@@ -190,6 +232,47 @@ impl AppError {
             AppError::ConflictError(_) => StatusCode::CONFLICT,
             AppError::NotFoundError(_) => StatusCode::NOT_FOUND,
             AppError::NetworkError(_) => StatusCode::BAD_GATEWAY,
+            AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            AppError::ClientClosedRequest(_) => {
+                StatusCode::from_u16(CLIENT_CLOSED_REQUEST).unwrap_or(StatusCode::BAD_REQUEST)
+            }
+        }
+    }
+
+    // Get the error detail without the English prefix added by Display
+    pub fn detail(&self) -> String {
+        match self {
+            AppError::ConfigError(e) => e.to_string(),
+            AppError::ClientError(e) => e.to_string(),
+            AppError::IoError(e) => e.to_string(),
+            AppError::NotFound(msg)
+            | AppError::BadRequest(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::RateLimited(msg)
+            | AppError::ExternalServiceError(msg)
+            | AppError::CacheError(msg)
+            | AppError::ValidationError(msg)
+            | AppError::InternalServerError(msg)
+            | AppError::AuthenticationError(msg)
+            | AppError::AuthorizationError(msg)
+            | AppError::ConfigurationError(msg)
+            | AppError::NotImplementedError(msg)
+            | AppError::ConflictError(msg)
+            | AppError::NotFoundError(msg)
+            | AppError::NetworkError(msg)
+            | AppError::Timeout(msg)
+            | AppError::PayloadTooLarge(msg)
+            | AppError::UnsupportedMediaType(msg)
+            | AppError::UnprocessableEntity(msg)
+            | AppError::PreconditionFailed(msg)
+            | AppError::PreconditionRequired(msg)
+            | AppError::ClientClosedRequest(msg) => msg.clone(),
         }
     }
 
@@ -224,14 +307,26 @@ impl AppError {
     pub fn not_found_error(message: impl Into<String>) -> Self {
         Self::NotFoundError(message.into())
     }
+
+    pub fn timeout(message: impl Into<String>) -> Self {
+        Self::Timeout(message.into())
+    }
 }
 
 // Implement conversion to HTTP response for AppError
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        self.into_response_with_request_id(None)
+    }
+}
+
+impl AppError {
+    /// Render the error, quoting the id of the request that caused it
+    pub fn into_response_with_request_id(self, request_id: Option<String>) -> Response {
         let status = self.status_code();
         let error_type = self.error_type();
         let error_message = self.to_string();
+        let error_detail = self.detail();
         let severity = self.severity();
 
         // Add detailed error info for internal errors if not in production
@@ -259,17 +354,23 @@ impl IntoResponse for AppError {
             }
         }
 
-        // Return the HTTP response
-        (
+        // Return the HTTP response, tagged so the message can be localized later
+        let mut response = (
             status,
             Json(ErrorResponse {
                 code: status.as_u16(),
                 message: error_message,
-                error_type,
+                error_type: error_type.clone(),
                 details,
+                request_id,
             }),
         )
-            .into_response()
+            .into_response();
+        response.extensions_mut().insert(LocalizableError {
+            error_type,
+            detail: error_detail,
+        });
+        response
     }
 }
 
@@ -289,9 +390,7 @@ impl From<CoreServiceError> for AppError {
             CoreServiceError::Unavailable(msg) => {
                 Self::InternalServerError(format!("Service unavailable: {}", msg))
             }
-            CoreServiceError::Timeout(msg) => {
-                Self::InternalServerError(format!("Service timeout: {}", msg))
-            }
+            CoreServiceError::Timeout(msg) => Self::Timeout(msg),
             CoreServiceError::ConfigurationError(msg) => Self::ConfigurationError(msg),
             CoreServiceError::ConversionError(msg) => {
                 Self::InternalServerError(format!("Conversion error: {}", msg))
@@ -301,6 +400,7 @@ impl From<CoreServiceError> for AppError {
             CoreServiceError::Repository(msg) => {
                 Self::InternalServerError(format!("Repository error: {}", msg))
             }
+            CoreServiceError::Cancelled(msg) => Self::ClientClosedRequest(msg),
             CoreServiceError::Other(msg) => Self::InternalServerError(msg),
         }
     }
@@ -368,6 +468,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cancelled_service_error_is_client_closed_request() {
+        let error = AppError::from(CoreServiceError::from(
+            crate::core::services::cancellation::Cancelled,
+        ));
+
+        assert_eq!(error.status_code().as_u16(), CLIENT_CLOSED_REQUEST);
+        assert_eq!(error.error_type(), "client_closed_request");
+    }
+
     #[test]
     fn test_status_code_mapping() {
         // Test HTTP status code mappings
@@ -399,6 +509,10 @@ mod tests {
             AppError::InternalServerError("test".into()).status_code(),
             StatusCode::INTERNAL_SERVER_ERROR
         );
+        assert_eq!(
+            AppError::Timeout("test".into()).status_code(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
     }
 
     #[test]
@@ -0,0 +1,389 @@
+//! Localized client-facing error messages
+//!
+//! Error codes (`ErrorResponse::error_type`) are stable across locales; only the
+//! human readable `message` is translated. Catalog entries are keyed by error
+//! code and may reference the error's detail with a `{detail}` placeholder:
+//!
+//! ```yaml
+//! # config/i18n/fr.yaml
+//! not_found: "Ressource introuvable : {detail}"
+//! ```
+//!
+//! When no requested locale has an entry for the code, the original English
+//! message is returned unchanged.
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::Response,
+};
+use config::{Config, File};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::core::error::middleware::request_id_of;
+use crate::core::error::{AppError, ErrorResponse};
+
+/// Default locale; messages in this locale come from `AppError` itself
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Largest error body the localization middleware will buffer and rewrite
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Error code and detail attached to error responses so they can be localized
+#[derive(Debug, Clone)]
+pub struct LocalizableError {
+    /// Stable error code, as in `AppError::error_type`
+    pub error_type: String,
+    /// Error detail without the English prefix
+    pub detail: String,
+}
+
+/// Message catalog keyed by locale and error code
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalog {
+    messages: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageCatalog {
+    /// Create an empty catalog
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add messages for a locale, replacing existing entries for the same codes
+    pub fn with_messages<I, K, V>(mut self, locale: &str, messages: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let entries = self.messages.entry(normalize_locale(locale)).or_default();
+        for (code, message) in messages {
+            entries.insert(code.into(), message.into());
+        }
+        self
+    }
+
+    /// Load every `<locale>.yaml` / `<locale>.json` file in a directory
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, AppError> {
+        let mut catalog = Self::new();
+
+        for entry in std::fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            let is_catalog_file = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext, "yaml" | "yml" | "json"));
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if !is_catalog_file {
+                continue;
+            }
+
+            let messages: HashMap<String, String> = Config::builder()
+                .add_source(File::from(path.as_path()))
+                .build()?
+                .try_deserialize()?;
+
+            debug!(
+                "Loaded {} error messages for locale '{}'",
+                messages.len(),
+                locale
+            );
+            catalog = catalog.with_messages(locale, messages);
+        }
+
+        Ok(catalog)
+    }
+
+    /// Look up the message for an error code, trying each locale in order
+    pub fn localize(&self, locales: &[String], error_type: &str, detail: &str) -> Option<String> {
+        self.localize_with_locale(locales, error_type, detail)
+            .map(|(_, message)| message)
+    }
+
+    /// Like [`localize`](Self::localize), also returning the locale the message came from
+    pub fn localize_with_locale<'a>(
+        &self,
+        locales: &'a [String],
+        error_type: &str,
+        detail: &str,
+    ) -> Option<(&'a str, String)> {
+        locales
+            .iter()
+            .take_while(|locale| locale.as_str() != DEFAULT_LOCALE)
+            .find_map(|locale| {
+                let template = self.messages.get(locale)?.get(error_type)?;
+                Some((locale.as_str(), template.replace("{detail}", detail)))
+            })
+    }
+
+    /// Whether the catalog has any messages
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// Lower-case a locale and reduce it to its primary language subtag (`fr-CH` -> `fr`)
+fn normalize_locale(locale: &str) -> String {
+    locale
+        .trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Parse `Accept-Language` into primary language tags ordered by preference
+pub fn preferred_locales(headers: &HeaderMap) -> Vec<String> {
+    let Some(value) = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut weighted: Vec<(String, f32)> = value
+        .split(',')
+        .filter_map(|part| {
+            let mut pieces = part.split(';');
+            let tag = pieces.next()?.trim();
+            if tag.is_empty() || tag == "*" {
+                return None;
+            }
+
+            let quality = pieces
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality <= 0.0 {
+                return None;
+            }
+
+            Some((normalize_locale(tag), quality))
+        })
+        .collect();
+
+    // Stable sort keeps header order for equal weights
+    weighted.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut locales: Vec<String> = Vec::with_capacity(weighted.len());
+    for (locale, _) in weighted {
+        if !locales.contains(&locale) {
+            locales.push(locale);
+        }
+    }
+    locales
+}
+
+/// Middleware that localizes `AppError` responses according to `Accept-Language`
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc<MessageCatalog>, localize_errors)`.
+pub async fn localize_errors(
+    State(catalog): State<Arc<MessageCatalog>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let locales = preferred_locales(req.headers());
+    let request_id = request_id_of(&req);
+    let response = next.run(req).await;
+
+    if locales.is_empty() {
+        return response;
+    }
+
+    let Some(localizable) = response.extensions().get::<LocalizableError>().cloned() else {
+        return response;
+    };
+
+    let Some((locale, message)) =
+        catalog.localize_with_locale(&locales, &localizable.error_type, &localizable.detail)
+    else {
+        return response;
+    };
+    let Ok(content_language) = header::HeaderValue::from_str(locale) else {
+        return response;
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to buffer error response for localization: {}", e);
+            return AppError::internal_server_error("Failed to read the error response body")
+                .into_response_with_request_id(request_id);
+        }
+    };
+
+    let Ok(mut error_response) = serde_json::from_slice::<ErrorResponse>(&bytes) else {
+        // Not an ErrorResponse body, hand it back untouched
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    error_response.message = message;
+    let body = match serde_json::to_vec(&error_response) {
+        Ok(body) => body,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(header::CONTENT_LANGUAGE, content_language);
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, http::StatusCode, response::IntoResponse, routing::get};
+    use tower::ServiceExt;
+
+    fn french_catalog() -> MessageCatalog {
+        MessageCatalog::new().with_messages(
+            "fr",
+            [
+                ("not_found", "Ressource introuvable : {detail}"),
+                ("bad_request", "Requête invalide : {detail}"),
+            ],
+        )
+    }
+
+    fn test_app(catalog: MessageCatalog) -> Router {
+        Router::new()
+            .route(
+                "/users/{id}",
+                get(|| async { Err::<(), _>(AppError::not_found("user 42")) }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(catalog),
+                localize_errors,
+            ))
+    }
+
+    async fn fetch_error(app: Router, accept_language: Option<&str>) -> ErrorResponse {
+        let mut request = axum::http::Request::builder().uri("/users/42");
+        if let Some(value) = accept_language {
+            request = request.header(header::ACCEPT_LANGUAGE, value);
+        }
+
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_french_message_for_accept_language_fr() {
+        let error = fetch_error(test_app(french_catalog()), Some("fr")).await;
+
+        assert_eq!(error.error_type, "not_found");
+        assert_eq!(error.message, "Ressource introuvable : user 42");
+    }
+
+    #[tokio::test]
+    async fn test_english_message_otherwise() {
+        for accept_language in [None, Some("en-US"), Some("de, en;q=0.5")] {
+            let error = fetch_error(test_app(french_catalog()), accept_language).await;
+
+            assert_eq!(error.error_type, "not_found");
+            assert_eq!(error.message, "Not found: user 42");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_key_falls_back_to_english() {
+        let catalog = MessageCatalog::new().with_messages("fr", [("bad_request", "Invalide")]);
+        let error = fetch_error(test_app(catalog), Some("fr-FR")).await;
+
+        assert_eq!(error.message, "Not found: user 42");
+    }
+
+    #[tokio::test]
+    async fn test_content_language_matches_translated_message() {
+        let catalog = french_catalog().with_messages("de", [("bad_request", "Ungültig")]);
+        let request = axum::http::Request::builder()
+            .uri("/users/42")
+            .header(header::ACCEPT_LANGUAGE, "de, fr;q=0.8")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = test_app(catalog).oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "fr");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.message, "Ressource introuvable : user 42");
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_error_body_is_a_500() {
+        let app = Router::new()
+            .route(
+                "/users/{id}",
+                get(|| async {
+                    let (parts, _) = AppError::not_found("user 42").into_response().into_parts();
+                    let stream = futures::stream::once(async {
+                        Err::<axum::body::Bytes, _>(std::io::Error::other("connection reset"))
+                    });
+                    Response::from_parts(parts, Body::from_stream(stream))
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(french_catalog()),
+                localize_errors,
+            ));
+        let request = axum::http::Request::builder()
+            .uri("/users/42")
+            .header(header::ACCEPT_LANGUAGE, "fr")
+            .header("x-request-id", "req-7")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.request_id.as_deref(), Some("req-7"));
+    }
+
+    #[test]
+    fn test_preferred_locales_ordering() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT_LANGUAGE,
+            "en;q=0.5, fr-CH, de;q=0.8, fr;q=0.9, *;q=0.1"
+                .parse()
+                .unwrap(),
+        );
+
+        assert_eq!(preferred_locales(&headers), vec!["fr", "de", "en"]);
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("fr.yaml"),
+            "not_found: \"Ressource introuvable : {detail}\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("README.md"), "ignored").unwrap();
+
+        let catalog = MessageCatalog::load_dir(dir.path()).unwrap();
+
+        assert_eq!(
+            catalog.localize(&["fr".to_string()], "not_found", "x"),
+            Some("Ressource introuvable : x".to_string())
+        );
+    }
+}
@@ -2,7 +2,7 @@ use axum::{
     body::Body,
     extract::MatchedPath,
     http::{
-        Request, Response, StatusCode,
+        HeaderMap, Request, Response, StatusCode,
         header::{self, HeaderValue},
     },
     response::IntoResponse,
@@ -18,27 +18,128 @@ use std::{
 use tower::{Layer, Service};
 use tracing::{Span, error, info};
 use tracing_futures::Instrument;
-use uuid::Uuid;
 
 use crate::core::error::{AppError, ErrorResponse, log_error};
+use crate::core::utils::sources::{self, RandomSource};
 
 /// Generate a unique request ID
 pub fn generate_request_id() -> String {
-    Uuid::new_v4().to_string()
+    generate_request_id_with(sources::random().as_ref())
+}
+
+/// Generate a request ID (a v4 UUID) from `random`
+pub fn generate_request_id_with(random: &dyn RandomSource) -> String {
+    let mut bytes = [0u8; 16];
+    random.fill_bytes(&mut bytes);
+    uuid::Builder::from_random_bytes(bytes)
+        .into_uuid()
+        .to_string()
+}
+
+/// Request id from the request tracking layer, or the one the client sent
+pub(crate) fn request_id_of<B>(request: &Request<B>) -> Option<String> {
+    request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .or_else(|| {
+            request
+                .headers()
+                .get("x-request-id")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        })
+}
+
+/// Head-based sampling of request spans
+///
+/// The decision is made when the request span would be created. An inbound
+/// `traceparent` sampled flag takes precedence over the local rate, and
+/// unsampled requests that end in a server error get a span after the fact.
+#[derive(Debug, Clone, Copy)]
+pub struct TraceSampling {
+    /// Fraction of requests to trace (0.0 - 1.0)
+    pub sample_rate: f64,
+    /// Trace requests that fail with a server error even when not sampled
+    pub always_sample_errors: bool,
+    /// Follow the sampled flag of an inbound `traceparent` header
+    pub honor_parent: bool,
+}
+
+impl Default for TraceSampling {
+    fn default() -> Self {
+        Self {
+            sample_rate: 1.0,
+            always_sample_errors: true,
+            honor_parent: true,
+        }
+    }
+}
+
+impl TraceSampling {
+    /// Sample the given fraction of requests
+    pub fn with_rate(sample_rate: f64) -> Self {
+        Self {
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+            ..Default::default()
+        }
+    }
+
+    /// Decide whether a request with these headers should be traced
+    pub fn should_sample(&self, headers: &HeaderMap) -> bool {
+        let parent = if self.honor_parent {
+            parent_sampled(headers)
+        } else {
+            None
+        };
+        if let Some(sampled) = parent {
+            return sampled;
+        }
+
+        if self.sample_rate >= 1.0 {
+            true
+        } else if self.sample_rate <= 0.0 {
+            false
+        } else {
+            sources::random().next_f64() < self.sample_rate
+        }
+    }
+}
+
+/// Sampled flag from a W3C `traceparent` header (`00-<trace-id>-<parent-id>-<flags>`)
+fn parent_sampled(headers: &HeaderMap) -> Option<bool> {
+    let value = headers.get("traceparent")?.to_str().ok()?;
+    let mut parts = value.trim().split('-');
+    let (_version, trace_id, parent_id, flags) =
+        (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    if trace_id.len() != 32 || parent_id.len() != 16 || flags.len() != 2 {
+        return None;
+    }
+
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+    Some(flags & 0x01 == 0x01)
 }
 
 /// Request tracking middleware
 #[derive(Clone)]
 pub struct RequestTrackingLayer {
     pub service_name: String,
+    pub sampling: TraceSampling,
 }
 
 impl RequestTrackingLayer {
     pub fn new(service_name: &str) -> Self {
         Self {
             service_name: service_name.to_string(),
+            sampling: TraceSampling::default(),
         }
     }
+
+    /// Only create request spans for sampled requests
+    pub fn with_sampling(mut self, sampling: TraceSampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
 }
 
 impl<S> Layer<S> for RequestTrackingLayer {
@@ -48,6 +149,7 @@ impl<S> Layer<S> for RequestTrackingLayer {
         RequestTrackingMiddleware {
             inner: service,
             service_name: self.service_name.clone(),
+            sampling: self.sampling,
         }
     }
 }
@@ -56,6 +158,29 @@ impl<S> Layer<S> for RequestTrackingLayer {
 pub struct RequestTrackingMiddleware<S> {
     inner: S,
     service_name: String,
+    sampling: TraceSampling,
+}
+
+/// Fields recorded on the request span
+struct RequestSpanFields {
+    service_name: String,
+    request_id: String,
+    method: String,
+    path: String,
+    remote_addr: String,
+}
+
+impl RequestSpanFields {
+    fn span(&self) -> Span {
+        tracing::info_span!(
+            "request",
+            service = %self.service_name,
+            request_id = %self.request_id,
+            method = %self.method,
+            path = %self.path,
+            remote_addr = %self.remote_addr,
+        )
+    }
 }
 
 impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestTrackingMiddleware<S>
@@ -90,17 +215,25 @@ where
         // Record request metrics
         let _ = counter!("http.requests.total", "path" => path.clone(), "method" => req.method().to_string());
 
-        // Create a span for tracing
-        let span = tracing::info_span!(
-            "request",
-            service = %self.service_name,
-            request_id = %request_id,
-            method = %req.method(),
-            path = %path,
-            remote_addr = %req.extensions().get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+        // Create a span for tracing if this request is sampled
+        let span_fields = RequestSpanFields {
+            service_name: self.service_name.clone(),
+            request_id: request_id.clone(),
+            method: req.method().to_string(),
+            path: path.clone(),
+            remote_addr: req
+                .extensions()
+                .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
                 .map(|connect_info| connect_info.0.to_string())
                 .unwrap_or_else(|| "unknown".to_string()),
-        );
+        };
+        let sampled = self.sampling.should_sample(req.headers());
+        let span = if sampled {
+            span_fields.span()
+        } else {
+            Span::none()
+        };
+        let sample_errors = !sampled && self.sampling.always_sample_errors;
 
         info!(parent: &span, "Request started");
 
@@ -134,6 +267,11 @@ where
                     );
 
                     if status.is_server_error() {
+                        let span = if sample_errors {
+                            span_fields.span()
+                        } else {
+                            span
+                        };
                         error!(
                             parent: &span,
                             status = %status.as_u16(),
@@ -156,6 +294,11 @@ where
                     Ok(response)
                 }
                 Err(err) => {
+                    let span = if sample_errors {
+                        span_fields.span()
+                    } else {
+                        span
+                    };
                     let err = err.into();
                     let app_err = AppError::internal_server_error(format!("{}", err));
                     let status = app_err.status_code();
@@ -210,3 +353,131 @@ impl<B> RequestIdExt for Request<B> {
         self.extensions().get::<RequestId>().map(|id| id.0.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::sync::Mutex;
+    use tower::{ServiceExt, service_fn};
+    use tracing::span::{Attributes, Id};
+    use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+    use tracing_subscriber::{Layer as SubscriberLayer, Registry};
+
+    /// Records the names of spans created while it is the default subscriber
+    #[derive(Clone, Default)]
+    struct SpanRecorder {
+        names: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl<S: tracing::Subscriber> SubscriberLayer<S> for SpanRecorder {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: LayerContext<'_, S>) {
+            self.names
+                .lock()
+                .unwrap()
+                .push(attrs.metadata().name().to_string());
+        }
+    }
+
+    async fn request_spans(
+        sampling: TraceSampling,
+        status: StatusCode,
+        traceparent: Option<&str>,
+    ) -> usize {
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let service = RequestTrackingLayer::new("test")
+            .with_sampling(sampling)
+            .layer(service_fn(move |_req: Request<Body>| async move {
+                Ok::<_, Infallible>(
+                    Response::builder()
+                        .status(status)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+            }));
+
+        let mut request = Request::builder().uri("/orders");
+        if let Some(value) = traceparent {
+            request = request.header("traceparent", value);
+        }
+        service
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let names = recorder.names.lock().unwrap();
+        names.iter().filter(|name| *name == "request").count()
+    }
+
+    #[tokio::test]
+    async fn test_zero_rate_skips_successful_requests() {
+        let spans = request_spans(TraceSampling::with_rate(0.0), StatusCode::OK, None).await;
+        assert_eq!(spans, 0);
+    }
+
+    #[tokio::test]
+    async fn test_zero_rate_still_samples_errors() {
+        let spans = request_spans(
+            TraceSampling::with_rate(0.0),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            None,
+        )
+        .await;
+        assert_eq!(spans, 1);
+
+        let sampling = TraceSampling {
+            always_sample_errors: false,
+            ..TraceSampling::with_rate(0.0)
+        };
+        let spans = request_spans(sampling, StatusCode::INTERNAL_SERVER_ERROR, None).await;
+        assert_eq!(spans, 0);
+    }
+
+    #[tokio::test]
+    async fn test_inbound_sampling_decision_is_honored() {
+        let sampled = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let not_sampled = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00";
+
+        let spans =
+            request_spans(TraceSampling::with_rate(0.0), StatusCode::OK, Some(sampled)).await;
+        assert_eq!(spans, 1);
+
+        let spans = request_spans(
+            TraceSampling::with_rate(1.0),
+            StatusCode::OK,
+            Some(not_sampled),
+        )
+        .await;
+        assert_eq!(spans, 0);
+    }
+
+    #[test]
+    fn test_malformed_traceparent_is_ignored() {
+        let mut headers = HeaderMap::new();
+        headers.insert("traceparent", HeaderValue::from_static("00-abc-01"));
+
+        assert_eq!(parent_sampled(&headers), None);
+        assert!(TraceSampling::with_rate(1.0).should_sample(&headers));
+    }
+
+    #[test]
+    fn test_seeded_request_ids_are_reproducible() {
+        use crate::core::utils::sources::SeededRandom;
+
+        let ids = |seed| {
+            let random = SeededRandom::new(seed);
+            [
+                generate_request_id_with(&random),
+                generate_request_id_with(&random),
+            ]
+        };
+
+        assert_eq!(ids(42), ids(42));
+        assert_ne!(ids(42)[0], ids(42)[1]);
+        assert_ne!(ids(42), ids(43));
+        let id = uuid::Uuid::parse_str(&ids(42)[0]).unwrap();
+        assert_eq!(id.get_version_num(), 4);
+    }
+}
@@ -0,0 +1,292 @@
+//! RFC 7807 problem details for error responses
+//!
+//! `AppError` renders the [`ErrorResponse`] shape by default. With
+//! `server.error_format` set to `problem_json` (or `negotiate` and an
+//! `Accept: application/problem+json` request) the [`problem_json_errors`]
+//! middleware rewrites error bodies into an `application/problem+json`
+//! document:
+//!
+//! ```json
+//! {
+//!   "type": "/problems/not_found",
+//!   "title": "Not Found",
+//!   "status": 404,
+//!   "detail": "Not found: user 42",
+//!   "instance": "5f0c9a1e-…"
+//! }
+//! ```
+//!
+//! `instance` is the request id when one is known.
+
+use axum::{
+    Json,
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::core::config::app_config::ErrorFormat;
+use crate::core::error::middleware::request_id_of;
+use crate::core::error::{ErrorResponse, LocalizableError};
+
+/// Media type of problem details documents
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Prefix of the `type` URI reference, followed by the error code
+pub const PROBLEM_TYPE_PREFIX: &str = "/problems/";
+
+/// Largest error body the middleware will buffer and rewrite
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// An RFC 7807 problem details document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+impl ProblemDetails {
+    /// Problem details equivalent to an [`ErrorResponse`]
+    pub fn from_error_response(error: &ErrorResponse, instance: Option<String>) -> Self {
+        let title = StatusCode::from_u16(error.code)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or("Error");
+        Self {
+            problem_type: format!("{}{}", PROBLEM_TYPE_PREFIX, error.error_type),
+            title: title.to_string(),
+            status: error.code,
+            detail: error.message.clone(),
+            instance,
+        }
+    }
+}
+
+impl ErrorFormat {
+    /// Whether a request with these headers gets problem+json errors
+    pub fn wants_problem_json(&self, headers: &HeaderMap) -> bool {
+        match self {
+            Self::Legacy => false,
+            Self::ProblemJson => true,
+            Self::Negotiate => accepts_problem_json(headers),
+        }
+    }
+}
+
+/// Whether `Accept` lists `application/problem+json` with a non-zero quality
+fn accepts_problem_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|range| {
+            let mut params = range.split(';');
+            let media_type = params.next().unwrap_or("").trim();
+            let quality = params
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            media_type.eq_ignore_ascii_case(PROBLEM_JSON) && quality > 0.0
+        })
+}
+
+/// Middleware rendering `AppError` responses as problem+json per `format`
+///
+/// Use with `axum::middleware::from_fn_with_state(ErrorFormat, problem_json_errors)`,
+/// outside of [`localize_errors`](super::localize_errors) so the localized
+/// message becomes the `detail`.
+pub async fn problem_json_errors(
+    State(format): State<ErrorFormat>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !format.wants_problem_json(req.headers()) {
+        return next.run(req).await;
+    }
+
+    let instance = request_id_of(&req);
+    let response = next.run(req).await;
+    if response.extensions().get::<LocalizableError>().is_none() {
+        return response;
+    }
+
+    let instance = instance.or_else(|| {
+        response
+            .headers()
+            .get("x-request-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    });
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to buffer error response for problem+json: {}", e);
+            let mut error = ErrorResponse::new(
+                "internal_server_error",
+                "Failed to read the error response body",
+            );
+            error.code = StatusCode::INTERNAL_SERVER_ERROR.as_u16();
+            let problem = ProblemDetails::from_error_response(&error, instance);
+            let mut response = (StatusCode::INTERNAL_SERVER_ERROR, Json(problem)).into_response();
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+            return response;
+        }
+    };
+
+    let Ok(error_response) = serde_json::from_slice::<ErrorResponse>(&bytes) else {
+        // Not an ErrorResponse body, hand it back untouched
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    let problem = ProblemDetails::from_error_response(&error_response, instance);
+    let body = match serde_json::to_vec(&problem) {
+        Ok(body) => body,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::AppError;
+    use axum::{Router, routing::get};
+    use tower::ServiceExt;
+
+    fn test_app(format: ErrorFormat) -> Router {
+        Router::new()
+            .route(
+                "/users/{id}",
+                get(|| async { Err::<(), _>(AppError::not_found("user 42")) }),
+            )
+            .route("/ok", get(|| async { "fine" }))
+            .layer(axum::middleware::from_fn_with_state(
+                format,
+                problem_json_errors,
+            ))
+    }
+
+    async fn send(app: Router, uri: &str, accept: Option<&str>) -> Response {
+        let mut request = axum::http::Request::builder()
+            .uri(uri)
+            .header("x-request-id", "req-123");
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn json_body(response: Response) -> serde_json::Value {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_not_found_renders_problem_json() {
+        let response = send(test_app(ErrorFormat::ProblemJson), "/users/42", None).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+
+        let body = json_body(response).await;
+        assert_eq!(body["type"], "/problems/not_found");
+        assert_eq!(body["title"], "Not Found");
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["detail"], "Not found: user 42");
+        assert_eq!(body["instance"], "req-123");
+
+        let problem: ProblemDetails = serde_json::from_value(body).unwrap();
+        assert_eq!(problem.status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_format_is_unchanged() {
+        let response = send(
+            test_app(ErrorFormat::Legacy),
+            "/users/42",
+            Some(PROBLEM_JSON),
+        )
+        .await;
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let error: ErrorResponse = serde_json::from_value(json_body(response).await).unwrap();
+        assert_eq!(error.code, 404);
+        assert_eq!(error.error_type, "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_negotiate_follows_accept_header() {
+        let app = test_app(ErrorFormat::Negotiate);
+
+        let response = send(app.clone(), "/users/42", Some("application/problem+json")).await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+
+        let response = send(app.clone(), "/users/42", Some("application/json")).await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+
+        let response = send(app, "/users/42", Some("application/problem+json;q=0")).await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[tokio::test]
+    async fn test_successful_responses_pass_through() {
+        let response = send(test_app(ErrorFormat::ProblemJson), "/ok", None).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"fine");
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_error_body_is_a_problem_500() {
+        let app = Router::new()
+            .route(
+                "/broken",
+                get(|| async {
+                    let body = Body::from_stream(futures::stream::once(async {
+                        Err::<axum::body::Bytes, _>(std::io::Error::other("connection reset"))
+                    }));
+                    let mut response = Response::new(body);
+                    *response.status_mut() = StatusCode::NOT_FOUND;
+                    response.extensions_mut().insert(LocalizableError {
+                        error_type: "not_found".to_string(),
+                        detail: "user 42".to_string(),
+                    });
+                    response
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                ErrorFormat::ProblemJson,
+                problem_json_errors,
+            ));
+
+        let response = send(app, "/broken", None).await;
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+        let body = json_body(response).await;
+        assert_eq!(body["type"], "/problems/internal_server_error");
+        assert_eq!(body["instance"], "req-123");
+    }
+}
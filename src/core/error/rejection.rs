@@ -0,0 +1,282 @@
+//! Structured errors for axum's built-in extractor rejections
+//!
+//! When an extractor such as `Json`, `Path` or `Query` rejects a request,
+//! axum answers with a plain-text body. The [`json_rejections`] middleware
+//! turns those responses into the usual [`AppError`] JSON with the same
+//! status, quoting the request id, so clients see one error shape whether
+//! the handler or the framework refused the request.
+//!
+//! Handlers that take `Result<Json<T>, JsonRejection>` can convert the
+//! rejection with `?` through the `From` impls here.
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{
+        Request,
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+    },
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::core::error::middleware::request_id_of;
+use crate::core::error::{AppError, LocalizableError};
+
+/// Largest rejection body the middleware will buffer and rewrite
+const MAX_REJECTION_BODY_BYTES: usize = 64 * 1024;
+
+/// `AppError` carrying `status`, or `None` if no variant maps to it
+fn error_for_status(status: StatusCode, message: String) -> Option<AppError> {
+    let error = match status {
+        StatusCode::BAD_REQUEST => AppError::BadRequest(message),
+        StatusCode::UNAUTHORIZED => AppError::Unauthorized(message),
+        StatusCode::FORBIDDEN => AppError::Forbidden(message),
+        StatusCode::NOT_FOUND => AppError::NotFound(message),
+        StatusCode::CONFLICT => AppError::ConflictError(message),
+        StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge(message),
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => AppError::UnsupportedMediaType(message),
+        StatusCode::UNPROCESSABLE_ENTITY => AppError::UnprocessableEntity(message),
+        StatusCode::TOO_MANY_REQUESTS => AppError::RateLimited(message),
+        StatusCode::INTERNAL_SERVER_ERROR => AppError::InternalServerError(message),
+        _ => return None,
+    };
+    Some(error)
+}
+
+/// `AppError` for a rejection, keeping its status where a variant exists
+fn rejection_error(status: StatusCode, message: String) -> AppError {
+    error_for_status(status, message.clone()).unwrap_or_else(|| {
+        if status.is_server_error() {
+            AppError::InternalServerError(message)
+        } else {
+            AppError::BadRequest(message)
+        }
+    })
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        rejection_error(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        rejection_error(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        rejection_error(rejection.status(), rejection.body_text())
+    }
+}
+
+/// Whether `response` is an error rendered by axum rather than by `AppError`
+fn is_plain_text_error(response: &Response) -> bool {
+    let status = response.status();
+    (status.is_client_error() || status.is_server_error())
+        && response.extensions().get::<LocalizableError>().is_none()
+        && response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/plain"))
+}
+
+/// Middleware rewriting plain-text rejection responses as `AppError` JSON
+///
+/// Use with `axum::middleware::from_fn(json_rejections)`, inside
+/// [`localize_errors`](super::localize_errors) and
+/// [`problem_json_errors`](super::problem_json_errors) so the rewritten
+/// errors get the same treatment as any other.
+pub async fn json_rejections(req: Request, next: Next) -> Response {
+    let request_id = request_id_of(&req);
+    let response = next.run(req).await;
+    if !is_plain_text_error(&response) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_REJECTION_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to buffer rejection response: {}", e);
+            return AppError::internal_server_error("Failed to read the rejection response body")
+                .into_response_with_request_id(request_id);
+        }
+    };
+    let message = String::from_utf8_lossy(&bytes).trim().to_string();
+    let Some(error) = error_for_status(parts.status, message) else {
+        // No variant carries this status; rewriting would change it
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    let mut response = error.into_response_with_request_id(request_id);
+    for (name, value) in &parts.headers {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            response.headers_mut().append(name, value.clone());
+        }
+    }
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorResponse;
+    use axum::{
+        Json, Router,
+        extract::Path,
+        routing::{get, post},
+    };
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct NewPet {
+        name: String,
+    }
+
+    fn test_app() -> Router {
+        Router::new()
+            .route(
+                "/pets",
+                post(|Json(_pet): Json<NewPet>| async { "created" }),
+            )
+            .route(
+                "/pets/{id}",
+                get(|Path(id): Path<u32>| async move { id.to_string() }),
+            )
+            // The handler expects a segment the route doesn't capture
+            .route(
+                "/owners/{owner_id}/pets",
+                get(|Path((_owner, _pet)): Path<(u32, u32)>| async { "pet" }),
+            )
+            .layer(axum::middleware::from_fn(json_rejections))
+    }
+
+    async fn send(request: axum::http::Request<Body>) -> (StatusCode, String, ErrorResponse) {
+        let response = test_app().oneshot(request).await.unwrap();
+        let status = response.status();
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, content_type, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_malformed_json_body_is_structured() {
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/pets")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-request-id", "req-123")
+            .body(Body::from(r#"{"name": "#))
+            .unwrap();
+
+        let (status, content_type, error) = send(request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(content_type, "application/json");
+        assert_eq!(error.code, 400);
+        assert_eq!(error.error_type, "bad_request");
+        assert!(error.message.contains("JSON"), "{}", error.message);
+        assert_eq!(error.request_id.as_deref(), Some("req-123"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_type_keeps_status() {
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/pets")
+            .body(Body::from(r#"{"name": "Rex"}"#))
+            .unwrap();
+
+        let (status, _, error) = send(request).await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(error.error_type, "unsupported_media_type");
+    }
+
+    #[tokio::test]
+    async fn test_path_rejections_are_structured() {
+        let request = axum::http::Request::builder()
+            .uri("/pets/abc")
+            .body(Body::empty())
+            .unwrap();
+        let (status, _, error) = send(request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error_type, "bad_request");
+
+        let request = axum::http::Request::builder()
+            .uri("/owners/7/pets")
+            .body(Body::empty())
+            .unwrap();
+        let (status, _, error) = send(request).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(error.error_type, "internal_server_error");
+        assert!(error.message.contains("path"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn test_rejection_converts_with_question_mark() {
+        use axum::extract::FromRequest;
+
+        fn handle(payload: Result<Json<NewPet>, JsonRejection>) -> Result<String, AppError> {
+            let Json(pet) = payload?;
+            Ok(pet.name)
+        }
+
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/pets")
+            .body(Body::from(r#"{"name": "Rex"}"#))
+            .unwrap();
+        let payload = Json::<NewPet>::from_request(request, &()).await;
+
+        let error = handle(payload).unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_rejection_body_is_a_500() {
+        let app = Router::new()
+            .route(
+                "/broken",
+                get(|| async {
+                    let body = Body::from_stream(futures::stream::once(async {
+                        Err::<axum::body::Bytes, _>(std::io::Error::other("connection reset"))
+                    }));
+                    (
+                        StatusCode::BAD_REQUEST,
+                        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                        body,
+                    )
+                }),
+            )
+            .layer(axum::middleware::from_fn(json_rejections));
+
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/broken")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.error_type, "internal_server_error");
+    }
+}
@@ -3,19 +3,23 @@
 //! Provides a framework for creating customized server deployments with tailored feature sets.
 
 // Declare submodules
+pub mod builder;
 pub mod config;
 pub mod dependency_analyzer;
 pub mod documentation;
 pub mod features;
 pub mod macros;
+pub mod overrides;
 pub mod packaging;
 pub mod runtime;
 
 // Re-export the feature registry and related types
+pub use self::builder::{FeatureBuilder, FeatureSet};
 pub use self::config::FeatureConfig;
 pub use self::dependency_analyzer::DependencyAnalyzer;
 pub use self::documentation::{DocConfig, DocGenerator, DocTemplate};
 pub use self::features::{FeatureError, FeatureInfo, FeatureRegistry, FeatureRegistryExt};
+pub use self::overrides::{FeatureOverridePolicy, FeatureOverrides, apply_feature_overrides};
 pub use self::packaging::{BuildConfig, ContainerConfig, PackageManager, VersionInfo};
 pub use self::runtime::RuntimeFeatures;
 
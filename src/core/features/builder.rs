@@ -0,0 +1,228 @@
+//! Programmatic feature selection
+//!
+//! [`FeatureBuilder`] composes a [`FeatureSet`] from code, e.g. in tests or
+//! when embedding the server:
+//!
+//! ```ignore
+//! let features = FeatureBuilder::new()
+//!     .enable("advanced_metrics")? // also enables `metrics`
+//!     .disable("caching")?
+//!     .build()?;
+//! assert!(features.is_enabled("metrics"));
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+
+use super::features::{FeatureError, FeatureInfo, FeatureRegistry};
+
+/// Builds a validated [`FeatureSet`]
+#[derive(Clone)]
+pub struct FeatureBuilder {
+    registry: FeatureRegistry,
+    enabled: HashSet<String>,
+}
+
+impl FeatureBuilder {
+    /// Start from the built-in features with their defaults enabled
+    pub fn new() -> Self {
+        Self::from_registry(FeatureRegistry::new())
+    }
+
+    /// Start from no registered features
+    pub fn empty() -> Self {
+        Self::from_registry(FeatureRegistry::new_empty())
+    }
+
+    /// Start from the features registered and enabled in `registry`
+    pub fn from_registry(registry: FeatureRegistry) -> Self {
+        let enabled = registry.get_enabled_features().clone();
+        Self { registry, enabled }
+    }
+
+    /// Register an additional feature; it is not enabled until [`enable`](Self::enable)d
+    pub fn register(mut self, feature: FeatureInfo) -> Self {
+        self.registry.features.insert(feature.name.clone(), feature);
+        self
+    }
+
+    /// Enable `name` and, transitively, everything it depends on
+    pub fn enable(mut self, name: &str) -> Result<Self, FeatureError> {
+        let mut path = Vec::new();
+        self.enable_with_dependencies(name, &mut path)?;
+        Ok(self)
+    }
+
+    fn enable_with_dependencies(
+        &mut self,
+        name: &str,
+        path: &mut Vec<String>,
+    ) -> Result<(), FeatureError> {
+        if path.iter().any(|visiting| visiting == name) {
+            return Err(FeatureError::CircularDependency);
+        }
+        let feature = self
+            .registry
+            .get_feature(name)
+            .ok_or_else(|| FeatureError::UnknownFeature(name.to_string()))?;
+        let dependencies = feature.dependencies.clone();
+
+        path.push(name.to_string());
+        for dependency in &dependencies {
+            self.enable_with_dependencies(dependency, path)?;
+        }
+        path.pop();
+
+        self.enabled.insert(name.to_string());
+        Ok(())
+    }
+
+    /// Disable `name`, failing if an enabled feature depends on it
+    pub fn disable(mut self, name: &str) -> Result<Self, FeatureError> {
+        if self.registry.get_feature(name).is_none() {
+            return Err(FeatureError::UnknownFeature(name.to_string()));
+        }
+        if let Some(dependent) = self.dependents(name).into_iter().next() {
+            return Err(FeatureError::DependencyRequired(
+                name.to_string(),
+                dependent,
+            ));
+        }
+        self.enabled.remove(name);
+        Ok(self)
+    }
+
+    /// Enabled features depending directly on `name`, sorted
+    fn dependents(&self, name: &str) -> BTreeSet<String> {
+        self.enabled
+            .iter()
+            .filter(|enabled| {
+                self.registry
+                    .get_feature(enabled)
+                    .is_some_and(|feature| feature.dependencies.iter().any(|dep| dep == name))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Validate the selection and produce the resulting [`FeatureSet`]
+    pub fn build(self) -> Result<FeatureSet, FeatureError> {
+        let mut registry = self.registry;
+        registry.selected = self.enabled.clone();
+        registry.enabled_features = self.enabled;
+        registry.validate()?;
+
+        Ok(FeatureSet { registry })
+    }
+}
+
+impl Default for FeatureBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Validated set of enabled features produced by [`FeatureBuilder`]
+#[derive(Clone)]
+pub struct FeatureSet {
+    registry: FeatureRegistry,
+}
+
+impl FeatureSet {
+    /// Whether `name` is enabled
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.registry.feature_is_enabled(name)
+    }
+
+    /// Names of the enabled features, sorted
+    pub fn names(&self) -> Vec<String> {
+        let names: BTreeSet<&String> = self.registry.get_enabled_features().iter().collect();
+        names.into_iter().cloned().collect()
+    }
+
+    /// Registry with exactly these features selected and enabled
+    pub fn into_registry(self) -> FeatureRegistry {
+        self.registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature(name: &str, dependencies: &[&str]) -> FeatureInfo {
+        FeatureInfo {
+            name: name.to_string(),
+            description: format!("{} feature", name),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            default_enabled: false,
+            category: "Test".to_string(),
+            tags: Vec::new(),
+            size_impact: 10,
+        }
+    }
+
+    #[test]
+    fn test_enable_resolves_dependencies() {
+        let features = FeatureBuilder::empty()
+            .register(feature("core", &[]))
+            .register(feature("metrics", &["core"]))
+            .register(feature("advanced_metrics", &["metrics"]))
+            .enable("advanced_metrics")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            features.names(),
+            vec!["advanced_metrics", "core", "metrics"]
+        );
+    }
+
+    #[test]
+    fn test_defaults_are_the_starting_point() {
+        let features = FeatureBuilder::new()
+            .enable("advanced_metrics")
+            .unwrap()
+            .disable("caching")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert!(features.is_enabled("core"));
+        assert!(features.is_enabled("metrics"));
+        assert!(features.is_enabled("advanced_metrics"));
+        assert!(!features.is_enabled("caching"));
+
+        let registry = features.into_registry();
+        assert!(registry.is_selected("advanced_metrics"));
+        assert!(!registry.feature_is_enabled("caching"));
+    }
+
+    #[test]
+    fn test_disabling_a_dependency_fails() {
+        let result = FeatureBuilder::new()
+            .enable("advanced_metrics")
+            .unwrap()
+            .disable("metrics");
+
+        assert!(matches!(
+            result,
+            Err(FeatureError::DependencyRequired(feature, dependent))
+                if feature == "metrics" && dependent == "advanced_metrics"
+        ));
+    }
+
+    #[test]
+    fn test_circular_and_unknown_dependencies_fail() {
+        let result = FeatureBuilder::empty()
+            .register(feature("a", &["b"]))
+            .register(feature("b", &["a"]))
+            .enable("a");
+        assert!(matches!(result, Err(FeatureError::CircularDependency)));
+
+        let result = FeatureBuilder::empty()
+            .register(feature("a", &["missing"]))
+            .enable("a");
+        assert!(matches!(result, Err(FeatureError::UnknownFeature(name)) if name == "missing"));
+    }
+}
@@ -0,0 +1,280 @@
+//! Per-request feature flag overrides for testing
+//!
+//! QA can force a flag on or off for a single request without touching
+//! global configuration:
+//!
+//! ```text
+//! X-Feature-Override: new-checkout=on, legacy-search=off
+//! ```
+//!
+//! The header is only honoured when `features.overrides.enabled` is set,
+//! outside production, and from a client in `trusted_sources`; from anyone
+//! else it is stripped. Accepted overrides apply to
+//! [`RuntimeFeatures::is_enabled`](super::RuntimeFeatures::is_enabled)
+//! while that request is handled.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderName},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{debug, warn};
+
+use crate::core::config::app_config::{EnvironmentType, FeatureOverridesConfig};
+use crate::core::utils::trusted_proxy::{Cidr, ClientIp};
+
+/// Header carrying per-request overrides
+pub const FEATURE_OVERRIDE_HEADER: HeaderName = HeaderName::from_static("x-feature-override");
+
+tokio::task_local! {
+    static REQUEST_OVERRIDES: FeatureOverrides;
+}
+
+/// Flags forced on or off for the current request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureOverrides(HashMap<String, bool>);
+
+impl FeatureOverrides {
+    /// Overrides listed in every `X-Feature-Override` header
+    ///
+    /// Entries are `name=on|off` (or `true|false`), separated by commas;
+    /// malformed entries are skipped.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut overrides = HashMap::new();
+        let entries = headers
+            .get_all(&FEATURE_OVERRIDE_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for entry in entries {
+            let parsed = entry.split_once('=').and_then(|(name, state)| {
+                let enabled = match state.trim().to_ascii_lowercase().as_str() {
+                    "on" | "true" => true,
+                    "off" | "false" => false,
+                    _ => return None,
+                };
+                let name = name.trim();
+                (!name.is_empty()).then(|| (name.to_string(), enabled))
+            });
+            match parsed {
+                Some((name, enabled)) => {
+                    overrides.insert(name, enabled);
+                }
+                None => debug!("Ignoring malformed feature override '{}'", entry.trim()),
+            }
+        }
+        Self(overrides)
+    }
+
+    /// Forced state of `feature`, if overridden
+    pub fn get(&self, feature: &str) -> Option<bool> {
+        self.0.get(feature).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Forced state of `feature` for the request being handled, if any
+    pub fn current(feature: &str) -> Option<bool> {
+        REQUEST_OVERRIDES
+            .try_with(|overrides| overrides.get(feature))
+            .ok()
+            .flatten()
+    }
+
+    /// Run `future` with these overrides in effect
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        REQUEST_OVERRIDES.scope(self, future).await
+    }
+}
+
+/// Which clients may send feature overrides
+#[derive(Debug, Clone)]
+pub struct FeatureOverridePolicy {
+    trusted_sources: Arc<Vec<Cidr>>,
+}
+
+impl FeatureOverridePolicy {
+    /// Accept overrides from clients in `trusted_sources`
+    pub fn new(trusted_sources: Vec<Cidr>) -> Self {
+        Self {
+            trusted_sources: Arc::new(trusted_sources),
+        }
+    }
+
+    /// Policy for `environment`, or `None` if disabled or in production
+    pub fn from_config(
+        config: &FeatureOverridesConfig,
+        environment: &EnvironmentType,
+    ) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        if *environment == EnvironmentType::Production {
+            warn!("Ignoring features.overrides: per-request overrides are disabled in production");
+            return None;
+        }
+        let trusted_sources = config
+            .trusted_sources
+            .iter()
+            .filter_map(|cidr| match cidr.parse::<Cidr>() {
+                Ok(cidr) => Some(cidr),
+                Err(e) => {
+                    warn!("Ignoring feature override source: {}", e);
+                    None
+                }
+            })
+            .collect();
+        Some(Self::new(trusted_sources))
+    }
+
+    /// Whether a request from `client` may override flags
+    pub fn trusts(&self, client: IpAddr) -> bool {
+        self.trusted_sources
+            .iter()
+            .any(|cidr| cidr.contains(client))
+    }
+}
+
+fn client_ip(request: &Request) -> Option<IpAddr> {
+    let extensions = request.extensions();
+    extensions
+        .get::<ClientIp>()
+        .map(|client| client.0)
+        .or_else(|| {
+            extensions
+                .get::<ConnectInfo<std::net::SocketAddr>>()
+                .map(|connect_info| connect_info.0.ip())
+        })
+}
+
+/// Middleware applying `X-Feature-Override` from trusted clients
+///
+/// Use with `axum::middleware::from_fn_with_state(policy, apply_feature_overrides)`
+/// inside [`TrustedProxyLayer`](crate::core::utils::TrustedProxyLayer), so the
+/// client address is already resolved.
+pub async fn apply_feature_overrides(
+    State(policy): State<FeatureOverridePolicy>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if !request.headers().contains_key(&FEATURE_OVERRIDE_HEADER) {
+        return next.run(request).await;
+    }
+
+    let trusted = client_ip(&request).is_some_and(|client| policy.trusts(client));
+    if !trusted {
+        debug!("Stripping feature overrides from untrusted client");
+        request.headers_mut().remove(&FEATURE_OVERRIDE_HEADER);
+        return next.run(request).await;
+    }
+
+    let overrides = FeatureOverrides::from_headers(request.headers());
+    debug!("Applying feature overrides {:?}", overrides);
+    request.extensions_mut().insert(overrides.clone());
+    overrides.scope(next.run(request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::features::RuntimeFeatures;
+    use axum::{Router, body::Body, routing::get};
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    async fn checkout_enabled() -> String {
+        let mut features = RuntimeFeatures::new();
+        features.disable("new-checkout");
+        features.is_enabled("new-checkout").to_string()
+    }
+
+    fn app() -> Router {
+        let policy = FeatureOverridePolicy::new(vec!["10.0.0.0/8".parse().unwrap()]);
+        Router::new()
+            .route("/checkout", get(checkout_enabled))
+            .layer(axum::middleware::from_fn_with_state(
+                policy,
+                apply_feature_overrides,
+            ))
+    }
+
+    async fn send(app: &Router, peer: &str, header: Option<&str>) -> String {
+        let mut request = Request::builder().uri("/checkout");
+        if let Some(header) = header {
+            request = request.header(FEATURE_OVERRIDE_HEADER, header);
+        }
+        let mut request = request.body(Body::empty()).unwrap();
+        let peer: SocketAddr = format!("{}:40000", peer).parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(peer));
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_trusted_override_applies_to_that_request_only() {
+        let app = app();
+
+        assert_eq!(
+            send(&app, "10.1.2.3", Some("new-checkout=on")).await,
+            "true"
+        );
+        assert_eq!(send(&app, "10.1.2.3", None).await, "false");
+        assert!(FeatureOverrides::current("new-checkout").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_override_is_ignored() {
+        let app = app();
+
+        assert_eq!(
+            send(&app, "203.0.113.7", Some("new-checkout=on")).await,
+            "false"
+        );
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            FEATURE_OVERRIDE_HEADER,
+            "new-checkout=on, legacy-search=OFF, bogus, =on"
+                .parse()
+                .unwrap(),
+        );
+        headers.append(FEATURE_OVERRIDE_HEADER, "beta=true".parse().unwrap());
+
+        let overrides = FeatureOverrides::from_headers(&headers);
+
+        assert_eq!(overrides.get("new-checkout"), Some(true));
+        assert_eq!(overrides.get("legacy-search"), Some(false));
+        assert_eq!(overrides.get("beta"), Some(true));
+        assert_eq!(overrides.0.len(), 3);
+    }
+
+    #[test]
+    fn test_disabled_in_production() {
+        let config = FeatureOverridesConfig {
+            enabled: true,
+            trusted_sources: vec!["10.0.0.0/8".to_string()],
+        };
+
+        assert!(
+            FeatureOverridePolicy::from_config(&config, &EnvironmentType::Production).is_none()
+        );
+        assert!(
+            FeatureOverridePolicy::from_config(&config, &EnvironmentType::Development).is_some()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::core::features::FeatureConfig;
+use crate::core::features::overrides::FeatureOverrides;
 use std::collections::{HashMap, HashSet};
 
 /// Runtime feature flags for dynamic behavior
@@ -73,8 +74,12 @@ impl RuntimeFeatures {
     }
 
     /// Check if a feature is enabled
+    ///
+    /// A trusted `X-Feature-Override` on the current request takes precedence;
+    /// see [`overrides`](crate::core::features::overrides).
     pub fn is_enabled(&self, feature: &str) -> bool {
-        self.enabled_features.contains(feature)
+        FeatureOverrides::current(feature)
+            .unwrap_or_else(|| self.enabled_features.contains(feature))
     }
 
     /// Get a list of all enabled features
@@ -1,11 +1,27 @@
 use axum::{extract::State, response::Json};
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::sync::Arc;
 use tracing::{debug, info};
 
+#[cfg(feature = "auth")]
+use crate::core::auth::{
+    AuthError,
+    middleware::JwksRefresher,
+    providers::{JwksRefreshOutcome, ProviderRegistry},
+};
+#[cfg(feature = "auth")]
+use axum::extract::Extension;
+#[cfg(feature = "auth")]
+use std::collections::BTreeMap;
+
 use crate::core::{
+    config::{ConfigAuditLog, ConfigChange, ConfigChangeEvent, redaction},
+    error::AppError,
+    metrics::{LatencyTracker, RequestStats, RequestSummary, RouteLatency},
     models::{ActuatorEntry, InfoResponse},
     router::AppState,
+    utils::log_levels::{LogLevels, LoggerLevels},
 };
 
 /// Handler for the info endpoint
@@ -46,6 +62,218 @@ pub async fn info(State(_state): State<Arc<AppState>>) -> Json<Value> {
     Json(response)
 }
 
+/// Handler for the config endpoint
+///
+/// Returns the effective merged configuration, with fields marked
+/// sensitive redacted. Only available when
+/// `endpoint_security.expose_config` is enabled.
+pub async fn config(State(state): State<Arc<AppState>>) -> Result<Json<Value>, AppError> {
+    if !state.config.endpoint_security.expose_config {
+        return Err(AppError::NotFound(
+            "Configuration endpoint is disabled".to_string(),
+        ));
+    }
+
+    debug!("📊 Dumping effective configuration");
+    let config = redaction::to_redacted_value(&state.config).map_err(|e| {
+        AppError::internal_server_error(format!("Failed to serialize configuration: {}", e))
+    })?;
+
+    Ok(Json(config))
+}
+
+/// Handler for the config changes endpoint
+///
+/// Returns the most recent configuration changes, newest first, with
+/// sensitive values redacted. Guarded like the config endpoint.
+pub async fn config_changes(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ConfigChangeEvent>>, AppError> {
+    if !state.config.endpoint_security.expose_config {
+        return Err(AppError::NotFound(
+            "Configuration endpoint is disabled".to_string(),
+        ));
+    }
+
+    Ok(Json(ConfigAuditLog::global().recent()))
+}
+
+/// Handler for the latency endpoint
+///
+/// Returns rolling p50/p95/p99 latencies per route, computed from the
+/// most recent requests recorded by the metrics middleware.
+pub async fn latency() -> Json<Vec<RouteLatency>> {
+    Json(LatencyTracker::global().snapshot())
+}
+
+/// Body of the summary endpoint
+#[derive(Debug, Serialize)]
+pub struct ActuatorSummary {
+    /// Seconds since the application started
+    pub uptime_seconds: u64,
+    #[serde(flatten)]
+    pub requests: RequestSummary,
+}
+
+/// Handler for the summary endpoint
+///
+/// Returns request counts by status class, in-flight requests, average
+/// and p95 latency, and uptime since startup. Without authentication in
+/// front of the actuator it is only served when
+/// `endpoint_security.public_metrics` is enabled.
+pub async fn summary(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ActuatorSummary>, AppError> {
+    summary_from(&state, RequestStats::global())
+}
+
+fn summary_from(state: &AppState, stats: &RequestStats) -> Result<Json<ActuatorSummary>, AppError> {
+    if !state.config.endpoint_security.public_metrics && !state.config.auth.enabled {
+        return Err(AppError::NotFound(
+            "Summary endpoint is disabled".to_string(),
+        ));
+    }
+
+    let uptime = state.start_time.elapsed().unwrap_or_default();
+    Ok(Json(ActuatorSummary {
+        uptime_seconds: uptime.as_secs(),
+        requests: stats.snapshot(),
+    }))
+}
+
+/// Body of a loggers update
+#[derive(Debug, Deserialize)]
+pub struct LoggerChange {
+    /// Target to change, e.g. `navius::core::auth`; the global level if absent
+    #[serde(default)]
+    pub target: Option<String>,
+    /// New level; `null` reverts the target to its startup level
+    #[serde(default)]
+    pub level: Option<String>,
+}
+
+fn log_levels(state: &AppState) -> Result<&'static LogLevels, AppError> {
+    if !state.config.endpoint_security.expose_loggers {
+        return Err(AppError::NotFound(
+            "Loggers endpoint is disabled".to_string(),
+        ));
+    }
+    LogLevels::global()
+        .ok_or_else(|| AppError::NotFound("Log levels cannot be changed at runtime".to_string()))
+}
+
+/// Handler for viewing log levels
+///
+/// Returns the global level and any per-target levels. Only available when
+/// `endpoint_security.expose_loggers` is enabled.
+pub async fn loggers(State(state): State<Arc<AppState>>) -> Result<Json<LoggerLevels>, AppError> {
+    Ok(Json(log_levels(&state)?.levels()))
+}
+
+/// Handler for changing a log level
+///
+/// Sets the global level, or the level of `target` and the modules under
+/// it; a `null` level reverts the target. Takes effect immediately.
+pub async fn update_logger(
+    State(state): State<Arc<AppState>>,
+    Json(change): Json<LoggerChange>,
+) -> Result<Json<LoggerLevels>, AppError> {
+    update_logger_in(log_levels(&state)?, ConfigAuditLog::global(), change)
+}
+
+fn update_logger_in(
+    levels: &LogLevels,
+    audit: &ConfigAuditLog,
+    change: LoggerChange,
+) -> Result<Json<LoggerLevels>, AppError> {
+    let before = levels.levels();
+    let level = change
+        .level
+        .as_deref()
+        .map(LogLevels::parse_level)
+        .transpose()?;
+    match (change.target.as_deref(), level) {
+        (Some(target), Some(level)) => levels.set_target(target, level)?,
+        (Some(target), None) => levels.clear_target(target)?,
+        (None, Some(level)) => levels.set_default(level)?,
+        (None, None) => {
+            return Err(AppError::BadRequest(
+                "Provide a level, or a target to revert".to_string(),
+            ));
+        }
+    }
+    let after = levels.levels();
+    audit.record_changes(logger_changes(&before, &after));
+    Ok(Json(after))
+}
+
+/// Handler for restoring the startup log levels
+pub async fn reset_loggers(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<LoggerLevels>, AppError> {
+    let levels = log_levels(&state)?;
+    let before = levels.levels();
+    levels.reset()?;
+    let after = levels.levels();
+    ConfigAuditLog::global().record_changes(logger_changes(&before, &after));
+    Ok(Json(after))
+}
+
+/// Audit entries for a log level change, keyed `logging.level` and `logging.targets.<target>`
+fn logger_changes(before: &LoggerLevels, after: &LoggerLevels) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    if before.default_level != after.default_level {
+        changes.push(ConfigChange {
+            key: "logging.level".to_string(),
+            old: json!(before.default_level),
+            new: json!(after.default_level),
+        });
+    }
+    let mut targets: Vec<&String> = before.targets.keys().chain(after.targets.keys()).collect();
+    targets.sort();
+    targets.dedup();
+    for target in targets {
+        let (old, new) = (before.targets.get(target), after.targets.get(target));
+        if old != new {
+            changes.push(ConfigChange {
+                key: format!("logging.targets.{}", target),
+                old: json!(old),
+                new: json!(new),
+            });
+        }
+    }
+    changes
+}
+
+/// Handler for refreshing every auth provider's JWKS immediately
+///
+/// For use after rotating signing keys at the IdP, instead of waiting for
+/// the scheduled refresh. Reports the outcome per provider; calls within
+/// the registry's manual refresh interval get `429 Too Many Requests`.
+/// Requires the [`ProviderRegistry`] as an `Arc` request extension; with a
+/// [`JwksRefresher`] extension the auth middleware's own cache is refreshed
+/// too, reported as `middleware`.
+#[cfg(feature = "auth")]
+pub async fn refresh_jwks(
+    providers: Option<Extension<Arc<ProviderRegistry>>>,
+    middleware: Option<Extension<JwksRefresher>>,
+) -> Result<Json<BTreeMap<String, JwksRefreshOutcome>>, AppError> {
+    let Some(Extension(providers)) = providers else {
+        return Err(AppError::NotFound(
+            "No auth providers are configured".to_string(),
+        ));
+    };
+    let mut outcomes = match providers.refresh_all().await {
+        Ok(outcomes) => outcomes,
+        Err(AuthError::RateLimited(message)) => return Err(AppError::RateLimited(message)),
+        Err(e) => return Err(AppError::internal_server_error(e.to_string())),
+    };
+    if let Some(Extension(middleware)) = middleware {
+        outcomes.insert("middleware".to_string(), middleware.refresh().await);
+    }
+    Ok(Json(outcomes))
+}
+
 /// Returns the time the application was built
 fn get_build_time() -> String {
     // In a real implementation, this would be derived from build info
@@ -104,8 +332,323 @@ fn get_active_features() -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::app_config::AppConfig;
     use axum::http::StatusCode;
 
+    fn state_with(configure: impl FnOnce(&mut AppConfig)) -> Arc<AppState> {
+        let mut config = AppConfig::default();
+        config.endpoint_security.expose_config = true;
+        configure(&mut config);
+        Arc::new(AppState {
+            config,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_config_redacts_secrets() {
+        let state = state_with(|config| {
+            config.api.api_key = Some("live-key".to_string());
+        });
+
+        let Json(body) = config(State(state)).await.unwrap();
+
+        assert_eq!(body["api"]["api_key"], redaction::REDACTED);
+        assert_eq!(body["api"]["base_url"], "http://localhost:8080");
+        assert!(!body.to_string().contains("live-key"));
+    }
+
+    #[tokio::test]
+    async fn test_config_disabled_by_endpoint_security() {
+        let state = state_with(|config| config.endpoint_security.expose_config = false);
+
+        let error = config(State(state)).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_config_changes_lists_recorded_changes() {
+        let state = state_with(|_| {});
+        let mut changed = state.config.clone();
+        changed.logging.level = "trace-actuator-test".to_string();
+        ConfigAuditLog::global().record(&state.config, &changed);
+
+        let Json(events) = config_changes(State(state)).await.unwrap();
+
+        assert!(events.iter().any(|event| {
+            event
+                .changes
+                .iter()
+                .any(|change| change.key == "logging.level" && change.new == "trace-actuator-test")
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_latency_reports_recorded_routes() {
+        let route = "GET /test/latency-endpoint";
+        LatencyTracker::global().record(route, std::time::Duration::from_millis(5));
+
+        let Json(routes) = latency().await;
+        let entry = routes
+            .iter()
+            .find(|latency| latency.route == route)
+            .unwrap();
+
+        assert!(entry.count >= 1);
+        assert!(entry.p99_ms >= 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_summary_counts_requests_by_status_class() {
+        use crate::core::metrics::track_requests;
+        use axum::{Router, body::Body, http::Request, routing::get};
+        use tower::ServiceExt;
+
+        let stats = RequestStats::new();
+        let app = Router::new()
+            .route("/ok", get(|| async { StatusCode::OK }))
+            .route("/fail", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .layer(axum::middleware::from_fn_with_state(
+                stats.clone(),
+                track_requests,
+            ));
+        for uri in ["/ok", "/ok", "/ok", "/missing", "/fail"] {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+        let state = state_with(|config| config.endpoint_security.public_metrics = true);
+
+        let Json(summary) = summary_from(&state, &stats).unwrap();
+
+        assert_eq!(summary.requests.total_requests, 5);
+        assert_eq!(summary.requests.status_class("2xx"), 3);
+        assert_eq!(summary.requests.status_class("4xx"), 1);
+        assert_eq!(summary.requests.status_class("5xx"), 1);
+        assert_eq!(summary.requests.in_flight, 0);
+        let body = serde_json::to_value(&summary).unwrap();
+        assert_eq!(body["status_classes"]["2xx"], 3);
+        assert!(body["uptime_seconds"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_summary_disabled_by_endpoint_security() {
+        let state = state_with(|config| {
+            config.endpoint_security.public_metrics = false;
+            config.auth.enabled = false;
+        });
+
+        let error = summary(State(state)).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_loggers_disabled_by_endpoint_security() {
+        let state = state_with(|config| config.endpoint_security.expose_loggers = false);
+
+        let error = loggers(State(state)).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_update_logger_sets_and_reverts_targets() {
+        use tracing_subscriber::filter::LevelFilter;
+
+        let (_filter, levels) = LogLevels::for_registry(LevelFilter::INFO);
+        let change = |target: Option<&str>, level: Option<&str>| LoggerChange {
+            target: target.map(str::to_string),
+            level: level.map(str::to_string),
+        };
+
+        let audit = ConfigAuditLog::new(10);
+
+        let Json(updated) = update_logger_in(
+            &levels,
+            &audit,
+            change(Some("navius::core::auth"), Some("debug")),
+        )
+        .unwrap();
+        assert_eq!(updated.targets["navius::core::auth"], "debug");
+
+        let Json(updated) =
+            update_logger_in(&levels, &audit, change(Some("navius::core::auth"), None)).unwrap();
+        assert!(updated.targets.is_empty());
+
+        let error = update_logger_in(&levels, &audit, change(None, Some("loud"))).unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        let error = update_logger_in(&levels, &audit, change(None, None)).unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+
+        // Both successful changes were audited, most recent first
+        let events = audit.recent();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[1].changes[0].key,
+            "logging.targets.navius::core::auth"
+        );
+        assert_eq!(events[1].changes[0].old, Value::Null);
+        assert_eq!(events[1].changes[0].new, json!("debug"));
+        assert_eq!(events[0].changes[0].old, json!("debug"));
+        assert_eq!(events[0].changes[0].new, Value::Null);
+    }
+
+    #[cfg(feature = "auth")]
+    mod jwks {
+        use super::*;
+        use crate::core::auth::providers::{
+            CircuitState, HealthStatus, OAuthProvider, StandardClaims,
+        };
+        use crate::core::config::app_config::AuthConfig;
+        use axum::{Router, body::Body, http::Request, routing::post};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tower::ServiceExt;
+
+        #[derive(Clone, Default)]
+        struct CountingProvider {
+            config: AuthConfig,
+            refreshes: Arc<AtomicUsize>,
+            fail: bool,
+        }
+
+        #[async_trait::async_trait]
+        impl OAuthProvider for CountingProvider {
+            async fn validate_token(&self, _token: &str) -> Result<StandardClaims, AuthError> {
+                Err(AuthError::InvalidTokenFormat)
+            }
+
+            async fn refresh_jwks(&self) -> Result<(), AuthError> {
+                self.refreshes.fetch_add(1, Ordering::SeqCst);
+                if self.fail {
+                    return Err(AuthError::InternalError("IdP unreachable".to_string()));
+                }
+                Ok(())
+            }
+
+            fn config(&self) -> &AuthConfig {
+                &self.config
+            }
+
+            async fn get_roles(&self, _token: &str) -> Result<Vec<String>, AuthError> {
+                Ok(Vec::new())
+            }
+
+            fn name(&self) -> &str {
+                "counting"
+            }
+
+            async fn health_check(&self) -> HealthStatus {
+                HealthStatus {
+                    ready: true,
+                    jwks_valid: true,
+                    last_refresh: std::time::SystemTime::now(),
+                    error: None,
+                    circuit_state: CircuitState::Closed,
+                }
+            }
+
+            fn box_clone(&self) -> Box<dyn OAuthProvider> {
+                Box::new(self.clone())
+            }
+        }
+
+        fn refresh_request() -> Request<Body> {
+            Request::builder()
+                .method("POST")
+                .uri("/auth/refresh-jwks")
+                .body(Body::empty())
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_refresh_jwks_reports_each_provider_and_is_rate_limited() {
+            let healthy = CountingProvider::default();
+            let failing = CountingProvider {
+                fail: true,
+                ..Default::default()
+            };
+            let mut registry = ProviderRegistry::new(AuthConfig::default());
+            registry.register("entra", Arc::new(healthy.clone()));
+            registry.register("partner", Arc::new(failing.clone()));
+            let app = Router::new()
+                .route("/auth/refresh-jwks", post(refresh_jwks))
+                .layer(Extension(Arc::new(registry)));
+
+            let response = app.clone().oneshot(refresh_request()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let outcomes: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(outcomes["entra"], json!({"refreshed": true}));
+            assert_eq!(outcomes["partner"]["refreshed"], json!(false));
+            assert!(
+                outcomes["partner"]["error"]
+                    .as_str()
+                    .unwrap()
+                    .contains("IdP unreachable")
+            );
+            assert_eq!(healthy.refreshes.load(Ordering::SeqCst), 1);
+            assert_eq!(failing.refreshes.load(Ordering::SeqCst), 1);
+
+            let response = app.oneshot(refresh_request()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert!(String::from_utf8_lossy(&body).contains("retry in"));
+            assert_eq!(healthy.refreshes.load(Ordering::SeqCst), 1);
+        }
+
+        #[tokio::test]
+        async fn test_refresh_jwks_without_registry_is_not_found() {
+            let error = refresh_jwks(None, None).await.unwrap_err();
+
+            assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+        }
+
+        #[tokio::test]
+        async fn test_refresh_jwks_refreshes_middleware_cache() {
+            use crate::core::auth::middleware::{EntraAuthConfig, EntraAuthLayer};
+            use crate::core::config::app_config::{ProviderConfig, default_allowed_algorithms};
+            use wiremock::{Mock, MockServer, ResponseTemplate, matchers::path};
+
+            let idp = MockServer::start().await;
+            Mock::given(path("/keys"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "keys": [] })))
+                .mount(&idp)
+                .await;
+            let provider_config = ProviderConfig {
+                enabled: true,
+                client_id: "test-client-id".to_string(),
+                jwks_uri: format!("{}/keys", idp.uri()),
+                issuer_url: String::new(),
+                audience: String::new(),
+                role_mappings: Default::default(),
+                provider_specific: Default::default(),
+                allowed_algorithms: default_allowed_algorithms(),
+            };
+            let refresher =
+                EntraAuthLayer::new(EntraAuthConfig::new(&AppConfig::default(), &provider_config))
+                    .jwks_refresher();
+            let provider = CountingProvider::default();
+            let mut registry = ProviderRegistry::new(AuthConfig::default());
+            registry.register("entra", Arc::new(provider.clone()));
+
+            let Json(outcomes) = refresh_jwks(
+                Some(Extension(Arc::new(registry))),
+                Some(Extension(refresher)),
+            )
+            .await
+            .unwrap();
+
+            assert!(outcomes["entra"].refreshed);
+            assert!(outcomes["middleware"].refreshed);
+            assert_eq!(provider.refreshes.load(Ordering::SeqCst), 1);
+        }
+    }
+
     #[test]
     fn test_git_info() {
         let info = get_git_info();
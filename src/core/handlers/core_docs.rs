@@ -1,13 +1,91 @@
+//! API documentation handlers
+//!
+//! Swagger UI's own scripts are loaded from a CDN at a pinned version. The
+//! page's script and stylesheet are embedded in the binary and served under
+//! content-hashed names (`swagger-init.3f2a….js`) with a year-long
+//! `Cache-Control: immutable`, so browsers cache them indefinitely yet pick
+//! up new versions as soon as the HTML, which is never cached, names them.
+//!
+//! The spec file is size-capped and checked for the shape of an OpenAPI
+//! document before it is served; a missing, oversized or malformed file is
+//! logged and replaced by an empty spec rather than failing.
+
 use axum::{
     extract::{Path, State},
     http::{StatusCode, header},
-    response::{Html, IntoResponse},
+    response::{Html, IntoResponse, Response},
 };
-use std::sync::Arc;
-use tracing::info;
+use std::sync::{Arc, OnceLock};
+use tracing::{info, warn};
+use yaml_rust2::YamlLoader;
 
+use crate::core::config::app_config::AppConfig;
 use crate::core::router::AppState;
 
+/// Swagger UI release loaded from the CDN; bump it to upgrade
+pub const SWAGGER_UI_VERSION: &str = "5.17.14";
+
+/// Cache policy for content-addressed assets
+pub const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Path the hashed assets are served under
+const ASSETS_PATH: &str = "/actuator/docs/assets";
+
+/// A static file embedded in the binary
+struct DocsAsset {
+    name: &'static str,
+    content_type: &'static str,
+    body: &'static [u8],
+}
+
+static DOCS_ASSETS: [DocsAsset; 2] = [
+    DocsAsset {
+        name: "swagger-init.js",
+        content_type: "text/javascript; charset=utf-8",
+        body: include_bytes!("docs_assets/swagger-init.js"),
+    },
+    DocsAsset {
+        name: "docs.css",
+        content_type: "text/css; charset=utf-8",
+        body: include_bytes!("docs_assets/docs.css"),
+    },
+];
+
+/// FNV-1a, stable across builds and platforms
+fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// `name` with the hash of `body` before the extension
+fn hashed_name(name: &str, body: &[u8]) -> String {
+    let hash = format!("{:016x}", content_hash(body));
+    match name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}.{}.{}", stem, hash, extension),
+        None => format!("{}.{}", name, hash),
+    }
+}
+
+/// Every embedded asset with its hashed name
+fn hashed_assets() -> &'static [(String, &'static DocsAsset)] {
+    static ASSETS: OnceLock<Vec<(String, &'static DocsAsset)>> = OnceLock::new();
+    ASSETS.get_or_init(|| {
+        DOCS_ASSETS
+            .iter()
+            .map(|asset| (hashed_name(asset.name, asset.body), asset))
+            .collect()
+    })
+}
+
+/// URL of the embedded asset `name`, including its content hash
+pub fn docs_asset_url(name: &str) -> Option<String> {
+    hashed_assets()
+        .iter()
+        .find(|(_, asset)| asset.name == name)
+        .map(|(hashed, _)| format!("{}/{}", ASSETS_PATH, hashed))
+}
+
 /// Serves the Swagger UI HTML for the API documentation
 pub async fn swagger_ui_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     info!("Serving Swagger UI documentation");
@@ -16,7 +94,10 @@ pub async fn swagger_ui_handler(State(state): State<Arc<AppState>>) -> impl Into
     let spec_url = state.config.openapi_spec_url();
     info!("Using OpenAPI spec URL from config: {}", spec_url);
 
-    // Create a simple HTML page with Swagger UI
+    let init_script = docs_asset_url("swagger-init.js").unwrap_or_default();
+    let stylesheet = docs_asset_url("docs.css").unwrap_or_default();
+    let cdn = format!("https://unpkg.com/swagger-ui-dist@{}", SWAGGER_UI_VERSION);
+
     let html = format!(
         r#"<!DOCTYPE html>
 <html lang="en">
@@ -24,34 +105,149 @@ pub async fn swagger_ui_handler(State(state): State<Arc<AppState>>) -> impl Into
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>Navius API Documentation</title>
-    <link rel="stylesheet" type="text/css" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+    <link rel="stylesheet" type="text/css" href="{cdn}/swagger-ui.css">
+    <link rel="stylesheet" type="text/css" href="{stylesheet}">
 </head>
 <body>
-    <div id="swagger-ui"></div>
-    <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
-    <script>
-        window.onload = function() {{
-            SwaggerUIBundle({{
-                url: "{spec_url}",
-                dom_id: '#swagger-ui',
-                deepLinking: true,
-                presets: [
-                    SwaggerUIBundle.presets.apis,
-                    SwaggerUIBundle.SwaggerUIStandalonePreset
-                ],
-                layout: "BaseLayout",
-                syntaxHighlight: {{
-                    activated: true,
-                    theme: "agate"
-                }}
-            }});
-        }};
-    </script>
+    <div id="swagger-ui" data-spec-url="{spec_url}"></div>
+    <script src="{cdn}/swagger-ui-bundle.js"></script>
+    <script src="{init_script}"></script>
 </body>
 </html>"#
     );
 
-    Html(html)
+    // The page names the current asset versions, so it must always be revalidated
+    ([(header::CACHE_CONTROL, "no-cache")], Html(html))
+}
+
+/// Serves an embedded asset by its hashed name
+pub async fn docs_asset_handler(Path(file): Path<String>) -> Response {
+    match hashed_assets().iter().find(|(hashed, _)| *hashed == file) {
+        Some((_, asset)) => (
+            [
+                (header::CONTENT_TYPE, asset.content_type),
+                (header::CACHE_CONTROL, IMMUTABLE_CACHE_CONTROL),
+            ],
+            asset.body,
+        )
+            .into_response(),
+        // Unknown or outdated hash
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Why a spec file couldn't be served
+#[derive(Debug, thiserror::Error)]
+pub enum SpecError {
+    #[error("failed to read OpenAPI spec: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("OpenAPI spec exceeds the {limit} byte limit")]
+    TooLarge { limit: u64 },
+
+    #[error("OpenAPI spec is not valid YAML or JSON: {0}")]
+    Malformed(String),
+
+    #[error("OpenAPI spec is invalid: {0}")]
+    Invalid(String),
+}
+
+/// A spec ready to serve
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenApiDocument {
+    pub content: String,
+    pub content_type: &'static str,
+}
+
+impl OpenApiDocument {
+    /// Empty spec served in place of one that failed to load
+    pub fn fallback() -> Self {
+        Self {
+            content: serde_json::json!({
+                "openapi": "3.0.3",
+                "info": { "title": "Navius API", "version": "0.0.0" },
+                "paths": {}
+            })
+            .to_string(),
+            content_type: "application/json",
+        }
+    }
+}
+
+/// Read and validate the spec at `path`, refusing files over `max_bytes`
+pub fn read_openapi_spec(path: &str, max_bytes: u64) -> Result<OpenApiDocument, SpecError> {
+    use std::io::Read;
+
+    // Read one byte past the limit so an oversized file is never fully loaded
+    let mut content = String::new();
+    std::fs::File::open(path)?
+        .take(max_bytes.saturating_add(1))
+        .read_to_string(&mut content)?;
+    if content.len() as u64 > max_bytes {
+        return Err(SpecError::TooLarge { limit: max_bytes });
+    }
+
+    validate_openapi_spec(&content)?;
+
+    let content_type = if path.ends_with(".yaml") || path.ends_with(".yml") {
+        "text/yaml"
+    } else {
+        "application/json"
+    };
+    Ok(OpenApiDocument {
+        content,
+        content_type,
+    })
+}
+
+/// Check that `content` parses and has the top-level shape of an OpenAPI document
+fn validate_openapi_spec(content: &str) -> Result<(), SpecError> {
+    // YAML 1.2 is a superset of JSON, so one parser covers both formats
+    let documents =
+        YamlLoader::load_from_str(content).map_err(|e| SpecError::Malformed(e.to_string()))?;
+    let root = match documents.as_slice() {
+        [root] => root,
+        _ => {
+            return Err(SpecError::Malformed(
+                "expected exactly one document".to_string(),
+            ));
+        }
+    };
+    if root.as_hash().is_none() {
+        return Err(SpecError::Invalid(
+            "top level must be a mapping".to_string(),
+        ));
+    }
+
+    let version = root["openapi"].as_str().or(root["swagger"].as_str());
+    if !version.is_some_and(|version| version.starts_with("3.") || version == "2.0") {
+        return Err(SpecError::Invalid(
+            "missing or unsupported 'openapi' version".to_string(),
+        ));
+    }
+    if root["info"].as_hash().is_none() {
+        return Err(SpecError::Invalid("missing 'info' object".to_string()));
+    }
+    if root["paths"].as_hash().is_none() {
+        return Err(SpecError::Invalid("missing 'paths' object".to_string()));
+    }
+    Ok(())
+}
+
+/// The spec at `path`, or [`OpenApiDocument::fallback`] with a warning if it can't be used
+pub fn load_openapi_spec_file(path: &str, max_bytes: u64) -> OpenApiDocument {
+    match read_openapi_spec(path, max_bytes) {
+        Ok(document) => document,
+        Err(e) => {
+            warn!("Serving an empty OpenAPI spec instead of {}: {}", path, e);
+            OpenApiDocument::fallback()
+        }
+    }
+}
+
+/// The configured spec, falling back like [`load_openapi_spec_file`]
+pub fn load_openapi_spec(config: &AppConfig) -> OpenApiDocument {
+    load_openapi_spec_file(&config.openapi_spec_path(), config.openapi.max_spec_bytes)
 }
 
 /// Serves the OpenAPI specification file
@@ -74,29 +270,176 @@ pub async fn openapi_spec_handler(
 
     info!("Serving OpenAPI specification file: {}", file);
 
-    // Get the path to the OpenAPI spec file from config
-    let spec_path = state.config.openapi_spec_path();
-
-    // Read the file
-    match std::fs::read_to_string(&spec_path) {
-        Ok(content) => {
-            // Determine the content type based on file extension
-            let content_type = if spec_path.ends_with(".yaml") || spec_path.ends_with(".yml") {
-                "text/yaml"
-            } else {
-                "application/json" // Default to JSON
-            };
-
-            (
-                StatusCode::OK,
-                [(header::CONTENT_TYPE, content_type)],
-                content,
-            )
+    let document = load_openapi_spec(&state.config);
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, document.content_type)],
+        document.content,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/actuator/docs", get(swagger_ui_handler))
+            .route("/actuator/docs/assets/{file}", get(docs_asset_handler))
+            .with_state(Arc::new(AppState::default()))
+    }
+
+    async fn get_path(uri: &str) -> Response {
+        let request = axum::http::Request::builder()
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        app().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_html_references_hashed_assets_served_immutable() {
+        let response = get_path("/actuator/docs").await;
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+
+        for asset in &DOCS_ASSETS {
+            let url = docs_asset_url(asset.name).unwrap();
+            assert!(html.contains(&url), "{} not referenced in {}", url, html);
+            let hash = format!("{:016x}", content_hash(asset.body));
+            assert!(url.contains(&hash), "{}", url);
+
+            let response = get_path(&url).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers()[header::CACHE_CONTROL],
+                IMMUTABLE_CACHE_CONTROL
+            );
         }
-        Err(e) => (
-            StatusCode::NOT_FOUND,
-            [(header::CONTENT_TYPE, "text/plain")],
-            format!("OpenAPI spec file not found: {}", e),
-        ),
+    }
+
+    #[tokio::test]
+    async fn test_unhashed_or_stale_asset_names_are_not_found() {
+        for uri in [
+            "/actuator/docs/assets/swagger-init.js",
+            "/actuator/docs/assets/swagger-init.0000000000000000.js",
+        ] {
+            assert_eq!(get_path(uri).await.status(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    fn write_spec(dir: &tempfile::TempDir, name: &str, content: &str) -> String {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_valid_spec_is_served_as_is() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = "openapi: 3.0.3\ninfo:\n  title: Pets\n  version: '1'\npaths: {}\n";
+        let path = write_spec(&dir, "pets.yaml", spec);
+
+        let document = read_openapi_spec(&path, 1024).unwrap();
+        assert_eq!(document.content, spec);
+        assert_eq!(document.content_type, "text/yaml");
+    }
+
+    #[test]
+    fn test_malformed_spec_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_spec(&dir, "broken.yaml", "openapi: 3.0.3\ninfo: [unclosed\n");
+        assert!(matches!(
+            read_openapi_spec(&path, 1024),
+            Err(SpecError::Malformed(_))
+        ));
+
+        let path = write_spec(&dir, "other.json", r#"{"name": "not a spec"}"#);
+        assert!(matches!(
+            read_openapi_spec(&path, 1024),
+            Err(SpecError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_oversized_spec_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let padding = "#".repeat(2048);
+        let spec = format!("{}\nopenapi: 3.0.3\ninfo: {{}}\npaths: {{}}\n", padding);
+        let path = write_spec(&dir, "huge.yaml", &spec);
+
+        assert!(matches!(
+            read_openapi_spec(&path, 1024),
+            Err(SpecError::TooLarge { limit: 1024 })
+        ));
+        assert!(read_openapi_spec(&path, 4096).is_ok());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_malformed_spec_logs_warning_and_falls_back() {
+        use std::sync::Mutex;
+
+        #[derive(Clone, Default)]
+        struct Captured(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Captured {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_spec(&dir, "broken.yaml", "openapi: [");
+        let logs = Captured::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        let document =
+            tracing::subscriber::with_default(subscriber, || load_openapi_spec_file(&path, 1024));
+
+        assert_eq!(document, OpenApiDocument::fallback());
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("WARN"), "{}", logs);
+        assert!(logs.contains("broken.yaml"), "{}", logs);
+    }
+
+    #[tokio::test]
+    async fn test_unusable_spec_is_served_as_empty_spec() {
+        let mut state = AppState::default();
+        state.config.openapi.spec_file = "does-not-exist.yaml".to_string();
+        let app = Router::new()
+            .route("/actuator/docs/{*file}", get(openapi_spec_handler))
+            .with_state(Arc::new(state));
+
+        let request = axum::http::Request::builder()
+            .uri("/actuator/docs/does-not-exist.yaml")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, OpenApiDocument::fallback().content);
+    }
+
+    #[test]
+    fn test_hashed_name_changes_with_content() {
+        assert_ne!(hashed_name("app.js", b"v1"), hashed_name("app.js", b"v2"));
+        assert!(hashed_name("app.js", b"v1").ends_with(".js"));
     }
 }
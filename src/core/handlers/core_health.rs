@@ -1,11 +1,22 @@
-use axum::{extract::State, http::StatusCode, response::Json};
+use axum::{
+    extract::{Query, State},
+    http::{Extensions, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
 use serde_json::{Value, json};
-use std::{sync::Arc, time::SystemTime};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
+#[cfg(feature = "auth")]
+use crate::core::auth::providers::ProviderRegistry;
 use crate::core::{
     models::{DependencyStatus, DetailedHealthResponse, HealthCheckResponse},
     router::AppState,
     services::{
+        downstream_health::DownstreamHealthCheck,
         health::HealthService,
         health_indicators::CoreHealthIndicatorProvider,
         health_provider::{HealthConfig, HealthIndicatorProviderRegistry, HealthServiceV2},
@@ -26,23 +37,72 @@ pub async fn health_handler() -> Json<HealthCheckResponse> {
 
 /// Detailed health check that follows Spring Boot Actuator format
 /// Returns components with their statuses and details
-pub async fn detailed_health_handler(State(state): State<Arc<AppState>>) -> Json<Value> {
+pub async fn detailed_health_handler(
+    State(state): State<Arc<AppState>>,
+    extensions: Extensions,
+) -> Json<Value> {
+    Json(aggregate_health(&state, &extensions).await)
+}
+
+/// Query parameters of the readiness endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct ReadinessQuery {
+    /// Check again even if a recent result is cached
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Readiness check including downstream dependencies
+///
+/// Returns 503 when a critical component is down. Non-critical failures
+/// make the status `DEGRADED` and are listed under `degraded`, but still
+/// return 200. The result is reused
+/// for `health.readiness_cache_ms`; `?refresh=true` checks again.
+pub async fn readiness_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReadinessQuery>,
+    extensions: Extensions,
+) -> (StatusCode, Json<Value>) {
+    let ttl = Duration::from_millis(state.config.health.readiness_cache_ms);
+    let health = state
+        .readiness_cache
+        .get_or_check(ttl, query.refresh, || aggregate_health(&state, &extensions))
+        .await;
+    let status = if health["status"] == "DOWN" {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(health))
+}
+
+/// Aggregate the core indicators, downstream checks and, when the router
+/// provides a [`ProviderRegistry`] extension, the auth providers
+#[cfg_attr(not(feature = "auth"), allow(unused_variables))]
+async fn aggregate_health(state: &Arc<AppState>, extensions: &Extensions) -> Value {
     // Create health service with provider system
     let mut registry = HealthIndicatorProviderRegistry::new();
 
     // Register the core health indicators provider
     registry.register(Box::new(CoreHealthIndicatorProvider));
 
-    // Create the health service with default config
-    let health_service = HealthServiceV2::new(Arc::new(registry), HealthConfig::default());
+    // Create the health service with default config and the configured downstream checks
+    let health_service = HealthServiceV2::new(Arc::new(registry), HealthConfig::default())
+        .with_downstream(DownstreamHealthCheck::all_from_config(&state.config.health))
+        .with_criticality(state.config.health.criticality.clone());
+    #[cfg(feature = "auth")]
+    let health_service = match extensions.get::<Arc<ProviderRegistry>>() {
+        Some(providers) => health_service.with_auth_providers(providers.clone()),
+        None => health_service,
+    };
 
     // Get health status from service
-    match health_service.check_health(&state).await {
-        Ok(health_status) => Json(health_status),
-        Err(_) => Json(json!({
+    match health_service.check_health(state).await {
+        Ok(health_status) => health_status,
+        Err(_) => json!({
             "status": "DOWN",
             "components": {}
-        })),
+        }),
     }
 }
 
@@ -64,7 +124,7 @@ mod tests {
     #[tokio::test]
     async fn test_detailed_health_handler() {
         let state = AppState::default();
-        let response = detailed_health_handler(State(Arc::new(state))).await;
+        let response = detailed_health_handler(State(Arc::new(state)), Extensions::new()).await;
 
         // Get the response as a Value
         let health_status = response.0;
@@ -84,4 +144,94 @@ mod tests {
         assert!(components.contains_key("env"));
         assert!(components.contains_key("services"));
     }
+
+    fn state_with_downstream(url: String, critical: bool) -> Arc<AppState> {
+        let mut config = AppConfig::default();
+        config.health.downstream.insert(
+            "payments".to_string(),
+            crate::core::config::app_config::DownstreamHealthConfig {
+                url,
+                timeout_ms: 500,
+                critical,
+            },
+        );
+        Arc::new(AppState {
+            config,
+            ..Default::default()
+        })
+    }
+
+    async fn downstream_down() -> wiremock::MockServer {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_critical_downstream_down_is_not_ready() {
+        let server = downstream_down().await;
+        let state = state_with_downstream(format!("{}/health", server.uri()), true);
+
+        let (status, Json(body)) = readiness_handler(
+            State(state),
+            Query(ReadinessQuery::default()),
+            Extensions::new(),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "DOWN");
+        assert_eq!(body["components"]["payments"]["status"], "DOWN");
+    }
+
+    #[tokio::test]
+    async fn test_rapid_readiness_probes_share_one_check() {
+        let server = downstream_down().await;
+        let state = state_with_downstream(format!("{}/health", server.uri()), true);
+
+        for _ in 0..3 {
+            let (status, _) = readiness_handler(
+                State(state.clone()),
+                Query(ReadinessQuery::default()),
+                Extensions::new(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        }
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        let refresh = ReadinessQuery { refresh: true };
+        readiness_handler(State(state), Query(refresh), Extensions::new()).await;
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_critical_downstream_down_is_degraded() {
+        let server = downstream_down().await;
+        let state = state_with_downstream(format!("{}/health", server.uri()), false);
+
+        let (status, Json(body)) = readiness_handler(
+            State(state),
+            Query(ReadinessQuery::default()),
+            Extensions::new(),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "DEGRADED");
+        assert_eq!(body["components"]["payments"]["status"], "DOWN");
+        assert_eq!(
+            body["components"]["payments"]["criticality"],
+            "non_critical"
+        );
+        assert!(
+            body["degraded"]
+                .as_array()
+                .unwrap()
+                .contains(&json!("payments"))
+        );
+    }
 }
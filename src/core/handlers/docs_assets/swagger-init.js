@@ -0,0 +1,18 @@
+// Starts Swagger UI against the spec URL given on the #swagger-ui element
+window.onload = function () {
+    var container = document.getElementById("swagger-ui");
+    SwaggerUIBundle({
+        url: container.dataset.specUrl,
+        dom_id: "#swagger-ui",
+        deepLinking: true,
+        presets: [
+            SwaggerUIBundle.presets.apis,
+            SwaggerUIBundle.SwaggerUIStandalonePreset
+        ],
+        layout: "BaseLayout",
+        syntaxHighlight: {
+            activated: true,
+            theme: "agate"
+        }
+    });
+};
@@ -1,29 +1,35 @@
+pub mod latency;
 pub mod metrics_handler;
 pub mod metrics_service;
+pub mod request_stats;
+pub mod route_metrics;
 
 // Import required external dependencies
 #[cfg(feature = "metrics")]
-use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use metrics_exporter_prometheus::PrometheusHandle;
 
 // Re-export key components for easier access
+pub use latency::{LatencyTracker, RouteLatency, track_latency};
 pub use metrics_handler::{
-    create_key, export_metrics, metrics_handler, try_get_counter, try_get_counter_with_labels,
-    try_get_gauge, try_get_gauge_with_labels, try_record_metrics,
+    MetricsError, create_key, export_metrics, metrics_handler, try_get_counter,
+    try_get_counter_with_labels, try_get_gauge, try_get_gauge_with_labels, try_record_metrics,
 };
 pub use metrics_service::metrics_endpoint_handler;
+pub use request_stats::{RequestStats, RequestSummary, track_requests};
+pub use route_metrics::{RouteMetrics, record_request_metrics};
 
 /// Initialize metrics with Prometheus for easy recording
+///
+/// Idempotent: repeated calls return the handle of the installed recorder.
 #[cfg(feature = "metrics")]
-pub fn init_metrics() -> PrometheusHandle {
-    PrometheusBuilder::new()
-        .install_recorder()
-        .expect("Failed to install Prometheus recorder")
+pub fn init_metrics() -> Result<PrometheusHandle, MetricsError> {
+    metrics_handler::init_metrics()
 }
 
 #[cfg(not(feature = "metrics"))]
-pub fn init_metrics() -> () {
+pub fn init_metrics() -> Result<(), MetricsError> {
     // No-op when metrics feature is disabled
-    ()
+    Ok(())
 }
 
 // Metric recording functions with static strings
@@ -0,0 +1,292 @@
+//! Rolling per-route latency percentiles
+//!
+//! [`LatencyTracker`] keeps the most recent samples for each route pattern
+//! in a fixed-size ring buffer, so memory stays bounded however much
+//! traffic a route sees. Routes are keyed by their matched pattern (e.g.
+//! `GET /pets/{id}`) rather than the concrete path, and the number of
+//! tracked routes is capped as well; anything past the cap is folded into
+//! [`OTHER_ROUTE`].
+//!
+//! The metrics middleware records into [`LatencyTracker::global`], which
+//! `/actuator/latency` reports.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+
+/// Samples kept per route
+pub const DEFAULT_SAMPLES_PER_ROUTE: usize = 1024;
+
+/// Routes tracked before further routes are folded into [`OTHER_ROUTE`]
+pub const DEFAULT_MAX_ROUTES: usize = 256;
+
+/// Key for requests that matched no route
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Key for routes beyond the tracker's route limit
+pub const OTHER_ROUTE: &str = "other";
+
+/// Fixed-capacity ring buffer of latency samples in microseconds
+#[derive(Debug)]
+struct RouteSamples {
+    samples: Vec<u64>,
+    next: usize,
+    count: u64,
+}
+
+impl RouteSamples {
+    fn new(capacity: usize) -> Self {
+        Self {
+            samples: Vec::with_capacity(capacity),
+            next: 0,
+            count: 0,
+        }
+    }
+
+    fn record(&mut self, micros: u64, capacity: usize) {
+        if self.samples.len() < capacity {
+            self.samples.push(micros);
+        } else {
+            self.samples[self.next] = micros;
+        }
+        self.next = (self.next + 1) % capacity;
+        self.count += 1;
+    }
+}
+
+/// Latency percentiles for one route over its retained samples
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteLatency {
+    pub route: String,
+    /// Requests recorded since startup
+    pub count: u64,
+    /// Samples the percentiles were computed from
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Bounded, in-process store of recent request latencies per route
+#[derive(Debug, Clone)]
+pub struct LatencyTracker {
+    routes: Arc<Mutex<HashMap<String, RouteSamples>>>,
+    samples_per_route: usize,
+    max_routes: usize,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::with_limits(DEFAULT_SAMPLES_PER_ROUTE, DEFAULT_MAX_ROUTES)
+    }
+
+    /// Tracker keeping `samples_per_route` samples for at most `max_routes` routes
+    pub fn with_limits(samples_per_route: usize, max_routes: usize) -> Self {
+        Self {
+            routes: Arc::new(Mutex::new(HashMap::new())),
+            samples_per_route: samples_per_route.max(1),
+            max_routes: max_routes.max(1),
+        }
+    }
+
+    /// Process-wide tracker fed by the metrics middleware
+    pub fn global() -> &'static LatencyTracker {
+        static GLOBAL: OnceLock<LatencyTracker> = OnceLock::new();
+        GLOBAL.get_or_init(LatencyTracker::new)
+    }
+
+    /// Record one request to `route` that took `elapsed`
+    pub fn record(&self, route: &str, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+
+        // The overflow bucket doesn't count towards the limit
+        let tracked = routes.len() - usize::from(routes.contains_key(OTHER_ROUTE));
+        let key = if routes.contains_key(route) || tracked < self.max_routes {
+            route
+        } else {
+            OTHER_ROUTE
+        };
+
+        routes
+            .entry(key.to_string())
+            .or_insert_with(|| RouteSamples::new(self.samples_per_route))
+            .record(micros, self.samples_per_route);
+    }
+
+    /// Percentiles for every tracked route, sorted by route
+    pub fn snapshot(&self) -> Vec<RouteLatency> {
+        let routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        let mut snapshot: Vec<RouteLatency> = routes
+            .iter()
+            .map(|(route, samples)| {
+                let mut sorted = samples.samples.clone();
+                sorted.sort_unstable();
+                RouteLatency {
+                    route: route.clone(),
+                    count: samples.count,
+                    samples: sorted.len(),
+                    p50_ms: percentile(&sorted, 50.0),
+                    p95_ms: percentile(&sorted, 95.0),
+                    p99_ms: percentile(&sorted, 99.0),
+                    max_ms: sorted.last().map_or(0.0, |&max| to_millis(max)),
+                }
+            })
+            .collect();
+        snapshot.sort_by(|a, b| a.route.cmp(&b.route));
+        snapshot
+    }
+
+    /// Percentiles for a single route
+    pub fn route(&self, route: &str) -> Option<RouteLatency> {
+        self.snapshot()
+            .into_iter()
+            .find(|latency| latency.route == route)
+    }
+
+    /// Forget all recorded samples
+    pub fn reset(&self) {
+        self.routes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Nearest-rank percentile of `sorted` microsecond samples, in milliseconds
+fn percentile(sorted: &[u64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    to_millis(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn to_millis(micros: u64) -> f64 {
+    micros as f64 / 1000.0
+}
+
+/// Route key for a request: method plus matched pattern
+pub fn route_key(request: &Request) -> String {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str);
+    format!("{} {}", request.method(), route)
+}
+
+/// Middleware recording request latency into the given tracker
+pub async fn track_latency(
+    State(tracker): State<LatencyTracker>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = route_key(&request);
+    let start = Instant::now();
+    let response = next.run(request).await;
+    tracker.record(&route, start.elapsed());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= expected * 0.02,
+            "expected ~{expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_percentiles_of_known_latencies() {
+        let tracker = LatencyTracker::new();
+        for ms in 1..=1000 {
+            tracker.record("GET /pets", Duration::from_millis(ms));
+        }
+
+        let latency = tracker.route("GET /pets").unwrap();
+        assert_eq!(latency.count, 1000);
+        assert_close(latency.p50_ms, 500.0);
+        assert_close(latency.p95_ms, 950.0);
+        assert_close(latency.p99_ms, 990.0);
+        assert_close(latency.max_ms, 1000.0);
+    }
+
+    #[test]
+    fn test_samples_are_bounded_and_recent() {
+        let tracker = LatencyTracker::with_limits(100, 10);
+        for _ in 0..1000 {
+            tracker.record("GET /slow", Duration::from_millis(500));
+        }
+        for _ in 0..100 {
+            tracker.record("GET /slow", Duration::from_millis(10));
+        }
+
+        let latency = tracker.route("GET /slow").unwrap();
+        assert_eq!(latency.count, 1100);
+        assert_eq!(latency.samples, 100);
+        assert_close(latency.p99_ms, 10.0);
+        assert_close(latency.max_ms, 10.0);
+    }
+
+    #[test]
+    fn test_route_count_is_bounded() {
+        let tracker = LatencyTracker::with_limits(10, 2);
+        for route in ["GET /a", "GET /b", "GET /c", "GET /d"] {
+            tracker.record(route, Duration::from_millis(1));
+        }
+        tracker.record("GET /a", Duration::from_millis(1));
+
+        let routes: Vec<String> = tracker.snapshot().into_iter().map(|l| l.route).collect();
+        assert_eq!(routes, vec!["GET /a", "GET /b", OTHER_ROUTE]);
+        assert_eq!(tracker.route(OTHER_ROUTE).unwrap().count, 2);
+        assert_eq!(tracker.route("GET /a").unwrap().count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_keys_by_route_pattern() {
+        let tracker = LatencyTracker::new();
+        let app = Router::new()
+            .route("/pets/{id}", get(|| async { "pet" }))
+            .layer(axum::middleware::from_fn_with_state(
+                tracker.clone(),
+                track_latency,
+            ));
+
+        for uri in ["/pets/1", "/pets/2", "/missing"] {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(tracker.route("GET /pets/{id}").unwrap().count, 2);
+        assert_eq!(
+            tracker
+                .route(&format!("GET {}", UNMATCHED_ROUTE))
+                .unwrap()
+                .count,
+            1
+        );
+    }
+}
@@ -2,16 +2,41 @@ use metrics::{Label, counter, gauge, histogram};
 #[cfg(feature = "metrics")]
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use std::collections::HashMap;
+#[cfg(feature = "metrics")]
+use std::sync::Mutex;
+use thiserror::Error;
 use tracing::error;
 
 use crate::core::error::AppError;
 
+/// Error installing the metrics recorder
+#[derive(Debug, Error)]
+pub enum MetricsError {
+    #[error("Failed to install Prometheus recorder: {0}")]
+    Install(String),
+}
+
+/// Handle of the recorder installed by [`init_metrics`]
+#[cfg(feature = "metrics")]
+static INSTALLED_HANDLE: Mutex<Option<PrometheusHandle>> = Mutex::new(None);
+
 /// Initialize metrics with Prometheus
+///
+/// Installs the global recorder on the first call; later calls return the
+/// handle of the recorder already installed. Fails if a different recorder
+/// was installed by other code. Without a recorder, metric macros are no-ops.
 #[cfg(feature = "metrics")]
-pub fn init_metrics() -> PrometheusHandle {
-    PrometheusBuilder::new()
+pub fn init_metrics() -> Result<PrometheusHandle, MetricsError> {
+    let mut installed = INSTALLED_HANDLE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(handle) = installed.as_ref() {
+        return Ok(handle.clone());
+    }
+
+    let handle = PrometheusBuilder::new()
         .install_recorder()
-        .expect("Failed to install Prometheus recorder")
+        .map_err(|e| MetricsError::Install(e.to_string()))?;
+    *installed = Some(handle.clone());
+    Ok(handle)
 }
 
 /// Export metrics in Prometheus format
@@ -174,4 +199,15 @@ mod tests {
         let key = create_key("test_metric");
         assert_eq!(key, "navius_test_metric");
     }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_init_metrics_twice_returns_usable_handle() {
+        let first = init_metrics().expect("first install");
+        let second = init_metrics().expect("second install is a no-op");
+
+        counter!("navius_init_twice_total").increment(1);
+        assert!(second.render().contains("navius_init_twice_total"));
+        assert_eq!(first.render(), second.render());
+    }
 }
@@ -1,22 +1,22 @@
-use crate::core::metrics::metrics_handler;
+use crate::core::metrics::metrics_handler::{self, MetricsError};
 #[cfg(feature = "metrics")]
 use metrics_exporter_prometheus::PrometheusHandle;
 use tracing::info;
 
 /// Initialize metrics system
 #[cfg(feature = "metrics")]
-pub fn init_metrics() -> PrometheusHandle {
+pub fn init_metrics() -> Result<PrometheusHandle, MetricsError> {
     // Create a Prometheus exporter
-    let handle = metrics_handler::init_metrics();
+    let handle = metrics_handler::init_metrics()?;
 
     info!("📊 Metrics system initialized");
-    handle
+    Ok(handle)
 }
 
 #[cfg(not(feature = "metrics"))]
-pub fn init_metrics() -> () {
+pub fn init_metrics() -> Result<(), MetricsError> {
     info!("📊 Metrics system disabled (feature 'metrics' not enabled)");
-    ()
+    Ok(())
 }
 
 /// Try to record metrics and return the raw metrics text
@@ -0,0 +1,209 @@
+//! Aggregate request counters since startup
+//!
+//! [`RequestStats`] counts completed requests by status class, tracks how
+//! many are in flight and keeps recent latencies for an overall average and
+//! p95. The metrics middleware records into [`RequestStats::global`], which
+//! `/actuator/summary` reports.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+
+use crate::core::metrics::latency::{DEFAULT_SAMPLES_PER_ROUTE, LatencyTracker};
+
+/// Key all samples are recorded under in the overall latency tracker
+const ALL_REQUESTS: &str = "all";
+
+/// Status classes reported, indexed by the status code's first digit minus one
+const STATUS_CLASSES: [&str; 5] = ["1xx", "2xx", "3xx", "4xx", "5xx"];
+
+#[derive(Debug, Default)]
+struct Counters {
+    total: AtomicU64,
+    by_class: [AtomicU64; 5],
+    in_flight: AtomicI64,
+    latency_micros: AtomicU64,
+}
+
+/// Point-in-time view of [`RequestStats`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestSummary {
+    /// Requests completed since startup
+    pub total_requests: u64,
+    /// Completed requests per status class (`2xx`, `4xx`, ...)
+    pub status_classes: BTreeMap<String, u64>,
+    /// Requests currently being handled
+    pub in_flight: u64,
+    /// Mean latency over every completed request
+    pub average_latency_ms: f64,
+    /// 95th percentile over recent requests
+    pub p95_latency_ms: f64,
+}
+
+impl RequestSummary {
+    /// Completed requests in `class`, e.g. `"5xx"`
+    pub fn status_class(&self, class: &str) -> u64 {
+        self.status_classes.get(class).copied().unwrap_or(0)
+    }
+}
+
+/// Process-wide request counters; clones share the same counts
+#[derive(Debug, Clone)]
+pub struct RequestStats {
+    counters: Arc<Counters>,
+    latency: LatencyTracker,
+}
+
+impl RequestStats {
+    pub fn new() -> Self {
+        Self {
+            counters: Arc::new(Counters::default()),
+            latency: LatencyTracker::with_limits(DEFAULT_SAMPLES_PER_ROUTE, 1),
+        }
+    }
+
+    /// Stats fed by the metrics middleware
+    pub fn global() -> &'static RequestStats {
+        static GLOBAL: OnceLock<RequestStats> = OnceLock::new();
+        GLOBAL.get_or_init(RequestStats::new)
+    }
+
+    /// Count a request as in flight until the returned guard is finished or dropped
+    pub fn start(&self) -> InFlight {
+        self.counters.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight {
+            stats: self.clone(),
+            started: Instant::now(),
+        }
+    }
+
+    fn complete(&self, status: StatusCode, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.counters.total.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .latency_micros
+            .fetch_add(micros, Ordering::Relaxed);
+        let class = usize::from(status.as_u16() / 100);
+        if let Some(counter) = class
+            .checked_sub(1)
+            .and_then(|i| self.counters.by_class.get(i))
+        {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        self.latency.record(ALL_REQUESTS, elapsed);
+    }
+
+    /// Current counts
+    pub fn snapshot(&self) -> RequestSummary {
+        let total = self.counters.total.load(Ordering::Relaxed);
+        let latency_micros = self.counters.latency_micros.load(Ordering::Relaxed);
+        RequestSummary {
+            total_requests: total,
+            status_classes: STATUS_CLASSES
+                .iter()
+                .zip(&self.counters.by_class)
+                .map(|(class, count)| (class.to_string(), count.load(Ordering::Relaxed)))
+                .collect(),
+            in_flight: self.counters.in_flight.load(Ordering::Relaxed).max(0) as u64,
+            average_latency_ms: if total == 0 {
+                0.0
+            } else {
+                latency_micros as f64 / total as f64 / 1000.0
+            },
+            p95_latency_ms: self
+                .latency
+                .route(ALL_REQUESTS)
+                .map_or(0.0, |latency| latency.p95_ms),
+        }
+    }
+}
+
+impl Default for RequestStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A request counted as in flight
+///
+/// Dropping it without [`finish`](InFlight::finish) (the client went away)
+/// only ends the in-flight count.
+#[derive(Debug)]
+pub struct InFlight {
+    stats: RequestStats,
+    started: Instant,
+}
+
+impl InFlight {
+    /// Record the request as completed with `status`
+    pub fn finish(self, status: StatusCode) {
+        self.stats.complete(status, self.started.elapsed());
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.stats
+            .counters
+            .in_flight
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Middleware recording requests into the given stats
+pub async fn track_requests(
+    State(stats): State<RequestStats>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let in_flight = stats.start();
+    let response = next.run(request).await;
+    in_flight.finish(response.status());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_by_status_class() {
+        let stats = RequestStats::new();
+        for status in [200, 201, 404, 500, 503] {
+            stats.start().finish(StatusCode::from_u16(status).unwrap());
+        }
+
+        let summary = stats.snapshot();
+        assert_eq!(summary.total_requests, 5);
+        assert_eq!(summary.status_class("2xx"), 2);
+        assert_eq!(summary.status_class("4xx"), 1);
+        assert_eq!(summary.status_class("5xx"), 2);
+        assert_eq!(summary.status_class("3xx"), 0);
+        assert_eq!(summary.in_flight, 0);
+    }
+
+    #[test]
+    fn test_in_flight_until_finished_or_dropped() {
+        let stats = RequestStats::new();
+        let first = stats.start();
+        let second = stats.start();
+        assert_eq!(stats.snapshot().in_flight, 2);
+
+        first.finish(StatusCode::OK);
+        drop(second);
+
+        let summary = stats.snapshot();
+        assert_eq!(summary.in_flight, 0);
+        // Abandoned requests aren't counted as completed
+        assert_eq!(summary.total_requests, 1);
+    }
+}
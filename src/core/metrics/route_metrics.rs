@@ -0,0 +1,271 @@
+//! Per-route request metrics
+//!
+//! The metrics middleware records `http_requests_total` and
+//! `http_request_duration_seconds` for every request, labelled with
+//! `method`, `route` (the matched pattern, e.g. `/pets/{id}`) and `status`,
+//! and feeds [`LatencyTracker::global`] and [`RequestStats::global`]. [`RouteMetrics`] adjusts that per
+//! route when the router is built:
+//!
+//! ```ignore
+//! let app = RouterBuilder::new()
+//!     .with_route_metrics(
+//!         RouteMetrics::new()
+//!             .exclude("/actuator/health")
+//!             .with_label("/api/pets", "team", "pets")
+//!             .with_header_label("/api/pets/{id}", "tenant", "x-tenant-id"),
+//!     )
+//!     .build();
+//! ```
+//!
+//! Header labels take whatever the client sends, so only use them for
+//! headers with a small, known set of values.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{HeaderMap, HeaderName},
+    middleware::Next,
+    response::Response,
+};
+use metrics::{Label, counter, histogram};
+use tracing::warn;
+
+use crate::core::metrics::latency::{LatencyTracker, UNMATCHED_ROUTE, route_key};
+use crate::core::metrics::request_stats::RequestStats;
+
+/// Requests handled, by method, route and status
+pub const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+
+/// Request latency in seconds, by method, route and status
+pub const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
+
+/// Value used when a header label's header is missing
+pub const MISSING_LABEL_VALUE: &str = "none";
+
+#[derive(Debug, Clone)]
+enum LabelValue {
+    Static(String),
+    Header(HeaderName),
+}
+
+#[derive(Debug, Clone, Default)]
+struct RouteRule {
+    excluded: bool,
+    labels: Vec<(String, LabelValue)>,
+}
+
+/// Metric settings keyed by route pattern
+#[derive(Debug, Clone, Default)]
+pub struct RouteMetrics {
+    routes: HashMap<String, RouteRule>,
+}
+
+impl RouteMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Don't record requests to `route`
+    pub fn exclude(mut self, route: &str) -> Self {
+        self.rule(route).excluded = true;
+        self
+    }
+
+    /// Attach `name="value"` to the series of `route`
+    pub fn with_label(mut self, route: &str, name: &str, value: &str) -> Self {
+        self.rule(route)
+            .labels
+            .push((name.to_string(), LabelValue::Static(value.to_string())));
+        self
+    }
+
+    /// Attach `name` to the series of `route`, taking its value from `header`
+    pub fn with_header_label(mut self, route: &str, name: &str, header: &str) -> Self {
+        match HeaderName::try_from(header) {
+            Ok(header) => self
+                .rule(route)
+                .labels
+                .push((name.to_string(), LabelValue::Header(header))),
+            Err(_) => warn!(
+                "Ignoring metric label '{}': invalid header '{}'",
+                name, header
+            ),
+        }
+        self
+    }
+
+    /// Whether requests to `route` are left unrecorded
+    pub fn is_excluded(&self, route: &str) -> bool {
+        self.routes.get(route).is_some_and(|rule| rule.excluded)
+    }
+
+    fn rule(&mut self, route: &str) -> &mut RouteRule {
+        self.routes.entry(route.to_string()).or_default()
+    }
+
+    /// Extra labels for a request to `route`
+    fn labels(&self, route: &str, headers: &HeaderMap) -> Vec<Label> {
+        let Some(rule) = self.routes.get(route) else {
+            return Vec::new();
+        };
+        rule.labels
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    LabelValue::Static(value) => value.clone(),
+                    LabelValue::Header(header) => headers
+                        .get(header)
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or(MISSING_LABEL_VALUE)
+                        .to_string(),
+                };
+                Label::new(name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// Middleware recording request metrics according to `config`
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc<RouteMetrics>, record_request_metrics)`.
+pub async fn record_request_metrics(
+    State(config): State<Arc<RouteMetrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    observe(&config, request, next).await
+}
+
+/// Record `request` unless its route is excluded
+pub(crate) async fn observe(config: &RouteMetrics, request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_string();
+    if config.is_excluded(&route) {
+        return next.run(request).await;
+    }
+
+    let key = route_key(&request);
+    let mut labels = vec![
+        Label::new("method", request.method().to_string()),
+        Label::new("route", route.clone()),
+    ];
+    labels.extend(config.labels(&route, request.headers()));
+
+    let in_flight = RequestStats::global().start();
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = start.elapsed();
+    in_flight.finish(response.status());
+
+    LatencyTracker::global().record(&key, elapsed);
+    labels.push(Label::new("status", response.status().as_u16().to_string()));
+    counter!(HTTP_REQUESTS_TOTAL, labels.clone()).increment(1);
+    histogram!(HTTP_REQUEST_DURATION_SECONDS, labels).record(elapsed.as_secs_f64());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use tower::ServiceExt;
+
+    fn app(config: RouteMetrics) -> Router {
+        Router::new()
+            .route("/actuator/health", get(|| async { "UP" }))
+            .route("/pets/{id}", get(|| async { "pet" }))
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(config),
+                record_request_metrics,
+            ))
+    }
+
+    /// Send `requests` through `app` and render what was recorded
+    fn render(app: Router, requests: Vec<axum::http::Request<Body>>) -> String {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            // The recorder is thread-local, so run the requests on this thread
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    for request in requests {
+                        app.clone().oneshot(request).await.unwrap();
+                    }
+                });
+        });
+        handle.render()
+    }
+
+    fn get_request(uri: &str) -> axum::http::Request<Body> {
+        axum::http::Request::builder()
+            .uri(uri)
+            .header("x-tenant-id", "acme")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_excluded_route_is_not_recorded() {
+        let rendered = render(
+            app(RouteMetrics::new().exclude("/actuator/health")),
+            vec![get_request("/actuator/health"), get_request("/pets/1")],
+        );
+
+        assert!(!rendered.contains("/actuator/health"), "{}", rendered);
+        assert!(
+            rendered.contains(
+                "http_requests_total{method=\"GET\",route=\"/pets/{id}\",status=\"200\"} 1"
+            ),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn test_custom_labels_are_attached() {
+        let config = RouteMetrics::new()
+            .with_label("/pets/{id}", "team", "pets")
+            .with_header_label("/pets/{id}", "tenant", "x-tenant-id");
+        let rendered = render(
+            app(config),
+            vec![get_request("/pets/1"), get_request("/actuator/health")],
+        );
+
+        assert!(
+            rendered.contains(
+                "http_requests_total{method=\"GET\",route=\"/pets/{id}\",team=\"pets\",tenant=\"acme\",status=\"200\"} 1"
+            ),
+            "{}",
+            rendered
+        );
+        // Other routes keep the default labels
+        assert!(
+            rendered.contains(
+                "http_requests_total{method=\"GET\",route=\"/actuator/health\",status=\"200\"} 1"
+            ),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn test_missing_header_label_uses_placeholder() {
+        let config = RouteMetrics::new().with_header_label("/pets/{id}", "tenant", "x-tenant-id");
+        let request = axum::http::Request::builder()
+            .uri("/pets/1")
+            .body(Body::empty())
+            .unwrap();
+        let rendered = render(app(config), vec![request]);
+
+        assert!(rendered.contains("tenant=\"none\""), "{}", rendered);
+    }
+}
@@ -1,9 +1,13 @@
+pub mod batch;
 pub mod core_error;
 pub mod core_extensions;
 pub mod core_response;
 pub mod entity;
+pub mod id_generator;
 
+pub use batch::{BatchItem, BatchResult};
 pub use core_error::*;
 pub use core_extensions::*;
 pub use core_response::*;
 pub use entity::*;
+pub use id_generator::{IdGenerator, IdStrategy, Ulid, UlidGenerator, UuidV4Generator};
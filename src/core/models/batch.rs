@@ -0,0 +1,189 @@
+//! Per-item results for batch operations
+//!
+//! A batch fetch or create shouldn't fail as a whole because one item did.
+//! Handlers run every item, collect the outcomes into a [`BatchResult`] and
+//! return it: `200 OK` when every item succeeded, `207 Multi-Status` when
+//! results are mixed, and the items' shared status when all failed the same
+//! way. Items are reported in request order.
+//!
+//! ```ignore
+//! async fn create_pets(Json(pets): Json<Vec<NewPet>>) -> BatchResult<Pet> {
+//!     BatchResult::join_all(pets.into_iter().map(|pet| repository.create(pet))).await
+//! }
+//! ```
+
+use std::future::Future;
+
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::core::error::AppError;
+
+/// Outcome of one item in a batch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchItem<T> {
+    Ok {
+        value: T,
+    },
+    Error {
+        /// Error type, e.g. `not_found`
+        code: String,
+        /// HTTP status the item would have had on its own
+        http_status: u16,
+        message: String,
+    },
+}
+
+impl<T> BatchItem<T> {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok { .. })
+    }
+
+    fn http_status(&self) -> StatusCode {
+        match self {
+            Self::Ok { .. } => StatusCode::OK,
+            Self::Error { http_status, .. } => {
+                StatusCode::from_u16(*http_status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
+
+impl<T> From<Result<T, AppError>> for BatchItem<T> {
+    fn from(result: Result<T, AppError>) -> Self {
+        match result {
+            Ok(value) => Self::Ok { value },
+            Err(error) => Self::Error {
+                code: error.error_type(),
+                http_status: error.status_code().as_u16(),
+                message: error.detail(),
+            },
+        }
+    }
+}
+
+/// Response body for a batch operation, one entry per item
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchResult<T> {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub items: Vec<BatchItem<T>>,
+}
+
+impl<T> BatchResult<T> {
+    /// Await every item, without stopping at the first failure
+    pub async fn join_all<I, Fut>(items: I) -> Self
+    where
+        I: IntoIterator<Item = Fut>,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        futures::future::join_all(items).await.into_iter().collect()
+    }
+
+    /// Overall status: 200 if all succeeded, the shared status if all failed
+    /// alike, otherwise 207 Multi-Status
+    pub fn status(&self) -> StatusCode {
+        let mut statuses = self.items.iter().map(BatchItem::http_status);
+        let Some(first) = statuses.next() else {
+            return StatusCode::OK;
+        };
+        if self.succeeded == 0 && statuses.all(|status| status == first) {
+            return first;
+        }
+        if self.failed == 0 {
+            StatusCode::OK
+        } else {
+            StatusCode::MULTI_STATUS
+        }
+    }
+}
+
+impl<T> FromIterator<Result<T, AppError>> for BatchResult<T> {
+    fn from_iter<I: IntoIterator<Item = Result<T, AppError>>>(results: I) -> Self {
+        let items: Vec<BatchItem<T>> = results.into_iter().map(BatchItem::from).collect();
+        let succeeded = items.iter().filter(|item| item.is_ok()).count();
+        Self {
+            succeeded,
+            failed: items.len() - succeeded,
+            items,
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for BatchResult<T> {
+    fn into_response(self) -> Response {
+        (self.status(), Json(self)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    async fn fetch(id: u32) -> Result<u32, AppError> {
+        match id {
+            0 => Err(AppError::NotFound("pet 0 not found".to_string())),
+            99 => Err(AppError::BadRequest("id out of range".to_string())),
+            id => Ok(id * 10),
+        }
+    }
+
+    async fn body(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mixed_results_are_multi_status() {
+        let batch = BatchResult::join_all([1, 0, 2, 99].map(fetch)).await;
+        let response = batch.into_response();
+
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        assert_eq!(
+            body(response).await,
+            json!({
+                "succeeded": 2,
+                "failed": 2,
+                "items": [
+                    {"status": "ok", "value": 10},
+                    {
+                        "status": "error",
+                        "code": "not_found",
+                        "http_status": 404,
+                        "message": "pet 0 not found"
+                    },
+                    {"status": "ok", "value": 20},
+                    {
+                        "status": "error",
+                        "code": "bad_request",
+                        "http_status": 400,
+                        "message": "id out of range"
+                    }
+                ]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_uniform_results_use_their_status() {
+        let ok = BatchResult::join_all([1, 2].map(fetch)).await;
+        assert_eq!(ok.status(), StatusCode::OK);
+
+        let not_found = BatchResult::join_all([0, 0].map(fetch)).await;
+        assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+
+        let mixed_failures = BatchResult::join_all([0, 99].map(fetch)).await;
+        assert_eq!(mixed_failures.status(), StatusCode::MULTI_STATUS);
+
+        let empty: BatchResult<u32> = std::iter::empty().collect();
+        assert_eq!(empty.status(), StatusCode::OK);
+    }
+}
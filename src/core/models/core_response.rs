@@ -1,12 +1,24 @@
 use axum::{
     Json,
-    http::StatusCode,
+    body::{Body, to_bytes},
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::Next,
     response::{IntoResponse, Response},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
 use uuid::Uuid;
 
+use crate::core::error::AppError;
+use crate::core::error::middleware::request_id_of;
+use crate::core::utils::sources;
+
+/// Largest JSON body [`envelope_responses`] buffers to wrap
+pub const MAX_ENVELOPE_BODY_BYTES: usize = 16 * 1024 * 1024;
+
 /// Standard API response structure for all endpoints
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
@@ -216,6 +228,103 @@ impl<T: Serialize> IntoResponse for ApiResponse<T> {
     }
 }
 
+/// `{ "data": ..., "meta": { ... } }` body of a successful enveloped response
+///
+/// Produced by the [`envelope_responses`] middleware; errors keep the plain
+/// `ErrorResponse` body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    /// The handler's response body
+    pub data: T,
+    /// Request metadata
+    pub meta: EnvelopeMeta,
+}
+
+/// `meta` of an [`Envelope`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvelopeMeta {
+    /// Id of the request, as in the `x-request-id` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// When the server produced the response
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Marks a response to be sent as is by [`envelope_responses`]
+#[derive(Debug, Clone, Copy)]
+pub struct SkipEnvelope;
+
+/// Handler result sent without an envelope on enveloped routes
+///
+/// ```ignore
+/// async fn export() -> Raw<Json<Vec<Pet>>> { Raw(Json(pets)) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Raw<T>(pub T);
+
+impl<T: IntoResponse> IntoResponse for Raw<T> {
+    fn into_response(self) -> Response {
+        let mut response = self.0.into_response();
+        response.extensions_mut().insert(SkipEnvelope);
+        response
+    }
+}
+
+/// Middleware wrapping successful JSON responses in an [`Envelope`]
+///
+/// Opt in per router with `axum::middleware::from_fn(envelope_responses)`;
+/// routes without the layer keep raw bodies. Error responses, non-JSON
+/// bodies and [`Raw`] results pass through unchanged. A body that can't be
+/// buffered, including one over [`MAX_ENVELOPE_BODY_BYTES`], is answered
+/// with a 500 carrying the request id; send large payloads as [`Raw`].
+pub async fn envelope_responses(request: Request, next: Next) -> Response {
+    let request_id = request_id_of(&request);
+    let response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !response.status().is_success()
+        || !is_json
+        || response.extensions().get::<SkipEnvelope>().is_some()
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let data: Value = match to_bytes(body, MAX_ENVELOPE_BODY_BYTES).await {
+        Ok(bytes) => match serde_json::from_slice(&bytes) {
+            Ok(data) => data,
+            Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+        },
+        Err(e) => {
+            warn!("Failed to buffer JSON response for the envelope: {}", e);
+            return AppError::internal_server_error("Failed to read the response body")
+                .into_response_with_request_id(request_id);
+        }
+    };
+
+    let envelope = Envelope {
+        data,
+        meta: EnvelopeMeta {
+            request_id: request_id.clone(),
+            timestamp: sources::time().now().into(),
+        },
+    };
+    let body = match serde_json::to_vec(&envelope) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to serialize response envelope: {}", e);
+            return AppError::internal_server_error("Failed to serialize the response")
+                .into_response_with_request_id(request_id);
+        }
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,4 +399,84 @@ mod tests {
             ApiResponse::<()>::error(StatusCode::BAD_REQUEST, "Invalid input".to_string());
         assert_eq!(response.into_response().status(), StatusCode::BAD_REQUEST);
     }
+
+    mod envelope {
+        use super::*;
+        use crate::core::error::AppError;
+        use axum::{Router, routing::get};
+        use serde_json::json;
+        use tower::ServiceExt;
+
+        fn app() -> Router {
+            Router::new()
+                .route(
+                    "/pets/1",
+                    get(|| async { Json(json!({"id": 1, "name": "Rex"})) }),
+                )
+                .route(
+                    "/pets/export",
+                    get(|| async { Raw(Json(json!([{"id": 1}]))) }),
+                )
+                .route(
+                    "/pets/2",
+                    get(|| async {
+                        Err::<Json<Value>, _>(AppError::NotFound("Pet 2".to_string()))
+                    }),
+                )
+                .route(
+                    "/pets/broken",
+                    get(|| async {
+                        let stream = futures::stream::once(async {
+                            Err::<axum::body::Bytes, _>(std::io::Error::other("connection reset"))
+                        });
+                        (
+                            [(axum::http::header::CONTENT_TYPE, "application/json")],
+                            Body::from_stream(stream),
+                        )
+                    }),
+                )
+                .layer(axum::middleware::from_fn(envelope_responses))
+        }
+
+        async fn get_json(uri: &str) -> (StatusCode, Value) {
+            let request = axum::http::Request::get(uri)
+                .header("x-request-id", "req-123")
+                .body(Body::empty())
+                .unwrap();
+            let response = app().oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice(&body).unwrap())
+        }
+
+        #[tokio::test]
+        async fn test_success_is_wrapped_with_request_id() {
+            let (status, body) = get_json("/pets/1").await;
+
+            assert_eq!(status, StatusCode::OK);
+            let envelope: Envelope<Value> = serde_json::from_value(body).unwrap();
+            assert_eq!(envelope.data, json!({"id": 1, "name": "Rex"}));
+            assert_eq!(envelope.meta.request_id.as_deref(), Some("req-123"));
+        }
+
+        #[tokio::test]
+        async fn test_raw_and_error_responses_are_not_wrapped() {
+            let (_, raw) = get_json("/pets/export").await;
+            assert_eq!(raw, json!([{"id": 1}]));
+
+            let (status, error) = get_json("/pets/2").await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert!(error.get("data").is_none());
+            assert!(error.get("message").is_some());
+        }
+
+        #[tokio::test]
+        async fn test_unreadable_body_is_a_500_with_request_id() {
+            let (status, error) = get_json("/pets/broken").await;
+
+            assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+            assert!(error.get("data").is_none());
+            assert_eq!(error["request_id"], "req-123");
+        }
+    }
 }
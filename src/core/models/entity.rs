@@ -30,6 +30,18 @@ pub trait Entity: Clone + Debug + Serialize + DeserializeOwned + Send + Sync + '
     fn validate(&self) -> Result<(), ServiceError> {
         Ok(())
     }
+
+    /// Version for optimistic concurrency control, if the entity is versioned
+    ///
+    /// With `optimistic_locking` enabled, a save only succeeds when this
+    /// matches the stored version, and stores the entity with the version
+    /// incremented.
+    fn version(&self) -> Option<u64> {
+        None
+    }
+
+    /// Set the version; called by repositories on a successful versioned save
+    fn set_version(&mut self, _version: u64) {}
 }
 
 /// Generic entity interface for CRUD operations
@@ -151,6 +163,50 @@ impl RepositoryProviderRegistry {
     }
 }
 
+// Testing utilities
+#[cfg(test)]
+pub mod test_utils {
+    use super::*;
+
+    /// Entity shared by repository tests; versioned when `version` is set
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Pet {
+        pub id: String,
+        pub name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub version: Option<u64>,
+    }
+
+    impl Entity for Pet {
+        type Id = String;
+
+        fn id(&self) -> &Self::Id {
+            &self.id
+        }
+
+        fn collection_name() -> String {
+            "pets".to_string()
+        }
+
+        fn version(&self) -> Option<u64> {
+            self.version
+        }
+
+        fn set_version(&mut self, version: u64) {
+            self.version = Some(version);
+        }
+    }
+
+    /// An unversioned pet
+    pub fn pet(id: &str, name: &str) -> Pet {
+        Pet {
+            id: id.to_string(),
+            name: name.to_string(),
+            version: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,286 @@
+//! Strategies for generating entity ids
+//!
+//! Services creating entities take an [`IdGenerator`] instead of calling
+//! `Uuid::new_v4` directly, so a deployment can choose its id format:
+//!
+//! - [`UuidV4Generator`] (default): random UUIDs
+//! - [`UlidGenerator`]: [ULIDs](https://github.com/ulid/spec), a 48-bit
+//!   millisecond timestamp followed by 80 random bits. They sort by creation
+//!   time, which keeps inserts into B-tree indexes local. Ids generated in
+//!   the same millisecond increment the random part, so ids from one
+//!   generator are strictly increasing.
+//!
+//! Both produce 128-bit values, returned as [`Uuid`] so they fit existing
+//! `Uuid` id fields; a ULID's byte order is preserved, so it sorts the same
+//! either way. [`Ulid`] gives the canonical 26-character form.
+//!
+//! Randomness and time come from [`sources`](crate::core::utils::sources),
+//! so seeded tests get reproducible ids.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::core::utils::sources::{self, RandomSource, TimeSource};
+
+/// Generates ids for new entities
+pub trait IdGenerator: Send + Sync + fmt::Debug {
+    /// A new id
+    fn generate(&self) -> Uuid;
+
+    /// A new id in the generator's canonical text form
+    fn generate_string(&self) -> String {
+        self.generate().to_string()
+    }
+}
+
+/// Which [`IdGenerator`] to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdStrategy {
+    #[default]
+    Uuid,
+    Ulid,
+}
+
+impl IdStrategy {
+    /// Generator for this strategy
+    pub fn generator(self) -> Arc<dyn IdGenerator> {
+        match self {
+            IdStrategy::Uuid => Arc::new(UuidV4Generator),
+            IdStrategy::Ulid => Arc::new(UlidGenerator::new()),
+        }
+    }
+}
+
+/// Random (version 4) UUIDs
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UuidV4Generator;
+
+impl IdGenerator for UuidV4Generator {
+    fn generate(&self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        sources::random().fill_bytes(&mut bytes);
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+}
+
+/// Crockford's base32 alphabet used by ULIDs
+const ULID_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const ULID_LEN: usize = 26;
+const RANDOM_BITS: u32 = 80;
+
+/// A ULID: 48-bit millisecond timestamp and 80 random bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ulid(pub u128);
+
+impl Ulid {
+    /// Milliseconds since the Unix epoch when the ULID was generated
+    pub fn timestamp_ms(self) -> u64 {
+        (self.0 >> RANDOM_BITS) as u64
+    }
+}
+
+impl fmt::Display for Ulid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text = [0u8; ULID_LEN];
+        for (i, digit) in text.iter_mut().enumerate() {
+            let shift = 5 * (ULID_LEN - 1 - i);
+            *digit = ULID_ALPHABET[((self.0 >> shift) & 0x1f) as usize];
+        }
+        f.write_str(std::str::from_utf8(&text).expect("ULID alphabet is ASCII"))
+    }
+}
+
+/// Error parsing a [`Ulid`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum UlidParseError {
+    #[error("ULID must be {ULID_LEN} characters, got {0}")]
+    Length(usize),
+    #[error("Invalid ULID character '{0}'")]
+    Character(char),
+    #[error("ULID is out of range")]
+    Overflow,
+}
+
+impl FromStr for Ulid {
+    type Err = UlidParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.len() != ULID_LEN {
+            return Err(UlidParseError::Length(text.len()));
+        }
+        // 26 characters hold 130 bits; the first may only use the low 3
+        if text.as_bytes()[0] > b'7' {
+            return Err(UlidParseError::Overflow);
+        }
+        text.chars()
+            .try_fold(0u128, |value, c| {
+                let digit = ULID_ALPHABET
+                    .iter()
+                    .position(|digit| *digit as char == c.to_ascii_uppercase())
+                    .ok_or(UlidParseError::Character(c))?;
+                Ok((value << 5) | digit as u128)
+            })
+            .map(Ulid)
+    }
+}
+
+impl From<Ulid> for Uuid {
+    fn from(ulid: Ulid) -> Self {
+        Uuid::from_u128(ulid.0)
+    }
+}
+
+impl From<Uuid> for Ulid {
+    fn from(uuid: Uuid) -> Self {
+        Ulid(uuid.as_u128())
+    }
+}
+
+/// Monotonic ULIDs
+#[derive(Debug)]
+pub struct UlidGenerator {
+    random: Option<Arc<dyn RandomSource>>,
+    time: Option<Arc<dyn TimeSource>>,
+    last: Mutex<Option<Ulid>>,
+}
+
+impl Default for UlidGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UlidGenerator {
+    /// Generator using the process-wide random and time sources
+    pub fn new() -> Self {
+        Self {
+            random: None,
+            time: None,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Generator using the given sources instead of the process-wide ones
+    pub fn with_sources(random: Arc<dyn RandomSource>, time: Arc<dyn TimeSource>) -> Self {
+        Self {
+            random: Some(random),
+            time: Some(time),
+            last: Mutex::new(None),
+        }
+    }
+
+    /// A new ULID, greater than any previous one from this generator
+    pub fn next_ulid(&self) -> Ulid {
+        let time = self.time.clone().unwrap_or_else(sources::time);
+        let now_ms = time
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default()
+            & ((1 << 48) - 1);
+
+        let mut last = self.last.lock().unwrap();
+        let next = match *last {
+            // Same millisecond, or the clock went back: keep ordering
+            Some(previous) if previous.timestamp_ms() >= now_ms => Ulid(previous.0 + 1),
+            _ => {
+                let random = self.random.clone().unwrap_or_else(sources::random);
+                let mut bytes = [0u8; 16];
+                random.fill_bytes(&mut bytes[6..]);
+                Ulid(((now_ms as u128) << RANDOM_BITS) | u128::from_be_bytes(bytes))
+            }
+        };
+        *last = Some(next);
+        next
+    }
+}
+
+impl IdGenerator for UlidGenerator {
+    fn generate(&self) -> Uuid {
+        self.next_ulid().into()
+    }
+
+    fn generate_string(&self) -> String {
+        self.next_ulid().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::utils::sources::{ManualClock, SeededRandom};
+    use std::time::Duration;
+
+    fn ulid_generator(clock: Arc<ManualClock>) -> UlidGenerator {
+        UlidGenerator::with_sources(Arc::new(SeededRandom::new(1)), clock)
+    }
+
+    #[test]
+    fn test_ulids_increase_over_time() {
+        let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let clock = Arc::new(ManualClock::new(start));
+        let generator = ulid_generator(clock.clone());
+
+        let mut ids = Vec::new();
+        for step in 0..20 {
+            // Several ids per millisecond, then move on
+            if step % 4 == 0 {
+                clock.advance(Duration::from_millis(1));
+            }
+            ids.push(generator.generate_string());
+        }
+
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+        sorted.dedup();
+        assert_eq!(sorted.len(), ids.len());
+
+        // Uuid form keeps the same order
+        let uuids: Vec<Uuid> = ids
+            .iter()
+            .map(|id| id.parse::<Ulid>().unwrap().into())
+            .collect();
+        assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_ulid_parses_back() {
+        let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        let generator = ulid_generator(Arc::new(ManualClock::new(start)));
+
+        let ulid = generator.next_ulid();
+        let text = ulid.to_string();
+        assert_eq!(text.len(), 26);
+        assert_eq!(text.parse::<Ulid>(), Ok(ulid));
+        assert_eq!(text.to_lowercase().parse::<Ulid>(), Ok(ulid));
+        assert_eq!(ulid.timestamp_ms(), 1_700_000_000_123);
+        assert_eq!(Ulid::from(Uuid::from(ulid)), ulid);
+
+        assert_eq!(
+            "01ARZ3NDEKTSV4RRFFQ69G5FA".parse::<Ulid>(),
+            Err(UlidParseError::Length(25))
+        );
+        assert_eq!(
+            "01ARZ3NDEKTSV4RRFFQ69G5FAU".parse::<Ulid>(),
+            Err(UlidParseError::Character('U'))
+        );
+        assert_eq!(
+            "81ARZ3NDEKTSV4RRFFQ69G5FAV".parse::<Ulid>(),
+            Err(UlidParseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_uuid_strategy_is_v4() {
+        let id = IdStrategy::default().generator().generate();
+        assert_eq!(id.get_version_num(), 4);
+    }
+}
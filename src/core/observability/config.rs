@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
+use crate::core::error::middleware::TraceSampling;
+
 /// Configuration for the observability system
 #[derive(Debug, Clone)]
 pub struct ObservabilityConfig {
@@ -28,6 +30,12 @@ pub struct ObservabilityConfig {
     /// Sample rate for tracing (0.0 - 1.0)
     pub trace_sample_rate: f64,
 
+    /// Whether requests failing with a server error are traced even when not sampled
+    pub always_sample_errors: bool,
+
+    /// Whether to follow the sampled flag of an inbound `traceparent` header
+    pub honor_parent_sampling: bool,
+
     /// Whether correlation between metrics and traces is enabled
     pub correlation_enabled: bool,
 
@@ -52,6 +60,8 @@ impl Default for ObservabilityConfig {
             tracing_enabled: true,
             profiling_enabled: false,
             trace_sample_rate: 0.1,
+            always_sample_errors: true,
+            honor_parent_sampling: true,
             correlation_enabled: true,
             tracing_endpoint: None,
             propagation_headers: vec!["traceparent".to_string(), "tracestate".to_string()],
@@ -106,6 +116,31 @@ impl ObservabilityConfig {
         self
     }
 
+    /// Set whether server errors are always traced
+    pub fn with_always_sample_errors(mut self, enabled: bool) -> Self {
+        self.always_sample_errors = enabled;
+        self
+    }
+
+    /// Set whether inbound `traceparent` sampling decisions are honored
+    pub fn with_honor_parent_sampling(mut self, enabled: bool) -> Self {
+        self.honor_parent_sampling = enabled;
+        self
+    }
+
+    /// Sampling settings for the request tracking layer
+    pub fn trace_sampling(&self) -> TraceSampling {
+        TraceSampling {
+            sample_rate: if self.tracing_enabled {
+                self.trace_sample_rate
+            } else {
+                0.0
+            },
+            always_sample_errors: self.tracing_enabled && self.always_sample_errors,
+            honor_parent: self.tracing_enabled && self.honor_parent_sampling,
+        }
+    }
+
     /// Set correlation enabled
     pub fn with_correlation_enabled(mut self, enabled: bool) -> Self {
         self.correlation_enabled = enabled;
@@ -26,6 +26,8 @@ pub enum ObservabilityError {
     SpanCreationError(String),
     /// Failed to start profiling
     ProfilingError(String),
+    /// Failed to flush or stop the exporter
+    ShutdownError(String),
     /// Internal error
     InternalError(String),
 }
@@ -52,6 +54,7 @@ impl fmt::Display for ObservabilityError {
             ObservabilityError::MetricError(msg) => format!("Metric error: {}", msg),
             ObservabilityError::SpanCreationError(msg) => format!("Span creation error: {}", msg),
             ObservabilityError::ProfilingError(msg) => format!("Profiling error: {}", msg),
+            ObservabilityError::ShutdownError(msg) => format!("Shutdown error: {}", msg),
             ObservabilityError::InternalError(msg) => format!("Internal error: {}", msg),
         };
         write!(f, "{}", message)
@@ -7,7 +7,7 @@
 //!
 //! ```rust
 //! use navius::core::observability::{
-//!     ObservabilityConfig, ObservabilityProviderRegistry, PrometheusProvider
+//!     ObservabilityConfig, ObservabilityProviderRegistry, ObservabilityService, PrometheusProvider,
 //! };
 //!
 //! async fn setup_observability() -> ObservabilityService {
@@ -20,6 +20,12 @@
 //!     service
 //! }
 //! ```
+//!
+//! Before the process exits, flush what the exporter still buffers:
+//!
+//! ```rust,ignore
+//! service.shutdown(DEFAULT_SHUTDOWN_TIMEOUT).await?;
+//! ```
 
 pub mod config;
 pub mod error;
@@ -42,7 +48,7 @@ pub use operations::{
 };
 pub use prometheus::{PrometheusClient, PrometheusProvider, get_prometheus_metrics_text};
 pub use provider::{ObservabilityProvider, ObservabilityProviderRegistry};
-pub use service::ObservabilityService;
+pub use service::{DEFAULT_SHUTDOWN_TIMEOUT, ObservabilityService};
 
 /// Initialize the observability system with the default configuration
 ///
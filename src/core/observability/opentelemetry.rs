@@ -446,6 +446,41 @@ impl ObservabilityOperations for OpenTelemetryClient {
         // Simple health check - checks if the client is initialized
         Ok(self.init_time.elapsed().as_secs() > 0)
     }
+
+    fn shutdown(&self) -> Result<(), ObservabilityError> {
+        // End spans still open so the batch processor has them to export
+        if let Ok(mut spans) = self.active_spans.lock() {
+            for (_, mut span) in spans.drain() {
+                span.end();
+            }
+        }
+
+        let mut failures = Vec::new();
+        if let Some(ref tracer_provider) = self.tracer_provider {
+            for result in tracer_provider.force_flush() {
+                if let Err(e) = result {
+                    failures.push(format!("span flush: {}", e));
+                }
+            }
+        }
+        if let Some(ref meter_provider) = self.metrics_provider {
+            if let Err(e) = meter_provider.force_flush() {
+                failures.push(format!("metric flush: {}", e));
+            }
+            if let Err(e) = meter_provider.shutdown() {
+                failures.push(format!("meter provider: {}", e));
+            }
+        }
+        #[cfg(feature = "opentelemetry-jaeger")]
+        global::shutdown_tracer_provider();
+
+        if failures.is_empty() {
+            info!("Flushed telemetry for {}", self.service_name);
+            Ok(())
+        } else {
+            Err(ObservabilityError::ShutdownError(failures.join("; ")))
+        }
+    }
 }
 
 /// OpenTelemetry provider - base implementation
@@ -147,4 +147,12 @@ pub trait ObservabilityOperations: Send + Sync + 'static {
 
     /// Health check for the observability system
     fn health_check(&self) -> Result<bool, ObservabilityError>;
+
+    /// Flush buffered spans and metrics to the exporter and stop it
+    ///
+    /// Called once on shutdown; anything recorded afterwards may be dropped.
+    /// May block while the exporter drains.
+    fn shutdown(&self) -> Result<(), ObservabilityError> {
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use async_trait::async_trait;
-use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use metrics_exporter_prometheus::PrometheusHandle;
 use tracing::{Level, error, info, span};
 use uuid::Uuid;
 
@@ -18,8 +18,8 @@ use crate::core::observability::provider::ObservabilityProvider;
 pub struct PrometheusClient {
     /// Handle to the Prometheus registry
     handle: PrometheusHandle,
-    /// Service name for metrics
-    service_name: String,
+    /// Prefix of every metric this client records, derived from the service name
+    namespace: String,
     /// Profiling sessions
     profiling_sessions: Arc<Mutex<HashMap<String, ProfilingSession>>>,
     /// Active spans
@@ -32,10 +32,12 @@ pub struct PrometheusClient {
 
 impl PrometheusClient {
     /// Create a new Prometheus client
+    ///
+    /// Shares the process-wide recorder from [`init_metrics`](crate::core::metrics::init_metrics),
+    /// installing it if nothing has yet. That recorder has no namespace of its
+    /// own, so the `record_*` methods prefix names with the service name.
     pub fn new(config: &ObservabilityConfig) -> Result<Self, ObservabilityError> {
-        let handle = PrometheusBuilder::new()
-            .with_namespace(config.service_name.clone())
-            .install_recorder()
+        let handle = crate::core::metrics::init_metrics()
             .map_err(|e| ObservabilityError::InitializationError(e.to_string()))?;
 
         info!(
@@ -45,7 +47,7 @@ impl PrometheusClient {
 
         Ok(Self {
             handle,
-            service_name: config.service_name.clone(),
+            namespace: metric_namespace(&config.service_name),
             profiling_sessions: Arc::new(Mutex::new(HashMap::new())),
             active_spans: Arc::new(Mutex::new(HashMap::new())),
             init_time: Instant::now(),
@@ -55,7 +57,7 @@ impl PrometheusClient {
 
     /// Create prefixed metric name
     fn create_metric_name(&self, name: &str) -> String {
-        format!("{}_{}", self.service_name, name)
+        format!("{}_{}", self.namespace, name)
     }
 
     /// Add current trace context to labels if correlation is enabled
@@ -84,6 +86,14 @@ impl PrometheusClient {
     }
 }
 
+/// `service_name` as a Prometheus metric name prefix
+fn metric_namespace(service_name: &str) -> String {
+    service_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
 impl ObservabilityOperations for PrometheusClient {
     fn record_counter(
         &self,
@@ -392,6 +402,23 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    async fn test_prometheus_client_prefixes_metrics_with_service_name() {
+        let config = ObservabilityConfig::new("prometheus", "checkout-service");
+        let client = PrometheusClient::new(&config).unwrap();
+
+        client
+            .record_counter("prefixed_orders_total", 3, &[])
+            .unwrap();
+
+        let rendered = client.handle.render();
+        assert!(
+            rendered.contains("checkout_service_prefixed_orders_total 3"),
+            "{}",
+            rendered
+        );
+    }
+
     #[test]
     async fn test_prometheus_client_span() {
         let config = ObservabilityConfig::new("prometheus", "test-service");
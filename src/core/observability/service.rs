@@ -1,6 +1,7 @@
 use std::sync::Arc;
+use std::time::Duration;
 
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::core::observability::config::ObservabilityConfig;
 use crate::core::observability::error::ObservabilityError;
@@ -14,6 +15,9 @@ use crate::core::observability::operations::{
 use crate::core::observability::prometheus::PrometheusProvider;
 use crate::core::observability::provider::ObservabilityProviderRegistry;
 
+/// Time allowed for the exporter to drain on shutdown
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Main service for observability operations
 #[derive(Clone)]
 pub struct ObservabilityService {
@@ -44,7 +48,7 @@ impl ObservabilityService {
             .await?;
 
         Ok(Self {
-            client: Arc::new(*client),
+            client: Arc::from(client),
             registry,
             config,
         })
@@ -62,7 +66,7 @@ impl ObservabilityService {
         let client = registry.create_default_client(config.clone()).await?;
 
         Ok(Self {
-            client: Arc::new(*client),
+            client: Arc::from(client),
             registry,
             config,
         })
@@ -216,12 +220,42 @@ impl ObservabilityService {
     pub fn health_check(&self) -> Result<bool, ObservabilityError> {
         self.client.health_check()
     }
+
+    /// Flush buffered telemetry and stop the exporter, waiting at most `timeout`
+    ///
+    /// Call once the server has stopped serving requests, so the spans of the
+    /// last requests are exported before the process exits.
+    pub async fn shutdown(&self, timeout: Duration) -> Result<(), ObservabilityError> {
+        // Exporters flush synchronously; keep that off the async workers
+        let client = self.client.clone();
+        let flush = tokio::task::spawn_blocking(move || client.shutdown());
+
+        match tokio::time::timeout(timeout, flush).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => Err(ObservabilityError::ShutdownError(format!(
+                "flush task failed: {}",
+                e
+            ))),
+            Err(_) => {
+                warn!(
+                    "Telemetry exporter did not drain within {:?}, pending data may be lost",
+                    timeout
+                );
+                Err(ObservabilityError::ShutdownError(format!(
+                    "exporter did not drain within {:?}",
+                    timeout
+                )))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::core::observability::prometheus::PrometheusProvider;
+    use crate::core::observability::provider::ObservabilityProvider;
+    use std::sync::Mutex;
 
     #[tokio::test]
     async fn test_observability_service_creation() {
@@ -271,6 +305,153 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    /// Client that buffers ended spans like a batch exporter and exports them on shutdown
+    struct CollectingClient {
+        pending: Mutex<Vec<String>>,
+        exported: Arc<Mutex<Vec<String>>>,
+        flush_delay: Duration,
+    }
+
+    impl ObservabilityOperations for CollectingClient {
+        fn record_counter(
+            &self,
+            _name: &str,
+            _value: u64,
+            _labels: &[(&str, String)],
+        ) -> Result<(), ObservabilityError> {
+            Ok(())
+        }
+
+        fn record_gauge(
+            &self,
+            _name: &str,
+            _value: f64,
+            _labels: &[(&str, String)],
+        ) -> Result<(), ObservabilityError> {
+            Ok(())
+        }
+
+        fn record_histogram(
+            &self,
+            _name: &str,
+            _value: f64,
+            _labels: &[(&str, String)],
+        ) -> Result<(), ObservabilityError> {
+            Ok(())
+        }
+
+        fn get_metric(
+            &self,
+            _name: &str,
+            _metric_type: MetricType,
+            _labels: &[(&str, String)],
+        ) -> Result<Option<MetricValue>, ObservabilityError> {
+            Ok(None)
+        }
+
+        fn start_span(&self, name: &str) -> SpanContext {
+            SpanContext {
+                span_id: "span".to_string(),
+                trace_id: "trace".to_string(),
+                name: name.to_string(),
+                start_time: std::time::Instant::now(),
+                attributes: Vec::new(),
+            }
+        }
+
+        fn end_span(&self, context: SpanContext) {
+            self.pending.lock().unwrap().push(context.name);
+        }
+
+        fn set_span_attribute(&self, _context: &SpanContext, _key: &str, _value: &str) {}
+
+        fn set_span_status(
+            &self,
+            _context: &SpanContext,
+            _status: SpanStatus,
+            _description: Option<&str>,
+        ) {
+        }
+
+        fn start_profiling(&self, name: &str) -> Result<ProfilingSession, ObservabilityError> {
+            Ok(ProfilingSession::new(name))
+        }
+
+        fn health_check(&self) -> Result<bool, ObservabilityError> {
+            Ok(true)
+        }
+
+        fn shutdown(&self) -> Result<(), ObservabilityError> {
+            std::thread::sleep(self.flush_delay);
+            let mut pending = self.pending.lock().unwrap();
+            self.exported.lock().unwrap().append(&mut pending);
+            Ok(())
+        }
+    }
+
+    struct CollectingProvider {
+        exported: Arc<Mutex<Vec<String>>>,
+        flush_delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl ObservabilityProvider for CollectingProvider {
+        async fn create_client(
+            &self,
+            _config: ObservabilityConfig,
+        ) -> Result<Box<dyn ObservabilityOperations>, ObservabilityError> {
+            Ok(Box::new(CollectingClient {
+                pending: Mutex::new(Vec::new()),
+                exported: self.exported.clone(),
+                flush_delay: self.flush_delay,
+            }))
+        }
+
+        fn supports(&self, _config: &ObservabilityConfig) -> bool {
+            true
+        }
+
+        fn name(&self) -> &str {
+            "collecting"
+        }
+    }
+
+    async fn collecting_service(
+        flush_delay: Duration,
+    ) -> (ObservabilityService, Arc<Mutex<Vec<String>>>) {
+        let exported = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = ObservabilityProviderRegistry::new();
+        registry.register(CollectingProvider {
+            exported: exported.clone(),
+            flush_delay,
+        });
+        let config = ObservabilityConfig::new("collecting", "test-service");
+        let service = ObservabilityService::new(registry, config).await.unwrap();
+        (service, exported)
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_span_recorded_just_before() {
+        let (service, exported) = collecting_service(Duration::from_millis(20)).await;
+
+        let span = service.start_span("last-request");
+        service.end_span(span);
+        assert!(exported.lock().unwrap().is_empty());
+
+        service.shutdown(DEFAULT_SHUTDOWN_TIMEOUT).await.unwrap();
+
+        assert_eq!(*exported.lock().unwrap(), vec!["last-request".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_gives_up_after_timeout() {
+        let (service, _exported) = collecting_service(Duration::from_secs(2)).await;
+
+        let result = service.shutdown(Duration::from_millis(50)).await;
+
+        assert!(matches!(result, Err(ObservabilityError::ShutdownError(_))));
+    }
+
     #[tokio::test]
     async fn test_observability_service_with_all_providers() {
         // Create configuration
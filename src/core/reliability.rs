@@ -1,17 +1,31 @@
 //! Reliability features
 //!
 //! This module provides middleware components for enhancing application resilience:
-//! - Retry mechanisms
+//! - Retry mechanisms, with backoff schedules exposed as [`BackoffSchedule`]
 //! - Circuit breakers
 //! - Rate limiting
 //! - Concurrency control
-//! - Request timeouts
-pub use circuit_breaker::CircuitBreakerError;
+//! - Draining admissions on shutdown
+//! - Named circuit breakers per external dependency
+//! - Bulkheads isolating external dependencies
+//! - Fallback responses when a circuit breaker or rate limit trips
+//! - Request timeouts, overall and per phase (body read, handler, response write)
+//! - Fault injection for resilience testing
+pub use backoff::BackoffSchedule;
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitBreakerError, CircuitBreakerRegistry, CircuitState,
+};
+pub mod backoff;
+pub mod bulkhead;
+pub mod chaos;
 pub mod circuit_breaker;
 pub mod concurrency;
+pub mod drain;
+pub mod fallback;
 pub mod metrics;
 pub mod rate_limit;
 pub mod retry;
+pub mod timeout;
 
 use crate::core::reliability::retry::RetryPolicy;
 
@@ -33,11 +47,15 @@ mod tests {
             timeout: TimeoutConfig {
                 enabled: true,
                 timeout_seconds: 1,
+                ..Default::default()
             },
             circuit_breaker: CircuitBreakerConfig::default(),
             rate_limit: RateLimitConfig::default(),
             concurrency: ConcurrencyConfig::default(),
             retry: RetryConfig::default(),
+            circuit_breakers: Default::default(),
+            bulkheads: Default::default(),
+            chaos: Default::default(),
         };
 
         let router = apply_reliability(
@@ -66,6 +84,7 @@ mod tests {
             timeout: TimeoutConfig {
                 enabled: true,
                 timeout_seconds: 1,
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -134,12 +153,57 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_invalid_write_rate_limit_config() {
+        let config = RateLimitConfig {
+            enabled: true,
+            write_requests_per_window: Some(0),
+            ..Default::default()
+        };
+        assert!(build_rate_limit_layer(&config).is_err());
+
+        let config = RateLimitConfig {
+            write_requests_per_window: Some(10),
+            ..config
+        };
+        assert!(build_rate_limit_layer(&config).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_retry_policy_uses_configured_backoff() {
+        let config = RetryConfig {
+            enabled: true,
+            max_attempts: 4,
+            base_delay_ms: 50,
+            max_delay_ms: 300,
+            use_exponential_backoff: true,
+            retry_status_codes: vec![503],
+        };
+
+        let policy = retry_policy(&config);
+        let schedule = policy.schedule();
+        assert_eq!(schedule.nominal(1), Duration::from_millis(50));
+        assert_eq!(schedule.nominal(2), Duration::from_millis(100));
+        assert_eq!(schedule.nominal(4), Duration::from_millis(300));
+
+        let fixed = RetryConfig {
+            use_exponential_backoff: false,
+            ..config
+        };
+        let policy = retry_policy(&fixed);
+        assert_eq!(
+            policy.schedule().bounds(3),
+            (Duration::from_millis(50), Duration::from_millis(50))
+        );
+    }
+
     // Add test for configuration safety limits
     #[tokio::test]
     async fn test_safety_limits() {
         let invalid_config = TimeoutConfig {
             enabled: true,
             timeout_seconds: 0,
+            ..Default::default()
         };
 
         let result = build_timeout_layer(&invalid_config);
@@ -148,10 +212,15 @@ mod tests {
 }
 
 // Re-export key components
+pub use bulkhead::{Bulkhead, BulkheadError, BulkheadRegistry, BulkheadStats};
+pub use chaos::{ChaosInjector, inject_faults};
 pub use circuit_breaker::CircuitBreakerConfig as CbConfig;
 pub use concurrency::ConcurrencyLimitLayer;
-pub use rate_limit::RateLimitLayer;
+pub use drain::DrainSignal;
+pub use fallback::{DEGRADED_HEADER, FallbackLayer, TripContext, TripReason};
+pub use rate_limit::{LimiterRejection, RateLimitLayer};
 pub use retry::RetryConfig as ReliabilityRetryConfig;
+pub use timeout::{PhaseTimeouts, TimeoutPhase, enforce_phase_timeouts};
 
 use crate::core::reliability::rate_limit::{ConcurrencyLimitError, RateLimitError, RetryError};
 use crate::core::reliability::retry::RetryLayer;
@@ -185,6 +254,15 @@ use tower::retry::backoff::ExponentialBackoff;
 pub fn apply_reliability(router: Router, config: &ReliabilityConfig) -> Router {
     let mut modified_router = router;
 
+    // Per-phase timeouts sit inside the overall timeout, which still caps the whole request
+    if let Some(timeouts) = timeout::PhaseTimeouts::from_config(&config.timeout) {
+        info!("Applying phase timeouts: {:?}", timeouts);
+        modified_router = modified_router.layer(axum::middleware::from_fn_with_state(
+            timeouts,
+            timeout::enforce_phase_timeouts,
+        ));
+    }
+
     // Add timeout middleware if enabled
     if config.timeout.enabled {
         let timeout_duration = Duration::from_secs(config.timeout.timeout_seconds);
@@ -266,12 +344,28 @@ fn build_retry_layer(config: &RetryConfig) -> Result<Option<RetryLayer>, AppErro
     }
 
     info!(
-        "Configuring request retries: max_attempts={}, base_delay={}ms, exponential_backoff={}",
-        config.max_attempts, config.base_delay_ms, config.use_exponential_backoff
+        "Configuring request retries: max_attempts={}, base_delay={}ms, max_delay={}ms, exponential_backoff={}",
+        config.max_attempts,
+        config.base_delay_ms,
+        config.max_delay_ms,
+        config.use_exponential_backoff
     );
 
-    let retry_policy = RetryPolicy::new(config.max_attempts);
-    Ok(Some(RetryLayer::new(retry_policy)))
+    Ok(Some(RetryLayer::new(retry_policy(config))))
+}
+
+/// Retry policy with the attempts, status codes and backoff of `config`
+fn retry_policy(config: &RetryConfig) -> RetryPolicy {
+    let base_delay = Duration::from_millis(config.base_delay_ms);
+    let max_delay = Duration::from_millis(config.max_delay_ms);
+    let schedule = if config.use_exponential_backoff {
+        BackoffSchedule::new(base_delay, max_delay)
+    } else {
+        BackoffSchedule::fixed(base_delay.min(max_delay))
+    };
+    RetryPolicy::new(config.max_attempts)
+        .with_status_codes(config.retry_status_codes.clone())
+        .with_schedule(schedule)
 }
 
 /// Build the circuit breaker layer based on configuration
@@ -315,16 +409,29 @@ fn build_rate_limit_layer(
         ));
     }
 
+    if config.write_requests_per_window == Some(0) {
+        return Err(AppError::validation_error(
+            "Invalid rate limit configuration: write_requests_per_window cannot be zero",
+        ));
+    }
+
     info!(
-        "Configuring rate limiter: {} requests per {} seconds, per_client={}",
-        config.requests_per_window, config.window_seconds, config.per_client
+        "Configuring rate limiter: {} requests per {} seconds, writes={:?}, per_client={}",
+        config.requests_per_window,
+        config.window_seconds,
+        config.write_requests_per_window,
+        config.per_client
     );
 
-    Ok(Some(rate_limit::RateLimitLayer::new(
+    let layer = rate_limit::RateLimitLayer::new(
         config.requests_per_window,
         Duration::from_secs(config.window_seconds),
         config.per_client,
-    )))
+    );
+    Ok(Some(match config.write_requests_per_window {
+        Some(writes) => layer.with_write_quota(writes),
+        None => layer,
+    }))
 }
 
 /// Build the timeout layer based on configuration
@@ -0,0 +1,204 @@
+//! Backoff schedules
+//!
+//! [`BackoffSchedule`] is the delay computation behind the retry layer,
+//! [`OperationRetryPolicy`](super::retry::OperationRetryPolicy) and the HTTP
+//! client's retries, exposed so schedules can be checked without running
+//! them. Retry `n` (1-based) waits
+//!
+//! ```text
+//! min(base * multiplier^(n - 1) * jitter, max)
+//! ```
+//!
+//! with `jitter` drawn uniformly from the configured bounds (0.5-1.5 by
+//! default). Iterating a schedule yields the delays of retries 1, 2, 3, ...
+//!
+//! ```ignore
+//! let delays: Vec<_> = BackoffSchedule::new(Duration::from_millis(100), Duration::from_secs(1))
+//!     .without_jitter()
+//!     .take(5)
+//!     .collect();
+//! // 100ms, 200ms, 400ms, 800ms, 1s
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::core::utils::sources::{self, RandomSource};
+
+/// Delays between retries for given base, cap, multiplier and jitter bounds
+#[derive(Debug, Clone)]
+pub struct BackoffSchedule {
+    base: Duration,
+    max: Duration,
+    multiplier: f64,
+    jitter_min: f64,
+    jitter_max: f64,
+    random: Option<Arc<dyn RandomSource>>,
+    /// Retries already yielded by the iterator
+    yielded: u32,
+}
+
+impl BackoffSchedule {
+    /// Exponential backoff doubling `base` per retry, with 0.5-1.5x jitter, capped at `max`
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            multiplier: 2.0,
+            jitter_min: 0.5,
+            jitter_max: 1.5,
+            random: None,
+            yielded: 0,
+        }
+    }
+
+    /// The same `delay` before every retry
+    pub fn fixed(delay: Duration) -> Self {
+        Self::new(delay, delay)
+            .with_multiplier(1.0)
+            .without_jitter()
+    }
+
+    /// Grow the delay by `multiplier` per retry; values below 1 are treated as 1
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Scale each delay by a factor drawn uniformly from `min..max`
+    ///
+    /// Bounds are clamped to be non-negative and swapped if reversed.
+    pub fn with_jitter(mut self, min: f64, max: f64) -> Self {
+        let (min, max) = (min.max(0.0), max.max(0.0));
+        (self.jitter_min, self.jitter_max) = if min <= max { (min, max) } else { (max, min) };
+        self
+    }
+
+    /// Use the nominal delays
+    pub fn without_jitter(self) -> Self {
+        self.with_jitter(1.0, 1.0)
+    }
+
+    /// Draw jitter from `random` instead of the process-wide source
+    pub fn with_random(mut self, random: Arc<dyn RandomSource>) -> Self {
+        self.random = Some(random);
+        self
+    }
+
+    /// Longest delay of any retry
+    pub fn max_delay(&self) -> Duration {
+        self.max
+    }
+
+    /// Delay before retry `retry` (1-based) without jitter, capped
+    pub fn nominal(&self, retry: u32) -> Duration {
+        self.scaled(retry, 1.0)
+    }
+
+    /// Shortest and longest delay jitter can produce for retry `retry`
+    pub fn bounds(&self, retry: u32) -> (Duration, Duration) {
+        (
+            self.scaled(retry, self.jitter_min),
+            self.scaled(retry, self.jitter_max),
+        )
+    }
+
+    /// Delay before retry `retry` (1-based)
+    pub fn delay(&self, retry: u32) -> Duration {
+        match &self.random {
+            Some(random) => self.delay_with(random.as_ref(), retry),
+            None => self.delay_with(sources::random().as_ref(), retry),
+        }
+    }
+
+    /// [`delay`](Self::delay) drawing its jitter from `random`
+    pub fn delay_with(&self, random: &dyn RandomSource, retry: u32) -> Duration {
+        if self.jitter_min == self.jitter_max {
+            return self.scaled(retry, self.jitter_min);
+        }
+        let jitter = self.jitter_min + (self.jitter_max - self.jitter_min) * random.next_f64();
+        self.scaled(retry, jitter)
+    }
+
+    fn scaled(&self, retry: u32, factor: f64) -> Duration {
+        let nominal =
+            self.base.as_millis() as f64 * self.multiplier.powf(retry.saturating_sub(1) as f64);
+        let capped = (nominal * factor).min(self.max.as_millis() as f64);
+        Duration::from_millis(capped as u64)
+    }
+}
+
+impl Iterator for BackoffSchedule {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        self.yielded = self.yielded.saturating_add(1);
+        Some(self.delay(self.yielded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::utils::sources::SeededRandom;
+
+    fn schedule() -> BackoffSchedule {
+        BackoffSchedule::new(Duration::from_millis(100), Duration::from_secs(2))
+    }
+
+    #[test]
+    fn test_nominal_delays_grow_monotonically_to_the_cap() {
+        let delays: Vec<_> = schedule().without_jitter().take(8).collect();
+
+        assert_eq!(delays[0], Duration::from_millis(100));
+        assert_eq!(delays[4], Duration::from_millis(1600));
+        assert!(delays.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(
+            delays[5..]
+                .iter()
+                .all(|&delay| delay == Duration::from_secs(2))
+        );
+
+        let tripled: Vec<_> = schedule()
+            .with_multiplier(3.0)
+            .without_jitter()
+            .take(3)
+            .collect();
+        assert_eq!(tripled, [100, 300, 900].map(Duration::from_millis).to_vec());
+    }
+
+    #[test]
+    fn test_jittered_delays_stay_within_bounds() {
+        let schedule = schedule()
+            .with_jitter(0.8, 1.2)
+            .with_random(Arc::new(SeededRandom::new(3)));
+
+        for (retry, delay) in (1..=10).zip(schedule.clone()) {
+            let (low, high) = schedule.bounds(retry);
+            assert!(
+                low <= delay && delay <= high,
+                "retry {}: {:?}",
+                retry,
+                delay
+            );
+            assert!(delay <= schedule.max_delay());
+        }
+        assert_eq!(
+            schedule.bounds(1),
+            (Duration::from_millis(80), Duration::from_millis(120))
+        );
+        // Past the cap both bounds are the cap
+        assert_eq!(
+            schedule.bounds(10),
+            (Duration::from_secs(2), Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn test_fixed_schedule_never_changes() {
+        let delays: Vec<_> = BackoffSchedule::fixed(Duration::from_millis(40))
+            .take(4)
+            .collect();
+        assert_eq!(delays, vec![Duration::from_millis(40); 4]);
+    }
+}
@@ -0,0 +1,436 @@
+//! Bulkhead isolation for calls to external dependencies
+//!
+//! Each named dependency gets its own bounded pool of call slots and a bounded
+//! wait queue, so a slow dependency can only exhaust its own bulkhead rather
+//! than every worker. This is independent of the global request concurrency
+//! limit in [`super::concurrency`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use metrics::{counter, gauge};
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, warn};
+
+use crate::core::config::app_config::BulkheadConfig;
+use crate::core::error::AppError;
+
+/// Error returned when a bulkhead cannot admit a call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkheadError {
+    /// All slots and queue positions are taken
+    Full { name: String },
+    /// The call waited in the queue longer than `max_wait_ms`
+    QueueTimeout { name: String, waited: Duration },
+}
+
+impl fmt::Display for BulkheadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BulkheadError::Full { name } => write!(f, "Bulkhead '{}' is full", name),
+            BulkheadError::QueueTimeout { name, waited } => write!(
+                f,
+                "Bulkhead '{}' had no free slot after {}ms",
+                name,
+                waited.as_millis()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BulkheadError {}
+
+impl From<BulkheadError> for AppError {
+    fn from(err: BulkheadError) -> Self {
+        match err {
+            BulkheadError::Full { .. } => AppError::RateLimited(err.to_string()),
+            BulkheadError::QueueTimeout { .. } => AppError::timeout(err.to_string()),
+        }
+    }
+}
+
+/// Point-in-time utilization of a bulkhead
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BulkheadStats {
+    pub name: String,
+    pub max_concurrent_calls: u32,
+    pub max_queued_calls: u32,
+    pub in_flight: u32,
+    pub queued: u32,
+    pub rejected: u64,
+    /// Fraction of call slots in use, from 0.0 to 1.0
+    pub utilization: f64,
+}
+
+struct BulkheadInner {
+    name: String,
+    config: BulkheadConfig,
+    semaphore: Arc<Semaphore>,
+    queued: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+/// Bounded call pool for a single named dependency
+#[derive(Clone)]
+pub struct Bulkhead {
+    inner: Arc<BulkheadInner>,
+}
+
+impl fmt::Debug for Bulkhead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bulkhead")
+            .field("name", &self.inner.name)
+            .field("config", &self.inner.config)
+            .finish()
+    }
+}
+
+impl Bulkhead {
+    /// Create a bulkhead for the named dependency
+    pub fn new(name: impl Into<String>, config: BulkheadConfig) -> Self {
+        let permits = config.max_concurrent_calls.max(1) as usize;
+        Self {
+            inner: Arc::new(BulkheadInner {
+                name: name.into(),
+                config,
+                semaphore: Arc::new(Semaphore::new(permits)),
+                queued: AtomicUsize::new(0),
+                rejected: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Name of the dependency this bulkhead protects
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Run `call` inside the bulkhead, rejecting it if the bulkhead is saturated
+    pub async fn call<F, T>(&self, call: F) -> Result<T, BulkheadError>
+    where
+        F: Future<Output = T>,
+    {
+        let permit = self.acquire().await?;
+        self.record_metrics();
+        let result = call.await;
+        drop(permit);
+        self.record_metrics();
+        Ok(result)
+    }
+
+    /// Acquire a call slot, waiting in the queue if one is available
+    async fn acquire(&self) -> Result<OwnedSemaphorePermit, BulkheadError> {
+        let inner = &self.inner;
+
+        if let Ok(permit) = inner.semaphore.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        // Reserve a queue position, or shed the call if the queue is full
+        let max_queued = inner.config.max_queued_calls as usize;
+        let reserved = inner
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                (queued < max_queued).then_some(queued + 1)
+            })
+            .is_ok();
+        if !reserved {
+            return Err(self.reject(BulkheadError::Full {
+                name: inner.name.clone(),
+            }));
+        }
+        // Gives the position back however the wait ends, including cancellation
+        let queued = QueuePosition { bulkhead: self };
+        self.record_metrics();
+
+        let wait = Duration::from_millis(inner.config.max_wait_ms);
+        let acquired = tokio::time::timeout(wait, inner.semaphore.clone().acquire_owned()).await;
+        drop(queued);
+
+        match acquired {
+            Ok(Ok(permit)) => Ok(permit),
+            // The semaphore is never closed, treat it like a full bulkhead anyway
+            Ok(Err(_)) => Err(self.reject(BulkheadError::Full {
+                name: inner.name.clone(),
+            })),
+            Err(_) => Err(self.reject(BulkheadError::QueueTimeout {
+                name: inner.name.clone(),
+                waited: wait,
+            })),
+        }
+    }
+
+    fn reject(&self, err: BulkheadError) -> BulkheadError {
+        self.inner.rejected.fetch_add(1, Ordering::Relaxed);
+        counter!("bulkhead.rejected", "bulkhead" => self.inner.name.clone()).increment(1);
+        warn!("{}", err);
+        self.record_metrics();
+        err
+    }
+
+    /// Current utilization of the bulkhead
+    pub fn stats(&self) -> BulkheadStats {
+        let inner = &self.inner;
+        let capacity = inner.config.max_concurrent_calls.max(1);
+        let in_flight = capacity.saturating_sub(inner.semaphore.available_permits() as u32);
+
+        BulkheadStats {
+            name: inner.name.clone(),
+            max_concurrent_calls: capacity,
+            max_queued_calls: inner.config.max_queued_calls,
+            in_flight,
+            queued: inner.queued.load(Ordering::Acquire) as u32,
+            rejected: inner.rejected.load(Ordering::Relaxed),
+            utilization: in_flight as f64 / capacity as f64,
+        }
+    }
+
+    fn record_metrics(&self) {
+        let stats = self.stats();
+        debug!(
+            "Bulkhead '{}': {}/{} in flight, {} queued",
+            stats.name, stats.in_flight, stats.max_concurrent_calls, stats.queued
+        );
+        gauge!("bulkhead.in_flight", "bulkhead" => stats.name.clone()).set(stats.in_flight as f64);
+        gauge!("bulkhead.queued", "bulkhead" => stats.name.clone()).set(stats.queued as f64);
+        gauge!("bulkhead.utilization", "bulkhead" => stats.name).set(stats.utilization);
+    }
+}
+
+/// A reserved place in a bulkhead's wait queue, released on drop
+struct QueuePosition<'a> {
+    bulkhead: &'a Bulkhead,
+}
+
+impl Drop for QueuePosition<'_> {
+    fn drop(&mut self) {
+        self.bulkhead.inner.queued.fetch_sub(1, Ordering::AcqRel);
+        self.bulkhead.record_metrics();
+    }
+}
+
+/// Registry of bulkheads keyed by dependency name
+#[derive(Debug, Clone, Default)]
+pub struct BulkheadRegistry {
+    bulkheads: Arc<RwLock<HashMap<String, Bulkhead>>>,
+    default_config: BulkheadConfig,
+}
+
+impl BulkheadRegistry {
+    /// Create an empty registry; unknown dependencies get the default config
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry from the configured bulkheads
+    pub fn from_config(configs: &HashMap<String, BulkheadConfig>) -> Self {
+        let registry = Self::new();
+        for (name, config) in configs {
+            registry.register(name, config.clone());
+        }
+        registry
+    }
+
+    /// Register or replace the bulkhead for a dependency
+    pub fn register(&self, name: &str, config: BulkheadConfig) -> Bulkhead {
+        let bulkhead = Bulkhead::new(name, config);
+        self.bulkheads
+            .write()
+            .unwrap()
+            .insert(name.to_string(), bulkhead.clone());
+        bulkhead
+    }
+
+    /// The bulkhead registered for a dependency, without creating one
+    pub fn find(&self, name: &str) -> Option<Bulkhead> {
+        self.bulkheads.read().unwrap().get(name).cloned()
+    }
+
+    /// Get the bulkhead for a dependency, creating it with the default config if needed
+    pub fn get(&self, name: &str) -> Bulkhead {
+        if let Some(bulkhead) = self.bulkheads.read().unwrap().get(name) {
+            return bulkhead.clone();
+        }
+
+        self.bulkheads
+            .write()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| Bulkhead::new(name, self.default_config.clone()))
+            .clone()
+    }
+
+    /// Run `call` inside the named dependency's bulkhead
+    pub async fn call<F, T>(&self, name: &str, call: F) -> Result<T, BulkheadError>
+    where
+        F: Future<Output = T>,
+    {
+        self.get(name).call(call).await
+    }
+
+    /// Utilization of every registered bulkhead, sorted by name
+    pub fn stats(&self) -> Vec<BulkheadStats> {
+        let mut stats: Vec<_> = self
+            .bulkheads
+            .read()
+            .unwrap()
+            .values()
+            .map(Bulkhead::stats)
+            .collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    fn config(max_concurrent_calls: u32, max_queued_calls: u32) -> BulkheadConfig {
+        BulkheadConfig {
+            max_concurrent_calls,
+            max_queued_calls,
+            max_wait_ms: 50,
+        }
+    }
+
+    /// Occupy a slot in `bulkhead` until the returned sender is dropped
+    async fn occupy(bulkhead: &Bulkhead) -> (oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+        let (release, hold) = oneshot::channel::<()>();
+        let task_bulkhead = bulkhead.clone();
+        let handle = tokio::spawn(async move {
+            task_bulkhead
+                .call(async {
+                    let _ = hold.await;
+                })
+                .await
+                .unwrap();
+        });
+
+        // Wait for the spawned call to take its slot
+        while bulkhead.stats().in_flight == 0 {
+            tokio::task::yield_now().await;
+        }
+        (release, handle)
+    }
+
+    #[tokio::test]
+    async fn test_saturated_bulkhead_rejects_while_other_available() {
+        let registry = BulkheadRegistry::new();
+        let payments = registry.register("payments", config(1, 0));
+        registry.register("inventory", config(1, 0));
+
+        let (release, handle) = occupy(&payments).await;
+
+        let rejected = registry.call("payments", async { "charged" }).await;
+        assert_eq!(
+            rejected,
+            Err(BulkheadError::Full {
+                name: "payments".to_string()
+            })
+        );
+
+        let other = registry.call("inventory", async { "reserved" }).await;
+        assert_eq!(other, Ok("reserved"));
+
+        drop(release);
+        handle.await.unwrap();
+        assert_eq!(
+            registry.call("payments", async { "charged" }).await,
+            Ok("charged")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queued_call_is_shed_after_max_wait() {
+        let bulkhead = Bulkhead::new("slow", config(1, 1));
+        let (release, handle) = occupy(&bulkhead).await;
+
+        let result = bulkhead.call(async {}).await;
+        assert!(matches!(result, Err(BulkheadError::QueueTimeout { .. })));
+        assert_eq!(bulkhead.stats().queued, 0);
+
+        drop(release);
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_queued_call_runs_when_slot_frees() {
+        let bulkhead = Bulkhead::new("search", config(1, 1));
+        let (release, handle) = occupy(&bulkhead).await;
+
+        let queued = tokio::spawn({
+            let bulkhead = bulkhead.clone();
+            async move { bulkhead.call(async { 42 }).await }
+        });
+        while bulkhead.stats().queued == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        drop(release);
+        handle.await.unwrap();
+        assert_eq!(queued.await.unwrap(), Ok(42));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_queued_call_frees_its_position() {
+        // Waits long enough that only the cancellation ends it
+        let bulkhead = Bulkhead::new(
+            "search",
+            BulkheadConfig {
+                max_wait_ms: 60_000,
+                ..config(1, 1)
+            },
+        );
+        let (release, handle) = occupy(&bulkhead).await;
+
+        let queued = tokio::spawn({
+            let bulkhead = bulkhead.clone();
+            async move { bulkhead.call(async {}).await }
+        });
+        while bulkhead.stats().queued == 0 {
+            tokio::task::yield_now().await;
+        }
+        queued.abort();
+        let _ = queued.await;
+
+        assert_eq!(bulkhead.stats().queued, 0);
+        drop(release);
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stats_report_utilization() {
+        let registry = BulkheadRegistry::new();
+        let bulkhead = registry.register("email", config(4, 0));
+        let (release, handle) = occupy(&bulkhead).await;
+        let _ = registry.call("email", async {}).await;
+
+        let stats = registry.stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].in_flight, 1);
+        assert_eq!(stats[0].utilization, 0.25);
+        assert_eq!(stats[0].rejected, 0);
+
+        drop(release);
+        handle.await.unwrap();
+    }
+
+    #[test]
+    fn test_bulkhead_error_maps_to_app_error() {
+        let full: AppError = BulkheadError::Full {
+            name: "payments".to_string(),
+        }
+        .into();
+        assert_eq!(
+            full.status_code(),
+            axum::http::StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+}
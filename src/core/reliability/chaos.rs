@@ -0,0 +1,253 @@
+//! Fault injection for resilience testing
+//!
+//! With `reliability.chaos.enabled`, [`inject_faults`] delays or fails a
+//! configurable percentage of requests, optionally only under
+//! `path_prefix`, so retries, timeouts and circuit breakers can be exercised
+//! against a real deployment:
+//!
+//! ```yaml
+//! reliability:
+//!   chaos:
+//!     enabled: true
+//!     path_prefix: /api/pets
+//!     error_percentage: 20
+//!     error_status: 503
+//!     latency_percentage: 50
+//!     latency_ms: 1500
+//! ```
+//!
+//! [`ChaosInjector::from_config`] refuses to build an injector in
+//! production, whatever the configuration says.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{OriginalUri, Request, State};
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use metrics::counter;
+use tracing::{debug, warn};
+
+use crate::core::config::app_config::{ChaosConfig, EnvironmentType};
+use crate::core::utils::sources;
+
+/// Header marking a response whose request had a fault injected
+pub const CHAOS_HEADER: &str = "x-chaos-injected";
+
+/// Shared fault injection settings
+#[derive(Debug, Clone)]
+pub struct ChaosInjector {
+    config: Arc<ChaosConfig>,
+}
+
+impl ChaosInjector {
+    /// Injector applying `config` regardless of environment
+    pub fn new(config: ChaosConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+
+    /// Injector for `environment`, or `None` if disabled or in production
+    pub fn from_config(config: &ChaosConfig, environment: &EnvironmentType) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        if *environment == EnvironmentType::Production {
+            warn!("Ignoring reliability.chaos: fault injection is disabled in production");
+            return None;
+        }
+        warn!(
+            "Fault injection enabled: {}% errors, {}% delayed by {}ms{}",
+            config.error_percentage,
+            config.latency_percentage,
+            config.latency_ms,
+            config
+                .path_prefix
+                .as_deref()
+                .map(|prefix| format!(" under {}", prefix))
+                .unwrap_or_default()
+        );
+        Some(Self::new(config.clone()))
+    }
+
+    /// Whether requests to `path` are subject to fault injection
+    pub fn applies_to(&self, path: &str) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        match self.config.path_prefix.as_deref() {
+            None => true,
+            Some(prefix) => {
+                let prefix = prefix.trim_end_matches('/');
+                prefix.is_empty() || path == prefix || path.starts_with(&format!("{}/", prefix))
+            }
+        }
+    }
+
+    fn error_status(&self) -> StatusCode {
+        StatusCode::from_u16(self.config.error_status).unwrap_or(StatusCode::SERVICE_UNAVAILABLE)
+    }
+}
+
+/// Roll against a percentage between 0 and 100
+fn roll(percentage: f64) -> bool {
+    if percentage >= 100.0 {
+        true
+    } else if percentage <= 0.0 {
+        false
+    } else {
+        sources::random().next_f64() * 100.0 < percentage
+    }
+}
+
+/// Middleware injecting the configured latency and errors
+///
+/// Use with `axum::middleware::from_fn_with_state(ChaosInjector, inject_faults)`.
+pub async fn inject_faults(
+    State(chaos): State<ChaosInjector>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Nested routers see a stripped URI; the prefix is written against the full path
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| request.uri().path(), |uri| uri.0.path())
+        .to_string();
+    if !chaos.applies_to(&path) {
+        return next.run(request).await;
+    }
+
+    let delayed = chaos.config.latency_ms > 0 && roll(chaos.config.latency_percentage);
+    if delayed {
+        debug!("Injecting {}ms of latency", chaos.config.latency_ms);
+        counter!("chaos_faults_injected_total", "fault" => "latency").increment(1);
+        tokio::time::sleep(Duration::from_millis(chaos.config.latency_ms)).await;
+    }
+
+    if roll(chaos.config.error_percentage) {
+        debug!("Injecting {} for {}", chaos.error_status(), path);
+        counter!("chaos_faults_injected_total", "fault" => "error").increment(1);
+        let mut response = (chaos.error_status(), "Injected fault").into_response();
+        response
+            .headers_mut()
+            .insert(CHAOS_HEADER, HeaderValue::from_static("error"));
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    if delayed {
+        response
+            .headers_mut()
+            .insert(CHAOS_HEADER, HeaderValue::from_static("latency"));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    fn app(chaos: Option<ChaosInjector>) -> Router {
+        let router = Router::new()
+            .route("/api/pets", get(|| async { "pets" }))
+            .route("/api/pets/{id}", get(|| async { "pet" }))
+            .route("/api/petsitters", get(|| async { "sitters" }))
+            .route("/health", get(|| async { "ok" }));
+        match chaos {
+            Some(chaos) => router.layer(axum::middleware::from_fn_with_state(chaos, inject_faults)),
+            None => router,
+        }
+    }
+
+    async fn status_of(app: &Router, path: &str) -> StatusCode {
+        let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    fn failing_pets() -> ChaosConfig {
+        ChaosConfig {
+            enabled: true,
+            error_percentage: 100.0,
+            error_status: 503,
+            path_prefix: Some("/api/pets".to_string()),
+            ..ChaosConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_error_rate_fails_only_scoped_path() {
+        let chaos = ChaosInjector::from_config(&failing_pets(), &EnvironmentType::Development);
+        let app = app(chaos);
+
+        assert_eq!(
+            status_of(&app, "/api/pets").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            status_of(&app, "/api/pets/1").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(status_of(&app, "/api/petsitters").await, StatusCode::OK);
+        assert_eq!(status_of(&app, "/health").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_injected_error_is_marked() {
+        let app = app(Some(ChaosInjector::new(failing_pets())));
+        let request = Request::builder()
+            .uri("/api/pets")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers()[CHAOS_HEADER], "error");
+    }
+
+    #[tokio::test]
+    async fn test_disabled_is_noop() {
+        let config = ChaosConfig {
+            enabled: false,
+            ..failing_pets()
+        };
+        assert!(ChaosInjector::from_config(&config, &EnvironmentType::Development).is_none());
+
+        // A layer built from a disabled config passes everything through
+        let app = app(Some(ChaosInjector::new(config)));
+        assert_eq!(status_of(&app, "/api/pets").await, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_never_enabled_in_production() {
+        assert!(
+            ChaosInjector::from_config(&failing_pets(), &EnvironmentType::Production).is_none()
+        );
+        assert!(ChaosInjector::from_config(&failing_pets(), &EnvironmentType::Staging).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_injects_latency() {
+        let config = ChaosConfig {
+            enabled: true,
+            latency_percentage: 100.0,
+            latency_ms: 50,
+            ..ChaosConfig::default()
+        };
+        let app = app(Some(ChaosInjector::new(config)));
+        let request = Request::builder()
+            .uri("/health")
+            .body(Body::empty())
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CHAOS_HEADER], "latency");
+    }
+}
@@ -1,9 +1,9 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::error::Error as StdError;
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
@@ -15,6 +15,9 @@ use pin_project::pin_project;
 use tower::{Layer, Service};
 use tracing::{debug, error, info, warn};
 
+use crate::core::config::app_config::CircuitBreakerConfig as DependencyBreakerConfig;
+use crate::core::error::AppError;
+
 /// Circuit state
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CircuitState {
@@ -228,6 +231,164 @@ impl CircuitBreakerState {
     }
 }
 
+/// Standalone circuit breaker for guarding calls outside a tower stack
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    state: Arc<Mutex<CircuitBreakerState>>,
+}
+
+impl CircuitBreaker {
+    /// Create a circuit breaker from configuration
+    pub fn from_config(config: &crate::core::config::app_config::CircuitBreakerConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CircuitBreakerState::new(
+                Duration::from_millis(config.reset_timeout_ms),
+                config.success_threshold,
+                config.window_seconds,
+                config.failure_percentage,
+                config.failure_status_codes.clone(),
+            ))),
+        }
+    }
+
+    /// Current state of the circuit
+    pub fn state(&self) -> CircuitState {
+        self.state.lock().unwrap().state
+    }
+
+    /// Check whether a call may proceed, moving to half-open once the reset timeout elapsed
+    pub fn try_acquire(&self) -> Result<(), CircuitBreakerError> {
+        let mut state = self.state.lock().unwrap();
+        if state.state == CircuitState::Open && !state.check_transition_to_half_open() {
+            return Err(CircuitBreakerError {
+                reset_timeout: state.reset_timeout,
+                failure_rate: state.calculate_failure_percentage(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Record the status code of a completed call
+    pub fn record_status(&self, status: StatusCode) {
+        let mut state = self.state.lock().unwrap();
+        if state.is_failure_status(status) {
+            state.record_failure();
+        } else {
+            state.record_success();
+        }
+    }
+
+    /// Record a call that failed without a response
+    pub fn record_failure(&self) {
+        self.state.lock().unwrap().record_failure();
+    }
+
+    /// Record a call that succeeded
+    pub fn record_success(&self) {
+        self.state.lock().unwrap().record_success();
+    }
+}
+
+/// Circuit breakers keyed by downstream dependency name
+///
+/// Built from `reliability.circuit_breakers`, so each dependency trips on its
+/// own thresholds. Dependencies without an entry get a breaker with the
+/// default config; a disabled config means calls are never short-circuited.
+#[derive(Debug, Clone, Default)]
+pub struct CircuitBreakerRegistry {
+    breakers: Arc<RwLock<HashMap<String, Option<CircuitBreaker>>>>,
+    default_config: DependencyBreakerConfig,
+}
+
+impl CircuitBreakerRegistry {
+    /// Create an empty registry; unknown dependencies get the default config
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry from the configured breakers, using `default_config`
+    /// for dependencies without an entry
+    pub fn from_config(
+        configs: &HashMap<String, DependencyBreakerConfig>,
+        default_config: DependencyBreakerConfig,
+    ) -> Self {
+        let registry = Self {
+            default_config,
+            ..Self::default()
+        };
+        for (name, config) in configs {
+            registry.register(name, config);
+        }
+        registry
+    }
+
+    /// Register or replace the breaker for a dependency
+    pub fn register(&self, name: &str, config: &DependencyBreakerConfig) -> Option<CircuitBreaker> {
+        let breaker = config.enabled.then(|| CircuitBreaker::from_config(config));
+        self.breakers
+            .write()
+            .unwrap()
+            .insert(name.to_string(), breaker.clone());
+        breaker
+    }
+
+    /// Breaker for a dependency, created with the default config if needed;
+    /// `None` when the dependency's breaker is disabled
+    pub fn get(&self, name: &str) -> Option<CircuitBreaker> {
+        if let Some(breaker) = self.breakers.read().unwrap().get(name) {
+            return breaker.clone();
+        }
+
+        let default_config = &self.default_config;
+        self.breakers
+            .write()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                default_config
+                    .enabled
+                    .then(|| CircuitBreaker::from_config(default_config))
+            })
+            .clone()
+    }
+
+    /// Circuit state of a dependency, if it has a breaker
+    pub fn state(&self, name: &str) -> Option<CircuitState> {
+        self.breakers
+            .read()
+            .unwrap()
+            .get(name)
+            .and_then(|breaker| breaker.as_ref().map(CircuitBreaker::state))
+    }
+
+    /// Run `call` through the named dependency's breaker
+    ///
+    /// Fails fast with [`AppError::ExternalServiceError`] while the circuit
+    /// is open; an `Err` from `call` counts as a failure.
+    pub async fn call<F, T>(&self, name: &str, call: F) -> Result<T, AppError>
+    where
+        F: Future<Output = Result<T, AppError>>,
+    {
+        let Some(breaker) = self.get(name) else {
+            return call.await;
+        };
+        if let Err(e) = breaker.try_acquire() {
+            debug!("Circuit open for dependency '{}', failing fast", name);
+            return Err(AppError::ExternalServiceError(format!(
+                "Circuit breaker open for {}: {}",
+                name, e
+            )));
+        }
+
+        let result = call.await;
+        match &result {
+            Ok(_) => breaker.record_success(),
+            Err(_) => breaker.record_failure(),
+        }
+        result
+    }
+}
+
 /// Layer for adding circuit breaker capability to services
 #[derive(Clone, Debug)]
 pub struct CircuitBreakerLayer {
@@ -459,3 +620,86 @@ pub struct CircuitBreakerConfig {
     pub reset_timeout_secs: u64,
     pub success_threshold: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(failure_percentage: u8) -> DependencyBreakerConfig {
+        DependencyBreakerConfig {
+            failure_percentage,
+            reset_timeout_ms: 60_000,
+            ..Default::default()
+        }
+    }
+
+    async fn fail(registry: &CircuitBreakerRegistry, name: &str) {
+        let _ = registry
+            .call(name, async {
+                Err::<(), _>(AppError::ExternalServiceError("boom".to_string()))
+            })
+            .await;
+    }
+
+    async fn succeed(registry: &CircuitBreakerRegistry, name: &str) -> Result<(), AppError> {
+        registry.call(name, async { Ok(()) }).await
+    }
+
+    #[tokio::test]
+    async fn test_named_breakers_trip_independently() {
+        let mut configs = HashMap::new();
+        configs.insert("payments".to_string(), config(50));
+        configs.insert("inventory".to_string(), config(50));
+        let registry = CircuitBreakerRegistry::from_config(&configs, config(50));
+
+        fail(&registry, "payments").await;
+
+        assert_eq!(registry.state("payments"), Some(CircuitState::Open));
+        assert!(matches!(
+            succeed(&registry, "payments").await,
+            Err(AppError::ExternalServiceError(_))
+        ));
+        assert_eq!(registry.state("inventory"), Some(CircuitState::Closed));
+        assert!(succeed(&registry, "inventory").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_named_breakers_use_their_own_thresholds() {
+        let mut configs = HashMap::new();
+        configs.insert("strict".to_string(), config(25));
+        configs.insert("lenient".to_string(), config(75));
+        let registry = CircuitBreakerRegistry::from_config(&configs, config(50));
+
+        // Same traffic for both: three successes then a failure, 25% failing
+        for name in ["strict", "lenient"] {
+            for _ in 0..3 {
+                succeed(&registry, name).await.unwrap();
+            }
+            fail(&registry, name).await;
+        }
+
+        assert_eq!(registry.state("strict"), Some(CircuitState::Open));
+        assert_eq!(registry.state("lenient"), Some(CircuitState::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_and_unknown_dependencies() {
+        let mut configs = HashMap::new();
+        configs.insert(
+            "legacy".to_string(),
+            DependencyBreakerConfig {
+                enabled: false,
+                ..config(50)
+            },
+        );
+        let registry = CircuitBreakerRegistry::from_config(&configs, config(50));
+
+        fail(&registry, "legacy").await;
+        assert!(succeed(&registry, "legacy").await.is_ok());
+        assert_eq!(registry.state("legacy"), None);
+
+        // Unknown dependencies get a breaker with the default config
+        fail(&registry, "search").await;
+        assert_eq!(registry.state("search"), Some(CircuitState::Open));
+    }
+}
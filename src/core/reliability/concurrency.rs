@@ -11,6 +11,9 @@ use pin_project::pin_project;
 use tower::{Layer, Service};
 use tracing::{debug, info, warn};
 
+use super::drain::{DrainSignal, draining_response};
+use super::rate_limit::LimiterRejection;
+
 /// Concurrency tracker
 #[derive(Debug)]
 struct ConcurrencyTracker {
@@ -54,9 +57,8 @@ impl ConcurrencyTracker {
                 self.start_times.remove(0);
             }
 
-            // Wake one waiter if available
-            if !self.waiters.is_empty() {
-                let waker = self.waiters.remove(0);
+            // Every waiter polls again; those that miss the permit re-register
+            for waker in self.waiters.drain(..) {
                 waker.wake();
             }
         }
@@ -87,12 +89,22 @@ impl Drop for ConcurrencyPermit {
 #[derive(Clone)]
 pub struct ConcurrencyLimitLayer {
     max_concurrent: u32,
+    drain: Option<DrainSignal>,
 }
 
 impl ConcurrencyLimitLayer {
     /// Create a new concurrency limit layer
     pub fn new(max_concurrent: u32) -> Self {
-        Self { max_concurrent }
+        Self {
+            max_concurrent,
+            drain: None,
+        }
+    }
+
+    /// Reject new requests with 503 once `drain` starts; admitted requests still complete
+    pub fn with_drain(mut self, drain: DrainSignal) -> Self {
+        self.drain = Some(drain);
+        self
     }
 }
 
@@ -103,15 +115,52 @@ impl<S> Layer<S> for ConcurrencyLimitLayer {
         ConcurrencyLimitService {
             inner: service,
             tracker: Arc::new(Mutex::new(ConcurrencyTracker::new(self.max_concurrent))),
+            drain: self.drain.clone(),
+            drain_started: Mutex::new(None),
         }
     }
 }
 
 /// Service implementing concurrency limiting
-#[derive(Clone)]
 pub struct ConcurrencyLimitService<S> {
     inner: S,
     tracker: Arc<Mutex<ConcurrencyTracker>>,
+    drain: Option<DrainSignal>,
+    /// Resolves when draining starts, waking a task parked for capacity;
+    /// in a `Mutex` only to keep the service `Sync`
+    drain_started: Mutex<Option<BoxFuture<'static, ()>>>,
+}
+
+impl<S: Clone> Clone for ConcurrencyLimitService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            tracker: self.tracker.clone(),
+            drain: self.drain.clone(),
+            drain_started: Mutex::new(None),
+        }
+    }
+}
+
+impl<S> ConcurrencyLimitService<S> {
+    fn is_draining(&self) -> bool {
+        self.drain.as_ref().is_some_and(DrainSignal::is_draining)
+    }
+
+    /// Ready once draining has started; registers `cx` to be woken when it does
+    fn poll_drain_started(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let Some(drain) = &self.drain else {
+            return Poll::Pending;
+        };
+        self.drain_started
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(|| {
+                let drain = drain.clone();
+                async move { drain.wait().await }.boxed()
+            })
+            .poll_unpin(cx)
+    }
 }
 
 /// Type alias for the future response type to reduce complexity
@@ -132,6 +181,11 @@ where
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Don't queue for capacity while draining, `call` rejects straight away
+        if self.is_draining() {
+            return Poll::Ready(Ok(()));
+        }
+
         let mut state = self.tracker.lock().unwrap();
         if !state.try_acquire() {
             debug!(
@@ -139,7 +193,13 @@ where
                 state.count, state.max_concurrent
             );
             state.register_waiter(cx.waker());
-            return Poll::Pending;
+            drop(state);
+
+            // Stop waiting once draining starts, `call` then rejects the request
+            return match self.poll_drain_started(cx) {
+                Poll::Ready(()) => Poll::Ready(Ok(())),
+                Poll::Pending => Poll::Pending,
+            };
         }
 
         debug!(
@@ -155,6 +215,11 @@ where
     }
 
     fn call(&mut self, req: axum::http::Request<ReqBody>) -> Self::Future {
+        if self.is_draining() {
+            debug!("Draining, rejecting new request");
+            return futures::future::ready(Ok(draining_response())).boxed();
+        }
+
         // Try to acquire a permit
         let mut state = self.tracker.lock().unwrap();
         if !state.try_acquire() {
@@ -164,6 +229,7 @@ where
             let response = Response::builder()
                 .status(StatusCode::TOO_MANY_REQUESTS)
                 .header("Retry-After", "5")
+                .extension(LimiterRejection)
                 .body(ResBody::from(axum::body::Body::from(
                     "Server is at maximum capacity. Please try again later.",
                 )))
@@ -272,3 +338,93 @@ impl std::fmt::Display for ConcurrencyLimitError {
 }
 
 impl std::error::Error for ConcurrencyLimitError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn request(uri: &str) -> axum::http::Request<Body> {
+        axum::http::Request::builder()
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_drain_rejects_new_requests_but_finishes_in_flight() {
+        let drain = DrainSignal::new();
+        let started = Arc::new(tokio::sync::Notify::new());
+        let router = Router::new()
+            .route(
+                "/slow",
+                get({
+                    let started = started.clone();
+                    move || async move {
+                        started.notify_one();
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        "done"
+                    }
+                }),
+            )
+            .route("/fast", get(|| async { "ok" }));
+        let service = ConcurrencyLimitLayer::new(10)
+            .with_drain(drain.clone())
+            .layer(router);
+
+        let in_flight = tokio::spawn(service.clone().oneshot(request("/slow")));
+        started.notified().await;
+        drain.start();
+
+        let rejected = service.clone().oneshot(request("/fast")).await.unwrap();
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(rejected.headers().contains_key("retry-after"));
+
+        let completed = in_flight.await.unwrap().unwrap();
+        assert_eq!(completed.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(completed.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"done");
+    }
+
+    #[tokio::test]
+    async fn test_drain_wakes_requests_waiting_for_capacity() {
+        let drain = DrainSignal::new();
+        let started = Arc::new(tokio::sync::Notify::new());
+        let finish = Arc::new(tokio::sync::Notify::new());
+        let router = Router::new().route(
+            "/slow",
+            get({
+                let (started, finish) = (started.clone(), finish.clone());
+                move || async move {
+                    started.notify_one();
+                    finish.notified().await;
+                    "done"
+                }
+            }),
+        );
+        let service = ConcurrencyLimitLayer::new(1)
+            .with_drain(drain.clone())
+            .layer(router);
+
+        let in_flight = tokio::spawn(service.clone().oneshot(request("/slow")));
+        started.notified().await;
+        let waiting = tokio::spawn(service.clone().oneshot(request("/slow")));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drain.start();
+
+        // Rejected while the request holding the only permit is still running
+        let rejected = tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .expect("waiting request was not woken by the drain")
+            .unwrap()
+            .unwrap();
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        finish.notify_one();
+        assert_eq!(in_flight.await.unwrap().unwrap().status(), StatusCode::OK);
+    }
+}
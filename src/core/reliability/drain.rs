@@ -0,0 +1,109 @@
+//! Connection draining on shutdown
+//!
+//! A [`DrainSignal`] is shared between the server and the admission layers
+//! ([`ConcurrencyLimitLayer`](super::ConcurrencyLimitLayer) and
+//! [`RateLimitLayer`](super::RateLimitLayer)). Once draining starts the
+//! layers answer new requests with 503 while requests already admitted run
+//! to completion, and the server stops accepting connections. Routers
+//! without those layers can use the [`reject_while_draining`] middleware.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::Response;
+use tokio::sync::Notify;
+
+/// Seconds clients are asked to wait before retrying a drained request
+const DRAIN_RETRY_AFTER_SECONDS: &str = "5";
+
+/// Shared flag telling the server and reliability layers to drain
+#[derive(Debug, Clone, Default)]
+pub struct DrainSignal {
+    draining: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl DrainSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begin draining; calling this more than once has no further effect
+    pub fn start(&self) {
+        if !self.draining.swap(true, Ordering::SeqCst) {
+            self.notify.notify_waiters();
+        }
+    }
+
+    /// Whether draining has begun
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once draining has begun
+    pub async fn wait(&self) {
+        let notified = self.notify.notified();
+        if self.is_draining() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// 503 response for requests arriving while draining
+pub(crate) fn draining_response<B: From<axum::body::Body>>() -> Response<B> {
+    let mut response = Response::new(B::from(axum::body::Body::from(
+        "Server is shutting down. Please retry.",
+    )));
+    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    let headers = response.headers_mut();
+    headers.insert(
+        header::RETRY_AFTER,
+        HeaderValue::from_static(DRAIN_RETRY_AFTER_SECONDS),
+    );
+    headers.insert(header::CONNECTION, HeaderValue::from_static("close"));
+    response
+}
+
+/// Middleware answering 503 to every request once `drain` has started
+///
+/// Use with `axum::middleware::from_fn_with_state(DrainSignal, reject_while_draining)`.
+pub async fn reject_while_draining(
+    State(drain): State<DrainSignal>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if drain.is_draining() {
+        return draining_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_wait_resolves_on_start() {
+        let drain = DrainSignal::new();
+        let waiter = tokio::spawn({
+            let drain = drain.clone();
+            async move { drain.wait().await }
+        });
+
+        assert!(!drain.is_draining());
+        drain.start();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(drain.is_draining());
+
+        // Already draining: resolves immediately
+        drain.wait().await;
+    }
+}
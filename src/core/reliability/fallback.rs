@@ -0,0 +1,322 @@
+//! Graceful degradation when reliability layers trip
+//!
+//! [`FallbackLayer`] sits outside the circuit breaker and rate limiter. When
+//! either trips, an optional fallback hook can produce a substitute response
+//! (a cached last-known-good body, a default payload, ...) which is returned
+//! with an `x-degraded` header. Without a fallback, or when the hook declines,
+//! the caller gets the usual 503/429 error. Only 429s tagged with
+//! [`LimiterRejection`] count as rate-limit trips; a handler's own 429 passes
+//! through untouched.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::Json;
+use axum::body::{Body, HttpBody};
+use axum::http::{HeaderMap, HeaderValue, Method, Request, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use tower::{BoxError, Layer, Service, ServiceExt};
+use tracing::{error, info};
+
+use crate::core::error::ErrorResponse;
+use crate::core::reliability::circuit_breaker::CircuitBreakerError;
+use crate::core::reliability::rate_limit::{LimiterRejection, RateLimitError};
+
+/// Header marking a response produced by a fallback
+pub const DEGRADED_HEADER: &str = "x-degraded";
+
+/// Why a request was not served normally
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TripReason {
+    /// The circuit breaker is open
+    CircuitOpen,
+    /// The rate limit was exceeded
+    RateLimited,
+}
+
+impl TripReason {
+    /// Value of the `x-degraded` header for this trip
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TripReason::CircuitOpen => "circuit-open",
+            TripReason::RateLimited => "rate-limited",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            TripReason::CircuitOpen => StatusCode::SERVICE_UNAVAILABLE,
+            TripReason::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
+
+impl fmt::Display for TripReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The tripped request, passed to the fallback hook
+#[derive(Debug, Clone)]
+pub struct TripContext {
+    pub reason: TripReason,
+    pub method: Method,
+    pub uri: Uri,
+    pub headers: HeaderMap,
+}
+
+type FallbackFn =
+    Arc<dyn Fn(TripContext) -> BoxFuture<'static, Option<Response>> + Send + Sync + 'static>;
+
+/// Layer turning circuit breaker and rate limit trips into responses
+#[derive(Clone, Default)]
+pub struct FallbackLayer {
+    fallback: Option<FallbackFn>,
+}
+
+impl FallbackLayer {
+    /// Return plain 503/429 errors on trips
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Try `fallback` on trips; returning `None` falls through to the error
+    ///
+    /// ```ignore
+    /// let cache = last_known_good.clone();
+    /// FallbackLayer::new().with_fallback(move |trip| {
+    ///     let cache = cache.clone();
+    ///     async move { cache.get(trip.uri.path()).await.map(|body| Json(body).into_response()) }
+    /// })
+    /// ```
+    pub fn with_fallback<F, Fut>(mut self, fallback: F) -> Self
+    where
+        F: Fn(TripContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<Response>> + Send + 'static,
+    {
+        self.fallback = Some(Arc::new(move |trip| Box::pin(fallback(trip))));
+        self
+    }
+}
+
+impl<S> Layer<S> for FallbackLayer {
+    type Service = FallbackService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FallbackService {
+            inner,
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+/// Service produced by [`FallbackLayer`]
+#[derive(Clone)]
+pub struct FallbackService<S> {
+    inner: S,
+    fallback: Option<FallbackFn>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for FallbackService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+    ResBody: HttpBody<Data = axum::body::Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Readiness errors (an open circuit) are handled per call
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        let fallback = self.fallback.clone();
+
+        let method = req.method().clone();
+        let uri = req.uri().clone();
+        let headers = req.headers().clone();
+
+        Box::pin(async move {
+            let (reason, original) = match inner.oneshot(req).await {
+                Ok(response) if response.extensions().get::<LimiterRejection>().is_some() => {
+                    (TripReason::RateLimited, Some(response.map(Body::new)))
+                }
+                Ok(response) => return Ok(response.map(Body::new)),
+                Err(err) => {
+                    let err: BoxError = err.into();
+                    if err.is::<CircuitBreakerError>() {
+                        (TripReason::CircuitOpen, None)
+                    } else if err.is::<RateLimitError>() {
+                        (TripReason::RateLimited, None)
+                    } else {
+                        error!("Unhandled reliability error for {}: {}", uri, err);
+                        return Ok(error_response(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            "internal_error",
+                            "Internal server error",
+                        ));
+                    }
+                }
+            };
+
+            if let Some(fallback) = fallback {
+                let trip = TripContext {
+                    reason,
+                    method,
+                    uri: uri.clone(),
+                    headers,
+                };
+                if let Some(mut response) = fallback(trip).await {
+                    info!("Serving degraded response for {} ({})", uri, reason);
+                    response
+                        .headers_mut()
+                        .insert(DEGRADED_HEADER, HeaderValue::from_static(reason.as_str()));
+                    return Ok(response);
+                }
+            }
+
+            Ok(original.unwrap_or_else(|| match reason {
+                TripReason::CircuitOpen => error_response(
+                    reason.status(),
+                    "service_unavailable",
+                    "Service temporarily unavailable",
+                ),
+                TripReason::RateLimited => error_response(
+                    reason.status(),
+                    "rate_limited",
+                    "Rate limit exceeded. Please try again later.",
+                ),
+            }))
+        })
+    }
+}
+
+fn error_response(status: StatusCode, error_type: &str, message: &str) -> Response {
+    let mut error = ErrorResponse::new(error_type, message);
+    error.code = status.as_u16();
+    (status, Json(error)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::reliability::circuit_breaker::CircuitBreakerLayer;
+    use crate::core::reliability::rate_limit::RateLimitLayer;
+    use axum::Router;
+    use axum::routing::get;
+    use std::time::Duration;
+    use tower::ServiceBuilder;
+
+    fn failing_router(layer: FallbackLayer) -> Router {
+        Router::new().route(
+            "/pets",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }).layer(
+                ServiceBuilder::new()
+                    .layer(layer)
+                    .layer(CircuitBreakerLayer::new(Duration::from_secs(60), 1)),
+            ),
+        )
+    }
+
+    async fn get_pets(app: &Router) -> Response {
+        app.clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/pets")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_serves_cached_fallback() {
+        let app = failing_router(FallbackLayer::new().with_fallback(|trip| async move {
+            (trip.uri.path() == "/pets")
+                .then(|| Json(serde_json::json!([{ "name": "Rex" }])).into_response())
+        }));
+
+        // The first failure opens the circuit
+        assert_eq!(
+            get_pets(&app).await.status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+
+        let response = get_pets(&app).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[DEGRADED_HEADER], "circuit-open");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], br#"[{"name":"Rex"}]"#);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_without_fallback_returns_503() {
+        let app = failing_router(FallbackLayer::new());
+
+        get_pets(&app).await;
+        let response = get_pets(&app).await;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.headers().get(DEGRADED_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_declined_fallback_returns_503() {
+        let app = failing_router(FallbackLayer::new().with_fallback(|_| async { None }));
+
+        get_pets(&app).await;
+        let response = get_pets(&app).await;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_response_uses_fallback() {
+        let app = Router::new().route(
+            "/pets",
+            get(|| async { StatusCode::OK }).layer(
+                ServiceBuilder::new()
+                    .layer(FallbackLayer::new().with_fallback(|_| async {
+                        Some(Json(serde_json::json!([])).into_response())
+                    }))
+                    .layer(RateLimitLayer::new(1, Duration::from_secs(60), false)),
+            ),
+        );
+
+        assert_eq!(get_pets(&app).await.status(), StatusCode::OK);
+        let response = get_pets(&app).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[DEGRADED_HEADER], "rate-limited");
+    }
+
+    #[tokio::test]
+    async fn test_handler_429_passes_through() {
+        let app = Router::new().route(
+            "/pets",
+            get(|| async { StatusCode::TOO_MANY_REQUESTS })
+                .layer(FallbackLayer::new().with_fallback(|_| async {
+                    Some(Json(serde_json::json!([])).into_response())
+                })),
+        );
+
+        let response = get_pets(&app).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().get(DEGRADED_HEADER).is_none());
+    }
+}
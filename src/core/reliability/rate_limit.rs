@@ -8,14 +8,17 @@ use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
 use axum::extract::ConnectInfo;
-use axum::http::{Request, StatusCode};
-use axum::response::{IntoResponse, Response};
+use axum::http::{Method, Request, StatusCode};
+use axum::response::Response;
 use futures::{FutureExt, TryFutureExt, future::BoxFuture};
 use pin_project::pin_project;
 use thiserror::Error;
 use tower::{Layer, Service};
 use tracing::{debug, info, warn};
 
+use super::drain::{DrainSignal, draining_response};
+use crate::core::utils::trusted_proxy::ClientIp;
+
 /// Token bucket rate limiter implementation
 #[derive(Debug, Clone)]
 struct TokenBucket {
@@ -131,33 +134,88 @@ impl GlobalRateLimiter {
     }
 }
 
-/// Layer for adding rate limiting capability to services
+/// Global and (optionally) per-client limits for one class of requests
 #[derive(Clone)]
-pub struct RateLimitLayer {
-    /// Global rate limiter (applied to all requests)
+struct Quota {
+    /// Global rate limiter (applied to all requests of this class)
     global_limiter: Arc<GlobalRateLimiter>,
     /// Per-client rate limiter (if enabled)
     client_limiter: Option<Arc<RateLimitStore<IpAddr>>>,
 }
 
+impl Quota {
+    fn new(requests_per_window: u32, window: Duration, per_client: bool) -> Self {
+        Self {
+            global_limiter: Arc::new(GlobalRateLimiter::new(requests_per_window, window)),
+            client_limiter: per_client
+                .then(|| Arc::new(RateLimitStore::new(requests_per_window, window))),
+        }
+    }
+}
+
+/// Whether `method` is safe (read-only) and so counts against the read quota
+fn is_read(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// Client address used for per-client limits
+fn client_ip<B>(request: &Request<B>) -> Option<IpAddr> {
+    let extensions = request.extensions();
+    extensions
+        .get::<ClientIp>()
+        .map(|client| client.0)
+        .or_else(|| {
+            extensions
+                .get::<ConnectInfo<std::net::SocketAddr>>()
+                .map(|connect_info| connect_info.0.ip())
+        })
+}
+
+/// Layer for adding rate limiting capability to services
+///
+/// All requests share one quota unless [`RateLimitLayer::with_write_quota`]
+/// gives mutating methods their own.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    /// Quota for safe methods, and for all methods without a write quota
+    read: Quota,
+    /// Quota for mutating methods (POST, PUT, PATCH, DELETE, ...)
+    write: Option<Quota>,
+    window: Duration,
+    per_client: bool,
+    drain: Option<DrainSignal>,
+}
+
 impl RateLimitLayer {
     /// Create a new rate limit layer
     pub fn new(requests_per_window: u32, window: Duration, per_client: bool) -> Self {
-        // Create global rate limiter
-        let global_limiter = Arc::new(GlobalRateLimiter::new(requests_per_window, window));
-
-        // Create per-client rate limiter if enabled
-        let client_limiter = if per_client {
-            Some(Arc::new(RateLimitStore::new(requests_per_window, window)))
-        } else {
-            None
-        };
-
         Self {
-            global_limiter,
-            client_limiter,
+            read: Quota::new(requests_per_window, window, per_client),
+            write: None,
+            window,
+            per_client,
+            drain: None,
         }
     }
+
+    /// Reject new requests with 503 once `drain` starts
+    pub fn with_drain(mut self, drain: DrainSignal) -> Self {
+        self.drain = Some(drain);
+        self
+    }
+
+    /// Limit mutating methods to `requests_per_window`, separately from reads
+    pub fn with_write_quota(mut self, requests_per_window: u32) -> Self {
+        self.write = Some(Quota::new(
+            requests_per_window,
+            self.window,
+            self.per_client,
+        ));
+        self
+    }
 }
 
 impl<S> Layer<S> for RateLimitLayer {
@@ -166,8 +224,9 @@ impl<S> Layer<S> for RateLimitLayer {
     fn layer(&self, service: S) -> Self::Service {
         RateLimitService {
             inner: service,
-            global_limiter: self.global_limiter.clone(),
-            client_limiter: self.client_limiter.clone(),
+            read: self.read.clone(),
+            write: self.write.clone(),
+            drain: self.drain.clone(),
         }
     }
 }
@@ -176,17 +235,22 @@ impl<S> Layer<S> for RateLimitLayer {
 #[derive(Clone)]
 pub struct RateLimitService<S> {
     inner: S,
-    global_limiter: Arc<GlobalRateLimiter>,
-    client_limiter: Option<Arc<RateLimitStore<IpAddr>>>,
+    read: Quota,
+    write: Option<Quota>,
+    drain: Option<DrainSignal>,
 }
 
+/// Response extension marking a 429 produced by a limiter rather than a handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimiterRejection;
+
 /// Rate limit exceeded error response
-fn rate_limit_exceeded() -> Response {
-    (
-        StatusCode::TOO_MANY_REQUESTS,
-        "Rate limit exceeded. Please try again later.",
-    )
-        .into_response()
+fn rate_limit_exceeded<B: From<axum::body::Body>>() -> Response<B> {
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .extension(LimiterRejection)
+        .body(axum::body::Body::from("Rate limit exceeded. Please try again later.").into())
+        .unwrap()
 }
 
 impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RateLimitService<S>
@@ -207,36 +271,35 @@ where
     }
 
     fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        if self.drain.as_ref().is_some_and(DrainSignal::is_draining) {
+            debug!("Draining, rejecting new request");
+            return futures::future::ready(Ok(draining_response())).boxed();
+        }
+
+        let quota = match &self.write {
+            Some(write) if !is_read(request.method()) => write,
+            _ => &self.read,
+        };
+
         // Try to consume a global token
-        let mut limiter = self.global_limiter.bucket.lock().unwrap();
+        let mut limiter = quota.global_limiter.bucket.lock().unwrap();
         if !limiter.try_consume() {
-            warn!("Global rate limit exceeded for {}", request.uri().path());
-            return futures::future::ready(Ok(Response::builder()
-                .status(StatusCode::TOO_MANY_REQUESTS)
-                .body(axum::body::Body::from("Rate limit exceeded. Please try again later.").into())
-                .unwrap()))
-            .boxed();
+            warn!(
+                "Global rate limit exceeded for {} {}",
+                request.method(),
+                request.uri().path()
+            );
+            return futures::future::ready(Ok(rate_limit_exceeded())).boxed();
         }
         drop(limiter);
 
         // Apply per-client rate limit if enabled
-        if let Some(client_limiter) = &self.client_limiter {
-            // Try to get client IP from ConnectInfo extension
-            if let Some(client_ip) = request
-                .extensions()
-                .get::<ConnectInfo<std::net::SocketAddr>>()
-                .map(|connect_info| connect_info.0.ip())
-            {
+        if let Some(client_limiter) = &quota.client_limiter {
+            // Prefer the address resolved by TrustedProxyLayer over the socket peer
+            if let Some(client_ip) = client_ip(&request) {
                 if !client_limiter.try_consume(&client_ip) {
                     warn!("Client rate limit exceeded for IP: {}", client_ip);
-                    return futures::future::ready(Ok(Response::builder()
-                        .status(StatusCode::TOO_MANY_REQUESTS)
-                        .body(
-                            axum::body::Body::from("Rate limit exceeded. Please try again later.")
-                                .into(),
-                        )
-                        .unwrap()))
-                    .boxed();
+                    return futures::future::ready(Ok(rate_limit_exceeded())).boxed();
                 }
 
                 debug!("Rate limit check passed for client: {}", client_ip);
@@ -320,3 +383,97 @@ pub struct RetryError {
     pub attempts: u32,
     pub final_status: StatusCode,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    fn app(layer: RateLimitLayer) -> RateLimitService<Router> {
+        layer.layer(
+            Router::new().route("/pets", get(|| async { "ok" }).post(|| async { "created" })),
+        )
+    }
+
+    async fn send(app: &RateLimitService<Router>, method: Method) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri("/pets")
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_drain_rejects_new_requests() {
+        let drain = DrainSignal::new();
+        let app =
+            app(RateLimitLayer::new(5, Duration::from_secs(60), false).with_drain(drain.clone()));
+
+        assert_eq!(send(&app, Method::GET).await, StatusCode::OK);
+        drain.start();
+        assert_eq!(
+            send(&app, Method::GET).await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn test_clients_keyed_by_resolved_ip() {
+        let mut request = Request::builder().uri("/pets").body(()).unwrap();
+        let peer: std::net::SocketAddr = "10.0.0.1:443".parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(peer));
+        assert_eq!(client_ip(&request), Some(peer.ip()));
+
+        let resolved: IpAddr = "203.0.113.1".parse().unwrap();
+        request.extensions_mut().insert(ClientIp(resolved));
+        assert_eq!(client_ip(&request), Some(resolved));
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_writes_still_allow_reads() {
+        let app = app(RateLimitLayer::new(5, Duration::from_secs(60), false).with_write_quota(2));
+
+        for _ in 0..2 {
+            assert_eq!(send(&app, Method::POST).await, StatusCode::OK);
+        }
+        assert_eq!(
+            send(&app, Method::POST).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        for _ in 0..5 {
+            assert_eq!(send(&app, Method::GET).await, StatusCode::OK);
+        }
+        assert_eq!(send(&app, Method::GET).await, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_reads_still_allow_writes() {
+        let app = app(RateLimitLayer::new(2, Duration::from_secs(60), false).with_write_quota(1));
+
+        for _ in 0..2 {
+            assert_eq!(send(&app, Method::GET).await, StatusCode::OK);
+        }
+        assert_eq!(
+            send(&app, Method::HEAD).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        assert_eq!(send(&app, Method::POST).await, StatusCode::OK);
+        assert_eq!(
+            send(&app, Method::POST).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_single_quota_is_shared() {
+        let app = app(RateLimitLayer::new(2, Duration::from_secs(60), false));
+
+        assert_eq!(send(&app, Method::GET).await, StatusCode::OK);
+        assert_eq!(send(&app, Method::POST).await, StatusCode::OK);
+        assert_eq!(send(&app, Method::GET).await, StatusCode::TOO_MANY_REQUESTS);
+    }
+}
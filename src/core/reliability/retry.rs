@@ -1,10 +1,11 @@
 //! # Retry Middleware
 //!
 //! Configurable retry functionality for HTTP requests with:
-//! - Exponential backoff with jitter
+//! - Exponential backoff with jitter, computed by [`BackoffSchedule`]
 //! - Status code-based retry triggers
 //! - Configurable attempt limits
 //! - Tower middleware integration
+//! - [`retry`] for arbitrary async operations (database calls, SDK calls)
 //!
 //! ## Example
 //!
@@ -25,6 +26,16 @@
 //! };
 //! ```
 //!
+//! ## Retrying Any Async Operation
+//!
+//! ```ignore
+//! use navius::core::reliability::retry::{OperationRetryPolicy, retry};
+//!
+//! let policy = OperationRetryPolicy::from_config(&config.reliability.retry)
+//!     .with_retry_if(|e: &ServiceError| matches!(e, ServiceError::Timeout(_)));
+//! let pet = retry(&policy, || repository.find_by_id(&id)).await?;
+//! ```
+//!
 //! ## Example with Default Configuration
 //!
 //! ```
@@ -36,13 +47,14 @@
 
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
 use axum::http::Request;
 use axum::http::StatusCode;
 use axum::response::Response;
-use futures::future::{self, BoxFuture, Ready};
+use futures::future::BoxFuture;
 use futures::{FutureExt, TryFutureExt};
 use tower::{Layer, Service};
 use tracing::{debug, info, warn};
@@ -54,6 +66,8 @@ use crate::core::config::app_config::RetryConfig as AppConfigRetryConfig;
 use crate::core::error::AppError;
 use crate::core::error::error_types::Result;
 use crate::core::error::{ErrorResponse, ErrorType};
+use crate::core::reliability::backoff::BackoffSchedule;
+use crate::core::utils::sources::{self, RandomSource};
 use tower::ServiceBuilder;
 use tower::retry::Policy;
 use tower::retry::RetryLayer as TowerRetryLayer;
@@ -68,11 +82,6 @@ pub enum RetryAction {
     Return,
 }
 
-// Helper for creating Ready futures
-fn ready<T>(value: T) -> Ready<T> {
-    future::ready(value)
-}
-
 /// Type alias for our configured retry layer using Tower's RetryLayer
 /// with a custom [RetryPolicy] implementation.
 pub type RetryLayer = tower::retry::RetryLayer<RetryPolicy>;
@@ -84,8 +93,7 @@ pub struct RetryPolicy {
     max_attempts: u32,
     retry_status_codes: Vec<u16>,
     current_attempts: u32,
-    base_delay: Duration,
-    max_delay: Duration,
+    schedule: BackoffSchedule,
 }
 
 impl RetryPolicy {
@@ -94,8 +102,7 @@ impl RetryPolicy {
             max_attempts,
             retry_status_codes: vec![500, 502, 503, 504],
             current_attempts: 0,
-            base_delay: Duration::from_millis(100),
-            max_delay: Duration::from_secs(1),
+            schedule: BackoffSchedule::new(Duration::from_millis(100), Duration::from_secs(1)),
         }
     }
 
@@ -104,28 +111,199 @@ impl RetryPolicy {
         self
     }
 
+    pub fn with_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.schedule = BackoffSchedule::new(base_delay, max_delay);
+        self
+    }
+
+    /// Wait according to `schedule` between attempts
+    pub fn with_schedule(mut self, schedule: BackoffSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Delays between attempts
+    pub fn schedule(&self) -> &BackoffSchedule {
+        &self.schedule
+    }
+}
+
+/// Delay before retry number `retry` (1-based)
+///
+/// Exponential backoff doubles `base_delay` per retry and applies 0.5-1.5x
+/// jitter; fixed backoff always waits `base_delay`. Either is capped at
+/// `max_delay`.
+pub fn backoff_delay(
+    base_delay: Duration,
+    max_delay: Duration,
+    retry: u32,
+    exponential: bool,
+) -> Duration {
+    backoff_delay_with(
+        sources::random().as_ref(),
+        base_delay,
+        max_delay,
+        retry,
+        exponential,
+    )
+}
+
+/// [`backoff_delay`] drawing its jitter from `random`
+pub fn backoff_delay_with(
+    random: &dyn RandomSource,
+    base_delay: Duration,
+    max_delay: Duration,
+    retry: u32,
+    exponential: bool,
+) -> Duration {
+    schedule_for(base_delay, max_delay, exponential).delay_with(random, retry)
+}
+
+/// Schedule of [`backoff_delay`] for the given settings
+fn schedule_for(base_delay: Duration, max_delay: Duration, exponential: bool) -> BackoffSchedule {
+    let schedule = BackoffSchedule::new(base_delay, max_delay);
+    if exponential {
+        schedule
+    } else {
+        schedule.with_multiplier(1.0).without_jitter()
+    }
+}
+
+/// Retry policy for arbitrary async operations failing with `E`
+///
+/// Uses the same attempt limit and backoff settings as the HTTP retry layer;
+/// which errors are worth retrying is decided by a predicate instead of status
+/// codes.
+pub struct OperationRetryPolicy<E> {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub use_exponential_backoff: bool,
+    should_retry: Arc<dyn Fn(&E) -> bool + Send + Sync>,
+}
+
+impl<E> Clone for OperationRetryPolicy<E> {
+    fn clone(&self) -> Self {
+        Self {
+            max_attempts: self.max_attempts,
+            base_delay: self.base_delay,
+            max_delay: self.max_delay,
+            use_exponential_backoff: self.use_exponential_backoff,
+            should_retry: self.should_retry.clone(),
+        }
+    }
+}
+
+impl<E> std::fmt::Debug for OperationRetryPolicy<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OperationRetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("use_exponential_backoff", &self.use_exponential_backoff)
+            .finish()
+    }
+}
+
+impl<E> OperationRetryPolicy<E> {
+    /// Make up to `max_attempts` attempts, retrying every error
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            use_exponential_backoff: true,
+            should_retry: Arc::new(|_| true),
+        }
+    }
+
+    /// Policy with the attempts and backoff of `reliability.retry`
+    ///
+    /// A disabled retry config makes a single attempt.
+    pub fn from_config(config: &AppConfigRetryConfig) -> Self {
+        let max_attempts = if config.enabled {
+            config.max_attempts
+        } else {
+            1
+        };
+        let mut policy = Self::new(max_attempts).with_backoff(
+            Duration::from_millis(config.base_delay_ms),
+            Duration::from_millis(config.max_delay_ms),
+        );
+        policy.use_exponential_backoff = config.use_exponential_backoff;
+        policy
+    }
+
     pub fn with_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
         self.base_delay = base_delay;
         self.max_delay = max_delay;
         self
     }
 
-    // Calculate backoff delay using exponential backoff with jitter
-    fn get_backoff_duration(&self) -> Duration {
-        let base = self.base_delay.as_millis() as f64;
-        let attempt = self.current_attempts as f64;
+    /// Wait `delay` between every attempt instead of backing off exponentially
+    pub fn with_fixed_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self.max_delay = delay;
+        self.use_exponential_backoff = false;
+        self
+    }
 
-        // Exponential backoff: base * 2^attempt
-        let exp_backoff = base * 2.0_f64.powf(attempt);
+    /// Only retry errors for which `should_retry` returns true
+    pub fn with_retry_if<F>(mut self, should_retry: F) -> Self
+    where
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        self.should_retry = Arc::new(should_retry);
+        self
+    }
 
-        // Apply jitter (0.5-1.5 of the calculated value)
-        let jitter = 0.5 + rand::random::<f64>();
-        let with_jitter = exp_backoff * jitter;
+    /// Whether `error` is worth another attempt
+    pub fn should_retry(&self, error: &E) -> bool {
+        (self.should_retry)(error)
+    }
+
+    /// Delays between attempts
+    pub fn schedule(&self) -> BackoffSchedule {
+        schedule_for(
+            self.base_delay,
+            self.max_delay,
+            self.use_exponential_backoff,
+        )
+    }
 
-        // Cap at max_delay
-        let capped = with_jitter.min(self.max_delay.as_millis() as f64);
+    /// Delay before retry number `retry` (1-based)
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        self.schedule().delay(retry)
+    }
+}
 
-        Duration::from_millis(capped as u64)
+/// Run `operation` until it succeeds, fails with a non-retryable error, or
+/// `policy.max_attempts` is reached, returning the last error
+pub async fn retry<T, E, F, Fut>(
+    policy: &OperationRetryPolicy<E>,
+    mut operation: F,
+) -> std::result::Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+    E: std::fmt::Display,
+{
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts && policy.should_retry(&e) => {
+                let delay = policy.delay_for(attempt);
+                debug!(
+                    "Attempt {} of {} failed, retrying in {:?}: {}",
+                    attempt, policy.max_attempts, delay, e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
     }
 }
 
@@ -133,7 +311,7 @@ impl<B> Policy<Request<B>, Response<axum::body::Body>, Error> for RetryPolicy
 where
     B: Clone + Send + 'static,
 {
-    type Future = Ready<()>;
+    type Future = BoxFuture<'static, ()>;
 
     fn retry(
         &mut self,
@@ -153,7 +331,15 @@ where
             Err(_) => true,
         };
 
-        if should_retry { Some(ready(())) } else { None }
+        if !should_retry {
+            return None;
+        }
+        let delay = self.schedule.delay(self.current_attempts);
+        debug!(
+            "Attempt {} of {} failed, retrying in {:?}",
+            self.current_attempts, self.max_attempts, delay
+        );
+        Some(Box::pin(tokio::time::sleep(delay)))
     }
 
     fn clone_request(&mut self, req: &Request<B>) -> Option<Request<B>> {
@@ -197,8 +383,11 @@ impl RetryConfig {
                 .map(|&s| s.as_u16())
                 .collect(),
             current_attempts: 0,
-            base_delay: Duration::from_millis(self.base_delay),
-            max_delay: Duration::from_millis(self.max_delay),
+            schedule: schedule_for(
+                Duration::from_millis(self.base_delay),
+                Duration::from_millis(self.max_delay),
+                self.use_exponential_backoff,
+            ),
         };
 
         RetryLayer::new(policy)
@@ -268,10 +457,11 @@ pub fn build_retry_policy(config: &RetryConfig) -> Result<RetryPolicy> {
                 .map(|&s| s.as_u16())
                 .collect(),
         )
-        .with_backoff(
+        .with_schedule(schedule_for(
             Duration::from_millis(config.base_delay),
             Duration::from_millis(config.max_delay),
-        );
+            config.use_exponential_backoff,
+        ));
 
     Ok(policy)
 }
@@ -281,3 +471,140 @@ impl From<RetryError> for AppError {
         AppError::internal_server_error(format!("Request failed after {} retries", err.attempts))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug, PartialEq)]
+    enum DbError {
+        Timeout,
+        UniqueViolation,
+    }
+
+    impl std::fmt::Display for DbError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    fn policy(max_attempts: u32) -> OperationRetryPolicy<DbError> {
+        OperationRetryPolicy::new(max_attempts)
+            .with_fixed_delay(Duration::from_millis(1))
+            .with_retry_if(|e| *e == DbError::Timeout)
+    }
+
+    /// Operation failing with `error` for the first `failures` calls
+    async fn flaky(
+        calls: &AtomicU32,
+        failures: u32,
+        error: fn() -> DbError,
+    ) -> std::result::Result<u32, DbError> {
+        let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if call <= failures {
+            Err(error())
+        } else {
+            Ok(call)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_succeeds_after_failures() {
+        let calls = AtomicU32::new(0);
+        let result = retry(&policy(5), || flaky(&calls, 3, || DbError::Timeout)).await;
+
+        assert_eq!(result, Ok(4));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_error_is_not_retried() {
+        let calls = AtomicU32::new(0);
+        let result = retry(&policy(5), || flaky(&calls, 3, || DbError::UniqueViolation)).await;
+
+        assert_eq!(result, Err(DbError::UniqueViolation));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_max_attempts() {
+        let calls = AtomicU32::new(0);
+        let result = retry(&policy(3), || flaky(&calls, 10, || DbError::Timeout)).await;
+
+        assert_eq!(result, Err(DbError::Timeout));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_policy_from_config() {
+        let mut config = AppConfigRetryConfig {
+            max_attempts: 4,
+            base_delay_ms: 50,
+            max_delay_ms: 120,
+            ..Default::default()
+        };
+        let policy = OperationRetryPolicy::<DbError>::from_config(&config);
+
+        assert_eq!(policy.max_attempts, 4);
+        assert!(policy.delay_for(10) <= Duration::from_millis(120));
+
+        config.enabled = false;
+        let policy = OperationRetryPolicy::<DbError>::from_config(&config);
+        assert_eq!(policy.max_attempts, 1);
+    }
+
+    #[test]
+    fn test_fixed_backoff_ignores_attempt() {
+        let delay = backoff_delay(Duration::from_millis(40), Duration::from_secs(1), 5, false);
+        assert_eq!(delay, Duration::from_millis(40));
+    }
+
+    #[test]
+    fn test_http_policy_follows_configured_schedule() {
+        let config = RetryConfig {
+            base_delay: 50,
+            max_delay: 400,
+            use_exponential_backoff: false,
+            ..Default::default()
+        };
+        let policy = build_retry_policy(&config).unwrap();
+        let delays: Vec<_> = policy.schedule().clone().take(3).collect();
+        assert_eq!(delays, vec![Duration::from_millis(50); 3]);
+
+        let config = RetryConfig {
+            use_exponential_backoff: true,
+            ..config
+        };
+        let schedule = build_retry_policy(&config).unwrap().schedule().clone();
+        assert_eq!(schedule.nominal(4), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_seeded_jitter_is_reproducible() {
+        use crate::core::utils::sources::SeededRandom;
+
+        let delays = |seed| {
+            let random = SeededRandom::new(seed);
+            (1..=4)
+                .map(|retry| {
+                    backoff_delay_with(
+                        &random,
+                        Duration::from_millis(100),
+                        Duration::from_secs(10),
+                        retry,
+                        true,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(delays(7), delays(7));
+        assert_ne!(delays(7), delays(8));
+        for (retry, delay) in (1..=4).zip(delays(7)) {
+            let nominal = 100 * 2u64.pow(retry - 1);
+            assert!(delay >= Duration::from_millis(nominal / 2));
+            assert!(delay <= Duration::from_millis(nominal * 3 / 2));
+        }
+    }
+}
@@ -76,6 +76,7 @@ mod tests {
         let config = TimeoutConfig {
             enabled: true,
             timeout_seconds: 30,
+            ..Default::default()
         };
 
         let timeout_layer = build_timeout_layer(&config);
@@ -99,6 +100,7 @@ mod tests {
             requests_per_window: 100,
             window_seconds: 60,
             per_client: true,
+            write_requests_per_window: Some(20),
         };
 
         let rate_limit_layer = build_rate_limit_layer(&config);
@@ -211,6 +213,7 @@ mod tests {
             metrics_handle: Some(metrics_handle),
             resource_registry: Some(Arc::new(ApiResourceRegistry::new())),
             service_registry: Arc::new(ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create a router
@@ -221,6 +224,7 @@ mod tests {
             timeout: TimeoutConfig {
                 enabled: true,
                 timeout_seconds: 30,
+                ..Default::default()
             },
             retry: RetryConfig {
                 enabled: false,
@@ -238,6 +242,8 @@ mod tests {
                 enabled: false,
                 ..Default::default()
             },
+            circuit_breakers: Default::default(),
+            bulkheads: Default::default(),
         };
 
         // Apply reliability to the router
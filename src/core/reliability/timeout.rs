@@ -0,0 +1,310 @@
+//! Request timeouts by phase
+//!
+//! The overall `reliability.timeout.timeout_seconds` can't say whether a
+//! client was slow sending its body or the handler (or its upstream) was
+//! slow answering. [`PhaseTimeouts`] limits each phase separately, with its
+//! own status, log line and `http_request_timeouts_total{phase}` count:
+//!
+//! - `body_read`: the client didn't finish sending the body in time;
+//!   answered with `408 Request Timeout`
+//! - `handler`: the handler didn't produce a response in time; answered with
+//!   `504 Gateway Timeout`
+//! - `response_write`: the response body wasn't written in time; the status
+//!   has already been sent, so the body is cut short with an error
+//!
+//! Request and response bodies stay streamed; a phase's clock starts when
+//! the request reaches the layer (body read, handler) or when the handler
+//! returns (response write).
+
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use metrics::counter;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use thiserror::Error;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::core::config::app_config::TimeoutConfig;
+use crate::core::error::AppError;
+
+/// Counter of requests that timed out, labelled with `phase`
+pub const HTTP_REQUEST_TIMEOUTS_TOTAL: &str = "http_request_timeouts_total";
+
+/// Part of a request that took too long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPhase {
+    BodyRead,
+    Handler,
+    ResponseWrite,
+}
+
+impl TimeoutPhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BodyRead => "body_read",
+            Self::Handler => "handler",
+            Self::ResponseWrite => "response_write",
+        }
+    }
+}
+
+/// Error ending a body stream whose phase timed out
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{} timed out", .0.as_str())]
+pub struct PhaseTimeoutError(pub TimeoutPhase);
+
+/// Time allowed for each phase of a request; `None` leaves a phase unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseTimeouts {
+    pub body_read: Option<Duration>,
+    pub handler: Option<Duration>,
+    pub response_write: Option<Duration>,
+}
+
+impl PhaseTimeouts {
+    /// Phase timeouts from `config`, or `None` if disabled or none are set
+    pub fn from_config(config: &TimeoutConfig) -> Option<Self> {
+        let timeouts = Self {
+            body_read: config.body_read_timeout_seconds.map(Duration::from_secs),
+            handler: config.handler_timeout_seconds.map(Duration::from_secs),
+            response_write: config
+                .response_write_timeout_seconds
+                .map(Duration::from_secs),
+        };
+        (config.enabled && timeouts != Self::default()).then_some(timeouts)
+    }
+}
+
+fn record_timeout(phase: TimeoutPhase, path: &str) {
+    warn!("Request to {} timed out in phase {}", path, phase.as_str());
+    counter!(HTTP_REQUEST_TIMEOUTS_TOTAL, "phase" => phase.as_str()).increment(1);
+}
+
+/// `body`, failing with [`PhaseTimeoutError`] if not fully streamed within `limit`
+fn with_deadline(
+    body: Body,
+    limit: Duration,
+    phase: TimeoutPhase,
+    on_timeout: impl FnOnce() + Send + 'static,
+) -> Body {
+    let deadline = Instant::now() + limit;
+    let stream = futures::stream::unfold(
+        (body.into_data_stream(), Some(on_timeout)),
+        move |(mut stream, on_timeout)| async move {
+            // Already timed out: end the stream after the error
+            let on_timeout = on_timeout?;
+            match tokio::time::timeout_at(deadline, stream.next()).await {
+                Ok(Some(chunk)) => Some((
+                    chunk.map_err(axum::BoxError::from),
+                    (stream, Some(on_timeout)),
+                )),
+                Ok(None) => None,
+                Err(_) => {
+                    on_timeout();
+                    Some((Err(PhaseTimeoutError(phase).into()), (stream, None)))
+                }
+            }
+        },
+    );
+    Body::from_stream(stream)
+}
+
+/// Middleware enforcing [`PhaseTimeouts`]
+///
+/// Use with `axum::middleware::from_fn_with_state(timeouts, enforce_phase_timeouts)`.
+pub async fn enforce_phase_timeouts(
+    State(timeouts): State<PhaseTimeouts>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+
+    // The handler sees the body time out as a read error; remember it so the
+    // response can say the client was too slow
+    let body_timed_out = Arc::new(AtomicBool::new(false));
+    let request = match timeouts.body_read {
+        Some(limit) => {
+            let flag = body_timed_out.clone();
+            let log_path = path.clone();
+            request.map(|body| {
+                with_deadline(body, limit, TimeoutPhase::BodyRead, move || {
+                    flag.store(true, Ordering::SeqCst);
+                    record_timeout(TimeoutPhase::BodyRead, &log_path);
+                })
+            })
+        }
+        None => request,
+    };
+
+    let response = match timeouts.handler {
+        Some(limit) => match tokio::time::timeout(limit, next.run(request)).await {
+            Ok(response) => response,
+            Err(_) => {
+                record_timeout(TimeoutPhase::Handler, &path);
+                return AppError::Timeout("Request handling timed out".to_string()).into_response();
+            }
+        },
+        None => next.run(request).await,
+    };
+    if body_timed_out.load(Ordering::SeqCst) {
+        return (
+            StatusCode::REQUEST_TIMEOUT,
+            "Timed out reading the request body",
+        )
+            .into_response();
+    }
+
+    match timeouts.response_write {
+        Some(limit) => response.map(|body| {
+            with_deadline(body, limit, TimeoutPhase::ResponseWrite, move || {
+                record_timeout(TimeoutPhase::ResponseWrite, &path)
+            })
+        }),
+        None => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+    use axum::{Router, routing::post};
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use tower::ServiceExt;
+
+    const SHORT: Duration = Duration::from_millis(50);
+    const SLOW: Duration = Duration::from_millis(300);
+
+    /// Body sending one chunk, then another after `delay`
+    fn slow_body(delay: Duration) -> Body {
+        let chunks = futures::stream::iter([0, 1]).then(move |i| async move {
+            if i == 1 {
+                tokio::time::sleep(delay).await;
+            }
+            Ok::<_, std::io::Error>(Bytes::from_static(b"chunk"))
+        });
+        Body::from_stream(chunks)
+    }
+
+    fn app(timeouts: PhaseTimeouts) -> Router {
+        Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .route(
+                "/slow",
+                post(|| async {
+                    tokio::time::sleep(SLOW).await;
+                    "done"
+                }),
+            )
+            .route("/stream", post(|| async { slow_body(SLOW) }))
+            .layer(axum::middleware::from_fn_with_state(
+                timeouts,
+                enforce_phase_timeouts,
+            ))
+    }
+
+    fn all_phases() -> PhaseTimeouts {
+        PhaseTimeouts {
+            body_read: Some(SHORT),
+            handler: Some(Duration::from_millis(150)),
+            response_write: Some(SHORT),
+        }
+    }
+
+    async fn send(app: Router, uri: &str, body: Body) -> Response {
+        let request = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .body(body)
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_slow_body_and_slow_handler_time_out_distinctly() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let response = send(app(all_phases()), "/echo", slow_body(SLOW)).await;
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+
+        let response = send(app(all_phases()), "/slow", Body::empty()).await;
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let rendered = handle.render();
+        assert!(
+            rendered.contains("http_request_timeouts_total{phase=\"body_read\"} 1"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("http_request_timeouts_total{phase=\"handler\"} 1"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fast_request_is_unaffected() {
+        let response = send(app(all_phases()), "/echo", Body::from("hello")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_slow_response_write_ends_body_with_error() {
+        let response = send(app(all_phases()), "/stream", Body::empty()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let error = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap_err();
+        let timed_out = std::iter::successors(
+            Some(&error as &(dyn std::error::Error + 'static)),
+            |error| error.source(),
+        )
+        .find_map(|error| error.downcast_ref::<PhaseTimeoutError>());
+        assert_eq!(
+            timed_out,
+            Some(&PhaseTimeoutError(TimeoutPhase::ResponseWrite))
+        );
+    }
+
+    #[test]
+    fn test_from_config_requires_a_phase() {
+        let config = TimeoutConfig::default();
+        assert_eq!(PhaseTimeouts::from_config(&config), None);
+
+        let config = TimeoutConfig {
+            body_read_timeout_seconds: Some(10),
+            ..TimeoutConfig::default()
+        };
+        assert_eq!(
+            PhaseTimeouts::from_config(&config),
+            Some(PhaseTimeouts {
+                body_read: Some(Duration::from_secs(10)),
+                ..PhaseTimeouts::default()
+            })
+        );
+
+        let disabled = TimeoutConfig {
+            enabled: false,
+            ..config
+        };
+        assert_eq!(PhaseTimeouts::from_config(&disabled), None);
+    }
+}
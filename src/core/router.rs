@@ -1,6 +1,10 @@
+pub mod actuator_endpoints;
+pub mod app_handle;
 pub mod core_app_router;
 pub mod core_router;
 
 // Only use the core prefixed modules
+pub use actuator_endpoints::{ActuatorEndpoint, ExposureCheck};
+pub use app_handle::{AppHandle, ShutdownError};
 pub use core_app_router::*;
 pub use core_router::*;
@@ -0,0 +1,123 @@
+//! Application-defined actuator endpoints
+//!
+//! Operational endpoints of the application itself (a cache dump, a queue
+//! depth probe, ...) registered with
+//! [`RouterBuilder::with_actuator_endpoint`](super::RouterBuilder::with_actuator_endpoint)
+//! are served at `/actuator/<name>` behind the same authentication and
+//! authorization as the built-in endpoints, and listed by the `/actuator`
+//! index. [`ActuatorEndpoint::exposed_when`] ties an endpoint to the
+//! `endpoint_security` settings: while the check fails it answers 404 and is
+//! left out of the index, like `/actuator/config`.
+//!
+//! ```ignore
+//! let endpoint = ActuatorEndpoint::new("queue-depth", get(queue_depth))
+//!     .exposed_when(|config| config.endpoint_security.public_metrics);
+//! let (router, handle) = RouterBuilder::new().with_actuator_endpoint(endpoint).build();
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use axum::routing::MethodRouter;
+use serde_json::{Map, Value, json};
+
+use super::AppState;
+use crate::core::config::app_config::AppConfig;
+
+/// Whether an endpoint is exposed under the given configuration
+pub type ExposureCheck = fn(&AppConfig) -> bool;
+
+/// Built-in endpoints listed by the index: name, path under `/actuator`, gate
+pub(crate) const BUILTIN_ENDPOINTS: &[(&str, &str, ExposureCheck)] = &[
+    ("health", "health", |_| true),
+    ("info", "info", |_| true),
+    ("config", "config", |config| {
+        config.endpoint_security.expose_config
+    }),
+    ("config-changes", "config/changes", |config| {
+        config.endpoint_security.expose_config
+    }),
+    ("latency", "latency", |_| true),
+    ("summary", "summary", |config| {
+        config.endpoint_security.public_metrics || config.auth.enabled
+    }),
+    ("loggers", "loggers", |config| {
+        config.endpoint_security.expose_loggers
+    }),
+    ("docs", "docs", |config| config.docs_enabled()),
+    ("dashboard", "dashboard", |_| true),
+];
+
+/// An application endpoint served under `/actuator`
+#[derive(Clone)]
+pub struct ActuatorEndpoint {
+    name: String,
+    route: MethodRouter<Arc<AppState>>,
+    exposed: ExposureCheck,
+}
+
+impl fmt::Debug for ActuatorEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActuatorEndpoint")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl ActuatorEndpoint {
+    /// Endpoint at `/actuator/{name}` handled by `route`, always exposed
+    pub fn new(name: impl Into<String>, route: MethodRouter<Arc<AppState>>) -> Self {
+        Self {
+            name: name.into().trim_matches('/').to_string(),
+            route,
+            exposed: |_| true,
+        }
+    }
+
+    /// Only expose the endpoint when `check` passes
+    pub fn exposed_when(mut self, check: ExposureCheck) -> Self {
+        self.exposed = check;
+        self
+    }
+
+    /// Name of the endpoint, also its path under `/actuator`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the endpoint is exposed under `config`
+    pub fn is_exposed(&self, config: &AppConfig) -> bool {
+        (self.exposed)(config)
+    }
+
+    /// Whether the endpoint would shadow a built-in one
+    pub(crate) fn is_builtin(&self) -> bool {
+        BUILTIN_ENDPOINTS
+            .iter()
+            .any(|(name, path, _)| self.name == *name || self.name == *path)
+    }
+
+    pub(crate) fn into_route(self) -> MethodRouter<Arc<AppState>> {
+        self.route
+    }
+}
+
+/// Body of the `/actuator` index: a link to every exposed endpoint
+pub fn actuator_index(config: &AppConfig, custom: &[ActuatorEndpoint]) -> Value {
+    let mut links = Map::new();
+    links.insert("self".to_string(), json!({ "href": "/actuator" }));
+
+    let builtin = BUILTIN_ENDPOINTS
+        .iter()
+        .filter(|(_, _, exposed)| exposed(config))
+        .map(|(name, path, _)| (name.to_string(), path.to_string()));
+    let custom = custom
+        .iter()
+        .filter(|endpoint| endpoint.is_exposed(config))
+        .map(|endpoint| (endpoint.name.clone(), endpoint.name.clone()));
+    for (name, path) in builtin.chain(custom) {
+        links.insert(name, json!({ "href": format!("/actuator/{}", path) }));
+    }
+
+    json!({ "_links": links })
+}
@@ -0,0 +1,438 @@
+//! Coordinated application shutdown
+//!
+//! [`RouterBuilder::build`](super::RouterBuilder::build) returns an
+//! [`AppHandle`] next to the router. The handle owns everything that has to
+//! stop when the application does, and [`AppHandle::shutdown`] stops it in
+//! order:
+//!
+//! 1. stop admitting requests and let every server finish in-flight ones;
+//! 2. cancel background tasks started with [`AppHandle::spawn`];
+//! 3. run the [`on_flush`](AppHandle::on_flush) hooks (tracing/metrics exporters);
+//! 4. run the [`on_close`](AppHandle::on_close) hooks (database pools and
+//!    other connections).
+//!
+//! ```ignore
+//! let (app, handle) = create_application().with_config(config.clone()).build();
+//! handle.serve(server::bind(addr, &config.server.tuning)?, app, config.server.tuning.clone())?;
+//! handle.spawn("outbox-relay", relay.run());
+//! handle.on_close("database", move || async move { pool.close().await });
+//!
+//! server::shutdown_signal().await;
+//! handle.shutdown(Duration::from_secs(30)).await?;
+//! ```
+//!
+//! Several named servers, each with its own router and address, can run
+//! under one handle:
+//!
+//! ```ignore
+//! handle.start(HttpServer::new("public", public_addr, api))?;
+//! handle.start(HttpServer::new("admin", "127.0.0.1:9090".parse()?, admin))?;
+//! ```
+//!
+//! Every step shares the one timeout. Steps still running when it passes are
+//! abandoned and `shutdown` reports [`ShutdownError::TimedOut`].
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::Router;
+use futures::future::BoxFuture;
+use tokio::net::TcpListener;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{Instant, timeout_at};
+use tracing::{debug, info, warn};
+
+use crate::core::config::app_config::ServerTuning;
+use crate::core::error::AppError;
+use crate::core::reliability::DrainSignal;
+use crate::core::server::{self, HttpServer};
+
+/// Shutdown did not complete cleanly
+#[derive(Debug, thiserror::Error)]
+pub enum ShutdownError {
+    #[error("Shutdown did not finish within {0:?}")]
+    TimedOut(Duration),
+
+    #[error("Server failed while draining: {0}")]
+    Server(#[from] io::Error),
+}
+
+impl From<ShutdownError> for AppError {
+    fn from(error: ShutdownError) -> Self {
+        AppError::InternalServerError(error.to_string())
+    }
+}
+
+type Hook = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// Handle to a built application, used to shut it down
+///
+/// Clones share the same state.
+#[derive(Clone)]
+pub struct AppHandle {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    drain: DrainSignal,
+    servers: Mutex<Vec<(String, JoinHandle<io::Result<()>>)>>,
+    tasks: Mutex<JoinSet<()>>,
+    flush: Mutex<Vec<(String, Hook)>>,
+    close: Mutex<Vec<(String, Hook)>>,
+}
+
+impl std::fmt::Debug for AppHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppHandle")
+            .field("draining", &self.inner.drain.is_draining())
+            .field("servers", &self.server_names())
+            .field("tasks", &self.inner.tasks.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl AppHandle {
+    /// Handle that shuts the application down through `drain`
+    pub fn new(drain: DrainSignal) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                drain,
+                servers: Mutex::new(Vec::new()),
+                tasks: Mutex::new(JoinSet::new()),
+                flush: Mutex::new(Vec::new()),
+                close: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Signal the router and server drain on
+    pub fn drain(&self) -> &DrainSignal {
+        &self.inner.drain
+    }
+
+    /// Serve `app` on `listener` in the background until shutdown
+    pub fn serve(
+        &self,
+        listener: TcpListener,
+        app: Router,
+        tuning: ServerTuning,
+    ) -> io::Result<()> {
+        self.serve_named("main", listener, app, tuning)
+    }
+
+    /// Serve `app` on `listener` as server `name` until shutdown
+    ///
+    /// Fails with [`io::ErrorKind::AlreadyExists`] if a server is already
+    /// running under `name`; the listener is then dropped unserved.
+    pub fn serve_named(
+        &self,
+        name: &str,
+        listener: TcpListener,
+        app: Router,
+        tuning: ServerTuning,
+    ) -> io::Result<()> {
+        let mut servers = self.inner.servers.lock().unwrap();
+        if servers.iter().any(|(existing, _)| existing == name) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("A server named {} is already running", name),
+            ));
+        }
+        let server = tokio::spawn(server::serve_with_drain(
+            listener,
+            app,
+            tuning,
+            self.inner.drain.clone(),
+        ));
+        servers.push((name.to_string(), server));
+        Ok(())
+    }
+
+    /// Bind `server` and serve it until shutdown, returning its bound address
+    pub fn start(&self, server: HttpServer) -> io::Result<SocketAddr> {
+        let name = server.name().to_string();
+        let (listener, app, tuning) = server.bind()?;
+        let addr = listener.local_addr()?;
+        self.serve_named(&name, listener, app, tuning)?;
+        info!("Server {} listening on {}", name, addr);
+        Ok(addr)
+    }
+
+    /// Names of the servers running under this handle
+    pub fn server_names(&self) -> Vec<String> {
+        let servers = self.inner.servers.lock().unwrap();
+        servers.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Run `task` in the background, cancelling it on shutdown
+    pub fn spawn<F>(&self, name: &str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if self.inner.drain.is_draining() {
+            warn!("Not starting background task {}: shutting down", name);
+            return;
+        }
+        let name = name.to_string();
+        self.inner.tasks.lock().unwrap().spawn(async move {
+            task.await;
+            debug!("Background task {} finished", name);
+        });
+    }
+
+    /// Run `hook` on shutdown once background tasks have stopped
+    ///
+    /// Meant for flushing tracing and metrics exporters.
+    pub fn on_flush<F, Fut>(&self, name: &str, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        push_hook(&self.inner.flush, name, hook);
+    }
+
+    /// Run `hook` last on shutdown, after the flush hooks
+    ///
+    /// Meant for closing database pools and other connections.
+    pub fn on_close<F, Fut>(&self, name: &str, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        push_hook(&self.inner.close, name, hook);
+    }
+
+    /// Stop the application, giving the whole sequence at most `timeout`
+    ///
+    /// Calling this again after it returned does nothing.
+    pub async fn shutdown(&self, timeout: Duration) -> Result<(), ShutdownError> {
+        let started = Instant::now();
+        let deadline = started + timeout;
+        let mut timed_out = false;
+        let mut server_error = None;
+
+        // 1. Stop admitting requests; every server finishes those in flight
+        self.inner.drain.start();
+        let servers = std::mem::take(&mut *self.inner.servers.lock().unwrap());
+        let drained =
+            futures::future::join_all(servers.into_iter().map(|(name, mut server)| async move {
+                let result = timeout_at(deadline, &mut server).await;
+                if result.is_err() {
+                    server.abort();
+                }
+                (name, result)
+            }))
+            .await;
+        for (name, result) in drained {
+            match result {
+                Ok(Ok(Ok(()))) => {}
+                Ok(Ok(Err(e))) => {
+                    warn!("Server {} failed while draining: {}", name, e);
+                    server_error.get_or_insert(e);
+                }
+                Ok(Err(e)) => warn!("Server {} task failed: {}", name, e),
+                Err(_) => {
+                    warn!(
+                        "Server {} did not drain in time, closing open connections",
+                        name
+                    );
+                    timed_out = true;
+                }
+            }
+        }
+
+        // 2. Cancel background tasks
+        let mut tasks = std::mem::take(&mut *self.inner.tasks.lock().unwrap());
+        if !tasks.is_empty() {
+            debug!("Cancelling {} background tasks", tasks.len());
+            tasks.abort_all();
+            let joined = timeout_at(deadline, async {
+                while tasks.join_next().await.is_some() {}
+            })
+            .await;
+            timed_out |= joined.is_err();
+        }
+
+        // 3. Flush observability, 4. close resources
+        let flush = std::mem::take(&mut *self.inner.flush.lock().unwrap());
+        timed_out |= run_hooks(flush, deadline).await;
+        let close = std::mem::take(&mut *self.inner.close.lock().unwrap());
+        timed_out |= run_hooks(close, deadline).await;
+
+        if timed_out {
+            return Err(ShutdownError::TimedOut(timeout));
+        }
+        if let Some(e) = server_error {
+            return Err(e.into());
+        }
+        info!("Shutdown complete in {:?}", started.elapsed());
+        Ok(())
+    }
+}
+
+fn push_hook<F, Fut>(hooks: &Mutex<Vec<(String, Hook)>>, name: &str, hook: F)
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let hook: Hook = Box::new(move || Box::pin(hook()));
+    hooks.lock().unwrap().push((name.to_string(), hook));
+}
+
+/// Run `hooks` in order; returns whether any missed the deadline
+async fn run_hooks(hooks: Vec<(String, Hook)>, deadline: Instant) -> bool {
+    let mut timed_out = false;
+    for (name, hook) in hooks {
+        debug!("Running shutdown hook {}", name);
+        if timeout_at(deadline, hook()).await.is_err() {
+            warn!("Shutdown hook {} did not finish in time", name);
+            timed_out = true;
+        }
+    }
+    timed_out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::router::core_app_router::RouterBuilder;
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use tokio::sync::oneshot;
+    use tower::ServiceExt;
+
+    async fn status_of(app: &Router) -> StatusCode {
+        let request = Request::builder()
+            .uri("/health")
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    /// Reports on `sender` when the task owning it is dropped
+    struct DropReporter(Option<oneshot::Sender<()>>);
+
+    impl Drop for DropReporter {
+        fn drop(&mut self) {
+            if let Some(sender) = self.0.take() {
+                let _ = sender.send(());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_completes_and_refuses_requests() {
+        let (app, handle) = RouterBuilder::new().build();
+        let tuning = ServerTuning::default();
+        let listener = server::bind("127.0.0.1:0".parse().unwrap(), &tuning).unwrap();
+        let addr = listener.local_addr().unwrap();
+        handle.serve(listener, app.clone(), tuning).unwrap();
+
+        let (cancelled_tx, cancelled_rx) = oneshot::channel();
+        handle.spawn("forever", async move {
+            let _reporter = DropReporter(Some(cancelled_tx));
+            std::future::pending::<()>().await;
+        });
+        let order = Arc::new(Mutex::new(Vec::new()));
+        // Registered out of order on purpose
+        handle.on_close("database", {
+            let order = order.clone();
+            move || async move { order.lock().unwrap().push("database") }
+        });
+        handle.on_flush("tracing", {
+            let order = order.clone();
+            move || async move { order.lock().unwrap().push("tracing") }
+        });
+
+        assert_eq!(status_of(&app).await, StatusCode::OK);
+        let response = reqwest::get(format!("http://{}/health", addr))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        handle.shutdown(Duration::from_secs(2)).await.unwrap();
+
+        // Background task cancelled, flush before close
+        cancelled_rx.await.unwrap();
+        assert_eq!(*order.lock().unwrap(), vec!["tracing", "database"]);
+
+        // New requests are refused
+        assert_eq!(status_of(&app).await, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+
+        // A second shutdown has nothing left to do
+        handle.shutdown(Duration::from_secs(1)).await.unwrap();
+    }
+
+    async fn get(addr: SocketAddr, path: &str) -> reqwest::StatusCode {
+        reqwest::get(format!("http://{}{}", addr, path))
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_named_servers_serve_own_routes_and_shut_down_together() {
+        let (api, handle) = RouterBuilder::new().build();
+        let admin = Router::new().route("/admin/ping", axum::routing::get(|| async { "pong" }));
+        let local = "127.0.0.1:0".parse().unwrap();
+
+        let public_addr = handle.start(HttpServer::new("public", local, api)).unwrap();
+        let admin_addr = handle
+            .start(HttpServer::new("admin", local, admin))
+            .unwrap();
+        assert_eq!(handle.server_names(), vec!["public", "admin"]);
+
+        assert_eq!(get(public_addr, "/health").await, reqwest::StatusCode::OK);
+        assert_eq!(
+            get(admin_addr, "/admin/ping").await,
+            reqwest::StatusCode::OK
+        );
+        assert_eq!(
+            get(public_addr, "/admin/ping").await,
+            reqwest::StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            get(admin_addr, "/health").await,
+            reqwest::StatusCode::NOT_FOUND
+        );
+
+        handle.shutdown(Duration::from_secs(2)).await.unwrap();
+
+        assert!(handle.server_names().is_empty());
+        assert!(tokio::net::TcpStream::connect(public_addr).await.is_err());
+        assert!(tokio::net::TcpStream::connect(admin_addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_server_name_is_rejected() {
+        let (api, handle) = RouterBuilder::new().build();
+        let local = "127.0.0.1:0".parse().unwrap();
+        let first_addr = handle
+            .start(HttpServer::new("public", local, api.clone()))
+            .unwrap();
+
+        let error = handle
+            .start(HttpServer::new("public", local, api))
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(handle.server_names(), vec!["public"]);
+        // The first server is still served and still awaited on shutdown
+        assert_eq!(get(first_addr, "/health").await, reqwest::StatusCode::OK);
+        handle.shutdown(Duration::from_secs(2)).await.unwrap();
+        assert!(tokio::net::TcpStream::connect(first_addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slow_hook_times_out() {
+        let handle = AppHandle::new(DrainSignal::new());
+        handle.on_close("slow", || tokio::time::sleep(Duration::from_secs(5)));
+
+        let result = handle.shutdown(Duration::from_millis(50)).await;
+
+        assert!(matches!(result, Err(ShutdownError::TimedOut(_))));
+    }
+}
@@ -1,19 +1,49 @@
 use axum::{
-    Router,
-    extract::State,
-    routing::{get, post},
+    BoxError, Router,
+    error_handling::HandleErrorLayer,
+    extract::{Request, State},
+    response::IntoResponse,
+    routing::{Route, get, post},
 };
 #[cfg(feature = "metrics")]
 use metrics_exporter_prometheus::PrometheusHandle;
 use reqwest::Client;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, OnceLock};
 use std::time::SystemTime;
+use tower::{Layer, Service};
+use tracing::warn;
 
 #[cfg(feature = "auth")]
 use crate::core::auth::TokenClient;
+#[cfg(feature = "auth")]
+use crate::core::auth::middleware::{EntraAuthLayer, RoleRequirement};
+#[cfg(feature = "auth")]
+use crate::core::auth::policy::{AuthorizationPolicy, authorize};
+#[cfg(feature = "metrics")]
+use crate::core::observability::ObservabilityConfig;
+use crate::core::router::actuator_endpoints::ActuatorEndpoint;
+use crate::core::router::app_handle::AppHandle;
 use crate::core::{
-    cache::cache_manager::CacheRegistry, config::app_config::AppConfig,
+    cache::cache_manager::CacheRegistry,
+    config::app_config::{AppConfig, ErrorFormat},
+    error::localization::{MessageCatalog, localize_errors},
+    error::AppError,
+    error::middleware::RequestTrackingLayer,
+    error::problem::problem_json_errors,
+    error::rejection::json_rejections,
+    features::overrides::{FeatureOverridePolicy, apply_feature_overrides},
+    metrics::route_metrics::{RouteMetrics, record_request_metrics},
+    reliability::chaos::{ChaosInjector, inject_faults},
+    reliability::drain::{DrainSignal, reject_while_draining},
+    services::{HealthResultCache, cancellation::cancel_on_disconnect},
     utils::api_resource::ApiResourceRegistry,
+    utils::cors::cors_layer,
+    utils::http_client::build_client,
+    utils::json_numbers::{IntegerEncoding, encode_json_integers},
+    utils::path_normalization::PathNormalizationLayer,
+    utils::trusted_proxy::TrustedProxyLayer,
 };
 
 /// ServiceRegistry for dependency injection
@@ -85,6 +115,9 @@ pub struct AppState {
 
     /// Service registry for dependency injection
     pub service_registry: Arc<ServiceRegistry>,
+
+    /// Recent readiness result, reused by rapid probes
+    pub readiness_cache: HealthResultCache,
 }
 
 impl Default for AppState {
@@ -98,7 +131,144 @@ impl Default for AppState {
             metrics_handle: None,
             resource_registry: None,
             service_registry: Arc::new(ServiceRegistry::new()),
+            readiness_cache: HealthResultCache::new(),
+        }
+    }
+}
+
+impl AppState {
+    /// Shared client for outbound calls
+    ///
+    /// [`RouterBuilder::build`] configures one from `api.http_client` unless
+    /// a client was supplied. States without a client share a single
+    /// process-wide client, so no caller ends up with a pool of its own.
+    pub fn http_client(&self) -> &Client {
+        static FALLBACK: OnceLock<Client> = OnceLock::new();
+        match &self.client {
+            Some(client) => client,
+            None => FALLBACK.get_or_init(|| build_client(&self.config)),
+        }
+    }
+}
+
+/// Named positions in the built-in middleware stack
+///
+/// Requests pass through the stack in declaration order and responses travel
+/// back in reverse:
+///
+/// ```text
+/// Cors -> Metrics -> Reliability -> Auth -> handler
+/// ```
+///
+/// `Cors`, `Metrics` and `Reliability` wrap every route. `Auth` wraps the
+/// protected route group (the actuator endpoints), since that is where the
+/// authentication layer lives. A custom layer keeps its position even when the
+/// built-in layer it is anchored to is disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayerMarker {
+    /// CORS handling, outermost so preflight requests are answered first
+    Cors,
+    /// Request metrics collection
+    Metrics,
+    /// Timeouts, retries and other reliability middleware
+    Reliability,
+    /// Bearer token authentication for protected routes
+    Auth,
+}
+
+/// A deferred `Router::layer` call
+type RouterLayerFn = Box<dyn FnOnce(Router) -> Router + Send>;
+
+/// Custom layers anchored to positions in the built-in middleware stack
+#[derive(Default)]
+pub struct MiddlewareStack {
+    before: HashMap<LayerMarker, Vec<RouterLayerFn>>,
+    after: HashMap<LayerMarker, Vec<RouterLayerFn>>,
+}
+
+impl std::fmt::Debug for MiddlewareStack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = |map: &HashMap<LayerMarker, Vec<RouterLayerFn>>| {
+            map.iter()
+                .map(|(marker, layers)| (*marker, layers.len()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        f.debug_struct("MiddlewareStack")
+            .field("before", &count(&self.before))
+            .field("after", &count(&self.after))
+            .finish()
+    }
+}
+
+impl MiddlewareStack {
+    /// Create an empty stack
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a layer that runs before the built-in layer at `marker`
+    pub fn insert_before<L>(&mut self, marker: LayerMarker, layer: L)
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.before
+            .entry(marker)
+            .or_default()
+            .push(Box::new(move |router: Router| router.layer(layer)));
+    }
+
+    /// Insert a layer that runs after the built-in layer at `marker`
+    pub fn insert_after<L>(&mut self, marker: LayerMarker, layer: L)
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.after
+            .entry(marker)
+            .or_default()
+            .push(Box::new(move |router: Router| router.layer(layer)));
+    }
+
+    /// Wrap `router` with the built-in layer for `marker` and the custom layers around it
+    ///
+    /// Layers anchored to the same side of a marker run in the order they were added.
+    pub fn apply_at<F>(&mut self, marker: LayerMarker, router: Router, builtin: F) -> Router
+    where
+        F: FnOnce(Router) -> Router,
+    {
+        // `Router::layer` wraps from the inside out, so the innermost layers go first
+        let mut router = router;
+        for apply in self
+            .after
+            .remove(&marker)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+        {
+            router = apply(router);
+        }
+
+        router = builtin(router);
+
+        for apply in self
+            .before
+            .remove(&marker)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+        {
+            router = apply(router);
         }
+
+        router
     }
 }
 
@@ -115,6 +285,33 @@ pub struct RouterBuilder {
 
     /// Whether authentication is enabled
     auth_enabled: bool,
+
+    /// Whether the reliability middleware is enabled
+    reliability_enabled: bool,
+
+    /// Custom layers positioned relative to the built-in layers
+    middleware: MiddlewareStack,
+
+    /// Catalog used to localize error messages, if any
+    message_catalog: Option<Arc<MessageCatalog>>,
+
+    /// Body format for error responses
+    error_format: ErrorFormat,
+
+    /// Request tracking with its span sampling, if any
+    request_tracking: Option<RequestTrackingLayer>,
+
+    /// Signal that stops admitting requests on shutdown, if any
+    drain: Option<DrainSignal>,
+
+    /// Per-route metric exclusions and labels
+    route_metrics: RouteMetrics,
+
+    /// Application endpoints served under `/actuator`
+    actuator_endpoints: Vec<ActuatorEndpoint>,
+
+    /// Application routes served alongside the core routes
+    routes: Router<Arc<AppState>>,
 }
 
 impl RouterBuilder {
@@ -125,6 +322,15 @@ impl RouterBuilder {
             cors_enabled: true,
             metrics_enabled: true,
             auth_enabled: false,
+            reliability_enabled: false,
+            middleware: MiddlewareStack::new(),
+            message_catalog: None,
+            error_format: ErrorFormat::default(),
+            request_tracking: None,
+            drain: None,
+            route_metrics: RouteMetrics::default(),
+            actuator_endpoints: Vec::new(),
+            routes: Router::new(),
         }
     }
 
@@ -132,6 +338,7 @@ impl RouterBuilder {
     pub fn with_config(mut self, config: AppConfig) -> Self {
         self.app_state.config = config.clone();
         self.auth_enabled = config.auth.enabled;
+        self.error_format = config.server.error_format;
         self
     }
 
@@ -195,15 +402,286 @@ impl RouterBuilder {
         self
     }
 
+    /// Enable or disable the reliability middleware configured in `reliability`
+    pub fn with_reliability(mut self, enabled: bool) -> Self {
+        self.reliability_enabled = enabled;
+        self
+    }
+
+    /// Localize error messages from `catalog` based on the request's `Accept-Language`
+    pub fn with_message_catalog(mut self, catalog: MessageCatalog) -> Self {
+        self.message_catalog = Some(Arc::new(catalog));
+        self
+    }
+
+    /// Render error responses in the given format
+    pub fn with_error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_format = format;
+        self
+    }
+
+    /// Track requests, sampling their spans as `config` says
+    #[cfg(feature = "metrics")]
+    pub fn with_observability(mut self, config: &ObservabilityConfig) -> Self {
+        self.request_tracking = Some(
+            RequestTrackingLayer::new(&config.service_name).with_sampling(config.trace_sampling()),
+        );
+        self
+    }
+
+    /// Exclude routes from request metrics or give them extra labels
+    pub fn with_route_metrics(mut self, route_metrics: RouteMetrics) -> Self {
+        self.route_metrics = route_metrics;
+        self
+    }
+
+    /// Serve `endpoint` under `/actuator`, secured like the built-in actuator endpoints
+    pub fn with_actuator_endpoint(mut self, endpoint: ActuatorEndpoint) -> Self {
+        self.actuator_endpoints.push(endpoint);
+        self
+    }
+
+    /// Serve application `routes` alongside the core routes
+    ///
+    /// They sit behind the same layers as the core routes, including the
+    /// `auth.rules` authorization policy.
+    pub fn with_routes(mut self, routes: Router<Arc<AppState>>) -> Self {
+        self.routes = self.routes.merge(routes);
+        self
+    }
+
+    /// Share `drain` with the router instead of creating a new signal
+    ///
+    /// New requests are answered with 503 once it starts, letting in-flight
+    /// ones finish. [`AppHandle::shutdown`] starts it.
+    pub fn with_drain(mut self, drain: DrainSignal) -> Self {
+        self.drain = Some(drain);
+        self
+    }
+
+    /// Insert a custom layer so that it runs before the built-in layer at `marker`
+    ///
+    /// See [`LayerMarker`] for the resulting request-processing order.
+    pub fn with_layer_before<L>(mut self, marker: LayerMarker, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.middleware.insert_before(marker, layer);
+        self
+    }
+
+    /// Insert a custom layer so that it runs after the built-in layer at `marker`
+    ///
+    /// See [`LayerMarker`] for the resulting request-processing order.
+    pub fn with_layer_after<L>(mut self, marker: LayerMarker, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.middleware.insert_after(marker, layer);
+        self
+    }
+
     /// Build the router with all configured components
-    pub fn build(self) -> Router {
+    ///
+    /// The [`AppHandle`] shuts the application down; see [`app_handle`](super::app_handle).
+    pub fn build(self) -> (Router, AppHandle) {
+        let (router, handle, _) = self.build_with_state();
+        (router, handle)
+    }
+
+    /// [`build`](Self::build), also returning the state for routers served separately
+    ///
+    /// ```ignore
+    /// let (api, handle, state) = create_application().with_config(config).build_with_state();
+    /// let admin = admin_routes().with_state(state);
+    /// handle.start(HttpServer::new("public", public_addr, api))?;
+    /// handle.start(HttpServer::new("admin", "127.0.0.1:9090".parse()?, admin))?;
+    /// ```
+    pub fn build_with_state(mut self) -> (Router, AppHandle, Arc<AppState>) {
+        if self.app_state.client.is_none() {
+            self.app_state.client = Some(build_client(&self.app_state.config));
+        }
+        let reliability_config = self.app_state.config.reliability.clone();
+        let trusted_proxy =
+            match TrustedProxyLayer::from_cidrs(&self.app_state.config.server.trusted_proxies) {
+                Ok(layer) => layer,
+                Err(e) => {
+                    warn!("Ignoring server.trusted_proxies: {}", e);
+                    TrustedProxyLayer::default()
+                }
+            };
+        let cors = match cors_layer(&self.app_state.config.server.cors) {
+            Ok(layer) => layer,
+            Err(e) => {
+                warn!(
+                    "Ignoring server.cors, allowing no cross-origin requests: {}",
+                    e
+                );
+                tower_http::cors::CorsLayer::new()
+            }
+        };
+        let path_normalization =
+            PathNormalizationLayer::from_config(&self.app_state.config.server.path_normalization);
+        let chaos = ChaosInjector::from_config(
+            &reliability_config.chaos,
+            &self.app_state.config.environment,
+        );
+        let feature_overrides = FeatureOverridePolicy::from_config(
+            &self.app_state.config.features.overrides,
+            &self.app_state.config.environment,
+        );
         let state = Arc::new(self.app_state);
+        let mut middleware = self.middleware;
+
+        // Delegate route creation to CoreRouter, which applies the auth slot
+        let router =
+            crate::core::router::core_router::CoreRouter::create_core_routes_with_middleware(
+                state.clone(),
+                &mut middleware,
+                self.actuator_endpoints,
+            );
+        let router = router.merge(self.routes.with_state(state.clone()));
+
+        // Route rules from `auth.rules`, over the core and application routes alike
+        #[cfg(feature = "auth")]
+        let router = match authorization_policy(&state) {
+            Some(policy) => router.layer(axum::middleware::from_fn_with_state(policy, authorize)),
+            None => router,
+        };
+
+        // Apply the remaining slots from the inside out
+        let reliability_enabled = self.reliability_enabled;
+        let drain = self.drain.unwrap_or_default();
+        let handle = AppHandle::new(drain.clone());
+        let router = middleware.apply_at(LayerMarker::Reliability, router, |router| {
+            // Scoped around the handler itself so nothing in between loses the overrides
+            let router = match feature_overrides {
+                Some(policy) => router.layer(axum::middleware::from_fn_with_state(
+                    policy,
+                    apply_feature_overrides,
+                )),
+                None => router,
+            };
+            // Innermost, so injected latency and errors meet the timeout and other policies
+            let router = match chaos {
+                Some(chaos) => {
+                    router.layer(axum::middleware::from_fn_with_state(chaos, inject_faults))
+                }
+                None => router,
+            };
+            let router = if reliability_enabled {
+                crate::core::reliability::apply_reliability(router, &reliability_config)
+            } else {
+                router
+            };
+            let router = router.layer(axum::middleware::from_fn_with_state(
+                drain,
+                reject_while_draining,
+            ));
+            // Let handlers abandon queries when the client disconnects
+            let router = router.layer(axum::middleware::from_fn(cancel_on_disconnect));
+            // Resolve the client address before anything keys on it
+            router.layer(trusted_proxy)
+        });
+
+        let metrics_enabled = self.metrics_enabled;
+        let route_metrics = Arc::new(self.route_metrics);
+        let router = middleware.apply_at(LayerMarker::Metrics, router, |router| {
+            if metrics_enabled {
+                router.layer(axum::middleware::from_fn_with_state(
+                    route_metrics,
+                    record_request_metrics,
+                ))
+            } else {
+                router
+            }
+        });
+
+        let cors_enabled = self.cors_enabled;
+        let router = middleware.apply_at(LayerMarker::Cors, router, |router| {
+            if cors_enabled {
+                router.layer(cors)
+            } else {
+                router
+            }
+        });
+
+        // Let JavaScript clients ask for large integers as strings
+        let router = router.layer(axum::middleware::from_fn_with_state(
+            IntegerEncoding::Number,
+            encode_json_integers,
+        ));
 
-        // Delegate route creation to CoreRouter
-        crate::core::router::core_router::CoreRouter::create_core_routes(state)
+        // Give axum's plain-text extractor rejections the AppError shape
+        let router = router.layer(axum::middleware::from_fn(json_rejections));
+
+        // Localize errors produced anywhere in the stack
+        let router = match self.message_catalog {
+            Some(catalog) => router.layer(axum::middleware::from_fn_with_state(
+                catalog,
+                localize_errors,
+            )),
+            None => router,
+        };
+
+        // Reshape (possibly localized) errors last
+        let router = match self.error_format {
+            ErrorFormat::Legacy => router,
+            format => router.layer(axum::middleware::from_fn_with_state(
+                format,
+                problem_json_errors,
+            )),
+        };
+
+        // Outermost, so the layers above see its request id
+        let router = match self.request_tracking {
+            Some(tracking) => router.layer(
+                tower::ServiceBuilder::new()
+                    .layer(HandleErrorLayer::new(|e: BoxError| async move {
+                        AppError::internal_server_error(e.to_string()).into_response()
+                    }))
+                    .layer(tracking),
+            ),
+            None => router,
+        };
+
+        // Outside the router, so the canonical path is the one that gets routed
+        let router = match path_normalization {
+            Some(layer) => Router::new().fallback_service(layer.layer(router)),
+            None => router,
+        };
+        (router, handle, state)
     }
 }
 
+/// Policy from `auth.rules`, or `None` when it would allow everything
+///
+/// With authentication enabled, the policy validates tokens itself for rules
+/// that require roles, since most routes have no authentication layer.
+#[cfg(feature = "auth")]
+fn authorization_policy(state: &AppState) -> Option<AuthorizationPolicy> {
+    let policy = AuthorizationPolicy::from_config(&state.config.auth);
+    if policy.is_permissive() {
+        return None;
+    }
+    if !state.config.auth.enabled {
+        return Some(policy);
+    }
+    let authentication =
+        EntraAuthLayer::from_app_config_require_any_role(&state.config, Vec::new())
+            .with_role_requirement(RoleRequirement::None)
+            .with_http_client(state.http_client().clone());
+    Some(policy.with_authentication(authentication))
+}
+
 /// Initialize the application state with default configuration
 pub fn init_app_state() -> AppState {
     AppState::default()
@@ -226,7 +704,7 @@ mod tests {
     #[tokio::test]
     async fn test_router_builder_basic() {
         // Create a router with default settings
-        let app = RouterBuilder::new().build();
+        let (app, _handle) = RouterBuilder::new().build();
 
         // Create a request to the health endpoint
         let request = Request::builder()
@@ -242,6 +720,367 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_observability_config_enables_request_tracking() {
+        let request = || Request::builder().uri("/health").body(Body::empty()).unwrap();
+
+        let (app, _handle) = RouterBuilder::new().build();
+        let response = app.oneshot(request()).await.unwrap();
+        assert!(!response.headers().contains_key("x-request-id"));
+
+        let config = ObservabilityConfig::new("prometheus", "test").with_trace_sample_rate(0.0);
+        let (app, _handle) = RouterBuilder::new().with_observability(&config).build();
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key("x-request-id"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_layers_run_in_marker_order() {
+        use std::sync::Mutex;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let recorder = |name: &'static str, log: Arc<Mutex<Vec<&'static str>>>| {
+            axum::middleware::from_fn(
+                move |req: axum::extract::Request, next: axum::middleware::Next| {
+                    let log = log.clone();
+                    async move {
+                        log.lock().unwrap().push(name);
+                        next.run(req).await
+                    }
+                },
+            )
+        };
+
+        let (app, _handle) = RouterBuilder::new()
+            .with_layer_after(LayerMarker::Metrics, recorder("after_metrics", log.clone()))
+            .with_layer_before(LayerMarker::Cors, recorder("before_cors", log.clone()))
+            .with_layer_before(
+                LayerMarker::Reliability,
+                recorder("before_reliability", log.clone()),
+            )
+            .with_layer_before(
+                LayerMarker::Metrics,
+                recorder("before_metrics", log.clone()),
+            )
+            .build();
+
+        let request = Request::builder()
+            .uri("/health")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "before_cors",
+                "before_metrics",
+                "after_metrics",
+                "before_reliability"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_format_from_config() {
+        let mut config = AppConfig::default();
+        config.server.error_format = ErrorFormat::ProblemJson;
+        let (app, _handle) = RouterBuilder::new().with_config(config).build();
+
+        // The config endpoint is disabled by default and answers 404
+        let request = Request::builder()
+            .uri("/actuator/config")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            crate::core::error::problem::PROBLEM_JSON
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["type"], "/problems/not_found");
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn test_layers_around_auth_see_identity_only_after_auth() {
+        use crate::core::auth::middleware::EntraClaims;
+        use crate::core::config::app_config::{ProviderConfig, default_allowed_algorithms};
+        use std::sync::Mutex;
+
+        // Debug validation accepts any well-formed JWT and attaches debug claims
+        let mut config = AppConfig::default();
+        config.auth.enabled = true;
+        config.auth.debug = true;
+        config.auth.default_provider = "test-provider".to_string();
+        config.auth.providers.insert(
+            "test-provider".to_string(),
+            ProviderConfig {
+                enabled: true,
+                client_id: "test-client-id".to_string(),
+                jwks_uri: "https://test.jwks".to_string(),
+                issuer_url: "https://test.issuer".to_string(),
+                audience: "test-audience".to_string(),
+                role_mappings: HashMap::new(),
+                provider_specific: HashMap::new(),
+                allowed_algorithms: default_allowed_algorithms(),
+            },
+        );
+
+        // Records whether the request carried an identity when the layer saw it
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let observer = |name: &'static str, seen: Arc<Mutex<Vec<(&'static str, bool)>>>| {
+            axum::middleware::from_fn(
+                move |req: axum::extract::Request, next: axum::middleware::Next| {
+                    let seen = seen.clone();
+                    async move {
+                        let has_identity = req.extensions().get::<EntraClaims>().is_some();
+                        seen.lock().unwrap().push((name, has_identity));
+                        next.run(req).await
+                    }
+                },
+            )
+        };
+
+        let (app, _handle) = RouterBuilder::new()
+            .with_config(config)
+            .with_layer_before(LayerMarker::Auth, observer("before", seen.clone()))
+            .with_layer_after(LayerMarker::Auth, observer("after", seen.clone()))
+            .build();
+
+        // Without a token only the layer before auth runs
+        let request = Request::builder()
+            .uri("/actuator/info")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(*seen.lock().unwrap(), vec![("before", false)]);
+
+        seen.lock().unwrap().clear();
+
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "sub": "tester" }),
+            &jsonwebtoken::EncodingKey::from_secret(b"test"),
+        )
+        .unwrap();
+        let request = Request::builder()
+            .uri("/actuator/info")
+            .header("Authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![("before", false), ("after", true)]
+        );
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn test_configured_authorization_rules_apply_to_all_routes() {
+        use crate::core::config::app_config::{
+            AuthorizationRule, DefaultPolicy, ProviderConfig, default_allowed_algorithms,
+        };
+
+        // Debug validation attaches claims with the `admin` role
+        let mut config = AppConfig::default();
+        config.auth.enabled = true;
+        config.auth.debug = true;
+        config.auth.default_provider = "test-provider".to_string();
+        config.auth.providers.insert(
+            "test-provider".to_string(),
+            ProviderConfig {
+                enabled: true,
+                client_id: "test-client-id".to_string(),
+                jwks_uri: "https://test.jwks".to_string(),
+                issuer_url: "https://test.issuer".to_string(),
+                audience: "test-audience".to_string(),
+                role_mappings: HashMap::new(),
+                provider_specific: HashMap::new(),
+                allowed_algorithms: default_allowed_algorithms(),
+            },
+        );
+        config.auth.default_policy = DefaultPolicy::Deny;
+        config.auth.rules = vec![
+            AuthorizationRule {
+                method: Some("GET".to_string()),
+                path: "/actuator/info".to_string(),
+                roles: vec!["admin".to_string()],
+            },
+            AuthorizationRule {
+                method: None,
+                path: "/actuator/latency".to_string(),
+                roles: vec!["auditor".to_string()],
+            },
+            AuthorizationRule {
+                method: None,
+                path: "/api/pets".to_string(),
+                roles: vec!["admin".to_string()],
+            },
+        ];
+
+        let api = Router::new()
+            .route("/api/pets", get(|| async { "pets" }))
+            .route("/api/owners", get(|| async { "owners" }));
+        let (app, _handle) = RouterBuilder::new()
+            .with_config(config)
+            .with_routes(api)
+            .build();
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "sub": "tester" }),
+            &jsonwebtoken::EncodingKey::from_secret(b"test"),
+        )
+        .unwrap();
+        let get = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .header("Authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(get("/actuator/info")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The rule requires a role the caller lacks
+        let response = app.clone().oneshot(get("/actuator/latency")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // No rule covers the config endpoint, so default-deny blocks it
+        let response = app.clone().oneshot(get("/actuator/config")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Application routes are covered too; the policy authenticates the caller itself
+        let response = app.clone().oneshot(get("/api/pets")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let request = Request::builder()
+            .uri("/api/pets")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.clone().oneshot(get("/api/owners")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Health probes stay public under default-deny (readiness may report
+        // the unreachable test provider as down, but is not refused)
+        for uri in ["/health", "/health/readiness"] {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let status = app.clone().oneshot(request).await.unwrap().status();
+            assert_ne!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+            assert_ne!(status, StatusCode::FORBIDDEN, "{}", uri);
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn test_authorization_rules_match_rewritten_paths() {
+        use crate::core::config::app_config::{AuthorizationRule, PathNormalizationMode};
+
+        let mut config = AppConfig::default();
+        config.server.path_normalization.mode = PathNormalizationMode::Rewrite;
+        config.server.path_normalization.lowercase = true;
+        config.auth.rules = vec![AuthorizationRule {
+            method: None,
+            path: "/api/pets".to_string(),
+            roles: vec!["admin".to_string()],
+        }];
+
+        let api = Router::new().route("/api/pets", post(|| async { "created" }));
+        let (app, _handle) = RouterBuilder::new()
+            .with_config(config)
+            .with_routes(api)
+            .build();
+
+        // Routed to `/api/pets`, so the rule for it applies instead of the default
+        for uri in ["/api/pets", "/API/Pets", "/api/pets/"] {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn test_refresh_jwks_refreshes_providers_and_middleware_cache() {
+        use crate::core::config::app_config::{ProviderConfig, default_allowed_algorithms};
+        use wiremock::{Mock, MockServer, ResponseTemplate, matchers::path};
+
+        let idp = MockServer::start().await;
+        Mock::given(path("/keys"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "keys": []
+            })))
+            .mount(&idp)
+            .await;
+        let jwks_uri = format!("{}/keys", idp.uri());
+
+        // Debug validation attaches claims with the `admin` role
+        let mut config = AppConfig::default();
+        config.auth.enabled = true;
+        config.auth.debug = true;
+        config.auth.default_provider = "entra".to_string();
+        config.auth.providers.insert(
+            "entra".to_string(),
+            ProviderConfig {
+                enabled: true,
+                client_id: "test-client-id".to_string(),
+                jwks_uri: jwks_uri.clone(),
+                issuer_url: "https://test.issuer".to_string(),
+                audience: "test-audience".to_string(),
+                role_mappings: HashMap::new(),
+                provider_specific: HashMap::from([(
+                    "jwks_uri".to_string(),
+                    serde_json::json!(jwks_uri),
+                )]),
+                allowed_algorithms: default_allowed_algorithms(),
+            },
+        );
+
+        let (app, _handle) = RouterBuilder::new().with_config(config).build();
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "sub": "tester" }),
+            &jsonwebtoken::EncodingKey::from_secret(b"test"),
+        )
+        .unwrap();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/actuator/auth/refresh-jwks")
+            .header("Authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let outcomes: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(outcomes.get("entra").is_some());
+        assert_eq!(
+            outcomes["middleware"],
+            serde_json::json!({"refreshed": true})
+        );
+    }
+
     #[test]
     fn test_service_registry() {
         // Create a new service registry
@@ -274,4 +1113,34 @@ mod tests {
         assert!(state.metrics_handle.is_none());
         assert!(state.resource_registry.is_none());
     }
+
+    #[tokio::test]
+    async fn test_http_client_is_shared_and_configured() {
+        use wiremock::{Mock, MockServer, ResponseTemplate, matchers::path};
+
+        let mut config = AppConfig::default();
+        config.server.timeout_seconds = 1;
+        let state = AppState {
+            client: Some(build_client(&config)),
+            config,
+            ..AppState::default()
+        };
+        assert!(std::ptr::eq(state.http_client(), state.http_client()));
+
+        let fallback = AppState::default();
+        assert!(std::ptr::eq(fallback.http_client(), fallback.http_client()));
+
+        let server = MockServer::start().await;
+        Mock::given(path("/slow"))
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(3)))
+            .mount(&server)
+            .await;
+        let error = state
+            .http_client()
+            .get(format!("{}/slow", server.uri()))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(error.is_timeout());
+    }
 }
@@ -1,24 +1,35 @@
+#[cfg(feature = "auth")]
+use axum::Extension;
 use axum::{
     extract::State,
-    routing::{Router, get, post},
+    routing::{Router, any, get, post},
 };
 #[cfg(feature = "metrics")]
 use metrics_exporter_prometheus::PrometheusBuilder;
 use std::{sync::Arc, time::SystemTime};
+use tracing::warn;
 
+#[cfg(feature = "auth")]
+use crate::core::auth::credential_schemes::{CredentialSchemes, enforce_single_credential};
 #[cfg(feature = "auth")]
 use crate::core::auth::middleware::EntraAuthLayer;
+#[cfg(feature = "auth")]
+use crate::core::auth::providers::ProviderRegistry;
 use crate::core::handlers::health_dashboard_handler::{
     clear_dashboard_history, health_dashboard_handler, register_dynamic_indicator,
 };
 use crate::core::{
     config::app_config::AppConfig,
+    error::AppError,
     handlers::{
         self, core_actuator, core_docs,
-        core_health::{detailed_health_handler, health_handler},
+        core_health::{self, detailed_health_handler, health_handler},
     },
     models::{DetailedHealthResponse, HealthCheckResponse},
-    router::core_app_router::ServiceRegistry,
+    router::{
+        actuator_endpoints::{ActuatorEndpoint, actuator_index},
+        core_app_router::{LayerMarker, MiddlewareStack, ServiceRegistry},
+    },
 };
 
 use super::AppState;
@@ -29,47 +40,177 @@ pub struct CoreRouter;
 impl CoreRouter {
     /// Creates the core routes for the application
     pub fn create_core_routes(state: Arc<AppState>) -> Router {
+        Self::create_core_routes_with_middleware(state, &mut MiddlewareStack::new(), Vec::new())
+    }
+
+    /// Creates the core routes, applying the `Auth` slot of `middleware` to the protected routes
+    ///
+    /// `actuator_endpoints` are served under `/actuator` alongside the built-in endpoints.
+    pub fn create_core_routes_with_middleware(
+        state: Arc<AppState>,
+        middleware: &mut MiddlewareStack,
+        actuator_endpoints: Vec<ActuatorEndpoint>,
+    ) -> Router {
         // Get the auth enabled flag from config
         let auth_enabled = state.config.auth.enabled;
 
         // Create admin auth middleware only if auth is enabled
         #[cfg(feature = "auth")]
         let admin_auth = if auth_enabled {
-            Some(EntraAuthLayer::from_app_config_require_admin(&state.config))
+            Some(
+                EntraAuthLayer::from_app_config_require_admin(&state.config)
+                    .with_http_client(state.http_client().clone()),
+            )
         } else {
             None
         };
 
+        // Requests with both a bearer token and an API key are resolved before authenticating;
+        // an invalid API key header already fails config validation at startup
+        #[cfg(feature = "auth")]
+        let credential_schemes = auth_enabled
+            .then(|| CredentialSchemes::from_config(&state.config.auth))
+            .transpose()
+            .unwrap_or_else(|e| {
+                tracing::error!("Not resolving multiple credentials: {}", e);
+                None
+            });
+
+        // Providers and the admin layer's JWKS cache, for `/actuator/auth/refresh-jwks`;
+        // the providers are also reported by the health endpoints
+        #[cfg(feature = "auth")]
+        let provider_registry = auth_enabled
+            .then(|| {
+                ProviderRegistry::from_app_config_with_client(
+                    &state.config,
+                    state.http_client().clone(),
+                )
+            })
+            .transpose()
+            .unwrap_or_else(|e| {
+                tracing::error!("Auth providers unavailable for JWKS refresh: {}", e);
+                None
+            })
+            .map(Arc::new);
+        #[cfg(feature = "auth")]
+        let jwks_refresher = admin_auth.as_ref().map(EntraAuthLayer::jwks_refresher);
+
+        let readiness = get(core_health::readiness_handler);
+        let detailed_health = get(detailed_health_handler);
+        #[cfg(feature = "auth")]
+        let (readiness, detailed_health) = match &provider_registry {
+            Some(registry) => (
+                readiness.layer(Extension(registry.clone())),
+                detailed_health.layer(Extension(registry.clone())),
+            ),
+            None => (readiness, detailed_health),
+        };
+
         // Public core routes - accessible without authentication
-        let public_routes = Router::new().route("/health", get(health_handler));
+        let public_routes = Router::new()
+            .route("/health", get(health_handler))
+            .route("/health/readiness", readiness);
 
         // Create actuator routes
         let mut actuator_routes = Router::new();
 
         // Add all actuator routes
         actuator_routes = actuator_routes
-            .route("/health", get(detailed_health_handler))
+            .route("/health", detailed_health)
             .route("/info", get(core_actuator::info))
-            .route("/docs", get(core_docs::swagger_ui_handler))
-            .route("/docs/{*file}", get(core_docs::openapi_spec_handler))
+            .route("/config", get(core_actuator::config))
+            .route("/config/changes", get(core_actuator::config_changes))
+            .route("/latency", get(core_actuator::latency))
+            .route("/summary", get(core_actuator::summary))
+            .route(
+                "/loggers",
+                get(core_actuator::loggers)
+                    .post(core_actuator::update_logger)
+                    .delete(core_actuator::reset_loggers),
+            )
             // Add health dashboard routes
             .route("/dashboard", get(health_dashboard_handler))
             .route("/dashboard/history/clear", get(clear_dashboard_history))
             .route("/dashboard/register", post(register_dynamic_indicator));
 
-        // Apply authentication layers if enabled
-        #[cfg(feature = "auth")]
-        let actuator_routes = if auth_enabled {
-            actuator_routes.layer(admin_auth.unwrap())
+        // Docs answer 404 when `openapi.enabled` is off, or by default in production
+        let actuator_routes = if state.config.docs_enabled() {
+            actuator_routes
+                .route("/docs", get(core_docs::swagger_ui_handler))
+                .route("/docs/assets/{file}", get(core_docs::docs_asset_handler))
+                .route("/docs/{*file}", get(core_docs::openapi_spec_handler))
         } else {
+            let docs_disabled =
+                || async { AppError::NotFound("API docs are disabled".to_string()) };
             actuator_routes
+                .route("/docs", any(docs_disabled))
+                .route("/docs/{*file}", any(docs_disabled))
+        };
+
+        #[cfg(feature = "auth")]
+        let actuator_routes = {
+            let mut refresh_jwks = post(core_actuator::refresh_jwks);
+            if let Some(registry) = provider_registry {
+                refresh_jwks = refresh_jwks.layer(Extension(registry));
+            }
+            if let Some(refresher) = jwks_refresher {
+                refresh_jwks = refresh_jwks.layer(Extension(refresher));
+            }
+            actuator_routes.route("/auth/refresh-jwks", refresh_jwks)
         };
 
+        // Application endpoints, behind the same security as the built-in ones
+        let actuator_endpoints: Vec<ActuatorEndpoint> = actuator_endpoints
+            .into_iter()
+            .filter(|endpoint| {
+                if endpoint.is_builtin() {
+                    warn!(
+                        "Ignoring actuator endpoint '{}': it is a built-in endpoint",
+                        endpoint.name()
+                    );
+                }
+                !endpoint.is_builtin()
+            })
+            .collect();
+        let index = actuator_index(&state.config, &actuator_endpoints);
+        let mut actuator_routes =
+            actuator_routes.route("/", get(move || async move { axum::Json(index) }));
+        for endpoint in actuator_endpoints {
+            let path = format!("/{}", endpoint.name());
+            actuator_routes = if endpoint.is_exposed(&state.config) {
+                actuator_routes.route(&path, endpoint.into_route())
+            } else {
+                let message = format!("Actuator endpoint '{}' is disabled", endpoint.name());
+                actuator_routes.route(
+                    &path,
+                    any(move || async move { AppError::NotFound(message) }),
+                )
+            };
+        }
+
+        // Apply authentication layers if enabled, along with any custom layers around them
+        let actuator_routes: Router = actuator_routes.with_state(state);
+        let actuator_routes = middleware.apply_at(LayerMarker::Auth, actuator_routes, |routes| {
+            #[cfg(feature = "auth")]
+            let routes = match admin_auth {
+                Some(admin_auth) => routes.layer(admin_auth),
+                None => routes,
+            };
+            #[cfg(feature = "auth")]
+            let routes = match credential_schemes {
+                Some(schemes) => routes.layer(axum::middleware::from_fn_with_state(
+                    schemes,
+                    enforce_single_credential,
+                )),
+                None => routes,
+            };
+            routes
+        });
+
         // Return the final router with all routes
         Router::new()
             .merge(public_routes)
             .nest("/actuator", actuator_routes)
-            .with_state(state)
     }
 }
 
@@ -78,6 +219,7 @@ mod tests {
     use super::*;
     #[cfg(feature = "auth")]
     use crate::core::auth::EntraTokenClient;
+    use crate::core::config::app_config::EnvironmentType;
     use crate::core::utils::api_resource::ApiResourceRegistry;
     use crate::models::{DetailedHealthResponse, HealthCheckResponse};
 
@@ -99,7 +241,7 @@ mod tests {
         // Add a default provider and role mappings to avoid the "Default provider not found" error
         #[cfg(feature = "auth")]
         if auth_enabled {
-            use crate::core::config::app_config::ProviderConfig;
+            use crate::core::config::app_config::{ProviderConfig, default_allowed_algorithms};
             use std::collections::HashMap;
 
             // Create a default provider config manually
@@ -117,6 +259,7 @@ mod tests {
                     mappings
                 },
                 provider_specific: HashMap::new(),
+                allowed_algorithms: default_allowed_algorithms(),
             };
 
             // Add the provider to config
@@ -145,6 +288,7 @@ mod tests {
             token_client: None,
             resource_registry: None,
             service_registry: Arc::new(ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         })
     }
 
@@ -221,6 +365,107 @@ mod tests {
         assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
+    fn queue_depth_endpoint() -> ActuatorEndpoint {
+        ActuatorEndpoint::new(
+            "queue-depth",
+            get(|| async { axum::Json(serde_json::json!({ "depth": 3 })) }),
+        )
+    }
+
+    fn routes_with(state: Arc<AppState>, endpoint: ActuatorEndpoint) -> Router {
+        CoreRouter::create_core_routes_with_middleware(
+            state,
+            &mut MiddlewareStack::new(),
+            vec![endpoint],
+        )
+    }
+
+    async fn json_body(response: Response) -> serde_json::Value {
+        let body = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_custom_actuator_endpoint_is_listed_and_reachable() {
+        let router = routes_with(create_test_state(false), queue_depth_endpoint());
+
+        let response = send_request(router.clone(), "/actuator", Method::GET).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let links = json_body(response).await["_links"].clone();
+        assert_eq!(links["queue-depth"]["href"], "/actuator/queue-depth");
+        assert_eq!(links["health"]["href"], "/actuator/health");
+        // Disabled built-in endpoints are not listed
+        assert!(links.get("config").is_none());
+
+        let response = send_request(router, "/actuator/queue-depth", Method::GET).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["depth"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_custom_actuator_endpoint_follows_endpoint_security() {
+        let endpoint =
+            queue_depth_endpoint().exposed_when(|config| config.endpoint_security.public_metrics);
+        let router = routes_with(create_test_state(false), endpoint);
+
+        let response = send_request(router.clone(), "/actuator", Method::GET).await;
+        let links = json_body(response).await["_links"].clone();
+        assert!(links.get("queue-depth").is_none());
+
+        let response = send_request(router, "/actuator/queue-depth", Method::GET).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn test_custom_actuator_endpoint_requires_auth() {
+        let router = routes_with(create_test_state(true), queue_depth_endpoint());
+
+        let response = send_request(router, "/actuator/queue-depth", Method::GET).await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    fn state_with_docs(enabled: Option<bool>) -> Arc<AppState> {
+        let mut state = Arc::try_unwrap(create_test_state(false)).unwrap();
+        state.config.environment = EnvironmentType::Production;
+        state.config.openapi.enabled = enabled;
+        Arc::new(state)
+    }
+
+    #[tokio::test]
+    async fn test_docs_disabled_in_production_by_default() {
+        let router = CoreRouter::create_core_routes(state_with_docs(None));
+
+        for uri in [
+            "/actuator/docs",
+            "/actuator/docs/navius-swagger.yaml",
+            "/actuator/docs/assets/docs.css",
+        ] {
+            let response = send_request(router.clone(), uri, Method::GET).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+        }
+
+        // The API itself is unaffected
+        let response = send_request(router.clone(), "/health", Method::GET).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send_request(router, "/actuator/info", Method::GET).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_docs_served_when_enabled() {
+        let router = CoreRouter::create_core_routes(state_with_docs(Some(true)));
+
+        let response = send_request(router.clone(), "/actuator/docs", Method::GET).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let stylesheet = core_docs::docs_asset_url("docs.css").unwrap();
+        let response = send_request(router, &stylesheet, Method::GET).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
     #[tokio::test]
     async fn test_route_not_found() {
         // Create state with auth disabled
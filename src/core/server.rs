@@ -0,0 +1,372 @@
+//! HTTP server binding and connection handling
+//!
+//! Binds the listener and serves connections with the socket options and
+//! HTTP protocol versions from [`ServerTuning`]. `SO_REUSEADDR` and the
+//! listen backlog apply to the listening socket; TCP_NODELAY and keep-alive
+//! are applied to every accepted connection. Requests carry the peer
+//! address as [`ConnectInfo<SocketAddr>`](ConnectInfo).
+//!
+//! [`serve_with_drain`] stops accepting once its [`DrainSignal`] starts and
+//! returns when every open connection has finished its in-flight requests.
+//!
+//! An [`HttpServer`] names a router and the address it is served on, so one
+//! process can expose several (a public API and a localhost-only admin API)
+//! started and shut down together through
+//! [`AppHandle::start`](crate::core::router::AppHandle::start).
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::extract::ConnectInfo;
+use axum::{Extension, Router};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::service::TowerToHyperService;
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tower::Layer;
+use tracing::{debug, info, warn};
+
+use crate::core::config::app_config::ServerTuning;
+use crate::core::reliability::DrainSignal;
+
+/// First wait before retrying after a failed accept, doubled per failure
+const MIN_ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+/// Longest wait between accept retries
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// A named router and the address it is served on
+#[derive(Debug, Clone)]
+pub struct HttpServer {
+    name: String,
+    addr: SocketAddr,
+    router: Router,
+    tuning: ServerTuning,
+}
+
+impl HttpServer {
+    /// Server called `name` serving `router` on `addr`, with default tuning
+    pub fn new(name: impl Into<String>, addr: SocketAddr, router: Router) -> Self {
+        Self {
+            name: name.into(),
+            addr,
+            router,
+            tuning: ServerTuning::default(),
+        }
+    }
+
+    /// Set the socket and protocol options
+    pub fn with_tuning(mut self, tuning: ServerTuning) -> Self {
+        self.tuning = tuning;
+        self
+    }
+
+    /// Name used in logs
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Address the server binds to
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Bind the listener, returning it with the router and tuning to serve
+    pub fn bind(self) -> io::Result<(TcpListener, Router, ServerTuning)> {
+        let listener = bind(self.addr, &self.tuning)?;
+        Ok((listener, self.router, self.tuning))
+    }
+}
+
+/// Bind a listener on `addr` with the socket options from `tuning`
+pub fn bind(addr: SocketAddr, tuning: &ServerTuning) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_reuse_address(tuning.reuse_address)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(tuning.backlog.min(i32::MAX as u32) as i32)?;
+    TcpListener::from_std(socket.into())
+}
+
+/// Apply per-connection socket options from `tuning` to an accepted stream
+pub fn tune_stream(stream: &TcpStream, tuning: &ServerTuning) -> io::Result<()> {
+    stream.set_nodelay(tuning.tcp_nodelay)?;
+    let socket = SockRef::from(stream);
+    match tuning.tcp_keepalive_seconds {
+        Some(seconds) => socket
+            .set_tcp_keepalive(&TcpKeepalive::new().with_time(Duration::from_secs(seconds.max(1)))),
+        None => socket.set_keepalive(false),
+    }
+}
+
+/// HTTP connection builder honouring the `http1`/`http2` settings
+pub fn connection_builder(tuning: &ServerTuning) -> auto::Builder<TokioExecutor> {
+    let builder = auto::Builder::new(TokioExecutor::new());
+    match (tuning.http1, tuning.http2) {
+        (true, false) => builder.http1_only(),
+        (false, true) => builder.http2_only(),
+        _ => builder,
+    }
+}
+
+/// Serve `app` on `listener` until the listener fails
+pub async fn serve(listener: TcpListener, app: Router, tuning: ServerTuning) -> io::Result<()> {
+    serve_with_drain(listener, app, tuning, DrainSignal::new()).await
+}
+
+/// Serve `app` on `listener` until `drain` starts, then finish open connections
+///
+/// Once draining, no new connections are accepted and open connections are
+/// shut down gracefully: requests already in flight complete, idle
+/// keep-alive connections close.
+pub async fn serve_with_drain(
+    listener: TcpListener,
+    app: Router,
+    tuning: ServerTuning,
+    drain: DrainSignal,
+) -> io::Result<()> {
+    let builder = connection_builder(&tuning);
+    let mut connections = JoinSet::new();
+    let mut accept_backoff = MIN_ACCEPT_BACKOFF;
+
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = drain.wait() => break,
+        };
+        let (stream, remote_addr) = match accepted {
+            Ok(accepted) => {
+                accept_backoff = MIN_ACCEPT_BACKOFF;
+                accepted
+            }
+            Err(e) => {
+                // Per-connection failures (e.g. the peer reset) must not stop the
+                // server, but errors like EMFILE persist, so wait before retrying
+                warn!(
+                    "Failed to accept connection, retrying in {:?}: {}",
+                    accept_backoff, e
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(accept_backoff) => {}
+                    _ = drain.wait() => break,
+                }
+                accept_backoff = (accept_backoff * 2).min(MAX_ACCEPT_BACKOFF);
+                continue;
+            }
+        };
+        if let Err(e) = tune_stream(&stream, &tuning) {
+            warn!("Failed to tune connection from {}: {}", remote_addr, e);
+        }
+
+        let builder = builder.clone();
+        // Expose the peer address to handlers and layers as `ConnectInfo`
+        let service =
+            TowerToHyperService::new(Extension(ConnectInfo(remote_addr)).layer(app.clone()));
+        let drain = drain.clone();
+        connections.spawn(async move {
+            let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+            tokio::pin!(connection);
+
+            let result = tokio::select! {
+                result = connection.as_mut() => result,
+                _ = drain.wait() => {
+                    connection.as_mut().graceful_shutdown();
+                    connection.await
+                }
+            };
+            if let Err(e) = result {
+                debug!("Connection from {} closed with error: {}", remote_addr, e);
+            }
+        });
+
+        // Reap finished connections so the set doesn't grow unbounded
+        while connections.try_join_next().is_some() {}
+    }
+
+    info!("Draining {} open connections", connections.len());
+    while connections.join_next().await.is_some() {}
+    Ok(())
+}
+
+/// Resolves on Ctrl+C or, on Unix, SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    fn local_addr() -> SocketAddr {
+        "127.0.0.1:0".parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_socket_options_are_applied() {
+        let tuning = ServerTuning {
+            tcp_keepalive_seconds: Some(30),
+            ..Default::default()
+        };
+        let listener = bind(local_addr(), &tuning).unwrap();
+        assert!(SockRef::from(&listener).reuse_address().unwrap());
+
+        let client = TcpStream::connect(listener.local_addr().unwrap());
+        let ((accepted, _), _client) =
+            tokio::join!(async { listener.accept().await.unwrap() }, async {
+                client.await.unwrap()
+            });
+        tune_stream(&accepted, &tuning).unwrap();
+
+        let socket = SockRef::from(&accepted);
+        assert!(accepted.nodelay().unwrap());
+        assert!(socket.keepalive().unwrap());
+        #[cfg(target_os = "linux")]
+        assert_eq!(socket.keepalive_time().unwrap(), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_options_are_applied() {
+        let tuning = ServerTuning {
+            tcp_nodelay: false,
+            tcp_keepalive_seconds: None,
+            reuse_address: false,
+            ..Default::default()
+        };
+        let listener = bind(local_addr(), &tuning).unwrap();
+        assert!(!SockRef::from(&listener).reuse_address().unwrap());
+
+        let client = TcpStream::connect(listener.local_addr().unwrap());
+        let ((accepted, _), _client) =
+            tokio::join!(async { listener.accept().await.unwrap() }, async {
+                client.await.unwrap()
+            });
+        tune_stream(&accepted, &tuning).unwrap();
+
+        assert!(!accepted.nodelay().unwrap());
+        assert!(!SockRef::from(&accepted).keepalive().unwrap());
+    }
+
+    async fn start(tuning: ServerTuning) -> SocketAddr {
+        let listener = bind(local_addr(), &tuning).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/health", get(|| async { "ok" }));
+        tokio::spawn(serve(listener, app, tuning));
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_requests_carry_peer_address() {
+        let tuning = ServerTuning::default();
+        let listener = bind(local_addr(), &tuning).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route(
+            "/peer",
+            get(|ConnectInfo(peer): ConnectInfo<SocketAddr>| async move { peer.ip().to_string() }),
+        );
+        tokio::spawn(serve(listener, app, tuning));
+
+        let body = reqwest::get(format!("http://{}/peer", addr))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "127.0.0.1");
+    }
+
+    #[tokio::test]
+    async fn test_drain_finishes_in_flight_requests() {
+        let tuning = ServerTuning::default();
+        let listener = bind(local_addr(), &tuning).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                "done"
+            }),
+        );
+        let drain = DrainSignal::new();
+        let server = tokio::spawn(serve_with_drain(listener, app, tuning, drain.clone()));
+
+        let in_flight = tokio::spawn(reqwest::get(format!("http://{}/slow", addr)));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        drain.start();
+
+        let response = in_flight.await.unwrap().unwrap();
+        assert_eq!(response.text().await.unwrap(), "done");
+        tokio::time::timeout(Duration::from_secs(2), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+
+    async fn http2_request(addr: SocketAddr) -> reqwest::Result<reqwest::Response> {
+        reqwest::Client::builder()
+            .http2_prior_knowledge()
+            .build()
+            .unwrap()
+            .get(format!("http://{}/health", addr))
+            .send()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_http2_enabled() {
+        let addr = start(ServerTuning::default()).await;
+
+        let response = http2_request(addr).await.unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_2);
+
+        let response = reqwest::get(format!("http://{}/health", addr))
+            .await
+            .unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_11);
+    }
+
+    #[tokio::test]
+    async fn test_http2_disabled() {
+        let addr = start(ServerTuning {
+            http2: false,
+            ..Default::default()
+        })
+        .await;
+
+        assert!(http2_request(addr).await.is_err());
+
+        let response = reqwest::get(format!("http://{}/health", addr))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
+}
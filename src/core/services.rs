@@ -1,32 +1,59 @@
 // Core service module exports
+pub mod cache_health;
 pub mod cache_provider;
 pub mod cache_service;
+pub mod cached_repository;
+pub mod cancellation;
+pub mod connection_string;
 pub mod database_interface;
 pub mod database_service;
+pub mod downstream_health;
 pub mod error;
+pub mod event_sink;
+pub mod fallback_cache;
 pub mod health;
+pub mod health_aggregate;
+pub mod health_cache;
 pub mod health_dashboard;
 pub mod health_discovery;
 pub mod health_indicators;
 pub mod health_provider;
+pub mod idempotency;
+pub mod long_poll;
 pub mod memory_cache;
 pub mod memory_database;
 pub mod memory_repository;
+pub mod migrations;
+pub mod observed_repository;
+pub mod outbox;
+#[cfg(feature = "postgres")]
+pub mod postgres_pool;
+pub mod pubsub;
 pub mod redis_cache;
 pub mod repository_service;
 pub mod service_traits;
+pub mod transaction;
 
 // Re-export key components
+pub use cache_health::BackendHealth;
 pub use cache_provider::{
     CacheConfig, CacheError, CacheOperations, CacheProvider, CacheProviderRegistry, CacheStats,
     EvictionPolicy,
 };
 pub use cache_service::{CacheHelpers, CacheService};
+pub use cached_repository::{CacheMode, CachedRepository};
+pub use cancellation::{RequestCancellation, cancel_on_disconnect, run_until_cancelled};
+pub use connection_string::{ConnectionStringError, PostgresConnection, SslMode};
 pub use database_interface::{
-    DatabaseConfig, DatabaseOperations, DatabaseProvider, DatabaseProviderRegistry,
+    BatchWrite, DatabaseConfig, DatabaseOperations, DatabaseProvider, DatabaseProviderRegistry,
 };
 pub use database_service::{DatabaseService, InMemoryDatabaseServiceProvider};
+pub use downstream_health::DownstreamHealthCheck;
+pub use event_sink::{BoundedEventSink, EventReceiver, OverflowPolicy, TrySendError};
+pub use fallback_cache::FallbackCache;
 pub use health::HealthService;
+pub use health_aggregate::{ComponentHealth, HealthAggregate, HealthState};
+pub use health_cache::HealthResultCache;
 pub use health_dashboard::{
     HealthDashboardConfig, HealthDashboardService, HealthStatusHistoryEntry,
 };
@@ -42,11 +69,20 @@ pub use health_provider::{
     HealthConfig, HealthIndicator, HealthIndicatorProvider, HealthIndicatorProviderRegistry,
     HealthServiceV2,
 };
+pub use idempotency::{IdempotencyRecord, IdempotencyStore, Idempotent};
+pub use long_poll::{LongPoll, long_poll};
 pub use memory_cache::InMemoryCacheProvider;
 pub use memory_database::{InMemoryDatabase, InMemoryDatabaseProvider};
 pub use memory_repository::{
     InMemoryRepository, InMemoryRepositoryProvider, register_memory_repository_provider,
 };
+pub use observed_repository::{ChangeKind, ChangeListener, EntityChange, ObservedRepository};
+pub use outbox::{EventPublisher, Outbox, OutboxEvent};
+pub use pubsub::{CacheInvalidation, MemoryPubSub, PubSub, PubSubBackend};
 pub use redis_cache::RedisCacheProvider;
 pub use repository_service::{GenericRepository, RepositoryService};
 pub use service_traits::{Lifecycle, Service, ServiceProvider, ServiceRegistry};
+pub use transaction::{
+    RequestTransaction, Transaction, TransactionalRepository, with_request_transaction,
+    with_transaction,
+};
@@ -0,0 +1,177 @@
+//! Connection health of cache backends
+//!
+//! Hit ratios say nothing about whether Redis is reachable. A
+//! [`BackendHealth`] per backend tier tracks that separately: every
+//! operation run through [`BackendHealth::track`] is timed into
+//! `cache_op_duration` (seconds), and connection or timeout errors flip
+//! `cache_backend_up` to 0 until an operation succeeds again, which counts
+//! as a reconnect. Both metrics are labelled by `cache` and `tier`.
+//!
+//! [`BackendHealth::add_to`] copies the state into
+//! [`CacheStats::custom_metrics`] as `<tier>.up`, `<tier>.op_latency_ms` and
+//! `<tier>.reconnects`.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use metrics::{gauge, histogram};
+use tracing::{info, warn};
+
+use crate::core::services::cache_provider::{CacheError, CacheStats};
+
+/// Up/down state, latency and reconnects of one cache backend tier
+#[derive(Debug)]
+pub struct BackendHealth {
+    cache: String,
+    tier: String,
+    up: AtomicBool,
+    reconnects: AtomicU64,
+    last_latency_us: AtomicU64,
+}
+
+impl BackendHealth {
+    /// Health of `tier` of `cache`, initially up
+    pub fn new(cache: impl Into<String>, tier: impl Into<String>) -> Self {
+        let health = Self {
+            cache: cache.into(),
+            tier: tier.into(),
+            up: AtomicBool::new(true),
+            reconnects: AtomicU64::new(0),
+            last_latency_us: AtomicU64::new(0),
+        };
+        health.record_up(true);
+        health
+    }
+
+    /// The tier name used in metric labels
+    pub fn tier(&self) -> &str {
+        &self.tier
+    }
+
+    /// Whether the last operation reached the backend
+    pub fn is_up(&self) -> bool {
+        self.up.load(Ordering::Relaxed)
+    }
+
+    /// Times the backend came back after being down
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Duration of the most recent operation
+    pub fn last_latency(&self) -> Duration {
+        Duration::from_micros(self.last_latency_us.load(Ordering::Relaxed))
+    }
+
+    /// Run `operation` against the backend, recording its latency and outcome
+    pub async fn track<T, F>(&self, operation: F) -> Result<T, CacheError>
+    where
+        F: Future<Output = Result<T, CacheError>>,
+    {
+        let started = Instant::now();
+        let result = operation.await;
+        self.observe(&result, started.elapsed());
+        result
+    }
+
+    /// Record an operation that took `elapsed` and ended with `result`
+    pub fn observe<T>(&self, result: &Result<T, CacheError>, elapsed: Duration) {
+        self.last_latency_us
+            .store(elapsed.as_micros() as u64, Ordering::Relaxed);
+        histogram!(
+            "cache_op_duration",
+            "cache" => self.cache.clone(),
+            "tier" => self.tier.clone()
+        )
+        .record(elapsed.as_secs_f64());
+
+        // Only losing the connection marks the backend down; a missing key or
+        // a bad value says nothing about reachability
+        let reachable = !matches!(
+            result,
+            Err(CacheError::Connection(_)) | Err(CacheError::Timeout(_))
+        );
+        let was_up = self.up.swap(reachable, Ordering::Relaxed);
+        match (was_up, reachable) {
+            (true, false) => warn!("Cache {} lost its {} backend", self.cache, self.tier),
+            (false, true) => {
+                self.reconnects.fetch_add(1, Ordering::Relaxed);
+                info!(
+                    "Cache {} reconnected to its {} backend",
+                    self.cache, self.tier
+                );
+            }
+            _ => {}
+        }
+        self.record_up(reachable);
+    }
+
+    /// Add this tier's state to `stats.custom_metrics`
+    pub fn add_to(&self, stats: &mut CacheStats) {
+        let metrics = &mut stats.custom_metrics;
+        metrics.insert(
+            format!("{}.up", self.tier),
+            u8::from(self.is_up()).to_string(),
+        );
+        metrics.insert(
+            format!("{}.op_latency_ms", self.tier),
+            format!("{:.3}", self.last_latency().as_secs_f64() * 1000.0),
+        );
+        metrics.insert(
+            format!("{}.reconnects", self.tier),
+            self.reconnects().to_string(),
+        );
+    }
+
+    fn record_up(&self, up: bool) {
+        gauge!(
+            "cache_backend_up",
+            "cache" => self.cache.clone(),
+            "tier" => self.tier.clone()
+        )
+        .set(if up { 1.0 } else { 0.0 });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn empty_stats() -> CacheStats {
+        CacheStats {
+            size: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+            capacity: None,
+            custom_metrics: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_errors_mark_backend_down_until_reconnect() {
+        let health = BackendHealth::new("sessions", "redis");
+
+        let missing: Result<(), CacheError> = Err(CacheError::NotFound("key".to_string()));
+        health.observe(&missing, Duration::from_millis(2));
+        assert!(health.is_up());
+
+        let result = health
+            .track(async { Err::<(), _>(CacheError::Connection("refused".to_string())) })
+            .await;
+        assert!(result.is_err());
+        assert!(!health.is_up());
+
+        health.track(async { Ok(()) }).await.unwrap();
+        assert!(health.is_up());
+        assert_eq!(health.reconnects(), 1);
+
+        let mut stats = empty_stats();
+        health.add_to(&mut stats);
+        assert_eq!(stats.custom_metrics["redis.up"], "1");
+        assert_eq!(stats.custom_metrics["redis.reconnects"], "1");
+        assert!(stats.custom_metrics.contains_key("redis.op_latency_ms"));
+    }
+}
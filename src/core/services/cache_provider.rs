@@ -5,9 +5,12 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use bincode::{Decode, Encode};
+use metrics::counter;
 use serde::{Serialize, de::DeserializeOwned};
+use tracing::warn;
 
 use crate::core::services::error::ServiceError;
+use crate::core::services::fallback_cache::{DEFAULT_PRIMARY_RETRY_INTERVAL, FallbackCache};
 
 /// Cache statistics
 #[derive(Debug, Clone)]
@@ -88,6 +91,14 @@ pub enum CacheError {
     #[error("Connection error: {0}")]
     Connection(String),
 
+    /// A cache or entry that was required does not exist
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    /// The backend did not respond in time
+    #[error("Cache operation timed out: {0}")]
+    Timeout(String),
+
     /// Capacity error (cache full)
     #[error("Cache capacity reached: {0}")]
     Capacity(String),
@@ -118,11 +129,36 @@ impl From<CacheError> for ServiceError {
         match error {
             CacheError::Configuration(msg) => ServiceError::configuration_error(msg),
             CacheError::Connection(msg) => ServiceError::unavailable(msg),
+            CacheError::NotFound(msg) => ServiceError::not_found(msg),
+            CacheError::Timeout(msg) => ServiceError::timeout(msg),
             _ => ServiceError::other(error.to_string()),
         }
     }
 }
 
+/// Decode a cached value, treating one that no longer decodes as a miss
+///
+/// Entries written before a type changed shape fail to decode; reads fall
+/// through to the source instead of failing. Any other error is returned.
+pub fn decode_or_miss<T: Decode<()>>(
+    cache: &str,
+    key: &str,
+    bytes: &[u8],
+) -> Result<Option<T>, CacheError> {
+    match bincode::decode_from_slice::<T, _>(bytes, bincode::config::standard()) {
+        Ok((value, _)) => Ok(Some(value)),
+        Err(e) => {
+            warn!(
+                "Treating undecodable entry {} in cache {} as a miss: {}",
+                key, cache, e
+            );
+            counter!("cache_deserialization_errors_total", "cache" => cache.to_string())
+                .increment(1);
+            Ok(None)
+        }
+    }
+}
+
 /// TypedCache trait for type-specific cache operations
 #[async_trait]
 pub trait TypedCache<T>: Send + Sync + 'static
@@ -144,6 +180,19 @@ where
 
     /// Get multiple values from the cache
     async fn get_many(&self, keys: &[&str]) -> Result<HashMap<String, Option<T>>, CacheError>;
+
+    /// Set a value tagged with `tags`, for [`DynCacheOperations::invalidate_tag`]
+    async fn set_with_tags(
+        &self,
+        _key: &str,
+        _value: T,
+        _ttl: Option<Duration>,
+        _tags: &[&str],
+    ) -> Result<(), CacheError> {
+        Err(CacheError::Operation(
+            "Cache does not support tagged entries".to_string(),
+        ))
+    }
 }
 
 /// Helper trait to create TypedCache instances for a specific type
@@ -182,6 +231,24 @@ pub trait DynCacheOperations: Send + Sync + 'static {
     /// Increment a counter
     async fn increment(&self, key: &str, delta: i64) -> Result<i64, CacheError>;
 
+    /// Time left before `key` expires
+    ///
+    /// `None` if the key doesn't exist or never expires.
+    async fn ttl(&self, key: &str) -> Result<Option<Duration>, CacheError>;
+
+    /// Make `key` expire `ttl_seconds` from now, returning whether it existed
+    async fn touch(&self, key: &str, ttl_seconds: u64) -> Result<bool, CacheError>;
+
+    /// Delete every entry set with `tag`, returning how many were removed
+    ///
+    /// Entries overwritten without the tag since are kept.
+    async fn invalidate_tag(&self, _tag: &str) -> Result<usize, CacheError> {
+        Err(CacheError::Operation(format!(
+            "Cache {} does not support tagged entries",
+            self.name()
+        )))
+    }
+
     /// Get cache statistics
     fn stats(&self) -> Result<CacheStats, CacheError>;
 
@@ -260,9 +327,41 @@ impl CacheProviderRegistry {
     }
 
     /// Create a cache with the given configuration
+    ///
+    /// When `provider_config` names a `fallback` provider, the cache is a
+    /// [`FallbackCache`] that switches to a cache from that provider while
+    /// the configured one is unreachable. `fallback_retry_ms` sets how often
+    /// the primary is probed again.
     pub async fn create_cache(
         &self,
         config: CacheConfig,
+    ) -> Result<Box<dyn DynCacheOperations>, CacheError> {
+        let Some(fallback_provider) = config.provider_config.get("fallback").cloned() else {
+            return self.create_single_cache(config).await;
+        };
+        let retry_interval = match config.provider_config.get("fallback_retry_ms") {
+            Some(ms) => Duration::from_millis(ms.parse().map_err(|_| {
+                CacheError::Configuration(format!("Invalid fallback_retry_ms: {}", ms))
+            })?),
+            None => DEFAULT_PRIMARY_RETRY_INTERVAL,
+        };
+        let fallback_config = CacheConfig {
+            name: format!("{}-fallback", config.name),
+            provider: fallback_provider,
+            provider_config: HashMap::new(),
+            ..config.clone()
+        };
+
+        let primary = self.create_single_cache(config).await?;
+        let fallback = self.create_single_cache(fallback_config).await?;
+        Ok(Box::new(
+            FallbackCache::new(primary, fallback).with_retry_interval(retry_interval),
+        ))
+    }
+
+    async fn create_single_cache(
+        &self,
+        config: CacheConfig,
     ) -> Result<Box<dyn DynCacheOperations>, CacheError> {
         // Find a provider that supports this configuration
         if let Some(provider) = self.get(&config.provider) {
@@ -385,6 +484,14 @@ mod tests {
             Ok(1)
         }
 
+        async fn ttl(&self, _key: &str) -> Result<Option<Duration>, CacheError> {
+            Ok(None)
+        }
+
+        async fn touch(&self, _key: &str, _ttl_seconds: u64) -> Result<bool, CacheError> {
+            Ok(false)
+        }
+
         fn stats(&self) -> Result<CacheStats, CacheError> {
             Ok(CacheStats {
                 size: 0,
@@ -471,6 +578,86 @@ mod tests {
         assert_eq!(cache.config().name, config.name);
     }
 
+    #[test]
+    fn test_undecodable_value_is_a_miss() {
+        let value: Option<String> = decode_or_miss("test", "key", &[0xFF]).unwrap();
+        assert_eq!(value, None);
+
+        let bytes = bincode::encode_to_vec(7u8, bincode::config::standard()).unwrap();
+        let value: Option<u8> = decode_or_miss("test", "key", &bytes).unwrap();
+        assert_eq!(value, Some(7));
+    }
+
+    /// Remote cache storing encoded values, reachable only while `connected`
+    struct RemoteTypedCache {
+        connected: bool,
+        stored: Vec<u8>,
+    }
+
+    #[async_trait]
+    impl TypedCache<String> for RemoteTypedCache {
+        async fn get(&self, key: &str) -> Result<Option<String>, CacheError> {
+            if !self.connected {
+                return Err(CacheError::Connection("connection refused".to_string()));
+            }
+            decode_or_miss("remote", key, &self.stored)
+        }
+
+        async fn set(
+            &self,
+            _key: &str,
+            _value: String,
+            _ttl: Option<Duration>,
+        ) -> Result<(), CacheError> {
+            Ok(())
+        }
+
+        async fn get_many(
+            &self,
+            _keys: &[&str],
+        ) -> Result<HashMap<String, Option<String>>, CacheError> {
+            Ok(HashMap::new())
+        }
+
+        async fn set_many(
+            &self,
+            _items: HashMap<String, String>,
+            _ttl: Option<Duration>,
+        ) -> Result<(), CacheError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_errors_surface() {
+        let stale = RemoteTypedCache {
+            connected: true,
+            stored: vec![0xFF],
+        };
+        assert_eq!(stale.get("key").await.unwrap(), None);
+
+        let disconnected = RemoteTypedCache {
+            connected: false,
+            stored: vec![0xFF],
+        };
+        assert!(matches!(
+            disconnected.get("key").await,
+            Err(CacheError::Connection(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_errors_map_to_service_errors() {
+        let error: ServiceError = CacheError::Connection("refused".to_string()).into();
+        assert!(matches!(error, ServiceError::Unavailable(_)));
+
+        let error: ServiceError = CacheError::Timeout("slow".to_string()).into();
+        assert!(matches!(error, ServiceError::Timeout(_)));
+
+        let error: ServiceError = CacheError::NotFound("pets".to_string()).into();
+        assert!(matches!(error, ServiceError::NotFound(_)));
+    }
+
     #[tokio::test]
     async fn test_cache_provider_not_found() {
         // Create registry without providers
@@ -4,9 +4,13 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use bincode::{Decode, Encode};
+use futures::StreamExt;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
-use tracing::{error, info};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 
 use crate::core::services::cache_provider::{
     CacheConfig, CacheError, CacheFactory, CacheOperations, CacheProvider, CacheProviderRegistry,
@@ -14,6 +18,7 @@ use crate::core::services::cache_provider::{
 };
 use crate::core::services::error::ServiceError;
 use crate::core::services::memory_cache::ClonableDynCacheOperations;
+use crate::core::services::pubsub::{CACHE_INVALIDATION_CHANNEL, CacheInvalidation, PubSub};
 
 /// Service for managing caches using provider registry
 pub struct CacheService {
@@ -21,6 +26,12 @@ pub struct CacheService {
     provider_registry: Arc<RwLock<CacheProviderRegistry>>,
     /// Cache instances
     caches: Arc<RwLock<HashMap<String, Box<dyn DynCacheOperations>>>>,
+    /// Channel for broadcasting invalidations to other instances
+    invalidations: Option<PubSub>,
+    /// Identifies this instance's own invalidation broadcasts
+    instance_id: String,
+    /// Invalidations from other instances, once applied locally
+    applied: broadcast::Sender<CacheInvalidation>,
 }
 
 impl CacheService {
@@ -29,9 +40,20 @@ impl CacheService {
         Self {
             provider_registry,
             caches: Arc::new(RwLock::new(HashMap::new())),
+            invalidations: None,
+            instance_id: Uuid::new_v4().to_string(),
+            applied: broadcast::channel(16).0,
         }
     }
 
+    /// Broadcast `clear_cache`/`delete_key` to other instances over `pubsub`
+    ///
+    /// Call [`CacheService::listen_for_invalidations`] to apply theirs here.
+    pub fn with_invalidation_broadcast(mut self, pubsub: PubSub) -> Self {
+        self.invalidations = Some(pubsub);
+        self
+    }
+
     /// Get or create a cache with the specified configuration
     pub async fn get_cache(
         &self,
@@ -105,9 +127,87 @@ impl CacheService {
     pub async fn clear_cache(&self, name: &str) -> Result<(), CacheError> {
         if let Some(cache) = self.get_existing_cache(name) {
             cache.clear().await?;
+            self.broadcast_invalidation(name, None).await;
             Ok(())
         } else {
-            Err(CacheError::Key(format!("Cache {} not found", name)))
+            Err(CacheError::NotFound(format!("Cache {}", name)))
+        }
+    }
+
+    /// Delete a key from a specific cache
+    pub async fn delete_key(&self, name: &str, key: &str) -> Result<bool, CacheError> {
+        let cache = self
+            .get_existing_cache(name)
+            .ok_or_else(|| CacheError::NotFound(format!("Cache {}", name)))?;
+        let deleted = cache.delete(key).await?;
+        self.broadcast_invalidation(name, Some(key)).await;
+        Ok(deleted)
+    }
+
+    /// Apply invalidations broadcast by other instances to the local caches
+    pub async fn listen_for_invalidations(&self) -> Result<JoinHandle<()>, CacheError> {
+        let pubsub = self.invalidations.as_ref().ok_or_else(|| {
+            CacheError::Configuration("Invalidation broadcast is not configured".to_string())
+        })?;
+        let mut invalidations = pubsub
+            .subscribe::<CacheInvalidation>(CACHE_INVALIDATION_CHANNEL)
+            .await?;
+        let caches = Arc::clone(&self.caches);
+        let instance_id = self.instance_id.clone();
+        let applied = self.applied.clone();
+
+        Ok(tokio::spawn(async move {
+            while let Some(invalidation) = invalidations.next().await {
+                if invalidation.origin == instance_id {
+                    continue;
+                }
+                let cache = caches.read().unwrap().get(&invalidation.cache).cloned();
+                let Some(cache) = cache else {
+                    continue;
+                };
+
+                let result = match &invalidation.key {
+                    Some(key) => cache.delete(key).await.map(|_| ()),
+                    None => cache.clear().await,
+                };
+                match result {
+                    Ok(()) => {
+                        debug!(
+                            "Applied invalidation of {} from {}",
+                            invalidation.cache, invalidation.origin
+                        );
+                        // Nobody may be watching
+                        let _ = applied.send(invalidation);
+                    }
+                    Err(e) => warn!(
+                        "Failed to apply invalidation of {}: {}",
+                        invalidation.cache, e
+                    ),
+                }
+            }
+        }))
+    }
+
+    /// Invalidations from other instances, received as each is applied here
+    pub fn applied_invalidations(&self) -> broadcast::Receiver<CacheInvalidation> {
+        self.applied.subscribe()
+    }
+
+    async fn broadcast_invalidation(&self, name: &str, key: Option<&str>) {
+        let Some(pubsub) = &self.invalidations else {
+            return;
+        };
+        let invalidation = CacheInvalidation {
+            origin: self.instance_id.clone(),
+            cache: name.to_string(),
+            key: key.map(str::to_string),
+        };
+        // The local change stands even if other instances can't be told
+        if let Err(e) = pubsub
+            .publish(CACHE_INVALIDATION_CHANNEL, &invalidation)
+            .await
+        {
+            warn!("Failed to broadcast invalidation of {}: {}", name, e);
         }
     }
 
@@ -180,6 +280,12 @@ where
         return Some(redis_cache.for_type::<T>());
     }
 
+    if let Some(fallback_cache) =
+        any.downcast_ref::<crate::core::services::fallback_cache::FallbackCache>()
+    {
+        return Some(fallback_cache.for_type::<T>());
+    }
+
     // Add more cache types here as needed
 
     None
@@ -349,4 +455,49 @@ mod tests {
         let value: Option<TestData> = cache.get("key1").await.unwrap();
         assert_eq!(value, None);
     }
+
+    async fn memory_service(pubsub: &PubSub) -> CacheService {
+        let registry = Arc::new(RwLock::new(CacheProviderRegistry::new()));
+        registry
+            .write()
+            .unwrap()
+            .register(InMemoryCacheProvider::new());
+        let service = CacheService::new(registry).with_invalidation_broadcast(pubsub.clone());
+        service
+            .get_cache(CacheConfig {
+                name: "pets".to_string(),
+                provider: "memory".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_invalidations_propagate_across_instances() {
+        let pubsub = PubSub::in_memory();
+        let first = memory_service(&pubsub).await;
+        let second = memory_service(&pubsub).await;
+        let _listener = second.listen_for_invalidations().await.unwrap();
+        let mut applied = second.applied_invalidations();
+
+        let remote = second.get_existing_cache("pets").unwrap();
+        for key in ["rex", "tom"] {
+            let data = TestData {
+                id: 1,
+                value: key.to_string(),
+            };
+            remote.set(key, data, None).await.unwrap();
+        }
+
+        first.delete_key("pets", "rex").await.unwrap();
+        applied.recv().await.unwrap();
+        assert!(!remote.exists("rex").await.unwrap());
+        assert!(remote.exists("tom").await.unwrap());
+
+        first.clear_cache("pets").await.unwrap();
+        applied.recv().await.unwrap();
+        assert!(!remote.exists("tom").await.unwrap());
+    }
 }
@@ -0,0 +1,673 @@
+//! Caching decorator for repositories
+//!
+//! [`CachedRepository`] wraps any [`Repository`] with a read-through cache and
+//! one of two write modes:
+//!
+//! - [`CacheMode::WriteThrough`] writes to the repository, then the cache.
+//! - [`CacheMode::WriteBehind`] writes to the cache immediately and flushes
+//!   to the repository in batches, either when `batch_size` writes are
+//!   pending or every `flush_interval`. Pending writes are flushed on
+//!   [`Lifecycle::shutdown`], which
+//!   [`CachedRepository::flush_on_close`] runs when the application shuts
+//!   down, or in the background when the repository is dropped; writes
+//!   still pending when the process dies are lost, so only use it where
+//!   that is acceptable. `save` returns the entity as given, before the
+//!   repository has seen it, so entities whose repository assigns ids or
+//!   versions on save should use write-through.
+//!
+//! A read that loads an entity while a write to the repository completes
+//! does not cache what it loaded, so a concurrent delete is never undone.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use moka::future::Cache;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use crate::core::models::{Entity, Repository};
+use crate::core::router::app_handle::AppHandle;
+use crate::core::services::error::ServiceError;
+use crate::core::services::{Lifecycle, Service};
+
+/// Maximum number of entities kept in the read cache
+const DEFAULT_CACHE_CAPACITY: u64 = 10_000;
+
+/// Shortest write-behind flush interval; shorter ones are raised to it
+const MIN_FLUSH_INTERVAL: Duration = Duration::from_millis(1);
+
+/// How writes reach the underlying repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// Write to the repository synchronously, then update the cache
+    #[default]
+    WriteThrough,
+    /// Write to the cache and flush to the repository asynchronously in batches
+    ///
+    /// Only for entities whose id (and version, if any) the caller sets:
+    /// what the repository assigns when the write is flushed never reaches
+    /// the caller or the cache.
+    WriteBehind {
+        batch_size: usize,
+        flush_interval: Duration,
+    },
+}
+
+/// A write waiting to be flushed to the repository
+#[derive(Debug, Clone)]
+enum PendingWrite<E: Entity> {
+    Save(E),
+    Delete(E::Id),
+}
+
+struct Shared<E: Entity> {
+    inner: Arc<dyn Repository<E>>,
+    cache: Cache<String, E>,
+    pending: Mutex<Vec<(String, PendingWrite<E>)>>,
+    /// Serializes flushes so batches reach the repository in order
+    flush_lock: tokio::sync::Mutex<()>,
+    /// Bumped whenever a write reaches the repository
+    version: AtomicU64,
+}
+
+impl<E: Entity> Shared<E> {
+    fn pending_len(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Queue a write, replacing an older pending write for the same key
+    fn enqueue(&self, key: String, write: PendingWrite<E>) -> usize {
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|(pending_key, _)| *pending_key != key);
+        pending.push((key, write));
+        pending.len()
+    }
+
+    fn pending_for(&self, key: &str) -> Option<PendingWrite<E>> {
+        self.pending
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(pending_key, _)| pending_key == key)
+            .map(|(_, write)| write.clone())
+    }
+
+    /// Record that the repository changed for `key` and drop its cached entity
+    async fn written(&self, key: &str) {
+        self.version.fetch_add(1, Ordering::SeqCst);
+        self.cache.invalidate(key).await;
+    }
+
+    /// Cache `entity` loaded under `version`, unless a write completed since
+    ///
+    /// Writers bump the version before invalidating, so either this sees the
+    /// bump or the writer's invalidation lands after the insert.
+    async fn cache_loaded(&self, key: String, entity: E, version: u64) {
+        self.cache.insert(key.clone(), entity).await;
+        if self.version.load(Ordering::SeqCst) != version {
+            self.cache.invalidate(&key).await;
+        }
+    }
+
+    /// Flush pending writes in batches of `batch_size`
+    async fn flush(&self, batch_size: usize) -> Result<usize, ServiceError> {
+        let _guard = self.flush_lock.lock().await;
+        let mut flushed = 0;
+
+        loop {
+            let batch: Vec<(String, PendingWrite<E>)> = {
+                let mut pending = self.pending.lock().unwrap();
+                let n = pending.len().min(batch_size.max(1));
+                pending.drain(..n).collect()
+            };
+            if batch.is_empty() {
+                break;
+            }
+
+            debug!("Flushing {} write-behind writes", batch.len());
+            let mut remaining = batch.into_iter();
+            while let Some((key, write)) = remaining.next() {
+                let result = match &write {
+                    PendingWrite::Save(entity) => self.inner.save(entity).await.map(|_| ()),
+                    PendingWrite::Delete(id) => {
+                        let result = self.inner.delete(id).await.map(|_| ());
+                        if result.is_ok() {
+                            self.written(&key).await;
+                        }
+                        result
+                    }
+                };
+
+                if let Err(e) = result {
+                    // Requeue the failed write and the rest of the batch unless superseded
+                    let mut pending = self.pending.lock().unwrap();
+                    let requeue: Vec<_> = std::iter::once((key, write))
+                        .chain(remaining)
+                        .filter(|(key, _)| !pending.iter().any(|(newer, _)| newer == key))
+                        .collect();
+                    pending.splice(0..0, requeue);
+                    return Err(e);
+                }
+                flushed += 1;
+            }
+        }
+
+        Ok(flushed)
+    }
+}
+
+/// Repository decorator adding a read-through cache
+pub struct CachedRepository<E: Entity> {
+    shared: Arc<Shared<E>>,
+    mode: CacheMode,
+    worker: Mutex<Option<JoinHandle<()>>>,
+    /// Dropping this makes the flush task flush once more and exit
+    stop: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl<E: Entity> fmt::Debug for CachedRepository<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedRepository")
+            .field("mode", &self.mode)
+            .field("pending_writes", &self.shared.pending_len())
+            .finish()
+    }
+}
+
+impl<E: Entity> CachedRepository<E> {
+    /// Wrap `inner`; write-behind mode spawns a background flush task
+    ///
+    /// A zero `flush_interval` is raised to one millisecond.
+    pub fn new(inner: Arc<dyn Repository<E>>, mode: CacheMode) -> Self {
+        let shared = Arc::new(Shared {
+            inner,
+            cache: Cache::new(DEFAULT_CACHE_CAPACITY),
+            pending: Mutex::new(Vec::new()),
+            flush_lock: tokio::sync::Mutex::new(()),
+            version: AtomicU64::new(0),
+        });
+
+        let mode = match mode {
+            CacheMode::WriteBehind {
+                batch_size,
+                flush_interval,
+            } if flush_interval < MIN_FLUSH_INTERVAL => {
+                warn!(
+                    "Write-behind flush interval {:?} is too short, using {:?}",
+                    flush_interval, MIN_FLUSH_INTERVAL
+                );
+                CacheMode::WriteBehind {
+                    batch_size,
+                    flush_interval: MIN_FLUSH_INTERVAL,
+                }
+            }
+            mode => mode,
+        };
+
+        let (worker, stop) = match mode {
+            CacheMode::WriteThrough => (None, None),
+            CacheMode::WriteBehind {
+                batch_size,
+                flush_interval,
+            } => {
+                let (stop, mut stopped) = oneshot::channel::<()>();
+                let shared = shared.clone();
+                let worker = tokio::spawn(async move {
+                    let mut ticker = tokio::time::interval(flush_interval);
+                    ticker.tick().await;
+                    loop {
+                        let last = tokio::select! {
+                            _ = ticker.tick() => false,
+                            _ = &mut stopped => true,
+                        };
+                        if let Err(e) = shared.flush(batch_size).await {
+                            error!("Write-behind flush failed: {}", e);
+                        }
+                        if last {
+                            break;
+                        }
+                    }
+                });
+                (Some(worker), Some(stop))
+            }
+        };
+
+        Self {
+            shared,
+            mode,
+            worker: Mutex::new(worker),
+            stop: Mutex::new(stop),
+        }
+    }
+
+    /// The configured cache mode
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// Number of writes not yet flushed to the repository
+    pub fn pending_writes(&self) -> usize {
+        self.shared.pending_len()
+    }
+
+    /// Flush all pending writes now, returning how many were written
+    pub async fn flush(&self) -> Result<usize, ServiceError> {
+        match self.mode {
+            CacheMode::WriteThrough => Ok(0),
+            CacheMode::WriteBehind { batch_size, .. } => self.shared.flush(batch_size).await,
+        }
+    }
+
+    /// Flush pending writes and stop the flush task when `handle` shuts down
+    ///
+    /// Runs as an [`on_close`](AppHandle::on_close) hook; close hooks run in
+    /// the order they were registered, so register this before the hook
+    /// closing the connections the inner repository writes through.
+    pub fn flush_on_close(self: &Arc<Self>, handle: &AppHandle) {
+        let repository = self.clone();
+        let name = format!("write-behind {}", E::collection_name());
+        handle.on_close(&name, move || async move {
+            if let Err(e) = repository.shutdown().await {
+                error!("Failed to flush write-behind writes on shutdown: {}", e);
+            }
+        });
+    }
+
+    fn key(id: &E::Id) -> String {
+        serde_json::to_string(id)
+            .unwrap_or_else(|_| format!("{:?}", id))
+            .trim_matches('"')
+            .to_string()
+    }
+
+    async fn flush_if_full(&self, pending: usize) -> Result<(), ServiceError> {
+        match self.mode {
+            CacheMode::WriteBehind { batch_size, .. } if pending >= batch_size => {
+                self.shared.flush(batch_size).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E: Entity> Repository<E> for CachedRepository<E> {
+    async fn find_by_id(&self, id: &E::Id) -> Result<Option<E>, ServiceError> {
+        let key = Self::key(id);
+
+        match self.shared.pending_for(&key) {
+            Some(PendingWrite::Save(entity)) => return Ok(Some(entity)),
+            Some(PendingWrite::Delete(_)) => return Ok(None),
+            None => {}
+        }
+
+        if let Some(entity) = self.shared.cache.get(&key).await {
+            return Ok(Some(entity));
+        }
+
+        let version = self.shared.version.load(Ordering::SeqCst);
+        let entity = self.shared.inner.find_by_id(id).await?;
+        if let Some(entity) = &entity {
+            self.shared.cache_loaded(key, entity.clone(), version).await;
+        }
+        Ok(entity)
+    }
+
+    async fn find_all(&self) -> Result<Vec<E>, ServiceError> {
+        self.flush().await?;
+        self.shared.inner.find_all().await
+    }
+
+    async fn save(&self, entity: &E) -> Result<E, ServiceError> {
+        let key = Self::key(entity.id());
+
+        match self.mode {
+            CacheMode::WriteThrough => {
+                let saved = self.shared.inner.save(entity).await?;
+                self.shared.written(&key).await;
+                self.shared.cache.insert(key, saved.clone()).await;
+                Ok(saved)
+            }
+            CacheMode::WriteBehind { .. } => {
+                entity.validate()?;
+                self.shared.cache.insert(key.clone(), entity.clone()).await;
+                let pending = self.shared.enqueue(key, PendingWrite::Save(entity.clone()));
+                self.flush_if_full(pending).await?;
+                // Not what the repository will store; see `CacheMode::WriteBehind`
+                Ok(entity.clone())
+            }
+        }
+    }
+
+    async fn delete(&self, id: &E::Id) -> Result<bool, ServiceError> {
+        let key = Self::key(id);
+
+        match self.mode {
+            CacheMode::WriteThrough => {
+                let deleted = self.shared.inner.delete(id).await?;
+                self.shared.written(&key).await;
+                Ok(deleted)
+            }
+            CacheMode::WriteBehind { .. } => {
+                let existed = self.find_by_id(id).await?.is_some();
+                let pending = self
+                    .shared
+                    .enqueue(key.clone(), PendingWrite::Delete(id.clone()));
+                self.shared.cache.invalidate(&key).await;
+                self.flush_if_full(pending).await?;
+                Ok(existed)
+            }
+        }
+    }
+
+    async fn count(&self) -> Result<usize, ServiceError> {
+        self.flush().await?;
+        self.shared.inner.count().await
+    }
+}
+
+impl<E: Entity> Service for CachedRepository<E> {}
+
+#[async_trait]
+impl<E: Entity> Lifecycle for CachedRepository<E> {
+    async fn init(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Ok(())
+    }
+
+    async fn shutdown(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let flushed = self.flush().await?;
+        if flushed > 0 {
+            info!(
+                "Flushed {} pending write-behind writes on shutdown",
+                flushed
+            );
+        }
+
+        // Stop the flush task only once nothing is left for it to write
+        drop(self.stop.lock().unwrap().take());
+        let worker = self.worker.lock().unwrap().take();
+        if let Some(worker) = worker {
+            let _ = worker.await;
+        }
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::entity::test_utils::{Pet, pet};
+    use std::collections::HashMap;
+
+    /// Repository recording how many writes reach it
+    #[derive(Debug, Default)]
+    struct CountingRepository {
+        rows: Mutex<HashMap<String, Pet>>,
+        writes: Mutex<usize>,
+    }
+
+    impl CountingRepository {
+        fn writes(&self) -> usize {
+            *self.writes.lock().unwrap()
+        }
+
+        fn rows(&self) -> usize {
+            self.rows.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait]
+    impl Repository<Pet> for CountingRepository {
+        async fn find_by_id(&self, id: &String) -> Result<Option<Pet>, ServiceError> {
+            Ok(self.rows.lock().unwrap().get(id).cloned())
+        }
+
+        async fn find_all(&self) -> Result<Vec<Pet>, ServiceError> {
+            Ok(self.rows.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn save(&self, entity: &Pet) -> Result<Pet, ServiceError> {
+            *self.writes.lock().unwrap() += 1;
+            self.rows
+                .lock()
+                .unwrap()
+                .insert(entity.id.clone(), entity.clone());
+            Ok(entity.clone())
+        }
+
+        async fn delete(&self, id: &String) -> Result<bool, ServiceError> {
+            *self.writes.lock().unwrap() += 1;
+            Ok(self.rows.lock().unwrap().remove(id).is_some())
+        }
+
+        async fn count(&self) -> Result<usize, ServiceError> {
+            Ok(self.rows.lock().unwrap().len())
+        }
+    }
+
+    fn write_behind(batch_size: usize) -> CacheMode {
+        CacheMode::WriteBehind {
+            batch_size,
+            flush_interval: Duration::from_secs(3600),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_through_writes_repository_immediately() {
+        let inner = Arc::new(CountingRepository::default());
+        let repo = CachedRepository::new(inner.clone(), CacheMode::default());
+
+        repo.save(&pet("1", "Rex")).await.unwrap();
+
+        assert_eq!(inner.rows(), 1);
+        assert_eq!(repo.pending_writes(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_write_behind_batches_writes() {
+        let inner = Arc::new(CountingRepository::default());
+        let repo = CachedRepository::new(inner.clone(), write_behind(3));
+
+        repo.save(&pet("1", "Rex")).await.unwrap();
+        repo.save(&pet("2", "Fido")).await.unwrap();
+
+        // Not yet flushed, but visible through the cache
+        assert_eq!(inner.writes(), 0);
+        assert_eq!(
+            repo.find_by_id(&"2".to_string())
+                .await
+                .unwrap()
+                .unwrap()
+                .name,
+            "Fido"
+        );
+
+        repo.save(&pet("3", "Tom")).await.unwrap();
+
+        assert_eq!(inner.writes(), 3);
+        assert_eq!(inner.rows(), 3);
+        assert_eq!(repo.pending_writes(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_write_behind_coalesces_writes_to_same_entity() {
+        let inner = Arc::new(CountingRepository::default());
+        let repo = CachedRepository::new(inner.clone(), write_behind(10));
+
+        repo.save(&pet("1", "Rex")).await.unwrap();
+        repo.save(&pet("1", "Max")).await.unwrap();
+        repo.flush().await.unwrap();
+
+        assert_eq!(inner.writes(), 1);
+        assert_eq!(
+            inner
+                .find_by_id(&"1".to_string())
+                .await
+                .unwrap()
+                .unwrap()
+                .name,
+            "Max"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_pending_writes() {
+        let inner = Arc::new(CountingRepository::default());
+        let repo = CachedRepository::new(inner.clone(), write_behind(100));
+
+        repo.save(&pet("1", "Rex")).await.unwrap();
+        repo.save(&pet("2", "Fido")).await.unwrap();
+        assert!(repo.delete(&"1".to_string()).await.unwrap());
+        assert_eq!(inner.writes(), 0);
+
+        repo.shutdown().await.unwrap();
+
+        assert_eq!(repo.pending_writes(), 0);
+        assert_eq!(inner.rows(), 1);
+        assert!(inner.find_by_id(&"2".to_string()).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_app_shutdown_flushes_pending_writes() {
+        use crate::core::reliability::DrainSignal;
+
+        let inner = Arc::new(CountingRepository::default());
+        let repo = Arc::new(CachedRepository::new(inner.clone(), write_behind(100)));
+        let handle = AppHandle::new(DrainSignal::default());
+        repo.flush_on_close(&handle);
+
+        repo.save(&pet("1", "Rex")).await.unwrap();
+        assert_eq!(inner.rows(), 0);
+
+        handle.shutdown(Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(inner.rows(), 1);
+        assert_eq!(repo.pending_writes(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_interval_flushes_pending_writes() {
+        let inner = Arc::new(CountingRepository::default());
+        let repo = CachedRepository::new(
+            inner.clone(),
+            CacheMode::WriteBehind {
+                batch_size: 100,
+                flush_interval: Duration::from_millis(20),
+            },
+        );
+
+        repo.save(&pet("1", "Rex")).await.unwrap();
+        for _ in 0..50 {
+            if inner.rows() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(inner.rows(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_flushes_pending_writes() {
+        let inner = Arc::new(CountingRepository::default());
+        let repo = CachedRepository::new(inner.clone(), write_behind(100));
+
+        repo.save(&pet("1", "Rex")).await.unwrap();
+        drop(repo);
+        for _ in 0..50 {
+            if inner.rows() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(inner.rows(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_zero_flush_interval_is_raised() {
+        let inner = Arc::new(CountingRepository::default());
+        let repo = CachedRepository::new(
+            inner.clone(),
+            CacheMode::WriteBehind {
+                batch_size: 100,
+                flush_interval: Duration::ZERO,
+            },
+        );
+
+        assert_eq!(
+            repo.mode(),
+            CacheMode::WriteBehind {
+                batch_size: 100,
+                flush_interval: MIN_FLUSH_INTERVAL,
+            }
+        );
+        repo.save(&pet("1", "Rex")).await.unwrap();
+        repo.shutdown().await.unwrap();
+        assert_eq!(inner.rows(), 1);
+    }
+
+    /// Repository whose reads wait for a permit after loading the row
+    #[derive(Debug)]
+    struct GatedRepository {
+        rows: CountingRepository,
+        gate: tokio::sync::Semaphore,
+    }
+
+    #[async_trait]
+    impl Repository<Pet> for GatedRepository {
+        async fn find_by_id(&self, id: &String) -> Result<Option<Pet>, ServiceError> {
+            let row = self.rows.find_by_id(id).await;
+            self.gate.acquire().await.unwrap().forget();
+            row
+        }
+
+        async fn find_all(&self) -> Result<Vec<Pet>, ServiceError> {
+            self.rows.find_all().await
+        }
+
+        async fn save(&self, entity: &Pet) -> Result<Pet, ServiceError> {
+            self.rows.save(entity).await
+        }
+
+        async fn delete(&self, id: &String) -> Result<bool, ServiceError> {
+            self.rows.delete(id).await
+        }
+
+        async fn count(&self) -> Result<usize, ServiceError> {
+            self.rows.count().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_racing_delete_does_not_recache_entity() {
+        let inner = Arc::new(GatedRepository {
+            rows: CountingRepository::default(),
+            gate: tokio::sync::Semaphore::new(0),
+        });
+        inner.save(&pet("1", "Rex")).await.unwrap();
+        let repo = Arc::new(CachedRepository::new(inner.clone(), CacheMode::default()));
+
+        // The read loads the row, then the delete completes before it returns
+        let read = tokio::spawn({
+            let repo = repo.clone();
+            async move { repo.find_by_id(&"1".to_string()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(repo.delete(&"1".to_string()).await.unwrap());
+        inner.gate.add_permits(2);
+        read.await.unwrap().unwrap();
+
+        assert!(repo.find_by_id(&"1".to_string()).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,402 @@
+//! Cancelling queries when the client goes away
+//!
+//! When a client disconnects, hyper drops the request's future, which stops
+//! the handler at its next `.await`. Work the handler handed off (a spawned
+//! task, a query still running on the database server) is not stopped by
+//! that. [`cancel_on_disconnect`] gives every request a
+//! [`RequestCancellation`] that fires when the request future is dropped
+//! before producing a response; pass it to [`run_until_cancelled`] to stop
+//! work the handler spawned.
+//!
+//! For queries awaited directly in the handler, use
+//! [`postgres::fetch_cancellable`]: it cancels the statement server-side
+//! with `pg_cancel_backend` when the signal fires or when its own future is
+//! dropped mid-query, so it also works when axum just drops the handler.
+//!
+//! ```ignore
+//! async fn report(cancel: RequestCancellation, State(pool): State<PgPool>) -> Result<Json<Report>, AppError> {
+//!     let report = fetch_cancellable(&pool, &cancel, |conn| Box::pin(build_report(conn))).await?;
+//!     Ok(Json(report))
+//! }
+//! ```
+//!
+//! [`run_until_cancelled`] alone only stops `operation` at an await point;
+//! a statement it started keeps running on the database server.
+
+use std::convert::Infallible;
+use std::future::Future;
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::request::Parts,
+    middleware::Next,
+    response::Response,
+};
+use metrics::counter;
+use tokio::sync::watch;
+
+use crate::core::services::error::ServiceError;
+
+/// The operation was abandoned because the client disconnected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Request was cancelled by the client")]
+pub struct Cancelled;
+
+impl From<Cancelled> for ServiceError {
+    fn from(error: Cancelled) -> Self {
+        ServiceError::Cancelled(error.to_string())
+    }
+}
+
+/// Signal that a request was abandoned, shared by every clone
+///
+/// Extract it in a handler; requests outside [`cancel_on_disconnect`] get
+/// one that never fires.
+#[derive(Debug, Clone)]
+pub struct RequestCancellation {
+    receiver: watch::Receiver<bool>,
+}
+
+impl RequestCancellation {
+    /// A signal and the guard that fires it when dropped
+    pub fn new() -> (Self, CancelOnDrop) {
+        let (sender, receiver) = watch::channel(false);
+        (
+            Self { receiver },
+            CancelOnDrop {
+                sender: Some(sender),
+            },
+        )
+    }
+
+    /// A signal that never fires
+    pub fn never() -> Self {
+        let (signal, guard) = Self::new();
+        guard.disarm();
+        signal
+    }
+
+    /// Whether the request has been abandoned
+    pub fn is_cancelled(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Resolve once the request is abandoned; never resolves otherwise
+    pub async fn cancelled(&self) {
+        let mut receiver = self.receiver.clone();
+        if receiver.wait_for(|cancelled| *cancelled).await.is_err() {
+            // Disarmed: the request completed normally
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestCancellation {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<RequestCancellation>()
+            .cloned()
+            .unwrap_or_else(RequestCancellation::never))
+    }
+}
+
+/// Fires its [`RequestCancellation`] when dropped, unless disarmed
+#[derive(Debug)]
+pub struct CancelOnDrop {
+    sender: Option<watch::Sender<bool>>,
+}
+
+impl CancelOnDrop {
+    /// Let the request finish without firing the signal
+    pub fn disarm(mut self) {
+        self.sender = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            sender.send_replace(true);
+        }
+    }
+}
+
+/// Run `operation` unless the request is abandoned first
+///
+/// On cancellation `operation` is dropped, which stops it at its current
+/// await point.
+pub async fn run_until_cancelled<F: Future>(
+    cancellation: &RequestCancellation,
+    operation: F,
+) -> Result<F::Output, Cancelled> {
+    tokio::select! {
+        output = operation => Ok(output),
+        _ = cancellation.cancelled() => {
+            counter!("cancelled_operations_total").increment(1);
+            Err(Cancelled)
+        }
+    }
+}
+
+/// Middleware attaching a [`RequestCancellation`] that fires on client disconnect
+///
+/// Use with `axum::middleware::from_fn(cancel_on_disconnect)`.
+pub async fn cancel_on_disconnect(mut request: Request, next: Next) -> Response {
+    let (cancellation, guard) = RequestCancellation::new();
+    request.extensions_mut().insert(cancellation);
+
+    // If hyper drops this future because the connection closed, `guard` fires
+    let response = next.run(request).await;
+    guard.disarm();
+    response
+}
+
+/// Server-side cancellation for Postgres queries
+#[cfg(feature = "postgres")]
+pub mod postgres {
+    use futures::future::BoxFuture;
+    use sqlx::{PgConnection, PgPool, Postgres, pool::PoolConnection};
+    use tracing::{debug, warn};
+
+    use super::{Cancelled, RequestCancellation, run_until_cancelled};
+    use crate::core::services::error::ServiceError;
+
+    /// Cancels the statement running on `conn` when dropped, unless disarmed
+    ///
+    /// Dropping covers both the [`RequestCancellation`] firing and the caller
+    /// dropping the future mid-query, as axum does when the client
+    /// disconnects.
+    struct CancelBackendOnDrop {
+        pool: PgPool,
+        pid: i32,
+        conn: PoolConnection<Postgres>,
+        armed: bool,
+    }
+
+    impl Drop for CancelBackendOnDrop {
+        fn drop(&mut self) {
+            if !self.armed {
+                return;
+            }
+            // The connection is mid-statement; close it rather than return it to the pool
+            self.conn.close_on_drop();
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                warn!("No runtime to cancel the query on backend {}", self.pid);
+                return;
+            };
+            debug!("Cancelling query on backend {}", self.pid);
+            let (pool, pid) = (self.pool.clone(), self.pid);
+            runtime.spawn(async move {
+                let result = sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(pid)
+                    .execute(&pool)
+                    .await;
+                if let Err(e) = result {
+                    warn!("Failed to cancel query on backend {}: {}", pid, e);
+                }
+            });
+        }
+    }
+
+    /// Run `query` on a pooled connection, cancelling it in Postgres if the request is abandoned
+    ///
+    /// Dropping a sqlx future doesn't stop the statement on the server, so
+    /// the backend running it is sent `pg_cancel_backend` both when
+    /// `cancellation` fires and when the returned future is dropped before
+    /// the query finishes.
+    pub async fn fetch_cancellable<T, F>(
+        pool: &PgPool,
+        cancellation: &RequestCancellation,
+        query: F,
+    ) -> Result<T, ServiceError>
+    where
+        F: for<'c> FnOnce(&'c mut PgConnection) -> BoxFuture<'c, Result<T, sqlx::Error>>,
+    {
+        let mut conn = pool.acquire().await.map_err(|e| {
+            ServiceError::unavailable(format!("Failed to acquire connection: {}", e))
+        })?;
+        let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut *conn)
+            .await?;
+
+        let mut guard = CancelBackendOnDrop {
+            pool: pool.clone(),
+            pid,
+            conn,
+            armed: true,
+        };
+        match run_until_cancelled(cancellation, query(&mut *guard.conn)).await {
+            Ok(result) => {
+                guard.armed = false;
+                result.map_err(ServiceError::from)
+            }
+            // Dropping the guard cancels the statement
+            Err(Cancelled) => Err(Cancelled.into()),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::time::Duration;
+
+        /// Whether a statement containing `marker` is running
+        async fn is_running(pool: &PgPool, marker: &str) -> bool {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT count(*) FROM pg_stat_activity \
+                 WHERE state = 'active' AND query LIKE '%' || $1 || '%' \
+                 AND pid <> pg_backend_pid()",
+            )
+            .bind(marker)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+                > 0
+        }
+
+        /// Needs a database: set `TEST_DATABASE_URL` (e.g. `postgres://localhost/navius_test`)
+        /// and run `cargo test --features postgres -- --ignored`
+        #[tokio::test]
+        #[ignore = "requires TEST_DATABASE_URL"]
+        async fn test_dropped_future_cancels_query() {
+            let url =
+                std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+            let pool = PgPool::connect(&url).await.unwrap();
+            let marker = format!("cancel-test-{}", uuid::Uuid::new_v4());
+
+            // No cancellation signal: only dropping the future can stop the query
+            let query = {
+                let (pool, sql) = (pool.clone(), format!("SELECT pg_sleep(30) -- {}", marker));
+                tokio::spawn(async move {
+                    fetch_cancellable(&pool, &RequestCancellation::never(), move |conn| {
+                        Box::pin(async move { sqlx::query(&sql).execute(conn).await })
+                    })
+                    .await
+                })
+            };
+            let mut started = false;
+            for _ in 0..100 {
+                if is_running(&pool, &marker).await {
+                    started = true;
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            assert!(started, "query never started");
+
+            query.abort();
+            let mut stopped = false;
+            for _ in 0..100 {
+                if !is_running(&pool, &marker).await {
+                    stopped = true;
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            assert!(stopped, "query kept running after its future was dropped");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
+    /// Sets `dropped` when the slow query's future is dropped
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_disconnect_cancels_in_flight_query() {
+        let started = Arc::new(Notify::new());
+        let completed = Arc::new(AtomicBool::new(false));
+        let dropped = Arc::new(AtomicBool::new(false));
+        let query_done = Arc::new(Notify::new());
+
+        let app = {
+            let (started, completed, dropped, query_done) = (
+                started.clone(),
+                completed.clone(),
+                dropped.clone(),
+                query_done.clone(),
+            );
+            Router::new()
+                .route(
+                    "/report",
+                    get(move |cancel: RequestCancellation| async move {
+                        // The query runs on its own task, as a pooled query would
+                        let query = tokio::spawn(async move {
+                            let slow_query = async {
+                                let _flag = DropFlag(dropped);
+                                started.notify_one();
+                                tokio::time::sleep(Duration::from_secs(30)).await;
+                                completed.store(true, Ordering::SeqCst);
+                            };
+                            let result = run_until_cancelled(&cancel, slow_query).await;
+                            query_done.notify_one();
+                            result
+                        });
+                        let _ = query.await;
+                        "done"
+                    }),
+                )
+                .layer(axum::middleware::from_fn(cancel_on_disconnect))
+        };
+
+        let request = axum::http::Request::builder()
+            .uri("/report")
+            .body(Body::empty())
+            .unwrap();
+        let in_flight = tokio::spawn(app.oneshot(request));
+        started.notified().await;
+
+        // Dropping the request future is what hyper does when the client disconnects
+        in_flight.abort();
+        tokio::time::timeout(Duration::from_secs(5), query_done.notified())
+            .await
+            .expect("query was not cancelled");
+
+        assert!(dropped.load(Ordering::SeqCst));
+        assert!(!completed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_completed_request_does_not_cancel() {
+        let (cancellation, guard) = RequestCancellation::new();
+        guard.disarm();
+
+        let result = run_until_cancelled(&cancellation, async { 42 }).await;
+        assert_eq!(result, Ok(42));
+        assert!(!cancellation.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_extractor_without_middleware_never_fires() {
+        let app = Router::new().route(
+            "/",
+            get(|cancel: RequestCancellation| async move {
+                run_until_cancelled(&cancel, async { "ok" }).await.unwrap()
+            }),
+        );
+        let request = axum::http::Request::builder()
+            .uri("/")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+    }
+}
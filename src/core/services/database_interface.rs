@@ -1,6 +1,62 @@
+use crate::core::config::redaction::REDACTED;
+use crate::core::services::connection_string::{
+    ConnectionStringError, PostgresConnection, SslMode, redact_url,
+};
 use crate::core::services::error::ServiceError;
 use async_trait::async_trait;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// A write applied as part of an atomic batch
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchWrite {
+    Set {
+        collection: String,
+        key: String,
+        value: String,
+    },
+    Delete {
+        collection: String,
+        key: String,
+    },
+    /// Set that fails the batch with a conflict if the key already exists
+    Insert {
+        collection: String,
+        key: String,
+        value: String,
+    },
+    /// Fails the batch with a conflict unless `key` holds `expected` (`None`: absent)
+    Check {
+        collection: String,
+        key: String,
+        expected: Option<String>,
+    },
+}
+
+impl BatchWrite {
+    /// Whether this write targets `key` in `collection`
+    pub fn targets(&self, collection: &str, key: &str) -> bool {
+        match self {
+            BatchWrite::Set {
+                collection: c,
+                key: k,
+                ..
+            }
+            | BatchWrite::Insert {
+                collection: c,
+                key: k,
+                ..
+            }
+            | BatchWrite::Delete {
+                collection: c,
+                key: k,
+            } => c == collection && k == key,
+            // Checks only read the key
+            BatchWrite::Check { .. } => false,
+        }
+    }
+}
 
 /// Trait defining database operations
 #[async_trait]
@@ -11,11 +67,31 @@ pub trait DatabaseOperations: Send + Sync + 'static {
     /// Set a value in the database
     async fn set(&self, collection: &str, key: &str, value: &str) -> Result<(), ServiceError>;
 
+    /// Set a value only if `key` is absent, returning whether it was inserted
+    ///
+    /// The check and the write are one atomic operation, so of several
+    /// concurrent inserts of the same key exactly one succeeds.
+    async fn insert(&self, collection: &str, key: &str, value: &str) -> Result<bool, ServiceError>;
+
     /// Delete a value from the database
     async fn delete(&self, collection: &str, key: &str) -> Result<bool, ServiceError>;
 
     /// Query the database with a filter
     async fn query(&self, collection: &str, filter: &str) -> Result<Vec<String>, ServiceError>;
+
+    /// Apply `writes` in order as one atomic operation
+    ///
+    /// Either all writes are applied or, on error, none is; an
+    /// [`BatchWrite::Insert`] of an existing key, or a [`BatchWrite::Check`]
+    /// that doesn't hold, fails the batch with a conflict. Databases that can't apply writes atomically keep the
+    /// default, which fails without applying any, so transactions against
+    /// them fail rather than commit partially.
+    async fn apply_batch(&self, writes: &[BatchWrite]) -> Result<(), ServiceError> {
+        Err(ServiceError::configuration_error(format!(
+            "This database does not support atomic batches ({} writes not applied)",
+            writes.len()
+        )))
+    }
 }
 
 /// Trait for database providers
@@ -35,7 +111,11 @@ pub trait DatabaseProvider: Send + Sync {
 }
 
 /// Configuration for database connections
-#[derive(Debug, Clone)]
+///
+/// A Postgres connection can be given as `url`, as the discrete `host`,
+/// `port`, `database`, `username`, `password` and `ssl_mode` fields, or
+/// both; see [`DatabaseConfig::postgres_connection`].
+#[derive(Clone)]
 pub struct DatabaseConfig {
     /// Provider name
     pub provider: String,
@@ -43,6 +123,24 @@ pub struct DatabaseConfig {
     /// Database URL
     pub url: String,
 
+    /// Host, overriding the one in `url`
+    pub host: Option<String>,
+
+    /// Port, overriding the one in `url`
+    pub port: Option<u16>,
+
+    /// Database name, overriding the one in `url`
+    pub database: Option<String>,
+
+    /// User name, overriding the one in `url`
+    pub username: Option<String>,
+
+    /// Password, overriding the one in `url`
+    pub password: Option<String>,
+
+    /// `sslmode`, overriding the one in `url` and `use_ssl`
+    pub ssl_mode: Option<SslMode>,
+
     /// Maximum connection pool size
     pub max_connections: u32,
 
@@ -52,6 +150,16 @@ pub struct DatabaseConfig {
     /// Whether to use SSL for connections
     pub use_ssl: bool,
 
+    /// Maximum time a single statement may run, in milliseconds (`statement_timeout`)
+    pub statement_timeout_ms: Option<u64>,
+
+    /// Maximum time a session may sit idle inside an open transaction, in milliseconds
+    /// (`idle_in_transaction_session_timeout`)
+    pub idle_in_transaction_session_timeout_ms: Option<u64>,
+
+    /// Maximum time to wait when checking a connection out of the pool, in seconds
+    pub acquire_timeout_seconds: u32,
+
     /// Provider-specific configuration
     pub provider_config: std::collections::HashMap<String, String>,
 }
@@ -61,14 +169,157 @@ impl Default for DatabaseConfig {
         Self {
             provider: "memory".to_string(),
             url: "memory://".to_string(),
+            host: None,
+            port: None,
+            database: None,
+            username: None,
+            password: None,
+            ssl_mode: None,
             max_connections: 10,
             timeout_seconds: 30,
             use_ssl: false,
+            statement_timeout_ms: None,
+            idle_in_transaction_session_timeout_ms: None,
+            acquire_timeout_seconds: 30,
             provider_config: std::collections::HashMap::new(),
         }
     }
 }
 
+impl std::fmt::Debug for DatabaseConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseConfig")
+            .field("provider", &self.provider)
+            .field("url", &redact_url(&self.url))
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("database", &self.database)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .field("ssl_mode", &self.ssl_mode)
+            .field("max_connections", &self.max_connections)
+            .field("timeout_seconds", &self.timeout_seconds)
+            .field("use_ssl", &self.use_ssl)
+            .field("statement_timeout_ms", &self.statement_timeout_ms)
+            .field(
+                "idle_in_transaction_session_timeout_ms",
+                &self.idle_in_transaction_session_timeout_ms,
+            )
+            .field("acquire_timeout_seconds", &self.acquire_timeout_seconds)
+            .field("provider_config", &self.provider_config)
+            .finish()
+    }
+}
+
+impl DatabaseConfig {
+    /// Postgres configuration from a connection URL such as `DATABASE_URL`
+    ///
+    /// Fails with a description of the problem if the URL is malformed.
+    pub fn from_url(url: &str) -> Result<Self, ConnectionStringError> {
+        let connection = PostgresConnection::parse(url)?;
+        Ok(Self {
+            provider: "postgres".to_string(),
+            url: url.to_string(),
+            use_ssl: connection.ssl_mode.requires_ssl(),
+            ..Self::default()
+        })
+    }
+
+    /// Resolve the Postgres connection from `url` and the discrete fields
+    ///
+    /// `url` provides the base and every discrete field that is set
+    /// overrides its part of it. Without a Postgres `url`, `host` and
+    /// `database` must be set. Unless `ssl_mode` is set, `use_ssl` raises
+    /// a weaker `sslmode` to `require`.
+    pub fn postgres_connection(&self) -> Result<PostgresConnection, ConnectionStringError> {
+        let mut connection = match PostgresConnection::parse(&self.url) {
+            Ok(connection) => connection,
+            // The discrete fields alone may describe the connection
+            Err(ConnectionStringError::UnsupportedScheme(_)) if self.host.is_some() => {
+                PostgresConnection::default()
+            }
+            Err(e) => return Err(e),
+        };
+
+        if let Some(host) = &self.host {
+            connection.host = host.clone();
+        }
+        if let Some(port) = self.port {
+            connection.port = port;
+        }
+        if let Some(database) = &self.database {
+            connection.database = database.clone();
+        }
+        if let Some(username) = &self.username {
+            connection.user = Some(username.clone());
+        }
+        if let Some(password) = &self.password {
+            connection.password = Some(password.clone());
+        }
+        match self.ssl_mode {
+            Some(ssl_mode) => connection.ssl_mode = ssl_mode,
+            None if self.use_ssl && !connection.ssl_mode.requires_ssl() => {
+                connection.ssl_mode = SslMode::Require
+            }
+            None => {}
+        }
+
+        connection.validate()?;
+        Ok(connection)
+    }
+
+    /// Statement timeout as a `Duration`, if configured
+    pub fn statement_timeout(&self) -> Option<Duration> {
+        self.statement_timeout_ms.map(Duration::from_millis)
+    }
+
+    /// Pool checkout timeout as a `Duration`
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_timeout_seconds as u64)
+    }
+
+    /// Session settings to apply whenever a new connection is established
+    ///
+    /// SQL providers should execute these statements in their `after_connect`
+    /// hook so that every pooled connection carries the configured limits.
+    pub fn session_settings(&self) -> Vec<String> {
+        let mut settings = Vec::new();
+
+        if let Some(ms) = self.statement_timeout_ms {
+            settings.push(format!("SET statement_timeout = {}", ms));
+        }
+
+        if let Some(ms) = self.idle_in_transaction_session_timeout_ms {
+            settings.push(format!("SET idle_in_transaction_session_timeout = {}", ms));
+        }
+
+        settings
+    }
+
+    /// Run a database operation, failing with `ServiceError::Timeout` if it
+    /// exceeds the configured statement timeout
+    ///
+    /// This bounds the wait on the client only, for providers with no
+    /// server-side timeout such as the in-memory database. Postgres pools
+    /// from [`postgres_pool::connect`](crate::core::services::postgres_pool::connect)
+    /// set `statement_timeout` on every session instead, so the server stops
+    /// the statement too.
+    pub async fn with_statement_timeout<T, F>(&self, operation: F) -> Result<T, ServiceError>
+    where
+        F: Future<Output = Result<T, ServiceError>>,
+    {
+        match self.statement_timeout() {
+            Some(limit) => tokio::time::timeout(limit, operation).await.map_err(|_| {
+                ServiceError::timeout(format!(
+                    "Statement exceeded timeout of {}ms",
+                    limit.as_millis()
+                ))
+            })?,
+            None => operation.await,
+        }
+    }
+}
+
 /// Registry for database providers
 pub struct DatabaseProviderRegistry {
     providers: std::collections::HashMap<String, Box<dyn std::any::Any + Send + Sync>>,
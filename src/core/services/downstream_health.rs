@@ -0,0 +1,133 @@
+//! Health checks for downstream HTTP dependencies
+//!
+//! Each configured dependency is probed with a GET to its health URL. A
+//! 2xx response within the timeout is `UP`; anything else is `DOWN`.
+//! Whether a `DOWN` dependency makes this service unready is decided by
+//! [`HealthServiceV2`](super::health_provider::HealthServiceV2) from
+//! [`DownstreamHealthCheck::critical`].
+
+use std::time::Duration;
+
+use reqwest::Client;
+
+use crate::core::config::app_config::{DownstreamHealthConfig, HealthChecksConfig};
+use crate::core::models::DependencyStatus;
+
+/// Health check of one downstream HTTP dependency
+#[derive(Debug, Clone)]
+pub struct DownstreamHealthCheck {
+    pub name: String,
+    pub url: String,
+    pub timeout: Duration,
+    pub critical: bool,
+}
+
+impl DownstreamHealthCheck {
+    /// Create a critical check with a 2 second timeout
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            timeout: Duration::from_secs(2),
+            critical: true,
+        }
+    }
+
+    /// Create a check from its configuration
+    pub fn from_config(name: &str, config: &DownstreamHealthConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            url: config.url.clone(),
+            timeout: Duration::from_millis(config.timeout_ms),
+            critical: config.critical,
+        }
+    }
+
+    /// Create the checks for every configured dependency, ordered by name
+    pub fn all_from_config(config: &HealthChecksConfig) -> Vec<Self> {
+        let mut checks: Vec<Self> = config
+            .downstream
+            .iter()
+            .map(|(name, dependency)| Self::from_config(name, dependency))
+            .collect();
+        checks.sort_by(|a, b| a.name.cmp(&b.name));
+        checks
+    }
+
+    /// Set how long to wait for the health URL
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set whether a failure makes the service unready
+    pub fn with_critical(mut self, critical: bool) -> Self {
+        self.critical = critical;
+        self
+    }
+
+    /// Probe the dependency
+    pub async fn check(&self, client: &Client) -> DependencyStatus {
+        let (status, details) = match client.get(&self.url).timeout(self.timeout).send().await {
+            Ok(response) if response.status().is_success() => (
+                "UP",
+                format!("{} responded {}", self.url, response.status()),
+            ),
+            Ok(response) => (
+                "DOWN",
+                format!("{} responded {}", self.url, response.status()),
+            ),
+            Err(e) if e.is_timeout() => (
+                "DOWN",
+                format!(
+                    "{} did not respond within {}ms",
+                    self.url,
+                    self.timeout.as_millis()
+                ),
+            ),
+            Err(e) => ("DOWN", format!("{} unreachable: {}", self.url, e)),
+        };
+        DependencyStatus::new(self.name.clone(), status).with_details(details)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::utils::http_client::shared_client;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_check_statuses() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/up"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/down"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/slow"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+            .mount(&server)
+            .await;
+        let client = shared_client();
+
+        let up = DownstreamHealthCheck::new("up", format!("{}/up", server.uri()));
+        assert_eq!(up.check(&client).await.status, "UP");
+
+        let down = DownstreamHealthCheck::new("down", format!("{}/down", server.uri()));
+        assert_eq!(down.check(&client).await.status, "DOWN");
+
+        let slow = DownstreamHealthCheck::new("slow", format!("{}/slow", server.uri()))
+            .with_timeout(Duration::from_millis(50));
+        let result = slow.check(&client).await;
+        assert_eq!(result.status, "DOWN");
+        assert!(result.details.unwrap().contains("did not respond"));
+    }
+}
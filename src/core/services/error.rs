@@ -38,6 +38,9 @@ pub enum ServiceError {
     /// Repository/database errors
     Repository(String),
 
+    /// Error when the client abandoned the request before it completed
+    Cancelled(String),
+
     /// Generic error with a message
     Other(String),
 }
@@ -62,6 +65,7 @@ impl Display for ServiceError {
             ServiceError::Validation(msg) => write!(f, "Validation error: {}", msg),
             ServiceError::Conflict(msg) => write!(f, "Conflict error: {}", msg),
             ServiceError::Repository(msg) => write!(f, "Repository error: {}", msg),
+            ServiceError::Cancelled(msg) => write!(f, "Request cancelled: {}", msg),
             ServiceError::Other(msg) => write!(f, "Service error: {}", msg),
         }
     }
@@ -131,6 +135,11 @@ impl ServiceError {
         ServiceError::Timeout(msg.into())
     }
 
+    /// Create a new cancelled error
+    pub fn cancelled<S: Into<String>>(msg: S) -> Self {
+        ServiceError::Cancelled(msg.into())
+    }
+
     /// Create a new configuration error
     pub fn configuration_error<S: Into<String>>(msg: S) -> Self {
         ServiceError::ConfigurationError(msg.into())
@@ -0,0 +1,340 @@
+//! Bounded, non-blocking channel for internal events
+//!
+//! Background workers (outbox, audit, metrics) receive events from the
+//! request path. An unbounded channel grows without limit under load, and a
+//! bounded one blocks producers when full. [`BoundedEventSink::try_send`]
+//! never waits: when the sink is full it either drops the oldest queued
+//! event or rejects the new one, per [`OverflowPolicy`]. Queue depth and
+//! drops are published as `event_sink_depth` and `event_sink_dropped_total`,
+//! labelled with the sink name.
+//!
+//! ```ignore
+//! let (sink, mut events) = BoundedEventSink::new("audit", 1024, OverflowPolicy::DropOldest);
+//! tokio::spawn(async move {
+//!     while let Some(event) = events.recv().await {
+//!         audit_log.write(event).await;
+//!     }
+//! });
+//! sink.try_send(AuditEvent::login(&user))?;
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use metrics::{counter, gauge};
+use thiserror::Error;
+use tokio::sync::Notify;
+
+/// Gauge of events waiting in a sink, labelled with `sink`
+pub const EVENT_SINK_DEPTH: &str = "event_sink_depth";
+
+/// Counter of events discarded by a full sink, labelled with `sink` and `policy`
+pub const EVENT_SINK_DROPPED_TOTAL: &str = "event_sink_dropped_total";
+
+/// What a full sink does with a new event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued event to make room
+    #[default]
+    DropOldest,
+    /// Refuse the new event, returning it to the producer
+    Reject,
+}
+
+impl OverflowPolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::DropOldest => "drop_oldest",
+            Self::Reject => "reject",
+        }
+    }
+}
+
+/// Why [`BoundedEventSink::try_send`] did not queue an event
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The sink is full and rejects new events
+    #[error("event sink is full")]
+    Full(T),
+    /// The receiver has been dropped
+    #[error("event sink is closed")]
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// The event that was not sent
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(event) | Self::Closed(event) => event,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Queue<T> {
+    events: VecDeque<T>,
+    senders: usize,
+    receiver_alive: bool,
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    name: String,
+    capacity: usize,
+    policy: OverflowPolicy,
+    queue: Mutex<Queue<T>>,
+    notify: Notify,
+    dropped: AtomicU64,
+}
+
+impl<T> Shared<T> {
+    fn record_depth(&self, depth: usize) {
+        gauge!(EVENT_SINK_DEPTH, "sink" => self.name.clone()).set(depth as f64);
+    }
+
+    fn record_drop(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        counter!(
+            EVENT_SINK_DROPPED_TOTAL,
+            "sink" => self.name.clone(),
+            "policy" => self.policy.as_str()
+        )
+        .increment(1);
+    }
+}
+
+/// Producer half of a bounded event channel; cheap to clone
+#[derive(Debug)]
+pub struct BoundedEventSink<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Consumer half of a bounded event channel
+#[derive(Debug)]
+pub struct EventReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> BoundedEventSink<T> {
+    /// Channel named `name` holding at most `capacity` events
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    pub fn new(
+        name: impl Into<String>,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> (Self, EventReceiver<T>) {
+        assert!(capacity > 0, "event sink capacity must be positive");
+        let shared = Arc::new(Shared {
+            name: name.into(),
+            capacity,
+            policy,
+            queue: Mutex::new(Queue {
+                events: VecDeque::with_capacity(capacity),
+                senders: 1,
+                receiver_alive: true,
+            }),
+            notify: Notify::new(),
+            dropped: AtomicU64::new(0),
+        });
+        (
+            Self {
+                shared: shared.clone(),
+            },
+            EventReceiver { shared },
+        )
+    }
+
+    /// Queue `event` without waiting, applying the overflow policy when full
+    pub fn try_send(&self, event: T) -> Result<(), TrySendError<T>> {
+        let shared = &self.shared;
+        let depth = {
+            let mut queue = shared.queue.lock().unwrap();
+            if !queue.receiver_alive {
+                return Err(TrySendError::Closed(event));
+            }
+            if queue.events.len() >= shared.capacity {
+                match shared.policy {
+                    OverflowPolicy::DropOldest => {
+                        queue.events.pop_front();
+                    }
+                    OverflowPolicy::Reject => {
+                        drop(queue);
+                        shared.record_drop();
+                        return Err(TrySendError::Full(event));
+                    }
+                }
+                shared.record_drop();
+            }
+            queue.events.push_back(event);
+            queue.events.len()
+        };
+        shared.record_depth(depth);
+        shared.notify.notify_one();
+        Ok(())
+    }
+
+    /// Events currently waiting to be received
+    pub fn depth(&self) -> usize {
+        self.shared.queue.lock().unwrap().events.len()
+    }
+
+    /// Events discarded since the sink was created
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Maximum number of queued events
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+}
+
+impl<T> Clone for BoundedEventSink<T> {
+    fn clone(&self) -> Self {
+        self.shared.queue.lock().unwrap().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for BoundedEventSink<T> {
+    fn drop(&mut self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.senders -= 1;
+        if queue.senders == 0 {
+            drop(queue);
+            // Wake the receiver so it sees the channel has closed
+            self.shared.notify.notify_one();
+        }
+    }
+}
+
+impl<T> EventReceiver<T> {
+    /// Next event, waiting until one arrives; `None` once every sink is
+    /// dropped and the queue is drained
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            {
+                let mut queue = self.shared.queue.lock().unwrap();
+                if let Some(event) = queue.events.pop_front() {
+                    let depth = queue.events.len();
+                    drop(queue);
+                    self.shared.record_depth(depth);
+                    return Some(event);
+                }
+                if queue.senders == 0 {
+                    return None;
+                }
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Next event if one is queued
+    pub fn try_recv(&mut self) -> Option<T> {
+        let mut queue = self.shared.queue.lock().unwrap();
+        let event = queue.events.pop_front();
+        let depth = queue.events.len();
+        drop(queue);
+        if event.is_some() {
+            self.shared.record_depth(depth);
+        }
+        event
+    }
+}
+
+impl<T> Drop for EventReceiver<T> {
+    fn drop(&mut self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.receiver_alive = false;
+        queue.events.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use std::time::Duration;
+
+    fn drain(events: &mut EventReceiver<u32>) -> Vec<u32> {
+        std::iter::from_fn(|| events.try_recv()).collect()
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_newest_events() {
+        let (sink, mut events) = BoundedEventSink::new("test", 3, OverflowPolicy::DropOldest);
+
+        for event in 1..=5 {
+            assert_eq!(sink.try_send(event), Ok(()));
+        }
+
+        assert_eq!(sink.depth(), 3);
+        assert_eq!(sink.dropped(), 2);
+        assert_eq!(drain(&mut events), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_reject_returns_new_event() {
+        let (sink, mut events) = BoundedEventSink::new("test", 2, OverflowPolicy::Reject);
+
+        sink.try_send(1).unwrap();
+        sink.try_send(2).unwrap();
+
+        assert_eq!(sink.try_send(3), Err(TrySendError::Full(3)));
+        assert_eq!(sink.dropped(), 1);
+        assert_eq!(drain(&mut events), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_closed_sink_returns_event() {
+        let (sink, events) = BoundedEventSink::new("test", 2, OverflowPolicy::DropOldest);
+        drop(events);
+
+        assert_eq!(sink.try_send(1).unwrap_err().into_inner(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recv_waits_and_ends_when_sinks_drop() {
+        let (sink, mut events) = BoundedEventSink::new("test", 4, OverflowPolicy::DropOldest);
+        let producer = sink.clone();
+        drop(sink);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            producer.try_send(7).unwrap();
+        });
+
+        assert_eq!(events.recv().await, Some(7));
+        assert_eq!(events.recv().await, None);
+    }
+
+    #[test]
+    fn test_depth_and_drops_are_recorded() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            let (sink, _events) = BoundedEventSink::new("audit", 2, OverflowPolicy::DropOldest);
+            for event in 0..5 {
+                sink.try_send(event).unwrap();
+            }
+        });
+        let rendered = handle.render();
+
+        assert!(
+            rendered.contains("event_sink_dropped_total{sink=\"audit\",policy=\"drop_oldest\"} 3"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("event_sink_depth{sink=\"audit\"} 2"),
+            "{}",
+            rendered
+        );
+    }
+}
@@ -0,0 +1,514 @@
+//! Cache that fails over from a remote backend to a local one
+//!
+//! [`FallbackCache`] sends every operation to its primary (usually Redis)
+//! while it is reachable. When the primary returns a connection or timeout
+//! error the operation is repeated on the fallback (usually in-memory), and
+//! later operations go straight to the fallback until `retry_interval` has
+//! passed, when the primary is probed again. Typed caches from
+//! [`CacheFactory::for_type`] fail over the same way and share the tier state.
+//!
+//! Each tier has its own [`BackendHealth`], so `cache_backend_up` and
+//! `cache_op_duration` are reported for `tier="primary"` and
+//! `tier="fallback"`. The tier serving requests is exported as
+//! `cache_backend_active` and as `active_tier` in [`CacheStats`].
+//!
+//! Entries written to the fallback while the primary is down are not copied
+//! back, so reads may miss after recovery.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use bincode::config::standard;
+use bincode::{Decode, Encode};
+use futures::future::BoxFuture;
+use metrics::gauge;
+
+use crate::core::services::cache_health::BackendHealth;
+use crate::core::services::cache_provider::{
+    CacheConfig, CacheError, CacheFactory, CacheStats, DynCacheOperations, TypedCache,
+    TypedCacheFactory,
+};
+use crate::core::services::cache_service::CacheHelpers;
+
+/// Default time between attempts to reach a primary that is down
+pub const DEFAULT_PRIMARY_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Tier health shared by a [`FallbackCache`] and its typed caches
+struct Failover {
+    name: String,
+    primary_health: BackendHealth,
+    fallback_health: BackendHealth,
+    last_primary_attempt: Mutex<Instant>,
+}
+
+impl Failover {
+    fn new(name: String) -> Self {
+        let failover = Self {
+            primary_health: BackendHealth::new(name.clone(), "primary"),
+            fallback_health: BackendHealth::new(name.clone(), "fallback"),
+            name,
+            last_primary_attempt: Mutex::new(Instant::now()),
+        };
+        failover.record_active();
+        failover
+    }
+
+    fn active_tier(&self) -> &str {
+        if self.primary_health.is_up() {
+            self.primary_health.tier()
+        } else {
+            self.fallback_health.tier()
+        }
+    }
+
+    fn should_try_primary(&self, retry_interval: Duration) -> bool {
+        if self.primary_health.is_up() {
+            return true;
+        }
+        let mut last_attempt = self.last_primary_attempt.lock().unwrap();
+        if last_attempt.elapsed() < retry_interval {
+            return false;
+        }
+        *last_attempt = Instant::now();
+        true
+    }
+
+    /// Run `operation` on the primary; `None` if it could not be reached
+    async fn on_primary<T, F>(&self, operation: F) -> Option<Result<T, CacheError>>
+    where
+        F: Future<Output = Result<T, CacheError>>,
+    {
+        match self.primary_health.track(operation).await {
+            Err(CacheError::Connection(_)) | Err(CacheError::Timeout(_)) => {
+                *self.last_primary_attempt.lock().unwrap() = Instant::now();
+                None
+            }
+            result => {
+                self.record_active();
+                Some(result)
+            }
+        }
+    }
+
+    async fn on_fallback<T, F>(&self, operation: F) -> Result<T, CacheError>
+    where
+        F: Future<Output = Result<T, CacheError>>,
+    {
+        self.record_active();
+        self.fallback_health.track(operation).await
+    }
+
+    fn record_active(&self) {
+        let active = self.active_tier();
+        for tier in [self.primary_health.tier(), self.fallback_health.tier()] {
+            gauge!(
+                "cache_backend_active",
+                "cache" => self.name.clone(),
+                "tier" => tier.to_string()
+            )
+            .set(if tier == active { 1.0 } else { 0.0 });
+        }
+    }
+}
+
+/// A primary cache with a fallback tier used while it is unreachable
+#[derive(Clone)]
+pub struct FallbackCache {
+    primary: Arc<dyn DynCacheOperations>,
+    fallback: Arc<dyn DynCacheOperations>,
+    failover: Arc<Failover>,
+    retry_interval: Duration,
+}
+
+impl FallbackCache {
+    /// Serve from `primary`, falling back to `fallback` when it is unreachable
+    pub fn new(
+        primary: Box<dyn DynCacheOperations>,
+        fallback: Box<dyn DynCacheOperations>,
+    ) -> Self {
+        Self {
+            failover: Arc::new(Failover::new(primary.name().to_string())),
+            primary: Arc::from(primary),
+            fallback: Arc::from(fallback),
+            retry_interval: DEFAULT_PRIMARY_RETRY_INTERVAL,
+        }
+    }
+
+    /// Set how long to wait before probing a primary that is down
+    pub fn with_retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// `"primary"` or `"fallback"`, whichever is serving requests
+    pub fn active_tier(&self) -> &str {
+        self.failover.active_tier()
+    }
+
+    /// Health of the primary tier
+    pub fn primary_health(&self) -> &BackendHealth {
+        &self.failover.primary_health
+    }
+
+    /// Health of the fallback tier
+    pub fn fallback_health(&self) -> &BackendHealth {
+        &self.failover.fallback_health
+    }
+
+    /// Run `op` on the primary if it may be reachable, else on the fallback
+    async fn route<'a, T, F>(&'a self, op: F) -> Result<T, CacheError>
+    where
+        F: Fn(&'a dyn DynCacheOperations) -> BoxFuture<'a, Result<T, CacheError>>,
+    {
+        if self.failover.should_try_primary(self.retry_interval) {
+            if let Some(result) = self.failover.on_primary(op(self.primary.as_ref())).await {
+                return result;
+            }
+        }
+        self.failover.on_fallback(op(self.fallback.as_ref())).await
+    }
+}
+
+impl CacheFactory for FallbackCache {
+    fn for_type<T>(&self) -> Box<dyn TypedCacheFactory<T>>
+    where
+        T: Encode + Decode<()> + Send + Sync + 'static,
+    {
+        Box::new(FallbackTypedCache {
+            primary: Arc::from(self.primary.as_ref().get_typed_cache::<T>()),
+            fallback: Arc::from(self.fallback.as_ref().get_typed_cache::<T>()),
+            failover: Arc::clone(&self.failover),
+            retry_interval: self.retry_interval,
+        })
+    }
+}
+
+#[async_trait]
+impl DynCacheOperations for FallbackCache {
+    async fn delete(&self, key: &str) -> Result<bool, CacheError> {
+        self.route(|cache| cache.delete(key)).await
+    }
+
+    async fn clear(&self) -> Result<(), CacheError> {
+        self.route(|cache| cache.clear()).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, CacheError> {
+        self.route(|cache| cache.exists(key)).await
+    }
+
+    async fn delete_many(&self, keys: &[&str]) -> Result<usize, CacheError> {
+        self.route(|cache| cache.delete_many(keys)).await
+    }
+
+    async fn increment(&self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.route(|cache| cache.increment(key, delta)).await
+    }
+
+    async fn ttl(&self, key: &str) -> Result<Option<Duration>, CacheError> {
+        self.route(|cache| cache.ttl(key)).await
+    }
+
+    async fn touch(&self, key: &str, ttl_seconds: u64) -> Result<bool, CacheError> {
+        self.route(|cache| cache.touch(key, ttl_seconds)).await
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<usize, CacheError> {
+        self.route(|cache| cache.invalidate_tag(tag)).await
+    }
+
+    fn stats(&self) -> Result<CacheStats, CacheError> {
+        let mut stats = if self.failover.primary_health.is_up() {
+            self.primary.stats()?
+        } else {
+            self.fallback.stats()?
+        };
+        stats
+            .custom_metrics
+            .insert("active_tier".to_string(), self.active_tier().to_string());
+        self.failover.primary_health.add_to(&mut stats);
+        self.failover.fallback_health.add_to(&mut stats);
+        Ok(stats)
+    }
+
+    fn name(&self) -> &str {
+        self.primary.name()
+    }
+
+    fn config(&self) -> &CacheConfig {
+        self.primary.config()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Typed view of a [`FallbackCache`]
+pub struct FallbackTypedCache<T> {
+    primary: Arc<dyn TypedCache<T>>,
+    fallback: Arc<dyn TypedCache<T>>,
+    failover: Arc<Failover>,
+    retry_interval: Duration,
+}
+
+impl<T> Clone for FallbackTypedCache<T> {
+    fn clone(&self) -> Self {
+        Self {
+            primary: Arc::clone(&self.primary),
+            fallback: Arc::clone(&self.fallback),
+            failover: Arc::clone(&self.failover),
+            retry_interval: self.retry_interval,
+        }
+    }
+}
+
+impl<T> FallbackTypedCache<T>
+where
+    T: Encode + Decode<()> + Send + Sync + 'static,
+{
+    async fn route<'a, R, F>(&'a self, op: F) -> Result<R, CacheError>
+    where
+        F: Fn(&'a dyn TypedCache<T>) -> BoxFuture<'a, Result<R, CacheError>>,
+    {
+        if self.failover.should_try_primary(self.retry_interval) {
+            if let Some(result) = self.failover.on_primary(op(self.primary.as_ref())).await {
+                return result;
+            }
+        }
+        self.failover.on_fallback(op(self.fallback.as_ref())).await
+    }
+
+    /// Write `value` to the primary, keeping a copy for the fallback in case
+    /// the primary cannot be reached
+    async fn write<'a, W, F>(&'a self, value: W, op: F) -> Result<(), CacheError>
+    where
+        W: Spare,
+        F: Fn(&'a dyn TypedCache<T>, W) -> BoxFuture<'a, Result<(), CacheError>>,
+    {
+        if self.failover.should_try_primary(self.retry_interval) {
+            let spare = value.spare()?;
+            if let Some(result) = self
+                .failover
+                .on_primary(op(self.primary.as_ref(), spare))
+                .await
+            {
+                return result;
+            }
+        }
+        self.failover
+            .on_fallback(op(self.fallback.as_ref(), value))
+            .await
+    }
+}
+
+/// Values that can be copied through their encoding
+trait Spare: Sized {
+    fn spare(&self) -> Result<Self, CacheError>;
+}
+
+impl<T: Encode + Decode<()>> Spare for T {
+    fn spare(&self) -> Result<Self, CacheError> {
+        let bytes = bincode::encode_to_vec(self, standard())
+            .map_err(|e| CacheError::Serialization(e.to_string()))?;
+        bincode::decode_from_slice(&bytes, standard())
+            .map(|(value, _)| value)
+            .map_err(|e| CacheError::Deserialization(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl<T> TypedCache<T> for FallbackTypedCache<T>
+where
+    T: Encode + Decode<()> + Send + Sync + 'static,
+{
+    async fn get(&self, key: &str) -> Result<Option<T>, CacheError> {
+        self.route(|cache| cache.get(key)).await
+    }
+
+    async fn set(&self, key: &str, value: T, ttl: Option<Duration>) -> Result<(), CacheError> {
+        self.write(value, |cache, value| cache.set(key, value, ttl))
+            .await
+    }
+
+    async fn set_many(
+        &self,
+        items: HashMap<String, T>,
+        ttl: Option<Duration>,
+    ) -> Result<(), CacheError> {
+        self.write(items, |cache, items| cache.set_many(items, ttl))
+            .await
+    }
+
+    async fn get_many(&self, keys: &[&str]) -> Result<HashMap<String, Option<T>>, CacheError> {
+        self.route(|cache| cache.get_many(keys)).await
+    }
+
+    async fn set_with_tags(
+        &self,
+        key: &str,
+        value: T,
+        ttl: Option<Duration>,
+        tags: &[&str],
+    ) -> Result<(), CacheError> {
+        self.write(value, |cache, value| {
+            cache.set_with_tags(key, value, ttl, tags)
+        })
+        .await
+    }
+}
+
+impl<T> TypedCacheFactory<T> for FallbackTypedCache<T>
+where
+    T: Encode + Decode<()> + Send + Sync + 'static,
+{
+    fn create_typed_cache(&self) -> Box<dyn TypedCache<T>> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::services::memory_cache::InMemoryCache;
+    use futures::executor::block_on;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    /// A primary whose connection is refused
+    struct Unreachable(CacheConfig);
+
+    impl Unreachable {
+        fn refused<T>(&self) -> Result<T, CacheError> {
+            Err(CacheError::Connection("connection refused".to_string()))
+        }
+    }
+
+    #[async_trait]
+    impl DynCacheOperations for Unreachable {
+        async fn delete(&self, _key: &str) -> Result<bool, CacheError> {
+            self.refused()
+        }
+        async fn clear(&self) -> Result<(), CacheError> {
+            self.refused()
+        }
+        async fn exists(&self, _key: &str) -> Result<bool, CacheError> {
+            self.refused()
+        }
+        async fn delete_many(&self, _keys: &[&str]) -> Result<usize, CacheError> {
+            self.refused()
+        }
+        async fn increment(&self, _key: &str, _delta: i64) -> Result<i64, CacheError> {
+            self.refused()
+        }
+        async fn ttl(&self, _key: &str) -> Result<Option<Duration>, CacheError> {
+            self.refused()
+        }
+        async fn touch(&self, _key: &str, _ttl_seconds: u64) -> Result<bool, CacheError> {
+            self.refused()
+        }
+        fn stats(&self) -> Result<CacheStats, CacheError> {
+            self.refused()
+        }
+        fn name(&self) -> &str {
+            &self.0.name
+        }
+        fn config(&self) -> &CacheConfig {
+            &self.0
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn config(name: &str) -> CacheConfig {
+        CacheConfig {
+            name: name.to_string(),
+            default_ttl: None,
+            ..Default::default()
+        }
+    }
+
+    fn failing_primary() -> FallbackCache {
+        FallbackCache::new(
+            Box::new(Unreachable(config("sessions"))),
+            Box::new(InMemoryCache::new(config("sessions-local"))),
+        )
+        .with_retry_interval(Duration::from_secs(60))
+    }
+
+    #[test]
+    fn test_failing_primary_reports_down_and_fallback_active() {
+        let cache = failing_primary();
+
+        assert_eq!(block_on(cache.increment("visits", 2)).unwrap(), 2);
+        assert_eq!(block_on(cache.increment("visits", 3)).unwrap(), 5);
+        assert_eq!(cache.active_tier(), "fallback");
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.custom_metrics["active_tier"], "fallback");
+        assert_eq!(stats.custom_metrics["primary.up"], "0");
+        assert_eq!(stats.custom_metrics["fallback.up"], "1");
+    }
+
+    #[test]
+    fn test_backend_health_is_exported_as_metrics() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            let cache = failing_primary();
+            block_on(cache.exists("visits")).unwrap();
+        });
+        let rendered = handle.render();
+
+        for expected in [
+            "cache_backend_up{cache=\"sessions\",tier=\"primary\"} 0",
+            "cache_backend_up{cache=\"sessions\",tier=\"fallback\"} 1",
+            "cache_backend_active{cache=\"sessions\",tier=\"fallback\"} 1",
+            "cache_backend_active{cache=\"sessions\",tier=\"primary\"} 0",
+            "cache_op_duration_count{cache=\"sessions\",tier=\"primary\"} 1",
+        ] {
+            assert!(rendered.contains(expected), "{}\n{}", expected, rendered);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_config_builds_typed_fallback() {
+        use crate::core::services::cache_provider::CacheProviderRegistry;
+        use crate::core::services::memory_cache::InMemoryCacheProvider;
+        use crate::core::services::redis_cache::RedisCacheProvider;
+
+        let mut registry = CacheProviderRegistry::new();
+        registry.register(RedisCacheProvider::new());
+        registry.register(InMemoryCacheProvider::new());
+        let cache = registry
+            .create_cache(CacheConfig {
+                name: "sessions".to_string(),
+                provider: "redis".to_string(),
+                provider_config: HashMap::from([
+                    ("url".to_string(), "redis://localhost:6379".to_string()),
+                    ("fallback".to_string(), "memory".to_string()),
+                ]),
+                ..config("sessions")
+            })
+            .await
+            .unwrap();
+
+        let typed = cache.as_ref().get_typed_cache::<String>();
+        typed
+            .set("greeting", "hello".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            typed.get("greeting").await.unwrap(),
+            Some("hello".to_string())
+        );
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.custom_metrics["active_tier"], "fallback");
+        assert_eq!(stats.custom_metrics["primary.up"], "0");
+    }
+}
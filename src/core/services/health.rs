@@ -10,7 +10,13 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{info, warn};
 
-use crate::core::{error::AppError, models::DependencyStatus, router::AppState};
+use crate::core::{
+    config::app_config::Criticality,
+    error::AppError,
+    models::DependencyStatus,
+    router::AppState,
+    services::health_aggregate::{ComponentHealth, HealthAggregate, HealthState},
+};
 
 /// Trait for implementing health indicators
 pub trait HealthIndicator: Send + Sync {
@@ -143,19 +149,24 @@ impl HealthService {
     }
 
     /// Check health of all components
+    ///
+    /// Every indicator is critical unless `health.criticality` says otherwise.
     pub async fn check_health(&self, state: &Arc<AppState>) -> Result<Value, AppError> {
-        let mut status = "UP".to_string();
+        let mut component_health = Vec::new();
         let mut components = HashMap::new();
 
         // Check each health indicator
         for indicator in &self.indicators {
             let result = indicator.check_health(state);
 
-            // If any component is down, the overall status is down
             if result.status != "UP" {
                 warn!("Health check failed for {}: {}", result.name, result.status);
-                status = "DOWN".to_string();
             }
+            component_health.push(ComponentHealth::new(
+                result.name.clone(),
+                HealthState::from_status(&result.status),
+                Criticality::Critical,
+            ));
 
             // Add component details to response
             let component_details = json!({
@@ -166,9 +177,11 @@ impl HealthService {
             components.insert(result.name, component_details);
         }
 
+        let health = HealthAggregate::new(component_health, &state.config.health.criticality);
+
         // Create Spring Boot-style health response
         Ok(json!({
-            "status": status,
+            "status": health.status.as_str(),
             "components": components
         }))
     }
@@ -191,6 +204,7 @@ mod tests {
             metrics_handle: None,
             resource_registry: None,
             service_registry: Arc::new(crate::core::router::ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create health service
@@ -213,6 +227,24 @@ mod tests {
         // Verify cache is down since we didn't provide it
         let cache = components.get("cache").unwrap();
         assert_eq!(cache.get("status").unwrap(), "DOWN");
+        assert_eq!(result["status"], "DOWN");
+    }
+
+    #[tokio::test]
+    async fn test_health_service_applies_criticality_overrides() {
+        let mut config = AppConfig::default();
+        config
+            .health
+            .criticality
+            .insert("cache".to_string(), Criticality::NonCritical);
+        let state = Arc::new(AppState {
+            config,
+            ..Default::default()
+        });
+
+        let result = HealthService::new().check_health(&state).await.unwrap();
+        assert_eq!(result["components"]["cache"]["status"], "DOWN");
+        assert_eq!(result["status"], "DEGRADED");
     }
 
     #[test]
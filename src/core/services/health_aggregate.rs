@@ -0,0 +1,190 @@
+//! Overall health from component statuses
+//!
+//! Every component (health indicators, downstream dependencies, auth
+//! providers) is reported with a [`Criticality`] that decides how much it
+//! counts: a critical component that is down makes the service `DOWN`, a
+//! non-critical one only makes it `DEGRADED`, and ignored components are
+//! listed without affecting the result. Components default to the
+//! criticality they declare; `health.criticality` overrides it by name.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::core::config::app_config::Criticality;
+
+/// Health of a component or of the whole service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HealthState {
+    Up,
+    Degraded,
+    Down,
+}
+
+impl HealthState {
+    /// Parse an indicator status; anything but `UP` or `DEGRADED` is down
+    pub fn from_status(status: &str) -> Self {
+        match status.to_ascii_uppercase().as_str() {
+            "UP" => HealthState::Up,
+            "DEGRADED" => HealthState::Degraded,
+            _ => HealthState::Down,
+        }
+    }
+
+    /// `UP`, `DEGRADED` or `DOWN`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HealthState::Up => "UP",
+            HealthState::Degraded => "DEGRADED",
+            HealthState::Down => "DOWN",
+        }
+    }
+}
+
+/// Status of one component
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentHealth {
+    pub name: String,
+    pub status: HealthState,
+    pub criticality: Criticality,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl ComponentHealth {
+    pub fn new(name: impl Into<String>, status: HealthState, criticality: Criticality) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            criticality,
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    /// Health of an authentication provider: down unless ready, degraded
+    /// while its JWKS cannot be validated
+    #[cfg(feature = "auth")]
+    pub fn from_auth_provider(
+        name: impl Into<String>,
+        status: &crate::core::auth::providers::HealthStatus,
+        criticality: Criticality,
+    ) -> Self {
+        let state = match (status.ready, status.jwks_valid) {
+            (true, true) => HealthState::Up,
+            (true, false) => HealthState::Degraded,
+            (false, _) => HealthState::Down,
+        };
+        let component = Self::new(name, state, criticality);
+        match &status.error {
+            Some(error) => component.with_details(error.clone()),
+            None => component,
+        }
+    }
+
+    /// How much this component lowers the overall status
+    fn impact(&self) -> HealthState {
+        match (self.criticality, self.status) {
+            (Criticality::Ignored, _) | (_, HealthState::Up) => HealthState::Up,
+            (Criticality::Critical, status) => status,
+            (Criticality::NonCritical, _) => HealthState::Degraded,
+        }
+    }
+}
+
+/// Overall health with the per-component breakdown
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthAggregate {
+    pub status: HealthState,
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthAggregate {
+    /// Combine component statuses, applying `overrides` to their criticality
+    pub fn new(
+        mut components: Vec<ComponentHealth>,
+        overrides: &HashMap<String, Criticality>,
+    ) -> Self {
+        for component in &mut components {
+            if let Some(criticality) = overrides.get(&component.name) {
+                component.criticality = *criticality;
+            }
+        }
+        let status = components
+            .iter()
+            .map(ComponentHealth::impact)
+            .max()
+            .unwrap_or(HealthState::Up);
+        Self { status, components }
+    }
+
+    /// Components that lower the status without taking the service down
+    pub fn degraded(&self) -> impl Iterator<Item = &ComponentHealth> {
+        self.components
+            .iter()
+            .filter(|component| component.impact() == HealthState::Degraded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn components(cache: HealthState, database: HealthState) -> Vec<ComponentHealth> {
+        vec![
+            ComponentHealth::new("database", database, Criticality::Critical),
+            ComponentHealth::new("cache", cache, Criticality::NonCritical),
+            ComponentHealth::new("env", HealthState::Down, Criticality::Ignored),
+        ]
+    }
+
+    fn aggregate(cache: HealthState, database: HealthState) -> HealthAggregate {
+        HealthAggregate::new(components(cache, database), &HashMap::new())
+    }
+
+    #[test]
+    fn test_all_up_is_up() {
+        let health = aggregate(HealthState::Up, HealthState::Up);
+        assert_eq!(health.status, HealthState::Up);
+        // Ignored components are still listed
+        assert_eq!(health.components.len(), 3);
+    }
+
+    #[test]
+    fn test_non_critical_down_is_degraded() {
+        let health = aggregate(HealthState::Down, HealthState::Up);
+        assert_eq!(health.status, HealthState::Degraded);
+        let degraded: Vec<&str> = health.degraded().map(|c| c.name.as_str()).collect();
+        assert_eq!(degraded, vec!["cache"]);
+    }
+
+    #[test]
+    fn test_critical_down_is_down() {
+        let health = aggregate(HealthState::Down, HealthState::Down);
+        assert_eq!(health.status, HealthState::Down);
+
+        let health = aggregate(HealthState::Up, HealthState::Degraded);
+        assert_eq!(health.status, HealthState::Degraded);
+    }
+
+    #[test]
+    fn test_overrides_change_criticality() {
+        let overrides = HashMap::from([
+            ("database".to_string(), Criticality::NonCritical),
+            ("cache".to_string(), Criticality::Critical),
+        ]);
+
+        let health =
+            HealthAggregate::new(components(HealthState::Up, HealthState::Down), &overrides);
+        assert_eq!(health.status, HealthState::Degraded);
+
+        let health =
+            HealthAggregate::new(components(HealthState::Down, HealthState::Up), &overrides);
+        assert_eq!(health.status, HealthState::Down);
+    }
+}
@@ -0,0 +1,132 @@
+//! Short-lived caching of health check results
+//!
+//! Readiness probes can arrive every second, and each check pings the
+//! database and downstream dependencies. [`HealthResultCache`] keeps the
+//! last result for `health.readiness_cache_ms` so rapid probes reuse it.
+//! Probes arriving while a check is running wait for that check instead of
+//! starting their own.
+
+use std::future::Future;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Most recent health result and when it was checked
+#[derive(Debug, Default)]
+pub struct HealthResultCache {
+    last: Mutex<Option<(Instant, Value)>>,
+}
+
+impl HealthResultCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached result if younger than `ttl`, otherwise run `check` and cache it
+    ///
+    /// `force` always runs `check`. A zero `ttl` disables caching.
+    pub async fn get_or_check<F, Fut>(&self, ttl: Duration, force: bool, check: F) -> Value
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Value>,
+    {
+        let mut last = self.last.lock().await;
+        let fresh = last
+            .as_ref()
+            .filter(|(checked_at, _)| !force && checked_at.elapsed() < ttl);
+        if let Some((_, result)) = fresh {
+            return result.clone();
+        }
+
+        let result = check().await;
+        *last = Some((Instant::now(), result.clone()));
+        result
+    }
+
+    /// Forget the cached result
+    pub async fn invalidate(&self) {
+        *self.last.lock().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn check_counting(
+        cache: &HealthResultCache,
+        checks: &AtomicUsize,
+        ttl: Duration,
+        force: bool,
+    ) -> Value {
+        cache
+            .get_or_check(ttl, force, || async {
+                let n = checks.fetch_add(1, Ordering::SeqCst) + 1;
+                json!({ "status": "UP", "check": n })
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_reuses_result_within_ttl() {
+        let cache = HealthResultCache::new();
+        let checks = AtomicUsize::new(0);
+        let ttl = Duration::from_secs(60);
+
+        for _ in 0..5 {
+            let result = check_counting(&cache, &checks, ttl, false).await;
+            assert_eq!(result["check"], 1);
+        }
+
+        assert_eq!(checks.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rechecks_after_ttl() {
+        let cache = HealthResultCache::new();
+        let checks = AtomicUsize::new(0);
+        let ttl = Duration::from_millis(20);
+
+        check_counting(&cache, &checks, ttl, false).await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        let result = check_counting(&cache, &checks, ttl, false).await;
+
+        assert_eq!(result["check"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_force_and_zero_ttl_bypass_cache() {
+        let cache = HealthResultCache::new();
+        let checks = AtomicUsize::new(0);
+
+        check_counting(&cache, &checks, Duration::from_secs(60), false).await;
+        check_counting(&cache, &checks, Duration::from_secs(60), true).await;
+        check_counting(&cache, &checks, Duration::ZERO, false).await;
+
+        assert_eq!(checks.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_probes_share_one_check() {
+        let cache = HealthResultCache::new();
+        let checks = AtomicUsize::new(0);
+        let ttl = Duration::from_secs(60);
+
+        let slow_check = || async {
+            checks.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            json!({ "status": "UP" })
+        };
+        let (a, b) = tokio::join!(
+            cache.get_or_check(ttl, false, slow_check),
+            cache.get_or_check(ttl, false, slow_check)
+        );
+
+        assert_eq!(a, b);
+        assert_eq!(checks.load(Ordering::SeqCst), 1);
+    }
+}
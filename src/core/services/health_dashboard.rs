@@ -6,9 +6,11 @@ use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tracing::{debug, info, warn};
 
+use crate::core::config::app_config::Criticality;
 use crate::core::models::DependencyStatus;
 use crate::core::router::AppState;
 use crate::core::services::error::ServiceError;
+use crate::core::services::health_aggregate::{ComponentHealth, HealthAggregate, HealthState};
 use crate::core::services::health_discovery::HealthDiscoveryService;
 use crate::core::services::health_provider::{HealthConfig, HealthIndicator};
 
@@ -128,8 +130,8 @@ impl HealthDashboardService {
 
         let indicators = discovery_read.get_all_indicators();
 
-        // Track overall status and performance
-        let mut aggregated_status = "UP".to_string();
+        // Track component health and performance
+        let mut component_health = Vec::new();
         let mut components = serde_json::Map::new();
         let mut component_statuses = HashMap::new();
         let check_start = Instant::now();
@@ -146,10 +148,11 @@ impl HealthDashboardService {
             // Store component status for history
             component_statuses.insert(indicator_name.clone(), result.status.clone());
 
-            // If any critical component is down, the overall status is down
-            if result.status != "UP" && indicator.is_critical() {
-                aggregated_status = "DOWN".to_string();
-            }
+            component_health.push(ComponentHealth::new(
+                indicator_name.clone(),
+                HealthState::from_status(&result.status),
+                Criticality::from_critical(indicator.is_critical()),
+            ));
 
             // Add component details to response
             if self.config.detailed_components {
@@ -187,6 +190,13 @@ impl HealthDashboardService {
         // Calculate total health check duration
         let check_duration = check_start.elapsed();
 
+        // `health.criticality` overrides what the indicators declare
+        let aggregated_status =
+            HealthAggregate::new(component_health, &state.config.health.criticality)
+                .status
+                .as_str()
+                .to_string();
+
         // Update history
         {
             let mut history = self.history.write().map_err(|e| {
@@ -374,6 +384,7 @@ mod tests {
             metrics_handle: None,
             resource_registry: None,
             service_registry: Arc::new(crate::core::router::ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create registry
@@ -466,4 +477,38 @@ mod tests {
         assert!(result["components"]["test1"].is_object());
         assert!(!result.get("components").unwrap().get("test2").is_some());
     }
+
+    #[tokio::test]
+    async fn test_health_dashboard_applies_criticality_overrides() {
+        let mut config = AppConfig::default();
+        config
+            .health
+            .criticality
+            .insert("optional".to_string(), Criticality::NonCritical);
+        config
+            .health
+            .criticality
+            .insert("noisy".to_string(), Criticality::Ignored);
+        let state = Arc::new(AppState {
+            config,
+            ..Default::default()
+        });
+
+        let discovery = Arc::new(RwLock::new(HealthDiscoveryService::new(
+            Arc::new(HealthIndicatorProviderRegistry::new()),
+            HealthConfig::default(),
+        )));
+        let dashboard = HealthDashboardService::new(discovery, HealthDashboardConfig::default());
+        for name in ["optional", "noisy"] {
+            dashboard
+                .register_indicator(Box::new(TestHealthIndicator {
+                    name: name.to_string(),
+                    status: "DOWN".to_string(),
+                }))
+                .unwrap();
+        }
+
+        let result = dashboard.check_health(&state).await.unwrap();
+        assert_eq!(result["status"], "DEGRADED");
+    }
 }
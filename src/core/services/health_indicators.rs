@@ -309,6 +309,7 @@ mod tests {
             metrics_handle: None,
             resource_registry: None,
             service_registry: Arc::new(crate::core::router::ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         let indicator = CacheHealthIndicator;
@@ -4,9 +4,14 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use serde_json::Value;
 
+#[cfg(feature = "auth")]
+use crate::core::auth::providers::ProviderRegistry;
+use crate::core::config::app_config::Criticality;
 use crate::core::models::DependencyStatus;
 use crate::core::router::AppState;
+use crate::core::services::downstream_health::DownstreamHealthCheck;
 use crate::core::services::error::ServiceError;
+use crate::core::services::health_aggregate::{ComponentHealth, HealthAggregate, HealthState};
 
 /// Enhanced HealthIndicator trait for health checks
 pub trait HealthIndicator: Send + Sync + 'static {
@@ -129,74 +134,159 @@ impl HealthIndicatorProviderRegistry {
 pub struct HealthServiceV2 {
     registry: Arc<HealthIndicatorProviderRegistry>,
     config: HealthConfig,
+    downstream: Vec<DownstreamHealthCheck>,
+    criticality: HashMap<String, Criticality>,
+    #[cfg(feature = "auth")]
+    auth_providers: Option<Arc<ProviderRegistry>>,
 }
 
 impl HealthServiceV2 {
     /// Create a new health service with the given registry and config
     pub fn new(registry: Arc<HealthIndicatorProviderRegistry>, config: HealthConfig) -> Self {
-        Self { registry, config }
+        Self {
+            registry,
+            config,
+            downstream: Vec::new(),
+            criticality: HashMap::new(),
+            #[cfg(feature = "auth")]
+            auth_providers: None,
+        }
+    }
+
+    /// Override the criticality of components by name
+    pub fn with_criticality(mut self, criticality: HashMap<String, Criticality>) -> Self {
+        self.criticality = criticality;
+        self
+    }
+
+    /// Also probe downstream HTTP dependencies
+    pub fn with_downstream(mut self, checks: Vec<DownstreamHealthCheck>) -> Self {
+        self.downstream = checks;
+        self
+    }
+
+    /// Also report the authentication providers, as non-critical `auth.<name>`
+    /// components unless overridden
+    #[cfg(feature = "auth")]
+    pub fn with_auth_providers(mut self, providers: Arc<ProviderRegistry>) -> Self {
+        self.auth_providers = Some(providers);
+        self
     }
 
     /// Check health of all components
+    ///
+    /// The overall status is aggregated by criticality (see
+    /// [`HealthAggregate`]): a critical component that is not `UP` makes it
+    /// `DOWN`, a non-critical one `DEGRADED`. Components that are not up
+    /// without taking the service down are listed under `degraded`.
     pub async fn check_health(&self, state: &Arc<AppState>) -> Result<Value, ServiceError> {
         let indicators = self.registry.get_indicators(&self.config);
-        let mut aggregated_status = "UP".to_string();
-        let mut components = serde_json::Map::new();
+        let mut results: Vec<(DependencyStatus, bool, HashMap<String, String>)> = indicators
+            .iter()
+            .map(|indicator| {
+                let mut status = indicator.check_health(state);
+                status.name = indicator.name();
+                (status, indicator.is_critical(), indicator.metadata())
+            })
+            .collect();
+
+        if !self.downstream.is_empty() {
+            let client = state.http_client();
+            let statuses =
+                futures::future::join_all(self.downstream.iter().map(|check| check.check(client)))
+                    .await;
+            for (check, mut status) in self.downstream.iter().zip(statuses) {
+                let metadata =
+                    HashMap::from([("critical".to_string(), check.critical.to_string())]);
+                status.name = check.name.clone();
+                results.push((status, check.critical, metadata));
+            }
+        }
 
-        // Check each health indicator
-        for indicator in indicators {
-            let result = indicator.check_health(state);
+        let mut metadata_by_name = HashMap::new();
+        let components: Vec<ComponentHealth> = results
+            .into_iter()
+            .map(|(status, critical, metadata)| {
+                metadata_by_name.insert(status.name.clone(), metadata);
+                let component = ComponentHealth::new(
+                    status.name,
+                    HealthState::from_status(&status.status),
+                    Criticality::from_critical(critical),
+                );
+                match status.details {
+                    Some(details) => component.with_details(details),
+                    None => component,
+                }
+            })
+            .collect();
+        #[cfg(feature = "auth")]
+        let components = self.with_auth_components(components).await;
+        let health = HealthAggregate::new(components, &self.criticality);
 
-            // If any critical component is down, the overall status is down
-            if result.status != "UP" && indicator.is_critical() {
-                aggregated_status = "DOWN".to_string();
-            }
+        // Build the response
+        let mut response = serde_json::Map::new();
+        response.insert(
+            "status".to_string(),
+            Value::String(health.status.as_str().to_string()),
+        );
+
+        let degraded: Vec<Value> = health
+            .degraded()
+            .map(|component| Value::String(component.name.clone()))
+            .collect();
+        if !degraded.is_empty() {
+            response.insert("degraded".to_string(), Value::Array(degraded));
+        }
 
-            // If we're showing components, add to response
-            if self.config.show_components {
+        if self.config.show_components {
+            let mut components = serde_json::Map::new();
+            for component in health.components {
                 let mut component_details = serde_json::Map::new();
                 component_details.insert(
                     "status".to_string(),
-                    serde_json::Value::String(result.status),
+                    Value::String(component.status.as_str().to_string()),
+                );
+                component_details.insert(
+                    "criticality".to_string(),
+                    serde_json::to_value(component.criticality).unwrap_or_default(),
                 );
 
-                if self.config.show_details && result.details.is_some() {
-                    component_details.insert(
-                        "details".to_string(),
-                        serde_json::Value::String(result.details.unwrap()),
-                    );
+                let details = component.details.filter(|_| self.config.show_details);
+                if let Some(details) = details {
+                    component_details.insert("details".to_string(), Value::String(details));
                 }
 
                 // Add metadata if present
-                let metadata = indicator.metadata();
-                if !metadata.is_empty() {
-                    for (k, v) in metadata {
-                        component_details.insert(k, serde_json::Value::String(v));
-                    }
+                for (k, v) in metadata_by_name.remove(&component.name).unwrap_or_default() {
+                    component_details.insert(k, Value::String(v));
                 }
 
-                components.insert(
-                    indicator.name(),
-                    serde_json::Value::Object(component_details),
-                );
+                components.insert(component.name, Value::Object(component_details));
             }
+            response.insert("components".to_string(), Value::Object(components));
         }
 
-        // Build the response
-        let mut response = serde_json::Map::new();
-        response.insert(
-            "status".to_string(),
-            serde_json::Value::String(aggregated_status),
-        );
+        Ok(Value::Object(response))
+    }
 
-        if self.config.show_components {
-            response.insert(
-                "components".to_string(),
-                serde_json::Value::Object(components),
-            );
+    /// `components` followed by the authentication providers, ordered by name
+    #[cfg(feature = "auth")]
+    async fn with_auth_components(
+        &self,
+        mut components: Vec<ComponentHealth>,
+    ) -> Vec<ComponentHealth> {
+        if let Some(providers) = &self.auth_providers {
+            let mut statuses: Vec<_> = providers.check_health().await.into_iter().collect();
+            statuses.sort_by(|a, b| a.0.cmp(&b.0));
+            components.extend(statuses.into_iter().map(|(name, status)| {
+                ComponentHealth::from_auth_provider(
+                    format!("auth.{}", name),
+                    &status,
+                    Criticality::NonCritical,
+                )
+            }));
         }
-
-        Ok(serde_json::Value::Object(response))
+        components
     }
 }
 
@@ -297,6 +387,7 @@ mod tests {
             metrics_handle: None,
             resource_registry: None,
             service_registry: Arc::new(crate::core::router::ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create registry with test provider
@@ -330,6 +421,7 @@ mod tests {
             metrics_handle: None,
             resource_registry: None,
             service_registry: Arc::new(crate::core::router::ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create registry with down provider
@@ -348,6 +440,18 @@ mod tests {
         assert!(result["components"].is_object());
         assert!(result["components"]["test"].is_object());
         assert_eq!(result["components"]["test"]["status"], "DOWN");
+        assert_eq!(result["components"]["test"]["criticality"], "critical");
+
+        // Overridden as non-critical, the same component only degrades the service
+        let mut registry = HealthIndicatorProviderRegistry::new();
+        registry.register(Box::new(TestDownProvider));
+        let health_service =
+            HealthServiceV2::new(Arc::new(registry), HealthConfig::default()).with_criticality(
+                HashMap::from([("test".to_string(), Criticality::NonCritical)]),
+            );
+        let result = health_service.check_health(&state).await.unwrap();
+        assert_eq!(result["status"], "DEGRADED");
+        assert_eq!(result["degraded"], serde_json::json!(["test"]));
     }
 
     #[tokio::test]
@@ -362,6 +466,7 @@ mod tests {
             metrics_handle: None,
             resource_registry: None,
             service_registry: Arc::new(crate::core::router::ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create registry with test provider
@@ -381,4 +486,78 @@ mod tests {
         assert_eq!(result["status"], "UP");
         assert!(result.get("components").is_none());
     }
+
+    #[cfg(feature = "auth")]
+    #[derive(Clone)]
+    struct UnreadyAuthProvider {
+        config: crate::core::config::app_config::AuthConfig,
+    }
+
+    #[cfg(feature = "auth")]
+    #[async_trait::async_trait]
+    impl crate::core::auth::providers::OAuthProvider for UnreadyAuthProvider {
+        async fn validate_token(
+            &self,
+            _token: &str,
+        ) -> Result<crate::core::auth::providers::StandardClaims, crate::core::auth::AuthError>
+        {
+            Err(crate::core::auth::AuthError::ValidationFailed(
+                "not ready".to_string(),
+            ))
+        }
+
+        async fn refresh_jwks(&self) -> Result<(), crate::core::auth::AuthError> {
+            Ok(())
+        }
+
+        fn config(&self) -> &crate::core::config::app_config::AuthConfig {
+            &self.config
+        }
+
+        async fn get_roles(
+            &self,
+            _token: &str,
+        ) -> Result<Vec<String>, crate::core::auth::AuthError> {
+            Ok(Vec::new())
+        }
+
+        fn name(&self) -> &str {
+            "idp"
+        }
+
+        async fn health_check(&self) -> crate::core::auth::providers::HealthStatus {
+            crate::core::auth::providers::HealthStatus {
+                ready: false,
+                jwks_valid: false,
+                last_refresh: SystemTime::now(),
+                error: Some("JWKS fetch failed".to_string()),
+                circuit_state: crate::core::auth::providers::CircuitState::Closed,
+            }
+        }
+
+        fn box_clone(&self) -> Box<dyn crate::core::auth::providers::OAuthProvider> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn test_auth_providers_are_aggregated() {
+        let config = crate::core::config::app_config::AuthConfig::default();
+        let mut providers = ProviderRegistry::new(config.clone());
+        providers.register("idp", Arc::new(UnreadyAuthProvider { config }));
+
+        let mut registry = HealthIndicatorProviderRegistry::new();
+        registry.register(Box::new(TestUpProvider));
+        let health_service = HealthServiceV2::new(Arc::new(registry), HealthConfig::default())
+            .with_auth_providers(Arc::new(providers));
+
+        let result = health_service
+            .check_health(&Arc::new(AppState::default()))
+            .await
+            .unwrap();
+        assert_eq!(result["components"]["auth.idp"]["status"], "DOWN");
+        assert_eq!(result["status"], "DEGRADED");
+        assert_eq!(result["degraded"], serde_json::json!(["auth.idp"]));
+    }
 }
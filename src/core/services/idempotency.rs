@@ -0,0 +1,466 @@
+//! Server-side idempotency keys
+//!
+//! A client sends the same `Idempotency-Key` with every retry of a mutating
+//! request. The key is first reserved with an atomic insert, so only one
+//! request per key (on any instance sharing the database) performs the write;
+//! the serialized result then replaces the reservation in the same
+//! transaction as the write itself. A replay returns the original result,
+//! and a retry arriving while the first request is still running is a
+//! conflict. A reservation left behind by a crashed instance blocks its key
+//! only for the reservation's lease; a retry after that takes it over. The
+//! takeover inserts a marker row next to the record, so of several retries
+//! racing for the same stale reservation exactly one wins.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::core::services::database_interface::DatabaseOperations;
+use crate::core::services::error::ServiceError;
+use crate::core::services::transaction::{Transaction, with_transaction};
+use crate::core::utils::sources;
+
+/// Default collection (table) holding idempotency records
+pub const IDEMPOTENCY_COLLECTION: &str = "idempotency_keys";
+
+/// Request header carrying the idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Default time after which a pending reservation may be taken over
+pub const DEFAULT_RESERVATION_LEASE: Duration = Duration::from_secs(60);
+
+/// Stored outcome of an idempotent operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub key: String,
+    /// Request the key was first used with
+    pub request: serde_json::Value,
+    /// Result returned to the first caller
+    pub response: serde_json::Value,
+    /// Whether the first request is still running, so there is no result yet
+    #[serde(default)]
+    pub pending: bool,
+    /// Unix timestamp in milliseconds
+    pub created_at: i64,
+}
+
+/// Result of an idempotent operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Idempotent<T> {
+    pub value: T,
+    /// Whether `value` was replayed from a previous request with the same key
+    pub replayed: bool,
+}
+
+/// Idempotency key store over a database collection
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    db: Arc<dyn DatabaseOperations>,
+    collection: String,
+    lease: Duration,
+}
+
+impl std::fmt::Debug for IdempotencyStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdempotencyStore")
+            .field("collection", &self.collection)
+            .field("lease", &self.lease)
+            .finish()
+    }
+}
+
+impl IdempotencyStore {
+    /// Create a store in the default `idempotency_keys` collection
+    pub fn new(db: Arc<dyn DatabaseOperations>) -> Self {
+        Self {
+            db,
+            collection: IDEMPOTENCY_COLLECTION.to_string(),
+            lease: DEFAULT_RESERVATION_LEASE,
+        }
+    }
+
+    /// Store records in a different collection
+    pub fn with_collection(mut self, collection: impl Into<String>) -> Self {
+        self.collection = collection.into();
+        self
+    }
+
+    /// Let retries take over a pending reservation older than `lease`
+    ///
+    /// Keep it above the longest operation: a request still running after
+    /// its lease can be repeated by a retry.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Look up the record stored for `key`
+    pub async fn get(&self, key: &str) -> Result<Option<IdempotencyRecord>, ServiceError> {
+        match self.db.get(&self.collection, key).await? {
+            Some(value) => serde_json::from_str(&value).map(Some).map_err(|e| {
+                ServiceError::conversion_error(format!(
+                    "Failed to deserialize idempotency record: {}",
+                    e
+                ))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Run `operation` at most once per `key`
+    ///
+    /// The first call reserves `key`, runs `operation` in a transaction and
+    /// stores its result under `key` in that same transaction. Later calls
+    /// with the same key and request return the stored result without running
+    /// `operation`, or a conflict while the first call is still running;
+    /// reusing a key with a different request is a conflict. If `operation`
+    /// fails the reservation is released, so the client can retry with the
+    /// same key; if the first call never finishes, a retry takes the
+    /// reservation over once its lease has expired.
+    pub async fn execute<R, T, F>(
+        &self,
+        key: &str,
+        request: &R,
+        operation: F,
+    ) -> Result<Idempotent<T>, ServiceError>
+    where
+        R: Serialize,
+        T: Serialize + DeserializeOwned + Send + 'static,
+        F: for<'tx> FnOnce(&'tx mut Transaction) -> BoxFuture<'tx, Result<T, ServiceError>>,
+    {
+        if key.is_empty() {
+            return Err(ServiceError::validation(
+                "Idempotency key must not be empty",
+            ));
+        }
+
+        let request = serde_json::to_value(request).map_err(|e| {
+            ServiceError::conversion_error(format!("Failed to serialize request: {}", e))
+        })?;
+
+        let reservation = IdempotencyRecord {
+            key: key.to_string(),
+            request: request.clone(),
+            response: serde_json::Value::Null,
+            pending: true,
+            created_at: sources::now_millis(),
+        };
+        if !self
+            .db
+            .insert(&self.collection, key, &encode(&reservation)?)
+            .await?
+        {
+            match self.get(key).await? {
+                Some(record) if self.is_stale(&record) && record.request == request => {
+                    self.take_over(&record, &reservation).await?
+                }
+                Some(record) => return self.replay(record, &request),
+                // The first request failed and released the key in between
+                None => {
+                    return Err(ServiceError::conflict(format!(
+                        "Idempotency key '{}' is already in use",
+                        key
+                    )));
+                }
+            }
+        }
+
+        let collection = self.collection.clone();
+        let result = with_transaction(self.db.clone(), move |tx| {
+            Box::pin(async move {
+                let value = operation(tx).await?;
+                let record = IdempotencyRecord {
+                    response: serde_json::to_value(&value).map_err(|e| {
+                        ServiceError::conversion_error(format!(
+                            "Failed to serialize idempotent result: {}",
+                            e
+                        ))
+                    })?,
+                    pending: false,
+                    ..reservation
+                };
+                tx.set(&collection, &record.key, &encode(&record)?);
+                Ok(value)
+            })
+        })
+        .await;
+
+        match result {
+            Ok(value) => Ok(Idempotent {
+                value,
+                replayed: false,
+            }),
+            Err(e) => {
+                if let Err(release) = self.db.delete(&self.collection, key).await {
+                    warn!("Failed to release idempotency key {}: {}", key, release);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Whether `record` is a reservation whose lease has expired
+    fn is_stale(&self, record: &IdempotencyRecord) -> bool {
+        let age = sources::now_millis().saturating_sub(record.created_at);
+        record.pending && age >= self.lease.as_millis() as i64
+    }
+
+    /// Replace the stale reservation `stale` with `reservation`
+    ///
+    /// Only the first caller to insert the takeover marker for `stale`
+    /// proceeds; the others see a conflict, as for any running request.
+    async fn take_over(
+        &self,
+        stale: &IdempotencyRecord,
+        reservation: &IdempotencyRecord,
+    ) -> Result<(), ServiceError> {
+        let marker = format!("{}#takeover-{}", stale.key, stale.created_at);
+        if !self.db.insert(&self.collection, &marker, "{}").await? {
+            return Err(ServiceError::conflict(format!(
+                "A request with idempotency key '{}' is still in progress",
+                stale.key
+            )));
+        }
+        warn!(
+            "Taking over idempotency key {} reserved at {}",
+            stale.key, stale.created_at
+        );
+        self.db
+            .set(&self.collection, &reservation.key, &encode(reservation)?)
+            .await
+    }
+
+    fn replay<T: DeserializeOwned>(
+        &self,
+        record: IdempotencyRecord,
+        request: &serde_json::Value,
+    ) -> Result<Idempotent<T>, ServiceError> {
+        if &record.request != request {
+            return Err(ServiceError::conflict(format!(
+                "Idempotency key '{}' was already used with a different request",
+                record.key
+            )));
+        }
+        if record.pending {
+            return Err(ServiceError::conflict(format!(
+                "A request with idempotency key '{}' is still in progress",
+                record.key
+            )));
+        }
+
+        debug!("Replaying result for idempotency key {}", record.key);
+        let value = serde_json::from_value(record.response).map_err(|e| {
+            ServiceError::conversion_error(format!(
+                "Failed to deserialize idempotent result: {}",
+                e
+            ))
+        })?;
+        Ok(Idempotent {
+            value,
+            replayed: true,
+        })
+    }
+}
+
+fn encode(record: &IdempotencyRecord) -> Result<String, ServiceError> {
+    serde_json::to_string(record).map_err(|e| {
+        ServiceError::conversion_error(format!("Failed to serialize idempotency record: {}", e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::entity::test_utils::{Pet, pet};
+    use crate::core::services::database_interface::DatabaseConfig;
+    use crate::core::services::memory_database::InMemoryDatabase;
+    use uuid::Uuid;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct CreatePet {
+        name: String,
+    }
+
+    fn database() -> Arc<dyn DatabaseOperations> {
+        Arc::new(InMemoryDatabase::new(Arc::new(DatabaseConfig::default())))
+    }
+
+    async fn create_pet(
+        store: &IdempotencyStore,
+        key: &str,
+        input: &CreatePet,
+    ) -> Result<Idempotent<Pet>, ServiceError> {
+        let name = input.name.clone();
+        store
+            .execute(key, input, move |tx| {
+                Box::pin(async move {
+                    let pet = pet(&Uuid::new_v4().to_string(), &name);
+                    tx.set("pets", &pet.id, &serde_json::to_string(&pet).unwrap());
+                    Ok(pet)
+                })
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_replay_returns_original_without_duplicate_row() {
+        let db = database();
+        let store = IdempotencyStore::new(db.clone());
+        let input = CreatePet {
+            name: "Rex".to_string(),
+        };
+
+        let first = create_pet(&store, "key-1", &input).await.unwrap();
+        let second = create_pet(&store, "key-1", &input).await.unwrap();
+
+        assert!(!first.replayed);
+        assert!(second.replayed);
+        assert_eq!(first.value, second.value);
+        assert_eq!(db.query("pets", "").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_requests_write_once() {
+        let db = database();
+        let store = IdempotencyStore::new(db.clone());
+        let barrier = Arc::new(tokio::sync::Barrier::new(2));
+
+        let attempts: Vec<_> = (0..2)
+            .map(|_| {
+                let store = store.clone();
+                let barrier = barrier.clone();
+                tokio::spawn(async move {
+                    let input = CreatePet {
+                        name: "Rex".to_string(),
+                    };
+                    barrier.wait().await;
+                    store
+                        .execute("key-1", &input, |tx| {
+                            Box::pin(async move {
+                                // Keep the first request in flight while the other arrives
+                                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                                let pet = pet(&Uuid::new_v4().to_string(), "Rex");
+                                tx.set("pets", &pet.id, &serde_json::to_string(&pet).unwrap());
+                                Ok(pet)
+                            })
+                        })
+                        .await
+                })
+            })
+            .collect();
+        let mut results = Vec::new();
+        for attempt in attempts {
+            results.push(attempt.await.unwrap());
+        }
+
+        let created: Vec<_> = results.iter().filter(|r| r.is_ok()).collect();
+        assert_eq!(created.len(), 1);
+        assert!(
+            results
+                .iter()
+                .any(|r| matches!(r, Err(ServiceError::Conflict(_))))
+        );
+        assert_eq!(db.query("pets", "").await.unwrap().len(), 1);
+
+        // Once the first request finished, a retry replays its result
+        let input = CreatePet {
+            name: "Rex".to_string(),
+        };
+        let retry = create_pet(&store, "key-1", &input).await.unwrap();
+        assert!(retry.replayed);
+        assert_eq!(db.query("pets", "").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_key_reused_with_different_request_conflicts() {
+        let store = IdempotencyStore::new(database());
+
+        create_pet(
+            &store,
+            "key-1",
+            &CreatePet {
+                name: "Rex".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let result = create_pet(
+            &store,
+            "key-1",
+            &CreatePet {
+                name: "Fido".to_string(),
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(ServiceError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_stale_reservation_is_taken_over() {
+        let db = database();
+        let store = IdempotencyStore::new(db.clone()).with_lease(Duration::from_secs(60));
+        let input = CreatePet {
+            name: "Rex".to_string(),
+        };
+        // Left behind by an instance that crashed mid-request
+        let reserve = |created_at| IdempotencyRecord {
+            key: "key-1".to_string(),
+            request: serde_json::to_value(&input).unwrap(),
+            response: serde_json::Value::Null,
+            pending: true,
+            created_at,
+        };
+        let fresh = reserve(sources::now_millis());
+        db.set(IDEMPOTENCY_COLLECTION, "key-1", &encode(&fresh).unwrap())
+            .await
+            .unwrap();
+
+        // Within the lease the first request may still be running
+        let result = create_pet(&store, "key-1", &input).await;
+        assert!(matches!(result, Err(ServiceError::Conflict(_))));
+
+        let stale = reserve(sources::now_millis() - 120_000);
+        db.set(IDEMPOTENCY_COLLECTION, "key-1", &encode(&stale).unwrap())
+            .await
+            .unwrap();
+
+        let created = create_pet(&store, "key-1", &input).await.unwrap();
+        assert!(!created.replayed);
+        assert!(!store.get("key-1").await.unwrap().unwrap().pending);
+        assert_eq!(db.query("pets", "").await.unwrap().len(), 1);
+
+        let retry = create_pet(&store, "key-1", &input).await.unwrap();
+        assert!(retry.replayed);
+        assert_eq!(retry.value, created.value);
+    }
+
+    #[tokio::test]
+    async fn test_failed_operation_stores_nothing() {
+        let db = database();
+        let store = IdempotencyStore::new(db.clone());
+        let input = CreatePet {
+            name: "Rex".to_string(),
+        };
+
+        let result: Result<Idempotent<Pet>, _> = store
+            .execute("key-1", &input, |tx| {
+                Box::pin(async move {
+                    tx.set("pets", "1", "{}");
+                    Err(ServiceError::unavailable("database busy"))
+                })
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert!(store.get("key-1").await.unwrap().is_none());
+        assert!(db.query("pets", "").await.unwrap().is_empty());
+
+        // The client can retry with the same key
+        assert!(!create_pet(&store, "key-1", &input).await.unwrap().replayed);
+    }
+}
@@ -0,0 +1,180 @@
+//! Long polling for clients that can't use WebSockets or SSE
+//!
+//! [`long_poll`] waits for the next event up to a timeout. The handler
+//! responds with the event as JSON when one arrives, or with
+//! `204 No Content` when the timeout passes first, so the client re-polls.
+//! If the client disconnects, the wait stops and the handler returns
+//! [`Cancelled`].
+//!
+//! ```ignore
+//! async fn adoptions(
+//!     cancel: RequestCancellation,
+//!     State(pubsub): State<PubSub>,
+//! ) -> Result<LongPoll<PetAdopted>, ServiceError> {
+//!     let mut events = pubsub.subscribe::<PetAdopted>("pets").await?;
+//!     Ok(long_poll(&cancel, Duration::from_secs(25), events.next()).await?)
+//! }
+//! ```
+//!
+//! Keep the timeout below `reliability.timeout.timeout_seconds`, or the
+//! request timeout answers first with an error instead of a 204.
+
+use std::future::Future;
+use std::time::Duration;
+
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::core::services::cancellation::{Cancelled, RequestCancellation, run_until_cancelled};
+
+/// Outcome of a long poll
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LongPoll<T> {
+    /// An event arrived in time
+    Event(T),
+    /// Nothing arrived before the timeout; the client should poll again
+    Timeout,
+}
+
+impl<T> LongPoll<T> {
+    /// The event, if one arrived
+    pub fn into_event(self) -> Option<T> {
+        match self {
+            LongPoll::Event(event) => Some(event),
+            LongPoll::Timeout => None,
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for LongPoll<T> {
+    fn into_response(self) -> Response {
+        match self {
+            LongPoll::Event(event) => Json(event).into_response(),
+            LongPoll::Timeout => StatusCode::NO_CONTENT.into_response(),
+        }
+    }
+}
+
+/// Wait up to `timeout` for `next_event`, stopping early if the request is abandoned
+///
+/// `next_event` resolves to `None` when the source is closed. That counts as
+/// no event: the wait still runs to `timeout` before answering, so clients
+/// don't re-poll a closed source in a tight loop. `Stream::next()` and
+/// `mpsc::Receiver::recv()` both fit.
+pub async fn long_poll<T, F>(
+    cancellation: &RequestCancellation,
+    timeout: Duration,
+    next_event: F,
+) -> Result<LongPoll<T>, Cancelled>
+where
+    F: Future<Output = Option<T>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    let waited =
+        run_until_cancelled(cancellation, tokio::time::timeout_at(deadline, next_event)).await?;
+    match waited {
+        Ok(Some(event)) => Ok(LongPoll::Event(event)),
+        Ok(None) => {
+            run_until_cancelled(cancellation, tokio::time::sleep_until(deadline)).await?;
+            Ok(LongPoll::Timeout)
+        }
+        Err(_) => Ok(LongPoll::Timeout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+    use serde_json::{Value, json};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_event_before_timeout_is_returned() {
+        let (sender, mut receiver) = mpsc::channel(1);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            sender
+                .send(json!({ "id": 1, "status": "adopted" }))
+                .await
+                .unwrap();
+        });
+
+        let result = long_poll(
+            &RequestCancellation::never(),
+            Duration::from_secs(5),
+            receiver.recv(),
+        )
+        .await
+        .unwrap();
+
+        let response = result.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            json!({ "id": 1, "status": "adopted" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_event_within_timeout_is_no_content() {
+        let (_sender, mut receiver) = mpsc::channel::<Value>(1);
+
+        let result = long_poll(
+            &RequestCancellation::never(),
+            Duration::from_millis(20),
+            receiver.recv(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result, LongPoll::Timeout);
+        assert_eq!(result.into_response().status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_closed_source_waits_out_the_timeout() {
+        let (sender, mut receiver) = mpsc::channel::<Value>(1);
+        drop(sender);
+
+        let started = tokio::time::Instant::now();
+        let result = long_poll(
+            &RequestCancellation::never(),
+            Duration::from_millis(50),
+            receiver.recv(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result, LongPoll::Timeout);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_stops_waiting_on_closed_source() {
+        let (cancellation, guard) = RequestCancellation::new();
+        let (sender, mut receiver) = mpsc::channel::<Value>(1);
+        drop(sender);
+        drop(guard);
+
+        let result = long_poll(&cancellation, Duration::from_secs(5), receiver.recv()).await;
+
+        assert_eq!(result, Err(Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_stops_waiting() {
+        let (cancellation, guard) = RequestCancellation::new();
+        let (_sender, mut receiver) = mpsc::channel::<Value>(1);
+        drop(guard);
+
+        let result = long_poll(&cancellation, Duration::from_secs(5), receiver.recv()).await;
+
+        assert_eq!(result, Err(Cancelled));
+    }
+}
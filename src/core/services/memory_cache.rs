@@ -1,5 +1,5 @@
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
@@ -15,8 +15,10 @@ use tracing::debug;
 
 use crate::core::services::cache_provider::{
     CacheConfig, CacheError, CacheFactory, CacheOperations, CacheProvider, CacheStats,
-    DynCacheOperations, EvictionPolicy, TypedCache, TypedCacheFactory,
+    DynCacheOperations, EvictionPolicy, TypedCache, TypedCacheFactory, decode_or_miss,
 };
+use crate::core::services::fallback_cache::FallbackCache;
+use crate::core::services::redis_cache::RedisCache;
 
 /// Cache entry with metadata
 struct CacheEntry {
@@ -30,6 +32,8 @@ struct CacheEntry {
     last_accessed: Instant,
     /// Hit count
     hit_count: u64,
+    /// Tags the entry was set with
+    tags: Vec<String>,
 }
 
 impl CacheEntry {
@@ -42,6 +46,7 @@ impl CacheEntry {
             ttl,
             last_accessed: now,
             hit_count: 0,
+            tags: Vec::new(),
         }
     }
 
@@ -54,6 +59,18 @@ impl CacheEntry {
         }
     }
 
+    /// Time left before the entry expires, if it has a TTL
+    fn remaining_ttl(&self) -> Option<Duration> {
+        self.ttl
+            .map(|ttl| ttl.saturating_sub(self.created_at.elapsed()))
+    }
+
+    /// Expire the entry `ttl` from now
+    fn expire_in(&mut self, ttl: Duration) {
+        // Keep created_at so FIFO eviction still sees the original insertion time
+        self.ttl = Some(self.created_at.elapsed() + ttl);
+    }
+
     /// Access the entry and update last_accessed
     fn access(&mut self) {
         self.last_accessed = Instant::now();
@@ -61,12 +78,33 @@ impl CacheEntry {
     }
 }
 
+/// Keys of the entries set with each tag
+type TagIndex = HashMap<String, HashSet<String>>;
+
+/// Take `key` out of the index of each tag `entry` was set with
+fn untag(tags: &RwLock<TagIndex>, key: &str, entry: &CacheEntry) {
+    if entry.tags.is_empty() {
+        return;
+    }
+    let mut index = tags.write().unwrap();
+    for tag in &entry.tags {
+        if let Some(keys) = index.get_mut(tag) {
+            keys.remove(key);
+            if keys.is_empty() {
+                index.remove(tag);
+            }
+        }
+    }
+}
+
 /// In-memory cache implementation
 pub struct InMemoryCache {
     name: String,
     config: CacheConfig,
     entries: Arc<RwLock<HashMap<String, CacheEntry>>>,
     stats: Arc<RwLock<CacheStats>>,
+    /// Keys currently set with each tag
+    tags: Arc<RwLock<TagIndex>>,
     _cleanup_task: Mutex<Option<JoinHandle<()>>>,
 }
 
@@ -100,7 +138,7 @@ where
 
             if let Some(entry) = entries.get_mut(key) {
                 if entry.is_expired() {
-                    entries.remove(key);
+                    self.cache.remove_entry(&mut entries, key);
                     stats.evictions += 1;
                     stats.misses += 1;
                     None
@@ -115,17 +153,35 @@ where
             }
         };
 
-        if let Some(value) = entry_opt {
-            match bincode::decode_from_slice::<T, _>(&value, standard()) {
-                Ok((val, _)) => Ok(Some(val)),
-                Err(e) => Err(CacheError::Deserialization(e.to_string())),
+        let Some(value) = entry_opt else {
+            return Ok(None);
+        };
+        let decoded = decode_or_miss(&self.cache.name, key, &value)?;
+        if decoded.is_none() {
+            // Drop the stale entry so the next write replaces it
+            let mut entries = self.cache.entries.write().unwrap();
+            let mut stats = self.cache.stats.write().unwrap();
+            if entries.get(key).is_some_and(|entry| entry.value == value) {
+                self.cache.remove_entry(&mut entries, key);
             }
-        } else {
-            Ok(None)
+            stats.size = entries.len();
+            stats.hits = stats.hits.saturating_sub(1);
+            stats.misses += 1;
         }
+        Ok(decoded)
     }
 
     async fn set(&self, key: &str, value: T, ttl: Option<Duration>) -> Result<(), CacheError> {
+        self.set_with_tags(key, value, ttl, &[]).await
+    }
+
+    async fn set_with_tags(
+        &self,
+        key: &str,
+        value: T,
+        ttl: Option<Duration>,
+        tags: &[&str],
+    ) -> Result<(), CacheError> {
         // Serialize value
         let serialized = match bincode::encode_to_vec(&value, standard()) {
             Ok(val) => val,
@@ -143,7 +199,21 @@ where
 
         // Set entry with effective TTL
         let effective_ttl = ttl.or(self.cache.config.default_ttl);
-        entries.insert(key.to_string(), CacheEntry::new(serialized, effective_ttl));
+        let mut entry = CacheEntry::new(serialized, effective_ttl);
+        entry.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        if let Some(previous) = entries.insert(key.to_string(), entry) {
+            untag(&self.cache.tags, key, &previous);
+        }
+
+        if !tags.is_empty() {
+            let mut index = self.cache.tags.write().unwrap();
+            for tag in tags {
+                index
+                    .entry(tag.to_string())
+                    .or_default()
+                    .insert(key.to_string());
+            }
+        }
 
         // Update stats
         self.cache.stats.write().unwrap().size = entries.len();
@@ -203,6 +273,7 @@ impl InMemoryCache {
             config,
             entries: Arc::clone(&entries),
             stats: Arc::clone(&stats),
+            tags: Arc::new(RwLock::new(HashMap::new())),
             _cleanup_task: Mutex::new(None),
         };
 
@@ -218,6 +289,7 @@ impl InMemoryCache {
     fn start_cleanup_task(&self) {
         let entries = Arc::clone(&self.entries);
         let stats = Arc::clone(&self.stats);
+        let tags = Arc::clone(&self.tags);
         let interval = Duration::from_secs(60); // Clean every minute
 
         let handle = tokio::spawn(async move {
@@ -232,9 +304,10 @@ impl InMemoryCache {
                 let before_count = entries_guard.len();
 
                 // Remove expired entries
-                entries_guard.retain(|_, entry| {
+                entries_guard.retain(|key, entry| {
                     let keep = !entry.is_expired();
                     if !keep {
+                        untag(&tags, key, entry);
                         stats_guard.evictions += 1;
                     }
                     keep
@@ -265,50 +338,32 @@ impl InMemoryCache {
         }
 
         // Choose entry to evict based on policy
-        match self.config.eviction_policy {
+        let victim = match self.config.eviction_policy {
             EvictionPolicy::None => {
                 return Err(CacheError::Capacity(format!(
                     "Cache {} is full (capacity: {})",
                     self.name, capacity
                 )));
             }
-            EvictionPolicy::LRU => {
-                // Find least recently used entry
-                if let Some(key) = entries
-                    .iter()
-                    .min_by_key(|(_, entry)| entry.last_accessed)
-                    .map(|(k, _)| k.clone())
-                {
-                    entries.remove(&key);
-                    self.stats.write().unwrap().evictions += 1;
-                }
-            }
-            EvictionPolicy::LFU => {
-                // Find least frequently used entry
-                if let Some(key) = entries
-                    .iter()
-                    .min_by_key(|(_, entry)| entry.hit_count)
-                    .map(|(k, _)| k.clone())
-                {
-                    entries.remove(&key);
-                    self.stats.write().unwrap().evictions += 1;
-                }
-            }
-            EvictionPolicy::FIFO => {
-                // Find oldest entry
-                if let Some(key) = entries
-                    .iter()
-                    .min_by_key(|(_, entry)| entry.created_at)
-                    .map(|(k, _)| k.clone())
-                {
-                    entries.remove(&key);
-                    self.stats.write().unwrap().evictions += 1;
-                }
-            }
+            // Least recently used entry
+            EvictionPolicy::LRU => entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_accessed)
+                .map(|(k, _)| k.clone()),
+            // Least frequently used entry
+            EvictionPolicy::LFU => entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.hit_count)
+                .map(|(k, _)| k.clone()),
+            // Oldest entry
+            EvictionPolicy::FIFO => entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.created_at)
+                .map(|(k, _)| k.clone()),
             EvictionPolicy::TTL => {
                 // Find entry closest to expiration or already expired
                 let now = Instant::now();
-                if let Some(key) = entries
+                entries
                     .iter()
                     .filter_map(|(k, entry)| {
                         entry.ttl.map(|ttl| {
@@ -322,32 +377,36 @@ impl InMemoryCache {
                     })
                     .min_by_key(|(_, remaining)| *remaining)
                     .map(|(k, _)| k)
-                {
-                    entries.remove(&key);
-                    self.stats.write().unwrap().evictions += 1;
-                } else {
                     // If no TTL set, fall back to LRU
-                    if let Some(key) = entries
-                        .iter()
-                        .min_by_key(|(_, entry)| entry.last_accessed)
-                        .map(|(k, _)| k.clone())
-                    {
-                        entries.remove(&key);
-                        self.stats.write().unwrap().evictions += 1;
-                    }
-                }
-            }
-            EvictionPolicy::Random => {
-                // Just remove the first entry we find
-                if let Some(key) = entries.keys().next().cloned() {
-                    entries.remove(&key);
-                    self.stats.write().unwrap().evictions += 1;
-                }
+                    .or_else(|| {
+                        entries
+                            .iter()
+                            .min_by_key(|(_, entry)| entry.last_accessed)
+                            .map(|(k, _)| k.clone())
+                    })
             }
+            // Just remove the first entry we find
+            EvictionPolicy::Random => entries.keys().next().cloned(),
+        };
+
+        if let Some(key) = victim {
+            self.remove_entry(entries, &key);
+            self.stats.write().unwrap().evictions += 1;
         }
 
         Ok(())
     }
+
+    /// Remove `key`, keeping the tag index in step
+    fn remove_entry(
+        &self,
+        entries: &mut HashMap<String, CacheEntry>,
+        key: &str,
+    ) -> Option<CacheEntry> {
+        let entry = entries.remove(key)?;
+        untag(&self.tags, key, &entry);
+        Some(entry)
+    }
 }
 
 impl CacheFactory for InMemoryCache {
@@ -363,7 +422,7 @@ impl CacheFactory for InMemoryCache {
 impl DynCacheOperations for InMemoryCache {
     async fn delete(&self, key: &str) -> Result<bool, CacheError> {
         let mut entries = self.entries.write().unwrap();
-        let removed = entries.remove(key).is_some();
+        let removed = self.remove_entry(&mut entries, key).is_some();
 
         if removed {
             let mut stats = self.stats.write().unwrap();
@@ -376,6 +435,7 @@ impl DynCacheOperations for InMemoryCache {
     async fn clear(&self) -> Result<(), CacheError> {
         let mut entries = self.entries.write().unwrap();
         entries.clear();
+        self.tags.write().unwrap().clear();
 
         let mut stats = self.stats.write().unwrap();
         stats.size = 0;
@@ -403,7 +463,7 @@ impl DynCacheOperations for InMemoryCache {
         let mut deleted = 0;
 
         for key in keys {
-            if entries.remove(*key).is_some() {
+            if self.remove_entry(&mut entries, key).is_some() {
                 deleted += 1;
             }
         }
@@ -449,7 +509,11 @@ impl DynCacheOperations for InMemoryCache {
                     .map_err(|e| CacheError::Serialization(e.to_string()))?;
 
                 // Update entry
-                entries.insert(key.to_string(), CacheEntry::new(serialized, ttl));
+                if let Some(previous) =
+                    entries.insert(key.to_string(), CacheEntry::new(serialized, ttl))
+                {
+                    untag(&self.tags, key, &previous);
+                }
 
                 Ok(new_value)
             }
@@ -458,11 +522,13 @@ impl DynCacheOperations for InMemoryCache {
                 let serialized = bincode::encode_to_vec(&delta, standard())
                     .map_err(|e| CacheError::Serialization(e.to_string()))?;
 
-                // Create new entry with default TTL
-                entries.insert(
+                // Create new entry with default TTL, replacing any expired one
+                if let Some(previous) = entries.insert(
                     key.to_string(),
                     CacheEntry::new(serialized, self.config.default_ttl),
-                );
+                ) {
+                    untag(&self.tags, key, &previous);
+                }
 
                 // Update stats
                 let mut stats = self.stats.write().unwrap();
@@ -473,6 +539,44 @@ impl DynCacheOperations for InMemoryCache {
         }
     }
 
+    async fn ttl(&self, key: &str) -> Result<Option<Duration>, CacheError> {
+        let entries = self.entries.read().unwrap();
+        Ok(entries
+            .get(key)
+            .filter(|entry| !entry.is_expired())
+            .and_then(CacheEntry::remaining_ttl))
+    }
+
+    async fn touch(&self, key: &str, ttl_seconds: u64) -> Result<bool, CacheError> {
+        let mut entries = self.entries.write().unwrap();
+        match entries.get_mut(key) {
+            Some(entry) if !entry.is_expired() => {
+                entry.expire_in(Duration::from_secs(ttl_seconds));
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<usize, CacheError> {
+        let mut entries = self.entries.write().unwrap();
+        let keys = self.tags.write().unwrap().remove(tag).unwrap_or_default();
+
+        let mut removed = 0;
+        for key in keys {
+            if self.remove_entry(&mut entries, &key).is_some() {
+                removed += 1;
+            }
+        }
+
+        self.stats.write().unwrap().size = entries.len();
+        debug!(
+            "Invalidated {} entries tagged {} in {}",
+            removed, tag, self.name
+        );
+        Ok(removed)
+    }
+
     fn stats(&self) -> Result<CacheStats, CacheError> {
         Ok(self.stats.read().unwrap().clone())
     }
@@ -498,6 +602,7 @@ impl Clone for InMemoryCache {
             config: self.config.clone(),
             entries: Arc::clone(&self.entries),
             stats: Arc::clone(&self.stats),
+            tags: Arc::clone(&self.tags),
             _cleanup_task: Mutex::new(None),
         }
     }
@@ -566,6 +671,10 @@ impl Clone for Box<dyn DynCacheOperations> {
             clonable.clone_box()
         } else if let Some(mem_cache) = self.as_any().downcast_ref::<InMemoryCache>() {
             Box::new(mem_cache.clone())
+        } else if let Some(redis_cache) = self.as_any().downcast_ref::<RedisCache>() {
+            Box::new(redis_cache.clone())
+        } else if let Some(fallback_cache) = self.as_any().downcast_ref::<FallbackCache>() {
+            Box::new(fallback_cache.clone())
         } else {
             // Fallback to panic - in a real-world scenario, you'd want a better solution
             panic!("Unable to clone Box<dyn DynCacheOperations>");
@@ -663,6 +772,67 @@ mod tests {
         assert_eq!(typed_cache.get("key1").await.unwrap(), None);
     }
 
+    #[tokio::test]
+    async fn test_ttl_and_touch() {
+        let config = CacheConfig {
+            name: "test-touch".to_string(),
+            provider: "memory".to_string(),
+            capacity: Some(10),
+            default_ttl: None,
+            eviction_policy: EvictionPolicy::LRU,
+            provider_config: HashMap::new(),
+        };
+
+        let cache = InMemoryCache::new(config);
+        let typed_cache = cache.for_type::<i32>().create_typed_cache();
+        typed_cache
+            .set("key1", 1, Some(Duration::from_millis(300)))
+            .await
+            .unwrap();
+        typed_cache.set("forever", 2, None).await.unwrap();
+
+        // TTL counts down
+        let first = cache.ttl("key1").await.unwrap().unwrap();
+        assert!(first <= Duration::from_millis(300));
+        sleep(Duration::from_millis(50)).await;
+        let second = cache.ttl("key1").await.unwrap().unwrap();
+        assert!(second < first);
+
+        // Touching extends the entry past its original expiry
+        assert!(cache.touch("key1", 60).await.unwrap());
+        let extended = cache.ttl("key1").await.unwrap().unwrap();
+        assert!(extended > Duration::from_secs(59));
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(typed_cache.get("key1").await.unwrap(), Some(1));
+
+        // Keys without an expiry report none until touched
+        assert_eq!(cache.ttl("forever").await.unwrap(), None);
+        assert!(cache.touch("forever", 60).await.unwrap());
+        assert!(cache.ttl("forever").await.unwrap().is_some());
+
+        // Missing keys
+        assert_eq!(cache.ttl("missing").await.unwrap(), None);
+        assert!(!cache.touch("missing", 60).await.unwrap());
+        assert!(!cache.exists("missing").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_touch_does_not_revive_expired_entry() {
+        let cache = InMemoryCache::new(CacheConfig {
+            name: "test-touch-expired".to_string(),
+            ..Default::default()
+        });
+        let typed_cache = cache.for_type::<i32>().create_typed_cache();
+        typed_cache
+            .set("key1", 1, Some(Duration::from_millis(20)))
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(50)).await;
+
+        assert!(!cache.touch("key1", 60).await.unwrap());
+        assert_eq!(typed_cache.get("key1").await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_capacity_and_eviction() {
         let config = CacheConfig {
@@ -730,6 +900,116 @@ mod tests {
         assert!(!cache.exists("key3").await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_invalidate_tag_removes_tagged_entries() {
+        let cache = InMemoryCache::new(CacheConfig::default());
+        let pets = cache.for_type::<String>().create_typed_cache();
+
+        pets.set_with_tags("pet:1", "Rex".to_string(), None, &["owner:42"])
+            .await
+            .unwrap();
+        pets.set_with_tags("pet:2", "Tom".to_string(), None, &["owner:42", "cats"])
+            .await
+            .unwrap();
+        pets.set("pet:3", "Kit".to_string(), None).await.unwrap();
+        pets.set_with_tags("pet:4", "Ace".to_string(), None, &["owner:7"])
+            .await
+            .unwrap();
+
+        assert_eq!(cache.invalidate_tag("owner:42").await.unwrap(), 2);
+
+        assert!(!cache.exists("pet:1").await.unwrap());
+        assert!(!cache.exists("pet:2").await.unwrap());
+        assert!(cache.exists("pet:3").await.unwrap());
+        assert!(cache.exists("pet:4").await.unwrap());
+        assert_eq!(cache.stats().unwrap().size, 2);
+        assert_eq!(cache.invalidate_tag("owner:42").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_overwritten_entry_loses_its_tags() {
+        let cache = InMemoryCache::new(CacheConfig::default());
+        let pets = cache.for_type::<String>().create_typed_cache();
+
+        pets.set_with_tags("pet:1", "Rex".to_string(), None, &["owner:42"])
+            .await
+            .unwrap();
+        pets.set("pet:1", "Rex".to_string(), None).await.unwrap();
+
+        assert_eq!(cache.invalidate_tag("owner:42").await.unwrap(), 0);
+        assert!(cache.exists("pet:1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_removed_entries_leave_the_tag_index() {
+        let cache = InMemoryCache::new(CacheConfig {
+            capacity: Some(2),
+            default_ttl: None,
+            ..Default::default()
+        });
+        let pets = cache.for_type::<String>().create_typed_cache();
+
+        pets.set_with_tags("pet:1", "Rex".to_string(), None, &["owner:1"])
+            .await
+            .unwrap();
+        pets.set_with_tags("pet:2", "Tom".to_string(), None, &["owner:2"])
+            .await
+            .unwrap();
+        cache.delete("pet:1").await.unwrap();
+        pets.set("pet:2", "Tom".to_string(), None).await.unwrap();
+        // Over capacity, so older entries are evicted
+        for (key, owner) in [
+            ("pet:3", "owner:3"),
+            ("pet:4", "owner:4"),
+            ("pet:5", "owner:5"),
+        ] {
+            pets.set_with_tags(key, "Kit".to_string(), None, &[owner])
+                .await
+                .unwrap();
+        }
+
+        let entries = cache.entries.read().unwrap();
+        let index = cache.tags.read().unwrap();
+        assert!(!index.contains_key("owner:1"));
+        assert!(!index.contains_key("owner:2"));
+        assert_eq!(index.len(), entries.len());
+        assert!(
+            index
+                .values()
+                .flatten()
+                .all(|key| entries.contains_key(key))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_entry_is_a_miss() {
+        let cache = InMemoryCache::new(CacheConfig::default());
+        let typed_cache = cache.for_type::<String>().create_typed_cache();
+
+        // An entry written by an older, incompatible version of the type
+        cache
+            .entries
+            .write()
+            .unwrap()
+            .insert("key1".to_string(), CacheEntry::new(vec![0xFF], None));
+
+        assert_eq!(typed_cache.get("key1").await.unwrap(), None);
+        assert!(!cache.exists("key1").await.unwrap());
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 1);
+
+        // The key can be repopulated with the current shape
+        typed_cache
+            .set("key1", "value1".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            typed_cache.get("key1").await.unwrap(),
+            Some("value1".to_string())
+        );
+    }
+
     #[tokio::test]
     async fn test_provider() {
         let provider = InMemoryCacheProvider::new();
@@ -3,11 +3,11 @@ use std::error::Error;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, MutexGuard};
 use tracing::{error, info};
 
 use crate::core::services::database_interface::{
-    DatabaseConfig, DatabaseOperations, DatabaseProvider, DatabaseProviderRegistry,
+    BatchWrite, DatabaseConfig, DatabaseOperations, DatabaseProvider, DatabaseProviderRegistry,
 };
 use crate::core::services::error::ServiceError;
 use crate::core::services::{Lifecycle, Service};
@@ -59,58 +59,195 @@ impl InMemoryDatabase {
             initialized: true,
         }
     }
+
+    /// Acquire the data store, honouring the configured acquire timeout
+    async fn checkout(
+        &self,
+    ) -> Result<MutexGuard<'_, HashMap<String, HashMap<String, String>>>, ServiceError> {
+        let acquire_timeout = self.config.acquire_timeout();
+        tokio::time::timeout(acquire_timeout, self.data.lock())
+            .await
+            .map_err(|_| {
+                ServiceError::timeout(format!(
+                    "Timed out acquiring database connection after {}s",
+                    acquire_timeout.as_secs()
+                ))
+            })
+    }
 }
 
 #[async_trait]
 impl DatabaseOperations for InMemoryDatabase {
     /// Get data from the database
     async fn get(&self, collection: &str, key: &str) -> Result<Option<String>, ServiceError> {
-        let data = self.data.lock().await;
-
-        match data.get(collection) {
-            Some(collection_data) => Ok(collection_data.get(key).cloned()),
-            None => Ok(None),
-        }
+        self.config
+            .with_statement_timeout(async {
+                let data = self.checkout().await?;
+
+                match data.get(collection) {
+                    Some(collection_data) => Ok(collection_data.get(key).cloned()),
+                    None => Ok(None),
+                }
+            })
+            .await
     }
 
     /// Set data in the database
     async fn set(&self, collection: &str, key: &str, value: &str) -> Result<(), ServiceError> {
-        let mut data = self.data.lock().await;
-
-        let collection_data = data
-            .entry(collection.to_string())
-            .or_insert_with(HashMap::new);
-        collection_data.insert(key.to_string(), value.to_string());
+        self.config
+            .with_statement_timeout(async {
+                let mut data = self.checkout().await?;
+
+                let collection_data = data
+                    .entry(collection.to_string())
+                    .or_insert_with(HashMap::new);
+                collection_data.insert(key.to_string(), value.to_string());
+
+                Ok(())
+            })
+            .await
+    }
 
-        Ok(())
+    /// Insert data unless the key is already present
+    async fn insert(&self, collection: &str, key: &str, value: &str) -> Result<bool, ServiceError> {
+        self.config
+            .with_statement_timeout(async {
+                let mut data = self.checkout().await?;
+
+                let collection_data = data
+                    .entry(collection.to_string())
+                    .or_insert_with(HashMap::new);
+                if collection_data.contains_key(key) {
+                    return Ok(false);
+                }
+                collection_data.insert(key.to_string(), value.to_string());
+
+                Ok(true)
+            })
+            .await
     }
 
     /// Delete data from the database
     async fn delete(&self, collection: &str, key: &str) -> Result<bool, ServiceError> {
-        let mut data = self.data.lock().await;
-
-        match data.get_mut(collection) {
-            Some(collection_data) => Ok(collection_data.remove(key).is_some()),
-            None => Ok(false),
-        }
+        self.config
+            .with_statement_timeout(async {
+                let mut data = self.checkout().await?;
+
+                match data.get_mut(collection) {
+                    Some(collection_data) => Ok(collection_data.remove(key).is_some()),
+                    None => Ok(false),
+                }
+            })
+            .await
     }
 
     /// Query the database with a filter
     async fn query(&self, collection: &str, filter: &str) -> Result<Vec<String>, ServiceError> {
-        let data = self.data.lock().await;
-
-        match data.get(collection) {
-            Some(collection_data) => {
-                // Simple contains filter for demonstration
-                let results = collection_data
-                    .iter()
-                    .filter(|(_, v)| v.contains(filter))
-                    .map(|(_, v)| v.clone())
-                    .collect();
-
-                Ok(results)
+        self.config
+            .with_statement_timeout(async {
+                let data = self.checkout().await?;
+
+                match data.get(collection) {
+                    Some(collection_data) => {
+                        // Simple contains filter for demonstration
+                        let results = collection_data
+                            .iter()
+                            .filter(|(_, v)| v.contains(filter))
+                            .map(|(_, v)| v.clone())
+                            .collect();
+
+                        Ok(results)
+                    }
+                    None => Ok(Vec::new()),
+                }
+            })
+            .await
+    }
+
+    /// Apply a batch of writes under one lock, undoing them on conflict
+    async fn apply_batch(&self, writes: &[BatchWrite]) -> Result<(), ServiceError> {
+        self.config
+            .with_statement_timeout(async {
+                let mut data = self.checkout().await?;
+
+                // Previous value of every key written so far, to undo on conflict
+                let mut undo: Vec<(&str, &str, Option<String>)> = Vec::new();
+                for write in writes {
+                    let (collection, key, value) = match write {
+                        BatchWrite::Set {
+                            collection,
+                            key,
+                            value,
+                        } => (collection, key, Some(value)),
+                        BatchWrite::Insert {
+                            collection,
+                            key,
+                            value,
+                        } => {
+                            let exists = data
+                                .get(collection.as_str())
+                                .is_some_and(|collection_data| collection_data.contains_key(key));
+                            if exists {
+                                undo_writes(&mut data, undo);
+                                return Err(ServiceError::conflict(format!(
+                                    "Key '{}' already exists in '{}'",
+                                    key, collection
+                                )));
+                            }
+                            (collection, key, Some(value))
+                        }
+                        BatchWrite::Delete { collection, key } => (collection, key, None),
+                        BatchWrite::Check {
+                            collection,
+                            key,
+                            expected,
+                        } => {
+                            let current = data
+                                .get(collection.as_str())
+                                .and_then(|collection_data| collection_data.get(key));
+                            if current != expected.as_ref() {
+                                undo_writes(&mut data, undo);
+                                return Err(ServiceError::conflict(format!(
+                                    "Key '{}' in '{}' was changed concurrently",
+                                    key, collection
+                                )));
+                            }
+                            continue;
+                        }
+                    };
+
+                    let collection_data = data
+                        .entry(collection.to_string())
+                        .or_insert_with(HashMap::new);
+                    let previous = match value {
+                        Some(value) => collection_data.insert(key.to_string(), value.to_string()),
+                        None => collection_data.remove(key),
+                    };
+                    undo.push((collection.as_str(), key.as_str(), previous));
+                }
+
+                Ok(())
+            })
+            .await
+    }
+}
+
+/// Restore the values recorded in `undo`, most recent write first
+fn undo_writes(
+    data: &mut HashMap<String, HashMap<String, String>>,
+    undo: Vec<(&str, &str, Option<String>)>,
+) {
+    for (collection, key, previous) in undo.into_iter().rev() {
+        let Some(collection_data) = data.get_mut(collection) else {
+            continue;
+        };
+        match previous {
+            Some(value) => {
+                collection_data.insert(key.to_string(), value);
+            }
+            None => {
+                collection_data.remove(key);
             }
-            None => Ok(Vec::new()),
         }
     }
 }
@@ -184,6 +321,114 @@ mod tests {
         assert_eq!(bob, vec!["Bob".to_string()]);
     }
 
+    #[test]
+    async fn test_insert_keeps_existing_value() {
+        let db = InMemoryDatabase::new(Arc::new(DatabaseConfig::default()));
+
+        assert!(db.insert("users", "1", "Alice").await.unwrap());
+        assert!(!db.insert("users", "1", "Mallory").await.unwrap());
+
+        assert_eq!(
+            db.get("users", "1").await.unwrap(),
+            Some("Alice".to_string())
+        );
+    }
+
+    #[test]
+    async fn test_apply_batch_is_all_or_nothing() {
+        let db = InMemoryDatabase::new(Arc::new(DatabaseConfig::default()));
+        db.set("users", "1", "Alice").await.unwrap();
+        let set = |key: &str, value: &str| BatchWrite::Set {
+            collection: "users".to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        let result = db
+            .apply_batch(&[
+                set("1", "Alicia"),
+                set("2", "Bob"),
+                BatchWrite::Delete {
+                    collection: "users".to_string(),
+                    key: "1".to_string(),
+                },
+                BatchWrite::Insert {
+                    collection: "users".to_string(),
+                    key: "2".to_string(),
+                    value: "Mallory".to_string(),
+                },
+            ])
+            .await;
+
+        assert!(matches!(result, Err(ServiceError::Conflict(_))));
+        assert_eq!(
+            db.get("users", "1").await.unwrap(),
+            Some("Alice".to_string())
+        );
+        assert_eq!(db.get("users", "2").await.unwrap(), None);
+
+        db.apply_batch(&[set("2", "Bob")]).await.unwrap();
+        assert_eq!(db.get("users", "2").await.unwrap(), Some("Bob".to_string()));
+    }
+
+    #[test]
+    async fn test_statement_timeout_maps_to_gateway_timeout() {
+        let config = DatabaseConfig {
+            statement_timeout_ms: Some(20),
+            ..DatabaseConfig::default()
+        };
+
+        // Deliberately slow statement
+        let result: Result<(), ServiceError> = config
+            .with_statement_timeout(async {
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                Ok(())
+            })
+            .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(err, ServiceError::Timeout(_)));
+
+        let app_err: crate::core::error::AppError = err.into();
+        assert_eq!(
+            app_err.status_code(),
+            axum::http::StatusCode::GATEWAY_TIMEOUT
+        );
+    }
+
+    #[test]
+    async fn test_acquire_timeout_when_connection_held() {
+        let config = Arc::new(DatabaseConfig {
+            acquire_timeout_seconds: 0,
+            ..DatabaseConfig::default()
+        });
+        let db = InMemoryDatabase::new(config);
+
+        // Hold the only "connection" so the next checkout cannot succeed
+        let _held = db.data.lock().await;
+
+        let result = db.get("users", "1").await;
+        assert!(matches!(result, Err(ServiceError::Timeout(_))));
+    }
+
+    #[test]
+    async fn test_session_settings() {
+        let config = DatabaseConfig {
+            statement_timeout_ms: Some(5000),
+            idle_in_transaction_session_timeout_ms: Some(10000),
+            ..DatabaseConfig::default()
+        };
+
+        assert_eq!(
+            config.session_settings(),
+            vec![
+                "SET statement_timeout = 5000".to_string(),
+                "SET idle_in_transaction_session_timeout = 10000".to_string(),
+            ]
+        );
+        assert!(DatabaseConfig::default().session_settings().is_empty());
+    }
+
     #[test]
     async fn test_provider() {
         // Create a config
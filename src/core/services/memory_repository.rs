@@ -136,7 +136,6 @@ impl<E: Entity + Serialize + DeserializeOwned> Repository<E> for InMemoryReposit
         // Validate the entity first
         entity.validate()?;
 
-        let json = self.serialize_entity(entity)?;
         let id_str = self.id_to_string(entity.id());
 
         let mut data = self.data_store.lock().await;
@@ -146,11 +145,27 @@ impl<E: Entity + Serialize + DeserializeOwned> Repository<E> for InMemoryReposit
             .entry(self.collection_name.clone())
             .or_insert_with(HashMap::new);
 
+        let mut saved = entity.clone();
+        let expected_version = entity.version().filter(|_| self.config.optimistic_locking);
+        if let Some(expected) = expected_version {
+            let stored = match collection.get(&id_str) {
+                Some(json) => self.deserialize_entity(json)?.version().unwrap_or(0),
+                None => 0,
+            };
+            if stored != expected {
+                return Err(ServiceError::conflict(format!(
+                    "{} {} was modified concurrently (expected version {}, found {})",
+                    self.collection_name, id_str, expected, stored
+                )));
+            }
+            saved.set_version(expected + 1);
+        }
+
         // Save entity
-        collection.insert(id_str, json);
+        collection.insert(id_str, self.serialize_entity(&saved)?);
 
         // Return a clone of the saved entity
-        Ok(entity.clone())
+        Ok(saved)
     }
 
     async fn delete(&self, id: &E::Id) -> Result<bool, ServiceError> {
@@ -345,6 +360,73 @@ mod tests {
         assert!(matches!(error, ServiceError::Validation(_)));
     }
 
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct VersionedNote {
+        id: Uuid,
+        text: String,
+        version: u64,
+    }
+
+    impl Entity for VersionedNote {
+        type Id = Uuid;
+
+        fn id(&self) -> &Self::Id {
+            &self.id
+        }
+
+        fn collection_name() -> String {
+            "notes".to_string()
+        }
+
+        fn version(&self) -> Option<u64> {
+            Some(self.version)
+        }
+
+        fn set_version(&mut self, version: u64) {
+            self.version = version;
+        }
+    }
+
+    #[test]
+    async fn test_optimistic_locking() {
+        let config = RepositoryConfig {
+            optimistic_locking: true,
+            ..Default::default()
+        };
+        let repository =
+            InMemoryRepository::<VersionedNote>::new(config, Arc::new(Mutex::new(HashMap::new())));
+
+        let note = VersionedNote {
+            id: Uuid::new_v4(),
+            text: "first".to_string(),
+            version: 0,
+        };
+        let saved = repository.save(&note).await.unwrap();
+        assert_eq!(saved.version, 1);
+
+        // Saving with the current version succeeds and bumps it
+        let updated = repository
+            .save(&VersionedNote {
+                text: "second".to_string(),
+                ..saved.clone()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.version, 2);
+
+        // Saving with a stale version conflicts and leaves the row untouched
+        let stale = repository
+            .save(&VersionedNote {
+                text: "stale".to_string(),
+                ..saved
+            })
+            .await;
+        assert!(matches!(stale, Err(ServiceError::Conflict(_))));
+        let stored = repository.find_by_id(&note.id).await.unwrap().unwrap();
+        assert_eq!(stored.text, "second");
+        assert_eq!(stored.version, 2);
+    }
+
     #[test]
     async fn test_repository_provider() {
         // Create provider
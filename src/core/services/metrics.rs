@@ -1,8 +1,8 @@
-use crate::core::metrics::metrics_handler;
+use crate::core::metrics::metrics_handler::{self, MetricsError};
 use metrics_exporter_prometheus::PrometheusHandle;
 
 /// Initialize metrics with Prometheus
-pub fn init_metrics() -> PrometheusHandle {
+pub fn init_metrics() -> Result<PrometheusHandle, MetricsError> {
     metrics_handler::init_metrics()
 }
 
@@ -0,0 +1,184 @@
+//! Embedded SQL migrations
+//!
+//! Migrations live in `migrations/` at the crate root and are embedded at
+//! compile time with `sqlx::migrate!`. They are applied at startup when
+//! `migrations.run_on_startup` is set, or on their own with `--migrate-only`.
+//! A migration that was edited after being applied fails the run with a
+//! checksum mismatch instead of silently diverging.
+
+use tracing::{info, warn};
+
+use crate::core::config::app_config::AppConfig;
+use crate::core::error::AppError;
+
+/// Command line flag running migrations and exiting
+pub const MIGRATE_ONLY_FLAG: &str = "--migrate-only";
+
+#[cfg(feature = "postgres")]
+mod postgres {
+    use std::collections::HashSet;
+
+    use sqlx::PgPool;
+    use sqlx::migrate::{Migrate, MigrateError, Migrator};
+    use tracing::info;
+
+    use crate::core::error::AppError;
+
+    /// Migrations embedded from `migrations/`
+    pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+    fn migration_error(e: MigrateError) -> AppError {
+        match e {
+            MigrateError::VersionMismatch(version) => AppError::ConfigurationError(format!(
+                "Migration {} was modified after it was applied (checksum mismatch)",
+                version
+            )),
+            e => AppError::internal_server_error(format!("Migration failed: {}", e)),
+        }
+    }
+
+    /// Apply pending migrations, returning the versions applied by this run
+    pub async fn run_migrations(pool: &PgPool) -> Result<Vec<i64>, AppError> {
+        let mut conn = pool.acquire().await.map_err(|e| {
+            AppError::internal_server_error(format!("Failed to connect for migrations: {}", e))
+        })?;
+        conn.ensure_migrations_table()
+            .await
+            .map_err(migration_error)?;
+        let already_applied: HashSet<i64> = conn
+            .list_applied_migrations()
+            .await
+            .map_err(migration_error)?
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect();
+        drop(conn);
+
+        MIGRATOR.run(pool).await.map_err(migration_error)?;
+
+        let applied: Vec<i64> = MIGRATOR
+            .iter()
+            .filter(|m| m.migration_type.is_up_migration())
+            .filter(|m| !already_applied.contains(&m.version))
+            .map(|m| {
+                info!("Applied migration {} ({})", m.version, m.description);
+                m.version
+            })
+            .collect();
+
+        Ok(applied)
+    }
+
+    /// Latest successfully applied migration version
+    pub async fn current_version(pool: &PgPool) -> Result<Option<i64>, AppError> {
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+            .fetch_one(pool)
+            .await
+            .map_err(|e| {
+                AppError::internal_server_error(format!("Failed to read schema version: {}", e))
+            })
+    }
+
+    /// Connect to `database_url` and apply pending migrations
+    pub async fn migrate_url(database_url: &str) -> Result<Vec<i64>, AppError> {
+        let pool = PgPool::connect(database_url).await.map_err(|e| {
+            AppError::internal_server_error(format!("Failed to connect for migrations: {}", e))
+        })?;
+        let applied = run_migrations(&pool).await?;
+        info!(
+            "Database schema at version {:?} ({} migrations applied)",
+            current_version(&pool).await?,
+            applied.len()
+        );
+        pool.close().await;
+        Ok(applied)
+    }
+}
+
+#[cfg(feature = "postgres")]
+pub use postgres::{MIGRATOR, current_version, run_migrations};
+
+/// Whether the process was started with `--migrate-only`
+pub fn migrate_only_requested() -> bool {
+    std::env::args().any(|arg| arg == MIGRATE_ONLY_FLAG)
+}
+
+/// Apply migrations if configured (or `force`d by `--migrate-only`)
+pub async fn run_from_config(config: &AppConfig, force: bool) -> Result<(), AppError> {
+    if !force && !config.migrations.run_on_startup {
+        return Ok(());
+    }
+
+    let database_url = config
+        .migrations
+        .database_url
+        .as_deref()
+        .filter(|url| !url.trim().is_empty())
+        .ok_or_else(|| {
+            AppError::ConfigurationError(
+                "migrations.database_url must be set to run migrations".to_string(),
+            )
+        })?;
+
+    #[cfg(feature = "postgres")]
+    {
+        postgres::migrate_url(database_url).await?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    {
+        let _ = database_url;
+        if force {
+            return Err(AppError::ConfigurationError(
+                "Migrations require the 'postgres' feature".to_string(),
+            ));
+        }
+        warn!("migrations.run_on_startup is set but the 'postgres' feature is disabled");
+        info!("Skipping database migrations");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_disabled_migrations_are_skipped() {
+        assert!(run_from_config(&AppConfig::default(), false).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_forced_migrations_require_database_url() {
+        let result = run_from_config(&AppConfig::default(), true).await;
+        assert!(matches!(result, Err(AppError::ConfigurationError(_))));
+    }
+
+    /// Needs a database: set `TEST_DATABASE_URL` (e.g. `postgres://localhost/navius_test`)
+    /// and run `cargo test --features postgres -- --ignored`
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn test_migrations_record_schema_version() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+        run_migrations(&pool).await.unwrap();
+        // Re-running is a no-op
+        assert!(run_migrations(&pool).await.unwrap().is_empty());
+
+        let latest = MIGRATOR.iter().map(|m| m.version).max();
+        assert_eq!(current_version(&pool).await.unwrap(), latest);
+
+        // The pets table matches the fields of the `Pet` entity
+        let columns: Vec<String> = sqlx::query_scalar(
+            "SELECT column_name::TEXT FROM information_schema.columns \
+             WHERE table_name = 'pets' ORDER BY ordinal_position",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(columns, ["id", "name", "created_at", "version"]);
+    }
+}
@@ -0,0 +1,248 @@
+//! Change notifications for repositories
+//!
+//! [`ObservedRepository`] wraps any [`Repository`] and, after each
+//! successful save or delete, calls the registered [`ChangeListener`]s with
+//! an [`EntityChange`]. Use it to keep a search index in sync or to emit
+//! events without touching every call site.
+//!
+//! Listeners run in registration order once the write has succeeded. A
+//! listener that fails or panics is logged and skipped; the write has
+//! already happened and its result is returned unchanged.
+//!
+//! ```ignore
+//! let pets = ObservedRepository::new(Arc::new(pet_repository))
+//!     .with_listener(Arc::new(SearchIndexer::new(index)));
+//! ```
+
+use std::fmt;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::FutureExt;
+use tracing::{error, warn};
+
+use crate::core::models::{Entity, Repository};
+use crate::core::services::error::ServiceError;
+
+/// Kind of change made to an entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// A change made through an [`ObservedRepository`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityChange<E: Entity> {
+    pub kind: ChangeKind,
+    /// The entity as saved, or as it was before being deleted
+    pub entity: E,
+}
+
+/// Receives changes after they have been written
+#[async_trait]
+pub trait ChangeListener<E: Entity>: Send + Sync + 'static {
+    async fn on_change(&self, change: &EntityChange<E>) -> Result<(), ServiceError>;
+}
+
+/// Repository decorator notifying listeners of successful writes
+pub struct ObservedRepository<E: Entity> {
+    inner: Arc<dyn Repository<E>>,
+    listeners: Vec<Arc<dyn ChangeListener<E>>>,
+}
+
+impl<E: Entity> fmt::Debug for ObservedRepository<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservedRepository")
+            .field("inner", &self.inner)
+            .field("listeners", &self.listeners.len())
+            .finish()
+    }
+}
+
+impl<E: Entity> ObservedRepository<E> {
+    /// Wrap `inner` with no listeners
+    pub fn new(inner: Arc<dyn Repository<E>>) -> Self {
+        Self {
+            inner,
+            listeners: Vec::new(),
+        }
+    }
+
+    /// Register a listener
+    pub fn with_listener(mut self, listener: Arc<dyn ChangeListener<E>>) -> Self {
+        self.listeners.push(listener);
+        self
+    }
+
+    async fn notify(&self, kind: ChangeKind, entity: E) {
+        let change = EntityChange { kind, entity };
+        for listener in &self.listeners {
+            match AssertUnwindSafe(listener.on_change(&change))
+                .catch_unwind()
+                .await
+            {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => warn!(
+                    "Change listener failed for {:?} {}: {}",
+                    change.kind,
+                    E::collection_name(),
+                    e
+                ),
+                Err(_) => error!(
+                    "Change listener panicked for {:?} {}",
+                    change.kind,
+                    E::collection_name()
+                ),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<E: Entity> Repository<E> for ObservedRepository<E> {
+    async fn find_by_id(&self, id: &E::Id) -> Result<Option<E>, ServiceError> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<E>, ServiceError> {
+        self.inner.find_all().await
+    }
+
+    async fn save(&self, entity: &E) -> Result<E, ServiceError> {
+        if self.listeners.is_empty() {
+            return self.inner.save(entity).await;
+        }
+
+        // Concurrent writers may race here; the kind is best effort
+        let kind = if self.inner.exists(entity.id()).await? {
+            ChangeKind::Updated
+        } else {
+            ChangeKind::Created
+        };
+        let saved = self.inner.save(entity).await?;
+        self.notify(kind, saved.clone()).await;
+        Ok(saved)
+    }
+
+    async fn delete(&self, id: &E::Id) -> Result<bool, ServiceError> {
+        if self.listeners.is_empty() {
+            return self.inner.delete(id).await;
+        }
+
+        let existing = self.inner.find_by_id(id).await?;
+        let deleted = self.inner.delete(id).await?;
+        if let (true, Some(entity)) = (deleted, existing) {
+            self.notify(ChangeKind::Deleted, entity).await;
+        }
+        Ok(deleted)
+    }
+
+    async fn count(&self) -> Result<usize, ServiceError> {
+        self.inner.count().await
+    }
+
+    async fn exists(&self, id: &E::Id) -> Result<bool, ServiceError> {
+        self.inner.exists(id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::RepositoryConfig;
+    use crate::core::models::entity::test_utils::{Pet, pet};
+    use crate::core::services::memory_repository::InMemoryRepository;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    fn repository() -> Arc<dyn Repository<Pet>> {
+        Arc::new(InMemoryRepository::new(
+            RepositoryConfig::default(),
+            Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+        ))
+    }
+
+    /// Listener recording every change it sees
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<EntityChange<Pet>>>);
+
+    #[async_trait]
+    impl ChangeListener<Pet> for Recorder {
+        async fn on_change(&self, change: &EntityChange<Pet>) -> Result<(), ServiceError> {
+            self.0.lock().unwrap().push(change.clone());
+            Ok(())
+        }
+    }
+
+    struct Failing;
+
+    #[async_trait]
+    impl ChangeListener<Pet> for Failing {
+        async fn on_change(&self, _change: &EntityChange<Pet>) -> Result<(), ServiceError> {
+            Err(ServiceError::Unavailable(
+                "search index is down".to_string(),
+            ))
+        }
+    }
+
+    struct Panicking;
+
+    #[async_trait]
+    impl ChangeListener<Pet> for Panicking {
+        async fn on_change(&self, _change: &EntityChange<Pet>) -> Result<(), ServiceError> {
+            panic!("listener bug");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_update_delete_notify_listener() {
+        let recorder = Arc::new(Recorder::default());
+        let pets = ObservedRepository::new(repository()).with_listener(recorder.clone());
+
+        pets.save(&pet("1", "Rex")).await.unwrap();
+        pets.save(&pet("1", "Max")).await.unwrap();
+        assert!(pets.delete(&"1".to_string()).await.unwrap());
+        // Nothing was deleted, so nothing is reported
+        assert!(!pets.delete(&"1".to_string()).await.unwrap());
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                EntityChange {
+                    kind: ChangeKind::Created,
+                    entity: pet("1", "Rex"),
+                },
+                EntityChange {
+                    kind: ChangeKind::Updated,
+                    entity: pet("1", "Max"),
+                },
+                EntityChange {
+                    kind: ChangeKind::Deleted,
+                    entity: pet("1", "Max"),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_listener_failure_does_not_fail_write() {
+        let recorder = Arc::new(Recorder::default());
+        let pets = ObservedRepository::new(repository())
+            .with_listener(Arc::new(Failing))
+            .with_listener(Arc::new(Panicking))
+            .with_listener(recorder.clone());
+
+        let saved = pets.save(&pet("1", "Rex")).await.unwrap();
+
+        assert_eq!(saved, pet("1", "Rex"));
+        assert_eq!(
+            pets.find_by_id(&"1".to_string()).await.unwrap(),
+            Some(pet("1", "Rex"))
+        );
+        // Later listeners still run
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+    }
+}
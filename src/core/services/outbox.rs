@@ -0,0 +1,370 @@
+//! Transactional outbox for reliable event publishing
+//!
+//! Events are written to the `outbox` collection in the same transaction as
+//! the business data they describe, and the transaction commits both
+//! atomically. A background worker publishes unsent events and only then
+//! marks their rows sent, giving at-least-once delivery: a crash between
+//! publishing and marking means the event is published again. Sent rows are
+//! kept with their publish time as a record of what went out.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::core::services::database_interface::DatabaseOperations;
+use crate::core::services::error::ServiceError;
+use crate::core::services::transaction::Transaction;
+use crate::core::utils::sources;
+
+/// Default collection (table) holding outbox events
+pub const OUTBOX_COLLECTION: &str = "outbox";
+
+/// Shortest worker interval; shorter ones are raised to it
+const MIN_WORKER_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Domain event stored in the outbox
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    pub id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    /// Unix timestamp in milliseconds
+    pub created_at: i64,
+    /// Unix timestamp in milliseconds, set once the event has been published
+    pub sent_at: Option<i64>,
+    /// Number of failed publish attempts
+    pub attempts: u32,
+}
+
+impl OutboxEvent {
+    /// Create a new unsent event
+    pub fn new(event_type: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            event_type: event_type.into(),
+            payload,
+            created_at: sources::now_millis(),
+            sent_at: None,
+            attempts: 0,
+        }
+    }
+
+    /// Whether the event has been published
+    pub fn is_sent(&self) -> bool {
+        self.sent_at.is_some()
+    }
+}
+
+/// Destination for outbox events (message broker, webhook, ...)
+#[async_trait]
+pub trait EventPublisher: Send + Sync + 'static {
+    /// Publish an event; returning `Ok` marks it sent
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), ServiceError>;
+}
+
+/// Transactional outbox over a database collection
+#[derive(Clone)]
+pub struct Outbox {
+    db: Arc<dyn DatabaseOperations>,
+    collection: String,
+    batch_size: usize,
+}
+
+impl Outbox {
+    /// Create an outbox stored in the default `outbox` collection
+    pub fn new(db: Arc<dyn DatabaseOperations>) -> Self {
+        Self {
+            db,
+            collection: OUTBOX_COLLECTION.to_string(),
+            batch_size: 100,
+        }
+    }
+
+    /// Store events in a different collection
+    pub fn with_collection(mut self, collection: impl Into<String>) -> Self {
+        self.collection = collection.into();
+        self
+    }
+
+    /// Maximum number of events published per worker pass
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Enqueue an event as part of `tx`; it is only visible once `tx` commits
+    pub fn enqueue(&self, tx: &mut Transaction, event: OutboxEvent) -> Result<(), ServiceError> {
+        let value = serde_json::to_string(&event).map_err(|e| {
+            ServiceError::conversion_error(format!("Failed to serialize outbox event: {}", e))
+        })?;
+        tx.set(&self.collection, &event.id, &value);
+        Ok(())
+    }
+
+    /// Unsent events, oldest first
+    pub async fn pending(&self) -> Result<Vec<OutboxEvent>, ServiceError> {
+        let mut events: Vec<OutboxEvent> = self
+            .db
+            .query(&self.collection, "")
+            .await?
+            .iter()
+            .filter_map(|value| match serde_json::from_str::<OutboxEvent>(value) {
+                Ok(event) => Some(event),
+                Err(e) => {
+                    warn!("Skipping unreadable outbox row: {}", e);
+                    None
+                }
+            })
+            .filter(|event| !event.is_sent())
+            .collect();
+
+        events.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        events.truncate(self.batch_size);
+        Ok(events)
+    }
+
+    /// Publish one batch of unsent events, returning how many were sent
+    ///
+    /// Published events are marked sent with the time of publishing. Events
+    /// that fail to publish stay pending with their attempt count incremented and are
+    /// retried on the next pass.
+    pub async fn publish_pending(
+        &self,
+        publisher: &dyn EventPublisher,
+    ) -> Result<usize, ServiceError> {
+        let mut sent = 0;
+
+        for mut event in self.pending().await? {
+            match publisher.publish(&event).await {
+                Ok(()) => {
+                    event.sent_at = Some(sources::now_millis());
+                    self.save(&event).await?;
+                    sent += 1;
+                }
+                Err(e) => {
+                    event.attempts += 1;
+                    warn!(
+                        "Failed to publish outbox event {} ({}), attempt {}: {}",
+                        event.id, event.event_type, event.attempts, e
+                    );
+                    self.save(&event).await?;
+                }
+            }
+        }
+
+        if sent > 0 {
+            debug!("Published {} outbox events", sent);
+        }
+        Ok(sent)
+    }
+
+    async fn save(&self, event: &OutboxEvent) -> Result<(), ServiceError> {
+        let value = serde_json::to_string(event).map_err(|e| {
+            ServiceError::conversion_error(format!("Failed to serialize outbox event: {}", e))
+        })?;
+        self.db.set(&self.collection, &event.id, &value).await
+    }
+
+    /// Spawn a worker that publishes pending events every `interval`
+    ///
+    /// An interval shorter than a millisecond (including zero) is raised to
+    /// one millisecond.
+    pub fn spawn_worker(
+        &self,
+        publisher: Arc<dyn EventPublisher>,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        let interval = if interval < MIN_WORKER_INTERVAL {
+            warn!(
+                "Outbox worker interval {:?} is too short, using {:?}",
+                interval, MIN_WORKER_INTERVAL
+            );
+            MIN_WORKER_INTERVAL
+        } else {
+            interval
+        };
+        let outbox = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = outbox.publish_pending(publisher.as_ref()).await {
+                    error!("Outbox worker pass failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::services::database_interface::DatabaseConfig;
+    use crate::core::services::memory_database::InMemoryDatabase;
+    use crate::core::services::transaction::with_transaction;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingPublisher {
+        published: Mutex<Vec<String>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl EventPublisher for RecordingPublisher {
+        async fn publish(&self, event: &OutboxEvent) -> Result<(), ServiceError> {
+            if self.fail {
+                return Err(ServiceError::unavailable("broker down"));
+            }
+            self.published
+                .lock()
+                .unwrap()
+                .push(event.event_type.clone());
+            Ok(())
+        }
+    }
+
+    fn database() -> Arc<dyn DatabaseOperations> {
+        Arc::new(InMemoryDatabase::new(Arc::new(DatabaseConfig::default())))
+    }
+
+    async fn create_order(outbox: &Outbox, db: Arc<dyn DatabaseOperations>, fail: bool) {
+        let outbox = outbox.clone();
+        let _ = with_transaction(db, move |tx| {
+            Box::pin(async move {
+                tx.set("orders", "42", "{\"id\":42}");
+                outbox.enqueue(tx, OutboxEvent::new("order.created", json!({ "id": 42 })))?;
+                if fail {
+                    return Err(ServiceError::validation("order rejected"));
+                }
+                Ok(())
+            })
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_rolled_back_transaction_leaves_no_outbox_row() {
+        let db = database();
+        let outbox = Outbox::new(db.clone());
+
+        create_order(&outbox, db.clone(), true).await;
+
+        assert!(db.query(OUTBOX_COLLECTION, "").await.unwrap().is_empty());
+        assert_eq!(db.get("orders", "42").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_event_and_entity_commit_atomically() {
+        let db = database();
+        let outbox = Outbox::new(db.clone());
+        db.set("orders", "42", "{\"id\":42}").await.unwrap();
+
+        // The entity insert conflicts, so the event must not be stored either
+        let result = with_transaction(db.clone(), |tx| {
+            let outbox = outbox.clone();
+            Box::pin(async move {
+                outbox.enqueue(tx, OutboxEvent::new("order.created", json!({ "id": 42 })))?;
+                tx.insert("orders", "42", "{\"id\":42}");
+                Ok(())
+            })
+        })
+        .await;
+
+        assert!(matches!(result, Err(ServiceError::Conflict(_))));
+        assert!(db.query(OUTBOX_COLLECTION, "").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_committed_event_is_published_and_marked_sent() {
+        let db = database();
+        let outbox = Outbox::new(db.clone());
+        let publisher = RecordingPublisher::default();
+
+        create_order(&outbox, db.clone(), false).await;
+        assert_eq!(outbox.pending().await.unwrap().len(), 1);
+
+        assert_eq!(outbox.publish_pending(&publisher).await.unwrap(), 1);
+        assert_eq!(*publisher.published.lock().unwrap(), vec!["order.created"]);
+        assert!(outbox.pending().await.unwrap().is_empty());
+
+        // The row stays, recording when it was published
+        let rows = db.query(OUTBOX_COLLECTION, "").await.unwrap();
+        assert_eq!(rows.len(), 1);
+        let stored: OutboxEvent = serde_json::from_str(&rows[0]).unwrap();
+        assert!(stored.is_sent());
+        assert!(stored.sent_at.unwrap() >= stored.created_at);
+
+        // Already sent events are not published again
+        assert_eq!(outbox.publish_pending(&publisher).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_publish_keeps_event_pending() {
+        let db = database();
+        let outbox = Outbox::new(db.clone());
+        let publisher = RecordingPublisher {
+            fail: true,
+            ..Default::default()
+        };
+
+        create_order(&outbox, db.clone(), false).await;
+
+        assert_eq!(outbox.publish_pending(&publisher).await.unwrap(), 0);
+        let pending = outbox.pending().await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_worker_publishes_committed_events() {
+        let db = database();
+        let outbox = Outbox::new(db.clone());
+        let publisher = Arc::new(RecordingPublisher::default());
+
+        create_order(&outbox, db.clone(), false).await;
+        let worker = outbox.spawn_worker(publisher.clone(), Duration::from_millis(10));
+
+        for _ in 0..100 {
+            if outbox.pending().await.unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        worker.abort();
+
+        assert!(outbox.pending().await.unwrap().is_empty());
+        assert_eq!(publisher.published.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_zero_worker_interval_is_clamped() {
+        let db = database();
+        let outbox = Outbox::new(db.clone());
+        let publisher = Arc::new(RecordingPublisher::default());
+
+        create_order(&outbox, db.clone(), false).await;
+        let worker = outbox.spawn_worker(publisher.clone(), Duration::ZERO);
+
+        for _ in 0..100 {
+            if outbox.pending().await.unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!worker.is_finished());
+        worker.abort();
+
+        assert_eq!(publisher.published.lock().unwrap().len(), 1);
+    }
+}
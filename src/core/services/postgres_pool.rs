@@ -0,0 +1,101 @@
+//! Postgres connection pool built from a [`DatabaseConfig`]
+//!
+//! Statements cut off by the session's `statement_timeout` fail with SQLSTATE
+//! `57014`; converting the `sqlx::Error` into a [`ServiceError`] turns that
+//! into [`ServiceError::Timeout`], which reaches clients as a 504
+//! `AppError::Timeout`.
+
+use std::sync::Arc;
+
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Executor, PgPool};
+use tracing::info;
+
+use crate::core::services::database_interface::DatabaseConfig;
+use crate::core::services::error::ServiceError;
+
+/// SQLSTATE `query_canceled`, raised when `statement_timeout` expires
+pub const QUERY_CANCELED: &str = "57014";
+
+impl From<sqlx::Error> for ServiceError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::Database(db) if db.code().as_deref() == Some(QUERY_CANCELED) => {
+                ServiceError::timeout(format!("Statement cancelled: {}", db.message()))
+            }
+            sqlx::Error::PoolTimedOut => {
+                ServiceError::timeout("Timed out waiting for a database connection")
+            }
+            _ => ServiceError::repository(error.to_string()),
+        }
+    }
+}
+
+/// Connect a pool sized and timed out as `config` describes
+///
+/// Every new connection runs [`DatabaseConfig::session_settings`] before it
+/// is handed out, so server-side timeouts apply to all pooled connections.
+///
+/// Close the pool with [`PgPool::close`] on shutdown so open connections
+/// are returned to the server instead of being dropped mid-session.
+pub async fn connect(config: &DatabaseConfig) -> Result<PgPool, ServiceError> {
+    let connection = config.postgres_connection()?;
+    let settings: Arc<[String]> = config.session_settings().into();
+    let pool = PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout())
+        .after_connect(move |conn, _meta| {
+            let settings = settings.clone();
+            Box::pin(async move {
+                for setting in settings.iter() {
+                    (&mut *conn).execute(setting.as_str()).await?;
+                }
+                Ok(())
+            })
+        })
+        .connect(&connection.to_url())
+        .await
+        .map_err(|e| ServiceError::unavailable(format!("Failed to connect to database: {}", e)))?;
+
+    info!(
+        "Connected to database {} on {}:{}",
+        connection.database, connection.host, connection.port
+    );
+    Ok(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::AppError;
+
+    /// Needs a database: set `TEST_DATABASE_URL` (e.g. `postgres://localhost/navius_test`)
+    /// and run `cargo test --features postgres -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn test_statement_timeout_is_enforced_by_the_server() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let config = DatabaseConfig {
+            statement_timeout_ms: Some(50),
+            ..DatabaseConfig::from_url(&url).unwrap()
+        };
+        let pool = connect(&config).await.unwrap();
+
+        let timeout: String = sqlx::query_scalar("SHOW statement_timeout")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(timeout, "50ms");
+
+        let error = ServiceError::from(
+            sqlx::query("SELECT pg_sleep(5)")
+                .execute(&pool)
+                .await
+                .unwrap_err(),
+        );
+        assert!(matches!(error, ServiceError::Timeout(_)), "{:?}", error);
+        assert!(matches!(AppError::from(error), AppError::Timeout(_)));
+
+        pool.close().await;
+    }
+}
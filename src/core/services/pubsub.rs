@@ -0,0 +1,362 @@
+//! Typed publish/subscribe over Redis
+//!
+//! [`PubSub`] serializes messages as JSON over a [`PubSubBackend`]. The Redis
+//! backend (feature `redis`) fans messages out across instances and
+//! resubscribes transparently after a lost connection; [`MemoryPubSub`] keeps
+//! everything in-process for single instances and tests. Messages that fail
+//! to deserialize are logged and skipped, never ending the stream.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use futures::stream::{self, BoxStream};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+use crate::core::services::cache_provider::CacheError;
+
+/// Channel carrying [`CacheInvalidation`] messages
+pub const CACHE_INVALIDATION_CHANNEL: &str = "navius:cache:invalidate";
+
+/// Raw message transport
+#[async_trait]
+pub trait PubSubBackend: Send + Sync + 'static {
+    /// Publish a payload to every current subscriber of `channel`
+    async fn publish(&self, channel: &str, payload: Vec<u8>) -> Result<(), CacheError>;
+
+    /// Stream of payloads published to `channel` from now on
+    async fn subscribe(&self, channel: &str) -> Result<BoxStream<'static, Vec<u8>>, CacheError>;
+}
+
+/// In-process backend built on broadcast channels
+#[derive(Clone)]
+pub struct MemoryPubSub {
+    channels: Arc<Mutex<HashMap<String, broadcast::Sender<Vec<u8>>>>>,
+    capacity: usize,
+}
+
+impl MemoryPubSub {
+    /// Create a backend buffering up to 1024 messages per slow subscriber
+    pub fn new() -> Self {
+        Self {
+            channels: Arc::new(Mutex::new(HashMap::new())),
+            capacity: 1024,
+        }
+    }
+
+    fn sender(&self, channel: &str) -> broadcast::Sender<Vec<u8>> {
+        self.channels
+            .lock()
+            .unwrap()
+            .entry(channel.to_string())
+            .or_insert_with(|| broadcast::channel(self.capacity).0)
+            .clone()
+    }
+}
+
+impl Default for MemoryPubSub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PubSubBackend for MemoryPubSub {
+    async fn publish(&self, channel: &str, payload: Vec<u8>) -> Result<(), CacheError> {
+        // Publishing with no subscribers is not an error
+        let _ = self.sender(channel).send(payload);
+        Ok(())
+    }
+
+    async fn subscribe(&self, channel: &str) -> Result<BoxStream<'static, Vec<u8>>, CacheError> {
+        let receiver = self.sender(channel).subscribe();
+        let channel = channel.to_string();
+        Ok(stream::unfold(receiver, move |mut receiver| {
+            let channel = channel.clone();
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(payload) => return Some((payload, receiver)),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!(
+                                "Subscriber on {} lagged, {} messages dropped",
+                                channel, skipped
+                            );
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        })
+        .boxed())
+    }
+}
+
+#[cfg(feature = "redis")]
+mod redis_backend {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use futures::stream::{self, BoxStream};
+    use redis::aio::ConnectionManager;
+    use tokio::sync::{OnceCell, mpsc};
+    use tracing::{debug, info, warn};
+
+    use super::PubSubBackend;
+    use crate::core::services::cache_provider::{CacheConfig, CacheError};
+
+    /// Redis backend; publishing and subscriptions reconnect on their own
+    pub struct RedisPubSub {
+        client: redis::Client,
+        publisher: OnceCell<ConnectionManager>,
+        reconnect_delay: Duration,
+    }
+
+    impl RedisPubSub {
+        /// Create a backend for the Redis server at `url`
+        pub fn new(url: &str) -> Result<Self, CacheError> {
+            let client = redis::Client::open(url)
+                .map_err(|e| CacheError::Configuration(format!("Invalid Redis URL: {}", e)))?;
+            Ok(Self {
+                client,
+                publisher: OnceCell::new(),
+                reconnect_delay: Duration::from_secs(1),
+            })
+        }
+
+        /// Create a backend from a Redis cache configuration (`url` provider setting)
+        pub fn from_config(config: &CacheConfig) -> Result<Self, CacheError> {
+            let url = config.provider_config.get("url").ok_or_else(|| {
+                CacheError::Configuration("Missing required Redis URL configuration".to_string())
+            })?;
+            Self::new(url)
+        }
+
+        /// Delay before resubscribing after a lost connection
+        pub fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+            self.reconnect_delay = delay;
+            self
+        }
+    }
+
+    #[async_trait]
+    impl PubSubBackend for RedisPubSub {
+        async fn publish(&self, channel: &str, payload: Vec<u8>) -> Result<(), CacheError> {
+            let publisher = self
+                .publisher
+                .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+                .await
+                .map_err(|e| CacheError::Connection(format!("Redis connection failed: {}", e)))?;
+
+            redis::cmd("PUBLISH")
+                .arg(channel)
+                .arg(payload)
+                .query_async::<i64>(&mut publisher.clone())
+                .await
+                .map_err(|e| CacheError::Operation(format!("Redis publish failed: {}", e)))?;
+            Ok(())
+        }
+
+        async fn subscribe(
+            &self,
+            channel: &str,
+        ) -> Result<BoxStream<'static, Vec<u8>>, CacheError> {
+            let (tx, rx) = mpsc::channel(1024);
+            let client = self.client.clone();
+            let channel = channel.to_string();
+            let reconnect_delay = self.reconnect_delay;
+
+            tokio::spawn(async move {
+                let forward = async {
+                    loop {
+                        let mut pubsub = match client.get_async_pubsub().await {
+                            Ok(pubsub) => pubsub,
+                            Err(e) => {
+                                warn!("Redis subscribe to {} failed: {}", channel, e);
+                                tokio::time::sleep(reconnect_delay).await;
+                                continue;
+                            }
+                        };
+                        if let Err(e) = pubsub.subscribe(&channel).await {
+                            warn!("Redis subscribe to {} failed: {}", channel, e);
+                            tokio::time::sleep(reconnect_delay).await;
+                            continue;
+                        }
+                        info!("Subscribed to Redis channel {}", channel);
+
+                        let mut messages = pubsub.into_on_message();
+                        while let Some(message) = messages.next().await {
+                            if tx.send(message.get_payload_bytes().to_vec()).await.is_err() {
+                                return;
+                            }
+                        }
+
+                        warn!("Lost Redis subscription to {}, reconnecting", channel);
+                        tokio::time::sleep(reconnect_delay).await;
+                    }
+                };
+
+                // Stop when the subscriber drops the stream, even while the channel is quiet
+                tokio::select! {
+                    _ = tx.closed() => {}
+                    _ = forward => {}
+                }
+                debug!("Unsubscribed from Redis channel {}", channel);
+            });
+
+            Ok(stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|payload| (payload, rx))
+            })
+            .boxed())
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_backend::RedisPubSub;
+
+/// Typed publish/subscribe handle
+#[derive(Clone)]
+pub struct PubSub {
+    backend: Arc<dyn PubSubBackend>,
+}
+
+impl PubSub {
+    /// Create a handle over `backend`
+    pub fn new(backend: Arc<dyn PubSubBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Create a handle delivering messages within this process only
+    pub fn in_memory() -> Self {
+        Self::new(Arc::new(MemoryPubSub::new()))
+    }
+
+    /// Publish `message` to `channel` as JSON
+    pub async fn publish<T: Serialize>(
+        &self,
+        channel: &str,
+        message: &T,
+    ) -> Result<(), CacheError> {
+        let payload = serde_json::to_vec(message).map_err(|e| {
+            CacheError::Serialization(format!("Failed to encode message for {}: {}", channel, e))
+        })?;
+        self.backend.publish(channel, payload).await
+    }
+
+    /// Stream of messages published to `channel`, skipping malformed ones
+    pub async fn subscribe<T>(&self, channel: &str) -> Result<BoxStream<'static, T>, CacheError>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let channel = channel.to_string();
+        let payloads = self.backend.subscribe(&channel).await?;
+        Ok(payloads
+            .filter_map(move |payload| {
+                let message = match serde_json::from_slice(&payload) {
+                    Ok(message) => Some(message),
+                    Err(e) => {
+                        warn!("Skipping malformed message on {}: {}", channel, e);
+                        None
+                    }
+                };
+                futures::future::ready(message)
+            })
+            .boxed())
+    }
+}
+
+/// Cache invalidation broadcast between instances
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheInvalidation {
+    /// Instance that made the change
+    pub origin: String,
+    /// Cache name
+    pub cache: String,
+    /// Deleted key, or `None` when the whole cache was cleared
+    pub key: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct PetAdopted {
+        id: u64,
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_published_message_is_received() {
+        let pubsub = PubSub::in_memory();
+        let mut messages = pubsub.subscribe::<PetAdopted>("pets").await.unwrap();
+
+        pubsub
+            .publish(
+                "pets",
+                &PetAdopted {
+                    id: 1,
+                    name: "Rex".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(1), messages.next())
+            .await
+            .unwrap();
+        assert_eq!(
+            received,
+            Some(PetAdopted {
+                id: 1,
+                name: "Rex".to_string()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_messages_are_skipped() {
+        let backend = Arc::new(MemoryPubSub::new());
+        let pubsub = PubSub::new(backend.clone());
+        let mut messages = pubsub.subscribe::<PetAdopted>("pets").await.unwrap();
+
+        backend.publish("pets", b"not json".to_vec()).await.unwrap();
+        backend
+            .publish("pets", br#"{"id":"wrong"}"#.to_vec())
+            .await
+            .unwrap();
+        pubsub
+            .publish(
+                "pets",
+                &PetAdopted {
+                    id: 2,
+                    name: "Tom".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(1), messages.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_messages_are_scoped_to_channel() {
+        let pubsub = PubSub::in_memory();
+        let mut messages = pubsub.subscribe::<u32>("pets").await.unwrap();
+
+        pubsub.publish("owners", &1u32).await.unwrap();
+        pubsub.publish("pets", &2u32).await.unwrap();
+
+        assert_eq!(messages.next().await, Some(2));
+    }
+}
@@ -9,6 +9,7 @@ use bincode::{Decode, Encode};
 use serde::{Serialize, de::DeserializeOwned};
 use tracing::{info, warn};
 
+use crate::core::services::cache_health::BackendHealth;
 use crate::core::services::cache_provider::{
     CacheConfig, CacheError, CacheFactory, CacheOperations, CacheProvider, CacheStats,
     DynCacheOperations, TypedCache, TypedCacheFactory,
@@ -28,6 +29,7 @@ impl RedisCacheProvider {
 pub struct RedisCache {
     name: String,
     config: CacheConfig,
+    health: Arc<BackendHealth>,
 }
 
 /// Typed cache for Redis implementation
@@ -54,27 +56,15 @@ where
     T: Encode + Decode<()> + Send + Sync + 'static,
 {
     async fn get(&self, _key: &str) -> Result<Option<T>, CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.cache.name
-        )))
+        self.cache.health.track(self.cache.unavailable()).await
     }
 
     async fn set(&self, _key: &str, _value: T, _ttl: Option<Duration>) -> Result<(), CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.cache.name
-        )))
+        self.cache.health.track(self.cache.unavailable()).await
     }
 
     async fn get_many(&self, _keys: &[&str]) -> Result<HashMap<String, Option<T>>, CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.cache.name
-        )))
+        self.cache.health.track(self.cache.unavailable()).await
     }
 
     async fn set_many(
@@ -82,11 +72,18 @@ where
         _items: HashMap<String, T>,
         _ttl: Option<Duration>,
     ) -> Result<(), CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.cache.name
-        )))
+        self.cache.health.track(self.cache.unavailable()).await
+    }
+
+    async fn set_with_tags(
+        &self,
+        _key: &str,
+        _value: T,
+        _ttl: Option<Duration>,
+        _tags: &[&str],
+    ) -> Result<(), CacheError> {
+        // Maps to `SET key` plus `SADD tag:<tag> key` per tag in one MULTI once the client is available
+        self.cache.health.track(self.cache.unavailable()).await
     }
 }
 
@@ -104,10 +101,22 @@ impl RedisCache {
     /// Create a new Redis cache
     pub fn new(config: CacheConfig) -> Self {
         Self {
+            health: Arc::new(BackendHealth::new(config.name.clone(), "redis")),
             name: config.name.clone(),
             config,
         }
     }
+
+    /// Result of every operation until the Redis client is available
+    ///
+    /// Reported as a connection error, so the backend shows as down and a
+    /// [`FallbackCache`](crate::core::services::FallbackCache) serves instead.
+    async fn unavailable<T>(&self) -> Result<T, CacheError> {
+        Err(CacheError::Connection(format!(
+            "Redis client for {} is not available",
+            self.name
+        )))
+    }
 }
 
 impl CacheFactory for RedisCache {
@@ -122,55 +131,51 @@ impl CacheFactory for RedisCache {
 #[async_trait]
 impl DynCacheOperations for RedisCache {
     async fn delete(&self, _key: &str) -> Result<bool, CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.name
-        )))
+        self.health.track(self.unavailable()).await
     }
 
     async fn clear(&self) -> Result<(), CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.name
-        )))
+        self.health.track(self.unavailable()).await
     }
 
     async fn exists(&self, _key: &str) -> Result<bool, CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.name
-        )))
+        self.health.track(self.unavailable()).await
     }
 
     async fn delete_many(&self, _keys: &[&str]) -> Result<usize, CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.name
-        )))
+        self.health.track(self.unavailable()).await
     }
 
     async fn increment(&self, _key: &str, _delta: i64) -> Result<i64, CacheError> {
-        // Redis implementation not yet available
-        Err(CacheError::Operation(format!(
-            "Redis provider for {} is not fully implemented",
-            self.name
-        )))
+        self.health.track(self.unavailable()).await
+    }
+
+    async fn ttl(&self, _key: &str) -> Result<Option<Duration>, CacheError> {
+        // Maps to `TTL key` (-2 missing, -1 no expiry) once the client is available
+        self.health.track(self.unavailable()).await
+    }
+
+    async fn touch(&self, _key: &str, _ttl_seconds: u64) -> Result<bool, CacheError> {
+        // Maps to `EXPIRE key ttl_seconds` (1 if the key existed) once the client is available
+        self.health.track(self.unavailable()).await
+    }
+
+    async fn invalidate_tag(&self, _tag: &str) -> Result<usize, CacheError> {
+        // Maps to `SMEMBERS tag:<tag>`, then `DEL` of the members and the set, once the client is available
+        self.health.track(self.unavailable()).await
     }
 
     fn stats(&self) -> Result<CacheStats, CacheError> {
-        // Return empty stats
-        Ok(CacheStats {
+        let mut stats = CacheStats {
             size: 0,
             hits: 0,
             misses: 0,
             evictions: 0,
             capacity: self.config.capacity,
             custom_metrics: HashMap::new(),
-        })
+        };
+        self.health.add_to(&mut stats);
+        Ok(stats)
     }
 
     fn name(&self) -> &str {
@@ -191,6 +196,7 @@ impl Clone for RedisCache {
         Self {
             name: self.name.clone(),
             config: self.config.clone(),
+            health: Arc::clone(&self.health),
         }
     }
 }
@@ -216,10 +222,12 @@ impl CacheProvider for RedisCacheProvider {
             ));
         }
 
-        // Placeholder - not actually connecting to Redis
-        Err(CacheError::Configuration(
-            "Redis provider is not fully implemented yet".to_string(),
-        ))
+        // No client yet: every operation fails as unreachable
+        warn!(
+            "Redis client is not available, cache {} will report its backend down",
+            config.name
+        );
+        Ok(Box::new(RedisCache::new(config)))
     }
 
     fn supports(&self, config: &CacheConfig) -> bool {
@@ -285,11 +293,15 @@ mod tests {
         let result = provider.create_cache(config.clone()).await;
         assert!(result.is_err());
 
-        // With URL but still should fail as not implemented
+        // With URL the cache is created, but cannot reach Redis
         config
             .provider_config
             .insert("url".to_string(), "redis://localhost:6379".to_string());
-        let result = provider.create_cache(config).await;
-        assert!(result.is_err());
+        let cache = provider.create_cache(config).await.unwrap();
+        assert!(matches!(
+            cache.exists("key").await,
+            Err(CacheError::Connection(_))
+        ));
+        assert_eq!(cache.stats().unwrap().custom_metrics["redis.up"], "0");
     }
 }
@@ -0,0 +1,714 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::request::Parts,
+    middleware::Next,
+    response::Response,
+};
+use futures::future::BoxFuture;
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::debug;
+
+use crate::core::error::AppError;
+use crate::core::error::middleware::request_id_of;
+use crate::core::models::{Entity, Repository};
+use crate::core::services::database_interface::{BatchWrite, DatabaseOperations};
+use crate::core::services::error::ServiceError;
+
+/// Unit of work over a database
+///
+/// Writes are staged and only applied to the database on commit, so a
+/// rolled-back (or dropped) transaction leaves no trace. Reads see the
+/// transaction's own staged writes.
+///
+/// Isolation is optimistic: every value read from the database is checked
+/// again on commit, and the commit fails with a conflict, applying nothing,
+/// if any of them changed in the meantime. Concurrent read-modify-write
+/// cycles on the same key therefore can't lose updates. Keys added to a
+/// collection after [`Transaction::values`] read it are not detected.
+pub struct Transaction {
+    db: Arc<dyn DatabaseOperations>,
+    writes: Vec<BatchWrite>,
+    /// First value read from the database for each `(collection, key)`
+    reads: HashMap<(String, String), Option<String>>,
+}
+
+impl Transaction {
+    /// Begin a transaction against a database
+    pub fn begin(db: Arc<dyn DatabaseOperations>) -> Self {
+        Self {
+            db,
+            writes: Vec::new(),
+            reads: HashMap::new(),
+        }
+    }
+
+    /// Get a value, including writes staged in this transaction
+    pub async fn get(
+        &mut self,
+        collection: &str,
+        key: &str,
+    ) -> Result<Option<String>, ServiceError> {
+        match self
+            .writes
+            .iter()
+            .rev()
+            .find(|write| write.targets(collection, key))
+        {
+            Some(BatchWrite::Set { value, .. } | BatchWrite::Insert { value, .. }) => {
+                Ok(Some(value.clone()))
+            }
+            Some(BatchWrite::Delete { .. }) => Ok(None),
+            _ => {
+                let value = self.db.get(collection, key).await?;
+                self.record_read(collection, key, value.clone());
+                Ok(value)
+            }
+        }
+    }
+
+    /// Remember the value read for a key, to be checked on commit
+    fn record_read(&mut self, collection: &str, key: &str, value: Option<String>) {
+        self.reads
+            .entry((collection.to_string(), key.to_string()))
+            .or_insert(value);
+    }
+
+    /// All values in `collection`, including writes staged in this transaction
+    ///
+    /// `key_of` recovers the key of each stored value so that staged writes
+    /// replace or remove it.
+    pub async fn values<F>(
+        &mut self,
+        collection: &str,
+        key_of: F,
+    ) -> Result<Vec<String>, ServiceError>
+    where
+        F: Fn(&str) -> Result<String, ServiceError>,
+    {
+        let mut values = HashMap::new();
+        for value in self.db.query(collection, "").await? {
+            let key = key_of(&value)?;
+            self.record_read(collection, &key, Some(value.clone()));
+            values.insert(key, value);
+        }
+        for write in &self.writes {
+            match write {
+                BatchWrite::Set {
+                    collection: c,
+                    key,
+                    value,
+                }
+                | BatchWrite::Insert {
+                    collection: c,
+                    key,
+                    value,
+                } if c == collection => {
+                    values.insert(key.clone(), value.clone());
+                }
+                BatchWrite::Delete { collection: c, key } if c == collection => {
+                    values.remove(key);
+                }
+                _ => {}
+            }
+        }
+        Ok(values.into_values().collect())
+    }
+
+    /// Stage a value to be set on commit
+    pub fn set(&mut self, collection: &str, key: &str, value: &str) {
+        self.writes.push(BatchWrite::Set {
+            collection: collection.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    /// Stage a value to be inserted on commit
+    ///
+    /// Unlike [`Transaction::set`], the commit fails with a conflict and
+    /// applies none of the staged writes if the key already exists, which
+    /// acts as a unique constraint between concurrent transactions.
+    pub fn insert(&mut self, collection: &str, key: &str, value: &str) {
+        self.writes.push(BatchWrite::Insert {
+            collection: collection.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    /// Stage a value to be deleted on commit
+    pub fn delete(&mut self, collection: &str, key: &str) {
+        self.writes.push(BatchWrite::Delete {
+            collection: collection.to_string(),
+            key: key.to_string(),
+        });
+    }
+
+    /// Number of staged writes
+    pub fn pending_writes(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Apply all staged writes in order, atomically
+    ///
+    /// Either every write is applied or, if any fails (such as an insert of
+    /// an existing key, or a value read by this transaction having changed),
+    /// none is.
+    pub async fn commit(self) -> Result<(), ServiceError> {
+        debug!("Committing transaction with {} writes", self.writes.len());
+        if self.writes.is_empty() {
+            return Ok(());
+        }
+        let mut batch: Vec<BatchWrite> = self
+            .reads
+            .into_iter()
+            .map(|((collection, key), expected)| BatchWrite::Check {
+                collection,
+                key,
+                expected,
+            })
+            .collect();
+        batch.extend(self.writes);
+        self.db.apply_batch(&batch).await
+    }
+
+    /// Discard all staged writes
+    pub fn rollback(self) {
+        debug!("Rolling back transaction with {} writes", self.writes.len());
+    }
+}
+
+/// Run `operation` in a transaction, committing on `Ok` and rolling back on `Err`
+///
+/// ```ignore
+/// with_transaction(db, |tx| Box::pin(async move {
+///     tx.set("orders", "42", "{...}");
+///     outbox.enqueue(tx, OutboxEvent::new("order.created", json!({"id": 42})))?;
+///     Ok(())
+/// }))
+/// .await?;
+/// ```
+pub async fn with_transaction<T, F>(
+    db: Arc<dyn DatabaseOperations>,
+    operation: F,
+) -> Result<T, ServiceError>
+where
+    F: for<'tx> FnOnce(&'tx mut Transaction) -> BoxFuture<'tx, Result<T, ServiceError>>,
+{
+    let mut tx = Transaction::begin(db);
+    match operation(&mut tx).await {
+        Ok(value) => {
+            tx.commit().await?;
+            Ok(value)
+        }
+        Err(e) => {
+            tx.rollback();
+            Err(e)
+        }
+    }
+}
+
+/// The transaction of the current request, shared by its repository calls
+///
+/// Extract it in a handler behind [`with_request_transaction`]; every clone
+/// stages writes in the same [`Transaction`].
+///
+/// ```ignore
+/// async fn place_order(tx: RequestTransaction, Json(order): Json<Order>) -> Result<StatusCode, AppError> {
+///     let mut tx = tx.lock().await;
+///     tx.set("orders", &order.id, &serde_json::to_string(&order)?);
+///     reserve_stock(&mut tx, &order).await?;
+///     Ok(StatusCode::CREATED)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RequestTransaction {
+    transaction: Arc<Mutex<Transaction>>,
+}
+
+impl RequestTransaction {
+    /// Exclusive access to the transaction
+    pub async fn lock(&self) -> MutexGuard<'_, Transaction> {
+        self.transaction.lock().await
+    }
+
+    /// Repository of `E` staging its writes in this transaction
+    pub fn repository<E: Entity>(&self) -> TransactionalRepository<E> {
+        TransactionalRepository {
+            transaction: self.clone(),
+            collection: E::collection_name(),
+            _entity: PhantomData,
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestTransaction {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<RequestTransaction>()
+            .cloned()
+            .ok_or_else(|| {
+                AppError::InternalServerError(
+                    "Route is not wrapped in with_request_transaction".to_string(),
+                )
+            })
+    }
+}
+
+/// Repository whose reads and writes go through a [`RequestTransaction`]
+///
+/// Saves and deletes are staged, so they are applied when the request's
+/// transaction commits and discarded when it rolls back; reads see them.
+/// Extract it in a handler behind [`with_request_transaction`], or get one
+/// from [`RequestTransaction::repository`]. Entities are stored as JSON
+/// keyed by their id.
+///
+/// ```ignore
+/// async fn place_order(
+///     orders: TransactionalRepository<Order>,
+///     payments: TransactionalRepository<Payment>,
+///     Json(order): Json<Order>,
+/// ) -> Result<StatusCode, AppError> {
+///     orders.save(&order).await?;
+///     payments.save(&Payment::authorize(&order)).await?;
+///     Ok(StatusCode::CREATED)
+/// }
+/// ```
+pub struct TransactionalRepository<E> {
+    transaction: RequestTransaction,
+    collection: String,
+    _entity: PhantomData<E>,
+}
+
+impl<E> fmt::Debug for TransactionalRepository<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionalRepository")
+            .field("collection", &self.collection)
+            .finish()
+    }
+}
+
+impl<E: Entity> TransactionalRepository<E> {
+    fn key(id: &E::Id) -> String {
+        serde_json::to_string(id)
+            .unwrap_or_else(|_| format!("{:?}", id))
+            .trim_matches('"')
+            .to_string()
+    }
+
+    fn deserialize(json: &str) -> Result<E, ServiceError> {
+        serde_json::from_str(json).map_err(|e| {
+            ServiceError::conversion_error(format!("Failed to deserialize entity: {}", e))
+        })
+    }
+}
+
+#[async_trait]
+impl<E: Entity> Repository<E> for TransactionalRepository<E> {
+    async fn find_by_id(&self, id: &E::Id) -> Result<Option<E>, ServiceError> {
+        let mut tx = self.transaction.lock().await;
+        match tx.get(&self.collection, &Self::key(id)).await? {
+            Some(json) => Ok(Some(Self::deserialize(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self) -> Result<Vec<E>, ServiceError> {
+        let mut tx = self.transaction.lock().await;
+        tx.values(&self.collection, |json| {
+            Self::deserialize(json).map(|entity| Self::key(entity.id()))
+        })
+        .await?
+        .iter()
+        .map(|json| Self::deserialize(json))
+        .collect()
+    }
+
+    async fn save(&self, entity: &E) -> Result<E, ServiceError> {
+        entity.validate()?;
+        let json = serde_json::to_string(entity).map_err(|e| {
+            ServiceError::conversion_error(format!("Failed to serialize entity: {}", e))
+        })?;
+        self.transaction
+            .lock()
+            .await
+            .set(&self.collection, &Self::key(entity.id()), &json);
+        Ok(entity.clone())
+    }
+
+    async fn delete(&self, id: &E::Id) -> Result<bool, ServiceError> {
+        let key = Self::key(id);
+        let mut tx = self.transaction.lock().await;
+        let existed = tx.get(&self.collection, &key).await?.is_some();
+        if existed {
+            tx.delete(&self.collection, &key);
+        }
+        Ok(existed)
+    }
+
+    async fn count(&self) -> Result<usize, ServiceError> {
+        Ok(self.find_all().await?.len())
+    }
+}
+
+impl<E: Entity, S: Send + Sync> FromRequestParts<S> for TransactionalRepository<E> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(RequestTransaction::from_request_parts(parts, state)
+            .await?
+            .repository())
+    }
+}
+
+/// Middleware running each request in a [`RequestTransaction`]
+///
+/// The transaction commits when the handler responds with a 2xx or 3xx
+/// status and rolls back otherwise; a concurrent change to a value it read
+/// fails the commit with a 409. A failed commit replaces the response
+/// with the error. Use with
+/// `axum::middleware::from_fn_with_state(db, with_request_transaction)`.
+pub async fn with_request_transaction(
+    State(db): State<Arc<dyn DatabaseOperations>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let request_id = request_id_of(&request);
+    let shared = RequestTransaction {
+        transaction: Arc::new(Mutex::new(Transaction::begin(db.clone()))),
+    };
+    request.extensions_mut().insert(shared.clone());
+
+    let response = next.run(request).await;
+
+    // Take the staged writes even if a spawned task still holds a clone
+    let tx = std::mem::replace(&mut *shared.lock().await, Transaction::begin(db));
+    let status = response.status();
+    if !status.is_success() && !status.is_redirection() {
+        tx.rollback();
+        return response;
+    }
+    match tx.commit().await {
+        Ok(()) => response,
+        Err(e) => AppError::from(e).into_response_with_request_id(request_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::services::database_interface::DatabaseConfig;
+    use crate::core::services::memory_database::InMemoryDatabase;
+    use axum::{Router, body::Body, http::StatusCode, routing::post};
+    use tower::ServiceExt;
+
+    fn database() -> Arc<dyn DatabaseOperations> {
+        Arc::new(InMemoryDatabase::new(Arc::new(DatabaseConfig::default())))
+    }
+
+    #[tokio::test]
+    async fn test_commit_applies_writes() {
+        let db = database();
+
+        with_transaction(db.clone(), |tx| {
+            Box::pin(async move {
+                tx.set("users", "1", "alice");
+                assert_eq!(tx.get("users", "1").await?, Some("alice".to_string()));
+                Ok(())
+            })
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            db.get("users", "1").await.unwrap(),
+            Some("alice".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_rolls_back_writes() {
+        let db = database();
+        db.set("users", "1", "alice").await.unwrap();
+
+        let result: Result<(), _> = with_transaction(db.clone(), |tx| {
+            Box::pin(async move {
+                tx.delete("users", "1");
+                tx.set("users", "2", "bob");
+                Err(ServiceError::validation("rejected"))
+            })
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            db.get("users", "1").await.unwrap(),
+            Some("alice".to_string())
+        );
+        assert_eq!(db.get("users", "2").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_insert_conflict_applies_no_writes() {
+        let db = database();
+        db.set("users", "1", "alice").await.unwrap();
+
+        let result: Result<(), _> = with_transaction(db.clone(), |tx| {
+            Box::pin(async move {
+                tx.set("users", "2", "bob");
+                tx.insert("users", "1", "mallory");
+                Ok(())
+            })
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            db.get("users", "1").await.unwrap(),
+            Some("alice".to_string())
+        );
+        assert_eq!(db.get("users", "2").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_update_of_a_read_value_conflicts() {
+        let db = database();
+        db.set("stock", "widget", "3").await.unwrap();
+
+        // Both transactions read 3 and decrement it
+        let mut first = Transaction::begin(db.clone());
+        let mut second = Transaction::begin(db.clone());
+        for tx in [&mut first, &mut second] {
+            let stock: u32 = tx.get("stock", "widget").await.unwrap().unwrap().parse().unwrap();
+            tx.set("stock", "widget", &(stock - 1).to_string());
+        }
+
+        first.commit().await.unwrap();
+        let result = second.commit().await;
+
+        assert!(matches!(result, Err(ServiceError::Conflict(_))));
+        assert_eq!(
+            db.get("stock", "widget").await.unwrap(),
+            Some("2".to_string())
+        );
+    }
+
+    /// Writes an order and a payment, then fails unless the product is in stock
+    async fn place_order(tx: RequestTransaction) -> Result<StatusCode, AppError> {
+        let mut tx = tx.lock().await;
+        tx.set("orders", "1", "pending");
+        tx.set("payments", "1", "authorized");
+        let stock: u32 = match tx.get("stock", "widget").await? {
+            Some(stock) => stock.parse().unwrap(),
+            None => return Err(AppError::NotFound("Unknown product".to_string())),
+        };
+        if stock == 0 {
+            return Err(AppError::ConflictError("Out of stock".to_string()));
+        }
+        tx.set("stock", "widget", &(stock - 1).to_string());
+        Ok(StatusCode::CREATED)
+    }
+
+    async fn order(db: Arc<dyn DatabaseOperations>) -> StatusCode {
+        let app = Router::new().route("/orders", post(place_order)).layer(
+            axum::middleware::from_fn_with_state(db, with_request_transaction),
+        );
+        let request = axum::http::Request::post("/orders")
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_request_transaction_commits_on_success() {
+        let db = database();
+        db.set("stock", "widget", "3").await.unwrap();
+
+        assert_eq!(order(db.clone()).await, StatusCode::CREATED);
+
+        assert_eq!(
+            db.get("orders", "1").await.unwrap(),
+            Some("pending".to_string())
+        );
+        assert_eq!(
+            db.get("stock", "widget").await.unwrap(),
+            Some("2".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_transaction_rolls_back_all_writes_on_error() {
+        let db = database();
+        db.set("stock", "widget", "0").await.unwrap();
+
+        assert_eq!(order(db.clone()).await, StatusCode::CONFLICT);
+
+        // Both the order and the payment were discarded
+        assert_eq!(db.get("orders", "1").await.unwrap(), None);
+        assert_eq!(db.get("payments", "1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_extractor_requires_middleware() {
+        let app = Router::new().route("/orders", post(place_order));
+        let request = axum::http::Request::post("/orders")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+    struct Order {
+        id: String,
+        quantity: u32,
+    }
+
+    impl Entity for Order {
+        type Id = String;
+
+        fn id(&self) -> &Self::Id {
+            &self.id
+        }
+
+        fn collection_name() -> String {
+            "orders".to_string()
+        }
+    }
+
+    #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+    struct Payment {
+        id: String,
+        order_id: String,
+    }
+
+    impl Entity for Payment {
+        type Id = String;
+
+        fn id(&self) -> &Self::Id {
+            &self.id
+        }
+
+        fn collection_name() -> String {
+            "payments".to_string()
+        }
+
+        fn validate(&self) -> Result<(), ServiceError> {
+            if self.order_id.is_empty() {
+                return Err(ServiceError::validation("Payment needs an order"));
+            }
+            Ok(())
+        }
+    }
+
+    /// Saves an order, then a payment that fails validation for order `"bad"`
+    async fn place_order_through_repositories(
+        orders: TransactionalRepository<Order>,
+        payments: TransactionalRepository<Payment>,
+        axum::extract::Path(id): axum::extract::Path<String>,
+    ) -> Result<StatusCode, AppError> {
+        let order = Order {
+            id: id.clone(),
+            quantity: 1,
+        };
+        orders.save(&order).await?;
+        assert!(orders.find_by_id(&id).await?.is_some());
+
+        let order_id = if id == "bad" {
+            String::new()
+        } else {
+            id.clone()
+        };
+        payments
+            .save(&Payment {
+                id: format!("pay-{}", id),
+                order_id,
+            })
+            .await?;
+        Ok(StatusCode::CREATED)
+    }
+
+    async fn order_through_repositories(db: Arc<dyn DatabaseOperations>, id: &str) -> StatusCode {
+        let app = Router::new()
+            .route("/orders/{id}", post(place_order_through_repositories))
+            .layer(axum::middleware::from_fn_with_state(
+                db,
+                with_request_transaction,
+            ));
+        let request = axum::http::Request::post(format!("/orders/{}", id))
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_repository_writes_commit_together() {
+        let db = database();
+
+        assert_eq!(
+            order_through_repositories(db.clone(), "1").await,
+            StatusCode::CREATED
+        );
+
+        assert!(db.get("orders", "1").await.unwrap().is_some());
+        assert!(db.get("payments", "pay-1").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_repository_writes_roll_back_together() {
+        let db = database();
+
+        assert_ne!(
+            order_through_repositories(db.clone(), "bad").await,
+            StatusCode::CREATED
+        );
+
+        // The order saved before the failing payment was discarded too
+        assert_eq!(db.get("orders", "bad").await.unwrap(), None);
+        assert_eq!(db.get("payments", "pay-bad").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_repository_reads_see_staged_writes() {
+        let db = database();
+        let stored = Order {
+            id: "1".to_string(),
+            quantity: 1,
+        };
+        db.set("orders", "1", &serde_json::to_string(&stored).unwrap())
+            .await
+            .unwrap();
+        let tx = RequestTransaction {
+            transaction: Arc::new(Mutex::new(Transaction::begin(db.clone()))),
+        };
+        let orders = tx.repository::<Order>();
+
+        orders
+            .save(&Order {
+                id: "2".to_string(),
+                quantity: 2,
+            })
+            .await
+            .unwrap();
+        assert!(orders.delete(&"1".to_string()).await.unwrap());
+
+        let all = orders.find_all().await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].quantity, 2);
+        assert_eq!(orders.count().await.unwrap(), 1);
+        // Nothing reaches the database until commit
+        assert!(db.get("orders", "1").await.unwrap().is_some());
+        assert_eq!(db.get("orders", "2").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Startup timing
+//!
+//! [`StartupTimer`] times each initialization step, logging it with a
+//! `component` and `elapsed_ms` field, and finishes with a single
+//! "Startup complete" event carrying every component's duration and the
+//! total. Durations are also exported as metrics, by
+//! [`StartupTimer::complete`] so that steps timed before `init_metrics`
+//! installs the recorder are not lost:
+//!
+//! - `startup_component_duration_seconds{component}` (gauge)
+//! - `startup_duration_seconds` (gauge)
+//! - `startup_failures_total{component}` (counter)
+
+use std::fmt::{Display, Write};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use metrics::{counter, gauge};
+use tracing::{error, info};
+
+/// Times startup steps and reports them
+#[derive(Debug)]
+pub struct StartupTimer {
+    started: Instant,
+    components: Vec<(String, Duration)>,
+}
+
+impl StartupTimer {
+    /// Start timing from now
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            components: Vec::new(),
+        }
+    }
+
+    /// Run an async init step as `component`, recording how long it took
+    pub async fn time<T, E, F>(&mut self, component: &str, step: F) -> Result<T, E>
+    where
+        E: Display,
+        F: Future<Output = Result<T, E>>,
+    {
+        let started = Instant::now();
+        let result = step.await;
+        self.record(component, started.elapsed(), &result);
+        result
+    }
+
+    /// Run a blocking init step as `component`, recording how long it took
+    pub fn time_sync<T, E, F>(&mut self, component: &str, step: F) -> Result<T, E>
+    where
+        E: Display,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let started = Instant::now();
+        let result = step();
+        self.record(component, started.elapsed(), &result);
+        result
+    }
+
+    fn record<T, E: Display>(&mut self, component: &str, elapsed: Duration, result: &Result<T, E>) {
+        let elapsed_ms = elapsed.as_millis() as u64;
+        match result {
+            Ok(_) => {
+                info!(component, elapsed_ms, "Initialized {}", component);
+                self.components.push((component.to_string(), elapsed));
+            }
+            Err(e) => {
+                error!(component, elapsed_ms, error = %e, "Failed to initialize {}", component);
+                counter!("startup_failures_total", "component" => component.to_string())
+                    .increment(1);
+            }
+        }
+    }
+
+    /// Durations of the steps completed so far
+    pub fn components(&self) -> &[(String, Duration)] {
+        &self.components
+    }
+
+    /// Log the overall startup time and export every duration, returning the total
+    ///
+    /// Call once the metrics recorder is installed.
+    pub fn complete(self) -> Duration {
+        let total = self.started.elapsed();
+        let mut components = String::new();
+        for (component, elapsed) in &self.components {
+            if !components.is_empty() {
+                components.push(' ');
+            }
+            let _ = write!(components, "{}={}ms", component, elapsed.as_millis());
+        }
+
+        info!(
+            total_ms = total.as_millis() as u64,
+            components = %components,
+            "Startup complete"
+        );
+        for (component, elapsed) in &self.components {
+            gauge!("startup_component_duration_seconds", "component" => component.clone())
+                .set(elapsed.as_secs_f64());
+        }
+        gauge!("startup_duration_seconds").set(total.as_secs_f64());
+        total
+    }
+}
+
+impl Default for StartupTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::{Layer, Registry};
+
+    /// Records the fields of every event while it is the default subscriber
+    #[derive(Clone, Default)]
+    struct EventRecorder {
+        events: Arc<Mutex<Vec<HashMap<String, String>>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for EventRecorder {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            let mut fields = HashMap::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+    }
+
+    fn message(event: &HashMap<String, String>) -> &str {
+        event.get("message").map(String::as_str).unwrap_or("")
+    }
+
+    #[tokio::test]
+    async fn test_startup_logs_component_timings() {
+        let recorder = EventRecorder::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let mut timer = StartupTimer::new();
+        timer.time_sync("config", || Ok::<_, String>(())).unwrap();
+        timer
+            .time("cache", async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok::<_, String>(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(timer.components().len(), 2);
+        timer.complete();
+
+        let events = recorder.events.lock().unwrap();
+        let cache = events
+            .iter()
+            .find(|event| event.get("component").map(String::as_str) == Some("cache"))
+            .unwrap();
+        assert!(cache["elapsed_ms"].parse::<u64>().unwrap() >= 10);
+
+        let complete = events
+            .iter()
+            .find(|event| message(event) == "Startup complete")
+            .unwrap();
+        assert!(complete.contains_key("total_ms"));
+        assert!(complete["components"].contains("config="));
+        assert!(complete["components"].contains("cache="));
+    }
+
+    #[tokio::test]
+    async fn test_failed_component_logs_elapsed_time() {
+        let recorder = EventRecorder::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let mut timer = StartupTimer::new();
+        let result = timer
+            .time("database", async { Err::<(), _>("connection refused") })
+            .await;
+
+        assert!(result.is_err());
+        assert!(timer.components().is_empty());
+
+        let events = recorder.events.lock().unwrap();
+        let failure = events
+            .iter()
+            .find(|event| message(event) == "Failed to initialize database")
+            .unwrap();
+        assert!(failure.contains_key("elapsed_ms"));
+        assert_eq!(failure["error"], "connection refused");
+    }
+
+    #[test]
+    fn test_steps_timed_before_the_recorder_are_exported() {
+        use metrics_exporter_prometheus::PrometheusBuilder;
+
+        // Runs before any recorder is installed, as config loading does
+        let mut timer = StartupTimer::new();
+        timer.time_sync("config", || Ok::<_, String>(())).unwrap();
+
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || timer.complete());
+        let rendered = handle.render();
+
+        assert!(
+            rendered.contains("startup_component_duration_seconds{component=\"config\"}"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("startup_duration_seconds"),
+            "{}",
+            rendered
+        );
+    }
+}
@@ -9,12 +9,52 @@ use crate::core::error::Result;
 // User-extensible modules
 pub mod api_logger;
 pub mod api_resource;
+pub mod cors;
+pub mod csv_export;
+pub mod deprecation;
+pub mod http_client;
+pub mod http_errors;
+pub mod json_numbers;
+pub mod log_levels;
+pub mod merge_patch;
+pub mod ndjson;
+pub mod pagination;
+pub mod path_normalization;
+pub mod preconditions;
+pub mod query;
 pub mod request_id;
+pub mod required_headers;
+pub mod sources;
+pub mod text;
+pub mod trusted_proxy;
 
 // Export specific items
 pub use api_logger::{RequestLogger, log_request, log_response};
-pub use api_resource::{ApiHandlerOptions, ApiResource, ApiResourceRegistry, create_api_handler};
+pub use api_resource::{
+    ApiHandlerOptions, ApiResource, ApiResourceRegistry, ApiVersion, CacheScope, CacheSubject,
+    api_resource_router, create_api_handler,
+};
+pub use cors::{CorsError, cors_layer, permissive_cors_layer};
+pub use csv_export::{CsvError, CsvExport, to_csv};
+pub use deprecation::DeprecationLayer;
+pub use http_client::{HttpClient, HttpInterceptor, build_client, shared_client};
+pub use http_errors::{
+    ErrorClass, classify_app_error, classify_error, classify_response, classify_status,
+    parse_retry_after,
+};
+pub use json_numbers::{IntegerEncoding, JSON_INTEGERS_HEADER, encode_json_integers, int_string};
+pub use log_levels::{LogLevelError, LogLevels, LoggerLevels};
+pub use merge_patch::{MERGE_PATCH_CONTENT_TYPE, MergePatch, apply_merge_patch};
+pub use ndjson::{LineError, NDJSON_CONTENT_TYPE, NdjsonIngest, NdjsonSummary};
+pub use pagination::{PageParams, pagination_links, resource_page_limits};
+pub use path_normalization::{PathNormalizationLayer, canonical_path};
+pub use preconditions::{IfMatch, Preconditions, entity_tag, require_preconditions};
+pub use query::{ArrayStyle, InvalidParam, InvalidQuery, ValidatedQuery};
 pub use request_id::get_req_id;
+pub use required_headers::RequireHeadersLayer;
+pub use sources::{ManualClock, RandomSource, SeededRandom, SystemClock, ThreadRandom, TimeSource};
+pub use text::{deserialize_nfc, grapheme_count, grapheme_length, nfc, no_control_chars};
+pub use trusted_proxy::{ClientIp, TrustedProxyLayer};
 
 // Add your custom utilities below
 // Example:
@@ -4,18 +4,11 @@ use crate::core::utils::api_resource::ApiResource;
 use reqwest::{Client, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use std::sync::Arc;
-use std::time::Duration;
-use tracing::{debug, error, instrument, warn};
+use tracing::{debug, error, instrument};
 
 /// Creates a new API client with the given configuration
 pub fn create_api_client(config: &AppConfig) -> Client {
-    Client::builder()
-        .timeout(Duration::from_secs(config.server.timeout_seconds))
-        .build()
-        .unwrap_or_else(|_| {
-            warn!("Failed to build custom HTTP client, using default");
-            Client::new()
-        })
+    crate::core::utils::http_client::build_client(config)
 }
 
 /// API handler for making requests to external services
@@ -8,7 +8,7 @@ use crate::core::error::AppError;
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::future::Future;
-use tracing::{info, warn};
+use tracing::info;
 
 #[derive(Debug)]
 pub struct RequestLogger {
@@ -88,12 +88,7 @@ pub fn check_response_status(status: StatusCode) -> Result<(), AppError> {
 
 /// Create a new API client with the specified configuration
 pub fn create_api_client(config: &AppConfig) -> Client {
-    Client::builder()
-        .timeout(std::time::Duration::from_secs(
-            config.server.timeout_seconds,
-        ))
-        .build()
-        .unwrap_or_else(|_| Client::new())
+    crate::core::utils::http_client::build_client(config)
 }
 
 // Add your custom API logging utilities below
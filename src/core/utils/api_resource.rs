@@ -3,8 +3,11 @@
 //! This module provides a high-level abstraction for API resources that
 //! handles common concerns like caching, retries, and error handling.
 
+pub mod cache_scope;
 pub mod core;
+pub mod openapi;
 pub mod registry;
+pub mod version;
 
 #[cfg(feature = "auth")]
 use crate::core::auth::TokenClient;
@@ -31,8 +34,12 @@ use std::sync::Arc;
 use std::time::Duration;
 
 // Re-export public items
-pub use core::{ApiHandlerOptions, ApiResource, create_api_handler, fetch_with_retry};
+pub use cache_scope::{CacheScope, CacheSubject};
+pub use core::{
+    ApiHandlerOptions, ApiResource, api_resource_router, create_api_handler, fetch_with_retry,
+};
 pub use registry::*;
+pub use version::ApiVersion;
 
 use tracing::info;
 
@@ -0,0 +1,126 @@
+//! Per-caller cache entries
+//!
+//! A resource whose content depends on who asks (a user's profile, their
+//! orders) must not share cache entries between callers, so by default
+//! ([`CacheScope::PerIdentity`]) the API handler adds the caller's
+//! [`CacheSubject`] to every cache key. Requests without an authenticated
+//! subject bypass the cache entirely. Resources that are the same for every
+//! caller opt into one shared entry per id through
+//! [`ApiResource::CACHE_SCOPE`](super::ApiResource::CACHE_SCOPE):
+//!
+//! ```ignore
+//! impl ApiResource for Breed {
+//!     type Id = String;
+//!     const CACHE_SCOPE: CacheScope = CacheScope::Shared;
+//!     // ...
+//! }
+//! ```
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+#[cfg(feature = "auth")]
+use crate::core::auth::Identity;
+
+/// Whether a resource's cache entries are shared or kept per caller
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheScope {
+    /// One entry per id, served to every caller
+    Shared,
+    /// One entry per id and authenticated subject
+    #[default]
+    PerIdentity,
+}
+
+/// The authenticated caller that per-identity cache entries are keyed by
+///
+/// Taken from a `CacheSubject` request extension if an auth layer inserted
+/// one, otherwise from the [`Identity`](crate::core::auth::Identity) set by
+/// the built-in authentication middleware (`issuer/subject`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheSubject(pub Option<String>);
+
+impl CacheSubject {
+    pub fn new(subject: impl Into<String>) -> Self {
+        Self(Some(subject.into()))
+    }
+
+    /// Cache key for `id` under `scope`, or `None` when it must not be cached
+    pub fn key(&self, scope: CacheScope, id: &str) -> Option<String> {
+        match (scope, &self.0) {
+            (CacheScope::Shared, _) => Some(id.to_string()),
+            // Length-prefixed so no subject/id pair can collide with another
+            (CacheScope::PerIdentity, Some(subject)) => {
+                Some(format!("{}:{}:{}", subject.len(), subject, id))
+            }
+            (CacheScope::PerIdentity, None) => None,
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for CacheSubject {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(subject) = parts.extensions.get::<CacheSubject>() {
+            return Ok(subject.clone());
+        }
+        Ok(Self(identity_subject(parts)))
+    }
+}
+
+#[cfg(feature = "auth")]
+fn identity_subject(parts: &Parts) -> Option<String> {
+    parts
+        .extensions
+        .get::<Identity>()
+        .map(|identity| format!("{}/{}", identity.issuer, identity.subject))
+}
+
+#[cfg(not(feature = "auth"))]
+fn identity_subject(_parts: &Parts) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_key_ignores_subject() {
+        assert_eq!(
+            CacheSubject::new("alice").key(CacheScope::Shared, "42"),
+            Some("42".to_string())
+        );
+        assert_eq!(
+            CacheSubject::default().key(CacheScope::Shared, "42"),
+            Some("42".to_string())
+        );
+    }
+
+    #[test]
+    fn test_per_identity_keys_differ_by_subject() {
+        let alice = CacheSubject::new("alice").key(CacheScope::PerIdentity, "42");
+        let bob = CacheSubject::new("bob").key(CacheScope::PerIdentity, "42");
+
+        assert_ne!(alice, bob);
+        assert_ne!(alice, Some("42".to_string()));
+        // A subject ending in the separator can't impersonate another id
+        assert_ne!(
+            CacheSubject::new("a:1").key(CacheScope::PerIdentity, "2"),
+            CacheSubject::new("a").key(CacheScope::PerIdentity, "1:2")
+        );
+    }
+
+    #[test]
+    fn test_per_identity_is_the_default() {
+        assert_eq!(CacheScope::default(), CacheScope::PerIdentity);
+    }
+
+    #[test]
+    fn test_per_identity_without_subject_is_not_cached() {
+        assert_eq!(
+            CacheSubject::default().key(CacheScope::PerIdentity, "42"),
+            None
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use axum::{
-    Json,
+    Extension, Json, Router,
     extract::{Path, State},
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
 };
 use metrics::{counter, gauge};
 use serde::{Serialize, de::DeserializeOwned};
@@ -14,9 +16,15 @@ use std::{
 use tracing::{debug, error, info, warn};
 
 use crate::{
-    core::{router::AppState, utils::api_logger},
+    core::{
+        config::app_config::{AppConfig, PageLimits},
+        router::AppState,
+        utils::api_logger,
+        utils::json_numbers::{self, IntegerEncoding},
+        utils::pagination::{pagination_links, resource_page_limits},
+    },
     error::{AppError, Result},
-    utils::api_resource::ApiResourceRegistry,
+    utils::api_resource::{ApiResourceRegistry, ApiVersion, CacheScope, CacheSubject},
 };
 
 #[cfg(feature = "auth")]
@@ -30,11 +38,67 @@ pub trait ApiResource: Clone + Send + Sync + 'static {
     /// The type used for resource identification
     type Id: Display + Clone + Send + Sync;
 
+    /// Whether cached entries are shared or kept per authenticated caller
+    ///
+    /// Entries are per caller by default, so one caller's entry is never
+    /// served to another. Set to [`CacheScope::Shared`] for resources that
+    /// are the same for everyone.
+    const CACHE_SCOPE: CacheScope = CacheScope::PerIdentity;
+
     /// The string representation of the resource type (e.g., "user", "account")
     fn resource_type() -> &'static str;
 
     /// The API name used for logging (e.g., "UserService", "AccountAPI")
     fn api_name() -> &'static str;
+
+    /// Example payload embedded in the generated OpenAPI schema and response
+    fn example() -> Option<serde_json::Value> {
+        None
+    }
+
+    /// How long cached entries of this resource live
+    ///
+    /// `None` uses the cache registry's TTL.
+    fn cache_ttl() -> Option<std::time::Duration> {
+        None
+    }
+
+    /// How long past its TTL an entry may still be served while it refreshes
+    ///
+    /// Within this window a read returns the stale entry immediately and
+    /// refreshes it in the background. `None` uses the cache registry's
+    /// `stale_ttl` (zero, no stale serving, by default).
+    fn stale_ttl() -> Option<std::time::Duration> {
+        None
+    }
+
+    /// How integers in this resource's responses are written
+    ///
+    /// Override with [`IntegerEncoding::String`] to send `i64` ids as
+    /// strings; an `x-json-integers` request header applies on top of this.
+    fn integer_encoding() -> IntegerEncoding {
+        IntegerEncoding::Number
+    }
+
+    /// Default and maximum page size of lists of this resource
+    ///
+    /// `None` uses the `api.pagination` limits; an entry for the resource in
+    /// `api.pagination.resources` overrides this.
+    fn page_limits() -> Option<PageLimits> {
+        None
+    }
+
+    /// Representation of this resource under `version`
+    ///
+    /// Defaults to the serialized resource for every version; override to
+    /// rename or drop fields in newer versions. An error is answered with a
+    /// 500 response.
+    fn to_version(&self, _version: ApiVersion) -> serde_json::Result<serde_json::Value>
+    where
+        Self: Serialize,
+    {
+        serde_json::to_value(self)
+    }
 }
 
 /// Type alias for boxed future results
@@ -112,6 +176,9 @@ impl Default for ApiHandlerOptions {
 /// - Automatic retries (if enabled)
 /// - Error handling
 /// - Logging and metrics
+/// - Versioned responses, using [`ApiResource::to_version`] with the
+///   [`ApiVersion`] from the path (`/v2/...`) or `Accept` header; responses
+///   carry `Vary: Accept` so shared caches keep the versions apart
 ///
 /// # Type Parameters
 ///
@@ -126,18 +193,23 @@ impl Default for ApiHandlerOptions {
 pub fn create_api_handler<R, F, Fut>(
     fetch_fn: F,
     options: ApiHandlerOptions,
-) -> impl Fn(State<Arc<AppState>>, Path<String>) -> futures::future::BoxFuture<'static, Result<Json<R>>>
+) -> impl Fn(
+    State<Arc<AppState>>,
+    Path<String>,
+    ApiVersion,
+    CacheSubject,
+) -> futures::future::BoxFuture<'static, Result<Response>>
 + Clone
 + Send
 + Sync
 + 'static
 where
-    R: ApiResource,
+    R: ApiResource + Serialize,
     F: Fn(&Arc<AppState>, R::Id) -> Fut + Clone + Send + Sync + 'static,
     Fut: std::future::Future<Output = Result<R>> + Send + 'static,
-    R::Id: std::str::FromStr + Clone,
+    R::Id: std::str::FromStr + Clone + 'static,
 {
-    move |State(state), Path(id_str)| {
+    move |State(state), Path(id_str), version, subject| {
         let fetch_fn = fetch_fn.clone();
         let options = options.clone();
         let state = state.clone();
@@ -160,7 +232,9 @@ where
 
             // Special rule for caching: if cache_registry is present we try to use cache manager
             if let Some(registry) = &state.cache_registry {
-                let cache_key = registry.create_key::<R>(&id);
+                let cache_key = registry
+                    .create_key::<R>(&id)
+                    .and_then(|key| subject.key(R::CACHE_SCOPE, &key));
                 if cache_key.is_none() {
                     if options.detailed_logging {
                         debug!(
@@ -209,7 +283,9 @@ where
                                 if options.detailed_logging {
                                     debug!("Found in cache!");
                                 }
-                                return Ok(Json(resource));
+                                return Ok(versioned_response(encoded_version(
+                                    &resource, version,
+                                )?));
                             }
                             Err(err) => {
                                 error!("Error getting resource from cache: {}", err);
@@ -239,7 +315,10 @@ where
 
             // Store in cache if we have a cache registry
             if let Some(registry) = &state.cache_registry {
-                if let Some(cache_key) = registry.create_key::<R>(&id) {
+                if let Some(cache_key) = registry
+                    .create_key::<R>(&id)
+                    .and_then(|key| subject.key(R::CACHE_SCOPE, &key))
+                {
                     if options.detailed_logging {
                         debug!("Storing resource {} in cache", id);
                     }
@@ -249,11 +328,54 @@ where
                 }
             }
 
-            Ok(Json(resource))
+            Ok(versioned_response(encoded_version(&resource, version)?))
         })
     }
 }
 
+/// `routes` of resource `R`, with its page limits and pagination `Link` headers
+///
+/// [`PageParams`](crate::core::utils::pagination::PageParams) in these routes
+/// uses [`resource_page_limits`] for `R` instead of the global `api.pagination`
+/// limits.
+pub fn api_resource_router<R: ApiResource>(
+    config: &AppConfig,
+    routes: Router<Arc<AppState>>,
+) -> Router<Arc<AppState>> {
+    routes
+        .layer(axum::middleware::from_fn(pagination_links))
+        .layer(Extension(resource_page_limits::<R>(&config.api.pagination)))
+}
+
+/// `resource` under `version`, with its integer encoding applied
+fn encoded_version<R: ApiResource + Serialize>(
+    resource: &R,
+    version: ApiVersion,
+) -> Result<serde_json::Value> {
+    let mut value = resource.to_version(version).map_err(|e| {
+        AppError::InternalServerError(format!(
+            "Failed to serialize {} for API {}: {}",
+            R::resource_type(),
+            version,
+            e
+        ))
+    })?;
+    json_numbers::encode_integers(&mut value, R::integer_encoding());
+    Ok(value)
+}
+
+/// JSON response for a version negotiated from the path or `Accept` header
+///
+/// The same URL can answer with different versions, so the response names
+/// `Accept` in `Vary`.
+fn versioned_response(body: serde_json::Value) -> Response {
+    (
+        [(header::VARY, HeaderValue::from_static("accept"))],
+        Json(body),
+    )
+        .into_response()
+}
+
 // Static counters for cache hits and misses
 static CACHE_HITS: AtomicU64 = AtomicU64::new(0);
 static CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
@@ -452,6 +574,7 @@ mod tests {
             metrics_handle: Some(metrics_handle),
             resource_registry: Some(Arc::new(ApiResourceRegistry::new())),
             service_registry: Arc::new(ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create a counter to track how many times the fetch function is called
@@ -477,8 +600,11 @@ mod tests {
             .route(
                 "/resources/{id}",
                 get(
-                    |state: State<Arc<AppState>>, path: Path<String>| async move {
-                        handler(state, path).await
+                    |state: State<Arc<AppState>>,
+                     path: Path<String>,
+                     version: ApiVersion,
+                     subject: CacheSubject| async move {
+                        handler(state, path, version, subject).await
                     },
                 ),
             )
@@ -510,6 +636,216 @@ mod tests {
         assert_eq!(call_count.load(Ordering::SeqCst), 1);
     }
 
+    // Resource renaming `name` to `display_name` and dropping `status` in v2
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct VersionedResource {
+        id: i64,
+        name: String,
+        status: String,
+    }
+
+    impl ApiResource for VersionedResource {
+        type Id = i64;
+
+        fn resource_type() -> &'static str {
+            "versioned_resource"
+        }
+
+        fn api_name() -> &'static str {
+            "VersionedService"
+        }
+
+        fn to_version(&self, version: ApiVersion) -> serde_json::Result<serde_json::Value> {
+            if version >= ApiVersion::V2 {
+                Ok(serde_json::json!({ "id": self.id, "display_name": self.name }))
+            } else {
+                serde_json::to_value(self)
+            }
+        }
+    }
+
+    async fn get_versioned(uri: &str, accept: Option<&str>) -> serde_json::Value {
+        let fetch_fn = |_state: &Arc<AppState>, id: i64| async move {
+            Ok(VersionedResource {
+                id,
+                name: format!("Resource {}", id),
+                status: "available".to_string(),
+            })
+        };
+        let handler = create_api_handler(
+            fetch_fn,
+            ApiHandlerOptions {
+                use_cache: false,
+                ..Default::default()
+            },
+        );
+        let route = move |state: State<Arc<AppState>>,
+                          path: Path<String>,
+                          version: ApiVersion,
+                          subject: CacheSubject| {
+            let handler = handler.clone();
+            async move { handler(state, path, version, subject).await }
+        };
+        let app = Router::new()
+            .route("/resources/{id}", get(route.clone()))
+            .route("/v1/resources/{id}", get(route.clone()))
+            .route("/v2/resources/{id}", get(route))
+            .with_state(Arc::new(AppState::default()));
+
+        let mut request = Request::builder().uri(uri);
+        if let Some(accept) = accept {
+            request = request.header("accept", accept);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["vary"], "accept");
+        let body = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_versioned_serialization() {
+        let v1 = get_versioned("/v1/resources/7", None).await;
+        assert_eq!(v1["name"], "Resource 7");
+        assert_eq!(v1["status"], "available");
+        assert!(v1.get("display_name").is_none());
+
+        let v2 = get_versioned("/v2/resources/7", None).await;
+        assert_eq!(v2["display_name"], "Resource 7");
+        assert!(v2.get("name").is_none());
+        assert!(v2.get("status").is_none());
+
+        let negotiated = get_versioned("/resources/7", Some("application/json; version=2")).await;
+        assert_eq!(negotiated, v2);
+
+        let unversioned = get_versioned("/resources/7", None).await;
+        assert_eq!(unversioned, v1);
+    }
+
+    // Per-user resource, relying on the default per-identity cache scope
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct PrivateResource {
+        id: i64,
+        owner: String,
+    }
+
+    impl ApiResource for PrivateResource {
+        type Id = i64;
+
+        fn resource_type() -> &'static str {
+            "private_resource"
+        }
+
+        fn api_name() -> &'static str {
+            "PrivateService"
+        }
+    }
+
+    /// Router serving `PrivateResource`, returning it and the fetch counter
+    fn private_app() -> (Router, Arc<AtomicUsize>) {
+        let registry = crate::core::cache::init_cache_registry(true, 100, 3600);
+        crate::core::cache::register_resource_cache::<PrivateResource>(
+            &registry,
+            PrivateResource::resource_type(),
+        )
+        .unwrap();
+        let state = Arc::new(AppState {
+            cache_registry: Some(Arc::new(registry)),
+            ..Default::default()
+        });
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = fetches.clone();
+        // Every fetch yields a distinct owner, so a shared entry would show up
+        let handler = create_api_handler(
+            move |_state: &Arc<AppState>, id: i64| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let owner = format!("owner-{}", counter.load(Ordering::SeqCst));
+                async move { Ok(PrivateResource { id, owner }) }
+            },
+            ApiHandlerOptions {
+                use_retries: false,
+                ..Default::default()
+            },
+        );
+        let app = Router::new()
+            .route(
+                "/private/{id}",
+                get(
+                    move |state: State<Arc<AppState>>,
+                          path: Path<String>,
+                          version: ApiVersion,
+                          subject: CacheSubject| {
+                        let handler = handler.clone();
+                        async move { handler(state, path, version, subject).await }
+                    },
+                ),
+            )
+            .layer(axum::middleware::from_fn(
+                |mut request: axum::extract::Request, next: axum::middleware::Next| async move {
+                    let user = request
+                        .headers()
+                        .get("x-user")
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_string);
+                    if let Some(user) = user {
+                        request.extensions_mut().insert(CacheSubject::new(user));
+                    }
+                    next.run(request).await
+                },
+            ))
+            .with_state(state);
+        (app, fetches)
+    }
+
+    async fn get_private(app: &Router, user: Option<&str>) -> serde_json::Value {
+        let mut request = Request::builder().uri("/private/1");
+        if let Some(user) = user {
+            request = request.header("x-user", user);
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["vary"], "accept");
+        let body = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_per_identity_cache_entries_are_separate() {
+        let (app, fetches) = private_app();
+
+        let alice = get_private(&app, Some("alice")).await;
+        let bob = get_private(&app, Some("bob")).await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert_ne!(alice, bob);
+
+        // Each caller is served their own cached entry
+        assert_eq!(get_private(&app, Some("alice")).await, alice);
+        assert_eq!(get_private(&app, Some("bob")).await, bob);
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_per_identity_resource_is_not_cached_anonymously() {
+        let (app, fetches) = private_app();
+
+        get_private(&app, None).await;
+        get_private(&app, None).await;
+
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
     #[tokio::test]
     async fn test_fetch_with_retry_success_first_try() {
         // Create sample app state
@@ -522,6 +858,7 @@ mod tests {
             metrics_handle: Some(PrometheusBuilder::new().build_recorder().handle()),
             resource_registry: Some(Arc::new(ApiResourceRegistry::new())),
             service_registry: Arc::new(ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create a counter to track how many times the fetch function is called
@@ -563,6 +900,7 @@ mod tests {
             metrics_handle: Some(PrometheusBuilder::new().build_recorder().handle()),
             resource_registry: Some(Arc::new(ApiResourceRegistry::new())),
             service_registry: Arc::new(ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create a counter to track how many times the fetch function is called
@@ -617,6 +955,7 @@ mod tests {
             metrics_handle: Some(PrometheusBuilder::new().build_recorder().handle()),
             resource_registry: Some(Arc::new(ApiResourceRegistry::new())),
             service_registry: Arc::new(ServiceRegistry::new()),
+            readiness_cache: crate::core::services::HealthResultCache::new(),
         });
 
         // Create a counter to track how many times the fetch function is called
@@ -643,4 +982,77 @@ mod tests {
         // Verify the fetch function was called exactly three times (initial + 2 retries)
         assert_eq!(call_count.load(Ordering::SeqCst), 3);
     }
+
+    #[test]
+    fn test_resource_integer_encoding() {
+        #[derive(Debug, Clone, Serialize)]
+        struct Pet {
+            id: i64,
+            age: u8,
+        }
+
+        impl ApiResource for Pet {
+            type Id = i64;
+
+            fn resource_type() -> &'static str {
+                "pet"
+            }
+
+            fn api_name() -> &'static str {
+                "PetService"
+            }
+
+            fn integer_encoding() -> IntegerEncoding {
+                IntegerEncoding::UnsafeAsString
+            }
+        }
+
+        let pet = Pet {
+            id: i64::MAX,
+            age: 3,
+        };
+        assert_eq!(
+            encoded_version(&pet, ApiVersion::default()).unwrap(),
+            serde_json::json!({ "id": i64::MAX.to_string(), "age": 3 })
+        );
+
+        // Resources keep plain numbers unless they opt in
+        let resource = MockResource {
+            id: i64::MAX,
+            name: "big".to_string(),
+            status: "available".to_string(),
+        };
+        assert_eq!(
+            encoded_version(&resource, ApiVersion::default()).unwrap()["id"],
+            serde_json::json!(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_failed_version_conversion_is_an_error() {
+        #[derive(Debug, Clone, Serialize)]
+        struct Broken {
+            id: i64,
+        }
+
+        impl ApiResource for Broken {
+            type Id = i64;
+
+            fn resource_type() -> &'static str {
+                "broken"
+            }
+
+            fn api_name() -> &'static str {
+                "BrokenService"
+            }
+
+            fn to_version(&self, _version: ApiVersion) -> serde_json::Result<serde_json::Value> {
+                Err(serde::ser::Error::custom("unsupported field"))
+            }
+        }
+
+        let result = encoded_version(&Broken { id: 1 }, ApiVersion::V2);
+
+        assert!(matches!(result, Err(AppError::InternalServerError(_))));
+    }
 }
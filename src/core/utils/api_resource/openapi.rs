@@ -0,0 +1,150 @@
+//! OpenAPI schema generation for API resources
+//!
+//! Schemas are derived from [`ApiResource::example`] when a resource provides
+//! one, and the example itself is embedded so Swagger UI can show it.
+//! Resources without an example get a bare object schema with no `example`.
+
+use serde_json::{Map, Value, json};
+
+use crate::core::utils::api_resource::ApiResource;
+
+/// Schema name used under `components.schemas` for a resource
+pub fn schema_name<R: ApiResource>() -> String {
+    R::resource_type()
+        .split(['_', '-', ' '])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// OpenAPI schema object for a resource
+pub fn resource_schema<R: ApiResource>() -> Value {
+    let mut schema = match R::example() {
+        Some(example) => {
+            let mut schema = schema_for(&example);
+            schema["example"] = example;
+            schema
+        }
+        None => json!({ "type": "object" }),
+    };
+    schema["title"] = Value::String(schema_name::<R>());
+    schema
+}
+
+/// OpenAPI response object returning a resource as JSON
+pub fn resource_response<R: ApiResource>() -> Value {
+    let mut media_type = json!({
+        "schema": { "$ref": format!("#/components/schemas/{}", schema_name::<R>()) }
+    });
+    if let Some(example) = R::example() {
+        media_type["example"] = example;
+    }
+
+    json!({
+        "description": format!("A {} from {}", R::resource_type(), R::api_name()),
+        "content": { "application/json": media_type }
+    })
+}
+
+/// Infer a schema from an example value
+fn schema_for(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "nullable": true }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => json!({
+            "type": "array",
+            "items": items.first().map(schema_for).unwrap_or_else(|| json!({}))
+        }),
+        Value::Object(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(name, value)| (name.clone(), schema_for(value)))
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Pet;
+
+    impl ApiResource for Pet {
+        type Id = i64;
+
+        fn resource_type() -> &'static str {
+            "pet_record"
+        }
+
+        fn api_name() -> &'static str {
+            "PetStore"
+        }
+
+        fn example() -> Option<Value> {
+            Some(json!({ "id": 1, "name": "Rex", "weight": 12.5, "tags": ["dog"] }))
+        }
+    }
+
+    #[derive(Clone)]
+    struct Plain;
+
+    impl ApiResource for Plain {
+        type Id = String;
+
+        fn resource_type() -> &'static str {
+            "plain"
+        }
+
+        fn api_name() -> &'static str {
+            "PlainAPI"
+        }
+    }
+
+    #[test]
+    fn test_example_embedded_in_schema() {
+        let schema = resource_schema::<Pet>();
+
+        assert_eq!(schema["title"], "PetRecord");
+        assert_eq!(schema["example"]["name"], "Rex");
+        assert_eq!(schema["properties"]["id"]["type"], "integer");
+        assert_eq!(schema["properties"]["weight"]["type"], "number");
+        assert_eq!(schema["properties"]["tags"]["items"]["type"], "string");
+    }
+
+    #[test]
+    fn test_example_embedded_in_response() {
+        let response = resource_response::<Pet>();
+        let media_type = &response["content"]["application/json"];
+
+        assert_eq!(
+            media_type["schema"]["$ref"],
+            "#/components/schemas/PetRecord"
+        );
+        assert_eq!(media_type["example"]["id"], 1);
+    }
+
+    #[test]
+    fn test_missing_example_is_omitted() {
+        let schema = resource_schema::<Plain>();
+        let response = resource_response::<Plain>();
+
+        assert!(schema.get("example").is_none());
+        assert!(
+            response["content"]["application/json"]
+                .get("example")
+                .is_none()
+        );
+    }
+}
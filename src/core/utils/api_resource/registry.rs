@@ -10,7 +10,7 @@ use crate::core::cache::CacheRegistry;
 use crate::core::models::DependencyStatus;
 use crate::core::router::AppState;
 use crate::core::router::ServiceRegistry;
-use crate::core::utils::api_resource::ApiResource;
+use crate::core::utils::api_resource::{ApiResource, openapi};
 
 /// Type for a health check function
 pub type HealthCheckFn = Box<
@@ -27,6 +27,12 @@ pub struct ResourceRegistration {
 
     /// Function to perform a health check for this resource
     pub health_check_fn: HealthCheckFn,
+
+    /// OpenAPI schema for the resource
+    pub schema: serde_json::Value,
+
+    /// OpenAPI response returning the resource
+    pub response: serde_json::Value,
 }
 
 /// Registry for API resources
@@ -74,6 +80,8 @@ impl ApiResourceRegistry {
                 resource_type: resource_type.to_string(),
                 api_name: api_name.to_string(),
                 health_check_fn: Box::new(health_check),
+                schema: openapi::resource_schema::<T>(),
+                response: openapi::resource_response::<T>(),
             },
         );
 
@@ -126,6 +134,28 @@ impl ApiResourceRegistry {
         })
     }
 
+    /// OpenAPI `components` with a schema and response per registered resource
+    pub fn openapi_components(&self) -> serde_json::Value {
+        let resources = match self.resources.read() {
+            Ok(resources) => resources,
+            Err(_) => {
+                debug!("Failed to acquire read lock on resource registry");
+                return serde_json::json!({ "schemas": {}, "responses": {} });
+            }
+        };
+
+        let mut schemas = serde_json::Map::new();
+        let mut responses = serde_json::Map::new();
+        for registration in resources.values() {
+            if let Some(name) = registration.schema["title"].as_str() {
+                schemas.insert(name.to_string(), registration.schema.clone());
+                responses.insert(name.to_string(), registration.response.clone());
+            }
+        }
+
+        serde_json::json!({ "schemas": schemas, "responses": responses })
+    }
+
     /// Run health checks for all registered resources
     pub async fn run_all_health_checks(&self, state: &Arc<AppState>) -> Vec<DependencyStatus> {
         // Collect futures for health checks while holding the lock
@@ -356,6 +386,26 @@ mod tests {
         assert!(has_down);
     }
 
+    #[test]
+    fn test_openapi_components_include_registered_resources() {
+        let registry = ApiResourceRegistry::new();
+        registry
+            .register::<MockResource, _>(create_health_check("UP"))
+            .unwrap();
+
+        let components = registry.openapi_components();
+        assert_eq!(
+            components["schemas"]["MockResource"]["title"],
+            "MockResource"
+        );
+        assert!(
+            components["schemas"]["MockResource"]
+                .get("example")
+                .is_none()
+        );
+        assert!(components["responses"]["MockResource"].is_object());
+    }
+
     #[test]
     fn test_debug_impl() {
         let registry = ApiResourceRegistry::new();
@@ -368,6 +418,8 @@ mod tests {
             resource_type: "test".to_string(),
             api_name: "TestAPI".to_string(),
             health_check_fn: health_check,
+            schema: serde_json::json!({}),
+            response: serde_json::json!({}),
         };
 
         let debug_str = format!("{:?}", registration);
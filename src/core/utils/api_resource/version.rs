@@ -0,0 +1,139 @@
+//! API version negotiation
+//!
+//! [`ApiVersion`] is extracted from a leading `/vN` path segment (e.g.
+//! `/v2/users/42` or `/api/v2/users/42`) or, failing that, a `version` parameter on the `Accept`
+//! header (e.g. `application/json; version=2`). Requests naming neither
+//! get [`ApiVersion::V1`].
+
+use std::fmt;
+
+use axum::{
+    extract::{FromRequestParts, OriginalUri},
+    http::{HeaderMap, Uri, header::ACCEPT, request::Parts},
+};
+
+/// Version of the API a request was made against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ApiVersion(pub u32);
+
+impl ApiVersion {
+    pub const V1: Self = Self(1);
+    pub const V2: Self = Self(2);
+
+    /// Version named by the leading `/vN` segment of `path`
+    ///
+    /// Only the first segment, or the one after an `/api` prefix, is a
+    /// version; a later `vN` segment is a path parameter like `/users/v2`.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+        let first = segments.next()?;
+        let leading = if first == "api" {
+            segments.next()?
+        } else {
+            first
+        };
+        leading
+            .strip_prefix('v')
+            .filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|n| n.parse().ok())
+            .map(Self)
+    }
+
+    /// Version named by a `version=N` parameter on the `Accept` header
+    pub fn from_accept(headers: &HeaderMap) -> Option<Self> {
+        let accept = headers.get(ACCEPT)?.to_str().ok()?;
+        accept
+            .split([',', ';'])
+            .filter_map(|param| param.trim().split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("version"))
+            .and_then(|(_, value)| {
+                let value = value.trim().trim_matches('"');
+                value.strip_prefix('v').unwrap_or(value).parse().ok()
+            })
+            .map(Self)
+    }
+
+    /// Version of a request, preferring the path over the `Accept` header
+    pub fn from_request(uri: &Uri, headers: &HeaderMap) -> Self {
+        Self::from_path(uri.path())
+            .or_else(|| Self::from_accept(headers))
+            .unwrap_or_default()
+    }
+}
+
+impl Default for ApiVersion {
+    fn default() -> Self {
+        Self::V1
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.0)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Nested routers strip their prefix from the URI, so prefer the original
+        let uri = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map(|original| &original.0)
+            .unwrap_or(&parts.uri);
+        Ok(Self::from_request(uri, &parts.headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_version_from_path() {
+        assert_eq!(ApiVersion::from_path("/v2/users/42"), Some(ApiVersion::V2));
+        assert_eq!(ApiVersion::from_path("/api/v1/users"), Some(ApiVersion::V1));
+        assert_eq!(ApiVersion::from_path("/users/v2x"), None);
+        assert_eq!(ApiVersion::from_path("/users/42"), None);
+        // An id that happens to look like a version
+        assert_eq!(ApiVersion::from_path("/users/v2"), None);
+        assert_eq!(ApiVersion::from_path("/api/users/v2"), None);
+        assert_eq!(ApiVersion::from_path("/"), None);
+    }
+
+    #[test]
+    fn test_version_from_accept() {
+        assert_eq!(
+            ApiVersion::from_accept(&accept("application/json; version=2")),
+            Some(ApiVersion::V2)
+        );
+        assert_eq!(
+            ApiVersion::from_accept(&accept("application/json;Version=\"v3\"")),
+            Some(ApiVersion(3))
+        );
+        assert_eq!(ApiVersion::from_accept(&accept("application/json")), None);
+    }
+
+    #[test]
+    fn test_path_takes_precedence() {
+        let uri: Uri = "/v1/users/42".parse().unwrap();
+        let headers = accept("application/json; version=2");
+        assert_eq!(ApiVersion::from_request(&uri, &headers), ApiVersion::V1);
+
+        let uri: Uri = "/users/42".parse().unwrap();
+        assert_eq!(ApiVersion::from_request(&uri, &headers), ApiVersion::V2);
+        assert_eq!(
+            ApiVersion::from_request(&uri, &HeaderMap::new()),
+            ApiVersion::V1
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! CORS layer built from configuration
+//!
+//! [`cors_layer`] turns `server.cors` into a `tower_http` [`CorsLayer`].
+//! Browsers reject credentialed responses that allow `*`, so with
+//! `allow_credentials` wildcard methods and headers are answered by echoing
+//! what the preflight asked for, and allowed origins are echoed individually.
+//! A wildcard origin or exposed header list can't be echoed safely; it fails
+//! configuration validation, and if it reaches the layer anyway credentials
+//! are left off.
+//!
+//! Nothing is allowed by default: without `allowed_origins` no cross-origin
+//! request gets CORS headers. Every listed origin must be a bare
+//! `scheme://host[:port]`, and a malformed one fails the build rather than
+//! being skipped.
+
+use std::net::Ipv6Addr;
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use thiserror::Error;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer, ExposeHeaders};
+use tracing::warn;
+
+use crate::core::config::app_config::{CorsConfig, EnvironmentType};
+
+/// Why an allowed origin was refused
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CorsError {
+    #[error("'{0}' is not a valid origin, expected scheme://host[:port]")]
+    InvalidOrigin(String),
+}
+
+/// Check that `origin` is `http(s)://host[:port]` with nothing after it
+pub fn parse_origin(origin: &str) -> Result<HeaderValue, CorsError> {
+    let invalid = || CorsError::InvalidOrigin(origin.to_string());
+    let (scheme, authority) = origin.split_once("://").ok_or_else(invalid)?;
+    if !matches!(scheme, "http" | "https") {
+        return Err(invalid());
+    }
+    let (host, port) = match authority.strip_prefix('[') {
+        // IPv6 literal, e.g. http://[::1]:8080
+        Some(rest) => {
+            let (address, port) = rest.split_once(']').ok_or_else(invalid)?;
+            address.parse::<Ipv6Addr>().map_err(|_| invalid())?;
+            (address, port.strip_prefix(':'))
+        }
+        None => match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let valid_host = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':'));
+    let valid_port = port.is_none_or(|port| port.parse::<u16>().is_ok());
+    if !valid_host || !valid_port {
+        return Err(invalid());
+    }
+    HeaderValue::from_str(origin).map_err(|_| invalid())
+}
+
+/// Layer applying `config`, or an error if an allowed origin is malformed
+pub fn cors_layer(config: &CorsConfig) -> Result<CorsLayer, CorsError> {
+    let wildcard_origin = CorsConfig::is_wildcard(&config.allowed_origins);
+    let wildcard_exposed = CorsConfig::is_wildcard(&config.exposed_headers);
+    let credentials = config.allow_credentials && !wildcard_origin && !wildcard_exposed;
+    if config.allow_credentials && !credentials {
+        warn!("Ignoring server.cors.allow_credentials: it can't be combined with a wildcard");
+    }
+
+    let origins = if wildcard_origin {
+        AllowOrigin::any()
+    } else {
+        let origins = config
+            .allowed_origins
+            .iter()
+            .map(|origin| parse_origin(origin.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    let methods = if !CorsConfig::is_wildcard(&config.allowed_methods) {
+        AllowMethods::list(parse_all(&config.allowed_methods, |method| {
+            method.to_ascii_uppercase().parse::<Method>().ok()
+        }))
+    } else if credentials {
+        AllowMethods::mirror_request()
+    } else {
+        AllowMethods::any()
+    };
+    let headers = if !CorsConfig::is_wildcard(&config.allowed_headers) {
+        AllowHeaders::list(parse_all(&config.allowed_headers, header_name))
+    } else if credentials {
+        AllowHeaders::mirror_request()
+    } else {
+        AllowHeaders::any()
+    };
+    let exposed = if wildcard_exposed {
+        ExposeHeaders::any()
+    } else {
+        ExposeHeaders::list(parse_all(&config.exposed_headers, header_name))
+    };
+
+    let mut layer = CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .expose_headers(exposed)
+        .allow_credentials(credentials);
+    if let Some(seconds) = config.max_age_seconds {
+        layer = layer.max_age(Duration::from_secs(seconds));
+    }
+    Ok(layer)
+}
+
+/// Layer allowing any origin, method and header, for local development
+///
+/// Logs a warning when used in any other environment.
+pub fn permissive_cors_layer(environment: &EnvironmentType) -> CorsLayer {
+    if *environment != EnvironmentType::Development {
+        warn!(
+            "⚠️ Permissive CORS is enabled in {:?}: any website can call this API; configure server.cors instead",
+            environment
+        );
+    }
+    CorsLayer::permissive()
+}
+
+fn header_name(name: &str) -> Option<HeaderName> {
+    HeaderName::try_from(name).ok()
+}
+
+/// Parse every entry, skipping (and logging) the ones that don't parse
+fn parse_all<T>(values: &[String], parse: impl Fn(&str) -> Option<T>) -> Vec<T> {
+    values
+        .iter()
+        .filter_map(|value| {
+            let parsed = parse(value.trim());
+            if parsed.is_none() {
+                warn!("Ignoring invalid CORS entry '{}'", value);
+            }
+            parsed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::Request, http::header, routing::get};
+    use tower::ServiceExt;
+
+    fn credentialed_config() -> CorsConfig {
+        CorsConfig {
+            allowed_origins: vec!["https://app.example.com".to_string()],
+            exposed_headers: vec!["x-request-id".to_string()],
+            allow_credentials: true,
+            max_age_seconds: Some(600),
+            ..CorsConfig::default()
+        }
+    }
+
+    fn app(config: &CorsConfig) -> Router {
+        Router::new()
+            .route("/pets", get(|| async { "pets" }))
+            .layer(cors_layer(config).unwrap())
+    }
+
+    fn header_of<'a>(response: &'a axum::response::Response, name: &str) -> Option<&'a str> {
+        response
+            .headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_credentialed_request_echoes_origin() {
+        let request = Request::builder()
+            .uri("/pets")
+            .header(header::ORIGIN, "https://app.example.com")
+            .header(header::COOKIE, "session=abc")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(&credentialed_config()).oneshot(request).await.unwrap();
+
+        assert_eq!(
+            header_of(&response, "access-control-allow-origin"),
+            Some("https://app.example.com")
+        );
+        assert_eq!(
+            header_of(&response, "access-control-allow-credentials"),
+            Some("true")
+        );
+        assert_eq!(
+            header_of(&response, "access-control-expose-headers"),
+            Some("x-request-id")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preflight_includes_max_age() {
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/pets")
+            .header(header::ORIGIN, "https://app.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(&credentialed_config()).oneshot(request).await.unwrap();
+
+        assert_eq!(header_of(&response, "access-control-max-age"), Some("600"));
+        // Wildcard methods and headers are echoed rather than sent as `*`
+        assert_eq!(
+            header_of(&response, "access-control-allow-methods"),
+            Some("DELETE")
+        );
+        assert_eq!(
+            header_of(&response, "access-control-allow-headers"),
+            Some("authorization")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_origin_is_not_allowed() {
+        let request = Request::builder()
+            .uri("/pets")
+            .header(header::ORIGIN, "https://evil.example.com")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(&credentialed_config()).oneshot(request).await.unwrap();
+
+        assert_eq!(header_of(&response, "access-control-allow-origin"), None);
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_origin_drops_credentials() {
+        let config = CorsConfig {
+            allowed_origins: vec!["*".to_string()],
+            allow_credentials: true,
+            ..CorsConfig::default()
+        };
+        let request = Request::builder()
+            .uri("/pets")
+            .header(header::ORIGIN, "https://app.example.com")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(&config).oneshot(request).await.unwrap();
+
+        assert_eq!(
+            header_of(&response, "access-control-allow-origin"),
+            Some("*")
+        );
+        assert_eq!(
+            header_of(&response, "access-control-allow-credentials"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_config_allows_no_origin() {
+        let request = Request::builder()
+            .uri("/pets")
+            .header(header::ORIGIN, "https://app.example.com")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(&CorsConfig::default()).oneshot(request).await.unwrap();
+
+        assert_eq!(header_of(&response, "access-control-allow-origin"), None);
+    }
+
+    #[test]
+    fn test_invalid_origin_is_rejected() {
+        for origin in [
+            "app.example.com",
+            "ftp://app.example.com",
+            "https://app.example.com/",
+            "https://app.example.com:http",
+            "https://",
+        ] {
+            let config = CorsConfig {
+                allowed_origins: vec![origin.to_string()],
+                ..CorsConfig::default()
+            };
+            assert_eq!(
+                cors_layer(&config).unwrap_err(),
+                CorsError::InvalidOrigin(origin.to_string())
+            );
+        }
+
+        for origin in [
+            "https://app.example.com",
+            "http://localhost:3000",
+            "http://[::1]:8080",
+        ] {
+            assert!(parse_origin(origin).is_ok(), "{}", origin);
+        }
+    }
+}
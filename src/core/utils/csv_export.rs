@@ -0,0 +1,562 @@
+//! CSV export responses
+//!
+//! [`CsvExport`] turns serde-serializable rows into a `text/csv` download.
+//! The header is the union of every row's columns, in order of first
+//! appearance. Nested structs and maps are flattened into dotted columns
+//! (`owner.name`), while sequences and enum variants carrying data are
+//! written as JSON in a single cell:
+//!
+//! ```ignore
+//! async fn export_pets(State(repo): State<PetRepository>) -> Result<CsvExport, AppError> {
+//!     let pets = repo.find_all().await?;
+//!     Ok(CsvExport::from_rows("pets.csv", &pets)?)
+//! }
+//! ```
+//!
+//! Text cells and header cells starting with `=`, `+`, `-`, `@`, a tab or a
+//! carriage return are prefixed with `'` so spreadsheets don't evaluate them
+//! as formulas.
+//!
+//! Large exports can be streamed with [`CsvExport::from_stream`]. The header
+//! is written before later rows are seen, so streamed rows should all have
+//! the same shape: a column missing from a later row is left empty and a
+//! column the first row lacks is dropped with a warning. An export without
+//! rows is an empty body, with no header.
+
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use axum::{
+    body::Body,
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use futures::{Stream, StreamExt};
+use serde::{Serialize, ser};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::core::error::AppError;
+
+/// Error turning rows into CSV
+#[derive(Debug, thiserror::Error)]
+pub enum CsvError {
+    /// A row serialized to something without named fields
+    #[error("CSV rows must be structs or maps, got {0}")]
+    NotARow(&'static str),
+
+    #[error("Failed to serialize CSV row: {0}")]
+    Serialize(String),
+}
+
+impl ser::Error for CsvError {
+    fn custom<T: Display>(msg: T) -> Self {
+        CsvError::Serialize(msg.to_string())
+    }
+}
+
+impl From<CsvError> for AppError {
+    fn from(error: CsvError) -> Self {
+        AppError::InternalServerError(error.to_string())
+    }
+}
+
+/// A CSV download
+#[derive(Debug)]
+pub struct CsvExport {
+    filename: String,
+    body: Body,
+}
+
+impl CsvExport {
+    /// Export `rows`, suggesting `filename` to the client
+    pub fn from_rows<T: Serialize>(filename: &str, rows: &[T]) -> Result<Self, CsvError> {
+        Ok(Self {
+            filename: filename.to_string(),
+            body: Body::from(to_csv(rows)?),
+        })
+    }
+
+    /// Export rows as they are produced
+    ///
+    /// The first row fixes the columns. A row that fails to serialize ends
+    /// the response early, since the status has already been sent.
+    pub fn from_stream<S, T>(filename: &str, rows: S) -> Self
+    where
+        S: Stream<Item = T> + Send + 'static,
+        T: Serialize,
+    {
+        let mut columns: Option<Vec<String>> = None;
+        let lines = rows.map(move |row| {
+            let cells = row_cells(&row)?;
+            let mut out = String::new();
+            let columns = columns.get_or_insert_with(|| {
+                let columns = cells.iter().map(|(column, _)| column.clone()).collect();
+                write_header(&mut out, &columns);
+                columns
+            });
+            for (column, _) in &cells {
+                if !columns.contains(column) {
+                    warn!(
+                        "Dropping CSV column '{}' missing from the first row",
+                        column
+                    );
+                }
+            }
+            write_row(&mut out, columns, &cells);
+            Ok::<_, CsvError>(out)
+        });
+        Self {
+            filename: filename.to_string(),
+            body: Body::from_stream(lines),
+        }
+    }
+}
+
+impl IntoResponse for CsvExport {
+    fn into_response(self) -> Response {
+        let disposition = format!("attachment; filename=\"{}\"", safe_filename(&self.filename));
+        let mut response = self.body.into_response();
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/csv; charset=utf-8"),
+        );
+        if let Ok(value) = HeaderValue::from_str(&disposition) {
+            headers.insert(header::CONTENT_DISPOSITION, value);
+        }
+        response
+    }
+}
+
+/// Serialize `rows` as CSV, header first
+pub fn to_csv<T: Serialize>(rows: &[T]) -> Result<String, CsvError> {
+    let rows = rows
+        .iter()
+        .map(|row| row_cells(row))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut columns: Vec<String> = Vec::new();
+    for (column, _) in rows.iter().flatten() {
+        if !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+
+    let mut out = String::new();
+    if !rows.is_empty() {
+        write_header(&mut out, &columns);
+    }
+    for cells in &rows {
+        write_row(&mut out, &columns, cells);
+    }
+    Ok(out)
+}
+
+/// Flatten one row into `(column, value)` pairs, in field order
+pub fn row_cells<T: Serialize + ?Sized>(row: &T) -> Result<Vec<(String, String)>, CsvError> {
+    let mut cells = Vec::new();
+    row.serialize(RowSerializer {
+        prefix: None,
+        cells: &mut cells,
+    })?;
+    Ok(cells)
+}
+
+/// The cell for a value that is not flattened into columns
+fn cell(value: Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => defuse_formula(text),
+        value => value.to_string(),
+    }
+}
+
+/// Prefix text a spreadsheet would evaluate as a formula with `'`
+fn defuse_formula(text: String) -> String {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text)
+    } else {
+        text
+    }
+}
+
+/// Column names may come from map keys, so they are defused like text cells
+fn write_header(out: &mut String, columns: &[String]) {
+    let names: Vec<String> = columns
+        .iter()
+        .map(|column| defuse_formula(column.clone()))
+        .collect();
+    write_line(out, &names);
+}
+
+fn write_row(out: &mut String, columns: &[String], cells: &[(String, String)]) {
+    let values: Vec<&str> = columns
+        .iter()
+        .map(|column| {
+            cells
+                .iter()
+                .find(|(name, _)| name == column)
+                .map_or("", |(_, value)| value.as_str())
+        })
+        .collect();
+    write_line(out, &values);
+}
+
+fn write_line<S: AsRef<str>>(out: &mut String, values: &[S]) {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&escape(value.as_ref()));
+    }
+    out.push_str("\r\n");
+}
+
+/// Quote a cell per RFC 4180 when it needs it
+fn escape(value: &str) -> Cow<'_, str> {
+    let needs_quotes =
+        value.contains([',', '"', '\r', '\n']) || value.starts_with(' ') || value.ends_with(' ');
+    if needs_quotes {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Keep the filename to characters safe inside a quoted header parameter
+fn safe_filename(filename: &str) -> String {
+    let name: String = filename
+        .chars()
+        .map(|c| {
+            if (c.is_ascii_graphic() || c == ' ') && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.trim().is_empty() {
+        "export.csv".to_string()
+    } else {
+        name
+    }
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Value, CsvError> {
+    serde_json::to_value(value).map_err(|e| CsvError::Serialize(e.to_string()))
+}
+
+/// Writes a struct or map into `cells`, one column per field
+///
+/// Anything else is rejected with [`CsvError::NotARow`]; below the row
+/// itself the field is then written as a single cell instead.
+struct RowSerializer<'a> {
+    prefix: Option<&'a str>,
+    cells: &'a mut Vec<(String, String)>,
+}
+
+macro_rules! not_a_row {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ret:ty: $kind:literal;)*) => {$(
+        fn $method(self, $($arg: $ty),*) -> Result<$ret, CsvError> {
+            Err(CsvError::NotARow($kind))
+        }
+    )*};
+}
+
+impl<'a> ser::Serializer for RowSerializer<'a> {
+    type Ok = ();
+    type Error = CsvError;
+    type SerializeSeq = ser::Impossible<(), CsvError>;
+    type SerializeTuple = ser::Impossible<(), CsvError>;
+    type SerializeTupleStruct = ser::Impossible<(), CsvError>;
+    type SerializeTupleVariant = ser::Impossible<(), CsvError>;
+    type SerializeMap = Fields<'a>;
+    type SerializeStruct = Fields<'a>;
+    type SerializeStructVariant = ser::Impossible<(), CsvError>;
+
+    not_a_row! {
+        serialize_bool(_v: bool) -> (): "bool";
+        serialize_i8(_v: i8) -> (): "i8";
+        serialize_i16(_v: i16) -> (): "i16";
+        serialize_i32(_v: i32) -> (): "i32";
+        serialize_i64(_v: i64) -> (): "i64";
+        serialize_i128(_v: i128) -> (): "i128";
+        serialize_u8(_v: u8) -> (): "u8";
+        serialize_u16(_v: u16) -> (): "u16";
+        serialize_u32(_v: u32) -> (): "u32";
+        serialize_u64(_v: u64) -> (): "u64";
+        serialize_u128(_v: u128) -> (): "u128";
+        serialize_f32(_v: f32) -> (): "f32";
+        serialize_f64(_v: f64) -> (): "f64";
+        serialize_char(_v: char) -> (): "char";
+        serialize_str(_v: &str) -> (): "str";
+        serialize_bytes(_v: &[u8]) -> (): "bytes";
+        serialize_none() -> (): "none";
+        serialize_unit() -> (): "unit";
+        serialize_unit_struct(_name: &'static str) -> (): "unit struct";
+        serialize_unit_variant(_name: &'static str, _index: u32, _variant: &'static str) -> (): "enum variant";
+        serialize_seq(_len: Option<usize>) -> Self::SerializeSeq: "sequence";
+        serialize_tuple(_len: usize) -> Self::SerializeTuple: "tuple";
+        serialize_tuple_struct(_name: &'static str, _len: usize) -> Self::SerializeTupleStruct: "tuple struct";
+        serialize_tuple_variant(_name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Self::SerializeTupleVariant: "enum variant";
+        serialize_struct_variant(_name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Self::SerializeStructVariant: "enum variant";
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CsvError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), CsvError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), CsvError> {
+        Err(CsvError::NotARow("enum variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Fields<'a>, CsvError> {
+        Ok(Fields::new(self))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Fields<'a>, CsvError> {
+        Ok(Fields::new(self))
+    }
+}
+
+/// Fields of a struct or map, each flattened under its own column
+struct Fields<'a> {
+    prefix: Option<&'a str>,
+    cells: &'a mut Vec<(String, String)>,
+    key: Option<String>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(row: RowSerializer<'a>) -> Self {
+        Self {
+            prefix: row.prefix,
+            cells: row.cells,
+            key: None,
+        }
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), CsvError> {
+        let column = match self.prefix {
+            Some(prefix) => format!("{}.{}", prefix, key),
+            None => key.to_string(),
+        };
+        let nested = RowSerializer {
+            prefix: Some(&column),
+            cells: &mut *self.cells,
+        };
+        match value.serialize(nested) {
+            Err(CsvError::NotARow(_)) => {
+                let cell = cell(to_json(value)?);
+                self.cells.push((column, cell));
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+impl ser::SerializeStruct for Fields<'_> {
+    type Ok = ();
+    type Error = CsvError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), CsvError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), CsvError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for Fields<'_> {
+    type Ok = ();
+    type Error = CsvError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), CsvError> {
+        self.key = Some(match to_json(key)? {
+            Value::String(key) => key,
+            key => key.to_string(),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CsvError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| CsvError::Serialize("Map value without a key".to_string()))?;
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<(), CsvError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+
+    #[derive(Serialize)]
+    struct Owner {
+        name: String,
+        email: String,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Species {
+        Dog,
+    }
+
+    #[derive(Serialize)]
+    struct Pet {
+        id: u64,
+        name: String,
+        species: Species,
+        owner: Owner,
+        tags: Vec<String>,
+        notes: Option<String>,
+    }
+
+    fn pets() -> Vec<Pet> {
+        vec![
+            Pet {
+                id: 1,
+                name: "Rex, Jr.".to_string(),
+                species: Species::Dog,
+                owner: Owner {
+                    name: "Ann".to_string(),
+                    email: "ann@example.com".to_string(),
+                },
+                tags: vec!["good".to_string(), "boy".to_string()],
+                notes: Some("says \"woof\"\nloudly".to_string()),
+            },
+            Pet {
+                id: 2,
+                name: "Fido".to_string(),
+                species: Species::Dog,
+                owner: Owner {
+                    name: "Bob".to_string(),
+                    email: "bob@example.com".to_string(),
+                },
+                tags: Vec::new(),
+                notes: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_header_matches_struct_fields() {
+        let csv = to_csv(&pets()).unwrap();
+        let header = csv.split("\r\n").next().unwrap();
+
+        assert_eq!(header, "id,name,species,owner.name,owner.email,tags,notes");
+    }
+
+    #[test]
+    fn test_values_are_quoted_and_escaped() {
+        let csv = to_csv(&pets()).unwrap();
+
+        assert_eq!(
+            csv,
+            "id,name,species,owner.name,owner.email,tags,notes\r\n\
+             1,\"Rex, Jr.\",dog,Ann,ann@example.com,\"[\"\"good\"\",\"\"boy\"\"]\",\"says \"\"woof\"\"\nloudly\"\r\n\
+             2,Fido,dog,Bob,bob@example.com,[],\r\n"
+        );
+    }
+
+    #[test]
+    fn test_formulas_are_defused() {
+        #[derive(Serialize)]
+        struct Row {
+            name: &'static str,
+            balance: i64,
+        }
+
+        let csv = to_csv(&[
+            Row {
+                name: "=HYPERLINK(\"http://evil\")",
+                balance: -5,
+            },
+            Row {
+                name: "@SUM(A1)",
+                balance: 3,
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(
+            csv,
+            "name,balance\r\n\"'=HYPERLINK(\"\"http://evil\"\")\",-5\r\n'@SUM(A1),3\r\n"
+        );
+    }
+
+    #[test]
+    fn test_control_characters_and_header_keys_are_defused() {
+        let rows = vec![serde_json::json!({ "=cmd": "\tx", "note": "\r=1+1" })];
+
+        assert_eq!(to_csv(&rows).unwrap(), "'=cmd,note\r\n'\tx,\"'\r=1+1\"\r\n");
+    }
+
+    #[test]
+    fn test_header_is_union_of_row_columns() {
+        let rows = vec![
+            serde_json::json!({ "id": 1 }),
+            serde_json::json!({ "id": 2, "name": "Fido" }),
+        ];
+
+        assert_eq!(to_csv(&rows).unwrap(), "id,name\r\n1,\r\n2,Fido\r\n");
+    }
+
+    #[test]
+    fn test_rows_must_have_fields() {
+        assert!(matches!(to_csv(&[1, 2]), Err(CsvError::NotARow(_))));
+        assert_eq!(to_csv::<Pet>(&[]).unwrap(), "");
+    }
+
+    #[tokio::test]
+    async fn test_response_headers() {
+        let response = CsvExport::from_rows("pets \"all\".csv", &pets())
+            .unwrap()
+            .into_response();
+
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"pets _all_.csv\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_matches_buffered_export() {
+        let response =
+            CsvExport::from_stream("pets.csv", futures::stream::iter(pets())).into_response();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        assert_eq!(body, to_csv(&pets()).unwrap());
+    }
+}
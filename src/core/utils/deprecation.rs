@@ -0,0 +1,241 @@
+//! Deprecation and sunset headers
+//!
+//! [`DeprecationLayer`] marks routes as deprecated. Responses carry a
+//! `Deprecation` header, a `Sunset` header (RFC 8594) once a removal date
+//! is set, and a `Link` to migration docs, and every hit increments
+//! `deprecated_route_hits_total` labelled with the route:
+//!
+//! ```ignore
+//! let sunset = Utc.with_ymd_and_hms(2026, 6, 30, 0, 0, 0).unwrap();
+//! let routes = Router::new()
+//!     .route("/v1/pets", get(list_pets_v1))
+//!     .route_layer(
+//!         DeprecationLayer::new()
+//!             .with_sunset(sunset)
+//!             .with_link("https://docs.example.com/migrate/pets-v2"),
+//!     );
+//! ```
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::{
+    extract::MatchedPath,
+    http::{HeaderValue, Request, header},
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, FutureExt};
+use metrics::counter;
+use tower::{Layer, Service};
+
+/// Counter of requests served by deprecated routes, labelled with `route`
+pub const DEPRECATED_ROUTE_HITS_TOTAL: &str = "deprecated_route_hits_total";
+
+/// Route label used when the request matched no route pattern
+const UNKNOWN_ROUTE: &str = "unknown";
+
+#[derive(Debug, Clone)]
+struct DeprecationHeaders {
+    deprecation: HeaderValue,
+    sunset: Option<HeaderValue>,
+    link: Option<HeaderValue>,
+}
+
+/// Layer marking the routes it wraps as deprecated
+#[derive(Debug, Clone)]
+pub struct DeprecationLayer {
+    headers: Arc<DeprecationHeaders>,
+}
+
+impl Default for DeprecationLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeprecationLayer {
+    /// Deprecated with no date, sent as `Deprecation: true`
+    pub fn new() -> Self {
+        Self {
+            headers: Arc::new(DeprecationHeaders {
+                deprecation: HeaderValue::from_static("true"),
+                sunset: None,
+                link: None,
+            }),
+        }
+    }
+
+    /// Record when the route was deprecated, sent as `Deprecation: @<unix seconds>`
+    pub fn deprecated_since(mut self, since: DateTime<Utc>) -> Self {
+        Arc::make_mut(&mut self.headers).deprecation =
+            HeaderValue::from_str(&format!("@{}", since.timestamp()))
+                .expect("timestamp is a valid header value");
+        self
+    }
+
+    /// Date after which the route may stop responding
+    pub fn with_sunset(mut self, sunset: DateTime<Utc>) -> Self {
+        Arc::make_mut(&mut self.headers).sunset = Some(
+            HeaderValue::from_str(&http_date(sunset)).expect("HTTP date is a valid header value"),
+        );
+        self
+    }
+
+    /// Documentation on migrating off the route, sent as `Link: <url>; rel="deprecation"`
+    ///
+    /// # Panics
+    ///
+    /// If `url` contains characters not allowed in a header value.
+    pub fn with_link(mut self, url: &str) -> Self {
+        Arc::make_mut(&mut self.headers).link = Some(
+            HeaderValue::from_str(&format!("<{}>; rel=\"deprecation\"", url))
+                .expect("deprecation link must be a valid header value"),
+        );
+        self
+    }
+}
+
+/// Format `date` as an IMF-fixdate, e.g. `Tue, 30 Jun 2026 00:00:00 GMT`
+fn http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+impl<S> Layer<S> for DeprecationLayer {
+    type Service = Deprecated<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Deprecated {
+            inner,
+            headers: self.headers.clone(),
+        }
+    }
+}
+
+/// Service produced by [`DeprecationLayer`]
+#[derive(Debug, Clone)]
+pub struct Deprecated<S> {
+    inner: S,
+    headers: Arc<DeprecationHeaders>,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for Deprecated<S>
+where
+    S: Service<Request<ReqBody>, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or(UNKNOWN_ROUTE, MatchedPath::as_str)
+            .to_string();
+        counter!(DEPRECATED_ROUTE_HITS_TOTAL, "route" => route).increment(1);
+
+        let headers = self.headers.clone();
+        let future = self.inner.call(req);
+        async move {
+            let mut response = future.await?;
+            let response_headers = response.headers_mut();
+            response_headers.insert("deprecation", headers.deprecation.clone());
+            if let Some(sunset) = &headers.sunset {
+                response_headers.insert("sunset", sunset.clone());
+            }
+            if let Some(link) = &headers.link {
+                response_headers.append(header::LINK, link.clone());
+            }
+            Ok(response)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::StatusCode, routing::get};
+    use chrono::TimeZone;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let sunset = Utc.with_ymd_and_hms(2026, 6, 30, 0, 0, 0).unwrap();
+        let deprecated = Router::new().route("/v1/pets/{id}", get(|| async { "pet" }));
+        Router::new()
+            .merge(
+                deprecated.route_layer(
+                    DeprecationLayer::new()
+                        .with_sunset(sunset)
+                        .with_link("https://docs.example.com/migrate/pets-v2"),
+                ),
+            )
+            .route("/v2/pets/{id}", get(|| async { "pet" }))
+    }
+
+    fn get_request(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_route_has_headers() {
+        let response = app().oneshot(get_request("/v1/pets/1")).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers["deprecation"], "true");
+        assert_eq!(headers["sunset"], "Tue, 30 Jun 2026 00:00:00 GMT");
+        assert_eq!(
+            headers[header::LINK],
+            "<https://docs.example.com/migrate/pets-v2>; rel=\"deprecation\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_other_routes_are_untouched() {
+        let response = app().oneshot(get_request("/v2/pets/1")).await.unwrap();
+
+        assert!(!response.headers().contains_key("deprecation"));
+        assert!(!response.headers().contains_key("sunset"));
+    }
+
+    #[test]
+    fn test_deprecated_since_is_unix_timestamp() {
+        let since = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let layer = DeprecationLayer::new().deprecated_since(since);
+
+        assert_eq!(layer.headers.deprecation, "@1735689600");
+    }
+
+    #[test]
+    fn test_hits_are_counted_per_route() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            // The recorder is thread-local, so run the requests on this thread
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    for uri in ["/v1/pets/1", "/v1/pets/2", "/v2/pets/1"] {
+                        app().oneshot(get_request(uri)).await.unwrap();
+                    }
+                });
+        });
+        let rendered = handle.render();
+
+        assert!(
+            rendered.contains("deprecated_route_hits_total{route=\"/v1/pets/{id}\"} 2"),
+            "{}",
+            rendered
+        );
+        assert!(!rendered.contains("/v2/pets"), "{}", rendered);
+    }
+}
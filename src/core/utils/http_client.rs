@@ -0,0 +1,1245 @@
+//! Outbound HTTP client with per-host circuit breakers
+//!
+//! [`HttpClient`] wraps a `reqwest::Client` and keeps one circuit breaker per
+//! downstream authority (`host:port`), so a failing host is short-circuited
+//! without affecting requests to other hosts sharing the same client.
+//! Calls made with [`HttpClient::execute_for`] use the named breaker of a
+//! dependency from `reliability.circuit_breakers` instead, and run inside the
+//! dependency's bulkhead from `reliability.bulkheads` when one is configured.
+//!
+//! Cross-cutting behavior (auth headers, logging, metrics) is added with
+//! [`HttpInterceptor`]s instead of wrapping every call.
+//!
+//! Independently of circuit breaking, each host can be capped to a number of
+//! requests in flight; requests beyond the cap wait for a slot, so one
+//! chatty downstream cannot tie up every connection.
+//!
+//! Identical concurrent GETs (same URL and headers) can be coalesced with
+//! [`HttpClient::get_coalesced`]: one request goes out and every caller gets
+//! a copy of the buffered [`BufferedResponse`].
+//!
+//! With [`HttpClient::with_retry`], failures classified as retryable by
+//! [`super::http_errors`] are repeated with backoff, honouring `Retry-After`.
+//! Only idempotent requests are repeated: `POST` and `PATCH` need an
+//! `Idempotency-Key` header, so a write that timed out is not applied twice.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use metrics::{counter, gauge};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, Method, Request, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use tokio::sync::Semaphore;
+use tracing::{debug, warn};
+
+use crate::core::config::app_config::{AppConfig, CircuitBreakerConfig, HttpClientConfig};
+use crate::core::error::AppError;
+use crate::core::reliability::retry::OperationRetryPolicy;
+use crate::core::reliability::{
+    BulkheadRegistry, CircuitBreaker, CircuitBreakerRegistry, CircuitState,
+};
+use crate::core::utils::http_errors::{ErrorClass, classify_app_error, classify_response};
+
+#[cfg(feature = "auth")]
+use crate::core::auth::TokenClient;
+
+/// Header marking a non-idempotent request as safe to retry
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// Hook into every request sent and response received by an [`HttpClient`]
+///
+/// Interceptors see requests in the order they were added and responses in
+/// reverse order, so the first interceptor wraps all the others.
+#[async_trait]
+pub trait HttpInterceptor: Send + Sync + 'static {
+    /// Inspect or modify an outgoing request; an error aborts the call
+    async fn on_request(&self, _request: &mut Request) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// Observe a response before it is returned to the caller
+    async fn on_response(&self, _response: &Response) {}
+}
+
+/// Adds a bearer token for `resource` from a [`TokenClient`] to every request
+#[cfg(feature = "auth")]
+#[derive(Debug, Clone)]
+pub struct BearerAuthInterceptor {
+    token_client: Arc<dyn TokenClient>,
+    resource: String,
+}
+
+#[cfg(feature = "auth")]
+impl BearerAuthInterceptor {
+    /// Create an interceptor requesting tokens for `resource`
+    pub fn new(token_client: Arc<dyn TokenClient>, resource: impl Into<String>) -> Self {
+        Self {
+            token_client,
+            resource: resource.into(),
+        }
+    }
+}
+
+#[cfg(feature = "auth")]
+#[async_trait]
+impl HttpInterceptor for BearerAuthInterceptor {
+    async fn on_request(&self, request: &mut Request) -> Result<(), AppError> {
+        let token = self
+            .token_client
+            .get_token(&self.resource)
+            .map_err(|e| AppError::AuthenticationError(e.to_string()))?;
+        let value = format!("Bearer {}", token).parse().map_err(|_| {
+            AppError::AuthenticationError("Token is not a valid header value".to_string())
+        })?;
+        request
+            .headers_mut()
+            .insert(reqwest::header::AUTHORIZATION, value);
+        Ok(())
+    }
+}
+
+/// A `reqwest::Client` with the server timeout and `api.http_client` pool
+/// settings and default headers
+///
+/// Every client owns its own connection pool, so build one at startup and
+/// share it (see `AppState::http_client`) rather than calling this per request.
+pub fn build_client(config: &AppConfig) -> Client {
+    let pool = &config.api.http_client;
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(config.server.timeout_seconds))
+        .pool_idle_timeout(pool.pool_idle_timeout_seconds.map(Duration::from_secs))
+        .default_headers(default_headers(pool));
+    if let Some(max_idle) = pool.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(seconds) = pool.connect_timeout_seconds {
+        builder = builder.connect_timeout(Duration::from_secs(seconds));
+    }
+    builder.build().unwrap_or_else(|_| {
+        warn!("Failed to build custom HTTP client, using default");
+        Client::new()
+    })
+}
+
+/// Process-wide client for callers constructed without an `AppState`
+///
+/// Built once with the default `api.http_client` settings; clones share its
+/// connection pool. Prefer `AppState::http_client` where a state is at hand.
+pub fn shared_client() -> Client {
+    static SHARED: OnceLock<Client> = OnceLock::new();
+    SHARED
+        .get_or_init(|| build_client(&AppConfig::default()))
+        .clone()
+}
+
+fn default_headers(config: &HttpClientConfig) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.default_headers {
+        match (
+            HeaderName::try_from(name.as_str()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                headers.insert(name, value);
+            }
+            _ => warn!("Ignoring invalid default header '{}'", name),
+        }
+    }
+    headers
+}
+
+/// A response read into memory, so it can be shared between callers
+#[derive(Debug, Clone)]
+pub struct BufferedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl BufferedResponse {
+    async fn read(response: Response) -> Result<Self, AppError> {
+        Ok(Self {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes().await?,
+        })
+    }
+
+    /// Body as UTF-8 text, with invalid sequences replaced
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Body deserialized from JSON
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, AppError> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| AppError::ExternalServiceError(format!("Invalid JSON in response: {}", e)))
+    }
+}
+
+type SharedGet = Shared<BoxFuture<'static, Result<BufferedResponse, Arc<AppError>>>>;
+
+/// HTTP client for calling downstream services
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    breaker_config: Option<CircuitBreakerConfig>,
+    breakers: Arc<Mutex<HashMap<String, CircuitBreaker>>>,
+    /// Breakers for calls made on behalf of a named dependency
+    dependency_breakers: CircuitBreakerRegistry,
+    /// Bulkheads for calls made on behalf of a named dependency
+    bulkheads: BulkheadRegistry,
+    interceptors: Vec<Arc<dyn HttpInterceptor>>,
+    /// In-flight cap for hosts without an override
+    max_in_flight: Option<usize>,
+    /// In-flight caps keyed by `host:port`
+    host_max_in_flight: HashMap<String, usize>,
+    in_flight: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    /// Coalesced GETs in flight, keyed by URL and headers
+    pending_gets: Arc<Mutex<HashMap<String, SharedGet>>>,
+    retry: Option<OperationRetryPolicy<ErrorClass>>,
+}
+
+impl HttpClient {
+    /// Wrap an existing client, with default per-host circuit breakers
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            breaker_config: Some(CircuitBreakerConfig::default()),
+            breakers: Arc::new(Mutex::new(HashMap::new())),
+            dependency_breakers: CircuitBreakerRegistry::new(),
+            bulkheads: BulkheadRegistry::new(),
+            interceptors: Vec::new(),
+            max_in_flight: None,
+            host_max_in_flight: HashMap::new(),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+            pending_gets: Arc::new(Mutex::new(HashMap::new())),
+            retry: None,
+        }
+    }
+
+    /// Build a client using the server timeout, `api.http_client` pool and
+    /// per-host limits, and circuit breaker and bulkhead settings
+    pub fn from_config(config: &AppConfig) -> Self {
+        let reliability = &config.reliability;
+        Self::new(build_client(config))
+            .with_circuit_breaker(reliability.circuit_breaker.clone())
+            .with_dependency_breakers(CircuitBreakerRegistry::from_config(
+                &reliability.circuit_breakers,
+                reliability.circuit_breaker.clone(),
+            ))
+            .with_bulkheads(BulkheadRegistry::from_config(&reliability.bulkheads))
+            .with_host_limits(&config.api.http_client)
+    }
+
+    /// Use `registry` for the breakers of calls made with [`Self::execute_for`]
+    pub fn with_dependency_breakers(mut self, registry: CircuitBreakerRegistry) -> Self {
+        self.dependency_breakers = registry;
+        self
+    }
+
+    /// Run calls made with [`Self::execute_for`] inside the dependency's
+    /// bulkhead from `registry`; dependencies without one are not limited
+    pub fn with_bulkheads(mut self, registry: BulkheadRegistry) -> Self {
+        self.bulkheads = registry;
+        self
+    }
+
+    /// Apply the per-host in-flight limits from `config`
+    pub fn with_host_limits(mut self, config: &HttpClientConfig) -> Self {
+        self.max_in_flight = config.max_in_flight_per_host;
+        self.host_max_in_flight = config
+            .hosts
+            .iter()
+            .map(|(host, limits)| (host.clone(), limits.max_in_flight))
+            .collect();
+        self.in_flight.lock().unwrap().clear();
+        self
+    }
+
+    /// Allow at most `max` requests in flight to each host; further requests queue
+    pub fn with_max_in_flight_per_host(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max);
+        self.in_flight.lock().unwrap().clear();
+        self
+    }
+
+    /// Allow at most `max` requests in flight to `authority` (`host:port`)
+    pub fn with_host_max_in_flight(mut self, authority: impl Into<String>, max: usize) -> Self {
+        self.host_max_in_flight.insert(authority.into(), max);
+        self.in_flight.lock().unwrap().clear();
+        self
+    }
+
+    /// Use `config` for every host's circuit breaker; disabled configs turn breakers off
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.breaker_config = config.enabled.then_some(config);
+        self.breakers.lock().unwrap().clear();
+        self
+    }
+
+    /// Retry failed calls under `policy`
+    ///
+    /// Only failures whose [`ErrorClass`] is retryable and accepted by the
+    /// policy's predicate are repeated. A `Retry-After` delay replaces the
+    /// backoff; one longer than the policy's `max_delay` ends retrying.
+    /// Requests with streaming bodies cannot be cloned and get one attempt,
+    /// as do non-idempotent requests without an `Idempotency-Key` header.
+    pub fn with_retry(mut self, policy: OperationRetryPolicy<ErrorClass>) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Add an interceptor after those already added
+    pub fn with_interceptor(mut self, interceptor: impl HttpInterceptor) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// The underlying `reqwest` client, for building requests
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+
+    /// Send a GET request to `url`
+    pub async fn get(&self, url: &str) -> Result<Response, AppError> {
+        let request = self.client.get(url).build()?;
+        self.execute(request).await
+    }
+
+    /// Send a GET request to `url`, sharing the response with identical GETs in flight
+    pub async fn get_coalesced(&self, url: &str) -> Result<BufferedResponse, AppError> {
+        let request = self.client.get(url).build()?;
+        self.execute_coalesced(request).await
+    }
+
+    /// Send a request and buffer the response, coalescing identical GETs
+    ///
+    /// While a GET with the same URL and headers is in flight, the request is
+    /// not sent; the caller waits for that request's response instead.
+    /// Request interceptors run for every caller, so headers they add (such
+    /// as credentials) are part of what must match; response interceptors run
+    /// once, for the request actually sent. The shared request runs to
+    /// completion even if every caller goes away. Other methods are never
+    /// coalesced. Errors are shared as [`AppError::ExternalServiceError`].
+    pub async fn execute_coalesced(
+        &self,
+        mut request: Request,
+    ) -> Result<BufferedResponse, AppError> {
+        if request.method() != Method::GET {
+            return BufferedResponse::read(self.execute(request).await?).await;
+        }
+
+        self.intercept_request(&mut request).await?;
+        let key = coalescing_key(request.url(), request.headers());
+        let shared = {
+            let mut pending = self.pending_gets.lock().unwrap();
+            match pending.get(&key) {
+                Some(shared) => {
+                    counter!("http_client.coalesced", "host" => authority(request.url()))
+                        .increment(1);
+                    debug!("Coalescing GET {} with a request in flight", request.url());
+                    shared.clone()
+                }
+                None => {
+                    let client = self.clone();
+                    let entry = PendingGet {
+                        pending_gets: self.pending_gets.clone(),
+                        key: key.clone(),
+                    };
+                    let leader = tokio::spawn(async move {
+                        // Later callers send a fresh request, even if this one panics
+                        let _entry = entry;
+                        let authority = authority(request.url());
+                        let breaker = client.breaker(&authority);
+                        let label = BreakerLabel::Host(authority);
+                        match client.send(request, breaker, label, false).await {
+                            Ok(response) => BufferedResponse::read(response).await,
+                            Err(e) => Err(e),
+                        }
+                    });
+                    let shared = async move {
+                        leader
+                            .await
+                            .unwrap_or_else(|e| {
+                                Err(AppError::internal_server_error(format!(
+                                    "Coalesced request failed: {}",
+                                    e
+                                )))
+                            })
+                            .map_err(Arc::new)
+                    }
+                    .boxed()
+                    .shared();
+                    pending.insert(key, shared.clone());
+                    shared
+                }
+            }
+        };
+
+        shared.await.map_err(|e| match e.as_ref() {
+            AppError::ExternalServiceError(message) => {
+                AppError::ExternalServiceError(message.clone())
+            }
+            other => AppError::ExternalServiceError(other.to_string()),
+        })
+    }
+
+    /// Send a request through the circuit breaker for its host
+    ///
+    /// Fails fast with [`AppError::ExternalServiceError`] while that host's
+    /// circuit is open. Responses with a configured failure status and
+    /// transport errors count as failures.
+    pub async fn execute(&self, request: Request) -> Result<Response, AppError> {
+        let authority = authority(request.url());
+        let breaker = self.breaker(&authority);
+        self.send(request, breaker, BreakerLabel::Host(authority), true)
+            .await
+    }
+
+    /// Send a GET request to `url` on behalf of `dependency`
+    pub async fn get_for(&self, dependency: &str, url: &str) -> Result<Response, AppError> {
+        let request = self.client.get(url).build()?;
+        self.execute_for(dependency, request).await
+    }
+
+    /// Send a request through the named circuit breaker of `dependency`
+    ///
+    /// Like [`Self::execute`], but the breaker is the one configured for the
+    /// dependency in `reliability.circuit_breakers` rather than the per-host
+    /// one, so several hosts behind one dependency trip together and each
+    /// dependency has its own thresholds. In-flight limits stay per host.
+    /// A saturated bulkhead fails the call with [`AppError::RateLimited`],
+    /// a queue wait that runs out with a timeout.
+    pub async fn execute_for(
+        &self,
+        dependency: &str,
+        request: Request,
+    ) -> Result<Response, AppError> {
+        let breaker = self.dependency_breakers.get(dependency);
+        let send = self.send(
+            request,
+            breaker,
+            BreakerLabel::Dependency(dependency.to_string()),
+            true,
+        );
+        match self.bulkheads.find(dependency) {
+            Some(bulkhead) => bulkhead.call(send).await?,
+            None => send.await,
+        }
+    }
+
+    /// Send `request`, retrying it under the configured policy
+    ///
+    /// Request interceptors run before each attempt unless `intercept` is
+    /// false because they already ran.
+    async fn send(
+        &self,
+        mut request: Request,
+        breaker: Option<CircuitBreaker>,
+        label: BreakerLabel,
+        intercept: bool,
+    ) -> Result<Response, AppError> {
+        let Some(policy) = self.retry.as_ref().filter(|_| is_replayable(&request)) else {
+            return self
+                .send_once(request, breaker.as_ref(), &label, intercept)
+                .await;
+        };
+
+        let mut attempt = 1;
+        loop {
+            let next = if attempt < policy.max_attempts {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = self
+                .send_once(request, breaker.as_ref(), &label, intercept)
+                .await;
+            let class = match &result {
+                Ok(response) => classify_response(response),
+                Err(e) => Some(classify_app_error(e)),
+            };
+            let (Some(next), Some(class)) = (next, class) else {
+                return result;
+            };
+            if !class.is_retryable() || !policy.should_retry(&class) {
+                return result;
+            }
+            let delay = class
+                .retry_after()
+                .unwrap_or_else(|| policy.delay_for(attempt));
+            if delay > policy.max_delay {
+                debug!(
+                    "{} asked to retry after {:?}, giving up",
+                    label.name(),
+                    delay
+                );
+                return result;
+            }
+
+            debug!(
+                "Attempt {} of {} to {} failed ({:?}), retrying in {:?}",
+                attempt,
+                policy.max_attempts,
+                label.name(),
+                class,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+            request = next;
+            attempt += 1;
+        }
+    }
+
+    async fn send_once(
+        &self,
+        mut request: Request,
+        breaker: Option<&CircuitBreaker>,
+        label: &BreakerLabel,
+        intercept: bool,
+    ) -> Result<Response, AppError> {
+        let authority = authority(request.url());
+
+        if let Some(breaker) = breaker {
+            let permit = breaker.try_acquire();
+            label.record_state(breaker.state());
+            if let Err(e) = permit {
+                debug!("Circuit open for {}, failing fast", label.name());
+                label.record_rejected();
+                return Err(AppError::ExternalServiceError(format!(
+                    "Circuit breaker open for {}: {}",
+                    label.name(),
+                    e
+                )));
+            }
+        }
+
+        // Held until the response headers arrive; queues while the host is at its cap
+        let _slot =
+            match self.in_flight_limit(&authority) {
+                Some(semaphore) => Some(semaphore.acquire_owned().await.map_err(|_| {
+                    AppError::internal_server_error("HTTP client host limit closed")
+                })?),
+                None => None,
+            };
+
+        if intercept {
+            self.intercept_request(&mut request).await?;
+        }
+
+        let result = self.client.execute(request).await;
+
+        if let Some(breaker) = breaker {
+            match &result {
+                Ok(response) => breaker.record_status(response.status()),
+                Err(_) => breaker.record_failure(),
+            }
+            label.record_state(breaker.state());
+        }
+
+        let response = result?;
+        for interceptor in self.interceptors.iter().rev() {
+            interceptor.on_response(&response).await;
+        }
+        Ok(response)
+    }
+
+    async fn intercept_request(&self, request: &mut Request) -> Result<(), AppError> {
+        for interceptor in &self.interceptors {
+            interceptor.on_request(request).await?;
+        }
+        Ok(())
+    }
+
+    /// Circuit state for `authority` (`host:port`), if it has been called
+    pub fn circuit_state(&self, authority: &str) -> Option<CircuitState> {
+        self.breakers
+            .lock()
+            .unwrap()
+            .get(authority)
+            .map(CircuitBreaker::state)
+    }
+
+    /// Circuit state of the named breaker for `dependency`, if it has been called
+    pub fn dependency_circuit_state(&self, dependency: &str) -> Option<CircuitState> {
+        self.dependency_breakers.state(dependency)
+    }
+
+    /// Requests currently in flight to `authority`, if it has a limit
+    pub fn in_flight(&self, authority: &str) -> Option<usize> {
+        let max = self.max_in_flight_for(authority)?;
+        let in_flight = self.in_flight.lock().unwrap();
+        Some(
+            in_flight
+                .get(authority)
+                .map_or(0, |semaphore| max - semaphore.available_permits()),
+        )
+    }
+
+    /// In-flight limit for `authority`; a limit of 0 is treated as 1
+    fn max_in_flight_for(&self, authority: &str) -> Option<usize> {
+        self.host_max_in_flight
+            .get(authority)
+            .copied()
+            .or(self.max_in_flight)
+            .map(|max| max.max(1))
+    }
+
+    fn in_flight_limit(&self, authority: &str) -> Option<Arc<Semaphore>> {
+        let max = self.max_in_flight_for(authority)?;
+        Some(
+            self.in_flight
+                .lock()
+                .unwrap()
+                .entry(authority.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(max)))
+                .clone(),
+        )
+    }
+
+    fn breaker(&self, authority: &str) -> Option<CircuitBreaker> {
+        let config = self.breaker_config.as_ref()?;
+        Some(
+            self.breakers
+                .lock()
+                .unwrap()
+                .entry(authority.to_string())
+                .or_insert_with(|| CircuitBreaker::from_config(config))
+                .clone(),
+        )
+    }
+}
+
+/// Whether sending `request` again cannot repeat a side effect
+///
+/// Idempotent methods qualify; other methods only when the caller marked the
+/// request with an [`IDEMPOTENCY_KEY`] header for the server to deduplicate.
+fn is_replayable(request: &Request) -> bool {
+    request.method().is_idempotent() || request.headers().contains_key(IDEMPOTENCY_KEY)
+}
+
+/// `host:port` of a URL, with the scheme's default port when none is given
+fn authority(url: &reqwest::Url) -> String {
+    format!(
+        "{}:{}",
+        url.host_str().unwrap_or_default(),
+        url.port_or_known_default().unwrap_or_default()
+    )
+}
+
+/// URL and headers, with headers in a stable order
+fn coalescing_key(url: &Url, headers: &HeaderMap) -> String {
+    let mut headers: Vec<_> = headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes())))
+        .collect();
+    headers.sort();
+    format!("{}\n{}", url, headers.join("\n"))
+}
+
+/// Removes a coalesced GET from the pending map once its request is done
+struct PendingGet {
+    pending_gets: Arc<Mutex<HashMap<String, SharedGet>>>,
+    key: String,
+}
+
+impl Drop for PendingGet {
+    fn drop(&mut self) {
+        self.pending_gets.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Which breaker guarded a call, for errors and metric labels
+enum BreakerLabel {
+    Host(String),
+    Dependency(String),
+}
+
+impl BreakerLabel {
+    fn name(&self) -> &str {
+        match self {
+            BreakerLabel::Host(name) | BreakerLabel::Dependency(name) => name,
+        }
+    }
+
+    fn record_state(&self, state: CircuitState) {
+        let value = match state {
+            CircuitState::Closed => 0.0,
+            CircuitState::HalfOpen => 1.0,
+            CircuitState::Open => 2.0,
+        };
+        match self {
+            BreakerLabel::Host(host) => {
+                gauge!("http_client.circuit_state", "host" => host.clone()).set(value)
+            }
+            BreakerLabel::Dependency(dependency) => {
+                gauge!("http_client.circuit_state", "dependency" => dependency.clone()).set(value)
+            }
+        }
+    }
+
+    fn record_rejected(&self) {
+        match self {
+            BreakerLabel::Host(host) => {
+                counter!("http_client.circuit_rejected", "host" => host.clone()).increment(1)
+            }
+            BreakerLabel::Dependency(dependency) => {
+                counter!("http_client.circuit_rejected", "dependency" => dependency.clone())
+                    .increment(1)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn server(status: u16) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn client() -> HttpClient {
+        HttpClient::new(Client::new()).with_circuit_breaker(CircuitBreakerConfig {
+            reset_timeout_ms: 60_000,
+            ..Default::default()
+        })
+    }
+
+    fn authority_of(server: &MockServer) -> String {
+        authority(&reqwest::Url::parse(&server.uri()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_failing_host_does_not_open_other_hosts() {
+        let failing = server(503).await;
+        let healthy = server(200).await;
+        let client = client();
+
+        // A single failure is a 100% failure rate and opens the circuit
+        client.get(&failing.uri()).await.unwrap();
+        assert_eq!(
+            client.circuit_state(&authority_of(&failing)),
+            Some(CircuitState::Open)
+        );
+
+        let short_circuited = client.get(&failing.uri()).await;
+        assert!(matches!(
+            short_circuited,
+            Err(AppError::ExternalServiceError(_))
+        ));
+        assert_eq!(failing.received_requests().await.unwrap().len(), 1);
+
+        let response = client.get(&healthy.uri()).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            client.circuit_state(&authority_of(&healthy)),
+            Some(CircuitState::Closed)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_named_dependency_breakers_trip_independently() {
+        let failing = server(503).await;
+        let mut configs = HashMap::new();
+        configs.insert(
+            "payments".to_string(),
+            CircuitBreakerConfig {
+                reset_timeout_ms: 60_000,
+                ..Default::default()
+            },
+        );
+        configs.insert(
+            "inventory".to_string(),
+            CircuitBreakerConfig {
+                failure_status_codes: vec![500],
+                reset_timeout_ms: 60_000,
+                ..Default::default()
+            },
+        );
+        let client = client().with_dependency_breakers(CircuitBreakerRegistry::from_config(
+            &configs,
+            CircuitBreakerConfig::default(),
+        ));
+
+        // Both dependencies call the same failing host
+        client.get_for("payments", &failing.uri()).await.unwrap();
+        client.get_for("inventory", &failing.uri()).await.unwrap();
+
+        assert_eq!(
+            client.dependency_circuit_state("payments"),
+            Some(CircuitState::Open)
+        );
+        assert!(matches!(
+            client.get_for("payments", &failing.uri()).await,
+            Err(AppError::ExternalServiceError(_))
+        ));
+        // Inventory only counts 500s, so its circuit stays closed
+        assert_eq!(
+            client.dependency_circuit_state("inventory"),
+            Some(CircuitState::Closed)
+        );
+        assert_eq!(
+            client
+                .get_for("inventory", &failing.uri())
+                .await
+                .unwrap()
+                .status(),
+            503
+        );
+        // The per-host breaker was not involved
+        assert_eq!(client.circuit_state(&authority_of(&failing)), None);
+        assert_eq!(failing.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_breaker_never_short_circuits() {
+        let failing = server(503).await;
+        let client = HttpClient::new(Client::new()).with_circuit_breaker(CircuitBreakerConfig {
+            enabled: false,
+            ..Default::default()
+        });
+
+        for _ in 0..3 {
+            assert_eq!(client.get(&failing.uri()).await.unwrap().status(), 503);
+        }
+        assert_eq!(client.circuit_state(&authority_of(&failing)), None);
+    }
+
+    fn retrying_client(max_attempts: u32) -> HttpClient {
+        HttpClient::new(Client::new())
+            .with_circuit_breaker(CircuitBreakerConfig {
+                enabled: false,
+                ..Default::default()
+            })
+            .with_retry(
+                OperationRetryPolicy::new(max_attempts).with_fixed_delay(Duration::from_millis(1)),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let response = retrying_client(3).get(&server.uri()).await.unwrap();
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failures_and_long_retry_after_are_not_retried() {
+        let missing = server(404).await;
+        let response = retrying_client(3).get(&missing.uri()).await.unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(missing.received_requests().await.unwrap().len(), 1);
+
+        let limited = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600"))
+            .mount(&limited)
+            .await;
+        let response = retrying_client(3).get(&limited.uri()).await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert_eq!(limited.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_retried_only_with_idempotency_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        let client = retrying_client(3);
+
+        let request = client
+            .inner()
+            .post(server.uri())
+            .body("{}")
+            .build()
+            .unwrap();
+        let response = client.execute(request).await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        let request = client
+            .inner()
+            .post(server.uri())
+            .header(IDEMPOTENCY_KEY, "order-42")
+            .body("{}")
+            .build()
+            .unwrap();
+        client.execute(request).await.unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+    }
+
+    /// Records hook calls and tags requests with its name
+    struct Recorder {
+        name: &'static str,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl HttpInterceptor for Recorder {
+        async fn on_request(&self, request: &mut Request) -> Result<(), AppError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{}:request", self.name));
+            request
+                .headers_mut()
+                .append("x-interceptor", self.name.parse().unwrap());
+            Ok(())
+        }
+
+        async fn on_response(&self, response: &Response) {
+            self.calls.lock().unwrap().push(format!(
+                "{}:response:{}",
+                self.name,
+                response.status().as_u16()
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interceptors_run_in_order_and_mutate_requests() {
+        let server = server(200).await;
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = client()
+            .with_interceptor(Recorder {
+                name: "first",
+                calls: calls.clone(),
+            })
+            .with_interceptor(Recorder {
+                name: "second",
+                calls: calls.clone(),
+            });
+
+        client.get(&server.uri()).await.unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "first:request",
+                "second:request",
+                "second:response:200",
+                "first:response:200"
+            ]
+        );
+        let received = server.received_requests().await.unwrap();
+        let tags: Vec<_> = received[0]
+            .headers
+            .get_all("x-interceptor")
+            .iter()
+            .map(|value| value.to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(tags, vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_error_aborts_request() {
+        struct Reject;
+
+        #[async_trait]
+        impl HttpInterceptor for Reject {
+            async fn on_request(&self, _request: &mut Request) -> Result<(), AppError> {
+                Err(AppError::AuthenticationError("no token".to_string()))
+            }
+        }
+
+        let server = server(200).await;
+        let client = client().with_interceptor(Reject);
+
+        let result = client.get(&server.uri()).await;
+
+        assert!(matches!(result, Err(AppError::AuthenticationError(_))));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn test_bearer_auth_interceptor_adds_token() {
+        use crate::core::auth::MockTokenClient;
+
+        let server = server(200).await;
+        let client = client().with_interceptor(BearerAuthInterceptor::new(
+            Arc::new(MockTokenClient::new()),
+            "api://pets",
+        ));
+
+        client.get(&server.uri()).await.unwrap();
+
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(
+            received[0].headers.get("authorization").unwrap(),
+            "Bearer mock_access_token"
+        );
+    }
+
+    async fn slow_server(delay: Duration) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(delay))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_per_host_limit_queues_excess_requests() {
+        let delay = Duration::from_millis(200);
+        let server = slow_server(delay).await;
+        let client = client().with_max_in_flight_per_host(2);
+        let host = authority_of(&server);
+
+        let started = std::time::Instant::now();
+        let requests: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                let uri = server.uri();
+                tokio::spawn(async move { client.get(&uri).await.unwrap().status() })
+            })
+            .collect();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(client.in_flight(&host), Some(2));
+
+        for request in requests {
+            assert_eq!(request.await.unwrap(), 200);
+        }
+        // Two rounds of two requests each
+        assert!(started.elapsed() >= delay * 2);
+        assert_eq!(client.in_flight(&host), Some(0));
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_configured_bulkhead_limits_dependency_calls() {
+        use crate::core::config::app_config::BulkheadConfig;
+
+        let server = slow_server(Duration::from_millis(200)).await;
+        let mut config = AppConfig::default();
+        config.reliability.bulkheads.insert(
+            "pets".to_string(),
+            BulkheadConfig {
+                max_concurrent_calls: 1,
+                max_queued_calls: 0,
+                max_wait_ms: 10,
+            },
+        );
+        let client = HttpClient::from_config(&config);
+
+        let (first, second) = tokio::join!(
+            client.get_for("pets", &server.uri()),
+            client.get_for("pets", &server.uri())
+        );
+        let mut results = [first, second];
+        results.sort_by_key(Result::is_ok);
+        assert!(matches!(results[0], Err(AppError::RateLimited(_))));
+        assert_eq!(results[1].as_ref().unwrap().status(), 200);
+
+        // Dependencies without a bulkhead are not limited
+        let (first, second) = tokio::join!(
+            client.get_for("other", &server.uri()),
+            client.get_for("other", &server.uri())
+        );
+        assert!(first.is_ok() && second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_host_override_does_not_limit_other_hosts() {
+        let delay = Duration::from_millis(300);
+        let chatty = slow_server(delay).await;
+        let other = server(200).await;
+        let client = client().with_host_max_in_flight(authority_of(&chatty), 1);
+
+        let blocked = {
+            let client = client.clone();
+            let uri = chatty.uri();
+            tokio::spawn(async move { client.get(&uri).await.unwrap() })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(client.in_flight(&authority_of(&chatty)), Some(1));
+
+        // Other hosts are unlimited and answer while the chatty host is saturated
+        let started = std::time::Instant::now();
+        assert_eq!(client.get(&other.uri()).await.unwrap().status(), 200);
+        assert!(started.elapsed() < delay);
+        assert_eq!(client.in_flight(&authority_of(&other)), None);
+
+        blocked.await.unwrap();
+    }
+
+    #[test]
+    fn test_host_limits_from_config() {
+        let mut config = HttpClientConfig {
+            max_in_flight_per_host: Some(8),
+            ..Default::default()
+        };
+        config.hosts.insert(
+            "api-a.example.com:443".to_string(),
+            crate::core::config::app_config::HostLimitsConfig { max_in_flight: 2 },
+        );
+
+        let client = client().with_host_limits(&config);
+
+        assert_eq!(client.in_flight("api-a.example.com:443"), Some(0));
+        assert_eq!(client.max_in_flight_for("api-a.example.com:443"), Some(2));
+        assert_eq!(client.max_in_flight_for("api-b.example.com:443"), Some(8));
+    }
+
+    #[test]
+    fn test_zero_in_flight_limit_allows_one_request() {
+        let client = client().with_max_in_flight_per_host(0);
+        let limit = client.in_flight_limit("api.example.com:443").unwrap();
+
+        assert_eq!(client.in_flight("api.example.com:443"), Some(0));
+        let _permit = limit.try_acquire().unwrap();
+        assert_eq!(client.in_flight("api.example.com:443"), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_identical_gets_are_coalesced() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("{\"species\":\"cat\"}")
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .mount(&server)
+            .await;
+        let client = client();
+
+        let requests: Vec<_> = (0..5)
+            .map(|_| {
+                let client = client.clone();
+                let uri = format!("{}/reference", server.uri());
+                tokio::spawn(async move { client.get_coalesced(&uri).await.unwrap() })
+            })
+            .collect();
+
+        for request in requests {
+            let response = request.await.unwrap();
+            assert_eq!(response.status, 200);
+            let body: serde_json::Value = response.json().unwrap();
+            assert_eq!(body["species"], "cat");
+        }
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        // Once the shared request completes, the next GET goes out again
+        client
+            .get_coalesced(&format!("{}/reference", server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_coalesced_get_does_not_linger() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(100)))
+            .mount(&server)
+            .await;
+        let client = client();
+        let uri = format!("{}/reference", server.uri());
+
+        let caller = tokio::spawn({
+            let client = client.clone();
+            let uri = uri.clone();
+            async move { client.get_coalesced(&uri).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        caller.abort();
+
+        // The request still completes and leaves the pending map
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(client.pending_gets.lock().unwrap().is_empty());
+        client.get_coalesced(&uri).await.unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_gets_differing_in_intercepted_headers_are_not_coalesced() {
+        struct Caller(std::sync::atomic::AtomicUsize);
+
+        #[async_trait]
+        impl HttpInterceptor for Caller {
+            async fn on_request(&self, request: &mut Request) -> Result<(), AppError> {
+                let caller = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                request
+                    .headers_mut()
+                    .insert("x-caller", HeaderValue::from(caller));
+                Ok(())
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(100)))
+            .mount(&server)
+            .await;
+        let client = client().with_interceptor(Caller(Default::default()));
+        let uri = format!("{}/reference", server.uri());
+
+        let (first, second) = tokio::join!(client.get_coalesced(&uri), client.get_coalesced(&uri));
+
+        first.unwrap();
+        second.unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_coalescing_key_depends_on_headers() {
+        let url = reqwest::Url::parse("https://api-a.example.com/pets").unwrap();
+        let mut first = HeaderMap::new();
+        first.insert("accept", HeaderValue::from_static("application/json"));
+        first.insert("x-tenant", HeaderValue::from_static("a"));
+        let mut reordered = HeaderMap::new();
+        reordered.insert("x-tenant", HeaderValue::from_static("a"));
+        reordered.insert("accept", HeaderValue::from_static("application/json"));
+        let mut other = first.clone();
+        other.insert("x-tenant", HeaderValue::from_static("b"));
+
+        assert_eq!(
+            coalescing_key(&url, &first),
+            coalescing_key(&url, &reordered)
+        );
+        assert_ne!(coalescing_key(&url, &first), coalescing_key(&url, &other));
+    }
+
+    #[test]
+    fn test_authority_includes_default_port() {
+        let url = reqwest::Url::parse("https://api-a.example.com/pets").unwrap();
+        assert_eq!(authority(&url), "api-a.example.com:443");
+    }
+}
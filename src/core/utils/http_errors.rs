@@ -0,0 +1,208 @@
+//! Retry classification of outbound HTTP failures
+//!
+//! Whether a failed call is worth repeating is decided in one place, so the
+//! retries of [`HttpClient`](super::http_client::HttpClient) and application
+//! code agree. Every failure is one of:
+//!
+//! - [`ErrorClass::Transient`]: timeouts, connection failures, 408, 425, 500,
+//!   502, 504 and 503 without `Retry-After`
+//! - [`ErrorClass::RateLimited`]: 429, and 503 with `Retry-After`; carries the
+//!   server's requested delay when it sent one
+//! - [`ErrorClass::Permanent`]: every other 4xx/5xx and errors building the
+//!   request or decoding the response
+//!
+//! ```ignore
+//! let policy = OperationRetryPolicy::from_config(&config.reliability.retry)
+//!     .with_retry_if(|e: &AppError| classify_app_error(e).is_retryable());
+//! let pets = retry(&policy, || fetch_pets(&client)).await?;
+//! ```
+
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::{Response, StatusCode};
+
+use crate::core::error::AppError;
+use crate::core::utils::sources;
+
+/// How a failed HTTP call should be treated by retry logic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Likely to succeed if repeated after a backoff
+    Transient,
+    /// Repeating the same request will fail the same way
+    Permanent,
+    /// The server asked the client to slow down, after the given delay if known
+    RateLimited(Option<Duration>),
+}
+
+impl ErrorClass {
+    /// Whether another attempt may succeed
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, ErrorClass::Permanent)
+    }
+
+    /// Delay requested by the server through `Retry-After`
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ErrorClass::RateLimited(delay) => *delay,
+            _ => None,
+        }
+    }
+}
+
+/// Class of a response status; `None` unless the status is an error
+pub fn classify_status(status: StatusCode, headers: &HeaderMap) -> Option<ErrorClass> {
+    if !status.is_client_error() && !status.is_server_error() {
+        return None;
+    }
+    let retry_after = headers.get(RETRY_AFTER).and_then(parse_retry_after);
+
+    Some(match status {
+        StatusCode::TOO_MANY_REQUESTS => ErrorClass::RateLimited(retry_after),
+        StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => {
+            ErrorClass::RateLimited(retry_after)
+        }
+        StatusCode::REQUEST_TIMEOUT
+        | StatusCode::TOO_EARLY
+        | StatusCode::INTERNAL_SERVER_ERROR
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT => ErrorClass::Transient,
+        _ => ErrorClass::Permanent,
+    })
+}
+
+/// Class of a received response; `None` unless its status is an error
+pub fn classify_response(response: &Response) -> Option<ErrorClass> {
+    classify_status(response.status(), response.headers())
+}
+
+/// Class of a `reqwest` error
+pub fn classify_error(error: &reqwest::Error) -> ErrorClass {
+    if let Some(class) = error
+        .status()
+        .and_then(|status| classify_status(status, &HeaderMap::new()))
+    {
+        return class;
+    }
+    if error.is_timeout() || error.is_connect() || error.is_body() {
+        ErrorClass::Transient
+    } else {
+        ErrorClass::Permanent
+    }
+}
+
+/// Class of an error returned by the HTTP client
+///
+/// Only transport errors are retryable; an open circuit or an interceptor
+/// rejecting the request is permanent for the current call.
+pub fn classify_app_error(error: &AppError) -> ErrorClass {
+    match error {
+        AppError::ClientError(e) => classify_error(e),
+        _ => ErrorClass::Permanent,
+    }
+}
+
+/// Delay requested by a `Retry-After` header, in seconds or as an HTTP date
+pub fn parse_retry_after(value: &HeaderValue) -> Option<Duration> {
+    parse_retry_after_at(value, sources::time().now())
+}
+
+/// [`parse_retry_after`] relative to `now`; dates in the past mean no delay
+pub fn parse_retry_after_at(value: &HeaderValue, now: SystemTime) -> Option<Duration> {
+    let value = value.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date: SystemTime = chrono::DateTime::parse_from_rfc2822(value).ok()?.into();
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn class(status: u16, retry_after: Option<&'static str>) -> Option<ErrorClass> {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = retry_after {
+            headers.insert(RETRY_AFTER, HeaderValue::from_static(value));
+        }
+        classify_status(StatusCode::from_u16(status).unwrap(), &headers)
+    }
+
+    #[test]
+    fn test_statuses_are_classified() {
+        assert_eq!(class(200, None), None);
+        assert_eq!(class(304, None), None);
+        for status in [408, 500, 502, 503, 504] {
+            assert_eq!(
+                class(status, None),
+                Some(ErrorClass::Transient),
+                "{}",
+                status
+            );
+        }
+        for status in [400, 401, 403, 404, 409, 422, 501] {
+            assert_eq!(
+                class(status, None),
+                Some(ErrorClass::Permanent),
+                "{}",
+                status
+            );
+        }
+        assert_eq!(class(429, None), Some(ErrorClass::RateLimited(None)));
+    }
+
+    #[test]
+    fn test_retry_after_seconds_are_parsed() {
+        let limited = class(429, Some("120")).unwrap();
+        assert_eq!(
+            limited,
+            ErrorClass::RateLimited(Some(Duration::from_secs(120)))
+        );
+        assert_eq!(limited.retry_after(), Some(Duration::from_secs(120)));
+        assert!(limited.is_retryable());
+
+        // 503 with Retry-After is a request to back off, not just an outage
+        assert_eq!(
+            class(503, Some("5")),
+            Some(ErrorClass::RateLimited(Some(Duration::from_secs(5))))
+        );
+        // An unparseable header is ignored
+        assert_eq!(class(503, Some("soon")), Some(ErrorClass::Transient));
+    }
+
+    #[test]
+    fn test_retry_after_http_date_is_relative_to_now() {
+        let value = HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT");
+        let date = UNIX_EPOCH + Duration::from_secs(1_445_412_480);
+
+        assert_eq!(
+            parse_retry_after_at(&value, date - Duration::from_secs(30)),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after_at(&value, date + Duration::from_secs(30)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transport_errors_are_classified() {
+        let connect = reqwest::get("http://127.0.0.1:1").await.unwrap_err();
+        assert_eq!(classify_error(&connect), ErrorClass::Transient);
+        assert_eq!(
+            classify_app_error(&AppError::from(connect)),
+            ErrorClass::Transient
+        );
+
+        let builder = reqwest::Client::new().get("not a url").build().unwrap_err();
+        assert_eq!(classify_error(&builder), ErrorClass::Permanent);
+
+        let open = AppError::ExternalServiceError("Circuit breaker open".to_string());
+        assert_eq!(classify_app_error(&open), ErrorClass::Permanent);
+        assert!(!ErrorClass::Permanent.is_retryable());
+    }
+}
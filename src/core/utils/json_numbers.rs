@@ -0,0 +1,329 @@
+//! Keeping large integers intact for JavaScript clients
+//!
+//! JavaScript parses JSON numbers as `f64`, so integers beyond ±2^53 - 1
+//! silently lose precision. Integers can be sent as strings instead, leaving
+//! the Rust type as `i64`/`u64`:
+//!
+//! - per field, with `#[serde(with = "int_string")]`, which also accepts
+//!   strings (and plain numbers) when deserializing;
+//! - per resource, by overriding [`ApiResource::integer_encoding`](super::ApiResource::integer_encoding);
+//! - per router, by layering [`encode_json_integers`] with the encoding as
+//!   its state;
+//! - per request, with an `x-json-integers: string` (or `unsafe_as_string`)
+//!   header, which [`encode_json_integers`] honours over its own encoding.
+//!   The app router installs it with [`IntegerEncoding::Number`], so every
+//!   client can opt in without changing responses for the others.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Pet {
+//!     #[serde(with = "int_string")]
+//!     id: i64,
+//!     name: String,
+//! }
+//! ```
+
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use tracing::warn;
+
+pub use crate::core::config::app_config::IntegerEncoding;
+use crate::core::error::AppError;
+use crate::core::error::middleware::request_id_of;
+
+/// Largest integer an `f64` represents exactly (2^53 - 1)
+pub const MAX_SAFE_INTEGER: u64 = 9_007_199_254_740_991;
+
+/// Request header choosing the integer encoding of the response
+pub const JSON_INTEGERS_HEADER: &str = "x-json-integers";
+
+/// Responses larger than this are passed through unchanged
+const MAX_REWRITE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Serde helpers writing an integer as a JSON string
+///
+/// Deserializing accepts either a string or a number.
+pub mod int_string {
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr + Deserialize<'de>,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrNumber<T> {
+            String(String),
+            Number(T),
+        }
+
+        match StringOrNumber::<T>::deserialize(deserializer)? {
+            StringOrNumber::String(value) => value.trim().parse().map_err(D::Error::custom),
+            StringOrNumber::Number(value) => Ok(value),
+        }
+    }
+}
+
+/// Rewrite the integers in `value` according to `encoding`
+pub fn encode_integers(value: &mut Value, encoding: IntegerEncoding) {
+    if encoding == IntegerEncoding::Number {
+        return;
+    }
+    match value {
+        Value::Number(number) => {
+            let unsafe_integer = match (number.as_i64(), number.as_u64()) {
+                (_, Some(n)) => n > MAX_SAFE_INTEGER,
+                (Some(n), None) => n.unsigned_abs() > MAX_SAFE_INTEGER,
+                // Floats are left alone
+                (None, None) => return,
+            };
+            if encoding == IntegerEncoding::String || unsafe_integer {
+                *value = Value::String(number.to_string());
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| encode_integers(value, encoding)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|value| encode_integers(value, encoding)),
+        _ => {}
+    }
+}
+
+/// Middleware applying `encoding` to JSON response bodies
+///
+/// Use with `axum::middleware::from_fn_with_state(IntegerEncoding, encode_json_integers)`.
+/// A valid [`JSON_INTEGERS_HEADER`] on the request takes precedence over `encoding`.
+pub async fn encode_json_integers(
+    State(encoding): State<IntegerEncoding>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let encoding = requested_encoding(request.headers()).unwrap_or(encoding);
+    let request_id = request_id_of(&request);
+    let mut response = next.run(request).await;
+    if !is_json(&response) {
+        return response;
+    }
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static(JSON_INTEGERS_HEADER));
+    if encoding == IntegerEncoding::Number {
+        return response;
+    }
+    if response
+        .body()
+        .size_hint()
+        .upper()
+        .is_none_or(|size| size > MAX_REWRITE_BYTES)
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_REWRITE_BYTES as usize).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to buffer JSON response for integer encoding: {}", e);
+            return AppError::internal_server_error("Failed to read the response body")
+                .into_response_with_request_id(request_id);
+        }
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    encode_integers(&mut value, encoding);
+    let body = match serde_json::to_vec(&value) {
+        Ok(body) => body,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Encoding asked for with [`JSON_INTEGERS_HEADER`], if any
+fn requested_encoding(headers: &HeaderMap) -> Option<IntegerEncoding> {
+    let value = headers.get(JSON_INTEGERS_HEADER)?.to_str().ok()?;
+    match value.trim() {
+        "number" => Some(IntegerEncoding::Number),
+        "string" => Some(IntegerEncoding::String),
+        "unsafe_as_string" => Some(IntegerEncoding::UnsafeAsString),
+        _ => None,
+    }
+}
+
+/// Whether the response is `application/json` or a `+json` type
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| {
+            let mime = mime.trim();
+            mime.eq_ignore_ascii_case("application/json") || mime.ends_with("+json")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Json, Router, routing::get};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Pet {
+        #[serde(with = "int_string")]
+        id: i64,
+        name: String,
+    }
+
+    #[test]
+    fn test_id_serializes_as_string_and_round_trips() {
+        let pet = Pet {
+            id: 9_007_199_254_740_993,
+            name: "Rex".to_string(),
+        };
+
+        let json = serde_json::to_value(&pet).unwrap();
+        assert_eq!(json, json!({ "id": "9007199254740993", "name": "Rex" }));
+
+        let back: Pet = serde_json::from_value(json).unwrap();
+        assert_eq!(back, pet);
+
+        // Clients that still send a number are accepted
+        let back: Pet = serde_json::from_value(json!({ "id": 42, "name": "Rex" })).unwrap();
+        assert_eq!(back.id, 42);
+    }
+
+    #[test]
+    fn test_encode_integers_modes() {
+        let original = json!({
+            "id": 9_007_199_254_740_993_i64,
+            "owner_id": -9_007_199_254_740_993_i64,
+            "age": 3,
+            "weight": 4.5,
+            "tags": [1, u64::MAX],
+        });
+
+        let mut value = original.clone();
+        encode_integers(&mut value, IntegerEncoding::Number);
+        assert_eq!(value, original);
+
+        let mut value = original.clone();
+        encode_integers(&mut value, IntegerEncoding::UnsafeAsString);
+        assert_eq!(
+            value,
+            json!({
+                "id": "9007199254740993",
+                "owner_id": "-9007199254740993",
+                "age": 3,
+                "weight": 4.5,
+                "tags": [1, "18446744073709551615"],
+            })
+        );
+
+        let mut value = original;
+        encode_integers(&mut value, IntegerEncoding::String);
+        assert_eq!(value["age"], json!("3"));
+        assert_eq!(value["weight"], json!(4.5));
+    }
+
+    #[tokio::test]
+    async fn test_middleware_stringifies_pet_id() {
+        let app = Router::new()
+            .route(
+                "/pets/1",
+                get(|| async { Json(json!({ "id": 9_007_199_254_740_993_i64, "name": "Rex" })) }),
+            )
+            .route("/text", get(|| async { "9007199254740993" }))
+            .layer(axum::middleware::from_fn_with_state(
+                IntegerEncoding::String,
+                encode_json_integers,
+            ));
+
+        let request = Request::builder()
+            .uri("/pets/1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let pet: Pet = serde_json::from_slice(&body).unwrap();
+        assert_eq!(pet.id, 9_007_199_254_740_993);
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap()["id"],
+            json!("9007199254740993")
+        );
+
+        // Non-JSON bodies pass through
+        let request = Request::builder().uri("/text").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"9007199254740993");
+    }
+
+    #[tokio::test]
+    async fn test_request_header_selects_encoding() {
+        let app = Router::new()
+            .route(
+                "/pets/1",
+                get(|| async { Json(json!({ "id": 9_007_199_254_740_993_i64, "age": 3 })) }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                IntegerEncoding::Number,
+                encode_json_integers,
+            ));
+
+        let fetch = |encoding: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut request = Request::builder().uri("/pets/1");
+                if let Some(encoding) = encoding {
+                    request = request.header(JSON_INTEGERS_HEADER, encoding);
+                }
+                let response = app
+                    .oneshot(request.body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.headers()[header::VARY], JSON_INTEGERS_HEADER);
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<Value>(&body).unwrap()
+            }
+        };
+
+        assert_eq!(
+            fetch(None).await,
+            json!({ "id": 9_007_199_254_740_993_i64, "age": 3 })
+        );
+        assert_eq!(
+            fetch(Some("unsafe_as_string")).await,
+            json!({ "id": "9007199254740993", "age": 3 })
+        );
+        assert_eq!(
+            fetch(Some("string")).await,
+            json!({ "id": "9007199254740993", "age": "3" })
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Runtime log level control
+//!
+//! [`LogLevels`] owns a reloadable [`Targets`] filter on the tracing
+//! subscriber, so the global level and per-target levels can be raised to
+//! debug a live issue and put back afterwards, without a redeploy.
+//! `/actuator/loggers` exposes it when `endpoint_security.expose_loggers`
+//! is enabled:
+//!
+//! ```text
+//! GET    /actuator/loggers                                             current levels
+//! POST   /actuator/loggers {"target": "navius::core::auth", "level": "debug"}
+//! POST   /actuator/loggers {"target": "navius::core::auth", "level": null}   revert target
+//! POST   /actuator/loggers {"level": "warn"}                          global level
+//! DELETE /actuator/loggers                                             startup levels
+//! ```
+//!
+//! ```ignore
+//! let (filter, levels) = LogLevels::layer(LevelFilter::INFO);
+//! tracing_subscriber::registry().with(filter).with(fmt::layer()).init();
+//! levels.install();
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{info, warn};
+use tracing_subscriber::{Registry, filter::LevelFilter, filter::Targets, reload};
+
+use crate::core::error::AppError;
+
+static GLOBAL: OnceLock<LogLevels> = OnceLock::new();
+
+/// Why a log level change was refused
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LogLevelError {
+    #[error("unknown log level '{0}', expected one of off, error, warn, info, debug, trace")]
+    InvalidLevel(String),
+    #[error("log target must not be empty")]
+    EmptyTarget,
+    #[error("failed to reload log filter: {0}")]
+    Reload(String),
+}
+
+impl From<LogLevelError> for AppError {
+    fn from(error: LogLevelError) -> Self {
+        match error {
+            LogLevelError::Reload(_) => AppError::internal_server_error(error.to_string()),
+            _ => AppError::BadRequest(error.to_string()),
+        }
+    }
+}
+
+/// Current global and per-target levels
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LoggerLevels {
+    /// Level for targets without their own
+    pub default_level: String,
+    /// Levels set for specific targets, e.g. `navius::core::auth`
+    pub targets: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Levels {
+    default: LevelFilter,
+    targets: BTreeMap<String, LevelFilter>,
+}
+
+impl Levels {
+    fn filter(&self) -> Targets {
+        Targets::new()
+            .with_default(self.default)
+            .with_targets(self.targets.clone())
+    }
+}
+
+type ReloadFn = Box<dyn Fn(Targets) -> Result<(), String> + Send + Sync>;
+
+/// Reloadable log levels for a tracing subscriber
+pub struct LogLevels {
+    initial: Levels,
+    current: Mutex<Levels>,
+    reload: ReloadFn,
+}
+
+impl std::fmt::Debug for LogLevels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogLevels")
+            .field("current", &self.current)
+            .finish()
+    }
+}
+
+impl LogLevels {
+    /// A filter layer logging at `default`, and the handle controlling it
+    pub fn layer<S>(default: LevelFilter) -> (reload::Layer<Targets, S>, Self)
+    where
+        S: tracing::Subscriber + 'static,
+    {
+        let initial = Levels {
+            default,
+            targets: BTreeMap::new(),
+        };
+        let (layer, handle) = reload::Layer::new(initial.filter());
+        let levels = Self {
+            current: Mutex::new(initial.clone()),
+            initial,
+            reload: Box::new(move |filter| handle.reload(filter).map_err(|e| e.to_string())),
+        };
+        (layer, levels)
+    }
+
+    /// Levels for a filter on the [`Registry`], as set up by `main`
+    pub fn for_registry(default: LevelFilter) -> (reload::Layer<Targets, Registry>, Self) {
+        Self::layer(default)
+    }
+
+    /// Make these the levels served by `/actuator/loggers`
+    pub fn install(self) {
+        if GLOBAL.set(self).is_err() {
+            warn!("Log levels already installed, ignoring");
+        }
+    }
+
+    /// Levels installed by [`LogLevels::install`], if any
+    pub fn global() -> Option<&'static LogLevels> {
+        GLOBAL.get()
+    }
+
+    /// Parse `off`, `error`, `warn`, `info`, `debug` or `trace`, in any case
+    pub fn parse_level(level: &str) -> Result<LevelFilter, LogLevelError> {
+        level
+            .trim()
+            .parse()
+            .map_err(|_| LogLevelError::InvalidLevel(level.to_string()))
+    }
+
+    /// Current global and per-target levels
+    pub fn levels(&self) -> LoggerLevels {
+        let current = self.current.lock().unwrap();
+        LoggerLevels {
+            default_level: current.default.to_string(),
+            targets: current
+                .targets
+                .iter()
+                .map(|(target, level)| (target.clone(), level.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Set the level for targets without their own
+    pub fn set_default(&self, level: LevelFilter) -> Result<(), LogLevelError> {
+        info!("Setting default log level to {}", level);
+        self.update(|levels| levels.default = level)
+    }
+
+    /// Set the level for `target` and the modules under it
+    pub fn set_target(&self, target: &str, level: LevelFilter) -> Result<(), LogLevelError> {
+        let target = Self::target(target)?;
+        info!("Setting log level for {} to {}", target, level);
+        self.update(|levels| {
+            levels.targets.insert(target, level);
+        })
+    }
+
+    /// Put `target` back on the level it started with
+    pub fn clear_target(&self, target: &str) -> Result<(), LogLevelError> {
+        let target = Self::target(target)?;
+        info!("Reverting log level for {}", target);
+        let initial = self.initial.targets.get(&target).copied();
+        self.update(|levels| match initial {
+            Some(level) => {
+                levels.targets.insert(target, level);
+            }
+            None => {
+                levels.targets.remove(&target);
+            }
+        })
+    }
+
+    /// Restore every level to its startup value
+    pub fn reset(&self) -> Result<(), LogLevelError> {
+        info!("Resetting log levels");
+        let initial = self.initial.clone();
+        self.update(|levels| *levels = initial)
+    }
+
+    fn target(target: &str) -> Result<String, LogLevelError> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Err(LogLevelError::EmptyTarget);
+        }
+        Ok(target.to_string())
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Levels)) -> Result<(), LogLevelError> {
+        let mut current = self.current.lock().unwrap();
+        let mut next = current.clone();
+        change(&mut next);
+        (self.reload)(next.filter()).map_err(LogLevelError::Reload)?;
+        *current = next;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    /// Records the targets of events that pass the filter
+    #[derive(Clone, Default)]
+    struct EventRecorder {
+        targets: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl<S: Subscriber> Layer<S> for EventRecorder {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            self.targets
+                .lock()
+                .unwrap()
+                .push(event.metadata().target().to_string());
+        }
+    }
+
+    impl EventRecorder {
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut *self.targets.lock().unwrap())
+        }
+    }
+
+    /// Targets of the events `emit` lets through, ignoring earlier ones
+    fn emitted(recorder: &EventRecorder) -> Vec<String> {
+        recorder.take();
+        emit();
+        recorder.take()
+    }
+
+    fn emit() {
+        tracing::debug!(target: "navius::core::auth", "validating token");
+        tracing::debug!(target: "navius::core::auth::providers", "fetching keys");
+        tracing::debug!(target: "navius::core::cache", "cache miss");
+        tracing::info!(target: "navius::core::cache", "cache ready");
+    }
+
+    #[test]
+    fn test_target_debug_is_emitted_until_reverted() {
+        let recorder = EventRecorder::default();
+        let (filter, levels) = LogLevels::for_registry(LevelFilter::INFO);
+        let subscriber = Registry::default().with(filter).with(recorder.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            assert_eq!(emitted(&recorder), vec!["navius::core::cache"]);
+
+            levels
+                .set_target("navius::core::auth", LevelFilter::DEBUG)
+                .unwrap();
+            assert_eq!(
+                emitted(&recorder),
+                vec![
+                    "navius::core::auth",
+                    "navius::core::auth::providers",
+                    "navius::core::cache"
+                ]
+            );
+
+            levels.clear_target("navius::core::auth").unwrap();
+            assert_eq!(emitted(&recorder), vec!["navius::core::cache"]);
+        });
+    }
+
+    #[test]
+    fn test_default_level_and_reset() {
+        let recorder = EventRecorder::default();
+        let (filter, levels) = LogLevels::for_registry(LevelFilter::INFO);
+        let subscriber = Registry::default().with(filter).with(recorder.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            levels.set_default(LevelFilter::DEBUG).unwrap();
+            levels
+                .set_target("navius::core::cache", LevelFilter::OFF)
+                .unwrap();
+            assert_eq!(
+                levels.levels(),
+                LoggerLevels {
+                    default_level: "debug".to_string(),
+                    targets: [("navius::core::cache".to_string(), "off".to_string())].into(),
+                }
+            );
+            assert_eq!(
+                emitted(&recorder),
+                vec!["navius::core::auth", "navius::core::auth::providers"]
+            );
+
+            levels.reset().unwrap();
+            assert_eq!(levels.levels().default_level, "info");
+            assert!(levels.levels().targets.is_empty());
+            assert_eq!(emitted(&recorder), vec!["navius::core::cache"]);
+        });
+    }
+
+    #[test]
+    fn test_invalid_input_is_rejected() {
+        assert_eq!(LogLevels::parse_level("DEBUG"), Ok(LevelFilter::DEBUG));
+        assert_eq!(
+            LogLevels::parse_level("verbose"),
+            Err(LogLevelError::InvalidLevel("verbose".to_string()))
+        );
+
+        let (_filter, levels) = LogLevels::for_registry(LevelFilter::INFO);
+        assert_eq!(
+            levels.set_target("  ", LevelFilter::DEBUG),
+            Err(LogLevelError::EmptyTarget)
+        );
+    }
+}
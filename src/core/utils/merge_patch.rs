@@ -0,0 +1,150 @@
+//! JSON Merge Patch (RFC 7386) for partial updates
+//!
+//! A PATCH body is a partial JSON object: fields present in the patch
+//! replace the current value, `null` deletes the field, and absent fields
+//! are left unchanged. Nested objects are merged the same way; arrays and
+//! other values are replaced whole.
+//!
+//! ```ignore
+//! async fn patch_pet(
+//!     Path(id): Path<i64>,
+//!     Json(patch): Json<MergePatch>,
+//! ) -> Result<Json<Pet>> {
+//!     let current = repository.get(id).await?;
+//!     let updated: Pet = patch.apply_to(&current)?;
+//!     repository.save(&updated).await?;
+//!     Ok(Json(updated))
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::core::error::{AppError, Result};
+
+/// Media type for merge patch request bodies
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+/// A merge patch document, as sent in a PATCH request body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MergePatch(pub Value);
+
+impl MergePatch {
+    /// `current` with this patch applied
+    ///
+    /// Fails with `422 Unprocessable Entity` if the patched document is not
+    /// a valid `T`, e.g. a required field was deleted.
+    pub fn apply_to<T>(&self, current: &T) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let mut document = serde_json::to_value(current).map_err(|e| {
+            AppError::internal_server_error(format!("Failed to serialize resource: {}", e))
+        })?;
+        apply_merge_patch(&mut document, &self.0);
+        serde_json::from_value(document)
+            .map_err(|e| AppError::UnprocessableEntity(format!("Invalid merge patch: {}", e)))
+    }
+}
+
+/// Apply `patch` to `target` in place, as specified by RFC 7386
+pub fn apply_merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("target was just made an object");
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            apply_merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Pet {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+        age: u32,
+    }
+
+    fn patched(mut target: Value, patch: Value) -> Value {
+        apply_merge_patch(&mut target, &patch);
+        target
+    }
+
+    #[test]
+    fn test_sets_field() {
+        assert_eq!(
+            patched(json!({"name": "Rex", "age": 3}), json!({"age": 4})),
+            json!({"name": "Rex", "age": 4})
+        );
+        assert_eq!(
+            patched(json!({"name": "Rex"}), json!({"owner": {"id": 7}})),
+            json!({"name": "Rex", "owner": {"id": 7}})
+        );
+    }
+
+    #[test]
+    fn test_null_deletes_field() {
+        assert_eq!(
+            patched(
+                json!({"name": "Rex", "tag": "dog", "owner": {"id": 7, "email": "a@b.c"}}),
+                json!({"tag": null, "owner": {"email": null}})
+            ),
+            json!({"name": "Rex", "owner": {"id": 7}})
+        );
+    }
+
+    #[test]
+    fn test_absent_field_is_unchanged() {
+        let current = json!({"name": "Rex", "tags": ["a", "b"], "owner": {"id": 7}});
+
+        assert_eq!(patched(current.clone(), json!({})), current);
+        // Arrays are replaced, not merged
+        assert_eq!(
+            patched(current, json!({"tags": ["c"]})),
+            json!({"name": "Rex", "tags": ["c"], "owner": {"id": 7}})
+        );
+    }
+
+    #[test]
+    fn test_apply_to_typed_resource() {
+        let current = Pet {
+            name: "Rex".to_string(),
+            tag: Some("dog".to_string()),
+            age: 3,
+        };
+
+        let updated = MergePatch(json!({"tag": null, "age": 4}))
+            .apply_to(&current)
+            .unwrap();
+        assert_eq!(
+            updated,
+            Pet {
+                name: "Rex".to_string(),
+                tag: None,
+                age: 4,
+            }
+        );
+
+        let error = MergePatch(json!({"name": null}))
+            .apply_to(&current)
+            .unwrap_err();
+        assert!(matches!(error, AppError::UnprocessableEntity(_)));
+    }
+}
@@ -0,0 +1,335 @@
+//! Streaming NDJSON ingestion
+//!
+//! Bulk imports send one JSON record per line (`application/x-ndjson`).
+//! [`NdjsonIngest`] reads the request body as it arrives, deserializes each
+//! line and hands the record to a handler, so the payload is never buffered
+//! as a whole. The outcome of every line is tallied in an [`NdjsonSummary`]:
+//!
+//! ```ignore
+//! async fn import_pets(body: Body) -> Json<NdjsonSummary> {
+//!     let summary = NdjsonIngest::new()
+//!         .process(body, |pet: NewPet| async move {
+//!             pet.validate()?;
+//!             repository.create(pet).await.map(|_| ())
+//!         })
+//!         .await;
+//!     Json(summary)
+//! }
+//! ```
+//!
+//! A malformed line or a failing handler is recorded and the next line is
+//! processed; with [`NdjsonIngest::with_stop_on_error`] the first failure
+//! ends ingestion instead. Blank lines are skipped, and a final line without
+//! a trailing newline is still processed.
+
+use std::future::Future;
+
+use axum::body::Body;
+use futures::StreamExt;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::{debug, warn};
+
+use crate::core::error::AppError;
+
+/// Content type of newline-delimited JSON
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+const DEFAULT_MAX_LINE_BYTES: usize = 1024 * 1024;
+const DEFAULT_MAX_REPORTED_ERRORS: usize = 100;
+
+/// A line that could not be ingested
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineError {
+    /// 1-based line number in the body
+    pub line: usize,
+    /// `invalid_json`, `line_too_long`, `read_error`, or the handler's error type
+    pub code: String,
+    pub message: String,
+}
+
+/// Outcome of an NDJSON ingestion
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NdjsonSummary {
+    /// Records handled successfully
+    pub processed: usize,
+    /// Lines that were malformed or whose handler failed
+    pub failed: usize,
+    /// Whether ingestion stopped before the end of the body
+    pub aborted: bool,
+    /// Failed lines, up to the configured limit
+    pub errors: Vec<LineError>,
+}
+
+/// Reads an NDJSON body line by line
+#[derive(Debug, Clone)]
+pub struct NdjsonIngest {
+    stop_on_error: bool,
+    max_line_bytes: usize,
+    max_reported_errors: usize,
+}
+
+impl Default for NdjsonIngest {
+    fn default() -> Self {
+        Self {
+            stop_on_error: false,
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            max_reported_errors: DEFAULT_MAX_REPORTED_ERRORS,
+        }
+    }
+}
+
+impl NdjsonIngest {
+    /// Continue past failed lines, with a 1 MiB line limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop at the first failed line
+    pub fn with_stop_on_error(mut self, stop_on_error: bool) -> Self {
+        self.stop_on_error = stop_on_error;
+        self
+    }
+
+    /// Longest accepted line; longer lines fail without being buffered
+    pub fn with_max_line_bytes(mut self, max_line_bytes: usize) -> Self {
+        self.max_line_bytes = max_line_bytes;
+        self
+    }
+
+    /// Number of failed lines listed in the summary; all are still counted
+    pub fn with_max_reported_errors(mut self, max_reported_errors: usize) -> Self {
+        self.max_reported_errors = max_reported_errors;
+        self
+    }
+
+    /// Deserialize every line of `body` into `T` and pass it to `handler`
+    pub async fn process<T, F, Fut>(&self, body: Body, mut handler: F) -> NdjsonSummary
+    where
+        T: DeserializeOwned,
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<(), AppError>>,
+    {
+        let mut summary = NdjsonSummary::default();
+        let mut stream = body.into_data_stream();
+        let mut buffer = Vec::new();
+        let mut line_number = 0;
+        // Set while skipping the rest of an over-long line
+        let mut discarding = false;
+
+        loop {
+            let chunk = match stream.next().await {
+                Some(Ok(chunk)) => chunk,
+                Some(Err(e)) => {
+                    self.fail(&mut summary, line_number + 1, "read_error", e.to_string());
+                    summary.aborted = true;
+                    return summary;
+                }
+                None => break,
+            };
+
+            let mut rest = &chunk[..];
+            while !rest.is_empty() {
+                let (part, complete) = match rest.iter().position(|byte| *byte == b'\n') {
+                    Some(end) => (&rest[..end], true),
+                    None => (rest, false),
+                };
+                rest = &rest[(part.len() + complete as usize).min(rest.len())..];
+
+                if !discarding {
+                    buffer.extend_from_slice(part);
+                    if buffer.len() > self.max_line_bytes {
+                        buffer.clear();
+                        discarding = true;
+                        let message = format!("Line exceeds {} bytes", self.max_line_bytes);
+                        self.fail(&mut summary, line_number + 1, "line_too_long", message);
+                        if self.stop_on_error {
+                            summary.aborted = true;
+                            return summary;
+                        }
+                    }
+                }
+                if !complete {
+                    continue;
+                }
+
+                line_number += 1;
+                if discarding {
+                    discarding = false;
+                    continue;
+                }
+                let line = std::mem::take(&mut buffer);
+                if !self
+                    .handle_line(&line, line_number, &mut handler, &mut summary)
+                    .await
+                {
+                    summary.aborted = true;
+                    return summary;
+                }
+            }
+        }
+
+        // Last line without a trailing newline
+        if !discarding && !buffer.is_empty() {
+            line_number += 1;
+            if !self
+                .handle_line(&buffer, line_number, &mut handler, &mut summary)
+                .await
+            {
+                summary.aborted = true;
+            }
+        }
+
+        debug!(
+            "NDJSON ingestion finished: {} processed, {} failed",
+            summary.processed, summary.failed
+        );
+        summary
+    }
+
+    /// Handle one complete line; `false` when ingestion should stop
+    async fn handle_line<T, F, Fut>(
+        &self,
+        line: &[u8],
+        line_number: usize,
+        handler: &mut F,
+        summary: &mut NdjsonSummary,
+    ) -> bool
+    where
+        T: DeserializeOwned,
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<(), AppError>>,
+    {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return true;
+        }
+
+        let result = match serde_json::from_slice::<T>(line) {
+            Ok(record) => handler(record)
+                .await
+                .map_err(|e| (e.error_type(), e.detail())),
+            Err(e) => Err(("invalid_json".to_string(), e.to_string())),
+        };
+        match result {
+            Ok(()) => {
+                summary.processed += 1;
+                true
+            }
+            Err((code, message)) => {
+                self.fail(summary, line_number, &code, message);
+                !self.stop_on_error
+            }
+        }
+    }
+
+    fn fail(&self, summary: &mut NdjsonSummary, line: usize, code: &str, message: String) {
+        warn!("NDJSON line {} failed ({}): {}", line, code, message);
+        summary.failed += 1;
+        if summary.errors.len() < self.max_reported_errors {
+            summary.errors.push(LineError {
+                line,
+                code: code.to_string(),
+                message,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use serde::Deserialize;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Deserialize)]
+    struct Pet {
+        name: String,
+        age: u32,
+    }
+
+    /// A body delivered in chunks that split lines at arbitrary points
+    fn chunked(chunks: &[&'static str]) -> Body {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = chunks
+            .iter()
+            .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())))
+            .collect();
+        Body::from_stream(futures::stream::iter(chunks))
+    }
+
+    async fn import(ingest: NdjsonIngest, body: Body) -> (NdjsonSummary, Vec<String>) {
+        let stored = Arc::new(Mutex::new(Vec::new()));
+        let summary = ingest
+            .process(body, |pet: Pet| {
+                let stored = stored.clone();
+                async move {
+                    if pet.age > 30 {
+                        return Err(AppError::validation_error("age is not plausible"));
+                    }
+                    stored.lock().unwrap().push(pet.name);
+                    Ok(())
+                }
+            })
+            .await;
+        let stored = stored.lock().unwrap().clone();
+        (summary, stored)
+    }
+
+    const MIXED: &[&str] = &[
+        "{\"name\":\"Rex\",\"a",
+        "ge\":3}\n{\"name\":\"Tom\"}\n\n",
+        "not json\r\n{\"name\":\"Old\",\"age\":99}\n",
+        "{\"name\":\"Kit\",\"age\":1}",
+    ];
+
+    #[tokio::test]
+    async fn test_mixed_stream_reports_each_failed_line() {
+        let (summary, stored) = import(NdjsonIngest::new(), chunked(MIXED)).await;
+
+        assert_eq!(stored, vec!["Rex", "Kit"]);
+        assert_eq!(summary.processed, 2);
+        assert_eq!(summary.failed, 3);
+        assert!(!summary.aborted);
+        let failed: Vec<(usize, &str)> = summary
+            .errors
+            .iter()
+            .map(|error| (error.line, error.code.as_str()))
+            .collect();
+        assert_eq!(
+            failed,
+            vec![
+                (2, "invalid_json"),
+                (4, "invalid_json"),
+                (5, "validation_error")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_on_error_aborts_at_first_failure() {
+        let ingest = NdjsonIngest::new().with_stop_on_error(true);
+        let (summary, stored) = import(ingest, chunked(MIXED)).await;
+
+        assert_eq!(stored, vec!["Rex"]);
+        assert_eq!(summary.processed, 1);
+        assert_eq!(summary.failed, 1);
+        assert!(summary.aborted);
+    }
+
+    #[tokio::test]
+    async fn test_long_line_is_skipped() {
+        let ingest = NdjsonIngest::new()
+            .with_max_line_bytes(32)
+            .with_max_reported_errors(0);
+        let body = chunked(&[
+            "{\"name\":\"Rexxxxxxxxxx",
+            "xxxxxxxxxxxxxxxx\",\"age\":3}\n{\"name\":\"Tom\",\"age\":2}\n",
+        ]);
+        let (summary, stored) = import(ingest, body).await;
+
+        assert_eq!(stored, vec!["Tom"]);
+        assert_eq!(summary.failed, 1);
+        assert!(summary.errors.is_empty());
+    }
+}
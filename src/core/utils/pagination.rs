@@ -0,0 +1,459 @@
+//! Pagination parameters and RFC 8288 `Link` headers
+//!
+//! [`PageParams`] extracts `page`/`per_page` from the query string. A
+//! handler reports where it is by returning its [`PaginationInfo`] as a
+//! response extension, and the [`pagination_links`] middleware turns that
+//! into a `Link` header with `first`, `prev`, `next` and `last` relations:
+//!
+//! ```ignore
+//! async fn list_pets(page: PageParams) -> impl IntoResponse {
+//!     let (pets, total) = load_pets(page.offset(), page.per_page).await;
+//!     (Extension(page.info(total)), Json(pets))
+//! }
+//!
+//! let app = Router::new()
+//!     .route("/pets", get(list_pets))
+//!     .layer(axum::middleware::from_fn(pagination_links));
+//! ```
+//!
+//! `prev` is omitted on the first page and `next` on the last. Other query
+//! parameters are carried over to every link unchanged.
+//!
+//! Page sizes follow the `api.pagination` limits in the application state.
+//! Routes of a resource with its own [`PageLimits`] add them as a request
+//! extension, taken from `api.pagination` and the resource's
+//! [`ApiResource::page_limits`] by [`resource_page_limits`];
+//! [`api_resource_router`](super::api_resource::api_resource_router) does this
+//! for a resource's routes. A `per_page` above the maximum is rejected or
+//! clamped as the limits say:
+//!
+//! ```ignore
+//! let pets = api_resource_router::<Pet>(
+//!     &config,
+//!     Router::new().route("/pets", get(list_pets)),
+//! );
+//! ```
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{FromRef, FromRequestParts, OriginalUri, Query, Request},
+    http::{HeaderValue, Uri, header::LINK, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+
+use crate::core::config::app_config::{OversizePage, PageLimits, PaginationConfig};
+use crate::core::error::AppError;
+use crate::core::models::PaginationInfo;
+use crate::core::router::AppState;
+use crate::core::utils::api_resource::ApiResource;
+
+/// Page size used when the request does not give one
+pub const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Largest page size a client may request
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// Requested page, from the `page` (1-based) and `per_page` query parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageParams {
+    pub page: u32,
+    pub per_page: u32,
+}
+
+impl PageParams {
+    /// Number of items before this page
+    pub fn offset(&self) -> u64 {
+        u64::from(self.page - 1) * u64::from(self.per_page)
+    }
+
+    /// Pagination metadata for this page of `total_items`
+    pub fn info(&self, total_items: u64) -> PaginationInfo {
+        let total_pages = total_items.div_ceil(u64::from(self.per_page)).max(1);
+        PaginationInfo {
+            page: self.page,
+            per_page: self.per_page,
+            total_items,
+            total_pages: total_pages.min(u64::from(u32::MAX)) as u32,
+        }
+    }
+}
+
+impl Default for PageParams {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            per_page: DEFAULT_PER_PAGE,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawPageParams {
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+/// Limits from the route's resource, falling back to `api.pagination`
+impl<S> FromRequestParts<S> for PageParams
+where
+    S: Send + Sync,
+    Arc<AppState>: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(raw) = Query::<RawPageParams>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+        let limits = match parts.extensions.get::<PageLimits>() {
+            Some(limits) => *limits,
+            None => {
+                Arc::<AppState>::from_ref(state)
+                    .config
+                    .api
+                    .pagination
+                    .limits
+            }
+        };
+
+        let page = raw.page.unwrap_or(1);
+        let mut per_page = raw.per_page.unwrap_or(limits.default_per_page);
+        if page == 0 {
+            return Err(AppError::BadRequest("page must be at least 1".to_string()));
+        }
+        if per_page > limits.max_per_page && limits.oversize == OversizePage::Clamp {
+            per_page = limits.max_per_page;
+        }
+        if per_page == 0 || per_page > limits.max_per_page {
+            return Err(AppError::BadRequest(format!(
+                "per_page must be between 1 and {}",
+                limits.max_per_page
+            )));
+        }
+        Ok(Self { page, per_page })
+    }
+}
+
+/// Page limits for `R`
+///
+/// Its entry in `api.pagination.resources` wins over
+/// [`ApiResource::page_limits`], which wins over the global `api.pagination`
+/// limits.
+pub fn resource_page_limits<R: ApiResource>(config: &PaginationConfig) -> PageLimits {
+    config
+        .resources
+        .get(R::resource_type())
+        .copied()
+        .or_else(R::page_limits)
+        .unwrap_or(config.limits)
+}
+
+/// `Link` header value for `info`, with targets relative to `uri`
+pub fn link_header(uri: &Uri, info: &PaginationInfo) -> String {
+    let last = info.total_pages.max(1);
+    let mut links = vec![(1, "first")];
+    if info.page > 1 {
+        links.push((info.page.min(last + 1) - 1, "prev"));
+    }
+    if info.page < last {
+        links.push((info.page + 1, "next"));
+    }
+    links.push((last, "last"));
+
+    links
+        .into_iter()
+        .map(|(page, rel)| format!("<{}>; rel=\"{}\"", page_uri(uri, page, info.per_page), rel))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `uri` with its `page`/`per_page` query parameters replaced
+fn page_uri(uri: &Uri, page: u32, per_page: u32) -> String {
+    let mut query: Vec<String> = uri
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| {
+            let name = pair.split('=').next().unwrap_or("");
+            !pair.is_empty() && name != "page" && name != "per_page"
+        })
+        .map(str::to_string)
+        .collect();
+    query.push(format!("page={}", page));
+    query.push(format!("per_page={}", per_page));
+    format!("{}?{}", uri.path(), query.join("&"))
+}
+
+/// Middleware adding a `Link` header to responses carrying [`PaginationInfo`]
+pub async fn pagination_links(request: Request, next: Next) -> Response {
+    // Nested routers strip their prefix from the URI, so prefer the original
+    let uri = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|original| original.0.clone())
+        .unwrap_or_else(|| request.uri().clone());
+
+    let mut response = next.run(request).await;
+    let header = response
+        .extensions()
+        .get::<PaginationInfo>()
+        .map(|info| link_header(&uri, info))
+        .and_then(|links| HeaderValue::from_str(&links).ok());
+    if let Some(header) = header {
+        response.headers_mut().insert(LINK, header);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension, Json, Router, body::Body, http::StatusCode, response::IntoResponse, routing::get,
+    };
+    use tower::ServiceExt;
+
+    async fn list_items(page: PageParams) -> impl IntoResponse {
+        let items: Vec<u64> = (page.offset()..45).take(page.per_page as usize).collect();
+        (Extension(page.info(45)), Json(items))
+    }
+
+    async fn get_links(uri: &str) -> (StatusCode, Option<String>) {
+        let app = Router::new()
+            .route("/items", get(list_items))
+            .layer(axum::middleware::from_fn(pagination_links))
+            .with_state(Arc::new(AppState::default()));
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let link = response
+            .headers()
+            .get(LINK)
+            .map(|value| value.to_str().unwrap().to_string());
+        (response.status(), link)
+    }
+
+    #[tokio::test]
+    async fn test_first_page_has_no_prev() {
+        let (status, link) = get_links("/items?per_page=10").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            link.unwrap(),
+            "</items?page=1&per_page=10>; rel=\"first\", \
+             </items?page=2&per_page=10>; rel=\"next\", \
+             </items?page=5&per_page=10>; rel=\"last\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_middle_page_has_prev_and_next() {
+        let (_, link) = get_links("/items?sort=name&page=3&per_page=10").await;
+        let link = link.unwrap();
+
+        assert!(link.contains("</items?sort=name&page=2&per_page=10>; rel=\"prev\""));
+        assert!(link.contains("</items?sort=name&page=4&per_page=10>; rel=\"next\""));
+        assert!(link.contains("</items?sort=name&page=1&per_page=10>; rel=\"first\""));
+        assert!(link.contains("</items?sort=name&page=5&per_page=10>; rel=\"last\""));
+    }
+
+    #[tokio::test]
+    async fn test_last_page_has_no_next() {
+        let (_, link) = get_links("/items?page=5&per_page=10").await;
+        let link = link.unwrap();
+
+        assert!(link.contains("rel=\"prev\""));
+        assert!(!link.contains("rel=\"next\""));
+        assert!(link.contains("</items?page=5&per_page=10>; rel=\"last\""));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_page_is_rejected() {
+        let (status, link) = get_links("/items?page=0").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(link.is_none());
+
+        let (status, _) = get_links("/items?per_page=1000").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[derive(Clone)]
+    struct Pet;
+
+    impl ApiResource for Pet {
+        type Id = u64;
+
+        fn resource_type() -> &'static str {
+            "pets"
+        }
+
+        fn api_name() -> &'static str {
+            "PetService"
+        }
+
+        fn page_limits() -> Option<PageLimits> {
+            Some(PageLimits {
+                default_per_page: 10,
+                max_per_page: 50,
+                oversize: OversizePage::Clamp,
+            })
+        }
+    }
+
+    #[derive(Clone)]
+    struct Order;
+
+    impl ApiResource for Order {
+        type Id = u64;
+
+        fn resource_type() -> &'static str {
+            "orders"
+        }
+
+        fn api_name() -> &'static str {
+            "OrderService"
+        }
+    }
+
+    async fn page_size(uri: &str) -> (StatusCode, Option<u32>) {
+        let config: PaginationConfig = serde_json::from_value(serde_json::json!({
+            "resources": { "orders": { "default_per_page": 5, "max_per_page": 25 } }
+        }))
+        .unwrap();
+        let per_page = get(|page: PageParams| async move { page.per_page.to_string() });
+        let app = Router::new()
+            .route(
+                "/pets",
+                per_page
+                    .clone()
+                    .layer(Extension(resource_page_limits::<Pet>(&config))),
+            )
+            .route(
+                "/orders",
+                per_page.layer(Extension(resource_page_limits::<Order>(&config))),
+            )
+            .with_state(Arc::new(AppState::default()));
+
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8_lossy(&body).parse().ok())
+    }
+
+    #[tokio::test]
+    async fn test_resource_default_page_size() {
+        assert_eq!(page_size("/pets").await, (StatusCode::OK, Some(10)));
+        assert_eq!(page_size("/orders").await, (StatusCode::OK, Some(5)));
+        assert_eq!(
+            page_size("/pets?per_page=30").await,
+            (StatusCode::OK, Some(30))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resource_max_page_size() {
+        // Pets clamp oversized pages to their maximum
+        assert_eq!(
+            page_size("/pets?per_page=80").await,
+            (StatusCode::OK, Some(50))
+        );
+        // Orders are configured with a lower maximum and reject by default
+        assert_eq!(page_size("/orders?per_page=25").await.1, Some(25));
+        assert_eq!(
+            page_size("/orders?per_page=30").await.0,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    async fn status_and_body(app: Router, uri: &str) -> (StatusCode, String) {
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn state_with_limits(default_per_page: u32, max_per_page: u32) -> Arc<AppState> {
+        let mut state = AppState::default();
+        state.config.api.pagination.limits = PageLimits {
+            default_per_page,
+            max_per_page,
+            oversize: OversizePage::Reject,
+        };
+        Arc::new(state)
+    }
+
+    #[tokio::test]
+    async fn test_configured_limits_apply_without_resource_limits() {
+        let app = Router::new()
+            .route(
+                "/items",
+                get(|page: PageParams| async move { page.per_page.to_string() }),
+            )
+            .with_state(state_with_limits(7, 30));
+
+        assert_eq!(
+            status_and_body(app.clone(), "/items").await,
+            (StatusCode::OK, "7".to_string())
+        );
+        assert_eq!(
+            status_and_body(app, "/items?per_page=31").await.0,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resource_router_applies_resource_limits() {
+        let state = state_with_limits(7, 30);
+        let pets = crate::core::utils::api_resource::api_resource_router::<Pet>(
+            &state.config,
+            Router::new().route("/pets", get(list_items)),
+        );
+        let app = Router::new()
+            .merge(pets)
+            .route(
+                "/items",
+                get(|page: PageParams| async move { page.per_page.to_string() }),
+            )
+            .with_state(state);
+
+        // Pets default to 10 per page and get `Link` headers
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/pets").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(
+            response.headers()[LINK]
+                .to_str()
+                .unwrap()
+                .contains("per_page=10")
+        );
+        // Other routes keep the configured limits
+        assert_eq!(status_and_body(app, "/items").await.1, "7");
+    }
+
+    #[test]
+    fn test_single_page() {
+        let info = PageParams::default().info(0);
+        let link = link_header(&"/items".parse().unwrap(), &info);
+
+        assert_eq!(
+            link,
+            "</items?page=1&per_page=20>; rel=\"first\", </items?page=1&per_page=20>; rel=\"last\""
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Canonical request paths
+//!
+//! Clients sending `/api/Pets/` instead of `/api/pets` otherwise get a 404.
+//! [`PathNormalizationLayer`] collapses repeated slashes, strips the trailing
+//! slash and, if configured, lowercases the path (`server.path_normalization`),
+//! then either redirects the client to the canonical URL or rewrites the
+//! request in place.
+//! Redirects use 301 for `GET` and `HEAD` and 308 for every other method, so
+//! clients repeat a `POST` with the same method and body. The query string
+//! is carried over unchanged. Collapsing slashes keeps a path like
+//! `//evil.com/` from becoming a protocol-relative `Location: //evil.com`.
+//!
+//! The layer must see the request before routing, so
+//! [`RouterBuilder::build`](crate::core::router::RouterBuilder::build) wraps
+//! the finished router in it rather than adding it with `Router::layer`.
+//! A rewrite also replaces the `OriginalUri` extension, so authorization rules
+//! matched against it see the canonical path.
+//! Lowercasing applies to path parameters too; leave it off when ids are
+//! case-sensitive.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::{
+    body::Body,
+    extract::OriginalUri,
+    http::{HeaderValue, Method, Request, StatusCode, Uri, header, uri::PathAndQuery},
+    response::Response,
+};
+use futures::future::{BoxFuture, FutureExt};
+use tower::{Layer, Service};
+use tracing::debug;
+
+use crate::core::config::app_config::{PathNormalizationConfig, PathNormalizationMode};
+
+/// The canonical form of `path`, or `None` if it already is canonical
+pub fn canonical_path(path: &str, config: &PathNormalizationConfig) -> Option<String> {
+    let collapsed = collapse_slashes(path);
+    let mut canonical = collapsed.as_str();
+    if config.trim_trailing_slash {
+        canonical = canonical.trim_end_matches('/');
+        if canonical.is_empty() {
+            canonical = "/";
+        }
+    }
+    let canonical = if config.lowercase {
+        canonical.to_lowercase()
+    } else {
+        canonical.to_string()
+    };
+    (canonical != path).then_some(canonical)
+}
+
+/// `path` with every run of slashes replaced by one
+fn collapse_slashes(path: &str) -> String {
+    let mut collapsed = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c != '/' || !collapsed.ends_with('/') {
+            collapsed.push(c);
+        }
+    }
+    collapsed
+}
+
+/// Layer redirecting or rewriting requests to their canonical path
+#[derive(Debug, Clone)]
+pub struct PathNormalizationLayer {
+    config: Arc<PathNormalizationConfig>,
+}
+
+impl PathNormalizationLayer {
+    /// Layer for `config`; `None` when normalization is off
+    pub fn from_config(config: &PathNormalizationConfig) -> Option<Self> {
+        (config.mode != PathNormalizationMode::Off).then(|| Self {
+            config: Arc::new(config.clone()),
+        })
+    }
+}
+
+impl<S> Layer<S> for PathNormalizationLayer {
+    type Service = PathNormalization<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PathNormalization {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Service produced by [`PathNormalizationLayer`]
+#[derive(Debug, Clone)]
+pub struct PathNormalization<S> {
+    inner: S,
+    config: Arc<PathNormalizationConfig>,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for PathNormalization<S>
+where
+    S: Service<Request<ReqBody>, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let Some(path) = canonical_path(req.uri().path(), &self.config) else {
+            return self.inner.call(req).boxed();
+        };
+        let path_and_query = match req.uri().query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path,
+        };
+
+        match self.config.mode {
+            PathNormalizationMode::Redirect => {
+                let status = if matches!(*req.method(), Method::GET | Method::HEAD) {
+                    StatusCode::MOVED_PERMANENTLY
+                } else {
+                    StatusCode::PERMANENT_REDIRECT
+                };
+                debug!("Redirecting {} to {}", req.uri(), path_and_query);
+                let response = match HeaderValue::try_from(path_and_query) {
+                    Ok(location) => Response::builder()
+                        .status(status)
+                        .header(header::LOCATION, location)
+                        .body(Body::empty())
+                        .unwrap_or_default(),
+                    Err(_) => return self.inner.call(req).boxed(),
+                };
+                futures::future::ready(Ok(response)).boxed()
+            }
+            PathNormalizationMode::Rewrite | PathNormalizationMode::Off => {
+                let mut parts = req.uri().clone().into_parts();
+                parts.path_and_query = PathAndQuery::try_from(path_and_query).ok();
+                if let Ok(uri) = Uri::from_parts(parts) {
+                    // The outer router already recorded the raw URI; anything
+                    // matching on `OriginalUri` must see the routed path
+                    req.extensions_mut().insert(OriginalUri(uri.clone()));
+                    *req.uri_mut() = uri;
+                }
+                self.inner.call(req).boxed()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::get};
+    use tower::ServiceExt;
+
+    fn config(mode: PathNormalizationMode, lowercase: bool) -> PathNormalizationConfig {
+        PathNormalizationConfig {
+            mode,
+            lowercase,
+            ..Default::default()
+        }
+    }
+
+    /// Routes behind the layer, as `RouterBuilder::build` wires them
+    fn app(config: PathNormalizationConfig) -> Router {
+        let routes = Router::new().route(
+            "/api/pets",
+            get(|| async { "pets" }).post(|body: String| async move { body }),
+        );
+        let layer = PathNormalizationLayer::from_config(&config).unwrap();
+        Router::new().fallback_service(layer.layer(routes))
+    }
+
+    async fn send(app: Router, method: &str, uri: &str, body: &str) -> Response {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    async fn text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_path() {
+        let trim = config(PathNormalizationMode::Rewrite, false);
+        assert_eq!(
+            canonical_path("/api/pets/", &trim).as_deref(),
+            Some("/api/pets")
+        );
+        assert_eq!(canonical_path("/api/pets", &trim), None);
+        assert_eq!(canonical_path("/", &trim), None);
+        assert_eq!(canonical_path("/api/Pets", &trim), None);
+
+        assert_eq!(
+            canonical_path("//api//pets", &trim).as_deref(),
+            Some("/api/pets")
+        );
+
+        let lower = config(PathNormalizationMode::Rewrite, true);
+        assert_eq!(
+            canonical_path("/api/Pets/", &lower).as_deref(),
+            Some("/api/pets")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_is_redirected_with_query() {
+        let app = app(config(PathNormalizationMode::Redirect, true));
+
+        let response = send(app, "GET", "/api/Pets/?limit=10&Sort=Name", "").await;
+
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "/api/pets?limit=10&Sort=Name"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_leading_slashes_never_redirect_off_site() {
+        let app = app(config(PathNormalizationMode::Redirect, false));
+
+        for uri in ["//evil.com/", "///evil.com", "//evil.com/?next=1"] {
+            let response = send(app.clone(), "GET", uri, "").await;
+            let location = response.headers()[header::LOCATION].to_str().unwrap();
+            assert!(location.starts_with("/evil.com"), "{} -> {}", uri, location);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_redirect_preserves_method_and_body() {
+        let app = app(config(PathNormalizationMode::Redirect, false));
+
+        let response = send(app.clone(), "POST", "/api/pets/", "{\"name\":\"Rex\"}").await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        let location = response.headers()[header::LOCATION].to_str().unwrap();
+        assert_eq!(location, "/api/pets");
+
+        // Following the 308 repeats the same method and body
+        let response = send(app, "POST", location, "{\"name\":\"Rex\"}").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(text(response).await, "{\"name\":\"Rex\"}");
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_routes_canonical_path() {
+        let app = app(config(PathNormalizationMode::Rewrite, true));
+
+        let response = send(app.clone(), "GET", "/API/Pets/", "").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(text(response).await, "pets");
+
+        let response = send(app, "POST", "/api/pets/", "body").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(text(response).await, "body");
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_replaces_original_uri() {
+        let routes = Router::new().route(
+            "/api/pets",
+            get(|OriginalUri(uri): OriginalUri| async move { uri.to_string() }),
+        );
+        let layer =
+            PathNormalizationLayer::from_config(&config(PathNormalizationMode::Rewrite, true))
+                .unwrap();
+        let app = Router::new().fallback_service(layer.layer(routes));
+
+        let response = send(app, "GET", "/API/Pets/?Sort=Name", "").await;
+        assert_eq!(text(response).await, "/api/pets?Sort=Name");
+    }
+
+    #[test]
+    fn test_off_builds_no_layer() {
+        assert!(PathNormalizationLayer::from_config(&PathNormalizationConfig::default()).is_none());
+    }
+}
@@ -0,0 +1,273 @@
+//! Conditional requests for mutations (RFC 9110 §13)
+//!
+//! A client that read a resource sends its `ETag` back in `If-Match`, or its
+//! `Last-Modified` in `If-Unmodified-Since`, with the `PUT`, `PATCH` or
+//! `DELETE` that changes it. If the resource changed in the meantime the
+//! write is refused with `412 Precondition Failed` instead of silently
+//! overwriting someone else's update.
+//!
+//! Versioned entities (see [`Entity::version`]) get a strong ETag from
+//! [`entity_tag`]. Handlers extract [`Preconditions`] and check them against
+//! the current resource before writing:
+//!
+//! ```ignore
+//! async fn update_pet(
+//!     Path(id): Path<String>,
+//!     preconditions: Preconditions,
+//!     Json(pet): Json<Pet>,
+//! ) -> Result<Json<Pet>> {
+//!     let current = repository.find_by_id(&id).await?.ok_or_else(not_found)?;
+//!     preconditions.check_entity(&current)?;
+//!     Ok(Json(repository.save(&pet).await?))
+//! }
+//! ```
+//!
+//! Add the [`require_preconditions`] middleware to routes where unconditional
+//! writes are not allowed; it answers `428 Precondition Required` when a
+//! mutation carries neither header.
+
+use std::convert::Infallible;
+use std::time::SystemTime;
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{HeaderMap, Method, header, request::Parts},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+
+use crate::core::error::AppError;
+use crate::core::models::Entity;
+
+/// Strong ETag for a version, e.g. `"3"`
+pub fn version_tag(version: u64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// ETag of a versioned entity; `None` if the entity is not versioned
+pub fn entity_tag<E: Entity>(entity: &E) -> Option<String> {
+    entity.version().map(version_tag)
+}
+
+/// `If-Match` header value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    /// `*`: the resource must exist
+    Any,
+    /// Entity tags, as sent
+    Tags(Vec<String>),
+}
+
+/// Preconditions sent with a request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preconditions {
+    pub if_match: Option<IfMatch>,
+    pub if_unmodified_since: Option<SystemTime>,
+}
+
+impl Preconditions {
+    /// Preconditions from request headers; an unparsable date is ignored
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let if_match = headers
+            .get(header::IF_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| match value.trim() {
+                "*" => IfMatch::Any,
+                tags => IfMatch::Tags(
+                    tags.split(',')
+                        .map(|tag| tag.trim().to_string())
+                        .filter(|tag| !tag.is_empty())
+                        .collect(),
+                ),
+            });
+        let if_unmodified_since = headers
+            .get(header::IF_UNMODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value.trim()).ok())
+            .map(|date| date.with_timezone(&Utc).into());
+
+        Self {
+            if_match,
+            if_unmodified_since,
+        }
+    }
+
+    /// Whether the request carries any precondition
+    pub fn is_empty(&self) -> bool {
+        self.if_match.is_none() && self.if_unmodified_since.is_none()
+    }
+
+    /// Check the preconditions against the current state of the resource
+    ///
+    /// `etag` and `last_modified` describe the resource as stored, `None`
+    /// when unknown. `If-Match` uses strong comparison, so weak tags never
+    /// match; `If-Unmodified-Since` is only evaluated without `If-Match`.
+    pub fn check(
+        &self,
+        etag: Option<&str>,
+        last_modified: Option<SystemTime>,
+    ) -> Result<(), AppError> {
+        match &self.if_match {
+            Some(IfMatch::Any) => return Ok(()),
+            Some(IfMatch::Tags(tags)) => {
+                let matches = etag.is_some_and(|etag| {
+                    !etag.starts_with("W/") && tags.iter().any(|tag| tag == etag)
+                });
+                return if matches {
+                    Ok(())
+                } else {
+                    Err(AppError::PreconditionFailed(
+                        "The resource has changed (If-Match)".to_string(),
+                    ))
+                };
+            }
+            None => {}
+        }
+
+        match (self.if_unmodified_since, last_modified) {
+            (Some(since), Some(modified)) if whole_seconds(modified) > whole_seconds(since) => {
+                Err(AppError::PreconditionFailed(
+                    "The resource has changed (If-Unmodified-Since)".to_string(),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the preconditions against a versioned entity
+    pub fn check_entity<E: Entity>(&self, entity: &E) -> Result<(), AppError> {
+        self.check(entity_tag(entity).as_deref(), None)
+    }
+}
+
+/// HTTP dates have one-second resolution
+fn whole_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Preconditions {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+/// Middleware rejecting `PUT`, `PATCH` and `DELETE` requests without preconditions
+pub async fn require_preconditions(request: Request, next: Next) -> Response {
+    let is_mutation = matches!(
+        *request.method(),
+        Method::PUT | Method::PATCH | Method::DELETE
+    );
+    if is_mutation && Preconditions::from_headers(request.headers()).is_empty() {
+        return AppError::PreconditionRequired(
+            "Send If-Match or If-Unmodified-Since with this request".to_string(),
+        )
+        .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::entity::test_utils::{Pet, pet};
+    use axum::{
+        Json, Router,
+        body::Body,
+        extract::State,
+        http::{HeaderValue, StatusCode},
+        routing::put,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    type Store = Arc<Mutex<Pet>>;
+
+    async fn update_pet(
+        State(store): State<Store>,
+        preconditions: Preconditions,
+        Json(name): Json<String>,
+    ) -> Result<Json<Pet>, AppError> {
+        let mut pet = store.lock().unwrap();
+        preconditions.check_entity(&*pet)?;
+        pet.name = name;
+        pet.version = pet.version.map(|version| version + 1);
+        Ok(Json(pet.clone()))
+    }
+
+    fn app() -> Router {
+        let store = Arc::new(Mutex::new(Pet {
+            version: Some(3),
+            ..pet("1", "Rex")
+        }));
+        Router::new()
+            .route("/pets/1", put(update_pet))
+            .layer(axum::middleware::from_fn(require_preconditions))
+            .with_state(store)
+    }
+
+    async fn rename(app: Router, headers: &[(header::HeaderName, &str)]) -> StatusCode {
+        let mut request = Request::builder()
+            .method("PUT")
+            .uri("/pets/1")
+            .header(header::CONTENT_TYPE, "application/json");
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        let request = request.body(Body::from("\"Max\"")).unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_matching_if_match_allows_update() {
+        let status = rename(app(), &[(header::IF_MATCH, "\"3\"")]).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let status = rename(app(), &[(header::IF_MATCH, "\"2\", \"3\"")]).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_stale_if_match_is_precondition_failed() {
+        let status = rename(app(), &[(header::IF_MATCH, "\"2\"")]).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+
+        // Weak tags never match a strong comparison
+        let status = rename(app(), &[(header::IF_MATCH, "W/\"3\"")]).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn test_missing_precondition_is_required() {
+        let status = rename(app(), &[]).await;
+        assert_eq!(status, StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    #[test]
+    fn test_if_unmodified_since() {
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_UNMODIFIED_SINCE,
+            HeaderValue::from_static("Tue, 14 Nov 2023 22:13:20 GMT"),
+        );
+        let preconditions = Preconditions::from_headers(&headers);
+        assert_eq!(preconditions.if_unmodified_since, Some(modified));
+
+        assert!(preconditions.check(None, Some(modified)).is_ok());
+        assert!(
+            preconditions
+                .check(None, Some(modified + Duration::from_millis(500)))
+                .is_ok()
+        );
+        assert!(matches!(
+            preconditions.check(None, Some(modified + Duration::from_secs(1))),
+            Err(AppError::PreconditionFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,696 @@
+//! Typed, validated query-string extraction
+//!
+//! [`ValidatedQuery`] deserializes the query string into a struct and runs its
+//! `validator` rules, so defaults, ranges and required parameters are declared
+//! on the struct instead of being parsed ad hoc in each handler:
+//!
+//! ```ignore
+//! #[derive(Deserialize, Validate)]
+//! struct ListParams {
+//!     #[serde(default = "default_limit")]
+//!     #[validate(range(min = 1, max = 100))]
+//!     limit: u32,
+//!     #[validate(required)]
+//!     q: Option<String>,
+//! }
+//!
+//! async fn list(ValidatedQuery(params): ValidatedQuery<ListParams>) -> ... {}
+//! ```
+//!
+//! Invalid parameters are reported in a 400 response: every parameter that
+//! fails validation together, but only the first one that fails to
+//! deserialize, as deserialization stops there.
+//!
+//! `Vec<T>` fields accept repeated keys (`?tag=a&tag=b`), comma-separated
+//! lists (`?tag=a,b`) or both, as chosen by the [`ArrayStyle`] request
+//! extension (default [`ArrayStyle::Both`]); mark them `#[serde(default)]` to
+//! allow the parameter to be omitted. A bad element is reported by parameter
+//! and 0-based index, e.g. `ids[1]`:
+//!
+//! ```ignore
+//! let app = Router::new()
+//!     .route("/items", get(list))
+//!     .layer(Extension(ArrayStyle::Repeated));
+//! ```
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{FromRequestParts, Query},
+    http::{StatusCode, Uri, request::Parts},
+    response::{IntoResponse, Response},
+};
+use serde::de::{
+    self, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned, forward_to_deserialize_any};
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::core::error::ErrorResponse;
+
+/// Query parameters deserialized into `T` and validated
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedQuery<T>(pub T);
+
+/// How `Vec<T>` query parameters may be written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrayStyle {
+    /// `?tag=a&tag=b`
+    Repeated,
+    /// `?tag=a,b`; repeating the key is rejected
+    CommaSeparated,
+    /// Repeated keys whose values may also be comma-separated
+    #[default]
+    Both,
+}
+
+/// A single invalid query parameter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidParam {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Rejection listing every invalid query parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidQuery {
+    pub invalid_params: Vec<InvalidParam>,
+}
+
+/// Body of an [`InvalidQuery`] response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvalidQueryResponse {
+    #[serde(flatten)]
+    pub error: ErrorResponse,
+    pub invalid_params: Vec<InvalidParam>,
+}
+
+impl From<QueryError> for InvalidQuery {
+    fn from(error: QueryError) -> Self {
+        Self {
+            invalid_params: vec![InvalidParam {
+                name: error.name.unwrap_or_else(|| "query".to_string()),
+                reason: error.reason,
+            }],
+        }
+    }
+}
+
+impl InvalidQuery {
+    fn from_validation(errors: ValidationErrors) -> Self {
+        let mut invalid_params: Vec<InvalidParam> = errors
+            .field_errors()
+            .into_iter()
+            .flat_map(|(name, errors)| {
+                errors.iter().map(move |error| InvalidParam {
+                    name: name.to_string(),
+                    reason: describe(error),
+                })
+            })
+            .collect();
+        invalid_params.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self { invalid_params }
+    }
+}
+
+/// Human readable reason for a validation failure
+fn describe(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |key: &str| error.params.get(key).map(|value| value.to_string());
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("required", _, _) => "is required".to_string(),
+        ("range", Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+        ("range", Some(min), None) => format!("must be at least {}", min),
+        ("range", None, Some(max)) => format!("must be at most {}", max),
+        ("length", Some(min), Some(max)) => {
+            format!("length must be between {} and {}", min, max)
+        }
+        ("length", Some(min), None) => format!("length must be at least {}", min),
+        ("length", None, Some(max)) => format!("length must be at most {}", max),
+        (code, _, _) => format!("failed '{}' validation", code),
+    }
+}
+
+impl IntoResponse for InvalidQuery {
+    fn into_response(self) -> Response {
+        let names: Vec<&str> = self
+            .invalid_params
+            .iter()
+            .map(|param| param.name.as_str())
+            .collect();
+        let mut error = ErrorResponse::new(
+            "validation_error",
+            format!("Invalid query parameters: {}", names.join(", ")),
+        );
+        error.code = StatusCode::BAD_REQUEST.as_u16();
+
+        (
+            StatusCode::BAD_REQUEST,
+            Json(InvalidQueryResponse {
+                error,
+                invalid_params: self.invalid_params,
+            }),
+        )
+            .into_response()
+    }
+}
+
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = InvalidQuery;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let style = parts
+            .extensions
+            .get::<ArrayStyle>()
+            .copied()
+            .unwrap_or_default();
+        let value: T = deserialize_query(&parts.uri, style)?;
+        value.validate().map_err(InvalidQuery::from_validation)?;
+
+        Ok(ValidatedQuery(value))
+    }
+}
+
+/// Deserialize the query string of `uri` into `T`
+fn deserialize_query<T: DeserializeOwned>(uri: &Uri, style: ArrayStyle) -> Result<T, InvalidQuery> {
+    let Query(pairs) = Query::<Vec<(String, String)>>::try_from_uri(uri).map_err(|rejection| {
+        InvalidQuery::from(QueryError {
+            name: None,
+            reason: rejection.body_text(),
+        })
+    })?;
+
+    // Group repeated keys, keeping the order in which keys first appear
+    let mut params: Vec<(String, Vec<String>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (key, value) in pairs {
+        match positions.get(&key) {
+            Some(&position) => params[position].1.push(value),
+            None => {
+                positions.insert(key.clone(), params.len());
+                params.push((key, vec![value]));
+            }
+        }
+    }
+
+    Ok(T::deserialize(QueryDeserializer { params, style })?)
+}
+
+/// Failure to deserialize a query parameter
+#[derive(Debug, thiserror::Error)]
+#[error("{reason}")]
+struct QueryError {
+    name: Option<String>,
+    reason: String,
+}
+
+impl QueryError {
+    /// Attribute the error to `name` unless a more specific name is set
+    fn in_param(mut self, name: &str) -> Self {
+        self.name.get_or_insert_with(|| name.to_string());
+        self
+    }
+}
+
+impl de::Error for QueryError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self {
+            name: None,
+            reason: msg.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self {
+            name: Some(field.to_string()),
+            reason: "is required".to_string(),
+        }
+    }
+}
+
+/// Deserializes the grouped query parameters as a map
+struct QueryDeserializer {
+    params: Vec<(String, Vec<String>)>,
+    style: ArrayStyle,
+}
+
+impl<'de> Deserializer<'de> for QueryDeserializer {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_map(QueryMap {
+            params: self.params.into_iter(),
+            value: None,
+            style: self.style,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct QueryMap {
+    params: std::vec::IntoIter<(String, Vec<String>)>,
+    value: Option<(String, Vec<String>)>,
+    style: ArrayStyle,
+}
+
+impl<'de> MapAccess<'de> for QueryMap {
+    type Error = QueryError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, QueryError> {
+        let Some((name, values)) = self.params.next() else {
+            return Ok(None);
+        };
+        let key = seed.deserialize(de::value::StringDeserializer::<QueryError>::new(
+            name.clone(),
+        ))?;
+        self.value = Some((name, values));
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, QueryError> {
+        let (name, values) = self
+            .value
+            .take()
+            .ok_or_else(|| <QueryError as de::Error>::custom("value requested before key"))?;
+        seed.deserialize(ValueDeserializer {
+            name: &name,
+            values,
+            style: self.style,
+        })
+        .map_err(|e| e.in_param(&name))
+    }
+}
+
+/// Every value given for one parameter
+struct ValueDeserializer<'a> {
+    name: &'a str,
+    values: Vec<String>,
+    style: ArrayStyle,
+}
+
+impl ValueDeserializer<'_> {
+    fn into_scalar(mut self) -> Result<ScalarDeserializer, QueryError> {
+        match self.values.pop() {
+            Some(value) if self.values.is_empty() => Ok(ScalarDeserializer(value)),
+            _ => Err(de::Error::custom("must not be repeated")),
+        }
+    }
+
+    fn into_items(self) -> Result<Vec<String>, QueryError> {
+        let values = match self.style {
+            ArrayStyle::Repeated => self.values,
+            ArrayStyle::CommaSeparated if self.values.len() > 1 => {
+                return Err(de::Error::custom(
+                    "must not be repeated; separate values with commas",
+                ));
+            }
+            ArrayStyle::CommaSeparated | ArrayStyle::Both => self
+                .values
+                .iter()
+                .flat_map(|value| value.split(','))
+                .map(str::to_string)
+                .collect(),
+        };
+        Ok(values
+            .into_iter()
+            .filter(|value| !value.is_empty())
+            .collect())
+    }
+}
+
+macro_rules! forward_to_scalar {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+                self.into_scalar()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        if self.values.len() > 1 {
+            self.deserialize_seq(visitor)
+        } else {
+            self.into_scalar()?.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        let name = self.name;
+        visitor.visit_seq(Elements {
+            name,
+            items: self.into_items()?.into_iter().enumerate(),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        self.into_scalar()?
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_scalar! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32
+        deserialize_f64 deserialize_char deserialize_str deserialize_string deserialize_unit
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 bytes byte_buf unit_struct tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Elements of a list parameter, named `name[index]` in errors
+struct Elements<'a> {
+    name: &'a str,
+    items: std::iter::Enumerate<std::vec::IntoIter<String>>,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_> {
+    type Error = QueryError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, QueryError> {
+        let Some((index, item)) = self.items.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(ScalarDeserializer(item))
+            .map(Some)
+            .map_err(|e| e.in_param(&format!("{}[{}]", self.name, index)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+macro_rules! parse_scalar {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(e) => Err(de::Error::custom(e)),
+                }
+            }
+        )*
+    };
+}
+
+/// A single value, parsed according to the requested type
+struct ScalarDeserializer(String);
+
+impl<'de> Deserializer<'de> for ScalarDeserializer {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    parse_scalar! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Extension, Router, body::Body, http::Request, routing::get};
+    use tower::ServiceExt;
+
+    fn default_limit() -> u32 {
+        20
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Validate)]
+    struct ListParams {
+        #[serde(default = "default_limit")]
+        #[validate(range(min = 1, max = 100))]
+        limit: u32,
+        #[serde(default)]
+        offset: u64,
+        #[validate(required)]
+        q: Option<String>,
+        sort: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Validate)]
+    struct FilterParams {
+        #[serde(default)]
+        tag: Vec<String>,
+        #[serde(default)]
+        #[validate(length(max = 3))]
+        ids: Vec<u32>,
+    }
+
+    async fn get_query(query: &str) -> (StatusCode, serde_json::Value) {
+        let app = Router::new().route(
+            "/items",
+            get(|ValidatedQuery(params): ValidatedQuery<ListParams>| async move { Json(params) }),
+        );
+        send(app, query).await
+    }
+
+    async fn get_filters(style: ArrayStyle, query: &str) -> (StatusCode, serde_json::Value) {
+        let app =
+            Router::new()
+                .route(
+                    "/items",
+                    get(
+                        |ValidatedQuery(params): ValidatedQuery<FilterParams>| async move {
+                            Json(params)
+                        },
+                    ),
+                )
+                .layer(Extension(style));
+        send(app, query).await
+    }
+
+    async fn send(app: Router, query: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/items?{}", query))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn invalid_names(body: &serde_json::Value) -> Vec<&str> {
+        body["invalid_params"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|param| param["name"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_defaults_applied_when_absent() {
+        let (status, body) = get_query("q=shoes").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["limit"], 20);
+        assert_eq!(body["offset"], 0);
+        assert!(body["sort"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_range_violation_names_param() {
+        let (status, body) = get_query("q=shoes&limit=500").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error_type"], "validation_error");
+        assert_eq!(invalid_names(&body), vec!["limit"]);
+        assert_eq!(
+            body["invalid_params"][0]["reason"],
+            "must be between 1 and 100"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_all_invalid_params_reported_together() {
+        let (status, body) = get_query("limit=0").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(invalid_names(&body), vec!["limit", "q"]);
+    }
+
+    #[tokio::test]
+    async fn test_unparseable_value_names_param() {
+        let (status, body) = get_query("q=shoes&limit=lots").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(invalid_names(&body), vec!["limit"]);
+    }
+
+    #[tokio::test]
+    async fn test_parses_all_params() {
+        let (status, body) = get_query("q=shoes&limit=50&offset=100&sort=price").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["q"], "shoes");
+        assert_eq!(body["limit"], 50);
+        assert_eq!(body["offset"], 100);
+        assert_eq!(body["sort"], "price");
+    }
+
+    #[tokio::test]
+    async fn test_repeated_keys_collect_into_vec() {
+        let (status, body) = get_filters(ArrayStyle::Repeated, "tag=a%2Cb&tag=c&ids=1&ids=2").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tag"], serde_json::json!(["a,b", "c"]));
+        assert_eq!(body["ids"], serde_json::json!([1, 2]));
+    }
+
+    #[tokio::test]
+    async fn test_comma_separated_values_collect_into_vec() {
+        let (status, body) = get_filters(ArrayStyle::CommaSeparated, "tag=a,b&ids=3,4,5").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tag"], serde_json::json!(["a", "b"]));
+        assert_eq!(body["ids"], serde_json::json!([3, 4, 5]));
+
+        let (status, body) = get_filters(ArrayStyle::CommaSeparated, "tag=a&tag=b").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(invalid_names(&body), vec!["tag"]);
+    }
+
+    #[tokio::test]
+    async fn test_both_styles_combine_and_absent_list_is_empty() {
+        let (status, body) = get_filters(ArrayStyle::Both, "ids=1,2&ids=3").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["ids"], serde_json::json!([1, 2, 3]));
+        assert_eq!(body["tag"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_bad_element_names_param_and_index() {
+        let (status, body) = get_filters(ArrayStyle::Both, "ids=1,two,3").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(invalid_names(&body), vec!["ids[1]"]);
+        assert_eq!(
+            body["invalid_params"][0]["reason"],
+            "invalid digit found in string"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_validation_applies_to_whole_vec() {
+        let (status, body) = get_filters(ArrayStyle::Both, "ids=1,2,3,4").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(invalid_names(&body), vec!["ids"]);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_scalar_is_rejected() {
+        let (status, body) = get_query("q=shoes&limit=10&limit=20").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(invalid_names(&body), vec!["limit"]);
+    }
+}
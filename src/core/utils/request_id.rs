@@ -1,5 +1,5 @@
-use uuid::Uuid;
+use crate::core::error::generate_request_id;
 
 pub fn get_req_id() -> String {
-    Uuid::new_v4().to_string()
+    generate_request_id()
 }
@@ -0,0 +1,230 @@
+//! Required request headers
+//!
+//! [`RequireHeadersLayer`] rejects requests missing a required header, or
+//! carrying a value outside its allowed set, with a 400 naming every
+//! offending header before the handler runs:
+//!
+//! ```ignore
+//! let tenant_routes = Router::new()
+//!     .route("/pets", get(list_pets))
+//!     .layer(
+//!         RequireHeadersLayer::new()
+//!             .require("x-tenant-id")?
+//!             .require_one_of("x-api-version", ["1", "2"])?,
+//!     );
+//! ```
+//!
+//! Header names are case-insensitive, so names read from configuration may
+//! use any case; a name that isn't a valid header is a configuration error.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::{
+    Json,
+    http::{HeaderMap, HeaderName, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+
+use crate::core::error::{AppError, ErrorResponse};
+use crate::core::utils::query::InvalidParam;
+
+/// A required header and, optionally, the values it may take
+#[derive(Debug, Clone)]
+struct RequiredHeader {
+    name: HeaderName,
+    allowed: Option<Vec<String>>,
+}
+
+/// Body of a rejected request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvalidHeadersResponse {
+    #[serde(flatten)]
+    pub error: ErrorResponse,
+    pub invalid_headers: Vec<InvalidParam>,
+}
+
+/// Layer rejecting requests without the configured headers
+#[derive(Debug, Clone, Default)]
+pub struct RequireHeadersLayer {
+    headers: Arc<Vec<RequiredHeader>>,
+}
+
+impl RequireHeadersLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require `name` to be present with any value
+    pub fn require(self, name: &str) -> Result<Self, AppError> {
+        self.push(name, None)
+    }
+
+    /// Require `name` to be present with one of `allowed`
+    pub fn require_one_of<I, V>(self, name: &str, allowed: I) -> Result<Self, AppError>
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<String>,
+    {
+        self.push(name, Some(allowed.into_iter().map(Into::into).collect()))
+    }
+
+    fn push(mut self, name: &str, allowed: Option<Vec<String>>) -> Result<Self, AppError> {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+            AppError::ConfigurationError(format!("Invalid required header name '{}'", name))
+        })?;
+        Arc::make_mut(&mut self.headers).push(RequiredHeader { name, allowed });
+        Ok(self)
+    }
+
+    /// Every missing or invalid header in `headers`
+    fn check(&self, headers: &HeaderMap) -> Vec<InvalidParam> {
+        self.headers
+            .iter()
+            .filter_map(|required| {
+                let name = required.name.to_string();
+                let Some(value) = headers.get(&required.name) else {
+                    return Some(InvalidParam {
+                        name,
+                        reason: "is required".to_string(),
+                    });
+                };
+                let allowed = required.allowed.as_ref()?;
+                let valid = value
+                    .to_str()
+                    .is_ok_and(|value| allowed.iter().any(|allowed| allowed == value));
+                (!valid).then(|| InvalidParam {
+                    name,
+                    reason: format!("must be one of: {}", allowed.join(", ")),
+                })
+            })
+            .collect()
+    }
+}
+
+impl<S> Layer<S> for RequireHeadersLayer {
+    type Service = RequireHeaders<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequireHeaders {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service produced by [`RequireHeadersLayer`]
+#[derive(Debug, Clone)]
+pub struct RequireHeaders<S> {
+    inner: S,
+    layer: RequireHeadersLayer,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for RequireHeaders<S>
+where
+    S: Service<Request<ReqBody>, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let invalid_headers = self.layer.check(req.headers());
+        if invalid_headers.is_empty() {
+            return self.inner.call(req).boxed();
+        }
+
+        let names: Vec<&str> = invalid_headers.iter().map(|h| h.name.as_str()).collect();
+        let mut error = ErrorResponse::new(
+            "validation_error",
+            format!("Missing or invalid headers: {}", names.join(", ")),
+        );
+        error.code = StatusCode::BAD_REQUEST.as_u16();
+        let response = (
+            StatusCode::BAD_REQUEST,
+            Json(InvalidHeadersResponse {
+                error,
+                invalid_headers,
+            }),
+        )
+            .into_response();
+
+        futures::future::ready(Ok(response)).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    async fn send(headers: &[(&str, &str)]) -> (StatusCode, serde_json::Value) {
+        let app = Router::new().route("/pets", get(|| async { "ok" })).layer(
+            RequireHeadersLayer::new()
+                .require("X-Tenant-ID")
+                .unwrap()
+                .require_one_of("x-api-version", ["1", "2"])
+                .unwrap(),
+        );
+
+        let mut request = Request::builder().uri("/pets");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_valid_headers_reach_handler() {
+        let (status, _) = send(&[("X-Tenant-ID", "acme"), ("X-Api-Version", "2")]).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_missing_header_is_named() {
+        let (status, body) = send(&[("x-api-version", "1")]).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["invalid_headers"][0]["name"], "x-tenant-id");
+        assert_eq!(body["invalid_headers"][0]["reason"], "is required");
+        assert!(body["message"].as_str().unwrap().contains("x-tenant-id"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_value_is_rejected() {
+        let (status, body) = send(&[("x-tenant-id", "acme"), ("x-api-version", "3")]).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["invalid_headers"].as_array().unwrap().len(), 1);
+        assert_eq!(body["invalid_headers"][0]["name"], "x-api-version");
+        assert_eq!(body["invalid_headers"][0]["reason"], "must be one of: 1, 2");
+    }
+
+    #[test]
+    fn test_invalid_header_name_is_configuration_error() {
+        let result = RequireHeadersLayer::new().require("x tenant id");
+
+        assert!(matches!(result, Err(AppError::ConfigurationError(_))));
+    }
+}
@@ -0,0 +1,186 @@
+//! Injectable sources of randomness and time
+//!
+//! Request ids, retry jitter, chaos fault rolls and trace sampling draw from
+//! the process-wide [`RandomSource`], and outbox and idempotency timestamps
+//! from the process-wide [`TimeSource`]. Both default to the real sources.
+//!
+//! For reproducible tests, pass a [`SeededRandom`] to the `*_with` variants
+//! (e.g. `generate_request_id_with`, `backoff_delay_with`), or install one
+//! for the whole process with [`seed`] / [`set_random_source`] and a
+//! [`ManualClock`] with [`set_time_source`]. The process-wide sources are
+//! shared by every test in the binary, so prefer the explicit variants in
+//! unit tests.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Source of random numbers
+pub trait RandomSource: Send + Sync + fmt::Debug {
+    /// Next random `u64`
+    fn next_u64(&self) -> u64;
+
+    /// Next random `f64`, uniform in `[0, 1)`
+    fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Fill `dest` with random bytes
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// The thread-local system RNG; the default source
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadRandom;
+
+impl RandomSource for ThreadRandom {
+    fn next_u64(&self) -> u64 {
+        rand::random()
+    }
+}
+
+/// Deterministic RNG producing the same sequence for the same seed
+#[derive(Debug)]
+pub struct SeededRandom(Mutex<ChaCha8Rng>);
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        Self(Mutex::new(ChaCha8Rng::seed_from_u64(seed)))
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn next_u64(&self) -> u64 {
+        self.0.lock().unwrap().next_u64()
+    }
+}
+
+/// Source of the current time
+pub trait TimeSource: Send + Sync + fmt::Debug {
+    fn now(&self) -> SystemTime;
+}
+
+/// The system clock; the default source
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Clock that only moves when told to
+#[derive(Debug)]
+pub struct ManualClock(Mutex<SystemTime>);
+
+impl ManualClock {
+    pub fn new(start: SystemTime) -> Self {
+        Self(Mutex::new(start))
+    }
+
+    pub fn set(&self, now: SystemTime) {
+        *self.0.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.0.lock().unwrap() += by;
+    }
+}
+
+impl TimeSource for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.0.lock().unwrap()
+    }
+}
+
+fn random_slot() -> &'static RwLock<Arc<dyn RandomSource>> {
+    static SLOT: OnceLock<RwLock<Arc<dyn RandomSource>>> = OnceLock::new();
+    SLOT.get_or_init(|| RwLock::new(Arc::new(ThreadRandom)))
+}
+
+fn time_slot() -> &'static RwLock<Arc<dyn TimeSource>> {
+    static SLOT: OnceLock<RwLock<Arc<dyn TimeSource>>> = OnceLock::new();
+    SLOT.get_or_init(|| RwLock::new(Arc::new(SystemClock)))
+}
+
+/// The process-wide random source
+pub fn random() -> Arc<dyn RandomSource> {
+    random_slot().read().unwrap().clone()
+}
+
+/// Replace the process-wide random source
+pub fn set_random_source(source: Arc<dyn RandomSource>) {
+    *random_slot().write().unwrap() = source;
+}
+
+/// Make the process-wide random source deterministic
+pub fn seed(seed: u64) {
+    set_random_source(Arc::new(SeededRandom::new(seed)));
+}
+
+/// The process-wide time source
+pub fn time() -> Arc<dyn TimeSource> {
+    time_slot().read().unwrap().clone()
+}
+
+/// Replace the process-wide time source
+pub fn set_time_source(source: Arc<dyn TimeSource>) {
+    *time_slot().write().unwrap() = source;
+}
+
+/// Current time in milliseconds since the Unix epoch, from the process-wide source
+pub fn now_millis() -> i64 {
+    time()
+        .now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let (a, b) = (SeededRandom::new(42), SeededRandom::new(42));
+        let first: Vec<u64> = (0..4).map(|_| a.next_u64()).collect();
+        let second: Vec<u64> = (0..4).map(|_| b.next_u64()).collect();
+        assert_eq!(first, second);
+
+        let other = SeededRandom::new(43);
+        assert_ne!(first[0], other.next_u64());
+    }
+
+    #[test]
+    fn test_values_in_range() {
+        let random = SeededRandom::new(7);
+        for _ in 0..1000 {
+            let value = random.next_f64();
+            assert!((0.0..1.0).contains(&value), "{}", value);
+        }
+
+        let mut bytes = [0u8; 13];
+        random.fill_bytes(&mut bytes);
+        assert!(bytes.iter().any(|byte| *byte != 0));
+    }
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(UNIX_EPOCH);
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(clock.now(), UNIX_EPOCH + Duration::from_millis(1500));
+
+        clock.set(UNIX_EPOCH);
+        assert_eq!(clock.now(), UNIX_EPOCH);
+    }
+}
@@ -0,0 +1,139 @@
+//! Validation and normalization of user-supplied text
+//!
+//! Reusable pieces for DTO string fields:
+//!
+//! - [`grapheme_length`] bounds length in grapheme clusters, so `é` written
+//!   as `e` + combining accent or a family emoji counts as one character
+//! - [`no_control_chars`] rejects control characters (line breaks, NUL, bidi
+//!   overrides) that break storage or display
+//! - [`deserialize_nfc`] normalizes to NFC while deserializing, so equal
+//!   names are stored with equal bytes
+//!
+//! `validator` custom functions take no arguments, so the checks for a field
+//! are combined in a small wrapper:
+//!
+//! ```ignore
+//! fn pet_name(name: &str) -> Result<(), ValidationError> {
+//!     no_control_chars(name)?;
+//!     grapheme_length(name, 1, 50)
+//! }
+//!
+//! #[derive(Deserialize, Validate)]
+//! struct NewPet {
+//!     #[serde(deserialize_with = "deserialize_nfc")]
+//!     #[validate(custom(function = "pet_name"))]
+//!     name: String,
+//! }
+//! ```
+
+use serde::{Deserialize, Deserializer};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use validator::ValidationError;
+
+/// Number of extended grapheme clusters in `value`
+pub fn grapheme_count(value: &str) -> usize {
+    value.graphemes(true).count()
+}
+
+/// Require between `min` and `max` grapheme clusters
+///
+/// Fails with the `length` code and `min`/`max` params, like
+/// `#[validate(length)]`.
+pub fn grapheme_length(value: &str, min: usize, max: usize) -> Result<(), ValidationError> {
+    let count = grapheme_count(value);
+    if (min..=max).contains(&count) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("length");
+    error.add_param("min".into(), &min);
+    error.add_param("max".into(), &max);
+    error.add_param("value".into(), &count);
+    Err(error)
+}
+
+/// Reject any Unicode control character, including tabs and line breaks
+pub fn no_control_chars(value: &str) -> Result<(), ValidationError> {
+    if value.chars().any(is_disallowed) {
+        let mut error = ValidationError::new("control_characters");
+        error.message = Some("must not contain control characters".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Control characters and the bidirectional formatting characters
+fn is_disallowed(c: char) -> bool {
+    c.is_control() || matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// `value` in Unicode Normalization Form C
+pub fn nfc(value: &str) -> String {
+    value.nfc().collect()
+}
+
+/// Deserialize a string and normalize it to NFC
+pub fn deserialize_nfc<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(|value| nfc(&value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    fn pet_name(name: &str) -> Result<(), ValidationError> {
+        no_control_chars(name)?;
+        grapheme_length(name, 1, 5)
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct NewPet {
+        #[serde(deserialize_with = "deserialize_nfc")]
+        #[validate(custom(function = "pet_name"))]
+        name: String,
+    }
+
+    fn pet(name: &str) -> NewPet {
+        serde_json::from_value(serde_json::json!({ "name": name })).unwrap()
+    }
+
+    #[test]
+    fn test_length_counts_graphemes() {
+        // Five graphemes, but ten chars and fifteen bytes
+        let decomposed = "e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}";
+        assert!(grapheme_length(decomposed, 1, 5).is_ok());
+        assert!(grapheme_length("👨‍👩‍👧", 1, 1).is_ok());
+
+        let error = pet("Fluffy").validate().unwrap_err();
+        let errors = &error.field_errors()["name"];
+        assert_eq!(errors[0].code, "length");
+        assert_eq!(errors[0].params["max"], 5);
+    }
+
+    #[test]
+    fn test_control_characters_are_rejected() {
+        for name in ["Rex\n", "Re\u{0}x", "\u{202E}xeR"] {
+            let error = pet(name).validate().unwrap_err();
+            assert_eq!(
+                error.field_errors()["name"][0].code,
+                "control_characters",
+                "{:?}",
+                name
+            );
+        }
+        assert!(pet("Rex").validate().is_ok());
+    }
+
+    #[test]
+    fn test_decomposed_input_is_normalized_to_nfc() {
+        let pet = pet("Zoe\u{301}");
+
+        assert_eq!(pet.name, "Zo\u{e9}");
+        assert_eq!(pet.name.chars().count(), 3);
+        assert!(pet.validate().is_ok());
+    }
+}
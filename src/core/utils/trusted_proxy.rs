@@ -0,0 +1,335 @@
+//! Client address resolution behind trusted proxies
+//!
+//! `X-Forwarded-For` and `X-Forwarded-Proto` can be set by anyone, so they
+//! are only honoured when the immediate peer is a known proxy.
+//! [`TrustedProxyLayer`] resolves each request's [`ClientIp`]:
+//!
+//! - from a trusted peer, the right-most `X-Forwarded-For` entry that is not
+//!   itself a trusted proxy
+//! - from any other peer, the socket address, with the forwarding headers
+//!   stripped so nothing downstream can be fooled by them
+//!
+//! ```ignore
+//! let app = Router::new()
+//!     .route("/pets", get(list_pets))
+//!     .layer(TrustedProxyLayer::from_cidrs(["10.0.0.0/8", "::1/128"])?);
+//! ```
+//!
+//! The rate limiter keys clients by [`ClientIp`] when it is present.
+
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::extract::ConnectInfo;
+use axum::http::{HeaderMap, HeaderName, Request};
+use tower::{Layer, Service};
+use tracing::debug;
+
+/// `X-Forwarded-For` header name
+pub const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// `X-Forwarded-Proto` header name
+pub const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+
+/// Resolved address of the client that originated a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClientIp(pub IpAddr);
+
+/// An IP network in CIDR notation, e.g. `10.0.0.0/8` or `fd00::/8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Whether `ip` lies within this network
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, canonical(ip)) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                prefix_matches(&network.octets(), &ip.octets(), self.prefix)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                prefix_matches(&network.octets(), &ip.octets(), self.prefix)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    /// Parse `addr/prefix`; a bare address is a single-host network
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s.trim(), None),
+        };
+        let network = canonical(
+            addr.parse::<IpAddr>()
+                .map_err(|_| format!("invalid address in CIDR '{}'", s))?,
+        );
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max)
+                .ok_or_else(|| format!("invalid prefix length in CIDR '{}'", s))?,
+            None => max,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// IPv4-mapped IPv6 addresses compare as IPv4
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(v6)),
+        v4 => v4,
+    }
+}
+
+fn prefix_matches(network: &[u8], ip: &[u8], prefix: u8) -> bool {
+    let full_bytes = usize::from(prefix / 8);
+    let remaining_bits = prefix % 8;
+    if network[..full_bytes] != ip[..full_bytes] {
+        return false;
+    }
+    if remaining_bits == 0 {
+        return true;
+    }
+    let mask = 0xffu8 << (8 - remaining_bits);
+    network[full_bytes] & mask == ip[full_bytes] & mask
+}
+
+/// Layer resolving [`ClientIp`] and dropping forwarding headers from untrusted peers
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxyLayer {
+    trusted: Arc<Vec<Cidr>>,
+}
+
+impl TrustedProxyLayer {
+    /// Trust the given proxy networks
+    pub fn new(trusted: Vec<Cidr>) -> Self {
+        Self {
+            trusted: Arc::new(trusted),
+        }
+    }
+
+    /// Trust the given proxy networks, parsed from CIDR notation
+    pub fn from_cidrs<I, S>(cidrs: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let trusted = cidrs
+            .into_iter()
+            .map(|cidr| cidr.as_ref().parse())
+            .collect::<Result<Vec<Cidr>, _>>()?;
+        Ok(Self::new(trusted))
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    /// Client address for a request from `peer` carrying `headers`
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        // Walk from the nearest hop outwards; the first untrusted hop is the client
+        let mut client = peer;
+        for hop in forwarded_for(headers).into_iter().rev() {
+            match hop {
+                Some(ip) if self.is_trusted(ip) => client = ip,
+                Some(ip) => return ip,
+                // An unparseable entry can't be attributed; stop at the last trusted hop
+                None => break,
+            }
+        }
+        client
+    }
+}
+
+/// Every `X-Forwarded-For` entry in order, `None` for unparseable ones
+fn forwarded_for(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .flat_map(|value| value.to_str().unwrap_or("").split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .parse::<IpAddr>()
+                .or_else(|_| entry.parse::<SocketAddr>().map(|addr| addr.ip()))
+                .ok()
+        })
+        .collect()
+}
+
+impl<S> Layer<S> for TrustedProxyLayer {
+    type Service = TrustedProxyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TrustedProxyService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service created by [`TrustedProxyLayer`]
+#[derive(Debug, Clone)]
+pub struct TrustedProxyService<S> {
+    inner: S,
+    layer: TrustedProxyLayer,
+}
+
+impl<S, B> Service<Request<B>> for TrustedProxyService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|connect_info| connect_info.0.ip());
+
+        match peer {
+            Some(peer) if self.layer.is_trusted(peer) => {
+                let client = self.layer.resolve(peer, request.headers());
+                request.extensions_mut().insert(ClientIp(client));
+            }
+            Some(peer) => {
+                if request.headers().contains_key(X_FORWARDED_FOR) {
+                    debug!("Ignoring X-Forwarded-For from untrusted peer {}", peer);
+                }
+                strip_forwarding_headers(request.headers_mut());
+                request.extensions_mut().insert(ClientIp(peer));
+            }
+            // Without a socket peer nothing can be trusted
+            None => strip_forwarding_headers(request.headers_mut()),
+        }
+
+        self.inner.call(request)
+    }
+}
+
+fn strip_forwarding_headers(headers: &mut HeaderMap) {
+    headers.remove(X_FORWARDED_FOR);
+    headers.remove(X_FORWARDED_PROTO);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, extract::Extension, http::HeaderValue, routing::get};
+    use tower::ServiceExt;
+
+    fn layer() -> TrustedProxyLayer {
+        TrustedProxyLayer::from_cidrs(["10.0.0.0/8", "fd00::/8"]).unwrap()
+    }
+
+    async fn client_ip(peer: &str, forwarded_for: Option<&str>) -> (String, Option<String>) {
+        let app = Router::new()
+            .route(
+                "/ip",
+                get(
+                    |Extension(ClientIp(ip)): Extension<ClientIp>, headers: HeaderMap| async move {
+                        let forwarded = headers
+                            .get(X_FORWARDED_FOR)
+                            .map(|value| value.to_str().unwrap().to_string())
+                            .unwrap_or_default();
+                        format!("{}|{}", ip, forwarded)
+                    },
+                ),
+            )
+            .layer(layer());
+
+        let mut request = Request::builder().uri("/ip");
+        if let Some(value) = forwarded_for {
+            request = request.header(X_FORWARDED_FOR, HeaderValue::from_str(value).unwrap());
+        }
+        let mut request = request.body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let (ip, forwarded) = body.split_once('|').unwrap();
+        let forwarded = (!forwarded.is_empty()).then(|| forwarded.to_string());
+        (ip.to_string(), forwarded)
+    }
+
+    #[tokio::test]
+    async fn test_trusted_proxy_passes_forwarded_ip() {
+        let (ip, forwarded) = client_ip("10.1.2.3:5000", Some("203.0.113.7")).await;
+
+        assert_eq!(ip, "203.0.113.7");
+        assert_eq!(forwarded.as_deref(), Some("203.0.113.7"));
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_peer_forwarded_header_is_ignored() {
+        let (ip, forwarded) = client_ip("198.51.100.20:5000", Some("203.0.113.7")).await;
+
+        assert_eq!(ip, "198.51.100.20");
+        assert_eq!(forwarded, None);
+    }
+
+    #[tokio::test]
+    async fn test_spoofed_entries_before_client_are_skipped() {
+        // The client prepended a fake address; only hops added by trusted proxies count
+        let (ip, _) = client_ip("10.0.0.1:5000", Some("1.2.3.4, 203.0.113.7, 10.0.0.2")).await;
+
+        assert_eq!(ip, "203.0.113.7");
+    }
+
+    #[test]
+    fn test_cidr_parsing_and_matching() {
+        let v4: Cidr = "192.168.0.0/16".parse().unwrap();
+        assert!(v4.contains("192.168.44.1".parse().unwrap()));
+        assert!(v4.contains("::ffff:192.168.1.1".parse().unwrap()));
+        assert!(!v4.contains("192.169.0.1".parse().unwrap()));
+
+        let odd: Cidr = "10.0.0.0/9".parse().unwrap();
+        assert!(odd.contains("10.127.0.1".parse().unwrap()));
+        assert!(!odd.contains("10.128.0.1".parse().unwrap()));
+
+        let host: Cidr = "::1".parse().unwrap();
+        assert_eq!(host.to_string(), "::1/128");
+        assert!(host.contains("::1".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("not-an-ip/8".parse::<Cidr>().is_err());
+    }
+}
@@ -82,6 +82,9 @@ pub mod core {
 
     // Data models and schemas
     pub mod models {
+        // Per-item batch results
+        pub mod batch;
+
         // Error models
         pub mod core_error;
 
@@ -94,30 +97,53 @@ pub mod core {
         // Entity definitions
         pub mod entity;
 
+        // Entity id generation strategies
+        pub mod id_generator;
+
+        pub use batch::{BatchItem, BatchResult};
         pub use core_error::*;
         pub use core_extensions::*;
         pub use core_response::*;
         pub use entity::*;
+        pub use id_generator::{IdGenerator, IdStrategy, Ulid, UlidGenerator, UuidV4Generator};
     }
 
+    // Observability providers (metrics, tracing, profiling)
+    #[cfg(feature = "metrics")]
+    pub mod observability;
+
     // Reliability features
     pub mod reliability;
 
     // Routing functionality
     pub mod router {
+        // Application-defined actuator endpoints
+        pub mod actuator_endpoints;
+
+        // Application handle for ordered shutdown
+        pub mod app_handle;
+
         // Core router implementation
         pub mod core_router;
 
         // Application router
         pub mod core_app_router;
 
+        pub use actuator_endpoints::{ActuatorEndpoint, ExposureCheck};
+        pub use app_handle::{AppHandle, ShutdownError};
         pub use core_app_router::*;
         pub use core_router::*;
     }
 
+    // HTTP server binding and connection handling
+    pub mod server;
+
     // Service implementations
     pub mod services;
 
+    // Startup timing
+    pub mod startup;
+
     // Utility functions
     pub mod utils;
 
@@ -133,7 +159,8 @@ pub mod core {
     pub use self::reliability::apply_reliability;
     pub use self::router::CoreRouter;
     pub use self::utils::api_resource::{
-        ApiHandlerOptions, ApiResource, ApiResourceRegistry, create_api_handler,
+        ApiHandlerOptions, ApiResource, ApiResourceRegistry, ApiVersion, CacheScope, CacheSubject,
+        api_resource_router, create_api_handler,
     };
     #[cfg(feature = "auth")]
     pub use crate::core::auth::TokenClient;
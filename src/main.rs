@@ -17,33 +17,45 @@ use navius::config;
 use navius::error::error_types::AppError;
 
 use std::{fs, path::Path, process};
-use tracing::{Level, error, info, warn};
-use tracing_subscriber::FmtSubscriber;
+use tracing::{error, info, warn};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
 
 use std::env;
-use std::net::SocketAddr;
-use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use navius::core::cache::cache_manager::init_cache_registry;
 use navius::core::config::app_config::AppConfig;
 use navius::core::config::load_config;
 use navius::core::router;
 use navius::core::router::core_app_router::{RouterBuilder, create_application};
+use navius::core::server;
+use navius::core::services::migrations;
+#[cfg(feature = "postgres")]
+use navius::core::services::{DatabaseConfig, postgres_pool};
+use navius::core::startup::StartupTimer;
+use navius::core::utils::log_levels::LogLevels;
+
+/// Longest a graceful shutdown may take before it is abandoned
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables from .env file
     dotenvy::dotenv().ok();
 
-    // Initialize tracing
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
+    // Initialize tracing, with levels adjustable through /actuator/loggers
+    let (log_filter, log_levels) = LogLevels::for_registry(LevelFilter::INFO);
+    let subscriber = tracing_subscriber::registry()
+        .with(log_filter)
+        .with(tracing_subscriber::fmt::layer());
 
     if let Err(err) = tracing::subscriber::set_global_default(subscriber) {
         eprintln!("Failed to set tracing subscriber: {}", err);
         process::exit(1);
     }
+    log_levels.install();
 
     // Run the application
     if let Err(err) = run_app().await {
@@ -55,20 +67,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 async fn run_app() -> Result<(), AppError> {
+    let mut startup = StartupTimer::new();
+
     // Load configuration
-    let config = config::app_config::load_config()?;
+    let config = startup.time_sync("config", config::app_config::load_config)?;
+
+    // Capture environment-derived constants once for the rest of the run
+    navius::core::config::constants::runtime::init(&config);
+    navius::core::config::constants::scoped::init(&config);
+
+    // Apply database migrations, or only migrations with --migrate-only
+    let migrate_only = migrations::migrate_only_requested();
+    startup
+        .time(
+            "migrations",
+            migrations::run_from_config(&config, migrate_only),
+        )
+        .await?;
+    if migrate_only {
+        info!("Migrations complete, exiting (--migrate-only)");
+        return Ok(());
+    }
 
     // Get server address
-    let addr = match SocketAddr::from_str(&format!("{}:{}", config.server.host, config.server.port))
-    {
-        Ok(addr) => addr,
-        Err(err) => {
-            return Err(AppError::internal_server_error(format!(
-                "Invalid server address: {}",
-                err
-            )));
-        }
-    };
+    let addr = config.server.socket_addr()?;
 
     // Ensure the OpenAPI directory exists
     let spec_directory = "config/swagger";
@@ -78,22 +100,100 @@ async fn run_app() -> Result<(), AppError> {
             AppError::internal_server_error(format!("Failed to create OpenAPI directory: {}", e))
         })?;
     }
+    // Report an unusable spec now; the docs endpoint serves an empty one in its place
+    if config.docs_enabled() {
+        navius::core::handlers::core_docs::load_openapi_spec(&config);
+    }
 
-    // Initialize metrics
-    let metrics_handle = navius::core::metrics::init_metrics();
+    // Initialize metrics; without a recorder metrics are dropped but startup continues
+    let metrics_handle = startup.time_sync("metrics", || {
+        Ok::<_, AppError>(
+            navius::core::metrics::init_metrics()
+                .map_err(|e| warn!("Metrics disabled: {}", e))
+                .ok(),
+        )
+    })?;
+
+    // Resource caches
+    let cache = Arc::new(init_cache_registry(
+        config.cache.enabled,
+        config.cache.max_capacity,
+        config.cache.ttl_seconds,
+    ));
+
+    // Connection pool, when a database is configured through DATABASE_URL
+    #[cfg(feature = "postgres")]
+    let db_pool = match env::var("DATABASE_URL") {
+        Ok(url) => {
+            let db_config = DatabaseConfig::from_url(&url)
+                .map_err(|e| AppError::ConfigurationError(format!("DATABASE_URL: {}", e)))?;
+            let pool = startup
+                .time("database", postgres_pool::connect(&db_config))
+                .await
+                .map_err(|e| AppError::internal_server_error(e.to_string()))?;
+            Some(pool)
+        }
+        Err(_) => None,
+    };
 
     // Create a Spring Boot-like application
     let app = create_application()
         .with_config(config.clone())
-        .with_metrics(Some(metrics_handle))
+        .with_metrics(metrics_handle.clone())
+        .with_cache(Some(cache))
         .with_cors(true)
         .with_metrics_enabled(true);
 
+    // Share the pool with handlers through the service registry
+    #[cfg(feature = "postgres")]
+    let app = match &db_pool {
+        Some(pool) => app.register_service(pool.clone()),
+        None => app,
+    };
+
     // Register services
     let app = navius::app::api::register_services(app);
 
-    // Build the router
-    let app = app.build();
+    // Application routes, behind the same layers and authorization policy as the core routes
+    let app = app.with_routes(navius::app::api::configure(axum::Router::new()));
+
+    // Track requests, tracing the sampled share of them
+    #[cfg(feature = "metrics")]
+    let app = app.with_observability(&navius::core::observability::ObservabilityConfig::new(
+        "prometheus",
+        env!("CARGO_PKG_NAME"),
+    ));
+
+    // Build the router and the handle that shuts it down
+    let (app, handle) = startup.time_sync("router", || Ok::<_, AppError>(app.build()))?;
+
+    // Flush buffered telemetry once the servers have drained
+    #[cfg(feature = "metrics")]
+    {
+        match navius::core::observability::init_observability(env!("CARGO_PKG_NAME")).await {
+            Ok(observability) => handle.on_flush("observability", move || async move {
+                let timeout = navius::core::observability::DEFAULT_SHUTDOWN_TIMEOUT;
+                if let Err(e) = observability.shutdown(timeout).await {
+                    warn!("Failed to flush observability exporters: {}", e);
+                }
+            }),
+            Err(e) => warn!("Observability disabled: {}", e),
+        }
+        if let Some(metrics_handle) = metrics_handle {
+            handle.on_flush(
+                "metrics",
+                move || async move { metrics_handle.run_upkeep() },
+            );
+        }
+    }
+
+    // Close connections last, after everything that may still use them has stopped
+    #[cfg(feature = "postgres")]
+    {
+        if let Some(pool) = db_pool {
+            handle.on_close("database", move || async move { pool.close().await });
+        }
+    }
 
     // Start the server
     info!(
@@ -101,13 +201,17 @@ async fn run_app() -> Result<(), AppError> {
         config.server.protocol, config.server.host, config.server.port
     );
 
-    // Bind the TCP listener
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-
-    // Run the server with our app
-    axum::serve(listener, app)
-        .await
-        .map_err(|e| AppError::internal_server_error(format!("Server error: {}", e)))?;
+    // Bind the TCP listener with the configured socket options; further named
+    // servers (e.g. an admin API on localhost) can be started on the same handle
+    let public =
+        server::HttpServer::new("public", addr, app).with_tuning(config.server.tuning.clone());
+    startup.time_sync("listener", || handle.start(public))?;
+    startup.complete();
+
+    // Drain the server, then stop everything else registered on the handle
+    server::shutdown_signal().await;
+    info!("Shutdown signal received, draining in-flight requests");
+    handle.shutdown(SHUTDOWN_TIMEOUT).await?;
 
     Ok(())
 }